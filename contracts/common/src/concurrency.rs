@@ -15,6 +15,7 @@ const CLOCK_KEY: Symbol = symbol_short!("OCC_CLK");
 const CONFLICT_Q: Symbol = symbol_short!("OCC_CFQ");
 const CONFLICT_CTR: Symbol = symbol_short!("OCC_CCTR");
 const STRATEGY_KEY: Symbol = symbol_short!("OCC_STRT");
+const CLOCK_HIST: Symbol = symbol_short!("OCC_CHST");
 
 const TTL_THRESHOLD: u32 = 5_184_000;
 const TTL_EXTEND_TO: u32 = 10_368_000;
@@ -23,6 +24,10 @@ const TTL_EXTEND_TO: u32 = 10_368_000;
 /// evicted. Prevents unbounded storage growth.
 pub const MAX_CONFLICT_QUEUE_SIZE: u32 = 256;
 
+/// Number of recent versions whose clocks are kept per record, so a stale
+/// writer's clock can be rebuilt from the version it read.
+pub const CLOCK_HISTORY_LEN: u64 = 16;
+
 // ── Types ───────────────────────────────────────────────────────────────────
 
 /// Resolution strategy for concurrent conflicts.
@@ -130,6 +135,29 @@ pub fn get_record_clock(env: &Env, record_id: u64) -> VectorClock {
         .unwrap_or_else(|| VectorClock::new(env))
 }
 
+fn clock_history_key(record_id: u64, version: u64) -> (Symbol, u64, u64) {
+    (CLOCK_HIST, record_id, version % CLOCK_HISTORY_LEN)
+}
+
+/// Remembers the clock of `stamp.version` in the record's clock history.
+fn record_clock_history(env: &Env, record_id: u64, stamp: &VersionStamp) {
+    let key = clock_history_key(record_id, stamp.version);
+    env.storage().persistent().set(&key, stamp);
+    env.storage()
+        .persistent()
+        .extend_ttl(&key, TTL_THRESHOLD, TTL_EXTEND_TO);
+}
+
+/// The clock the record had at `version`, if that version is still within
+/// the last [`CLOCK_HISTORY_LEN`] versions.
+pub fn get_clock_at_version(env: &Env, record_id: u64, version: u64) -> Option<VectorClock> {
+    env.storage()
+        .persistent()
+        .get::<_, VersionStamp>(&clock_history_key(record_id, version))
+        .filter(|stamp| stamp.version == version)
+        .map(|stamp| stamp.clock)
+}
+
 /// Sets the resolution strategy for a specific record.
 pub fn set_resolution_strategy(env: &Env, record_id: u64, strategy: &ResolutionStrategy) {
     env.storage()
@@ -161,7 +189,9 @@ pub fn init_record_version(env: &Env, record_id: u64, node_id: u32) -> VersionSt
         .persistent()
         .extend_ttl(&ck, TTL_THRESHOLD, TTL_EXTEND_TO);
 
-    VersionStamp { version: 1, clock }
+    let stamp = VersionStamp { version: 1, clock };
+    record_clock_history(env, record_id, &stamp);
+    stamp
 }
 
 // ── Compare-and-swap ────────────────────────────────────────────────────────
@@ -193,11 +223,21 @@ pub fn compare_and_swap(
         return apply_update(env, record_id, node_id, &current_clock);
     }
 
-    // Versions diverge — build the caller's tentative clock to check ordering.
-    let mut caller_clock = current_clock.clone();
+    // Versions diverge — rebuild the caller's clock from the version it read
+    // to check ordering. The caller has seen its own writes, so only updates
+    // from other nodes since `expected_version` make the write concurrent. A
+    // version too old to be in the clock history is treated as concurrent.
+    let seen = get_clock_at_version(env, record_id, expected_version);
+    let mut caller_clock = seen.clone().unwrap_or_else(|| current_clock.clone());
+    let own = current_clock.get(node_id);
+    if own > caller_clock.get(node_id) {
+        caller_clock.entries.set(node_id, own);
+    }
     caller_clock.increment(env, node_id);
-
-    let ordering = caller_clock.compare(&current_clock);
+    let ordering = match seen {
+        Some(_) => caller_clock.compare(&current_clock),
+        None => ClockOrdering::Concurrent,
+    };
 
     let strategy = get_resolution_strategy(env, record_id);
 
@@ -287,10 +327,12 @@ fn bump_version(
         .persistent()
         .extend_ttl(&ck, TTL_THRESHOLD, TTL_EXTEND_TO);
 
-    VersionStamp {
+    let stamp = VersionStamp {
         version: new_version,
         clock: new_clock,
-    }
+    };
+    record_clock_history(env, record_id, &stamp);
+    stamp
 }
 
 // ── Field-level conflict detection ──────────────────────────────────────────
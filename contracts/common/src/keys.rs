@@ -1,6 +1,8 @@
 #![allow(dead_code, clippy::incompatible_msrv)]
 extern crate alloc;
 
+use aes_gcm::aead::{Aead, KeyInit, Payload};
+use aes_gcm::{Aes256Gcm, Nonce};
 use alloc::collections::BTreeMap;
 use alloc::string::String;
 use alloc::vec::Vec;
@@ -37,10 +39,141 @@ impl AuditLog {
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyError {
+    /// Key material is not [`KEY_LEN`] bytes.
+    InvalidKeyLength,
+    /// Key id is empty or longer than [`MAX_KEY_ID_LEN`] bytes.
+    InvalidKeyId,
+    UnknownKey,
+    /// The ciphertext was sealed under a key version that is no longer held.
+    UnknownKeyVersion,
+    /// The ciphertext was sealed under a different key id than the one given.
+    KeyMismatch,
+    /// Not hex, truncated, or otherwise not a ciphertext.
+    MalformedCiphertext,
+    UnsupportedFormat,
+    /// The authentication tag did not verify: wrong key or tampered data.
+    AuthenticationFailed,
+    InvalidUtf8,
+    /// No OS randomness on this target; use [`KeyManager::encrypt_with_nonce`].
+    RandomnessUnavailable,
+}
+
+// Ciphertext layout (hex-encoded by `encrypt`):
+//
+//   format (1) | key version (4, BE) | key id length (1) | key id | nonce (12) | sealed + tag
+//
+// Everything before the nonce is the AES-GCM associated data, so a ciphertext
+// cannot be replayed under another key id or version.
+
+/// Header format written by this module.
+pub const FORMAT_V1: u8 = 1;
+/// AES-256 key length in bytes.
+pub const KEY_LEN: usize = 32;
+pub const NONCE_LEN: usize = 12;
+const TAG_LEN: usize = 16;
+/// Key ids must fit the header's one-byte length field.
+pub const MAX_KEY_ID_LEN: usize = 255;
+
+struct Sealed<'a> {
+    key_version: u32,
+    key_id: &'a [u8],
+    associated_data: &'a [u8],
+    nonce: [u8; NONCE_LEN],
+    payload: &'a [u8],
+}
+
+fn seal(
+    key: &[u8],
+    key_id: &str,
+    key_version: u32,
+    nonce: [u8; NONCE_LEN],
+    plaintext: &[u8],
+) -> Result<Vec<u8>, KeyError> {
+    let cipher = Aes256Gcm::new_from_slice(key).map_err(|_| KeyError::InvalidKeyLength)?;
+    let id_len = u8::try_from(key_id.len()).map_err(|_| KeyError::InvalidKeyId)?;
+
+    let mut out = Vec::with_capacity(6 + key_id.len() + NONCE_LEN + plaintext.len() + TAG_LEN);
+    out.push(FORMAT_V1);
+    out.extend_from_slice(&key_version.to_be_bytes());
+    out.push(id_len);
+    out.extend_from_slice(key_id.as_bytes());
+    let payload = cipher
+        .encrypt(
+            &Nonce::from(nonce),
+            Payload {
+                msg: plaintext,
+                aad: &out,
+            },
+        )
+        .map_err(|_| KeyError::AuthenticationFailed)?;
+    out.extend_from_slice(&nonce);
+    out.extend_from_slice(&payload);
+    Ok(out)
+}
+
+fn parse(bytes: &[u8]) -> Result<Sealed<'_>, KeyError> {
+    let (&format, rest) = bytes.split_first().ok_or(KeyError::MalformedCiphertext)?;
+    if format != FORMAT_V1 {
+        return Err(KeyError::UnsupportedFormat);
+    }
+    let header = rest.get(..5).ok_or(KeyError::MalformedCiphertext)?;
+    let key_version = u32::from_be_bytes([header[0], header[1], header[2], header[3]]);
+    let id_end = 6 + usize::from(header[4]);
+    let nonce_end = id_end + NONCE_LEN;
+    if bytes.len() < nonce_end + TAG_LEN {
+        return Err(KeyError::MalformedCiphertext);
+    }
+    Ok(Sealed {
+        key_version,
+        key_id: &bytes[6..id_end],
+        associated_data: &bytes[..id_end],
+        nonce: bytes[id_end..nonce_end]
+            .try_into()
+            .map_err(|_| KeyError::MalformedCiphertext)?,
+        payload: &bytes[nonce_end..],
+    })
+}
+
+fn open(key: &[u8], sealed: &Sealed) -> Result<Vec<u8>, KeyError> {
+    let cipher = Aes256Gcm::new_from_slice(key).map_err(|_| KeyError::InvalidKeyLength)?;
+    cipher
+        .decrypt(
+            &Nonce::from(sealed.nonce),
+            Payload {
+                msg: sealed.payload,
+                aad: sealed.associated_data,
+            },
+        )
+        .map_err(|_| KeyError::AuthenticationFailed)
+}
+
+#[cfg(all(feature = "std", not(target_family = "wasm")))]
+fn fresh_nonce() -> Result<[u8; NONCE_LEN], KeyError> {
+    let mut nonce = [0u8; NONCE_LEN];
+    getrandom::getrandom(&mut nonce).map_err(|_| KeyError::RandomnessUnavailable)?;
+    Ok(nonce)
+}
+
+#[cfg(not(all(feature = "std", not(target_family = "wasm"))))]
+fn fresh_nonce() -> Result<[u8; NONCE_LEN], KeyError> {
+    Err(KeyError::RandomnessUnavailable)
+}
+
+fn wipe(bytes: &mut [u8]) {
+    for b in bytes.iter_mut() {
+        *b = 0;
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct DataKey {
     pub id: String,
-    pub key: Vec<u8>,
+    /// The key material sealed under the master key.
+    pub wrapped_key: Vec<u8>,
+    /// Bumped each time a key is created under the same id.
+    pub version: u32,
     pub created: u64,
     pub expires: Option<u64>,
 }
@@ -48,7 +181,10 @@ pub struct DataKey {
 #[derive(Default)]
 pub struct KeyManager {
     pub master: Vec<u8>,
+    pub master_version: u32,
     pub data_keys: BTreeMap<String, DataKey>,
+    /// The master retired by the last rotation, kept so ciphertexts sealed
+    /// under it still decrypt. Wiped by the next rotation.
     pub old_master: Option<Vec<u8>>,
 }
 
@@ -56,81 +192,177 @@ impl KeyManager {
     pub fn new(master: Vec<u8>) -> Self {
         Self {
             master,
+            master_version: 1,
             data_keys: BTreeMap::new(),
             old_master: None,
         }
     }
 
-    pub fn create_data_key(&mut self, id: &str, key: Vec<u8>, ttl: Option<u64>, now: u64) {
+    /// Wraps `key` under the master key and stores it as `id`, replacing any
+    /// earlier key with that id. Ciphertexts sealed under the replaced key
+    /// no longer decrypt.
+    pub fn create_data_key(
+        &mut self,
+        id: &str,
+        mut key: Vec<u8>,
+        ttl: Option<u64>,
+        now: u64,
+    ) -> Result<(), KeyError> {
+        if id.is_empty() || id.len() > MAX_KEY_ID_LEN {
+            return Err(KeyError::InvalidKeyId);
+        }
+        if key.len() != KEY_LEN {
+            return Err(KeyError::InvalidKeyLength);
+        }
+        let wrapped = fresh_nonce()
+            .and_then(|nonce| seal(&self.master, id, self.master_version, nonce, &key));
+        wipe(&mut key);
+        let version = self
+            .data_keys
+            .get(id)
+            .map_or(1, |dk| dk.version.saturating_add(1));
         self.data_keys.insert(
             String::from(id),
             DataKey {
                 id: String::from(id),
-                key,
+                wrapped_key: wrapped?,
+                version,
                 created: now,
                 expires: ttl.and_then(|t| now.checked_add(t)),
             },
         );
+        Ok(())
     }
 
-    pub fn rotate_master(&mut self, new_master: Vec<u8>) {
-        self.master = new_master;
-    }
-
-    pub fn rotate_master_secure(&mut self, new_master: Vec<u8>, audit: &mut AuditLog, actor: &str, now: u64) {
-        self.old_master = Some(self.master.clone());
+    /// Replaces the master key and re-wraps every data key under it, so data
+    /// keys (and everything sealed under them) survive the rotation. The
+    /// previous master moves to `old_master`; the one before it is wiped.
+    /// On error nothing changes.
+    pub fn rotate_master(&mut self, new_master: Vec<u8>) -> Result<(), KeyError> {
+        if new_master.len() != KEY_LEN {
+            return Err(KeyError::InvalidKeyLength);
+        }
+        let new_version = self.master_version.saturating_add(1);
 
-        for dk in self.data_keys.values_mut() {
-            for (i, b) in dk.key.iter_mut().enumerate() {
-                *b ^= self.master.get(i % self.master.len()).unwrap_or(&0);
-            }
-            for (i, b) in dk.key.iter_mut().enumerate() {
-                *b ^= new_master.get(i % new_master.len()).unwrap_or(&0);
+        let mut rewrapped = Vec::with_capacity(self.data_keys.len());
+        for (id, dk) in &self.data_keys {
+            let mut key = self.unwrap_data_key(dk)?;
+            let wrapped =
+                fresh_nonce().and_then(|nonce| seal(&new_master, id, new_version, nonce, &key));
+            wipe(&mut key);
+            rewrapped.push((id.clone(), wrapped?));
+        }
+        for (id, wrapped) in rewrapped {
+            if let Some(dk) = self.data_keys.get_mut(&id) {
+                dk.wrapped_key = wrapped;
             }
         }
 
-        for b in &mut self.master {
-            *b = 0;
+        if let Some(mut retired) = self.old_master.take() {
+            wipe(&mut retired);
         }
-        self.master = new_master;
+        self.old_master = Some(core::mem::replace(&mut self.master, new_master));
+        self.master_version = new_version;
+        Ok(())
+    }
+
+    pub fn rotate_master_secure(
+        &mut self,
+        new_master: Vec<u8>,
+        audit: &mut AuditLog,
+        actor: &str,
+        now: u64,
+    ) -> Result<(), KeyError> {
+        self.rotate_master(new_master)?;
         audit.record(actor, "rotate_master_secure", "master_key", now);
+        Ok(())
     }
 
     pub fn get_key(&self, id: &str) -> Option<&DataKey> {
         self.data_keys.get(id)
     }
 
-    pub fn encrypt(&self, key_id: Option<&str>, plaintext: &str) -> String {
-        let key = key_id
-            .and_then(|id| self.get_key(id).map(|dk| dk.key.as_slice()))
-            .unwrap_or(self.master.as_slice());
-        xor_and_hex_encode(key, plaintext.as_bytes())
+    /// Seals `plaintext` under the data key `key_id`, or the master key when
+    /// `None`, with a random nonce. Needs the `std` feature on a target with
+    /// OS randomness; otherwise use [`encrypt_with_nonce`](Self::encrypt_with_nonce).
+    pub fn encrypt(&self, key_id: Option<&str>, plaintext: &str) -> Result<String, KeyError> {
+        self.encrypt_with_nonce(key_id, plaintext, fresh_nonce()?)
     }
 
-    pub fn decrypt(&self, key_id: Option<&str>, ciphertext_hex: &str) -> Option<String> {
-        let key = key_id
-            .and_then(|id| self.get_key(id).map(|dk| dk.key.as_slice()))
-            .unwrap_or(self.master.as_slice());
-        hex_decode_and_xor(key, ciphertext_hex)
+    /// Like [`encrypt`](Self::encrypt) with a caller-supplied nonce, e.g.
+    /// from a contract's PRNG. A nonce must never be reused with the same key.
+    pub fn encrypt_with_nonce(
+        &self,
+        key_id: Option<&str>,
+        plaintext: &str,
+        nonce: [u8; NONCE_LEN],
+    ) -> Result<String, KeyError> {
+        let sealed = match key_id {
+            None => seal(
+                &self.master,
+                "",
+                self.master_version,
+                nonce,
+                plaintext.as_bytes(),
+            )?,
+            Some(id) => {
+                let dk = self.get_key(id).ok_or(KeyError::UnknownKey)?;
+                let mut key = self.unwrap_data_key(dk)?;
+                let sealed = seal(&key, id, dk.version, nonce, plaintext.as_bytes());
+                wipe(&mut key);
+                sealed?
+            }
+        };
+        Ok(bytes_to_hex(&sealed))
     }
-}
 
-fn xor_and_hex_encode(key: &[u8], plaintext: &[u8]) -> String {
-    let mut out = Vec::with_capacity(plaintext.len());
-    if key.is_empty() {
-        out.extend_from_slice(plaintext);
-    } else {
-        for (i, b) in plaintext.iter().enumerate() {
-            out.push(b ^ key[i % key.len()]);
+    pub fn decrypt(&self, key_id: Option<&str>, ciphertext_hex: &str) -> Result<String, KeyError> {
+        let bytes = hex_to_bytes(ciphertext_hex).ok_or(KeyError::MalformedCiphertext)?;
+        let sealed = parse(&bytes)?;
+        if sealed.key_id != key_id.unwrap_or("").as_bytes() {
+            return Err(KeyError::KeyMismatch);
         }
+        let plaintext = match key_id {
+            None => open(self.master_for(sealed.key_version)?, &sealed)?,
+            Some(id) => {
+                let dk = self.get_key(id).ok_or(KeyError::UnknownKey)?;
+                if sealed.key_version != dk.version {
+                    return Err(KeyError::UnknownKeyVersion);
+                }
+                let mut key = self.unwrap_data_key(dk)?;
+                let plaintext = open(&key, &sealed);
+                wipe(&mut key);
+                plaintext?
+            }
+        };
+        String::from_utf8(plaintext).map_err(|_| KeyError::InvalidUtf8)
     }
 
-    let mut s = String::with_capacity(out.len() * 2);
-    for byte in out {
-        s.push(nibble_to_hex((byte >> 4) & 0xF));
-        s.push(nibble_to_hex(byte & 0xF));
+    /// Decrypts a ciphertext and seals it again under the current key
+    /// version, for migrating data off a master that is about to be wiped.
+    pub fn reencrypt(
+        &self,
+        key_id: Option<&str>,
+        ciphertext_hex: &str,
+    ) -> Result<String, KeyError> {
+        let plaintext = self.decrypt(key_id, ciphertext_hex)?;
+        self.encrypt(key_id, &plaintext)
+    }
+
+    fn master_for(&self, version: u32) -> Result<&[u8], KeyError> {
+        if version == self.master_version {
+            return Ok(&self.master);
+        }
+        match &self.old_master {
+            Some(old) if version.checked_add(1) == Some(self.master_version) => Ok(old),
+            _ => Err(KeyError::UnknownKeyVersion),
+        }
+    }
+
+    fn unwrap_data_key(&self, dk: &DataKey) -> Result<Vec<u8>, KeyError> {
+        let sealed = parse(&dk.wrapped_key)?;
+        open(self.master_for(sealed.key_version)?, &sealed)
     }
-    s
 }
 
 fn nibble_to_hex(n: u8) -> char {
@@ -150,33 +382,6 @@ fn hex_char_val(c: char) -> Option<u8> {
     }
 }
 
-fn hex_decode_and_xor(key: &[u8], hexstr: &str) -> Option<String> {
-    let chars: Vec<char> = hexstr.chars().collect();
-    if !chars.len().is_multiple_of(2) {
-        return None;
-    }
-
-    let mut bytes = Vec::with_capacity(chars.len() / 2);
-    let mut i = 0usize;
-    while i < chars.len() {
-        let hi = hex_char_val(chars[i])?;
-        let lo = hex_char_val(chars[i + 1])?;
-        bytes.push((hi << 4) | lo);
-        i += 2;
-    }
-
-    let mut out = Vec::with_capacity(bytes.len());
-    if key.is_empty() {
-        out.extend_from_slice(&bytes);
-    } else {
-        for (i, b) in bytes.iter().enumerate() {
-            out.push(b ^ key[i % key.len()]);
-        }
-    }
-
-    String::from_utf8(out).ok()
-}
-
 pub fn hex_to_bytes(hexstr: &str) -> Option<Vec<u8>> {
     let chars: Vec<char> = hexstr.chars().collect();
     if chars.len() % 2 != 0 {
@@ -194,6 +399,20 @@ pub fn hex_to_bytes(hexstr: &str) -> Option<Vec<u8>> {
     Some(bytes)
 }
 
+/// Reads a ciphertext written by the XOR scheme this module used before
+/// [`FORMAT_V1`]: hex of the plaintext XORed with `key` (repeated), or the
+/// bare hex when `key` is empty. For migrating stored data only; nothing is
+/// sealed this way any more.
+pub fn decrypt_legacy_xor(key: &[u8], ciphertext_hex: &str) -> Option<String> {
+    let mut bytes = hex_to_bytes(ciphertext_hex)?;
+    if !key.is_empty() {
+        for (i, b) in bytes.iter_mut().enumerate() {
+            *b ^= key[i % key.len()];
+        }
+    }
+    String::from_utf8(bytes).ok()
+}
+
 pub fn bytes_to_hex(bytes: &[u8]) -> String {
     let mut s = String::with_capacity(bytes.len() * 2);
     for &b in bytes {
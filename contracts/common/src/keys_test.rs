@@ -1,44 +1,173 @@
-use super::keys::{DataKey, KeyManager};
-use std::collections::HashMap;
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    #[test]
-    fn test_rotate_master_secure() {
-        // Setup initial master and data keys
-        let old_master = vec![1, 2, 3, 4];
-        let mut km = KeyManager::new(old_master.clone());
-        km.create_data_key("key1", vec![10, 20, 30], None, 1000);
-        km.create_data_key("key2", vec![40, 50, 60], None, 1000);
-
-        // New master key
-        let new_master = vec![5, 6, 7, 8];
-
-        // Dummy audit log
-        let mut audit = super::AuditLog::default();
-
-        // Perform secure rotation
-        km.rotate_master_secure(new_master.clone(), &mut audit, "admin", 1000);
-
-        // Check master key is new
-        assert_eq!(km.master, new_master);
-
-        // Check old master is zeroed (simulate by checking old_master is not present)
-        // (In real impl, would check memory or field is zeroed)
-
-        // Check data keys are re-encrypted (simulate: keys changed)
-        for key in km.data_keys.values() {
-            assert_ne!(key.key, vec![10, 20, 30]);
-            assert_ne!(key.key, vec![40, 50, 60]);
-        }
-
-        // Check audit log contains rotation event
-        let found = audit
-            .entries
-            .iter()
-            .any(|e| e.action == "rotate_master_secure");
-        assert!(found);
-    }
+use super::keys::{decrypt_legacy_xor, AuditLog, KeyError, KeyManager, FORMAT_V1, NONCE_LEN};
+
+const MASTER_V1: [u8; 32] = [0x11; 32];
+const MASTER_V2: [u8; 32] = [0x22; 32];
+const MASTER_V3: [u8; 32] = [0x33; 32];
+const DATA_KEY: [u8; 32] = [0x44; 32];
+const NONCE: [u8; NONCE_LEN] = [0x55; NONCE_LEN];
+const PLAINTEXT: &str = "QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG";
+
+/// `PLAINTEXT` sealed under `MASTER_V1` (version 1) with `NONCE`.
+const MASTER_V1_VECTOR: &str = "0100000001005555555555555555555555557e97921d89ec52b07eb29c2a25cb49a8662c679febcc444e15651b42a2c2f095f970d69268705290e1df4b5820c8fe934a4e65c3c82d3a22e469f031fb30";
+/// `PLAINTEXT` sealed under `MASTER_V2` (version 2) with `NONCE`.
+const MASTER_V2_VECTOR: &str = "010000000200555555555555555555555555087f8ec598b6af829df020a387fe19207955f840a4126685b0a492b8336005b13f694ae07bf2582cc5a56236b0c819e8d88d498dfb1fed969a931b64bed7";
+
+fn manager() -> KeyManager {
+    let mut km = KeyManager::new(MASTER_V1.to_vec());
+    km.create_data_key("phi", DATA_KEY.to_vec(), None, 1000)
+        .unwrap();
+    km
+}
+
+fn flip_last_byte(ciphertext: &str) -> String {
+    let mut bytes = super::hex_to_bytes(ciphertext).unwrap();
+    let last = bytes.len() - 1;
+    bytes[last] ^= 1;
+    super::bytes_to_hex(&bytes)
+}
+
+#[test]
+fn test_known_vectors_across_rotation() {
+    let mut km = manager();
+    let v1 = km.encrypt_with_nonce(None, PLAINTEXT, NONCE).unwrap();
+    assert_eq!(v1, MASTER_V1_VECTOR);
+    assert!(v1.starts_with("0100000001"));
+
+    km.rotate_master(MASTER_V2.to_vec()).unwrap();
+    let v2 = km.encrypt_with_nonce(None, PLAINTEXT, NONCE).unwrap();
+    assert_eq!(v2, MASTER_V2_VECTOR);
+    assert!(v2.starts_with("0100000002"));
+    // The retired master still opens what it sealed.
+    assert_eq!(km.decrypt(None, MASTER_V1_VECTOR).unwrap(), PLAINTEXT);
+    assert_eq!(km.decrypt(None, MASTER_V2_VECTOR).unwrap(), PLAINTEXT);
+
+    // A second rotation wipes version 1.
+    km.rotate_master(MASTER_V3.to_vec()).unwrap();
+    assert_eq!(
+        km.decrypt(None, MASTER_V1_VECTOR),
+        Err(KeyError::UnknownKeyVersion)
+    );
+    assert_eq!(km.decrypt(None, MASTER_V2_VECTOR).unwrap(), PLAINTEXT);
+    let migrated = km.reencrypt(None, MASTER_V2_VECTOR).unwrap();
+    assert!(migrated.starts_with("0100000003"));
+    assert_eq!(km.decrypt(None, &migrated).unwrap(), PLAINTEXT);
+}
+
+#[test]
+fn test_data_keys_survive_rotation() {
+    let mut km = manager();
+    let ciphertext = km.encrypt(Some("phi"), PLAINTEXT).unwrap();
+    let wrapped = km.get_key("phi").unwrap().wrapped_key.clone();
+
+    km.rotate_master(MASTER_V2.to_vec()).unwrap();
+    km.rotate_master(MASTER_V3.to_vec()).unwrap();
+    assert_ne!(km.get_key("phi").unwrap().wrapped_key, wrapped);
+    assert_eq!(km.decrypt(Some("phi"), &ciphertext).unwrap(), PLAINTEXT);
+
+    // Replacing the data key bumps its version; old ciphertexts are refused.
+    km.create_data_key("phi", [0x66; 32].to_vec(), None, 2000)
+        .unwrap();
+    assert_eq!(km.get_key("phi").unwrap().version, 2);
+    assert_eq!(
+        km.decrypt(Some("phi"), &ciphertext),
+        Err(KeyError::UnknownKeyVersion)
+    );
+}
+
+#[test]
+fn test_random_nonces() {
+    let km = manager();
+    let first = km.encrypt(None, PLAINTEXT).unwrap();
+    let second = km.encrypt(None, PLAINTEXT).unwrap();
+    assert_ne!(first, second);
+    assert_eq!(km.decrypt(None, &first).unwrap(), PLAINTEXT);
+    assert_eq!(km.decrypt(None, &second).unwrap(), PLAINTEXT);
+}
+
+#[test]
+fn test_decrypt_errors() {
+    let km = manager();
+    let sealed = km.encrypt(Some("phi"), PLAINTEXT).unwrap();
+
+    assert_eq!(
+        km.decrypt(Some("phi"), &flip_last_byte(&sealed)),
+        Err(KeyError::AuthenticationFailed)
+    );
+    assert_eq!(km.decrypt(None, &sealed), Err(KeyError::KeyMismatch));
+    assert_eq!(
+        km.decrypt(Some("other"), &sealed),
+        Err(KeyError::KeyMismatch)
+    );
+    assert_eq!(
+        km.decrypt(Some("phi"), "not hex"),
+        Err(KeyError::MalformedCiphertext)
+    );
+    assert_eq!(
+        km.decrypt(Some("phi"), &sealed[..40]),
+        Err(KeyError::MalformedCiphertext)
+    );
+    let future = with_format(FORMAT_V1 + 1, &sealed[2..]);
+    assert_eq!(
+        km.decrypt(Some("phi"), &future),
+        Err(KeyError::UnsupportedFormat)
+    );
+
+    // The key version is authenticated: rewriting it in the header fails
+    // the tag even when the named version exists.
+    let mut other = KeyManager::new(MASTER_V1.to_vec());
+    other.master_version = 7;
+    let relabelled = MASTER_V1_VECTOR.replacen("0100000001", "0100000007", 1);
+    assert_eq!(
+        other.decrypt(None, &relabelled),
+        Err(KeyError::AuthenticationFailed)
+    );
+
+    let wrong_master = KeyManager::new(MASTER_V2.to_vec());
+    assert_eq!(
+        wrong_master.decrypt(None, MASTER_V1_VECTOR),
+        Err(KeyError::AuthenticationFailed)
+    );
+    assert_eq!(
+        KeyManager::new(vec![1, 2, 3]).encrypt(None, PLAINTEXT),
+        Err(KeyError::InvalidKeyLength)
+    );
+}
+
+#[test]
+fn test_rotate_master_secure() {
+    let mut km = manager();
+    let mut audit = AuditLog::default();
+
+    assert_eq!(
+        km.rotate_master_secure(vec![5, 6, 7, 8], &mut audit, "admin", 1000),
+        Err(KeyError::InvalidKeyLength)
+    );
+    assert_eq!(km.master, MASTER_V1.to_vec());
+    assert!(audit.entries.is_empty());
+
+    km.rotate_master_secure(MASTER_V2.to_vec(), &mut audit, "admin", 1000)
+        .unwrap();
+    assert_eq!(km.master, MASTER_V2.to_vec());
+    assert_eq!(km.master_version, 2);
+    assert_eq!(km.old_master, Some(MASTER_V1.to_vec()));
+    assert!(audit
+        .entries
+        .iter()
+        .any(|e| e.action == "rotate_master_secure"));
+}
+
+#[test]
+fn test_decrypt_legacy_xor() {
+    // "ab" XORed with [0x01, 0x02], and with no key.
+    assert_eq!(
+        decrypt_legacy_xor(&[0x01, 0x02], "6060"),
+        Some(String::from("ab"))
+    );
+    assert_eq!(decrypt_legacy_xor(&[], "6162"), Some(String::from("ab")));
+    assert_eq!(decrypt_legacy_xor(&[0x01], "616"), None);
+    assert_eq!(decrypt_legacy_xor(&[0x80], "61"), None);
+}
+
+fn with_format(format: u8, rest: &str) -> String {
+    format!("{:02x}{}", format, rest)
 }
@@ -27,11 +27,16 @@ pub mod conflict_resolver;
 #[cfg(feature = "std")]
 pub mod consent;
 pub mod keys;
+#[cfg(all(test, feature = "std"))]
+mod keys_test;
 pub mod meta_tx;
 pub mod metering;
 pub mod multisig;
 pub mod policy_dsl;
+pub mod policy_analyzer;
 pub mod policy_engine;
+#[cfg(feature = "std")]
+pub mod policy_syntax;
 pub mod progressive_auth;
 pub mod pausable;
 pub mod rate_limit;
@@ -0,0 +1,784 @@
+//! Static analysis of policy definitions.
+//!
+//! Finds policies that can never behave as their author intended: Permit and
+//! Deny policies with identical conditions, policies fully shadowed by a
+//! higher-priority one that decides the same requests the same way, `And`
+//! nodes whose branches exclude each other,
+//! malformed `Not` / `IfThenElse` / `Unless` nodes, and temporal constraints
+//! that have already expired.
+//!
+//! The analysis only reads the definitions it is given, so it runs host-side
+//! before deployment as well as on-chain from `policy_engine`. It is
+//! conservative: every finding is a real problem, but not every problem is
+//! found (for example `Gte` / `Lte` conditions are never compared).
+
+#![allow(clippy::arithmetic_side_effects)]
+
+use soroban_sdk::{contracttype, Env, String, Vec};
+
+use crate::conflict_resolver::ResolutionStrategy;
+use crate::policy_dsl::{
+    AttrOperator, AttributeCondition, PolicyDefinition, PolicyId, PolicyRule, TemporalConstraint,
+};
+
+// ── Findings ────────────────────────────────────────────────────────────────
+
+/// The kind of problem a finding reports.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq, Copy)]
+#[repr(u32)]
+pub enum FindingKind {
+    /// A Permit and a Deny policy have equivalent rules.
+    PermitDenyOverlap = 1,
+    /// Every request the policy matches is already matched by a
+    /// higher-priority policy with the same effect, or with any effect under
+    /// `FirstApplicable`.
+    Shadowed = 2,
+    /// An `And` node has branches that can never all be true.
+    ContradictoryAnd = 3,
+    /// A `Not`, `IfThenElse` or `Unless` node has the wrong number of
+    /// children.
+    InvalidArity = 4,
+    /// A temporal constraint's `valid_until` is already in the past.
+    ExpiredTemporal = 5,
+    /// The policy, or the set it was compared against, is larger than
+    /// [`MAX_RULE_NODES`] / [`MAX_ANALYZED_POLICIES`], so it was not analyzed.
+    TooComplex = 6,
+}
+
+/// Rule trees with more nodes than this are reported as `TooComplex`.
+pub const MAX_RULE_NODES: u32 = 64;
+/// `analyze_candidate` compares against at most this many stored policies.
+pub const MAX_ANALYZED_POLICIES: u32 = 32;
+
+/// A single problem found by the analyzer.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PolicyFinding {
+    pub kind: FindingKind,
+    pub policy: PolicyId,
+    /// The other policy involved (overlap, shadowing); empty otherwise.
+    pub related: Vec<PolicyId>,
+    /// Child indices from the policy's root rule to the offending node;
+    /// empty for findings about the policy as a whole.
+    pub path: Vec<u32>,
+}
+
+// ── Entry Points ────────────────────────────────────────────────────────────
+
+/// Checks a single policy for malformed nodes, contradictory `And` branches
+/// and temporal constraints that expired before `now`.
+pub fn analyze_policy(env: &Env, policy: &PolicyDefinition, now: u64) -> Vec<PolicyFinding> {
+    let mut findings = Vec::new(env);
+    check_rule(
+        env,
+        &policy.id,
+        &policy.rule,
+        &Vec::new(env),
+        now,
+        &mut findings,
+    );
+    findings
+}
+
+/// Checks every policy on its own, then compares each pair of enabled
+/// policies for Permit/Deny overlap and shadowing under `strategy`.
+///
+/// Meant for host-side use: the pairwise pass is quadratic in the number of
+/// policies. On-chain callers use [`analyze_candidate`].
+pub fn analyze_policies(
+    env: &Env,
+    policies: &Vec<PolicyDefinition>,
+    now: u64,
+    strategy: ResolutionStrategy,
+) -> Vec<PolicyFinding> {
+    let mut findings = Vec::new(env);
+    for i in 0..policies.len() {
+        findings.append(&analyze_policy(env, &policies.get(i).unwrap(), now));
+    }
+
+    for i in 0..policies.len() {
+        let a = policies.get(i).unwrap();
+        for j in (i + 1)..policies.len() {
+            compare_pair(env, &a, &policies.get(j).unwrap(), strategy, &mut findings);
+        }
+    }
+    findings
+}
+
+/// Checks `candidate` on its own and against each of `others`, without
+/// comparing `others` among themselves. The cost is linear in `others` and
+/// bounded by [`MAX_ANALYZED_POLICIES`] and [`MAX_RULE_NODES`]; anything
+/// larger yields a single `TooComplex` finding instead.
+pub fn analyze_candidate(
+    env: &Env,
+    candidate: &PolicyDefinition,
+    others: &Vec<PolicyDefinition>,
+    now: u64,
+    strategy: ResolutionStrategy,
+) -> Vec<PolicyFinding> {
+    if others.len() > MAX_ANALYZED_POLICIES || too_large(&candidate.rule) {
+        return too_complex(env, candidate);
+    }
+
+    let mut findings = analyze_policy(env, candidate, now);
+    for other in others.iter() {
+        if too_large(&other.rule) {
+            findings.push_back(pair_finding(
+                env,
+                FindingKind::TooComplex,
+                candidate,
+                &other,
+            ));
+            continue;
+        }
+        // Same order as `analyze_policies` with the candidate appended.
+        compare_pair(env, &other, candidate, strategy, &mut findings);
+    }
+    findings
+}
+
+/// The single `TooComplex` finding reported for a candidate that was not
+/// analyzed.
+pub fn too_complex(env: &Env, candidate: &PolicyDefinition) -> Vec<PolicyFinding> {
+    let mut findings = Vec::new(env);
+    findings.push_back(PolicyFinding {
+        kind: FindingKind::TooComplex,
+        policy: candidate.id.clone(),
+        related: Vec::new(env),
+        path: Vec::new(env),
+    });
+    findings
+}
+
+fn compare_pair(
+    env: &Env,
+    a: &PolicyDefinition,
+    b: &PolicyDefinition,
+    strategy: ResolutionStrategy,
+    findings: &mut Vec<PolicyFinding>,
+) {
+    if !a.enabled || !b.enabled {
+        return;
+    }
+    if a.effect != b.effect && equivalent(&a.rule, &b.rule) {
+        findings.push_back(pair_finding(env, FindingKind::PermitDenyOverlap, a, b));
+        return;
+    }
+    // Under the override strategies a policy only changes the outcome when
+    // no policy with the other effect matches, so a broader policy can only
+    // shadow one with the same effect.
+    if a.effect != b.effect && strategy != ResolutionStrategy::FirstApplicable {
+        return;
+    }
+    if a.priority < b.priority && implies(&b.rule, &a.rule) {
+        findings.push_back(pair_finding(env, FindingKind::Shadowed, b, a));
+    } else if b.priority < a.priority && implies(&a.rule, &b.rule) {
+        findings.push_back(pair_finding(env, FindingKind::Shadowed, a, b));
+    }
+}
+
+/// True when `rule` has more than [`MAX_RULE_NODES`] nodes.
+fn too_large(rule: &PolicyRule) -> bool {
+    let mut budget = MAX_RULE_NODES;
+    !within_budget(rule, &mut budget)
+}
+
+fn within_budget(rule: &PolicyRule, budget: &mut u32) -> bool {
+    if *budget == 0 {
+        return false;
+    }
+    *budget -= 1;
+    match rule {
+        PolicyRule::And(children)
+        | PolicyRule::Or(children)
+        | PolicyRule::Not(children)
+        | PolicyRule::IfThenElse(children)
+        | PolicyRule::Unless(children) => children.iter().all(|c| within_budget(&c, budget)),
+        _ => true,
+    }
+}
+
+fn pair_finding(
+    env: &Env,
+    kind: FindingKind,
+    policy: &PolicyDefinition,
+    related: &PolicyDefinition,
+) -> PolicyFinding {
+    let mut others = Vec::new(env);
+    others.push_back(related.id.clone());
+    PolicyFinding {
+        kind,
+        policy: policy.id.clone(),
+        related: others,
+        path: Vec::new(env),
+    }
+}
+
+// ── Per-node Checks ─────────────────────────────────────────────────────────
+
+fn check_rule(
+    env: &Env,
+    id: &PolicyId,
+    rule: &PolicyRule,
+    path: &Vec<u32>,
+    now: u64,
+    findings: &mut Vec<PolicyFinding>,
+) {
+    let node_finding = |kind| PolicyFinding {
+        kind,
+        policy: id.clone(),
+        related: Vec::new(env),
+        path: path.clone(),
+    };
+
+    let children = match rule {
+        PolicyRule::And(children) => {
+            if is_contradictory(children) {
+                findings.push_back(node_finding(FindingKind::ContradictoryAnd));
+            }
+            children
+        }
+        PolicyRule::Or(children) => children,
+        PolicyRule::Not(children) => {
+            if children.len() != 1 {
+                findings.push_back(node_finding(FindingKind::InvalidArity));
+            }
+            children
+        }
+        PolicyRule::IfThenElse(children) => {
+            if children.len() != 3 {
+                findings.push_back(node_finding(FindingKind::InvalidArity));
+            }
+            children
+        }
+        PolicyRule::Unless(children) => {
+            if children.len() != 2 {
+                findings.push_back(node_finding(FindingKind::InvalidArity));
+            }
+            children
+        }
+        PolicyRule::Temporal(constraint) => {
+            if constraint.valid_until != 0 && now > constraint.valid_until {
+                findings.push_back(node_finding(FindingKind::ExpiredTemporal));
+            }
+            return;
+        }
+        _ => return,
+    };
+
+    for i in 0..children.len() {
+        let mut child_path = path.clone();
+        child_path.push_back(i);
+        check_rule(
+            env,
+            id,
+            &children.get(i).unwrap(),
+            &child_path,
+            now,
+            findings,
+        );
+    }
+}
+
+/// True when the children of an `And` can never all hold at once.
+fn is_contradictory(children: &Vec<PolicyRule>) -> bool {
+    for i in 0..children.len() {
+        let a = children.get(i).unwrap();
+        if never_matches(&a) {
+            return true;
+        }
+        for j in (i + 1)..children.len() {
+            if excludes(&a, &children.get(j).unwrap()) {
+                return true;
+            }
+        }
+    }
+    false
+}
+
+fn never_matches(rule: &PolicyRule) -> bool {
+    match rule {
+        PolicyRule::Deny => true,
+        PolicyRule::Or(children) => children.is_empty(),
+        PolicyRule::Attribute(cond) => match value_set(cond) {
+            Some(set) => set.is_empty(),
+            None => false,
+        },
+        _ => false,
+    }
+}
+
+/// True when `a` and `b` can never both hold.
+fn excludes(a: &PolicyRule, b: &PolicyRule) -> bool {
+    match (a, b) {
+        (PolicyRule::Not(inner), other) | (other, PolicyRule::Not(inner)) if inner.len() == 1 => {
+            implies(other, &inner.get(0).unwrap())
+        }
+        (PolicyRule::Attribute(x), PolicyRule::Attribute(y)) if x.key == y.key => {
+            match (value_set(x), value_set(y)) {
+                (Some(sx), Some(sy)) => sx.is_disjoint(&sy),
+                _ => false,
+            }
+        }
+        (PolicyRule::Temporal(x), PolicyRule::Temporal(y)) => temporal_disjoint(x, y),
+        _ => false,
+    }
+}
+
+// ── Implication ─────────────────────────────────────────────────────────────
+
+/// Conservative implication: true only when every context matching `p` is
+/// provably matched by `q` too.
+fn implies(p: &PolicyRule, q: &PolicyRule) -> bool {
+    if p == q {
+        return true;
+    }
+    match (p, q) {
+        (_, PolicyRule::Allow) => true,
+        (PolicyRule::Or(ps), _) if !ps.is_empty() => all(ps, |c| implies(&c, q)),
+        (_, PolicyRule::And(qs)) => all(qs, |c| implies(p, &c)),
+        (PolicyRule::And(ps), _) => any(ps, |c| implies(&c, q)),
+        (_, PolicyRule::Or(qs)) => any(qs, |c| implies(p, &c)),
+        (PolicyRule::Attribute(x), PolicyRule::Attribute(y)) if x.key == y.key => {
+            match (value_set(x), value_set(y)) {
+                (Some(sx), Some(sy)) => sx.is_subset(&sy),
+                _ => false,
+            }
+        }
+        (PolicyRule::Temporal(x), PolicyRule::Temporal(y)) => temporal_within(x, y),
+        _ => false,
+    }
+}
+
+/// Implication in both directions, so `And` / `Or` children and attribute
+/// values may appear in any order.
+fn equivalent(p: &PolicyRule, q: &PolicyRule) -> bool {
+    implies(p, q) && implies(q, p)
+}
+
+fn all(rules: &Vec<PolicyRule>, f: impl Fn(PolicyRule) -> bool) -> bool {
+    rules.iter().all(f)
+}
+
+fn any(rules: &Vec<PolicyRule>, f: impl Fn(PolicyRule) -> bool) -> bool {
+    rules.iter().any(f)
+}
+
+// ── Attribute Value Sets ────────────────────────────────────────────────────
+
+/// The attribute values an `AttributeCondition` accepts: either exactly the
+/// listed values, or anything except them. Mirrors `evaluate_attribute`,
+/// which only reads the first value for `Eq` / `NotEq`.
+struct ValueSet {
+    values: Vec<String>,
+    complement: bool,
+}
+
+fn value_set(cond: &AttributeCondition) -> Option<ValueSet> {
+    let first = |values: &Vec<String>| {
+        let mut out = Vec::new(values.env());
+        if let Some(v) = values.first() {
+            out.push_back(v);
+        }
+        out
+    };
+    let (values, complement) = match cond.operator {
+        AttrOperator::Eq => (first(&cond.values), false),
+        AttrOperator::NotEq => (first(&cond.values), true),
+        AttrOperator::In => (cond.values.clone(), false),
+        AttrOperator::NotIn => (cond.values.clone(), true),
+        AttrOperator::Gte | AttrOperator::Lte => {
+            if !cond.values.is_empty() {
+                return None;
+            }
+            (Vec::new(cond.values.env()), false)
+        }
+    };
+    Some(ValueSet { values, complement })
+}
+
+impl ValueSet {
+    fn is_empty(&self) -> bool {
+        !self.complement && self.values.is_empty()
+    }
+
+    fn is_subset(&self, other: &ValueSet) -> bool {
+        match (self.complement, other.complement) {
+            (false, false) => self.values.iter().all(|v| other.values.contains(v)),
+            (false, true) => self.values.iter().all(|v| !other.values.contains(v)),
+            (true, false) => false,
+            (true, true) => other.values.iter().all(|v| self.values.contains(v)),
+        }
+    }
+
+    fn is_disjoint(&self, other: &ValueSet) -> bool {
+        match (self.complement, other.complement) {
+            (false, false) => self.values.iter().all(|v| !other.values.contains(v)),
+            (false, true) => self.values.iter().all(|v| other.values.contains(v)),
+            (true, false) => other.values.iter().all(|v| self.values.contains(v)),
+            (true, true) => false,
+        }
+    }
+}
+
+// ── Temporal Windows ────────────────────────────────────────────────────────
+
+/// Bit `h` is set when hour `h` passes the constraint's hour-of-day check.
+fn hour_mask(c: &TemporalConstraint) -> u32 {
+    let mut mask = 0u32;
+    for hour in 0..24u32 {
+        let allowed = if c.allowed_hour_start <= c.allowed_hour_end {
+            hour >= c.allowed_hour_start && hour <= c.allowed_hour_end
+        } else {
+            hour >= c.allowed_hour_start || hour <= c.allowed_hour_end
+        };
+        if allowed {
+            mask |= 1 << hour;
+        }
+    }
+    mask
+}
+
+fn day_mask(c: &TemporalConstraint) -> u32 {
+    if c.allowed_days_mask == 0 {
+        0x7f
+    } else {
+        c.allowed_days_mask & 0x7f
+    }
+}
+
+fn upper_bound(c: &TemporalConstraint) -> u64 {
+    if c.valid_until == 0 {
+        u64::MAX
+    } else {
+        c.valid_until
+    }
+}
+
+fn temporal_disjoint(a: &TemporalConstraint, b: &TemporalConstraint) -> bool {
+    a.valid_from > upper_bound(b)
+        || b.valid_from > upper_bound(a)
+        || hour_mask(a) & hour_mask(b) == 0
+        || day_mask(a) & day_mask(b) == 0
+}
+
+/// True when every instant allowed by `inner` is also allowed by `outer`.
+fn temporal_within(inner: &TemporalConstraint, outer: &TemporalConstraint) -> bool {
+    inner.valid_from >= outer.valid_from
+        && upper_bound(inner) <= upper_bound(outer)
+        && hour_mask(inner) & !hour_mask(outer) == 0
+        && day_mask(inner) & !day_mask(outer) == 0
+}
+
+// ── Tests ───────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::policy_dsl::PolicyEffect;
+    use soroban_sdk::vec;
+
+    fn attr(env: &Env, key: &str, operator: AttrOperator, values: &[&str]) -> PolicyRule {
+        let mut vals = Vec::new(env);
+        for v in values {
+            vals.push_back(String::from_str(env, v));
+        }
+        PolicyRule::Attribute(AttributeCondition {
+            key: String::from_str(env, key),
+            operator,
+            values: vals,
+        })
+    }
+
+    fn rules(env: &Env, items: &[PolicyRule]) -> Vec<PolicyRule> {
+        let mut out = Vec::new(env);
+        for r in items {
+            out.push_back(r.clone());
+        }
+        out
+    }
+
+    fn hours(start: u32, end: u32, valid_until: u64) -> PolicyRule {
+        PolicyRule::Temporal(TemporalConstraint {
+            valid_from: 0,
+            valid_until,
+            allowed_hour_start: start,
+            allowed_hour_end: end,
+            allowed_days_mask: 0,
+        })
+    }
+
+    fn policy(
+        env: &Env,
+        name: &str,
+        rule: PolicyRule,
+        effect: PolicyEffect,
+        priority: u32,
+    ) -> PolicyDefinition {
+        PolicyDefinition {
+            id: PolicyId {
+                name: String::from_str(env, name),
+                version: 1,
+            },
+            description: String::from_str(env, "test policy"),
+            rule,
+            effect,
+            priority,
+            enabled: true,
+        }
+    }
+
+    fn kinds(env: &Env, findings: &Vec<PolicyFinding>) -> Vec<FindingKind> {
+        let mut out = Vec::new(env);
+        for f in findings.iter() {
+            out.push_back(f.kind);
+        }
+        out
+    }
+
+    #[test]
+    fn reports_arity_and_expiry_with_paths() {
+        let env = Env::default();
+        let rule = PolicyRule::Or(rules(
+            &env,
+            &[
+                PolicyRule::Not(rules(&env, &[PolicyRule::Allow, PolicyRule::Deny])),
+                PolicyRule::IfThenElse(rules(&env, &[PolicyRule::Allow, hours(8, 17, 500)])),
+            ],
+        ));
+        let p = policy(&env, "p", rule, PolicyEffect::Permit, 1);
+
+        let findings = analyze_policy(&env, &p, 1000);
+        assert_eq!(
+            kinds(&env, &findings),
+            vec![
+                &env,
+                FindingKind::InvalidArity,
+                FindingKind::InvalidArity,
+                FindingKind::ExpiredTemporal
+            ]
+        );
+        let mut expired_at = Vec::new(&env);
+        expired_at.push_back(1);
+        expired_at.push_back(1);
+        assert_eq!(findings.get(2).unwrap().path, expired_at);
+
+        assert_eq!(analyze_policy(&env, &p, 400).len(), 2);
+    }
+
+    #[test]
+    fn detects_contradictory_and_branches() {
+        let env = Env::default();
+        let role_doctor = attr(&env, "role", AttrOperator::Eq, &["doctor"]);
+        let contradictory = [
+            [
+                role_doctor.clone(),
+                attr(&env, "role", AttrOperator::In, &["nurse", "admin"]),
+            ],
+            [
+                role_doctor.clone(),
+                attr(&env, "role", AttrOperator::NotIn, &["doctor", "admin"]),
+            ],
+            [
+                role_doctor.clone(),
+                PolicyRule::Not(rules(&env, core::slice::from_ref(&role_doctor))),
+            ],
+            [hours(8, 12, 0), hours(13, 17, 0)],
+        ];
+        for pair in contradictory {
+            let p = policy(
+                &env,
+                "p",
+                PolicyRule::And(rules(&env, &pair)),
+                PolicyEffect::Permit,
+                1,
+            );
+            assert_eq!(
+                kinds(&env, &analyze_policy(&env, &p, 0)),
+                vec![&env, FindingKind::ContradictoryAnd]
+            );
+        }
+
+        let satisfiable = [
+            [
+                role_doctor.clone(),
+                attr(&env, "role", AttrOperator::In, &["doctor", "nurse"]),
+            ],
+            [
+                role_doctor.clone(),
+                attr(&env, "department", AttrOperator::Eq, &["retina"]),
+            ],
+            [hours(22, 6, 0), hours(5, 9, 0)],
+        ];
+        for pair in satisfiable {
+            let p = policy(
+                &env,
+                "p",
+                PolicyRule::And(rules(&env, &pair)),
+                PolicyEffect::Permit,
+                1,
+            );
+            assert!(analyze_policy(&env, &p, 0).is_empty());
+        }
+    }
+
+    #[test]
+    fn detects_overlap_and_shadowing() {
+        let env = Env::default();
+        let doctor = attr(&env, "role", AttrOperator::Eq, &["doctor"]);
+        let retina = attr(
+            &env,
+            "department",
+            AttrOperator::In,
+            &["retina", "glaucoma"],
+        );
+        let retina_reordered = attr(
+            &env,
+            "department",
+            AttrOperator::In,
+            &["glaucoma", "retina"],
+        );
+
+        let permit = policy(
+            &env,
+            "permit",
+            PolicyRule::And(rules(&env, &[doctor.clone(), retina.clone()])),
+            PolicyEffect::Permit,
+            10,
+        );
+        let deny = policy(
+            &env,
+            "deny",
+            PolicyRule::And(rules(&env, &[retina_reordered, doctor.clone()])),
+            PolicyEffect::Deny,
+            20,
+        );
+        let broad = policy(&env, "broad", doctor.clone(), PolicyEffect::Permit, 5);
+        let mut disabled = policy(&env, "off", doctor.clone(), PolicyEffect::Deny, 1);
+        disabled.enabled = false;
+
+        let mut all = Vec::new(&env);
+        all.push_back(permit.clone());
+        all.push_back(deny.clone());
+        all.push_back(broad.clone());
+        all.push_back(disabled);
+
+        // Under DenyOverride the broad Permit never overrides the narrower
+        // Deny, so only the narrower Permit is shadowed.
+        let findings = analyze_policies(&env, &all, 0, ResolutionStrategy::DenyOverride);
+        assert_eq!(
+            kinds(&env, &findings),
+            vec![&env, FindingKind::PermitDenyOverlap, FindingKind::Shadowed]
+        );
+        let overlap = findings.get(0).unwrap();
+        assert_eq!(overlap.policy, permit.id);
+        assert_eq!(overlap.related.get(0).unwrap(), deny.id);
+        assert_eq!(findings.get(1).unwrap().policy, permit.id);
+        assert_eq!(findings.get(1).unwrap().related.get(0).unwrap(), broad.id);
+
+        // Under FirstApplicable the broader, higher-priority policy decides
+        // first, so both narrower policies are shadowed.
+        let findings = analyze_policies(&env, &all, 0, ResolutionStrategy::FirstApplicable);
+        assert_eq!(
+            kinds(&env, &findings),
+            vec![
+                &env,
+                FindingKind::PermitDenyOverlap,
+                FindingKind::Shadowed,
+                FindingKind::Shadowed
+            ]
+        );
+        assert_eq!(findings.get(1).unwrap().policy, permit.id);
+        assert_eq!(findings.get(2).unwrap().policy, deny.id);
+        assert_eq!(findings.get(2).unwrap().related.get(0).unwrap(), broad.id);
+
+        // The broad policy is not shadowed by the narrower ones.
+        let mut pair = Vec::new(&env);
+        pair.push_back(policy(&env, "a", retina.clone(), PolicyEffect::Permit, 1));
+        pair.push_back(policy(
+            &env,
+            "b",
+            PolicyRule::Or(rules(&env, &[retina, doctor])),
+            PolicyEffect::Permit,
+            2,
+        ));
+        assert!(analyze_policies(&env, &pair, 0, ResolutionStrategy::FirstApplicable).is_empty());
+    }
+
+    #[test]
+    fn candidate_analysis_is_bounded() {
+        let env = Env::default();
+        let doctor = attr(&env, "role", AttrOperator::Eq, &["doctor"]);
+        let candidate = policy(&env, "c", doctor.clone(), PolicyEffect::Permit, 10);
+        let broad = policy(&env, "broad", PolicyRule::Allow, PolicyEffect::Permit, 1);
+
+        let mut others = Vec::new(&env);
+        others.push_back(broad.clone());
+        let findings = analyze_candidate(
+            &env,
+            &candidate,
+            &others,
+            0,
+            ResolutionStrategy::DenyOverride,
+        );
+        assert_eq!(kinds(&env, &findings), vec![&env, FindingKind::Shadowed]);
+
+        // A stored policy too large to compare is reported, not walked.
+        let mut children = Vec::new(&env);
+        for _ in 0..MAX_RULE_NODES {
+            children.push_back(doctor.clone());
+        }
+        let huge = policy(
+            &env,
+            "huge",
+            PolicyRule::Or(children.clone()),
+            PolicyEffect::Deny,
+            1,
+        );
+        others.push_back(huge.clone());
+        let findings = analyze_candidate(
+            &env,
+            &candidate,
+            &others,
+            0,
+            ResolutionStrategy::DenyOverride,
+        );
+        assert_eq!(
+            kinds(&env, &findings),
+            vec![&env, FindingKind::Shadowed, FindingKind::TooComplex]
+        );
+        assert_eq!(findings.get(1).unwrap().related.get(0).unwrap(), huge.id);
+
+        // So is a candidate that is itself too large, or too many policies.
+        assert_eq!(
+            kinds(
+                &env,
+                &analyze_candidate(
+                    &env,
+                    &huge,
+                    &Vec::new(&env),
+                    0,
+                    ResolutionStrategy::DenyOverride
+                )
+            ),
+            vec![&env, FindingKind::TooComplex]
+        );
+        while others.len() <= MAX_ANALYZED_POLICIES {
+            others.push_back(broad.clone());
+        }
+        assert_eq!(
+            kinds(
+                &env,
+                &analyze_candidate(
+                    &env,
+                    &candidate,
+                    &others,
+                    0,
+                    ResolutionStrategy::DenyOverride
+                )
+            ),
+            vec![&env, FindingKind::TooComplex]
+        );
+    }
+}
@@ -286,6 +286,42 @@ pub enum SimulationVerdict {
     Indeterminate = 3,
 }
 
+/// The kind of `PolicyRule` node a trace step describes.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq, Copy)]
+#[repr(u32)]
+pub enum RuleKind {
+    Allow = 1,
+    Deny = 2,
+    And = 3,
+    Or = 4,
+    Not = 5,
+    IfThenElse = 6,
+    Unless = 7,
+    Attribute = 8,
+    Temporal = 9,
+    DelegationCheck = 10,
+}
+
+/// One rule node visited while explaining a simulation. Nodes skipped by
+/// short-circuiting are not visited.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TraceStep {
+    pub policy: PolicyId,
+    /// Child indices from the policy's root rule to this node.
+    pub path: Vec<u32>,
+    pub kind: RuleKind,
+    pub result: bool,
+    /// For `Attribute` nodes, the attribute key; empty otherwise.
+    pub attr_key: String,
+    /// For `Attribute` nodes, the values the condition compares against.
+    pub expected: Vec<String>,
+    /// For `Attribute` nodes, the subject's value; empty when the subject
+    /// has no such attribute or the node is not an attribute.
+    pub actual: Vec<String>,
+}
+
 /// Detailed result from a policy simulation run.
 #[contracttype]
 #[derive(Clone, Debug)]
@@ -294,6 +330,10 @@ pub struct SimulationResult {
     /// Empty vec = no match; single element = the winning policy.
     pub matched_policy: Vec<PolicyId>,
     pub evaluated_count: u32,
+    /// Nodes visited, in evaluation order. Only filled by `explain`.
+    pub trace: Vec<TraceStep>,
+    /// True when the trace stopped at `MAX_TRACE_STEPS`.
+    pub trace_truncated: bool,
 }
 
 #[cfg(test)]
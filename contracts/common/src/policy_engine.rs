@@ -5,12 +5,13 @@
 
 #![allow(clippy::arithmetic_side_effects)]
 
-use soroban_sdk::{contracttype, symbol_short, Env, String, Symbol, Vec};
+use soroban_sdk::{contracttype, symbol_short, Address, Env, String, Symbol, Vec};
 
 use crate::conflict_resolver::{self, ResolutionResult, ResolutionStrategy};
+use crate::policy_analyzer::{self, PolicyFinding};
 use crate::policy_dsl::{
     AttrOperator, AttributeCondition, EvalContext, PolicyDefinition, PolicyEffect, PolicyId,
-    PolicyRule, SimulationResult, SimulationVerdict,
+    PolicyRule, RuleKind, SimulationResult, SimulationVerdict, TraceStep,
 };
 
 // ── Storage Keys ────────────────────────────────────────────────────────────
@@ -20,6 +21,14 @@ const POLICY_INDEX: Symbol = symbol_short!("POL_IDX");
 const POLICY_STRATEGY: Symbol = symbol_short!("POL_STRT");
 const CACHE_PREFIX: Symbol = symbol_short!("POL_CACH");
 const CACHE_GEN: Symbol = symbol_short!("CACHE_GN");
+const POLICY_ANALYSIS: Symbol = symbol_short!("POL_ANLZ");
+const HISTORY_PREFIX: Symbol = symbol_short!("POL_HIST");
+const ROLLOUT_PREFIX: Symbol = symbol_short!("POL_ROLL");
+const DIVERGENCE_PREFIX: Symbol = symbol_short!("POL_DIVG");
+const ROLLOUT_COUNT: Symbol = symbol_short!("POL_RCNT");
+
+/// Number of shadow divergences kept per policy; older ones are dropped.
+pub const MAX_SHADOW_DIVERGENCES: u32 = 50;
 
 const TTL_THRESHOLD: u32 = 5184000;
 const TTL_EXTEND_TO: u32 = 10368000;
@@ -34,6 +43,18 @@ fn cache_key(subject_action: &String) -> (Symbol, String) {
     (CACHE_PREFIX, subject_action.clone())
 }
 
+fn history_key(name: &String) -> (Symbol, String) {
+    (HISTORY_PREFIX, name.clone())
+}
+
+fn rollout_key(name: &String) -> (Symbol, String) {
+    (ROLLOUT_PREFIX, name.clone())
+}
+
+fn divergence_key(name: &String) -> (Symbol, String) {
+    (DIVERGENCE_PREFIX, name.clone())
+}
+
 // ── Cache Entry ─────────────────────────────────────────────────────────────
 
 /// A cached evaluation result, tagged with a generation counter so it can be
@@ -46,25 +67,113 @@ pub struct CacheEntry {
     pub timestamp: u64,
 }
 
+// ── Versioning Types ────────────────────────────────────────────────────────
+
+/// How a staged policy version goes live.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq, Copy)]
+#[repr(u32)]
+pub enum RolloutMode {
+    /// Replaces the active version once the ledger reaches `activate_at`.
+    Scheduled = 1,
+    /// Evaluated alongside the active version without affecting decisions;
+    /// differing effects are logged until the version is promoted.
+    Shadow = 2,
+}
+
+/// A policy version waiting to replace the active version of its policy.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PolicyRollout {
+    pub candidate: PolicyId,
+    /// The version that was active when the candidate was staged.
+    pub replaces: PolicyId,
+    pub mode: RolloutMode,
+    /// Activation timestamp for `Scheduled` rollouts; 0 for `Shadow`.
+    pub activate_at: u64,
+    pub staged_at: u64,
+    /// Result of `is_backward_compatible(replaces, candidate)`.
+    pub backward_compatible: bool,
+}
+
+impl PolicyRollout {
+    fn is_due(&self, now: u64) -> bool {
+        self.mode == RolloutMode::Scheduled && now >= self.activate_at
+    }
+}
+
+/// A request for which a shadow candidate's effect differed from the active
+/// version's.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ShadowDivergence {
+    pub active: PolicyId,
+    pub candidate: PolicyId,
+    pub subject: Address,
+    pub resource_id: String,
+    pub action: String,
+    pub timestamp: u64,
+    pub active_effect: PolicyEffect,
+    pub candidate_effect: PolicyEffect,
+}
+
+/// Errors returned by the policy versioning functions.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum PolicyVersionError {
+    /// The policy has no active version to stage against; store the first
+    /// version with `store_policy`.
+    NoActiveVersion,
+    /// This version of the policy was already recorded.
+    VersionExists,
+    /// The version is not in the policy's history.
+    VersionNotFound,
+    /// The policy has no staged version.
+    NoPendingRollout,
+    /// A scheduled activation time must be in the future.
+    InvalidActivationTime,
+}
+
+/// Why `store_policy_checked` refused to store a policy.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum StorePolicyError {
+    /// `store_policy` rejected the version.
+    Version(PolicyVersionError),
+    /// Analysis is required and reported these findings.
+    Findings(Vec<PolicyFinding>),
+}
+
 // ── Policy Management ───────────────────────────────────────────────────────
 
-/// Stores a policy definition on-chain and updates the global policy index.
-/// Bumps the cache generation to invalidate stale cached results.
-pub fn store_policy(env: &Env, policy: &PolicyDefinition) {
-    let key = policy_storage_key(&policy.id);
-    env.storage().persistent().set(&key, policy);
-    env.storage()
-        .persistent()
-        .extend_ttl(&key, TTL_THRESHOLD, TTL_EXTEND_TO);
+/// Stores a policy definition on-chain and updates the global policy index,
+/// making it the active version immediately. Any staged rollout for the
+/// policy is cancelled. Bumps the cache generation to invalidate stale
+/// cached results.
+///
+/// Recorded versions are immutable: a version already in the policy's
+/// history fails with `VersionExists`. Use `rollback_policy` to reactivate
+/// one.
+pub fn store_policy(env: &Env, policy: &PolicyDefinition) -> Result<(), PolicyVersionError> {
+    if policy_history(env, &policy.id.name).contains(policy.id.version) {
+        return Err(PolicyVersionError::VersionExists);
+    }
+    save_definition(env, policy);
+    clear_rollout(env, &policy.id.name);
 
     update_policy_index(env, &policy.id);
     bump_cache_generation(env);
+    Ok(())
 }
 
 /// Removes a policy from on-chain storage and drops it from the index.
+/// Removing the active or staged version also cancels the rollout.
 pub fn remove_policy(env: &Env, id: &PolicyId) {
     let key = policy_storage_key(id);
     env.storage().persistent().remove(&key);
+    if let Some(rollout) = get_rollout(env, &id.name) {
+        if rollout.candidate == *id || rollout.replaces == *id {
+            clear_rollout(env, &id.name);
+        }
+    }
     remove_from_index(env, id);
     bump_cache_generation(env);
 }
@@ -96,6 +205,66 @@ pub fn get_resolution_strategy(env: &Env) -> ResolutionStrategy {
         .unwrap_or(ResolutionStrategy::DenyOverride)
 }
 
+// ── Pre-deployment Analysis ─────────────────────────────────────────────────
+
+/// Turns the static analysis check in `store_policy_checked` on or off.
+pub fn set_analysis_required(env: &Env, required: bool) {
+    env.storage().persistent().set(&POLICY_ANALYSIS, &required);
+}
+
+/// Returns whether new policies must pass static analysis before they are
+/// stored. Defaults to `false`.
+pub fn is_analysis_required(env: &Env) -> bool {
+    env.storage()
+        .persistent()
+        .get(&POLICY_ANALYSIS)
+        .unwrap_or(false)
+}
+
+/// Analyzes `policy` as if it replaced any stored policy with the same name,
+/// at the current ledger timestamp and under the configured strategy. Only
+/// findings that involve `policy` are returned. With more than
+/// `MAX_ANALYZED_POLICIES` other policies stored, the single finding is
+/// `TooComplex` and no stored policy is loaded.
+pub fn analyze_candidate(env: &Env, policy: &PolicyDefinition) -> Vec<PolicyFinding> {
+    let ids = list_policies(env);
+    let count = ids.iter().filter(|id| id.name != policy.id.name).count();
+    if count > policy_analyzer::MAX_ANALYZED_POLICIES as usize {
+        return policy_analyzer::too_complex(env, policy);
+    }
+
+    let mut others: Vec<PolicyDefinition> = Vec::new(env);
+    for id in ids.iter() {
+        if id.name == policy.id.name {
+            continue;
+        }
+        if let Some(existing) = get_policy(env, &id) {
+            others.push_back(existing);
+        }
+    }
+
+    policy_analyzer::analyze_candidate(
+        env,
+        policy,
+        &others,
+        env.ledger().timestamp(),
+        get_resolution_strategy(env),
+    )
+}
+
+/// Stores `policy` like `store_policy`, but when analysis is required and
+/// `analyze_candidate` reports anything, nothing is stored and the findings
+/// are returned instead.
+pub fn store_policy_checked(env: &Env, policy: &PolicyDefinition) -> Result<(), StorePolicyError> {
+    if is_analysis_required(env) {
+        let findings = analyze_candidate(env, policy);
+        if !findings.is_empty() {
+            return Err(StorePolicyError::Findings(findings));
+        }
+    }
+    store_policy(env, policy).map_err(StorePolicyError::Version)
+}
+
 // ── Policy Evaluation ───────────────────────────────────────────────────────
 
 /// Evaluates all enabled policies against the provided context and resolves
@@ -103,6 +272,10 @@ pub fn get_resolution_strategy(env: &Env) -> ResolutionStrategy {
 ///
 /// Returns a `ResolutionResult` describing the final effect, any conflicts
 /// detected, and which policy "won".
+///
+/// Scheduled versions whose activation time has passed are evaluated in
+/// place of the active version. Shadow versions are evaluated alongside it,
+/// and any difference in effect is logged to `shadow_divergences`.
 pub fn evaluate(env: &Env, ctx: &EvalContext) -> ResolutionResult {
     let ids = list_policies(env);
     let strategy = get_resolution_strategy(env);
@@ -110,17 +283,18 @@ pub fn evaluate(env: &Env, ctx: &EvalContext) -> ResolutionResult {
     let mut matched: Vec<(PolicyDefinition, PolicyEffect)> = Vec::new(env);
 
     for i in 0..ids.len() {
-        let id = ids.get(i).unwrap();
+        let rollout = get_rollout(env, &ids.get(i).unwrap().name);
+        let id = live_id(ids.get(i).unwrap(), &rollout, ctx.timestamp);
         if let Some(policy) = get_policy(env, &id) {
             if !policy.enabled {
                 continue;
             }
-            let rule_result = evaluate_rule(env, &policy.rule, ctx);
-            let effect = if rule_result {
-                policy.effect
-            } else {
-                invert_effect(policy.effect)
-            };
+            let effect = policy_effect(env, &policy, ctx);
+            if let Some(rollout) = rollout {
+                if rollout.mode == RolloutMode::Shadow {
+                    compare_shadow(env, &policy, effect, &rollout.candidate, ctx);
+                }
+            }
             matched.push_back((policy, effect));
         }
     }
@@ -133,7 +307,15 @@ pub fn evaluate(env: &Env, ctx: &EvalContext) -> ResolutionResult {
 
 /// Evaluates policies with result caching. If a valid cached result exists
 /// for the given cache key, it is returned immediately.
+///
+/// While any policy has a staged version the cache is bypassed: a scheduled
+/// version can fall due between two calls, and a shadow version has to see
+/// every request to log divergences. Evaluation never activates versions
+/// itself; `apply_due_rollouts` does.
 pub fn evaluate_cached(env: &Env, ctx: &EvalContext, cache_hint: &String) -> ResolutionResult {
+    if pending_rollouts(env) > 0 {
+        return evaluate(env, ctx);
+    }
     let gen = current_cache_generation(env);
     let ck = cache_key(cache_hint);
 
@@ -162,10 +344,33 @@ pub fn evaluate_cached(env: &Env, ctx: &EvalContext, cache_hint: &String) -> Res
 
 // ── Simulation ──────────────────────────────────────────────────────────────
 
+/// Upper bound on the number of trace steps `explain` records.
+pub const MAX_TRACE_STEPS: u32 = 64;
+
 /// Runs a what-if simulation: evaluates all policies against the hypothetical
 /// context without modifying any on-chain state beyond what the evaluation
 /// itself reads. Returns a `SimulationResult`.
+///
+/// Scheduled versions are picked by `ctx.timestamp`, so a future timestamp
+/// previews a rollout. Shadow versions are not evaluated.
 pub fn simulate(env: &Env, ctx: &EvalContext) -> SimulationResult {
+    run_simulation(env, ctx, &mut None)
+}
+
+/// Runs `simulate` and also records an evaluation trace: every rule node
+/// visited, in order, with its result and, for attribute conditions, the
+/// values compared. The trace stops after `MAX_TRACE_STEPS` nodes.
+pub fn explain(env: &Env, ctx: &EvalContext) -> SimulationResult {
+    let mut tracer = Some(Tracer::new(env));
+    let mut result = run_simulation(env, ctx, &mut tracer);
+    if let Some(tracer) = tracer {
+        result.trace = tracer.steps;
+        result.trace_truncated = tracer.truncated;
+    }
+    result
+}
+
+fn run_simulation(env: &Env, ctx: &EvalContext, tracer: &mut Option<Tracer>) -> SimulationResult {
     let ids = list_policies(env);
     let strategy = get_resolution_strategy(env);
 
@@ -173,13 +378,17 @@ pub fn simulate(env: &Env, ctx: &EvalContext) -> SimulationResult {
     let mut eval_count: u32 = 0;
 
     for i in 0..ids.len() {
-        let id = ids.get(i).unwrap();
+        let rollout = get_rollout(env, &ids.get(i).unwrap().name);
+        let id = live_id(ids.get(i).unwrap(), &rollout, ctx.timestamp);
         if let Some(policy) = get_policy(env, &id) {
             if !policy.enabled {
                 continue;
             }
             eval_count += 1;
-            let rule_result = evaluate_rule(env, &policy.rule, ctx);
+            if let Some(t) = tracer.as_mut() {
+                t.policy = Some(policy.id.clone());
+            }
+            let rule_result = evaluate_node(env, &policy.rule, ctx, tracer);
             let effect = if rule_result {
                 policy.effect
             } else {
@@ -194,6 +403,8 @@ pub fn simulate(env: &Env, ctx: &EvalContext) -> SimulationResult {
             verdict: SimulationVerdict::Indeterminate,
             matched_policy: Vec::new(env),
             evaluated_count: eval_count,
+            trace: Vec::new(env),
+            trace_truncated: false,
         };
     }
 
@@ -209,6 +420,78 @@ pub fn simulate(env: &Env, ctx: &EvalContext) -> SimulationResult {
         verdict,
         matched_policy: resolution.winning_policy,
         evaluated_count: eval_count,
+        trace: Vec::new(env),
+        trace_truncated: false,
+    }
+}
+
+/// Collects `TraceStep`s for `explain`. Each node reserves its step when it
+/// is entered, so a truncated trace keeps the outermost nodes.
+struct Tracer {
+    policy: Option<PolicyId>,
+    path: Vec<u32>,
+    steps: Vec<TraceStep>,
+    truncated: bool,
+}
+
+impl Tracer {
+    fn new(env: &Env) -> Self {
+        Tracer {
+            policy: None,
+            path: Vec::new(env),
+            steps: Vec::new(env),
+            truncated: false,
+        }
+    }
+
+    /// Records `rule` with a provisional result and returns the step index,
+    /// or `None` once the trace is full.
+    fn enter(&mut self, env: &Env, rule: &PolicyRule, ctx: &EvalContext) -> Option<u32> {
+        let policy = self.policy.clone()?;
+        if self.steps.len() >= MAX_TRACE_STEPS {
+            self.truncated = true;
+            return None;
+        }
+        let mut step = TraceStep {
+            policy,
+            path: self.path.clone(),
+            kind: rule_kind(rule),
+            result: false,
+            attr_key: String::from_str(env, ""),
+            expected: Vec::new(env),
+            actual: Vec::new(env),
+        };
+        if let PolicyRule::Attribute(cond) = rule {
+            step.attr_key = cond.key.clone();
+            step.expected = cond.values.clone();
+            if let Some(value) = ctx.get_attr(&cond.key) {
+                step.actual.push_back(value);
+            }
+        }
+        self.steps.push_back(step);
+        Some(self.steps.len() - 1)
+    }
+
+    fn finish(&mut self, index: u32, result: bool) {
+        if let Some(mut step) = self.steps.get(index) {
+            step.result = result;
+            self.steps.set(index, step);
+        }
+    }
+}
+
+fn rule_kind(rule: &PolicyRule) -> RuleKind {
+    match rule {
+        PolicyRule::Allow => RuleKind::Allow,
+        PolicyRule::Deny => RuleKind::Deny,
+        PolicyRule::And(_) => RuleKind::And,
+        PolicyRule::Or(_) => RuleKind::Or,
+        PolicyRule::Not(_) => RuleKind::Not,
+        PolicyRule::IfThenElse(_) => RuleKind::IfThenElse,
+        PolicyRule::Unless(_) => RuleKind::Unless,
+        PolicyRule::Attribute(_) => RuleKind::Attribute,
+        PolicyRule::Temporal(_) => RuleKind::Temporal,
+        PolicyRule::DelegationCheck(_) => RuleKind::DelegationCheck,
     }
 }
 
@@ -230,64 +513,208 @@ pub fn is_backward_compatible(old: &PolicyDefinition, new: &PolicyDefinition) ->
     old.effect == new.effect && old.enabled == new.enabled
 }
 
+/// Returns every recorded version of a policy, oldest first.
+pub fn policy_history(env: &Env, name: &String) -> Vec<u32> {
+    env.storage()
+        .persistent()
+        .get(&history_key(name))
+        .unwrap_or(Vec::new(env))
+}
+
+/// Returns the version of a policy that decisions currently use, including
+/// a scheduled version that is due but not yet applied.
+pub fn active_version(env: &Env, name: &String) -> Option<PolicyId> {
+    let id = list_policies(env).iter().find(|id| id.name == *name)?;
+    Some(live_id(
+        id,
+        &get_rollout(env, name),
+        env.ledger().timestamp(),
+    ))
+}
+
+/// Returns the staged rollout for a policy, if any.
+pub fn get_rollout(env: &Env, name: &String) -> Option<PolicyRollout> {
+    env.storage().persistent().get(&rollout_key(name))
+}
+
+/// Stages a new version of an existing policy that replaces the active
+/// version once the ledger reaches `activate_at`. Until then decisions keep
+/// using the active version.
+pub fn schedule_policy(
+    env: &Env,
+    policy: &PolicyDefinition,
+    activate_at: u64,
+) -> Result<PolicyRollout, PolicyVersionError> {
+    if activate_at <= env.ledger().timestamp() {
+        return Err(PolicyVersionError::InvalidActivationTime);
+    }
+    stage(env, policy, RolloutMode::Scheduled, activate_at)
+}
+
+/// Stages a new version of an existing policy in shadow mode: it is
+/// evaluated next to the active version on every `evaluate`, and requests
+/// where the two disagree are recorded in `shadow_divergences`.
+pub fn shadow_policy(
+    env: &Env,
+    policy: &PolicyDefinition,
+) -> Result<PolicyRollout, PolicyVersionError> {
+    stage(env, policy, RolloutMode::Shadow, 0)
+}
+
+fn stage(
+    env: &Env,
+    policy: &PolicyDefinition,
+    mode: RolloutMode,
+    activate_at: u64,
+) -> Result<PolicyRollout, PolicyVersionError> {
+    apply_due_rollouts(env);
+    let replaces =
+        active_version(env, &policy.id.name).ok_or(PolicyVersionError::NoActiveVersion)?;
+    if policy_history(env, &policy.id.name).contains(policy.id.version) {
+        return Err(PolicyVersionError::VersionExists);
+    }
+    let backward_compatible = match get_policy(env, &replaces) {
+        Some(active) => is_backward_compatible(&active, policy),
+        None => false,
+    };
+
+    save_definition(env, policy);
+    let rollout = PolicyRollout {
+        candidate: policy.id.clone(),
+        replaces,
+        mode,
+        activate_at,
+        staged_at: env.ledger().timestamp(),
+        backward_compatible,
+    };
+    put_rollout(env, &rollout);
+    env.storage()
+        .persistent()
+        .remove(&divergence_key(&policy.id.name));
+    Ok(rollout)
+}
+
+/// Activates a policy's staged version now, whatever its rollout mode.
+/// Returns the newly active version.
+pub fn promote_policy(env: &Env, name: &String) -> Result<PolicyId, PolicyVersionError> {
+    let rollout = get_rollout(env, name).ok_or(PolicyVersionError::NoPendingRollout)?;
+    activate(env, &rollout.candidate);
+    Ok(rollout.candidate)
+}
+
+/// Drops a policy's staged version without activating it. The version stays
+/// in the history, so it can still be reached with `rollback_policy`.
+pub fn cancel_rollout(env: &Env, name: &String) -> Result<(), PolicyVersionError> {
+    if get_rollout(env, name).is_none() {
+        return Err(PolicyVersionError::NoPendingRollout);
+    }
+    clear_rollout(env, name);
+    Ok(())
+}
+
+/// Makes a recorded version the active version of its policy and cancels
+/// any staged rollout, in a single step.
+pub fn rollback_policy(env: &Env, name: &String, version: u32) -> Result<(), PolicyVersionError> {
+    let id = PolicyId {
+        name: name.clone(),
+        version,
+    };
+    if !policy_history(env, name).contains(version) || get_policy(env, &id).is_none() {
+        return Err(PolicyVersionError::VersionNotFound);
+    }
+    activate(env, &id);
+    Ok(())
+}
+
+/// Activates every scheduled version whose activation time has passed.
+/// Returns how many were activated.
+pub fn apply_due_rollouts(env: &Env) -> u32 {
+    let now = env.ledger().timestamp();
+    let ids = list_policies(env);
+    let mut activated = 0;
+    for i in 0..ids.len() {
+        if let Some(rollout) = get_rollout(env, &ids.get(i).unwrap().name) {
+            if rollout.is_due(now) {
+                activate(env, &rollout.candidate);
+                activated += 1;
+            }
+        }
+    }
+    activated
+}
+
+/// Returns how many policies currently have a staged version.
+pub fn pending_rollouts(env: &Env) -> u32 {
+    env.storage().persistent().get(&ROLLOUT_COUNT).unwrap_or(0)
+}
+
+/// Returns the logged shadow divergences for a policy, oldest first. The
+/// log is cleared whenever a new version is staged.
+pub fn shadow_divergences(env: &Env, name: &String) -> Vec<ShadowDivergence> {
+    env.storage()
+        .persistent()
+        .get(&divergence_key(name))
+        .unwrap_or(Vec::new(env))
+}
+
 // ── Rule Evaluation (recursive) ─────────────────────────────────────────────
 
 /// Recursively evaluates a `PolicyRule` tree against the given context.
-#[allow(clippy::only_used_in_recursion)]
 pub fn evaluate_rule(env: &Env, rule: &PolicyRule, ctx: &EvalContext) -> bool {
-    match rule {
+    evaluate_node(env, rule, ctx, &mut None)
+}
+
+fn evaluate_node(
+    env: &Env,
+    rule: &PolicyRule,
+    ctx: &EvalContext,
+    tracer: &mut Option<Tracer>,
+) -> bool {
+    let step = tracer.as_mut().and_then(|t| t.enter(env, rule, ctx));
+
+    let result = match rule {
         PolicyRule::Allow => true,
         PolicyRule::Deny => false,
 
         PolicyRule::And(children) => {
+            let mut all = true;
             for i in 0..children.len() {
-                if !evaluate_rule(env, &children.get(i).unwrap(), ctx) {
-                    return false;
+                if !evaluate_child(env, children, i, ctx, tracer) {
+                    all = false;
+                    break;
                 }
             }
-            true
+            all
         }
 
         PolicyRule::Or(children) => {
+            let mut any = false;
             for i in 0..children.len() {
-                if evaluate_rule(env, &children.get(i).unwrap(), ctx) {
-                    return true;
+                if evaluate_child(env, children, i, ctx, tracer) {
+                    any = true;
+                    break;
                 }
             }
-            false
+            any
         }
 
-        PolicyRule::Not(inner) => {
-            if inner.is_empty() {
-                return false;
-            }
-            !evaluate_rule(env, &inner.get(0).unwrap(), ctx)
-        }
+        PolicyRule::Not(inner) => !inner.is_empty() && !evaluate_child(env, inner, 0, ctx, tracer),
 
         PolicyRule::IfThenElse(parts) => {
             if parts.len() < 3 {
-                return false;
-            }
-            let condition = parts.get(0).unwrap();
-            let then_rule = parts.get(1).unwrap();
-            let else_rule = parts.get(2).unwrap();
-            if evaluate_rule(env, &condition, ctx) {
-                evaluate_rule(env, &then_rule, ctx)
+                false
+            } else if evaluate_child(env, parts, 0, ctx, tracer) {
+                evaluate_child(env, parts, 1, ctx, tracer)
             } else {
-                evaluate_rule(env, &else_rule, ctx)
+                evaluate_child(env, parts, 2, ctx, tracer)
             }
         }
 
         PolicyRule::Unless(parts) => {
-            if parts.len() < 2 {
-                return false;
-            }
-            let main_rule = parts.get(0).unwrap();
-            let exception = parts.get(1).unwrap();
-            if evaluate_rule(env, &exception, ctx) {
+            if parts.len() < 2 || evaluate_child(env, parts, 1, ctx, tracer) {
                 false
             } else {
-                evaluate_rule(env, &main_rule, ctx)
+                evaluate_child(env, parts, 0, ctx, tracer)
             }
         }
 
@@ -296,7 +723,30 @@ pub fn evaluate_rule(env: &Env, rule: &PolicyRule, ctx: &EvalContext) -> bool {
         PolicyRule::Temporal(constraint) => constraint.is_satisfied(ctx.timestamp),
 
         PolicyRule::DelegationCheck(chain) => chain.validate(ctx.timestamp),
+    };
+
+    if let (Some(t), Some(index)) = (tracer.as_mut(), step) {
+        t.finish(index, result);
     }
+    result
+}
+
+/// Evaluates `children[index]`, keeping the tracer's path in step.
+fn evaluate_child(
+    env: &Env,
+    children: &Vec<PolicyRule>,
+    index: u32,
+    ctx: &EvalContext,
+    tracer: &mut Option<Tracer>,
+) -> bool {
+    if let Some(t) = tracer.as_mut() {
+        t.path.push_back(index);
+    }
+    let result = evaluate_node(env, &children.get(index).unwrap(), ctx, tracer);
+    if let Some(t) = tracer.as_mut() {
+        t.path.pop_back();
+    }
+    result
 }
 
 // ── Attribute Evaluation ────────────────────────────────────────────────────
@@ -354,6 +804,116 @@ fn evaluate_attribute(cond: &AttributeCondition, ctx: &EvalContext) -> bool {
 
 // ── Internal Utilities ──────────────────────────────────────────────────────
 
+fn save_definition(env: &Env, policy: &PolicyDefinition) {
+    let key = policy_storage_key(&policy.id);
+    env.storage().persistent().set(&key, policy);
+    env.storage()
+        .persistent()
+        .extend_ttl(&key, TTL_THRESHOLD, TTL_EXTEND_TO);
+
+    let mut history = policy_history(env, &policy.id.name);
+    if !history.contains(policy.id.version) {
+        history.push_back(policy.id.version);
+        let key = history_key(&policy.id.name);
+        env.storage().persistent().set(&key, &history);
+        env.storage()
+            .persistent()
+            .extend_ttl(&key, TTL_THRESHOLD, TTL_EXTEND_TO);
+    }
+}
+
+fn activate(env: &Env, id: &PolicyId) {
+    clear_rollout(env, &id.name);
+    update_policy_index(env, id);
+    bump_cache_generation(env);
+}
+
+/// Stages `rollout` for its policy, replacing any staged version.
+fn put_rollout(env: &Env, rollout: &PolicyRollout) {
+    let key = rollout_key(&rollout.candidate.name);
+    if !env.storage().persistent().has(&key) {
+        set_rollout_count(env, pending_rollouts(env).saturating_add(1));
+    }
+    env.storage().persistent().set(&key, rollout);
+    env.storage()
+        .persistent()
+        .extend_ttl(&key, TTL_THRESHOLD, TTL_EXTEND_TO);
+}
+
+/// Drops the staged version of `name`, if any.
+fn clear_rollout(env: &Env, name: &String) {
+    let key = rollout_key(name);
+    if env.storage().persistent().has(&key) {
+        env.storage().persistent().remove(&key);
+        set_rollout_count(env, pending_rollouts(env).saturating_sub(1));
+    }
+}
+
+fn set_rollout_count(env: &Env, count: u32) {
+    env.storage().persistent().set(&ROLLOUT_COUNT, &count);
+    env.storage()
+        .persistent()
+        .extend_ttl(&ROLLOUT_COUNT, TTL_THRESHOLD, TTL_EXTEND_TO);
+}
+
+/// The version to evaluate for an index entry: the scheduled candidate once
+/// it is due, otherwise the active version.
+fn live_id(active: PolicyId, rollout: &Option<PolicyRollout>, now: u64) -> PolicyId {
+    match rollout {
+        Some(rollout) if rollout.is_due(now) => rollout.candidate.clone(),
+        _ => active,
+    }
+}
+
+fn policy_effect(env: &Env, policy: &PolicyDefinition, ctx: &EvalContext) -> PolicyEffect {
+    if evaluate_rule(env, &policy.rule, ctx) {
+        policy.effect
+    } else {
+        invert_effect(policy.effect)
+    }
+}
+
+/// Evaluates a shadow candidate and logs the request if its effect differs
+/// from the active version's. Disabled or missing candidates are skipped.
+fn compare_shadow(
+    env: &Env,
+    active: &PolicyDefinition,
+    active_effect: PolicyEffect,
+    candidate: &PolicyId,
+    ctx: &EvalContext,
+) {
+    let Some(shadow) = get_policy(env, candidate) else {
+        return;
+    };
+    if !shadow.enabled {
+        return;
+    }
+    let candidate_effect = policy_effect(env, &shadow, ctx);
+    if candidate_effect == active_effect {
+        return;
+    }
+
+    let key = divergence_key(&active.id.name);
+    let mut log = shadow_divergences(env, &active.id.name);
+    if log.len() >= MAX_SHADOW_DIVERGENCES {
+        log.pop_front();
+    }
+    log.push_back(ShadowDivergence {
+        active: active.id.clone(),
+        candidate: candidate.clone(),
+        subject: ctx.subject.clone(),
+        resource_id: ctx.resource_id.clone(),
+        action: ctx.action.clone(),
+        timestamp: ctx.timestamp,
+        active_effect,
+        candidate_effect,
+    });
+    env.storage().persistent().set(&key, &log);
+    env.storage()
+        .persistent()
+        .extend_ttl(&key, TTL_THRESHOLD, TTL_EXTEND_TO);
+}
+
 fn invert_effect(effect: PolicyEffect) -> PolicyEffect {
     match effect {
         PolicyEffect::Permit => PolicyEffect::Deny,
@@ -455,8 +1015,16 @@ fn sort_matched_by_priority(env: &Env, matched: &mut Vec<(PolicyDefinition, Poli
 mod tests {
     use super::*;
     use crate::policy_dsl::*;
-    use soroban_sdk::testutils::Address as _;
-    use soroban_sdk::{Address, Env, String, Vec};
+    use soroban_sdk::testutils::{Address as _, Ledger as _};
+    use soroban_sdk::{contract, contractimpl, Address, Env, String, Vec};
+
+    #[contract]
+    struct TestContract;
+
+    #[contractimpl]
+    impl TestContract {
+        pub fn noop(_env: Env) {}
+    }
 
     fn test_ctx(env: &Env) -> EvalContext {
         let subject = Address::generate(env);
@@ -679,7 +1247,7 @@ mod tests {
             PolicyEffect::Permit,
             1,
         );
-        store_policy(&env, &policy);
+        store_policy(&env, &policy).unwrap();
 
         let result = evaluate(&env, &ctx);
         assert_eq!(result.effect, PolicyEffect::Permit);
@@ -691,7 +1259,7 @@ mod tests {
         let ctx = test_ctx(&env);
 
         let policy = make_policy_def(&env, "temp", PolicyRule::Allow, PolicyEffect::Permit, 1);
-        store_policy(&env, &policy);
+        store_policy(&env, &policy).unwrap();
         assert_eq!(list_policies(&env).len(), 1);
 
         remove_policy(&env, &policy.id);
@@ -708,7 +1276,7 @@ mod tests {
         let ctx = test_ctx(&env);
 
         let policy = make_policy_def(&env, "cached", PolicyRule::Allow, PolicyEffect::Permit, 1);
-        store_policy(&env, &policy);
+        store_policy(&env, &policy).unwrap();
 
         let hint = String::from_str(&env, "test_cache_key");
         let r1 = evaluate_cached(&env, &ctx, &hint);
@@ -722,7 +1290,7 @@ mod tests {
         let ctx = test_ctx(&env);
 
         let policy = make_policy_def(&env, "v1", PolicyRule::Allow, PolicyEffect::Permit, 1);
-        store_policy(&env, &policy);
+        store_policy(&env, &policy).unwrap();
 
         let hint = String::from_str(&env, "cache_inv_test");
         let r1 = evaluate_cached(&env, &ctx, &hint);
@@ -731,7 +1299,7 @@ mod tests {
         // Store a new deny policy — bumps generation
         let deny_policy =
             make_policy_def(&env, "v1_deny", PolicyRule::Allow, PolicyEffect::Deny, 0);
-        store_policy(&env, &deny_policy);
+        store_policy(&env, &deny_policy).unwrap();
 
         // Cache is invalidated; re-evaluates
         let r2 = evaluate_cached(&env, &ctx, &hint);
@@ -754,7 +1322,7 @@ mod tests {
         let ctx = test_ctx(&env);
 
         let policy = make_policy_def(&env, "sim_test", PolicyRule::Allow, PolicyEffect::Permit, 1);
-        store_policy(&env, &policy);
+        store_policy(&env, &policy).unwrap();
 
         let sim = simulate(&env, &ctx);
         assert_eq!(sim.verdict, SimulationVerdict::Permitted);
@@ -822,7 +1390,7 @@ mod tests {
             PolicyEffect::Permit,
             1,
         );
-        store_policy(&env, &policy);
+        store_policy(&env, &policy).unwrap();
 
         let result = evaluate(&env, &ctx);
         assert_eq!(result.effect, PolicyEffect::Permit);
@@ -857,14 +1425,14 @@ mod tests {
             PolicyEffect::Permit,
             1,
         );
-        store_policy(&env, &v1);
+        store_policy(&env, &v1).unwrap();
 
         // Upgrade to v2 with same name — replaces in index
         let mut v2 = v1.clone();
         v2.id.version = 2;
         v2.rule = PolicyRule::Deny;
         v2.effect = PolicyEffect::Deny;
-        store_policy(&env, &v2);
+        store_policy(&env, &v2).unwrap();
 
         // Only one policy in index (upgraded)
         assert_eq!(list_policies(&env).len(), 1);
@@ -875,4 +1443,273 @@ mod tests {
         // Let me re-check: evaluate_rule(Deny) = false; since rule_result is false, effect = invert(Deny) = Permit
         assert_eq!(result.effect, PolicyEffect::Permit);
     }
+
+    #[test]
+    fn explain_traces_visited_nodes() {
+        let env = Env::default();
+        let contract_id = env.register(TestContract, ());
+        env.as_contract(&contract_id, || {
+            let ctx = test_ctx(&env);
+
+            let mut nurse = Vec::new(&env);
+            nurse.push_back(String::from_str(&env, "nurse"));
+            let mut children = Vec::new(&env);
+            children.push_back(PolicyRule::Attribute(AttributeCondition {
+                key: String::from_str(&env, "role"),
+                operator: AttrOperator::Eq,
+                values: nurse.clone(),
+            }));
+            children.push_back(PolicyRule::Temporal(TemporalConstraint::unrestricted(&env)));
+            let policy = make_policy_def(
+                &env,
+                "nurses",
+                PolicyRule::And(children),
+                PolicyEffect::Permit,
+                1,
+            );
+            store_policy(&env, &policy).unwrap();
+
+            let plain = simulate(&env, &ctx);
+            assert!(plain.trace.is_empty());
+
+            let explained = explain(&env, &ctx);
+            assert_eq!(explained.verdict, plain.verdict);
+            assert_eq!(explained.verdict, SimulationVerdict::Denied);
+            assert!(!explained.trace_truncated);
+            // The temporal branch is short-circuited and never visited.
+            assert_eq!(explained.trace.len(), 2);
+
+            let root = explained.trace.get(0).unwrap();
+            assert_eq!(root.policy, policy.id);
+            assert_eq!(root.kind, RuleKind::And);
+            assert!(root.path.is_empty());
+            assert!(!root.result);
+
+            let role = explained.trace.get(1).unwrap();
+            assert_eq!(role.kind, RuleKind::Attribute);
+            assert_eq!(role.path.get(0), Some(0));
+            assert!(!role.result);
+            assert_eq!(role.attr_key, String::from_str(&env, "role"));
+            assert_eq!(role.expected, nurse);
+            assert_eq!(
+                role.actual.get(0).unwrap(),
+                String::from_str(&env, "doctor")
+            );
+        });
+    }
+
+    #[test]
+    fn explain_trace_is_bounded() {
+        let env = Env::default();
+        let contract_id = env.register(TestContract, ());
+        env.as_contract(&contract_id, || {
+            let ctx = test_ctx(&env);
+            let mut children = Vec::new(&env);
+            for _ in 0..MAX_TRACE_STEPS + 10 {
+                children.push_back(PolicyRule::Deny);
+            }
+            let policy = make_policy_def(
+                &env,
+                "wide",
+                PolicyRule::Or(children),
+                PolicyEffect::Permit,
+                1,
+            );
+            store_policy(&env, &policy).unwrap();
+
+            let explained = explain(&env, &ctx);
+            assert_eq!(explained.verdict, SimulationVerdict::Denied);
+            assert!(explained.trace_truncated);
+            assert_eq!(explained.trace.len(), MAX_TRACE_STEPS);
+            // The root reserved its step first, so it still carries the result.
+            assert_eq!(explained.trace.get(0).unwrap().kind, RuleKind::Or);
+        });
+    }
+
+    #[test]
+    fn scheduled_rollout_and_rollback() {
+        let env = Env::default();
+        let contract_id = env.register(TestContract, ());
+        env.as_contract(&contract_id, || {
+            env.ledger().set_timestamp(1_000);
+            let name = String::from_str(&env, "rollout");
+            let v1 = make_policy_def(&env, "rollout", PolicyRule::Allow, PolicyEffect::Permit, 1);
+            store_policy(&env, &v1).unwrap();
+
+            let mut v2 = v1.clone();
+            v2.id.version = 2;
+            v2.rule = PolicyRule::Deny;
+            assert_eq!(
+                schedule_policy(&env, &v2, 1_000),
+                Err(PolicyVersionError::InvalidActivationTime)
+            );
+            let rollout = schedule_policy(&env, &v2, 2_000).unwrap();
+            assert_eq!(rollout.replaces, v1.id);
+            assert!(rollout.backward_compatible);
+            assert_eq!(
+                schedule_policy(&env, &v2, 3_000),
+                Err(PolicyVersionError::VersionExists)
+            );
+
+            let mut ctx = test_ctx(&env);
+            ctx.timestamp = 1_500;
+            assert_eq!(evaluate(&env, &ctx).effect, PolicyEffect::Permit);
+            // A future timestamp previews the scheduled version.
+            ctx.timestamp = 2_000;
+            assert_eq!(simulate(&env, &ctx).verdict, SimulationVerdict::Denied);
+            assert_eq!(list_policies(&env).get(0).unwrap(), v1.id);
+
+            env.ledger().set_timestamp(2_000);
+            assert_eq!(active_version(&env, &name), Some(v2.id.clone()));
+            assert_eq!(apply_due_rollouts(&env), 1);
+            assert_eq!(list_policies(&env).get(0).unwrap(), v2.id);
+            assert!(get_rollout(&env, &name).is_none());
+
+            rollback_policy(&env, &name, 1).unwrap();
+            assert_eq!(list_policies(&env).get(0).unwrap(), v1.id);
+            assert_eq!(evaluate(&env, &ctx).effect, PolicyEffect::Permit);
+            assert_eq!(
+                rollback_policy(&env, &name, 9),
+                Err(PolicyVersionError::VersionNotFound)
+            );
+            assert_eq!(policy_history(&env, &name).len(), 2);
+        });
+    }
+
+    #[test]
+    fn shadow_rollout_logs_divergences() {
+        let env = Env::default();
+        let contract_id = env.register(TestContract, ());
+        env.as_contract(&contract_id, || {
+            let name = String::from_str(&env, "shadowed");
+            let ctx = test_ctx(&env);
+            let v1 = make_policy_def(&env, "shadowed", PolicyRule::Allow, PolicyEffect::Permit, 1);
+            let mut v2 = v1.clone();
+            v2.id.version = 2;
+            v2.rule = PolicyRule::Deny;
+            assert_eq!(
+                shadow_policy(&env, &v2),
+                Err(PolicyVersionError::NoActiveVersion)
+            );
+
+            store_policy(&env, &v1).unwrap();
+            shadow_policy(&env, &v2).unwrap();
+
+            // Decisions still follow v1; the disagreement is logged.
+            assert_eq!(evaluate(&env, &ctx).effect, PolicyEffect::Permit);
+            let log = shadow_divergences(&env, &name);
+            assert_eq!(log.len(), 1);
+            let divergence = log.get(0).unwrap();
+            assert_eq!(divergence.active, v1.id);
+            assert_eq!(divergence.candidate, v2.id);
+            assert_eq!(divergence.candidate_effect, PolicyEffect::Deny);
+
+            assert_eq!(promote_policy(&env, &name), Ok(v2.id.clone()));
+            assert_eq!(evaluate(&env, &ctx).effect, PolicyEffect::Deny);
+            assert_eq!(shadow_divergences(&env, &name).len(), 1);
+            assert_eq!(
+                cancel_rollout(&env, &name),
+                Err(PolicyVersionError::NoPendingRollout)
+            );
+        });
+    }
+
+    #[test]
+    fn recorded_versions_cannot_be_stored_again() {
+        let env = Env::default();
+        let contract_id = env.register(TestContract, ());
+        env.as_contract(&contract_id, || {
+            let name = String::from_str(&env, "immutable");
+            let v1 = make_policy_def(
+                &env,
+                "immutable",
+                PolicyRule::Allow,
+                PolicyEffect::Permit,
+                1,
+            );
+            store_policy(&env, &v1).unwrap();
+
+            let mut rewritten = v1.clone();
+            rewritten.rule = PolicyRule::Deny;
+            assert_eq!(
+                store_policy(&env, &rewritten),
+                Err(PolicyVersionError::VersionExists)
+            );
+            assert_eq!(
+                store_policy_checked(&env, &rewritten),
+                Err(StorePolicyError::Version(PolicyVersionError::VersionExists))
+            );
+            assert_eq!(get_policy(&env, &v1.id), Some(v1.clone()));
+
+            // A staged version is in the history too.
+            let mut v2 = v1.clone();
+            v2.id.version = 2;
+            shadow_policy(&env, &v2).unwrap();
+            assert_eq!(
+                store_policy(&env, &v2),
+                Err(PolicyVersionError::VersionExists)
+            );
+            assert_eq!(policy_history(&env, &name).len(), 2);
+        });
+    }
+
+    #[test]
+    fn cached_evaluation_bypasses_cache_during_rollouts() {
+        let env = Env::default();
+        let contract_id = env.register(TestContract, ());
+        env.as_contract(&contract_id, || {
+            env.ledger().set_timestamp(1_000);
+            let name = String::from_str(&env, "cached");
+            let hint = String::from_str(&env, "hint");
+            let mut ctx = test_ctx(&env);
+            ctx.timestamp = 1_000;
+            let v1 = make_policy_def(&env, "cached", PolicyRule::Allow, PolicyEffect::Permit, 1);
+            store_policy(&env, &v1).unwrap();
+            assert_eq!(
+                evaluate_cached(&env, &ctx, &hint).effect,
+                PolicyEffect::Permit
+            );
+
+            // Every cached call still reaches the shadow version.
+            let mut v2 = v1.clone();
+            v2.id.version = 2;
+            v2.rule = PolicyRule::Deny;
+            shadow_policy(&env, &v2).unwrap();
+            assert_eq!(pending_rollouts(&env), 1);
+            assert_eq!(
+                evaluate_cached(&env, &ctx, &hint).effect,
+                PolicyEffect::Permit
+            );
+            assert_eq!(
+                evaluate_cached(&env, &ctx, &hint).effect,
+                PolicyEffect::Permit
+            );
+            assert_eq!(shadow_divergences(&env, &name).len(), 2);
+
+            // A due scheduled version is used, but not activated.
+            let mut v3 = v2.clone();
+            v3.id.version = 3;
+            schedule_policy(&env, &v3, 2_000).unwrap();
+            assert_eq!(pending_rollouts(&env), 1);
+            assert_eq!(
+                evaluate_cached(&env, &ctx, &hint).effect,
+                PolicyEffect::Permit
+            );
+            env.ledger().set_timestamp(2_000);
+            ctx.timestamp = 2_000;
+            assert_eq!(
+                evaluate_cached(&env, &ctx, &hint).effect,
+                PolicyEffect::Deny
+            );
+            assert_eq!(list_policies(&env).get(0).unwrap(), v1.id);
+            assert!(get_rollout(&env, &name).is_some());
+
+            assert_eq!(apply_due_rollouts(&env), 1);
+            assert_eq!(pending_rollouts(&env), 0);
+            assert_eq!(
+                evaluate_cached(&env, &ctx, &hint).effect,
+                PolicyEffect::Deny
+            );
+        });
+    }
 }
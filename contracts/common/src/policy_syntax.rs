@@ -0,0 +1,1297 @@
+//! Text syntax for policy definitions (requires `std` feature).
+//!
+//! Compliance staff author policies as text rather than nested
+//! [`PolicyRule`] vectors. [`compile`] parses the text, reporting errors by
+//! line and column, into a [`PolicyDefinition`]; [`pretty_print`] turns a
+//! stored definition back into text that compiles to the same definition.
+//!
+//! ```text
+//! # Comments run to the end of the line.
+//! policy "clinic_hours" version 2
+//! description "Optometrists in clinic hours, except research staff"
+//! priority 10
+//! permit if role == "optometrist" and time in 08:00..18:00 unless attr.dept == "research"
+//! ```
+//!
+//! `version` defaults to 1, `priority` to [`DEFAULT_PRIORITY`], and a policy
+//! is enabled unless marked `disabled`. A bare `permit` always applies.
+//!
+//! Expressions, loosest binding first: `a unless b`, `a or b`, `a and b`,
+//! `not a`, then parentheses and conditions:
+//!
+//! - `key == "v"`, `!=`, `>=`, `<=`, `key in ["a", "b"]`, `key not in [..]`.
+//!   Keys are dotted identifiers; `attr.key` and `attr["any key"]` reach keys
+//!   that clash with keywords or contain other characters.
+//! - `time in 08:00..18:00 on mon..fri from <unix> until <unix>`, any
+//!   non-empty subset of the parts. Hours are whole and the end is exclusive;
+//!   `22:00..06:00` wraps past midnight.
+//! - `delegation ["G..." -> "G..." ["read"] until <unix>, ...]`.
+//! - `true`, `false` and `if c then a else b`.
+//!
+//! Nodes whose arity the infix forms cannot express are written as calls:
+//! `all(..)`, `any(..)`, `not(..)`, `cond(..)` and `unless(..)`.
+
+use core::fmt;
+use std::string::String as StdString;
+use std::vec::Vec as StdVec;
+
+use soroban_sdk::{Address, Env, String, Vec};
+use stellar_strkey::Strkey;
+
+use crate::policy_dsl::{
+    AttrOperator, AttributeCondition, DelegationChain, DelegationLink, PolicyDefinition,
+    PolicyEffect, PolicyId, PolicyRule, TemporalConstraint,
+};
+
+/// Priority given to policies that do not set one.
+pub const DEFAULT_PRIORITY: u32 = 100;
+
+const DAYS: [&str; 7] = ["sun", "mon", "tue", "wed", "thu", "fri", "sat"];
+
+/// Words that cannot start a bare attribute key.
+const RESERVED: [&str; 26] = [
+    "all",
+    "and",
+    "any",
+    "attr",
+    "cond",
+    "delegation",
+    "deny",
+    "description",
+    "disabled",
+    "else",
+    "false",
+    "from",
+    "if",
+    "in",
+    "not",
+    "on",
+    "or",
+    "permit",
+    "policy",
+    "priority",
+    "then",
+    "time",
+    "true",
+    "unless",
+    "until",
+    "version",
+];
+
+// ── Errors ──────────────────────────────────────────────────────────────────
+
+/// A syntax or validation error, positioned at the offending token.
+/// Lines and columns start at 1; columns count characters.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SyntaxError {
+    pub line: u32,
+    pub column: u32,
+    pub message: StdString,
+}
+
+impl fmt::Display for SyntaxError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}: {}", self.line, self.column, self.message)
+    }
+}
+
+impl std::error::Error for SyntaxError {}
+
+// ── Compilation ─────────────────────────────────────────────────────────────
+
+/// Compiles a complete policy.
+pub fn compile(env: &Env, source: &str) -> Result<PolicyDefinition, SyntaxError> {
+    let mut parser = Parser::new(env, source)?;
+    let policy = parser.policy()?;
+    parser.expect_end()?;
+    Ok(policy)
+}
+
+/// Compiles a bare rule expression, e.g. `role == "optometrist" and not locum == "yes"`.
+pub fn compile_rule(env: &Env, source: &str) -> Result<PolicyRule, SyntaxError> {
+    let mut parser = Parser::new(env, source)?;
+    let rule = parser.expr()?;
+    parser.expect_end()?;
+    Ok(rule)
+}
+
+// ── Lexer ───────────────────────────────────────────────────────────────────
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Tok {
+    Ident(StdString),
+    Str(StdString),
+    Int(u64),
+    /// `HH:MM`
+    Time(u64, u64),
+    Op(&'static str),
+    Eof,
+}
+
+impl Tok {
+    fn describe(&self) -> StdString {
+        match self {
+            Tok::Ident(word) => format!("'{}'", word),
+            Tok::Str(_) => StdString::from("a string"),
+            Tok::Int(_) => StdString::from("a number"),
+            Tok::Time(..) => StdString::from("a time"),
+            Tok::Op(op) => format!("'{}'", op),
+            Tok::Eof => StdString::from("end of input"),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+struct Token {
+    tok: Tok,
+    line: u32,
+    column: u32,
+}
+
+const OPERATORS: [&str; 12] = [
+    "==", "!=", ">=", "<=", "..", "->", "(", ")", "[", "]", ",", ".",
+];
+
+struct Lexer {
+    chars: StdVec<char>,
+    pos: usize,
+    line: u32,
+    column: u32,
+}
+
+impl Lexer {
+    fn peek(&self, offset: usize) -> Option<char> {
+        self.chars.get(self.pos + offset).copied()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek(0)?;
+        self.pos += 1;
+        if c == '\n' {
+            self.line += 1;
+            self.column = 1;
+        } else {
+            self.column += 1;
+        }
+        Some(c)
+    }
+
+    fn error(&self, line: u32, column: u32, message: StdString) -> SyntaxError {
+        SyntaxError {
+            line,
+            column,
+            message,
+        }
+    }
+
+    fn number(&mut self, line: u32, column: u32) -> Result<u64, SyntaxError> {
+        let mut value: u64 = 0;
+        while let Some(digit) = self.peek(0).and_then(|c| c.to_digit(10)) {
+            self.bump();
+            value = value
+                .checked_mul(10)
+                .and_then(|v| v.checked_add(u64::from(digit)))
+                .ok_or_else(|| self.error(line, column, StdString::from("number is too large")))?;
+        }
+        Ok(value)
+    }
+
+    fn string(&mut self, line: u32, column: u32) -> Result<StdString, SyntaxError> {
+        self.bump();
+        let mut text = StdString::new();
+        loop {
+            let c = self
+                .bump()
+                .ok_or_else(|| self.error(line, column, StdString::from("unterminated string")))?;
+            match c {
+                '"' => return Ok(text),
+                '\\' => {
+                    let (esc_line, esc_column) = (self.line, self.column);
+                    match self.bump() {
+                        Some('"') => text.push('"'),
+                        Some('\\') => text.push('\\'),
+                        Some('n') => text.push('\n'),
+                        Some('t') => text.push('\t'),
+                        _ => {
+                            return Err(self.error(
+                                esc_line,
+                                esc_column.saturating_sub(1),
+                                StdString::from("unknown escape; use \\\", \\\\, \\n or \\t"),
+                            ))
+                        }
+                    }
+                }
+                c => text.push(c),
+            }
+        }
+    }
+
+    fn tokens(mut self) -> Result<StdVec<Token>, SyntaxError> {
+        let mut tokens = StdVec::new();
+        while let Some(c) = self.peek(0) {
+            let (line, column) = (self.line, self.column);
+            let tok = if c.is_whitespace() {
+                self.bump();
+                continue;
+            } else if c == '#' {
+                while self.peek(0).is_some_and(|c| c != '\n') {
+                    self.bump();
+                }
+                continue;
+            } else if c.is_ascii_alphabetic() || c == '_' {
+                let mut word = StdString::new();
+                while let Some(c) = self
+                    .peek(0)
+                    .filter(|c| c.is_ascii_alphanumeric() || *c == '_')
+                {
+                    word.push(c);
+                    self.bump();
+                }
+                Tok::Ident(word)
+            } else if c.is_ascii_digit() {
+                let value = self.number(line, column)?;
+                if self.peek(0) == Some(':') {
+                    self.bump();
+                    if !self.peek(0).is_some_and(|c| c.is_ascii_digit()) {
+                        return Err(self.error(
+                            line,
+                            column,
+                            StdString::from("expected a time such as 08:00"),
+                        ));
+                    }
+                    Tok::Time(value, self.number(line, column)?)
+                } else {
+                    Tok::Int(value)
+                }
+            } else if c == '"' {
+                Tok::Str(self.string(line, column)?)
+            } else {
+                let op = OPERATORS
+                    .iter()
+                    .find(|op| {
+                        op.chars()
+                            .enumerate()
+                            .all(|(i, expected)| self.peek(i) == Some(expected))
+                    })
+                    .ok_or_else(|| {
+                        self.error(line, column, format!("unexpected character '{}'", c))
+                    })?;
+                for _ in 0..op.len() {
+                    self.bump();
+                }
+                Tok::Op(op)
+            };
+            tokens.push(Token { tok, line, column });
+        }
+        tokens.push(Token {
+            tok: Tok::Eof,
+            line: self.line,
+            column: self.column,
+        });
+        Ok(tokens)
+    }
+}
+
+// ── Parser ──────────────────────────────────────────────────────────────────
+
+struct Parser<'a> {
+    env: &'a Env,
+    tokens: StdVec<Token>,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn new(env: &'a Env, source: &str) -> Result<Self, SyntaxError> {
+        let lexer = Lexer {
+            chars: source.chars().collect(),
+            pos: 0,
+            line: 1,
+            column: 1,
+        };
+        Ok(Self {
+            env,
+            tokens: lexer.tokens()?,
+            pos: 0,
+        })
+    }
+
+    // ── Token helpers ──
+
+    /// The current token; the trailing `Eof` is never consumed.
+    fn peek(&self) -> &Token {
+        &self.tokens[self.pos]
+    }
+
+    fn peek_second(&self) -> &Tok {
+        self.tokens
+            .get(self.pos + 1)
+            .map_or(&Tok::Eof, |token| &token.tok)
+    }
+
+    fn next(&mut self) -> Token {
+        let token = self.tokens[self.pos].clone();
+        if self.pos + 1 < self.tokens.len() {
+            self.pos += 1;
+        }
+        token
+    }
+
+    fn error_at<T>(token: &Token, message: StdString) -> Result<T, SyntaxError> {
+        Err(SyntaxError {
+            line: token.line,
+            column: token.column,
+            message,
+        })
+    }
+
+    fn unexpected<T>(token: &Token, expected: &str) -> Result<T, SyntaxError> {
+        Self::error_at(
+            token,
+            format!("expected {}, found {}", expected, token.tok.describe()),
+        )
+    }
+
+    fn is_keyword(&self, keyword: &str) -> bool {
+        matches!(&self.peek().tok, Tok::Ident(word) if word == keyword)
+    }
+
+    fn eat_keyword(&mut self, keyword: &str) -> bool {
+        let found = self.is_keyword(keyword);
+        if found {
+            self.next();
+        }
+        found
+    }
+
+    fn expect_keyword(&mut self, keyword: &str) -> Result<(), SyntaxError> {
+        if self.eat_keyword(keyword) {
+            return Ok(());
+        }
+        Self::unexpected(self.peek(), &format!("'{}'", keyword))
+    }
+
+    fn eat_op(&mut self, op: &'static str) -> bool {
+        let found = self.peek().tok == Tok::Op(op);
+        if found {
+            self.next();
+        }
+        found
+    }
+
+    fn expect_op(&mut self, op: &'static str) -> Result<(), SyntaxError> {
+        if self.eat_op(op) {
+            return Ok(());
+        }
+        Self::unexpected(self.peek(), &format!("'{}'", op))
+    }
+
+    fn expect_end(&mut self) -> Result<(), SyntaxError> {
+        if self.peek().tok == Tok::Eof {
+            return Ok(());
+        }
+        Self::unexpected(self.peek(), "end of input")
+    }
+
+    fn expect_string(&mut self, what: &str) -> Result<StdString, SyntaxError> {
+        let token = self.next();
+        match token.tok {
+            Tok::Str(text) => Ok(text),
+            _ => Self::unexpected(&token, what),
+        }
+    }
+
+    fn expect_int(&mut self, what: &str) -> Result<u64, SyntaxError> {
+        let token = self.next();
+        match token.tok {
+            Tok::Int(value) => Ok(value),
+            _ => Self::unexpected(&token, what),
+        }
+    }
+
+    fn expect_u32(&mut self, what: &str) -> Result<u32, SyntaxError> {
+        let token = self.peek().clone();
+        let value = self.expect_int(what)?;
+        u32::try_from(value).or_else(|_| Self::error_at(&token, format!("{} is too large", what)))
+    }
+
+    fn text(&self, text: &str) -> String {
+        String::from_str(self.env, text)
+    }
+
+    fn rules<const N: usize>(&self, rules: [PolicyRule; N]) -> Vec<PolicyRule> {
+        Vec::from_array(self.env, rules)
+    }
+
+    // ── Policies ──
+
+    fn policy(&mut self) -> Result<PolicyDefinition, SyntaxError> {
+        self.expect_keyword("policy")?;
+        let name_token = self.next();
+        let name = match name_token.tok {
+            Tok::Str(name) | Tok::Ident(name) => name,
+            _ => return Self::unexpected(&name_token, "a policy name"),
+        };
+
+        let mut version = None;
+        let mut description = None;
+        let mut priority = None;
+        let mut enabled = true;
+        let effect = loop {
+            let token = self.next();
+            let word = match &token.tok {
+                Tok::Ident(word) => word.as_str(),
+                _ => return Self::unexpected(&token, "'permit' or 'deny'"),
+            };
+            let duplicate = match word {
+                "permit" => break PolicyEffect::Permit,
+                "deny" => break PolicyEffect::Deny,
+                "version" => version.replace(self.expect_u32("a version")?).is_some(),
+                "description" => description
+                    .replace(self.expect_string("a description")?)
+                    .is_some(),
+                "priority" => priority.replace(self.expect_u32("a priority")?).is_some(),
+                "disabled" => !core::mem::replace(&mut enabled, false),
+                _ => return Self::unexpected(&token, "'permit' or 'deny'"),
+            };
+            if duplicate {
+                return Self::error_at(&token, format!("'{}' is given more than once", word));
+            }
+        };
+
+        let mut rule = if self.eat_keyword("if") {
+            self.expr()?
+        } else {
+            PolicyRule::Allow
+        };
+        while self.eat_keyword("unless") {
+            let exception = self.or()?;
+            rule = PolicyRule::Unless(self.rules([rule, exception]));
+        }
+
+        Ok(PolicyDefinition {
+            id: PolicyId {
+                name: self.text(&name),
+                version: version.unwrap_or(1),
+            },
+            description: self.text(&description.unwrap_or_default()),
+            rule,
+            effect,
+            priority: priority.unwrap_or(DEFAULT_PRIORITY),
+            enabled,
+        })
+    }
+
+    // ── Expressions ──
+
+    fn expr(&mut self) -> Result<PolicyRule, SyntaxError> {
+        let mut rule = self.or()?;
+        while self.eat_keyword("unless") {
+            let exception = self.or()?;
+            rule = PolicyRule::Unless(self.rules([rule, exception]));
+        }
+        Ok(rule)
+    }
+
+    fn or(&mut self) -> Result<PolicyRule, SyntaxError> {
+        let first = self.and()?;
+        if !self.is_keyword("or") {
+            return Ok(first);
+        }
+        let mut children = self.rules([first]);
+        while self.eat_keyword("or") {
+            children.push_back(self.and()?);
+        }
+        Ok(PolicyRule::Or(children))
+    }
+
+    fn and(&mut self) -> Result<PolicyRule, SyntaxError> {
+        let first = self.unary()?;
+        if !self.is_keyword("and") {
+            return Ok(first);
+        }
+        let mut children = self.rules([first]);
+        while self.eat_keyword("and") {
+            children.push_back(self.unary()?);
+        }
+        Ok(PolicyRule::And(children))
+    }
+
+    fn unary(&mut self) -> Result<PolicyRule, SyntaxError> {
+        if self.is_keyword("not") && *self.peek_second() != Tok::Op("(") {
+            self.next();
+            let inner = self.unary()?;
+            return Ok(PolicyRule::Not(self.rules([inner])));
+        }
+        self.primary()
+    }
+
+    fn primary(&mut self) -> Result<PolicyRule, SyntaxError> {
+        let token = self.peek().clone();
+        let word = match &token.tok {
+            Tok::Op("(") => {
+                self.next();
+                let rule = self.expr()?;
+                self.expect_op(")")?;
+                return Ok(rule);
+            }
+            Tok::Ident(word) => word.as_str(),
+            _ => return Self::unexpected(&token, "a condition"),
+        };
+        let is_call = *self.peek_second() == Tok::Op("(");
+        match word {
+            "true" => {
+                self.next();
+                Ok(PolicyRule::Allow)
+            }
+            "false" => {
+                self.next();
+                Ok(PolicyRule::Deny)
+            }
+            "if" => {
+                self.next();
+                let condition = self.expr()?;
+                self.expect_keyword("then")?;
+                let then_rule = self.expr()?;
+                self.expect_keyword("else")?;
+                let else_rule = self.expr()?;
+                Ok(PolicyRule::IfThenElse(
+                    self.rules([condition, then_rule, else_rule]),
+                ))
+            }
+            "time" => {
+                self.next();
+                self.temporal(&token)
+            }
+            "delegation" => {
+                self.next();
+                self.delegation()
+            }
+            "all" | "any" | "not" | "cond" | "unless" if is_call => {
+                self.next();
+                let args = self.call_args()?;
+                Ok(match word {
+                    "all" => PolicyRule::And(args),
+                    "any" => PolicyRule::Or(args),
+                    "not" => PolicyRule::Not(args),
+                    "cond" => PolicyRule::IfThenElse(args),
+                    _ => PolicyRule::Unless(args),
+                })
+            }
+            _ => self.attribute(),
+        }
+    }
+
+    fn call_args(&mut self) -> Result<Vec<PolicyRule>, SyntaxError> {
+        self.expect_op("(")?;
+        let mut args = Vec::new(self.env);
+        if self.eat_op(")") {
+            return Ok(args);
+        }
+        loop {
+            args.push_back(self.expr()?);
+            if self.eat_op(")") {
+                return Ok(args);
+            }
+            self.expect_op(",")?;
+        }
+    }
+
+    // ── Attribute conditions ──
+
+    fn attribute(&mut self) -> Result<PolicyRule, SyntaxError> {
+        let key = self.attribute_key()?;
+        let token = self.next();
+        let (operator, values) = match &token.tok {
+            Tok::Op("==") => (AttrOperator::Eq, self.values()?),
+            Tok::Op("!=") => (AttrOperator::NotEq, self.values()?),
+            Tok::Op(">=") => (AttrOperator::Gte, self.values()?),
+            Tok::Op("<=") => (AttrOperator::Lte, self.values()?),
+            Tok::Ident(word) if word == "in" => (AttrOperator::In, self.list()?),
+            Tok::Ident(word) if word == "not" => {
+                self.expect_keyword("in")?;
+                (AttrOperator::NotIn, self.list()?)
+            }
+            _ => {
+                return Self::unexpected(
+                    &token,
+                    "a comparison ('==', '!=', '>=', '<=', 'in' or 'not in')",
+                )
+            }
+        };
+        Ok(PolicyRule::Attribute(AttributeCondition {
+            key: self.text(&key),
+            operator,
+            values,
+        }))
+    }
+
+    fn attribute_key(&mut self) -> Result<StdString, SyntaxError> {
+        let token = self.next();
+        let first = match token.tok {
+            Tok::Ident(ref word) if word == "attr" => {
+                if self.eat_op("[") {
+                    let key = self.expect_string("an attribute key")?;
+                    self.expect_op("]")?;
+                    return Ok(key);
+                }
+                self.expect_op(".")?;
+                self.identifier("an attribute key")?
+            }
+            Tok::Ident(ref word) if !RESERVED.contains(&word.as_str()) => word.clone(),
+            _ => return Self::unexpected(&token, "a condition"),
+        };
+        let mut key = first;
+        while self.eat_op(".") {
+            key.push('.');
+            key.push_str(&self.identifier("an attribute key")?);
+        }
+        Ok(key)
+    }
+
+    fn identifier(&mut self, what: &str) -> Result<StdString, SyntaxError> {
+        let token = self.next();
+        match token.tok {
+            Tok::Ident(word) => Ok(word),
+            _ => Self::unexpected(&token, what),
+        }
+    }
+
+    fn value(&mut self) -> Result<String, SyntaxError> {
+        let token = self.next();
+        match token.tok {
+            Tok::Str(text) => Ok(self.text(&text)),
+            Tok::Int(value) => Ok(self.text(&value.to_string())),
+            _ => Self::unexpected(&token, "a string"),
+        }
+    }
+
+    /// A single value or a list.
+    fn values(&mut self) -> Result<Vec<String>, SyntaxError> {
+        if self.peek().tok == Tok::Op("[") {
+            return self.list();
+        }
+        let value = self.value()?;
+        Ok(Vec::from_array(self.env, [value]))
+    }
+
+    fn list(&mut self) -> Result<Vec<String>, SyntaxError> {
+        self.expect_op("[")?;
+        let mut values = Vec::new(self.env);
+        if self.eat_op("]") {
+            return Ok(values);
+        }
+        loop {
+            values.push_back(self.value()?);
+            if self.eat_op("]") {
+                return Ok(values);
+            }
+            self.expect_op(",")?;
+        }
+    }
+
+    // ── Temporal constraints ──
+
+    fn temporal(&mut self, start: &Token) -> Result<PolicyRule, SyntaxError> {
+        let mut constraint = TemporalConstraint {
+            valid_from: 0,
+            valid_until: 0,
+            allowed_hour_start: 0,
+            allowed_hour_end: 23,
+            allowed_days_mask: 0,
+        };
+        let mut seen: StdVec<&str> = StdVec::new();
+        loop {
+            let token = self.peek().clone();
+            let part = match &token.tok {
+                Tok::Ident(word) => match word.as_str() {
+                    "in" => "in",
+                    "on" => "on",
+                    "from" => "from",
+                    "until" => "until",
+                    _ => break,
+                },
+                _ => break,
+            };
+            if seen.contains(&part) {
+                return Self::error_at(&token, format!("'time {}' is given more than once", part));
+            }
+            seen.push(part);
+            self.next();
+            match part {
+                "in" => {
+                    let (from_hour, to_hour) = self.hour_range()?;
+                    constraint.allowed_hour_start = from_hour;
+                    constraint.allowed_hour_end = to_hour;
+                }
+                "on" => constraint.allowed_days_mask = self.days()?,
+                "from" => constraint.valid_from = self.expect_int("a unix timestamp")?,
+                _ => constraint.valid_until = self.expect_int("a unix timestamp")?,
+            }
+        }
+        if seen.is_empty() {
+            return Self::error_at(
+                start,
+                StdString::from("'time' needs at least one of 'in', 'on', 'from' or 'until'"),
+            );
+        }
+        Ok(PolicyRule::Temporal(constraint))
+    }
+
+    fn hour(&mut self, latest: u64) -> Result<(Token, u64), SyntaxError> {
+        let token = self.next();
+        let (hour, minute) = match token.tok {
+            Tok::Time(hour, minute) => (hour, minute),
+            _ => return Self::unexpected(&token, "a time such as 08:00"),
+        };
+        if minute != 0 {
+            return Self::error_at(
+                &token,
+                StdString::from("policies use whole hours; minutes must be 00"),
+            );
+        }
+        if hour > latest {
+            return Self::error_at(&token, format!("hour must be between 00 and {}", latest));
+        }
+        Ok((token, hour))
+    }
+
+    /// `HH:00..HH:00` with an exclusive end, as inclusive start and end hours.
+    fn hour_range(&mut self) -> Result<(u32, u32), SyntaxError> {
+        let (_, start) = self.hour(23)?;
+        self.expect_op("..")?;
+        let (end_token, end) = self.hour(24)?;
+        if end % 24 == start {
+            return Self::error_at(&end_token, StdString::from("time range is empty"));
+        }
+        // Both are at most 24, so the casts are lossless.
+        Ok((start as u32, ((end + 23) % 24) as u32))
+    }
+
+    fn day(&mut self) -> Result<u32, SyntaxError> {
+        let token = self.next();
+        if let Tok::Ident(word) = &token.tok {
+            if let Some(day) = DAYS.iter().position(|d| d == word) {
+                return Ok(day as u32);
+            }
+        }
+        Self::unexpected(&token, "a day (sun, mon, tue, wed, thu, fri or sat)")
+    }
+
+    /// Comma-separated days or `day..day` ranges (which may wrap past
+    /// Saturday), or a raw bitmask.
+    fn days(&mut self) -> Result<u32, SyntaxError> {
+        let mut mask = 0u32;
+        loop {
+            let token = self.peek().clone();
+            if let Tok::Int(raw) = token.tok {
+                self.next();
+                mask |= u32::try_from(raw).or_else(|_| {
+                    Self::error_at(&token, StdString::from("day mask is too large"))
+                })?;
+            } else {
+                let first = self.day()?;
+                let last = if self.eat_op("..") {
+                    self.day()?
+                } else {
+                    first
+                };
+                let mut day = first;
+                loop {
+                    mask |= 1 << day;
+                    if day == last {
+                        break;
+                    }
+                    day = (day + 1) % 7;
+                }
+            }
+            if !self.eat_op(",") {
+                break;
+            }
+        }
+        Ok(mask)
+    }
+
+    // ── Delegation chains ──
+
+    fn delegation(&mut self) -> Result<PolicyRule, SyntaxError> {
+        self.expect_op("[")?;
+        let mut links = Vec::new(self.env);
+        if !self.eat_op("]") {
+            loop {
+                links.push_back(self.delegation_link()?);
+                if self.eat_op("]") {
+                    break;
+                }
+                self.expect_op(",")?;
+            }
+        }
+        Ok(PolicyRule::DelegationCheck(DelegationChain { links }))
+    }
+
+    fn delegation_link(&mut self) -> Result<DelegationLink, SyntaxError> {
+        let delegator = self.address()?;
+        self.expect_op("->")?;
+        let delegatee = self.address()?;
+        let scoped_permissions = self.list()?;
+        let expires_at = if self.eat_keyword("until") {
+            self.expect_int("a unix timestamp")?
+        } else {
+            0
+        };
+        Ok(DelegationLink {
+            delegator,
+            delegatee,
+            scoped_permissions,
+            expires_at,
+        })
+    }
+
+    fn address(&mut self) -> Result<Address, SyntaxError> {
+        let token = self.peek().clone();
+        let strkey = self.expect_string("an address")?;
+        match Strkey::from_string(&strkey) {
+            Ok(Strkey::PublicKeyEd25519(_)) | Ok(Strkey::Contract(_)) => {
+                Ok(Address::from_str(self.env, &strkey))
+            }
+            _ => Self::error_at(&token, format!("'{}' is not a Stellar address", strkey)),
+        }
+    }
+}
+
+// ── Pretty printing ─────────────────────────────────────────────────────────
+
+// Binding strength, loosest first; a child binding looser than its slot
+// needs parentheses.
+const UNLESS: u8 = 1;
+const OR: u8 = 2;
+const AND: u8 = 3;
+const NOT: u8 = 4;
+const ATOM: u8 = 5;
+
+/// Prints a policy in the canonical layout accepted by [`compile`].
+pub fn pretty_print(policy: &PolicyDefinition) -> StdString {
+    let mut out = format!(
+        "policy {} version {}\n",
+        quote(&policy.id.name.to_string()),
+        policy.id.version
+    );
+    if !policy.description.is_empty() {
+        out.push_str(&format!(
+            "description {}\n",
+            quote(&policy.description.to_string())
+        ));
+    }
+    out.push_str(&format!("priority {}\n", policy.priority));
+    if !policy.enabled {
+        out.push_str("disabled\n");
+    }
+    out.push_str(match policy.effect {
+        PolicyEffect::Permit => "permit",
+        PolicyEffect::Deny => "deny",
+    });
+    match &policy.rule {
+        PolicyRule::Allow => {}
+        PolicyRule::Unless(parts)
+            if parts.len() == 2 && parts.get(0) == Some(PolicyRule::Allow) =>
+        {
+            out.push_str(" unless ");
+            write_rule(&mut out, &parts.get(1).unwrap(), OR);
+        }
+        rule => {
+            out.push_str(" if ");
+            write_rule(&mut out, rule, UNLESS);
+        }
+    }
+    out.push('\n');
+    out
+}
+
+/// Prints a rule expression accepted by [`compile_rule`].
+pub fn pretty_print_rule(rule: &PolicyRule) -> StdString {
+    let mut out = StdString::new();
+    write_rule(&mut out, rule, UNLESS);
+    out
+}
+
+fn binding(rule: &PolicyRule) -> u8 {
+    match rule {
+        PolicyRule::Unless(parts) if parts.len() == 2 => UNLESS,
+        PolicyRule::Or(children) if children.len() >= 2 => OR,
+        PolicyRule::And(children) if children.len() >= 2 => AND,
+        PolicyRule::Not(inner) if inner.len() == 1 => NOT,
+        _ => ATOM,
+    }
+}
+
+fn write_rule(out: &mut StdString, rule: &PolicyRule, slot: u8) {
+    if binding(rule) < slot {
+        out.push('(');
+        write_bare(out, rule);
+        out.push(')');
+    } else {
+        write_bare(out, rule);
+    }
+}
+
+fn write_joined(out: &mut StdString, rules: &Vec<PolicyRule>, separator: &str, slot: u8) {
+    for (i, rule) in rules.iter().enumerate() {
+        if i > 0 {
+            out.push_str(separator);
+        }
+        write_rule(out, &rule, slot);
+    }
+}
+
+fn write_bare(out: &mut StdString, rule: &PolicyRule) {
+    match rule {
+        PolicyRule::Allow => out.push_str("true"),
+        PolicyRule::Deny => out.push_str("false"),
+        PolicyRule::Unless(parts) if parts.len() == 2 => {
+            // `unless` chains to the left, so a nested exception needs parentheses.
+            write_rule(out, &parts.get(0).unwrap(), UNLESS);
+            out.push_str(" unless ");
+            write_rule(out, &parts.get(1).unwrap(), OR);
+        }
+        // Same-operator children are parenthesised so they are not flattened.
+        PolicyRule::Or(children) if children.len() >= 2 => write_joined(out, children, " or ", AND),
+        PolicyRule::And(children) if children.len() >= 2 => {
+            write_joined(out, children, " and ", NOT)
+        }
+        PolicyRule::Not(inner) if inner.len() == 1 => {
+            out.push_str("not ");
+            write_rule(out, &inner.get(0).unwrap(), NOT);
+        }
+        PolicyRule::IfThenElse(parts) if parts.len() == 3 => {
+            out.push_str("(if ");
+            write_rule(out, &parts.get(0).unwrap(), UNLESS);
+            out.push_str(" then ");
+            write_rule(out, &parts.get(1).unwrap(), UNLESS);
+            out.push_str(" else ");
+            write_rule(out, &parts.get(2).unwrap(), UNLESS);
+            out.push(')');
+        }
+        PolicyRule::And(args) => write_call(out, "all", args),
+        PolicyRule::Or(args) => write_call(out, "any", args),
+        PolicyRule::Not(args) => write_call(out, "not", args),
+        PolicyRule::IfThenElse(args) => write_call(out, "cond", args),
+        PolicyRule::Unless(args) => write_call(out, "unless", args),
+        PolicyRule::Attribute(condition) => write_attribute(out, condition),
+        PolicyRule::Temporal(constraint) => write_temporal(out, constraint),
+        PolicyRule::DelegationCheck(chain) => write_delegation(out, chain),
+    }
+}
+
+fn write_call(out: &mut StdString, name: &str, args: &Vec<PolicyRule>) {
+    out.push_str(name);
+    out.push('(');
+    write_joined(out, args, ", ", UNLESS);
+    out.push(')');
+}
+
+fn write_attribute(out: &mut StdString, condition: &AttributeCondition) {
+    let key = condition.key.to_string();
+    let is_dotted = !key.is_empty()
+        && key.split('.').all(|segment| {
+            segment.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+                && segment
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '_')
+        });
+    let first_segment = key.split('.').next().unwrap_or("");
+    if !is_dotted {
+        out.push_str(&format!("attr[{}]", quote(&key)));
+    } else if RESERVED.contains(&first_segment) {
+        out.push_str(&format!("attr.{}", key));
+    } else {
+        out.push_str(&key);
+    }
+
+    let (operator, always_list) = match condition.operator {
+        AttrOperator::Eq => ("==", false),
+        AttrOperator::NotEq => ("!=", false),
+        AttrOperator::Gte => (">=", false),
+        AttrOperator::Lte => ("<=", false),
+        AttrOperator::In => ("in", true),
+        AttrOperator::NotIn => ("not in", true),
+    };
+    out.push(' ');
+    out.push_str(operator);
+    out.push(' ');
+    if !always_list && condition.values.len() == 1 {
+        out.push_str(&quote(&condition.values.get(0).unwrap().to_string()));
+    } else {
+        write_list(out, &condition.values);
+    }
+}
+
+fn write_list(out: &mut StdString, values: &Vec<String>) {
+    out.push('[');
+    for (i, value) in values.iter().enumerate() {
+        if i > 0 {
+            out.push_str(", ");
+        }
+        out.push_str(&quote(&value.to_string()));
+    }
+    out.push(']');
+}
+
+fn write_temporal(out: &mut StdString, constraint: &TemporalConstraint) {
+    let mut parts: StdVec<StdString> = StdVec::new();
+    let all_day = constraint.allowed_hour_start == 0 && constraint.allowed_hour_end == 23;
+    if !all_day {
+        parts.push(format!(
+            "in {:02}:00..{:02}:00",
+            constraint.allowed_hour_start,
+            constraint.allowed_hour_end.saturating_add(1)
+        ));
+    }
+    if constraint.allowed_days_mask != 0 {
+        parts.push(format!("on {}", days_text(constraint.allowed_days_mask)));
+    }
+    if constraint.valid_from != 0 {
+        parts.push(format!("from {}", constraint.valid_from));
+    }
+    if constraint.valid_until != 0 {
+        parts.push(format!("until {}", constraint.valid_until));
+    }
+    if parts.is_empty() {
+        parts.push(StdString::from("in 00:00..24:00"));
+    }
+    out.push_str("time ");
+    out.push_str(&parts.join(" "));
+}
+
+/// Day names, with runs of three or more days as ranges.
+fn days_text(mask: u32) -> StdString {
+    if mask & !0x7f != 0 {
+        return mask.to_string();
+    }
+    let mut parts: StdVec<StdString> = StdVec::new();
+    let mut day = 0;
+    while day < 7 {
+        if mask & (1 << day) == 0 {
+            day += 1;
+            continue;
+        }
+        let start = day;
+        while day + 1 < 7 && mask & (1 << (day + 1)) != 0 {
+            day += 1;
+        }
+        if day - start >= 2 {
+            parts.push(format!("{}..{}", DAYS[start], DAYS[day]));
+        } else {
+            parts.extend(DAYS[start..=day].iter().map(|d| StdString::from(*d)));
+        }
+        day += 1;
+    }
+    parts.join(",")
+}
+
+fn write_delegation(out: &mut StdString, chain: &DelegationChain) {
+    out.push_str("delegation [");
+    for (i, link) in chain.links.iter().enumerate() {
+        if i > 0 {
+            out.push_str(", ");
+        }
+        out.push_str(&format!(
+            "{} -> {} ",
+            quote(&link.delegator.to_string().to_string()),
+            quote(&link.delegatee.to_string().to_string())
+        ));
+        write_list(out, &link.scoped_permissions);
+        if link.expires_at != 0 {
+            out.push_str(&format!(" until {}", link.expires_at));
+        }
+    }
+    out.push(']');
+}
+
+fn quote(text: &str) -> StdString {
+    let mut quoted = StdString::with_capacity(text.len() + 2);
+    quoted.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\t' => quoted.push_str("\\t"),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use soroban_sdk::testutils::Address as _;
+
+    const EXAMPLE: &str = r#"
+        # Clinic-hours access for optometrists.
+        policy "clinic_hours" version 2
+        description "Optometrists in clinic hours, except research staff"
+        priority 10
+        permit if role == "optometrist" and time in 08:00..18:00
+            unless attr.dept == "research"
+    "#;
+
+    fn attr(env: &Env, key: &str, operator: AttrOperator, values: &[&str]) -> PolicyRule {
+        let mut list = Vec::new(env);
+        for value in values {
+            list.push_back(String::from_str(env, value));
+        }
+        PolicyRule::Attribute(AttributeCondition {
+            key: String::from_str(env, key),
+            operator,
+            values: list,
+        })
+    }
+
+    fn round_trips(env: &Env, policy: &PolicyDefinition) {
+        let text = pretty_print(policy);
+        assert_eq!(compile(env, &text).as_ref(), Ok(policy), "{}", text);
+    }
+
+    #[test]
+    fn compiles_example_policy() {
+        let env = Env::default();
+        let policy = compile(&env, EXAMPLE).unwrap();
+
+        assert_eq!(policy.id.name, String::from_str(&env, "clinic_hours"));
+        assert_eq!(policy.id.version, 2);
+        assert_eq!(policy.priority, 10);
+        assert_eq!(policy.effect, PolicyEffect::Permit);
+        assert!(policy.enabled);
+
+        let clinic_hours = PolicyRule::Temporal(TemporalConstraint {
+            valid_from: 0,
+            valid_until: 0,
+            allowed_hour_start: 8,
+            allowed_hour_end: 17,
+            allowed_days_mask: 0,
+        });
+        let expected = PolicyRule::Unless(Vec::from_array(
+            &env,
+            [
+                PolicyRule::And(Vec::from_array(
+                    &env,
+                    [
+                        attr(&env, "role", AttrOperator::Eq, &["optometrist"]),
+                        clinic_hours,
+                    ],
+                )),
+                attr(&env, "dept", AttrOperator::Eq, &["research"]),
+            ],
+        ));
+        assert_eq!(policy.rule, expected);
+        assert_eq!(
+            pretty_print(&policy),
+            "policy \"clinic_hours\" version 2\n\
+             description \"Optometrists in clinic hours, except research staff\"\n\
+             priority 10\n\
+             permit if role == \"optometrist\" and time in 08:00..18:00 unless dept == \"research\"\n"
+        );
+    }
+
+    #[test]
+    fn precedence_and_grouping() {
+        let env = Env::default();
+        let a = attr(&env, "a", AttrOperator::Eq, &["1"]);
+        let b = attr(&env, "b", AttrOperator::Eq, &["1"]);
+        let c = attr(&env, "c", AttrOperator::Eq, &["1"]);
+
+        let rule = compile_rule(&env, r#"a == "1" or b == "1" and not c == "1""#).unwrap();
+        let not_c = PolicyRule::Not(Vec::from_array(&env, [c.clone()]));
+        let b_and_not_c = PolicyRule::And(Vec::from_array(&env, [b.clone(), not_c]));
+        assert_eq!(
+            rule,
+            PolicyRule::Or(Vec::from_array(&env, [a.clone(), b_and_not_c]))
+        );
+
+        // Nested same-operator nodes keep their parentheses.
+        let inner = PolicyRule::And(Vec::from_array(&env, [b.clone(), c.clone()]));
+        let nested = PolicyRule::And(Vec::from_array(&env, [a.clone(), inner]));
+        let text = pretty_print_rule(&nested);
+        assert_eq!(text, r#"a == "1" and (b == "1" and c == "1")"#);
+        assert_eq!(compile_rule(&env, &text), Ok(nested));
+    }
+
+    #[test]
+    fn pretty_print_round_trips() {
+        let env = Env::default();
+        let delegator = Address::generate(&env);
+        let delegatee = Address::generate(&env);
+        let source = format!(
+            r#"
+            policy "break_glass" version 3 priority 1 disabled
+            description "Escalation \"path\""
+            deny if (if attr.time == "night" then time in 22:00..06:00 on sat,sun else false)
+                or attr["on-call"] in ["yes", 1]
+                or delegation ["{}" -> "{}" ["read", "write"] until 5000]
+                or time on mon..fri from 100 until 200
+                or all() or cond(true) or not(true, false)
+                or level >= "3" and unit not in [] and ward != "icu"
+            "#,
+            delegator.to_string(),
+            delegatee.to_string()
+        );
+        let policy = compile(&env, &source).unwrap();
+        assert_eq!(policy.id.version, 3);
+        assert!(!policy.enabled);
+        assert_eq!(policy.effect, PolicyEffect::Deny);
+        round_trips(&env, &policy);
+
+        let text = pretty_print(&policy);
+        assert!(text.contains("time in 22:00..06:00 on sun,sat"));
+        assert!(text.contains("time on mon..fri from 100 until 200"));
+        assert!(text.contains(r#"attr["on-call"] in ["yes", "1"]"#));
+        assert!(text.contains("all() or cond(true) or not(true, false)"));
+
+        // Defaults and bare effects.
+        let bare = compile(&env, "policy open permit").unwrap();
+        assert_eq!(bare.rule, PolicyRule::Allow);
+        assert_eq!(bare.priority, DEFAULT_PRIORITY);
+        round_trips(&env, &bare);
+        let exception = compile(&env, r#"policy p permit unless role == "guest""#).unwrap();
+        assert!(pretty_print(&exception).ends_with("permit unless role == \"guest\"\n"));
+        round_trips(&env, &exception);
+    }
+
+    #[test]
+    fn errors_report_line_and_column() {
+        let env = Env::default();
+        let error = |source: &str| compile(&env, source).unwrap_err();
+
+        let missing = error("policy p\npermit if role == \"a\" and\n");
+        assert_eq!((missing.line, missing.column), (3, 1));
+        assert_eq!(missing.message, "expected a condition, found end of input");
+
+        let minutes = error("policy p\npermit if time in 08:30..18:00");
+        assert_eq!((minutes.line, minutes.column), (2, 19));
+        assert_eq!(
+            minutes.to_string(),
+            "2:19: policies use whole hours; minutes must be 00"
+        );
+
+        let operator = error("policy p permit if role = \"a\"");
+        assert_eq!((operator.line, operator.column), (1, 25));
+
+        let unterminated = error("policy p\n  permit if role == \"a");
+        assert_eq!((unterminated.line, unterminated.column), (2, 21));
+        assert_eq!(unterminated.message, "unterminated string");
+
+        let keyword = error("policy p permit if time == \"x\"");
+        assert_eq!((keyword.line, keyword.column), (1, 20));
+
+        let twice = error("policy p priority 1 priority 2 permit");
+        assert_eq!((twice.line, twice.column), (1, 21));
+        assert_eq!(twice.message, "'priority' is given more than once");
+
+        let address = error("policy p permit if delegation [\"GABC\" -> \"GABC\" []]");
+        assert_eq!((address.line, address.column), (1, 32));
+
+        let trailing = error("policy p permit deny");
+        assert_eq!(trailing.message, "expected end of input, found 'deny'");
+    }
+}
@@ -119,33 +119,54 @@ pub fn default_risk_score(input: &OperationRiskInput) -> u32 {
     score.min(100)
 }
 
-/// Score risk and update behavioral state to dynamically adjust anomaly pressure.
-pub fn evaluate_risk(
-    env: &Env,
-    input: &OperationRiskInput,
-    scorer: Option<RiskScoringFn>,
-) -> RiskAssessment {
-    let score_fn = scorer.unwrap_or(default_risk_score);
-    let base_score = score_fn(input).min(100);
-
+/// Behavior state for `key`, restarted if its window of `window_seconds`
+/// has passed.
+fn load_behavior(env: &Env, key: &(Symbol, Address, Symbol), window_seconds: u64) -> BehaviorState {
     let now = env.ledger().timestamp();
-    let key = behavior_key(&input.actor, input.operation.clone());
     let mut state: BehaviorState = env
         .storage()
         .persistent()
-        .get(&key)
+        .get(key)
         .unwrap_or(BehaviorState {
             window_start: now,
             count: 0,
             anomaly_hits: 0,
         });
 
-    if now.saturating_sub(state.window_start) > BEHAVIOR_WINDOW_SECONDS {
+    if now.saturating_sub(state.window_start) > window_seconds {
         state.window_start = now;
         state.count = 0;
         state.anomaly_hits = 0;
     }
+    state
+}
 
+/// Count one occurrence of `operation` by `actor` in a fixed window of
+/// `window_seconds` without scoring it, e.g. to drive circuit breakers.
+pub fn track_behavior(
+    env: &Env,
+    actor: &Address,
+    operation: Symbol,
+    window_seconds: u64,
+) -> BehaviorState {
+    let key = behavior_key(actor, operation);
+    let mut state = load_behavior(env, &key, window_seconds);
+    state.count = state.count.saturating_add(1);
+    env.storage().persistent().set(&key, &state);
+    state
+}
+
+/// Score risk and update behavioral state to dynamically adjust anomaly pressure.
+pub fn evaluate_risk(
+    env: &Env,
+    input: &OperationRiskInput,
+    scorer: Option<RiskScoringFn>,
+) -> RiskAssessment {
+    let score_fn = scorer.unwrap_or(default_risk_score);
+    let base_score = score_fn(input).min(100);
+
+    let key = behavior_key(&input.actor, input.operation.clone());
+    let mut state = load_behavior(env, &key, BEHAVIOR_WINDOW_SECONDS);
     state.count = state.count.saturating_add(1);
 
     let mut behavioral_adjustment: u32 = 0;
@@ -234,4 +255,25 @@ mod tests {
         assert!(assessment.behavioral_adjustment > 0);
         assert!(assessment.final_score >= assessment.base_score);
     }
+
+    #[test]
+    fn tracked_behavior_restarts_after_window() {
+        use soroban_sdk::testutils::Ledger as _;
+
+        let env = Env::default();
+        let contract_id = env.register(TestContract, ());
+        let actor = Address::generate(&env);
+
+        let state = env.as_contract(&contract_id, || {
+            track_behavior(&env, &actor, symbol_short!("DENY"), 60);
+            track_behavior(&env, &actor, symbol_short!("DENY"), 60)
+        });
+        assert_eq!(state.count, 2);
+
+        env.ledger().set_timestamp(state.window_start + 61);
+        let state = env.as_contract(&contract_id, || {
+            track_behavior(&env, &actor, symbol_short!("DENY"), 60)
+        });
+        assert_eq!(state.count, 1);
+    }
 }
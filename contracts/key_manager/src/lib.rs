@@ -27,6 +27,7 @@ const KEY: Symbol = symbol_short!("KEY");
 const KEY_VER: Symbol = symbol_short!("KEY_VER");
 const RECOVERY: Symbol = symbol_short!("RECOV");
 const AUDIT: Symbol = symbol_short!("AUDIT");
+const DERIVER: Symbol = symbol_short!("DERIVER");
 
 const RECOVERY_COOLDOWN: u64 = 86_400; // 24 hours
 
@@ -290,12 +291,42 @@ impl KeyManagerContract {
         Ok(key_bytes)
     }
 
+    /// Allows or disallows `deriver` to derive record keys from `key_id`.
+    /// The key's owner and the admin may always derive.
+    pub fn set_record_key_deriver(
+        env: Env,
+        caller: Address,
+        key_id: BytesN<32>,
+        deriver: Address,
+        allowed: bool,
+    ) -> Result<(), ContractError> {
+        caller.require_auth();
+        let record = Self::load_key_record(&env, &key_id)?;
+        Self::require_owner_or_admin(&env, &caller, &record.owner)?;
+
+        let key = (DERIVER, key_id.clone(), deriver);
+        if allowed {
+            env.storage().persistent().set(&key, &true);
+        } else {
+            env.storage().persistent().remove(&key);
+        }
+        Self::audit(&env, caller, symbol_short!("KEY_DRV"), Some(key_id.clone()), &key_id);
+        Ok(())
+    }
+
+    pub fn is_record_key_deriver(env: Env, key_id: BytesN<32>, deriver: Address) -> bool {
+        env.storage().persistent().has(&(DERIVER, key_id, deriver))
+    }
+
     pub fn derive_record_key(
         env: Env,
+        caller: Address,
         key_id: BytesN<32>,
         record_id: u64,
     ) -> Result<DerivedKey, ContractError> {
+        caller.require_auth();
         let record = Self::load_key_record(&env, &key_id)?;
+        Self::require_deriver(&env, &caller, &record)?;
         Self::ensure_active(&record)?;
         let (key_bytes, _) = Self::load_key_version(&env, &key_id, record.current_version)?;
         let derived = derive_record_key(&env, &key_bytes, record_id);
@@ -307,11 +338,14 @@ impl KeyManagerContract {
 
     pub fn derive_record_key_with_version(
         env: Env,
+        caller: Address,
         key_id: BytesN<32>,
         record_id: u64,
         version: u32,
     ) -> Result<DerivedKey, ContractError> {
-        let _record = Self::load_key_record(&env, &key_id)?;
+        caller.require_auth();
+        let record = Self::load_key_record(&env, &key_id)?;
+        Self::require_deriver(&env, &caller, &record)?;
         let (key_bytes, _) = Self::load_key_version(&env, &key_id, version)?;
         let derived = derive_record_key(&env, &key_bytes, record_id);
         Ok(DerivedKey {
@@ -524,6 +558,17 @@ impl KeyManagerContract {
         Ok(())
     }
 
+    fn require_deriver(env: &Env, caller: &Address, record: &KeyRecord) -> Result<(), ContractError> {
+        if env
+            .storage()
+            .persistent()
+            .has(&(DERIVER, record.id.clone(), caller.clone()))
+        {
+            return Ok(());
+        }
+        Self::require_owner_or_admin(env, caller, &record.owner)
+    }
+
     fn ensure_active(record: &KeyRecord) -> Result<(), ContractError> {
         if record.status == KeyStatus::Revoked {
             return Err(ContractError::KeyRevoked);
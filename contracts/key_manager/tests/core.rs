@@ -38,14 +38,14 @@ fn test_rotation_preserves_versions() {
     let key_bytes = BytesN::from_array(&env, &[7u8; 32]);
     let key_id = client.create_master_key(&admin, &KeyType::Encryption, &policy, &0u64, &key_bytes);
 
-    let derived_v1 = client.derive_record_key(&key_id, &1u64);
+    let derived_v1 = client.derive_record_key(&admin, &key_id, &1u64);
     let version_v1 = derived_v1.version;
 
     let v2 = client.rotate_key(&admin, &key_id);
     assert_eq!(v2, version_v1 + 1);
 
-    let derived_v1_again = client.derive_record_key_with_version(&key_id, &1u64, &version_v1);
-    let derived_v2 = client.derive_record_key_with_version(&key_id, &1u64, &v2);
+    let derived_v1_again = client.derive_record_key_with_version(&admin, &key_id, &1u64, &version_v1);
+    let derived_v2 = client.derive_record_key_with_version(&admin, &key_id, &1u64, &v2);
 
     assert_ne!(derived_v1_again.key, derived_v2.key);
 }
@@ -138,3 +138,38 @@ fn test_hierarchy_validation() {
     );
     assert!(matches!(err, Err(Ok(ContractError::InvalidHierarchy))));
 }
+
+#[test]
+fn test_record_key_derivation_requires_authorization() {
+    let (env, client, _identity, admin) = setup();
+
+    let policy = KeyPolicy {
+        max_uses: 0,
+        not_before: 0,
+        not_after: 0,
+        allowed_ops: Vec::new(&env),
+    };
+
+    let key_bytes = BytesN::from_array(&env, &[4u8; 32]);
+    let key_id = client.create_master_key(&admin, &KeyType::Encryption, &policy, &0u64, &key_bytes);
+
+    let stranger = Address::generate(&env);
+    let err = client.try_derive_record_key(&stranger, &key_id, &1u64);
+    assert!(matches!(err, Err(Ok(ContractError::Unauthorized))));
+    let err = client.try_derive_record_key_with_version(&stranger, &key_id, &1u64, &1u32);
+    assert!(matches!(err, Err(Ok(ContractError::Unauthorized))));
+
+    let err = client.try_set_record_key_deriver(&stranger, &key_id, &stranger, &true);
+    assert!(matches!(err, Err(Ok(ContractError::Unauthorized))));
+
+    client.set_record_key_deriver(&admin, &key_id, &stranger, &true);
+    assert!(client.is_record_key_deriver(&key_id, &stranger));
+    assert_eq!(
+        client.derive_record_key(&stranger, &key_id, &1u64),
+        client.derive_record_key(&admin, &key_id, &1u64)
+    );
+
+    client.set_record_key_deriver(&admin, &key_id, &stranger, &false);
+    let err = client.try_derive_record_key(&stranger, &key_id, &1u64);
+    assert!(matches!(err, Err(Ok(ContractError::Unauthorized))));
+}
@@ -0,0 +1,95 @@
+use soroban_sdk::{contracttype, symbol_short, Address, Env, String, Symbol, Vec};
+
+// ── Storage keys ──────────────────────────────────────────────
+const REC_HIST: Symbol = symbol_short!("REC_HIST");
+const REC_TOMB: Symbol = symbol_short!("REC_TOMB");
+
+const TTL_THRESHOLD: u32 = 5184000;
+const TTL_EXTEND_TO: u32 = 10368000;
+
+fn extend_ttl_record_key(env: &Env, key: &(Symbol, u64)) {
+    env.storage()
+        .persistent()
+        .extend_ttl(key, TTL_THRESHOLD, TTL_EXTEND_TO);
+}
+
+// ── Types ─────────────────────────────────────────────────────
+
+/// Why a record was retracted.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[repr(u32)]
+pub enum RetractionReason {
+    EnteredInError = 1,
+    WrongPatient = 2,
+    Duplicate = 3,
+    PatientRequest = 4,
+    Other = 5,
+}
+
+/// One entry in a record's version chain. `data_hash` is stored encrypted
+/// under `key_version`, exactly as on the record itself.
+#[contracttype]
+#[derive(Clone, Debug)]
+pub struct RecordVersion {
+    /// OCC version stamp the record carried once this version was written.
+    pub version: u64,
+    pub data_hash: String,
+    pub key_version: Option<String>,
+    pub author: Address,
+    /// Amendment reason; empty for the original version.
+    pub reason: String,
+    pub created_at: u64,
+}
+
+/// Left in place of a retracted record. The record and its version chain are
+/// kept so provenance survives the retraction.
+#[contracttype]
+#[derive(Clone, Debug)]
+pub struct Tombstone {
+    pub record_id: u64,
+    pub retracted_by: Address,
+    pub retracted_at: u64,
+    pub reason: RetractionReason,
+    pub note: String,
+    /// OCC version of the record at the time of retraction.
+    pub version: u64,
+}
+
+// ── Storage ───────────────────────────────────────────────────
+
+/// Returns the stored version chain, oldest first. Records that have never
+/// been amended have no stored chain.
+pub fn get_history(env: &Env, record_id: u64) -> Vec<RecordVersion> {
+    env.storage()
+        .persistent()
+        .get(&(REC_HIST, record_id))
+        .unwrap_or(Vec::new(env))
+}
+
+/// Appends a version to the chain. `original` is written first when the
+/// chain is still empty so the first amendment preserves the initial entry.
+pub fn push_version(env: &Env, record_id: u64, original: &RecordVersion, new: &RecordVersion) {
+    let key = (REC_HIST, record_id);
+    let mut history = get_history(env, record_id);
+    if history.is_empty() {
+        history.push_back(original.clone());
+    }
+    history.push_back(new.clone());
+    env.storage().persistent().set(&key, &history);
+    extend_ttl_record_key(env, &key);
+}
+
+pub fn get_tombstone(env: &Env, record_id: u64) -> Option<Tombstone> {
+    env.storage().persistent().get(&(REC_TOMB, record_id))
+}
+
+pub fn is_retracted(env: &Env, record_id: u64) -> bool {
+    env.storage().persistent().has(&(REC_TOMB, record_id))
+}
+
+pub fn set_tombstone(env: &Env, tombstone: &Tombstone) {
+    let key = (REC_TOMB, tombstone.record_id);
+    env.storage().persistent().set(&key, tombstone);
+    extend_ttl_record_key(env, &key);
+}
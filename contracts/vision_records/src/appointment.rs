@@ -1,11 +1,19 @@
 use soroban_sdk::{contracttype, symbol_short, Address, Env, String, Symbol, Vec};
 
+use crate::pagination::BucketList;
+
 // ── Storage keys ──────────────────────────────────────────────
 pub const APPT_CTR: Symbol = symbol_short!("APPT_CTR");
 const APPT_RECORD: Symbol = symbol_short!("APPT_REC");
-const APPT_PATIENT: Symbol = symbol_short!("APPT_PAT");
-const APPT_PROVIDER: Symbol = symbol_short!("APPT_PROV");
+const APPT_PATIENT_LIST: Symbol = symbol_short!("APPT_PATL");
+const APPT_PROVIDER_LIST: Symbol = symbol_short!("APPT_PRVL");
 const APPT_HISTORY: Symbol = symbol_short!("APPT_HIST");
+const APPT_PROV_DAY: Symbol = symbol_short!("APPT_PDAY");
+
+/// Longest bookable appointment.
+pub const MAX_APPOINTMENT_MINUTES: u32 = 480;
+
+const DAY_SECONDS: u64 = 86_400;
 
 const TTL_THRESHOLD: u32 = 5184000;
 const TTL_EXTEND_TO: u32 = 10368000;
@@ -17,14 +25,7 @@ fn extend_ttl_appointment_key(env: &Env, key: &(Symbol, u64)) {
         .extend_ttl(key, TTL_THRESHOLD, TTL_EXTEND_TO);
 }
 
-/// Extends the time-to-live (TTL) for appointment by patient keys.
-fn extend_ttl_appointment_patient_key(env: &Env, key: &(Symbol, Address, u64)) {
-    env.storage()
-        .persistent()
-        .extend_ttl(key, TTL_THRESHOLD, TTL_EXTEND_TO);
-}
-
-/// Extends the time-to-live (TTL) for appointment by provider keys.
+/// Extends the time-to-live (TTL) for per-provider, per-day appointment keys.
 fn extend_ttl_appointment_provider_key(env: &Env, key: &(Symbol, Address, u64)) {
     env.storage()
         .persistent()
@@ -105,80 +106,164 @@ pub fn increment_appointment_counter(env: &Env) -> u64 {
 /// Stores an appointment record
 pub fn set_appointment(env: &Env, appointment: &Appointment) {
     let key = (APPT_RECORD, appointment.id);
+    let previous = env.storage().persistent().get::<_, Appointment>(&key);
     env.storage().persistent().set(&key, appointment);
     extend_ttl_appointment_key(env, &key);
 
-    // Index by patient for quick lookup
-    let patient_key = (APPT_PATIENT, appointment.patient.clone(), appointment.id);
-    env.storage().persistent().set(&patient_key, &true);
-    extend_ttl_appointment_patient_key(env, &patient_key);
+    // New appointments are appended to the patient's and provider's lists.
+    if previous.is_none() {
+        patient_list(&appointment.patient).push(env, &appointment.id);
+        provider_list(&appointment.provider).push(env, &appointment.id);
+    }
 
-    // Index by provider for quick lookup
-    let provider_key = (APPT_PROVIDER, appointment.provider.clone(), appointment.id);
-    env.storage().persistent().set(&provider_key, &true);
-    extend_ttl_appointment_provider_key(env, &provider_key);
+    // Per-provider, per-day buckets used for double-booking detection hold
+    // active appointments only. A rescheduled appointment moves to the
+    // bucket of its new day; a cancelled or finished one leaves its bucket.
+    let listed_day = |appt: &Appointment| {
+        is_active_status(&appt.status).then_some(appt.scheduled_at / DAY_SECONDS)
+    };
+    let old_day = previous.as_ref().and_then(listed_day);
+    let new_day = listed_day(appointment);
+    if old_day != new_day {
+        if let Some(day) = old_day {
+            let mut ids = provider_day_ids(env, &appointment.provider, day);
+            if let Some(index) = ids.first_index_of(appointment.id) {
+                ids.remove(index);
+            }
+            set_provider_day_ids(env, &appointment.provider, day, &ids);
+        }
+        if let Some(day) = new_day {
+            let mut ids = provider_day_ids(env, &appointment.provider, day);
+            ids.push_back(appointment.id);
+            set_provider_day_ids(env, &appointment.provider, day, &ids);
+        }
+    }
 }
 
-/// Retrieves an appointment by ID
-pub fn get_appointment(env: &Env, appointment_id: u64) -> Option<Appointment> {
-    let key = (APPT_RECORD, appointment_id);
-    env.storage().persistent().get(&key)
+/// Ids of the provider's appointments starting on `day` (days since the
+/// Unix epoch, UTC).
+fn provider_day_ids(env: &Env, provider: &Address, day: u64) -> Vec<u64> {
+    env.storage()
+        .persistent()
+        .get(&(APPT_PROV_DAY, provider.clone(), day))
+        .unwrap_or(Vec::new(env))
 }
 
-/// Gets all appointments for a patient
-pub fn get_patient_appointments(env: &Env, patient: &Address) -> Vec<Appointment> {
-    let mut appointments = Vec::new(env);
-    let counter: u64 = env.storage().instance().get(&APPT_CTR).unwrap_or(0);
-    let start_id = if counter > 100 { counter - 100 } else { 1 };
-
-    for id in start_id..=counter {
-        let key = (APPT_RECORD, id);
-        if let Some(appointment) = env.storage().persistent().get::<_, Appointment>(&key) {
-            if appointment.patient == *patient {
-                appointments.push_back(appointment);
-            }
-        }
+fn set_provider_day_ids(env: &Env, provider: &Address, day: u64, ids: &Vec<u64>) {
+    let key = (APPT_PROV_DAY, provider.clone(), day);
+    if ids.is_empty() {
+        env.storage().persistent().remove(&key);
+        return;
     }
-    appointments
+    env.storage().persistent().set(&key, ids);
+    extend_ttl_appointment_provider_key(env, &key);
 }
 
-/// Gets all appointments for a provider
-pub fn get_provider_appointments(env: &Env, provider: &Address) -> Vec<Appointment> {
-    let mut appointments = Vec::new(env);
-    let counter: u64 = env.storage().instance().get(&APPT_CTR).unwrap_or(0);
-    let start_id = if counter > 100 { counter - 100 } else { 1 };
+/// Returns true if an appointment in this status still occupies the provider's calendar.
+pub fn is_active_status(status: &AppointmentStatus) -> bool {
+    matches!(
+        status,
+        AppointmentStatus::Scheduled
+            | AppointmentStatus::Confirmed
+            | AppointmentStatus::Rescheduled
+    )
+}
 
-    for id in start_id..=counter {
-        let key = (APPT_RECORD, id);
-        if let Some(appointment) = env.storage().persistent().get::<_, Appointment>(&key) {
-            if appointment.provider == *provider {
-                appointments.push_back(appointment);
+/// Finds an active appointment of `provider` whose time slot overlaps
+/// `[scheduled_at, scheduled_at + duration_minutes)`.
+///
+/// `exclude_id` skips the appointment being rescheduled so it does not
+/// conflict with its own previous slot.
+pub fn find_provider_conflict(
+    env: &Env,
+    provider: &Address,
+    scheduled_at: u64,
+    duration_minutes: u32,
+    exclude_id: Option<u64>,
+) -> Option<u64> {
+    let end = scheduled_at.saturating_add(u64::from(duration_minutes) * 60);
+
+    // An overlapping appointment starts before `end` and, being at most
+    // MAX_APPOINTMENT_MINUTES long, no earlier than that before `scheduled_at`.
+    let first_day =
+        scheduled_at.saturating_sub(u64::from(MAX_APPOINTMENT_MINUTES) * 60) / DAY_SECONDS;
+    let last_day = end.saturating_sub(1) / DAY_SECONDS;
+    for day in first_day..=last_day {
+        for id in provider_day_ids(env, provider, day).iter() {
+            if Some(id) == exclude_id {
+                continue;
+            }
+            if let Some(existing) = get_appointment(env, id) {
+                if !is_active_status(&existing.status) {
+                    continue;
+                }
+                let existing_end = existing
+                    .scheduled_at
+                    .saturating_add(u64::from(existing.duration_minutes) * 60);
+                if scheduled_at < existing_end && existing.scheduled_at < end {
+                    return Some(id);
+                }
             }
         }
     }
-    appointments
+    None
 }
 
-/// Gets upcoming appointments for a patient (scheduled time in the future)
-pub fn get_upcoming_patient_appointments(env: &Env, patient: &Address) -> Vec<Appointment> {
-    let mut appointments = Vec::new(env);
-    let current_time = env.ledger().timestamp();
-    let counter: u64 = env.storage().instance().get(&APPT_CTR).unwrap_or(0);
-    let start_id = if counter > 100 { counter - 100 } else { 1 };
+/// Retrieves an appointment by ID
+pub fn get_appointment(env: &Env, appointment_id: u64) -> Option<Appointment> {
+    let key = (APPT_RECORD, appointment_id);
+    env.storage().persistent().get(&key)
+}
 
-    for id in start_id..=counter {
-        let key = (APPT_RECORD, id);
-        if let Some(appointment) = env.storage().persistent().get::<_, Appointment>(&key) {
-            if appointment.patient == *patient
-                && appointment.scheduled_at > current_time
-                && (appointment.status == AppointmentStatus::Scheduled
-                    || appointment.status == AppointmentStatus::Confirmed)
-            {
-                appointments.push_back(appointment);
-            }
-        }
-    }
-    appointments
+fn patient_list(patient: &Address) -> BucketList<Address> {
+    BucketList::new(APPT_PATIENT_LIST, patient.clone())
+}
+
+fn provider_list(provider: &Address) -> BucketList<Address> {
+    BucketList::new(APPT_PROVIDER_LIST, provider.clone())
+}
+
+/// Page through a patient's appointments in booking order, keeping the
+/// ones `keep` accepts; see [`crate::pagination::page_positions`].
+pub fn page_patient_appointments(
+    env: &Env,
+    patient: &Address,
+    cursor: Option<u64>,
+    limit: u32,
+    newest_first: bool,
+    keep: impl FnMut(&Appointment) -> bool,
+) -> (Vec<Appointment>, Option<u64>) {
+    patient_list(patient).page_map(
+        env,
+        cursor,
+        limit,
+        newest_first,
+        |id| get_appointment(env, id),
+        keep,
+    )
+}
+
+/// Page through a provider's appointments in booking order.
+pub fn page_provider_appointments(
+    env: &Env,
+    provider: &Address,
+    cursor: Option<u64>,
+    limit: u32,
+    newest_first: bool,
+) -> (Vec<Appointment>, Option<u64>) {
+    provider_list(provider).page_map(
+        env,
+        cursor,
+        limit,
+        newest_first,
+        |id| get_appointment(env, id),
+        |_| true,
+    )
+}
+
+/// True if the appointment is still active and lies in the future.
+pub fn is_upcoming(env: &Env, appointment: &Appointment) -> bool {
+    appointment.scheduled_at > env.ledger().timestamp() && is_active_status(&appointment.status)
 }
 
 /// Adds a history entry for an appointment
@@ -233,8 +318,7 @@ pub fn get_appointments_needing_reminders(
             if appointment.scheduled_at <= reminder_threshold
                 && appointment.scheduled_at > current_time
                 && !appointment.reminder_sent
-                && (appointment.status == AppointmentStatus::Scheduled
-                    || appointment.status == AppointmentStatus::Confirmed)
+                && is_active_status(&appointment.status)
             {
                 appointments.push_back(appointment);
             }
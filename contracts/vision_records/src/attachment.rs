@@ -0,0 +1,220 @@
+//! Attachment manifests tie off-chain files (fundus photos, OCT scans,
+//! visual field plots) to an on-chain record. Each entry commits to the
+//! file's SHA-256 and size so a downloaded copy can be checked against it,
+//! and the manifest as a whole has a digest for integrity checks.
+
+use soroban_sdk::{contracttype, symbol_short, Address, Bytes, BytesN, Env, String, Symbol, Vec};
+
+use crate::ContractError;
+
+// ── Storage keys ──────────────────────────────────────────────
+const ATTACH: Symbol = symbol_short!("ATTACH");
+
+const TTL_THRESHOLD: u32 = 5184000;
+const TTL_EXTEND_TO: u32 = 10368000;
+
+/// Most attachments one record may carry.
+pub const MAX_ATTACHMENTS: u32 = 100;
+const MAX_MIME_LEN: u32 = 127;
+const MAX_URI_LEN: u32 = 256;
+const MAX_KEY_REF_LEN: u32 = 128;
+
+// ── Types ─────────────────────────────────────────────────────
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[repr(u32)]
+pub enum AttachmentKind {
+    FundusPhoto = 1,
+    Oct = 2,
+    VisualField = 3,
+    RetinalImage = 4,
+    Other = 5,
+}
+
+/// A file to attach, as supplied by the uploader.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AttachmentInput {
+    pub kind: AttachmentKind,
+    /// SHA-256 of the stored (encrypted) file.
+    pub content_hash: BytesN<32>,
+    pub mime_type: String,
+    pub size_bytes: u64,
+    pub storage_uri: String,
+    /// Identifies the key the file is encrypted under.
+    pub key_ref: String,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Attachment {
+    /// Unique within the record; never reused after removal.
+    pub id: u32,
+    pub kind: AttachmentKind,
+    pub content_hash: BytesN<32>,
+    pub mime_type: String,
+    pub size_bytes: u64,
+    pub storage_uri: String,
+    pub key_ref: String,
+    pub added_by: Address,
+    pub added_at: u64,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AttachmentManifest {
+    pub record_id: u64,
+    pub attachments: Vec<Attachment>,
+    pub next_id: u32,
+    /// See [`manifest_digest`].
+    pub digest: BytesN<32>,
+    pub updated_at: u64,
+}
+
+// ── Storage ───────────────────────────────────────────────────
+
+fn manifest_key(record_id: u64) -> (Symbol, u64) {
+    (ATTACH, record_id)
+}
+
+pub fn get_manifest(env: &Env, record_id: u64) -> Option<AttachmentManifest> {
+    env.storage().persistent().get(&manifest_key(record_id))
+}
+
+fn save_manifest(env: &Env, manifest: &mut AttachmentManifest) {
+    manifest.digest = manifest_digest(env, &manifest.attachments);
+    manifest.updated_at = env.ledger().timestamp();
+    let key = manifest_key(manifest.record_id);
+    env.storage().persistent().set(&key, manifest);
+    env.storage()
+        .persistent()
+        .extend_ttl(&key, TTL_THRESHOLD, TTL_EXTEND_TO);
+}
+
+// ── Validation ────────────────────────────────────────────────
+
+fn validate_input(input: &AttachmentInput) -> Result<(), ContractError> {
+    let mut mime = [0u8; MAX_MIME_LEN as usize];
+    let mime_len = input.mime_type.len();
+    if mime_len == 0 || mime_len > MAX_MIME_LEN {
+        return Err(ContractError::InvalidInput);
+    }
+    input
+        .mime_type
+        .copy_into_slice(&mut mime[..mime_len as usize]);
+    if !mime[..mime_len as usize].contains(&b'/') {
+        return Err(ContractError::InvalidInput);
+    }
+    if input.size_bytes == 0
+        || input.storage_uri.is_empty()
+        || input.storage_uri.len() > MAX_URI_LEN
+        || input.key_ref.is_empty()
+        || input.key_ref.len() > MAX_KEY_REF_LEN
+    {
+        return Err(ContractError::InvalidInput);
+    }
+    Ok(())
+}
+
+// ── Manifest operations ───────────────────────────────────────
+
+/// Appends `inputs` to the record's manifest and returns the new ids.
+/// A file already in the manifest (same content hash) is rejected.
+pub fn add_attachments(
+    env: &Env,
+    record_id: u64,
+    added_by: &Address,
+    inputs: &Vec<AttachmentInput>,
+) -> Result<Vec<u32>, ContractError> {
+    let mut manifest = get_manifest(env, record_id).unwrap_or(AttachmentManifest {
+        record_id,
+        attachments: Vec::new(env),
+        next_id: 1,
+        digest: BytesN::from_array(env, &[0u8; 32]),
+        updated_at: 0,
+    });
+    if inputs.is_empty()
+        || manifest.attachments.len().saturating_add(inputs.len()) > MAX_ATTACHMENTS
+    {
+        return Err(ContractError::InvalidInput);
+    }
+
+    let now = env.ledger().timestamp();
+    let mut ids = Vec::new(env);
+    for input in inputs.iter() {
+        validate_input(&input)?;
+        if find_by_hash(&manifest.attachments, &input.content_hash).is_some() {
+            return Err(ContractError::InvalidInput);
+        }
+        let id = manifest.next_id;
+        manifest.next_id = id.saturating_add(1);
+        manifest.attachments.push_back(Attachment {
+            id,
+            kind: input.kind,
+            content_hash: input.content_hash,
+            mime_type: input.mime_type,
+            size_bytes: input.size_bytes,
+            storage_uri: input.storage_uri,
+            key_ref: input.key_ref,
+            added_by: added_by.clone(),
+            added_at: now,
+        });
+        ids.push_back(id);
+    }
+    save_manifest(env, &mut manifest);
+    Ok(ids)
+}
+
+/// Removes one attachment and returns it.
+pub fn remove_attachment(
+    env: &Env,
+    record_id: u64,
+    attachment_id: u32,
+) -> Result<Attachment, ContractError> {
+    let mut manifest = get_manifest(env, record_id).ok_or(ContractError::AttachmentNotFound)?;
+    let index = manifest
+        .attachments
+        .iter()
+        .position(|a| a.id == attachment_id)
+        .ok_or(ContractError::AttachmentNotFound)?;
+    let removed = manifest
+        .attachments
+        .get(index as u32)
+        .ok_or(ContractError::AttachmentNotFound)?;
+    manifest.attachments.remove(index as u32);
+    save_manifest(env, &mut manifest);
+    Ok(removed)
+}
+
+fn find_by_hash(attachments: &Vec<Attachment>, content_hash: &BytesN<32>) -> Option<Attachment> {
+    attachments.iter().find(|a| a.content_hash == *content_hash)
+}
+
+// ── Integrity ─────────────────────────────────────────────────
+
+/// SHA-256 over each attachment's id, content hash and size, in manifest
+/// order. Off-chain storage can recompute it to prove it holds exactly the
+/// files the record lists.
+pub fn manifest_digest(env: &Env, attachments: &Vec<Attachment>) -> BytesN<32> {
+    let mut payload = Bytes::new(env);
+    for attachment in attachments.iter() {
+        payload.extend_from_array(&attachment.id.to_be_bytes());
+        payload.extend_from_array(&attachment.content_hash.to_array());
+        payload.extend_from_array(&attachment.size_bytes.to_be_bytes());
+    }
+    env.crypto().sha256(&payload).into()
+}
+
+/// True if `content_hash` and `size_bytes` match the listed attachment.
+pub fn verify_attachment(
+    env: &Env,
+    record_id: u64,
+    attachment_id: u32,
+    content_hash: &BytesN<32>,
+    size_bytes: u64,
+) -> bool {
+    get_manifest(env, record_id)
+        .and_then(|m| m.attachments.iter().find(|a| a.id == attachment_id))
+        .is_some_and(|a| a.content_hash == *content_hash && a.size_bytes == size_bytes)
+}
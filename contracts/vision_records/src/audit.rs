@@ -1,8 +1,13 @@
 extern crate alloc;
-use alloc::vec::Vec;
-use soroban_sdk::{contracttype, symbol_short, Address, BytesN, Env, String, Symbol};
-use audit::types::LogSegmentId;
+use alloc::vec::Vec as StdVec;
 use audit::merkle_log::hash_leaf;
+use audit::types::LogSegmentId;
+use soroban_sdk::{
+    contracttype, symbol_short, Address, BytesN, Env, IntoVal, Map, String, Symbol, Val, Vec,
+};
+
+use crate::circuit_breaker;
+use crate::pagination::{self, BucketList};
 
 const AUDIT_LATEST_HASH: Symbol = symbol_short!("AUD_HASH");
 const AUDIT_SEQUENCE: Symbol = symbol_short!("AUD_SEQ");
@@ -24,26 +29,24 @@ pub struct AuditLogEvent {
 
 impl AuditManager {
     /// Logs a tamper-evident audit event for the vision records contract.
-    pub fn log_event(
-        env: &Env,
-        actor: Address,
-        action: &str,
-        target: String,
-        result: &str,
-    ) {
+    pub fn log_event(env: &Env, actor: Address, action: &str, target: String, result: &str) {
         let mut sequence: u64 = env.storage().persistent().get(&AUDIT_SEQUENCE).unwrap_or(0);
         sequence += 1;
 
-        let prev_hash_bytes: [u8; 32] = env.storage().persistent().get(&AUDIT_LATEST_HASH).unwrap_or([0u8; 32]);
+        let prev_hash_bytes: [u8; 32] = env
+            .storage()
+            .persistent()
+            .get(&AUDIT_LATEST_HASH)
+            .unwrap_or([0u8; 32]);
         let timestamp = env.ledger().timestamp();
 
         // Use the segment "vision_records"
         let segment = LogSegmentId::new("vision_records").unwrap();
 
-        let mut buf = Vec::new();
+        let mut buf = StdVec::new();
         buf.extend_from_slice(&sequence.to_le_bytes());
         buf.extend_from_slice(&timestamp.to_le_bytes());
-        
+
         // Copy actor string to buffer
         let actor_str = actor.to_string();
         let mut actor_bytes = alloc::vec![0u8; actor_str.len() as usize];
@@ -68,7 +71,9 @@ impl AuditManager {
         let entry_hash = hash_leaf(&buf);
 
         // Update state
-        env.storage().persistent().set(&AUDIT_LATEST_HASH, &entry_hash);
+        env.storage()
+            .persistent()
+            .set(&AUDIT_LATEST_HASH, &entry_hash);
         env.storage().persistent().set(&AUDIT_SEQUENCE, &sequence);
 
         // Emit event
@@ -83,16 +88,28 @@ impl AuditManager {
             entry_hash: BytesN::from_array(env, &entry_hash),
         };
 
-        env.events().publish((symbol_short!("AUDIT"), actor), event_data);
-
-use soroban_sdk::{contracttype, symbol_short, Address, Env, String, Symbol, Vec};
+        #[allow(deprecated)]
+        env.events()
+            .publish((symbol_short!("AUDIT"), actor), event_data);
+    }
+}
 
 // ── Storage keys ──────────────────────────────────────────────
 pub const AUDIT_CTR: Symbol = symbol_short!("AUD_CTR");
 const AUDIT_ENTRY: Symbol = symbol_short!("AUD_ENT");
+// Per-id flags `(prefix, key, id)` written before the per-key lists below.
+// They are only read, for entries older than `AUDIT_LISTED_FROM`.
 const AUDIT_RECORD: Symbol = symbol_short!("AUD_REC");
 const AUDIT_USER: Symbol = symbol_short!("AUD_USR");
 const AUDIT_PATIENT: Symbol = symbol_short!("AUD_PAT");
+const AUDIT_RECORD_LIST: Symbol = symbol_short!("AUD_RECL");
+const AUDIT_USER_LIST: Symbol = symbol_short!("AUD_USRL");
+const AUDIT_PATIENT_LIST: Symbol = symbol_short!("AUD_PATL");
+/// How the actor of an entry related to its patient when it was written.
+/// Kept beside the entry so stored entries keep their layout.
+const AUDIT_KIND: Symbol = symbol_short!("AUD_KIND");
+/// Id of the first entry appended to the per-key lists.
+const AUDIT_LISTED_FROM: Symbol = symbol_short!("AUD_IDX0");
 
 const TTL_THRESHOLD: u32 = 5184000;
 const TTL_EXTEND_TO: u32 = 10368000;
@@ -104,27 +121,6 @@ fn extend_ttl_audit_key(env: &Env, key: &(Symbol, u64)) {
         .extend_ttl(key, TTL_THRESHOLD, TTL_EXTEND_TO);
 }
 
-/// Extends the time-to-live (TTL) for audit by record keys.
-fn extend_ttl_audit_record_key(env: &Env, key: &(Symbol, u64, u64)) {
-    env.storage()
-        .persistent()
-        .extend_ttl(key, TTL_THRESHOLD, TTL_EXTEND_TO);
-}
-
-/// Extends the time-to-live (TTL) for audit by user keys.
-fn extend_ttl_audit_user_key(env: &Env, key: &(Symbol, Address, u64)) {
-    env.storage()
-        .persistent()
-        .extend_ttl(key, TTL_THRESHOLD, TTL_EXTEND_TO);
-}
-
-/// Extends the time-to-live (TTL) for audit by patient keys.
-fn extend_ttl_audit_patient_key(env: &Env, key: &(Symbol, Address, u64)) {
-    env.storage()
-        .persistent()
-        .extend_ttl(key, TTL_THRESHOLD, TTL_EXTEND_TO);
-}
-
 // ── Types ─────────────────────────────────────────────────────
 
 /// Type of access action
@@ -179,29 +175,42 @@ pub fn increment_audit_counter(env: &Env) -> u64 {
     next
 }
 
-/// Stores an audit entry
-pub fn add_audit_entry(env: &Env, entry: &AuditEntry) {
+/// Stores an audit entry, with `kind` recording how the actor related to
+/// the patient at that moment (`None` for the patient's own activity).
+/// Denied entries also feed the `AccessDenied` circuit-breaker trip rule,
+/// counted for the entry's actor.
+pub fn add_audit_entry(env: &Env, entry: &AuditEntry, kind: Option<AccessorKind>) {
     // Store by entry ID
     let key = (AUDIT_ENTRY, entry.id);
     env.storage().persistent().set(&key, entry);
     extend_ttl_audit_key(env, &key);
+    if let Some(kind) = kind {
+        let kind_key = (AUDIT_KIND, entry.id);
+        env.storage().persistent().set(&kind_key, &kind);
+        extend_ttl_audit_key(env, &kind_key);
+    }
 
-    // Index by record ID for quick lookup
-    if let Some(record_id) = entry.record_id {
-        let record_key = (AUDIT_RECORD, record_id, entry.id);
-        env.storage().persistent().set(&record_key, &true);
-        extend_ttl_audit_record_key(env, &record_key);
+    if !env.storage().instance().has(&AUDIT_LISTED_FROM) {
+        env.storage().instance().set(&AUDIT_LISTED_FROM, &entry.id);
     }
 
-    // Index by actor (user) for quick lookup
-    let user_key = (AUDIT_USER, entry.actor.clone(), entry.id);
-    env.storage().persistent().set(&user_key, &true);
-    extend_ttl_audit_user_key(env, &user_key);
+    // Index by patient, actor (user) and record ID for quick lookup
+    let patient: Val = entry.patient.into_val(env);
+    BucketList::new(AUDIT_PATIENT_LIST, patient).push(env, &entry.id);
+    let actor: Val = entry.actor.into_val(env);
+    BucketList::new(AUDIT_USER_LIST, actor).push(env, &entry.id);
+    if let Some(record_id) = entry.record_id {
+        let record: Val = record_id.into_val(env);
+        BucketList::new(AUDIT_RECORD_LIST, record).push(env, &entry.id);
+    }
 
-    // Index by patient for quick lookup
-    let patient_key = (AUDIT_PATIENT, entry.patient.clone(), entry.id);
-    env.storage().persistent().set(&patient_key, &true);
-    extend_ttl_audit_patient_key(env, &patient_key);
+    if entry.result == AccessResult::Denied {
+        circuit_breaker::record_signal(
+            env,
+            circuit_breaker::TripTrigger::AccessDenied,
+            &entry.actor,
+        );
+    }
 }
 
 /// Retrieves an audit entry by ID
@@ -210,135 +219,399 @@ pub fn get_audit_entry(env: &Env, entry_id: u64) -> Option<AuditEntry> {
     env.storage().persistent().get(&key)
 }
 
-/// Gets all audit entries for a specific record
-pub fn get_record_audit_log(env: &Env, record_id: u64) -> Vec<AuditEntry> {
-    let mut entries = Vec::new(env);
-    let counter: u64 = env.storage().instance().get(&AUDIT_CTR).unwrap_or(0);
-    if counter == 0 {
-        return entries;
-    }
-    let start_id = if counter > 1000 { counter - 1000 } else { 1 };
+/// The accessor kind recorded with an entry. Entries written before kinds
+/// were recorded, and the patient's own entries, have none.
+pub fn get_accessor_kind(env: &Env, entry_id: u64) -> Option<AccessorKind> {
+    env.storage().persistent().get(&(AUDIT_KIND, entry_id))
+}
 
-    for id in start_id..=counter {
-        let record_key = (AUDIT_RECORD, record_id, id);
-        if env
-            .storage()
-            .persistent()
-            .get::<_, bool>(&record_key)
-            .is_some()
-        {
-            if let Some(entry) = get_audit_entry(env, id) {
-                entries.push_back(entry);
-            }
-        }
-    }
-    entries
+/// Filters for [`query_audit_log`]. `None` and empty lists match everything;
+/// `from`/`to` bound the entry timestamp inclusively.
+#[contracttype]
+#[derive(Clone, Debug)]
+pub struct AuditFilter {
+    pub patient: Option<Address>,
+    pub actor: Option<Address>,
+    pub record_id: Option<u64>,
+    pub actions: Vec<AccessAction>,
+    pub results: Vec<AccessResult>,
+    pub from: Option<u64>,
+    pub to: Option<u64>,
 }
 
-/// Gets all audit entries for a specific user (actor)
-pub fn get_user_audit_log(env: &Env, user: &Address) -> Vec<AuditEntry> {
-    let mut entries = Vec::new(env);
-    let counter: u64 = env.storage().instance().get(&AUDIT_CTR).unwrap_or(0);
-    let start_id = if counter > 1000 { counter - 1000 } else { 1 };
+/// A page of audit entries in ascending id (and therefore time) order.
+#[contracttype]
+#[derive(Clone, Debug)]
+pub struct AuditPage {
+    pub entries: Vec<AuditEntry>,
+    /// Opaque position to resume from; `None` once the log is exhausted.
+    pub next_cursor: Option<u64>,
+}
 
-    for id in start_id..=counter {
-        let user_key = (AUDIT_USER, user.clone(), id);
-        if env
-            .storage()
-            .persistent()
-            .get::<_, bool>(&user_key)
-            .is_some()
-        {
-            if let Some(entry) = get_audit_entry(env, id) {
-                entries.push_back(entry);
-            }
+/// Returns the number of audit entries written so far.
+pub fn audit_count(env: &Env) -> u64 {
+    env.storage().instance().get(&AUDIT_CTR).unwrap_or(0)
+}
+
+fn matches_entry(filter: &AuditFilter, entry: &AuditEntry) -> bool {
+    filter
+        .patient
+        .as_ref()
+        .map_or(true, |p| *p == entry.patient)
+        && filter.actor.as_ref().map_or(true, |a| *a == entry.actor)
+        && filter
+            .record_id
+            .map_or(true, |r| entry.record_id == Some(r))
+        && (filter.actions.is_empty() || filter.actions.contains(&entry.action))
+        && (filter.results.is_empty() || filter.results.contains(&entry.result))
+        && pagination::in_range(entry.timestamp, filter.from, None)
+}
+
+/// First audit id in `1..=last` whose timestamp is at or after `from`, or
+/// `last + 1`. Ids are assigned in ledger-time order, so the log can be
+/// bisected.
+fn first_id_from(env: &Env, from: u64, last: u64) -> u64 {
+    let (mut lo, mut hi) = (1u64, last + 1);
+    while lo < hi {
+        let mid = lo + (hi - lo) / 2;
+        match get_audit_entry(env, mid) {
+            Some(entry) if entry.timestamp < from => lo = mid + 1,
+            Some(_) => hi = mid,
+            // Missing entry: fall back to scanning from the lower bound.
+            None => break,
         }
     }
-    entries
+    lo
 }
 
-/// Gets all audit entries for a specific patient
-pub fn get_patient_audit_log(env: &Env, patient: &Address) -> Vec<AuditEntry> {
-    let mut entries = Vec::new(env);
-    let counter: u64 = env.storage().instance().get(&AUDIT_CTR).unwrap_or(0);
-    let start_id = if counter > 1000 { counter - 1000 } else { 1 };
+/// The per-key index a filtered query walks: the legacy flag prefix, the
+/// list prefix and the key. Unindexed queries walk the whole log.
+fn index_for(env: &Env, filter: &AuditFilter) -> Option<(Symbol, Symbol, Val)> {
+    if let Some(patient) = &filter.patient {
+        Some((AUDIT_PATIENT, AUDIT_PATIENT_LIST, patient.into_val(env)))
+    } else if let Some(actor) = &filter.actor {
+        Some((AUDIT_USER, AUDIT_USER_LIST, actor.into_val(env)))
+    } else {
+        filter
+            .record_id
+            .map(|record_id| (AUDIT_RECORD, AUDIT_RECORD_LIST, record_id.into_val(env)))
+    }
+}
 
-    for id in start_id..=counter {
-        let patient_key = (AUDIT_PATIENT, patient.clone(), id);
-        if env
-            .storage()
-            .persistent()
-            .get::<_, bool>(&patient_key)
-            .is_some()
-        {
-            if let Some(entry) = get_audit_entry(env, id) {
-                entries.push_back(entry);
-            }
+/// Number of entries written before the per-key lists existed.
+fn legacy_count(env: &Env, counter: u64) -> u64 {
+    env.storage()
+        .instance()
+        .get::<_, u64>(&AUDIT_LISTED_FROM)
+        .map_or(counter, |first| first.saturating_sub(1))
+}
+
+/// First position whose entry is at or after `from`: legacy ids are
+/// bisected first, then the list.
+fn first_position_from(env: &Env, from: u64, legacy: u64, list: Option<&BucketList<Val>>) -> u64 {
+    let id = first_id_from(env, from, legacy);
+    if id <= legacy {
+        return id - 1;
+    }
+    let Some(list) = list else {
+        return legacy;
+    };
+    let mut read = list.reader::<u64>(env);
+    let (mut lo, mut hi) = (0u64, list.len(env));
+    while lo < hi {
+        let mid = lo + (hi - lo) / 2;
+        match read(mid).and_then(|id| get_audit_entry(env, id)) {
+            Some(entry) if entry.timestamp < from => lo = mid + 1,
+            Some(_) => hi = mid,
+            None => break,
         }
     }
-    entries
+    legacy + lo
 }
 
-/// Gets audit entries filtered by action type
-pub fn get_audit_log_by_action(env: &Env, action: AccessAction) -> Vec<AuditEntry> {
-    let mut entries = Vec::new(env);
-    let counter: u64 = env.storage().instance().get(&AUDIT_CTR).unwrap_or(0);
-    let start_id = if counter > 1000 { counter - 1000 } else { 1 };
-
-    for id in start_id..=counter {
-        if let Some(entry) = get_audit_entry(env, id) {
-            if entry.action == action {
-                entries.push_back(entry);
+/// Walks the entries matching `filter` in id order from position `cursor`,
+/// examining at most `max_positions` candidates, and hands each match to
+/// `visit` until it returns false. Returns the position to resume from, or
+/// `None` once the candidates are exhausted or past `filter.to`.
+///
+/// A query on a patient, actor or record walks that key's index: first the
+/// ids written before the index lists existed (positions `0..legacy`,
+/// checked against their flag keys), then the key's list. Other queries
+/// walk every audit id.
+fn scan_audit_log(
+    env: &Env,
+    filter: &AuditFilter,
+    cursor: Option<u64>,
+    max_positions: u64,
+    mut visit: impl FnMut(AuditEntry) -> bool,
+) -> Option<u64> {
+    let counter = audit_count(env);
+    let index = index_for(env, filter);
+    let legacy = match index {
+        Some(_) => legacy_count(env, counter),
+        None => counter,
+    };
+    let list = index
+        .as_ref()
+        .map(|(_, prefix, key)| BucketList::new(prefix.clone(), *key));
+    let total = legacy + list.as_ref().map_or(0, |list| list.len(env));
+    let mut read_list = list.as_ref().map(|list| list.reader::<u64>(env));
+
+    let mut pos = match (cursor, filter.from) {
+        (Some(cursor), _) => cursor,
+        (None, Some(from)) => first_position_from(env, from, legacy, list.as_ref()),
+        (None, None) => 0,
+    };
+    let scan_end = pos.saturating_add(max_positions);
+
+    while pos < total && pos < scan_end {
+        let id = if pos < legacy {
+            let id = pos + 1;
+            match &index {
+                Some((flag, _, key))
+                    if !env.storage().persistent().has(&(flag.clone(), *key, id)) =>
+                {
+                    None
+                }
+                _ => Some(id),
             }
+        } else {
+            read_list.as_mut().and_then(|read| read(pos - legacy))
+        };
+        pos += 1;
+
+        let Some(entry) = id.and_then(|id| get_audit_entry(env, id)) else {
+            continue;
+        };
+        // Entries are in time order, so nothing later can match.
+        if filter.to.is_some_and(|to| entry.timestamp > to) {
+            return None;
+        }
+        if matches_entry(filter, &entry) && !visit(entry) {
+            break;
         }
     }
-    entries
+
+    if pos < total {
+        Some(pos)
+    } else {
+        None
+    }
 }
 
-/// Gets audit entries filtered by result
-pub fn get_audit_log_by_result(env: &Env, result: AccessResult) -> Vec<AuditEntry> {
+/// Returns one page of audit entries matching `filter`, starting at
+/// `cursor` (or the first entry in range). At most `MAX_PAGE_SCAN`
+/// candidates are examined per call.
+pub fn query_audit_log(
+    env: &Env,
+    filter: &AuditFilter,
+    cursor: Option<u64>,
+    limit: u32,
+) -> AuditPage {
     let mut entries = Vec::new(env);
-    let counter: u64 = env.storage().instance().get(&AUDIT_CTR).unwrap_or(0);
-    let start_id = if counter > 1000 { counter - 1000 } else { 1 };
+    let next_cursor = scan_audit_log(
+        env,
+        filter,
+        cursor,
+        u64::from(pagination::MAX_PAGE_SCAN),
+        |entry| {
+            entries.push_back(entry);
+            entries.len() < limit
+        },
+    );
+    AuditPage {
+        entries,
+        next_cursor,
+    }
+}
 
-    for id in start_id..=counter {
-        if let Some(entry) = get_audit_entry(env, id) {
-            if entry.result == result {
-                entries.push_back(entry);
-            }
-        }
+fn empty_filter(env: &Env) -> AuditFilter {
+    AuditFilter {
+        patient: None,
+        actor: None,
+        record_id: None,
+        actions: Vec::new(env),
+        results: Vec::new(env),
+        from: None,
+        to: None,
     }
-    entries
 }
 
-/// Gets audit entries within a time range
+/// First page of `filter`, scanning the most recent `MAX_PAGE_SCAN` ids
+/// when `recent` is set.
+fn first_page(env: &Env, filter: &AuditFilter, recent: bool) -> Vec<AuditEntry> {
+    let cursor =
+        recent.then(|| audit_count(env).saturating_sub(u64::from(pagination::MAX_PAGE_SCAN)));
+    query_audit_log(env, filter, cursor, pagination::MAX_PAGE_LIMIT).entries
+}
+
+/// Gets the first audit entries for a specific record
+#[deprecated(note = "returns one page only; use `query_audit_log`")]
+pub fn get_record_audit_log(env: &Env, record_id: u64) -> Vec<AuditEntry> {
+    let mut filter = empty_filter(env);
+    filter.record_id = Some(record_id);
+    first_page(env, &filter, false)
+}
+
+/// Gets the first audit entries for a specific user (actor)
+#[deprecated(note = "returns one page only; use `query_audit_log`")]
+pub fn get_user_audit_log(env: &Env, user: &Address) -> Vec<AuditEntry> {
+    let mut filter = empty_filter(env);
+    filter.actor = Some(user.clone());
+    first_page(env, &filter, false)
+}
+
+/// Gets the first audit entries for a specific patient
+#[deprecated(note = "returns one page only; use `query_audit_log`")]
+pub fn get_patient_audit_log(env: &Env, patient: &Address) -> Vec<AuditEntry> {
+    let mut filter = empty_filter(env);
+    filter.patient = Some(patient.clone());
+    first_page(env, &filter, false)
+}
+
+/// Gets the entries of one action type among the most recent ones
+#[deprecated(note = "scans the most recent entries only; use `query_audit_log`")]
+pub fn get_audit_log_by_action(env: &Env, action: AccessAction) -> Vec<AuditEntry> {
+    let mut filter = empty_filter(env);
+    filter.actions.push_back(action);
+    first_page(env, &filter, true)
+}
+
+/// Gets the entries with one result among the most recent ones
+#[deprecated(note = "scans the most recent entries only; use `query_audit_log`")]
+pub fn get_audit_log_by_result(env: &Env, result: AccessResult) -> Vec<AuditEntry> {
+    let mut filter = empty_filter(env);
+    filter.results.push_back(result);
+    first_page(env, &filter, true)
+}
+
+/// Gets the first audit entries within a time range
+#[deprecated(note = "returns one page only; use `query_audit_log`")]
 pub fn get_audit_log_by_time_range(env: &Env, start_time: u64, end_time: u64) -> Vec<AuditEntry> {
-    let mut entries = Vec::new(env);
-    let counter: u64 = env.storage().instance().get(&AUDIT_CTR).unwrap_or(0);
-    let start_id = if counter > 1000 { counter - 1000 } else { 1 };
+    let mut filter = empty_filter(env);
+    filter.from = Some(start_time);
+    filter.to = Some(end_time);
+    first_page(env, &filter, false)
+}
 
-    for id in start_id..=counter {
-        if let Some(entry) = get_audit_entry(env, id) {
-            if entry.timestamp >= start_time && entry.timestamp <= end_time {
-                entries.push_back(entry);
-            }
-        }
+/// Gets recent audit entries (last N entries, at most `MAX_PAGE_LIMIT`)
+#[deprecated(note = "returns one page only; use `query_audit_log`")]
+pub fn get_recent_audit_log(env: &Env, limit: u64) -> Vec<AuditEntry> {
+    let limit = limit.min(u64::from(pagination::MAX_PAGE_LIMIT));
+    if limit == 0 {
+        return Vec::new(env);
     }
-    entries
+    let cursor = audit_count(env).saturating_sub(limit);
+    query_audit_log(env, &empty_filter(env), Some(cursor), limit as u32).entries
 }
 
-/// Gets recent audit entries (last N entries)
-pub fn get_recent_audit_log(env: &Env, limit: u64) -> Vec<AuditEntry> {
-    let mut entries = Vec::new(env);
-    let counter: u64 = env.storage().instance().get(&AUDIT_CTR).unwrap_or(0);
-    let start_id = if counter > limit { counter - limit } else { 1 };
+/// Upper bound on audit entries examined by one access report page. Each
+/// entry costs two reads (the entry and its accessor kind).
+pub const MAX_REPORT_SCAN: u64 = 15;
 
-    for id in start_id..=counter {
-        if let Some(entry) = get_audit_entry(env, id) {
-            entries.push_back(entry);
+/// How an accessor reached the patient's data.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[repr(u32)]
+pub enum AccessorKind {
+    /// Holds a patient-level grant or consent.
+    Grantee = 1,
+    /// Holds a role or scoped delegation from the patient.
+    Delegate = 2,
+    /// Used break-glass access during the reporting window.
+    Emergency = 3,
+    /// Reads through an RBAC permission (`ReadAnyRecord` or `SystemAdmin`).
+    Privileged = 4,
+    /// Record authors, record-level grantees and anyone else.
+    Other = 5,
+}
+
+/// Aggregated activity of one accessor in an access report.
+#[contracttype]
+#[derive(Clone, Debug)]
+pub struct AccessReportEntry {
+    pub accessor: Address,
+    pub kind: AccessorKind,
+    /// Successful accesses in the window.
+    pub access_count: u32,
+    /// Denied or failed attempts in the window.
+    pub denied_count: u32,
+    /// Timestamp of the most recent attempt, successful or not.
+    pub last_access: u64,
+}
+
+/// Accounting of disclosures for a patient over `from..=to`, or one page of
+/// it. Pages are merged by accessor: add the counts, keep the latest
+/// `last_access`, and keep the latest `kind` unless a page says `Emergency`.
+#[contracttype]
+#[derive(Clone, Debug)]
+pub struct AccessReport {
+    pub patient: Address,
+    pub from: u64,
+    pub to: u64,
+    pub entries: Vec<AccessReportEntry>,
+    /// Pass back as `cursor` for the rest of the window; `None` once the
+    /// window is covered.
+    pub next_cursor: Option<u64>,
+}
+
+/// Aggregates the audit entries about `patient` in `from..=to` by actor,
+/// excluding the patient's own activity, examining at most
+/// `MAX_REPORT_SCAN` entries of the patient's audit index from `cursor`.
+///
+/// Each accessor is reported with the kind recorded with their latest entry,
+/// or `Emergency` if any entry was a break-glass access. `classify` supplies
+/// the kind of accessors whose entries predate recorded kinds.
+pub fn build_access_report(
+    env: &Env,
+    patient: &Address,
+    from: u64,
+    to: u64,
+    cursor: Option<u64>,
+    classify: impl Fn(&Address) -> AccessorKind,
+) -> AccessReport {
+    let mut filter = empty_filter(env);
+    filter.patient = Some(patient.clone());
+    filter.from = Some(from);
+    filter.to = Some(to);
+    let mut totals: Map<Address, AccessReportEntry> = Map::new(env);
+
+    let next_cursor = scan_audit_log(env, &filter, cursor, MAX_REPORT_SCAN, |entry| {
+        if entry.actor == *patient {
+            return true;
+        }
+        let recorded = get_accessor_kind(env, entry.id);
+        let mut total = totals
+            .get(entry.actor.clone())
+            .unwrap_or_else(|| AccessReportEntry {
+                accessor: entry.actor.clone(),
+                kind: recorded.clone().unwrap_or_else(|| classify(&entry.actor)),
+                access_count: 0,
+                denied_count: 0,
+                last_access: 0,
+            });
+        if entry.result == AccessResult::Success {
+            total.access_count = total.access_count.saturating_add(1);
+        } else {
+            total.denied_count = total.denied_count.saturating_add(1);
+        }
+        total.last_access = entry.timestamp;
+        if let Some(kind) = recorded {
+            if total.kind != AccessorKind::Emergency {
+                total.kind = kind;
+            }
         }
+        totals.set(entry.actor.clone(), total);
+        true
+    });
+
+    AccessReport {
+        patient: patient.clone(),
+        from,
+        to,
+        entries: totals.values(),
+        next_cursor,
     }
-    entries
 }
 
 /// Helper function to create an audit entry
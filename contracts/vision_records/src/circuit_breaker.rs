@@ -1,3 +1,18 @@
+//! Contract-wide and per-function pauses.
+//!
+//! Besides manual pauses by operators, trip rules pause a scope
+//! automatically when one caller produces a signal (denied accesses, batch
+//! grant calls) more than `threshold` times within `window_seconds`,
+//! counted per caller with the risk engine's behavior tracking. Counting
+//! per caller keeps a caller's signals from adding up with everyone
+//! else's. An automatic pause stops applying once its cooldown ends unless
+//! an operator confirms it.
+//!
+//! Signals are recorded with the work that produced them, so only signals
+//! from invocations that succeed can trip a rule. Denials are counted when
+//! their audit entry is written, which is kept only for reads that return
+//! the refusal instead of failing (`get_record_for_purpose`).
+
 use crate::{
     events,
     rbac::{self, Permission},
@@ -5,6 +20,7 @@ use crate::{
 };
 use soroban_sdk::{contracttype, symbol_short, Address, Env, Symbol};
 use teye_common::admin_tiers::{self, AdminTier};
+use teye_common::risk_engine;
 
 // ── Types ─────────────────────────────────────────────────────
 
@@ -18,6 +34,46 @@ pub enum PauseScope {
     Function(Symbol),
 }
 
+/// Signals that can trip the breaker automatically.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[repr(u32)]
+pub enum TripTrigger {
+    /// An audited access attempt was denied, and the denial was kept.
+    AccessDenied = 1,
+    /// `grant_access_batch` was called.
+    BatchGrantBurst = 2,
+}
+
+/// Pause `scope` once `trigger` fires more than `threshold` times within
+/// `window_seconds`.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TripRule {
+    pub trigger: TripTrigger,
+    pub threshold: u32,
+    pub window_seconds: u64,
+    pub scope: PauseScope,
+    /// How long the automatic pause lasts unless confirmed.
+    pub cooldown_seconds: u64,
+}
+
+/// A pause engaged by a trip rule.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AutoPause {
+    pub scope: PauseScope,
+    pub trigger: TripTrigger,
+    /// Caller whose signals tripped the rule.
+    pub tripped_by: Address,
+    /// Signals counted for that caller in the window when the rule tripped.
+    pub observed: u32,
+    pub tripped_at: u64,
+    pub resume_at: u64,
+    /// Operator who confirmed the pause; it then stays until resumed.
+    pub confirmed_by: Option<Address>,
+}
+
 // ── Storage Keys ─────────────────────────────────────────────
 
 pub fn global_pause_key() -> Symbol {
@@ -28,28 +84,34 @@ pub fn function_pause_key(func: &Symbol) -> (Symbol, Symbol) {
     (symbol_short!("P_FUNC"), func.clone())
 }
 
+fn trip_rule_key(trigger: &TripTrigger) -> (Symbol, TripTrigger) {
+    (symbol_short!("CB_RULE"), trigger.clone())
+}
+
+fn auto_pause_key(scope: &PauseScope) -> (Symbol, PauseScope) {
+    (symbol_short!("CB_AUTO"), scope.clone())
+}
+
+fn trigger_symbol(trigger: &TripTrigger) -> Symbol {
+    match trigger {
+        TripTrigger::AccessDenied => symbol_short!("CB_DENY"),
+        TripTrigger::BatchGrantBurst => symbol_short!("CB_BATCH"),
+    }
+}
+
 // ── Core Logistics ───────────────────────────────────────────
 
 /// Asserts the specified scope is currently active and not halted. Automatically evaluates Global halts simultaneously.
+/// Only reads state, so read-only calls can use it too.
 pub fn require_not_paused(env: &Env, scope: &PauseScope) -> Result<(), ContractError> {
     // 1. Check Global
-    if env
-        .storage()
-        .instance()
-        .get(&global_pause_key())
-        .unwrap_or(false)
-    {
+    if is_paused(env, &PauseScope::Global) {
         return Err(ContractError::Paused);
     }
 
     // 2. Check Specific Scope
-    if let PauseScope::Function(func_name) = scope {
-        if env
-            .storage()
-            .instance()
-            .get(&function_pause_key(func_name))
-            .unwrap_or(false)
-        {
+    if let PauseScope::Function(_) = scope {
+        if is_paused(env, scope) {
             return Err(ContractError::Paused);
         }
     }
@@ -57,25 +119,59 @@ pub fn require_not_paused(env: &Env, scope: &PauseScope) -> Result<(), ContractE
     Ok(())
 }
 
-/// Engages a circuit breaker for the specified scope.
-/// Requires at least `OperatorAdmin` tier, or the existing SystemAdmin RBAC permission.
-pub fn pause_contract(env: &Env, caller: &Address, scope: PauseScope) -> Result<(), ContractError> {
-    let has_tier = admin_tiers::require_tier(env, caller, &AdminTier::OperatorAdmin);
-    let has_rbac = rbac::has_permission(env, caller, &Permission::SystemAdmin);
-    if !has_tier && !has_rbac {
-        return Err(ContractError::Unauthorized);
+/// Whether `scope` is paused. A pause set by a trip rule stops counting once
+/// its cooldown ends, unless confirmed; its stored flag is left in place.
+fn is_paused(env: &Env, scope: &PauseScope) -> bool {
+    let flagged = match scope {
+        PauseScope::Global => env.storage().instance().get(&global_pause_key()),
+        PauseScope::Function(func_name) => {
+            env.storage().instance().get(&function_pause_key(func_name))
+        }
     }
+    .unwrap_or(false);
+    flagged && !auto_pause_lapsed(env, scope)
+}
+
+/// True if `scope`'s automatic pause is unconfirmed and past its cooldown.
+fn auto_pause_lapsed(env: &Env, scope: &PauseScope) -> bool {
+    env.storage()
+        .instance()
+        .get::<_, AutoPause>(&auto_pause_key(scope))
+        .is_some_and(|auto| {
+            auto.confirmed_by.is_none() && env.ledger().timestamp() >= auto.resume_at
+        })
+}
 
-    match &scope {
+fn set_paused(env: &Env, scope: &PauseScope, paused: bool) {
+    match scope {
         PauseScope::Global => {
-            env.storage().instance().set(&global_pause_key(), &true);
+            env.storage().instance().set(&global_pause_key(), &paused);
         }
         PauseScope::Function(func_name) => {
             env.storage()
                 .instance()
-                .set(&function_pause_key(func_name), &true);
+                .set(&function_pause_key(func_name), &paused);
         }
     }
+}
+
+fn require_operator(env: &Env, caller: &Address) -> Result<(), ContractError> {
+    let has_tier = admin_tiers::require_tier(env, caller, &AdminTier::OperatorAdmin);
+    let has_rbac = rbac::has_permission(env, caller, &Permission::SystemAdmin);
+    if !has_tier && !has_rbac {
+        return Err(ContractError::Unauthorized);
+    }
+    Ok(())
+}
+
+/// Engages a circuit breaker for the specified scope.
+/// Requires at least `OperatorAdmin` tier, or the existing SystemAdmin RBAC permission.
+/// A manual pause replaces any automatic pause of the same scope.
+pub fn pause_contract(env: &Env, caller: &Address, scope: PauseScope) -> Result<(), ContractError> {
+    require_operator(env, caller)?;
+
+    set_paused(env, &scope, true);
+    env.storage().instance().remove(&auto_pause_key(&scope));
 
     events::publish_contract_paused(env, caller.clone(), scope);
 
@@ -89,24 +185,94 @@ pub fn resume_contract(
     caller: &Address,
     scope: PauseScope,
 ) -> Result<(), ContractError> {
-    let has_tier = admin_tiers::require_tier(env, caller, &AdminTier::OperatorAdmin);
-    let has_rbac = rbac::has_permission(env, caller, &Permission::SystemAdmin);
-    if !has_tier && !has_rbac {
-        return Err(ContractError::Unauthorized);
-    }
+    require_operator(env, caller)?;
 
-    match &scope {
-        PauseScope::Global => {
-            env.storage().instance().set(&global_pause_key(), &false);
-        }
-        PauseScope::Function(func_name) => {
-            env.storage()
-                .instance()
-                .set(&function_pause_key(func_name), &false);
-        }
-    }
+    set_paused(env, &scope, false);
+    env.storage().instance().remove(&auto_pause_key(&scope));
 
     events::publish_contract_resumed(env, caller.clone(), scope);
 
     Ok(())
 }
+
+// ── Automatic tripping ───────────────────────────────────────
+
+/// Installs or replaces the rule for `rule.trigger`. Same authorization as
+/// `pause_contract`.
+pub fn set_trip_rule(env: &Env, caller: &Address, rule: TripRule) -> Result<(), ContractError> {
+    require_operator(env, caller)?;
+    if rule.threshold == 0 || rule.window_seconds == 0 || rule.cooldown_seconds == 0 {
+        return Err(ContractError::InvalidInput);
+    }
+    env.storage()
+        .instance()
+        .set(&trip_rule_key(&rule.trigger), &rule);
+    Ok(())
+}
+
+pub fn remove_trip_rule(
+    env: &Env,
+    caller: &Address,
+    trigger: &TripTrigger,
+) -> Result<(), ContractError> {
+    require_operator(env, caller)?;
+    env.storage().instance().remove(&trip_rule_key(trigger));
+    Ok(())
+}
+
+pub fn get_trip_rule(env: &Env, trigger: &TripTrigger) -> Option<TripRule> {
+    env.storage().instance().get(&trip_rule_key(trigger))
+}
+
+/// The automatic pause of `scope`, while it is in effect.
+pub fn get_auto_pause(env: &Env, scope: &PauseScope) -> Option<AutoPause> {
+    if auto_pause_lapsed(env, scope) {
+        return None;
+    }
+    env.storage().instance().get(&auto_pause_key(scope))
+}
+
+/// Keeps an automatic pause in place past its cooldown. It then lasts until
+/// an operator calls `resume_contract`.
+pub fn confirm_auto_pause(
+    env: &Env,
+    caller: &Address,
+    scope: &PauseScope,
+) -> Result<(), ContractError> {
+    require_operator(env, caller)?;
+    let mut auto = get_auto_pause(env, scope).ok_or(ContractError::InvalidInput)?;
+    auto.confirmed_by = Some(caller.clone());
+    env.storage().instance().set(&auto_pause_key(scope), &auto);
+    events::publish_contract_paused(env, caller.clone(), scope.clone());
+    Ok(())
+}
+
+/// Counts one occurrence of `trigger` by `caller` and pauses the rule's
+/// scope when the caller's count crosses the threshold. Does nothing
+/// without a rule for `trigger`.
+pub fn record_signal(env: &Env, trigger: TripTrigger, caller: &Address) {
+    let Some(rule) = get_trip_rule(env, &trigger) else {
+        return;
+    };
+    let state =
+        risk_engine::track_behavior(env, caller, trigger_symbol(&trigger), rule.window_seconds);
+    if state.count <= rule.threshold || is_paused(env, &rule.scope) {
+        return;
+    }
+
+    let now = env.ledger().timestamp();
+    let auto = AutoPause {
+        scope: rule.scope.clone(),
+        trigger,
+        tripped_by: caller.clone(),
+        observed: state.count,
+        tripped_at: now,
+        resume_at: now.saturating_add(rule.cooldown_seconds),
+        confirmed_by: None,
+    };
+    set_paused(env, &rule.scope, true);
+    env.storage()
+        .instance()
+        .set(&auto_pause_key(&rule.scope), &auto);
+    events::publish_circuit_breaker_tripped(env, &auto, rule.window_seconds);
+}
@@ -1,10 +1,17 @@
 use soroban_sdk::{contracttype, symbol_short, Address, Env, String, Symbol, Vec};
 
+use crate::pagination::BucketList;
+use crate::patient_profile::EmergencyContact;
+
 // ── Storage keys ──────────────────────────────────────────────
 pub const EMRG_CTR: Symbol = symbol_short!("EMRG_CTR");
 const EMRG_ACCESS: Symbol = symbol_short!("EMRG_ACC");
 const EMRG_AUDIT: Symbol = symbol_short!("EMRG_AUD");
-const EMRG_PATIENT: Symbol = symbol_short!("EMRG_PAT");
+const EMRG_PATIENT_LIST: Symbol = symbol_short!("EMRG_PATL");
+const EMRG_PAIR: Symbol = symbol_short!("EMRG_PAIR");
+
+/// Longest break-glass window a single request may ask for (24 hours).
+pub const MAX_EMERGENCY_DURATION: u64 = 86_400;
 
 const TTL_THRESHOLD: u32 = 5184000;
 const TTL_EXTEND_TO: u32 = 10368000;
@@ -16,8 +23,8 @@ fn extend_ttl_emergency_key(env: &Env, key: &(Symbol, u64)) {
         .extend_ttl(key, TTL_THRESHOLD, TTL_EXTEND_TO);
 }
 
-/// Extends the time-to-live (TTL) for emergency access by patient keys.
-fn extend_ttl_emergency_patient_key(env: &Env, key: &(Symbol, Address, u64)) {
+/// Extends the time-to-live (TTL) for emergency access by patient-requester keys.
+fn extend_ttl_emergency_pair_key(env: &Env, key: &(Symbol, Address, Address)) {
     env.storage()
         .persistent()
         .extend_ttl(key, TTL_THRESHOLD, TTL_EXTEND_TO);
@@ -57,7 +64,8 @@ pub struct EmergencyAccess {
     pub granted_at: u64,
     pub expires_at: u64,
     pub status: EmergencyStatus,
-    pub notified_contacts: Vec<Address>,
+    /// Emergency contacts told about this grant, each listed once.
+    pub notified_contacts: Vec<EmergencyContact>,
 }
 
 /// Immutable audit entry — written once, never deleted
@@ -83,13 +91,53 @@ pub fn increment_emergency_counter(env: &Env) -> u64 {
 /// Stores an emergency access grant
 pub fn set_emergency_access(env: &Env, access: &EmergencyAccess) {
     let key = (EMRG_ACCESS, access.id);
+    let is_new = !env.storage().persistent().has(&key);
     env.storage().persistent().set(&key, access);
     extend_ttl_emergency_key(env, &key);
 
-    // Also index by patient for quick lookup
-    let patient_key = (EMRG_PATIENT, access.patient.clone(), access.id);
-    env.storage().persistent().set(&patient_key, &true);
-    extend_ttl_emergency_patient_key(env, &patient_key);
+    // Also list by patient for quick lookup
+    if is_new {
+        patient_list(&access.patient).push(env, &access.id);
+    }
+
+    // Index the live grants of each patient-requester pair so access checks
+    // do not depend on how many grants were issued since.
+    if access.status == EmergencyStatus::Active {
+        let mut ids = live_pair_ids(env, &access.patient, &access.requester);
+        if !ids.contains(access.id) {
+            ids.push_back(access.id);
+        }
+        set_pair_ids(env, &access.patient, &access.requester, &ids);
+    }
+}
+
+/// Ids of the pair's grants that are still active and unexpired.
+fn live_pair_ids(env: &Env, patient: &Address, requester: &Address) -> Vec<u64> {
+    let ids: Vec<u64> = env
+        .storage()
+        .persistent()
+        .get(&(EMRG_PAIR, patient.clone(), requester.clone()))
+        .unwrap_or(Vec::new(env));
+    let now = env.ledger().timestamp();
+    let mut live = Vec::new(env);
+    for id in ids.iter() {
+        if let Some(access) = get_emergency_access(env, id) {
+            if access.status == EmergencyStatus::Active && access.expires_at > now {
+                live.push_back(id);
+            }
+        }
+    }
+    live
+}
+
+fn set_pair_ids(env: &Env, patient: &Address, requester: &Address, ids: &Vec<u64>) {
+    let key = (EMRG_PAIR, patient.clone(), requester.clone());
+    if ids.is_empty() {
+        env.storage().persistent().remove(&key);
+    } else {
+        env.storage().persistent().set(&key, ids);
+        extend_ttl_emergency_pair_key(env, &key);
+    }
 }
 
 /// Retrieves an emergency access grant by ID
@@ -104,24 +152,9 @@ pub fn has_active_emergency_access(
     patient: &Address,
     requester: &Address,
 ) -> Option<EmergencyAccess> {
-    // We need to iterate through potential access IDs
-    // For efficiency, we'll check recent IDs (last 100)
-    let counter: u64 = env.storage().instance().get(&EMRG_CTR).unwrap_or(0);
-    let start_id = if counter > 100 { counter - 100 } else { 1 };
-
-    for id in start_id..=counter {
-        let key = (EMRG_ACCESS, id);
-        if let Some(access) = env.storage().persistent().get::<_, EmergencyAccess>(&key) {
-            if access.patient == *patient
-                && access.requester == *requester
-                && access.status == EmergencyStatus::Active
-                && access.expires_at > env.ledger().timestamp()
-            {
-                return Some(access);
-            }
-        }
-    }
-    None
+    live_pair_ids(env, patient, requester)
+        .first()
+        .and_then(|id| get_emergency_access(env, id))
 }
 
 /// Revokes an emergency access grant
@@ -131,6 +164,8 @@ pub fn revoke_emergency_access(env: &Env, access_id: u64) -> Option<EmergencyAcc
         access.status = EmergencyStatus::Revoked;
         env.storage().persistent().set(&key, &access);
         extend_ttl_emergency_key(env, &key);
+        let ids = live_pair_ids(env, &access.patient, &access.requester);
+        set_pair_ids(env, &access.patient, &access.requester, &ids);
         Some(access)
     } else {
         None
@@ -172,21 +207,27 @@ pub fn get_audit_entries(env: &Env, access_id: u64) -> Vec<EmergencyAuditEntry>
         .unwrap_or(Vec::new(env))
 }
 
-/// Gets all active emergency accesses for a patient
-pub fn get_patient_emergency_accesses(env: &Env, patient: &Address) -> Vec<EmergencyAccess> {
-    let mut accesses = Vec::new(env);
-    let counter: u64 = env.storage().instance().get(&EMRG_CTR).unwrap_or(0);
-    let start_id = if counter > 100 { counter - 100 } else { 1 };
+fn patient_list(patient: &Address) -> BucketList<Address> {
+    BucketList::new(EMRG_PATIENT_LIST, patient.clone())
+}
 
-    for id in start_id..=counter {
-        let key = (EMRG_ACCESS, id);
-        if let Some(access) = env.storage().persistent().get::<_, EmergencyAccess>(&key) {
-            if access.patient == *patient && access.status == EmergencyStatus::Active {
-                accesses.push_back(access);
-            }
-        }
-    }
-    accesses
+/// Page through a patient's emergency access grants in the order they were
+/// issued, keeping the ones `keep` accepts.
+pub fn page_patient_emergency_accesses(
+    env: &Env,
+    patient: &Address,
+    cursor: Option<u64>,
+    limit: u32,
+    keep: impl FnMut(&EmergencyAccess) -> bool,
+) -> (Vec<EmergencyAccess>, Option<u64>) {
+    patient_list(patient).page_map(
+        env,
+        cursor,
+        limit,
+        false,
+        |id| get_emergency_access(env, id),
+        keep,
+    )
 }
 
 /// Expires emergency accesses that have passed their expiration time
@@ -0,0 +1,273 @@
+//! Per-record key envelopes.
+//!
+//! A record's data key (the [`DerivedKey`] the key manager derives for it,
+//! see [`data_key`]) is wrapped separately for the patient and for every record-level grantee
+//! that has registered an encryption public key. Wrapping is ECIES over
+//! BLS12-381 G1: an ephemeral scalar `r` gives the shared point `r·PK`,
+//! which is hashed into a one-time key-encryption key. Recipients recover
+//! the same point as `sk·R` from the stored ephemeral point `R = r·G`.
+//!
+//! The scalar `r` must stay secret, and nothing in a contract invocation
+//! is, so wrapping happens off-chain: whoever holds the data key calls
+//! [`wrap`] and submits the ephemeral point and wrapped key. The contract
+//! only keeps the header, checks submissions against it, and stores them.
+//!
+//! Deleting a wrapped key does not take the data key back from a recipient
+//! who already unwrapped it, so revoking a recipient re-keys the envelope:
+//! its epoch moves on, giving a new data key, and every wrapped key is
+//! dropped to be wrapped again for those who keep access. Amending a record
+//! under a newer key version re-keys it the same way.
+
+use key_manager::DerivedKey;
+use soroban_sdk::{
+    contracttype,
+    crypto::bls12_381::{Fr, G1Affine},
+    symbol_short, Address, Bytes, BytesN, Env, Symbol, Vec,
+};
+
+// ── Storage keys ──────────────────────────────────────────────
+const ENC_PUB: Symbol = symbol_short!("ENC_PUB");
+const ENV_HDR: Symbol = symbol_short!("ENV_HDR");
+const ENV_KEY: Symbol = symbol_short!("ENV_KEY");
+
+const TTL_THRESHOLD: u32 = 5184000;
+const TTL_EXTEND_TO: u32 = 10368000;
+
+/// Uncompressed BLS12-381 G1 generator.
+const G1_GENERATOR: [u8; 96] = [
+    0x17, 0xf1, 0xd3, 0xa7, 0x31, 0x97, 0xd7, 0x94, 0x26, 0x95, 0x63, 0x8c, 0x4f, 0xa9, 0xac, 0x0f,
+    0xc3, 0x68, 0x8c, 0x4f, 0x97, 0x74, 0xb9, 0x05, 0xa1, 0x4e, 0x3a, 0x3f, 0x17, 0x1b, 0xac, 0x58,
+    0x6c, 0x55, 0xe8, 0x3f, 0xf9, 0x7a, 0x1a, 0xef, 0xfb, 0x3a, 0xf0, 0x0a, 0xdb, 0x22, 0xc6, 0xbb,
+    0x08, 0xb3, 0xf4, 0x81, 0xe3, 0xaa, 0xa0, 0xf1, 0xa0, 0x9e, 0x30, 0xed, 0x74, 0x1d, 0x8a, 0xe4,
+    0xfc, 0xf5, 0xe0, 0x95, 0xd5, 0xd0, 0x0a, 0xf6, 0x00, 0xdb, 0x18, 0xcb, 0x2c, 0x04, 0xb3, 0xed,
+    0xd0, 0x3c, 0xc7, 0x44, 0xa2, 0x88, 0x8a, 0xe4, 0x0c, 0xaa, 0x23, 0x29, 0x46, 0xc5, 0xe7, 0xe1,
+];
+
+fn extend_ttl_address_key(env: &Env, key: &(Symbol, Address)) {
+    env.storage()
+        .persistent()
+        .extend_ttl(key, TTL_THRESHOLD, TTL_EXTEND_TO);
+}
+
+fn extend_ttl_record_key(env: &Env, key: &(Symbol, u64)) {
+    env.storage()
+        .persistent()
+        .extend_ttl(key, TTL_THRESHOLD, TTL_EXTEND_TO);
+}
+
+fn extend_ttl_wrapped_key(env: &Env, key: &(Symbol, u64, Address)) {
+    env.storage()
+        .persistent()
+        .extend_ttl(key, TTL_THRESHOLD, TTL_EXTEND_TO);
+}
+
+// ── Types ─────────────────────────────────────────────────────
+
+/// Envelope header for a record: which data key version is wrapped and for
+/// whom.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct KeyEnvelope {
+    pub record_id: u64,
+    pub key_version: u32,
+    /// Number of times the record was re-keyed after a revocation.
+    pub key_epoch: u32,
+    /// SHA-256 of the data key, so recipients can check what they unwrap.
+    pub key_commitment: BytesN<32>,
+    pub recipients: Vec<Address>,
+}
+
+/// The record data key wrapped for one recipient.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct WrappedKey {
+    pub recipient: Address,
+    pub key_version: u32,
+    /// Ephemeral point `R = r·G`, uncompressed.
+    pub ephemeral: BytesN<96>,
+    pub wrapped_key: BytesN<32>,
+    pub wrapped_at: u64,
+}
+
+// ── Public keys ───────────────────────────────────────────────
+
+/// Returns `secret·G`, the public key for `secret`.
+pub fn public_key_for(env: &Env, secret: &Fr) -> BytesN<96> {
+    let generator = G1Affine::from_array(env, &G1_GENERATOR);
+    env.crypto()
+        .bls12_381()
+        .g1_mul(&generator, secret)
+        .to_bytes()
+}
+
+/// True if `key` is a valid G1 point in the prime-order subgroup.
+pub fn is_valid_public_key(env: &Env, key: &BytesN<96>) -> bool {
+    env.crypto()
+        .bls12_381()
+        .g1_is_in_subgroup(&G1Affine::from_bytes(key.clone()))
+}
+
+pub fn set_public_key(env: &Env, user: &Address, key: &BytesN<96>) {
+    let storage_key = (ENC_PUB, user.clone());
+    env.storage().persistent().set(&storage_key, key);
+    extend_ttl_address_key(env, &storage_key);
+}
+
+pub fn get_public_key(env: &Env, user: &Address) -> Option<BytesN<96>> {
+    env.storage().persistent().get(&(ENC_PUB, user.clone()))
+}
+
+// ── Data keys ─────────────────────────────────────────────────
+
+/// The data key wrapped at `epoch`: the key manager's key for the record,
+/// hashed with the epoch once the record has been re-keyed.
+pub fn data_key(env: &Env, record_key: &DerivedKey, epoch: u32) -> DerivedKey {
+    if epoch == 0 {
+        return record_key.clone();
+    }
+    let mut material = Bytes::from_array(env, &record_key.key.to_array());
+    material.extend_from_array(&epoch.to_be_bytes());
+    DerivedKey {
+        key: env.crypto().sha256(&material).to_bytes(),
+        version: record_key.version,
+    }
+}
+
+// ── Wrapping ──────────────────────────────────────────────────
+
+fn key_encryption_key(env: &Env, shared: &G1Affine, record_id: u64, version: u32) -> [u8; 32] {
+    let mut material = Bytes::from_array(env, &shared.to_array());
+    material.extend_from_array(&record_id.to_be_bytes());
+    material.extend_from_array(&version.to_be_bytes());
+    env.crypto().sha256(&material).to_array()
+}
+
+fn xor(a: &[u8; 32], b: &[u8; 32]) -> [u8; 32] {
+    let mut out = [0u8; 32];
+    for (i, byte) in out.iter_mut().enumerate() {
+        *byte = a[i] ^ b[i];
+    }
+    out
+}
+
+/// Wraps `data_key` for the holder of `public_key` with the ephemeral scalar
+/// `ephemeral_secret`, returning the ephemeral point and the wrapped key.
+/// Runs wherever the data key lives, with a fresh secret scalar per call;
+/// the contract never calls it.
+pub fn wrap(
+    env: &Env,
+    public_key: &BytesN<96>,
+    record_id: u64,
+    data_key: &DerivedKey,
+    ephemeral_secret: &Fr,
+) -> (BytesN<96>, BytesN<32>) {
+    let shared = env
+        .crypto()
+        .bls12_381()
+        .g1_mul(&G1Affine::from_bytes(public_key.clone()), ephemeral_secret);
+    let kek = key_encryption_key(env, &shared, record_id, data_key.version);
+    (
+        public_key_for(env, ephemeral_secret),
+        BytesN::from_array(env, &xor(&data_key.key.to_array(), &kek)),
+    )
+}
+
+/// Recovers the data key from `wrapped` with the recipient's secret scalar.
+/// Runs wherever the secret lives; the contract never calls it.
+pub fn unwrap(env: &Env, wrapped: &WrappedKey, record_id: u64, secret: &Fr) -> DerivedKey {
+    let shared = env
+        .crypto()
+        .bls12_381()
+        .g1_mul(&G1Affine::from_bytes(wrapped.ephemeral.clone()), secret);
+    let kek = key_encryption_key(env, &shared, record_id, wrapped.key_version);
+    DerivedKey {
+        key: BytesN::from_array(env, &xor(&wrapped.wrapped_key.to_array(), &kek)),
+        version: wrapped.key_version,
+    }
+}
+
+// ── Envelope storage ──────────────────────────────────────────
+
+pub fn get_envelope(env: &Env, record_id: u64) -> Option<KeyEnvelope> {
+    env.storage().persistent().get(&(ENV_HDR, record_id))
+}
+
+fn set_envelope(env: &Env, envelope: &KeyEnvelope) {
+    let key = (ENV_HDR, envelope.record_id);
+    env.storage().persistent().set(&key, envelope);
+    extend_ttl_record_key(env, &key);
+}
+
+pub fn get_wrapped_key(env: &Env, record_id: u64, recipient: &Address) -> Option<WrappedKey> {
+    env.storage()
+        .persistent()
+        .get(&(ENV_KEY, record_id, recipient.clone()))
+}
+
+/// Writes a fresh envelope header for `record_key` at `epoch`, dropping
+/// every key wrapped under `previous`.
+fn start(
+    env: &Env,
+    record_id: u64,
+    record_key: &DerivedKey,
+    epoch: u32,
+    previous: Option<KeyEnvelope>,
+) {
+    if let Some(previous) = previous {
+        for recipient in previous.recipients.iter() {
+            env.storage()
+                .persistent()
+                .remove(&(ENV_KEY, record_id, recipient));
+        }
+    }
+
+    let key = data_key(env, record_key, epoch);
+    set_envelope(
+        env,
+        &KeyEnvelope {
+            record_id,
+            key_version: record_key.version,
+            key_epoch: epoch,
+            key_commitment: env
+                .crypto()
+                .sha256(&Bytes::from_array(env, &key.key.to_array()))
+                .to_bytes(),
+            recipients: Vec::new(env),
+        },
+    );
+}
+
+/// Starts a new envelope for `record_id` under `record_key`, unless its
+/// envelope already holds that key version. Re-keying drops every wrapped
+/// key, since they wrap the previous key; they are wrapped again off-chain.
+pub fn seal(env: &Env, record_id: u64, record_key: &DerivedKey) {
+    let previous = get_envelope(env, record_id);
+    let epoch = match &previous {
+        Some(previous) if previous.key_version == record_key.version => return,
+        Some(previous) => previous.key_epoch,
+        None => 0,
+    };
+    start(env, record_id, record_key, epoch, previous);
+}
+
+/// Moves the envelope to the next epoch under `record_key`, its current
+/// key version, and drops every wrapped key. Returns the new epoch, or
+/// `None` if the record has no envelope.
+pub fn rekey(env: &Env, record_id: u64, record_key: &DerivedKey) -> Option<u32> {
+    let previous = get_envelope(env, record_id)?;
+    let epoch = previous.key_epoch.saturating_add(1);
+    start(env, record_id, record_key, epoch, Some(previous));
+    Some(epoch)
+}
+
+/// Stores a key wrapped off-chain for `wrapped.recipient` and lists the
+/// recipient on the envelope, replacing any earlier copy.
+pub fn add_wrapped_key(env: &Env, envelope: &mut KeyEnvelope, wrapped: &WrappedKey) {
+    let key = (ENV_KEY, envelope.record_id, wrapped.recipient.clone());
+    env.storage().persistent().set(&key, wrapped);
+    extend_ttl_wrapped_key(env, &key);
+    if !envelope.recipients.contains(&wrapped.recipient) {
+        envelope.recipients.push_back(wrapped.recipient.clone());
+    }
+    set_envelope(env, envelope);
+}
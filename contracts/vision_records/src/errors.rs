@@ -116,6 +116,35 @@ pub enum ContractError {
     VersionConflict = 37,
     ConflictQueued = 38,
     ConflictNotFound = 39,
+    AppointmentConflict = 40,
+    PrescriptionNotFound = 41,
+    InvalidPrescription = 42,
+    PrescriptionExpired = 43,
+    InvalidPrescriptionStatus = 44,
+    EmergencyAccessExpired = 45,
+    EmergencyAccessRevoked = 46,
+    ProviderNotVerified = 47,
+    ProviderLicenseInvalid = 48,
+    RecordRetracted = 49,
+    InvalidIntraocularPressure = 50,
+    InvalidVisualAcuity = 51,
+    MissingEyeData = 52,
+    GuardianshipNotFound = 53,
+    ConsentPurposeMismatch = 54,
+    AttachmentNotFound = 55,
+    ImportJobNotFound = 56,
+    ImportJobClosed = 57,
+    ShareTokenNotFound = 58,
+    ShareTokenExpired = 59,
+    ShareTokenInactive = 60,
+    ReferralNotFound = 61,
+    InvalidReferralStatus = 62,
+    PolicyAnalysisFailed = 63,
+    PolicyVersionNotFound = 64,
+    PolicyRolloutNotFound = 65,
+    ImportChunkMismatch = 66,
+    PolicyAttached = 67,
+    DecryptionFailed = 68,
 }
 
 impl ContractError {
@@ -138,6 +167,13 @@ impl ContractError {
             | ContractError::InvalidAppointmentTime
             | ContractError::InvalidAppointmentStatus
             | ContractError::AppointmentNotVerified
+            | ContractError::InvalidPrescription
+            | ContractError::PrescriptionExpired
+            | ContractError::InvalidPrescriptionStatus
+            | ContractError::ProviderLicenseInvalid
+            | ContractError::InvalidIntraocularPressure
+            | ContractError::InvalidVisualAcuity
+            | ContractError::MissingEyeData
             | ContractError::MetaTxExpired => ErrorCategory::Validation,
             ContractError::VersionConflict | ContractError::ConflictQueued => {
                 ErrorCategory::StateConflict
@@ -147,18 +183,39 @@ impl ContractError {
             | ContractError::InsufficientPermissions
             | ContractError::ExpiredAccess
             | ContractError::ConsentRequired
-            | ContractError::ConsentExpired => ErrorCategory::Authorization,
+            | ContractError::ConsentExpired
+            | ContractError::ConsentPurposeMismatch
+            | ContractError::EmergencyAccessExpired
+            | ContractError::EmergencyAccessRevoked
+            | ContractError::ProviderNotVerified => ErrorCategory::Authorization,
             ContractError::UserNotFound
             | ContractError::RecordNotFound
             | ContractError::ProviderNotFound
             | ContractError::EmergencyAccessNotFound
-            | ContractError::AppointmentNotFound => ErrorCategory::NotFound,
+            | ContractError::AppointmentNotFound
+            | ContractError::PrescriptionNotFound => ErrorCategory::NotFound,
             ContractError::ProviderAlreadyRegistered
             | ContractError::DuplicateRecord
             | ContractError::DelegationExpired
-            | ContractError::NonceAlreadyUsed => ErrorCategory::StateConflict,
-            ContractError::ConflictNotFound => ErrorCategory::NotFound,
-            ContractError::StorageError => ErrorCategory::Storage,
+            | ContractError::NonceAlreadyUsed
+            | ContractError::AppointmentConflict
+            | ContractError::RecordRetracted => ErrorCategory::StateConflict,
+            ContractError::ConflictNotFound
+            | ContractError::GuardianshipNotFound
+            | ContractError::AttachmentNotFound
+            | ContractError::ImportJobNotFound
+            | ContractError::ShareTokenNotFound
+            | ContractError::ReferralNotFound
+            | ContractError::PolicyVersionNotFound
+            | ContractError::PolicyRolloutNotFound => ErrorCategory::NotFound,
+            ContractError::ImportJobClosed
+            | ContractError::ImportChunkMismatch
+            | ContractError::PolicyAttached
+            | ContractError::ShareTokenInactive
+            | ContractError::InvalidReferralStatus => ErrorCategory::StateConflict,
+            ContractError::ShareTokenExpired => ErrorCategory::Authorization,
+            ContractError::PolicyAnalysisFailed => ErrorCategory::Validation,
+            ContractError::StorageError | ContractError::DecryptionFailed => ErrorCategory::Storage,
             ContractError::TransientFailure | ContractError::RateLimitExceeded => {
                 ErrorCategory::Transient
             }
@@ -195,16 +252,48 @@ impl ContractError {
             | ContractError::ExpiredAccess
             | ContractError::ConsentRequired
             | ContractError::ConsentExpired
+            | ContractError::ConsentPurposeMismatch
             | ContractError::ProviderAlreadyRegistered
             | ContractError::DelegationExpired
             | ContractError::RateLimitExceeded
             | ContractError::NonceAlreadyUsed => ErrorSeverity::Medium,
             ContractError::EmergencyAccessNotFound
             | ContractError::AppointmentNotFound
-            | ContractError::AppointmentNotVerified => ErrorSeverity::Low,
+            | ContractError::AppointmentNotVerified
+            | ContractError::AppointmentConflict => ErrorSeverity::Low,
+            ContractError::PrescriptionNotFound
+            | ContractError::InvalidPrescription
+            | ContractError::PrescriptionExpired
+            | ContractError::InvalidPrescriptionStatus => ErrorSeverity::Low,
+            ContractError::EmergencyAccessExpired | ContractError::EmergencyAccessRevoked => {
+                ErrorSeverity::Medium
+            }
+            ContractError::ProviderNotVerified => ErrorSeverity::Medium,
+            ContractError::ProviderLicenseInvalid => ErrorSeverity::Low,
+            ContractError::RecordRetracted => ErrorSeverity::Low,
+            ContractError::InvalidIntraocularPressure
+            | ContractError::InvalidVisualAcuity
+            | ContractError::MissingEyeData => ErrorSeverity::Low,
             ContractError::VersionConflict | ContractError::ConflictQueued => ErrorSeverity::Medium,
-            ContractError::ConflictNotFound => ErrorSeverity::Low,
-            ContractError::StorageError | ContractError::TransientFailure => ErrorSeverity::High,
+            ContractError::ConflictNotFound
+            | ContractError::GuardianshipNotFound
+            | ContractError::AttachmentNotFound
+            | ContractError::ImportJobNotFound
+            | ContractError::ImportJobClosed
+            | ContractError::ImportChunkMismatch
+            | ContractError::PolicyAttached
+            | ContractError::ShareTokenNotFound
+            | ContractError::ReferralNotFound
+            | ContractError::InvalidReferralStatus
+            | ContractError::PolicyAnalysisFailed
+            | ContractError::PolicyVersionNotFound
+            | ContractError::PolicyRolloutNotFound => ErrorSeverity::Low,
+            ContractError::ShareTokenExpired | ContractError::ShareTokenInactive => {
+                ErrorSeverity::Medium
+            }
+            ContractError::StorageError
+            | ContractError::TransientFailure
+            | ContractError::DecryptionFailed => ErrorSeverity::High,
             ContractError::Paused | ContractError::ContractPaused => ErrorSeverity::Critical,
         }
     }
@@ -266,6 +355,53 @@ impl ContractError {
             }
             ContractError::ConflictQueued => "Concurrent modification conflict queued for review",
             ContractError::ConflictNotFound => "Conflict entry not found",
+            ContractError::AppointmentConflict => {
+                "Provider already has an appointment in the requested time slot"
+            }
+            ContractError::PrescriptionNotFound => "Prescription not found",
+            ContractError::InvalidPrescription => {
+                "Prescription values are outside accepted clinical ranges"
+            }
+            ContractError::PrescriptionExpired => "Prescription has expired",
+            ContractError::InvalidPrescriptionStatus => {
+                "Prescription is not in a valid state for this operation"
+            }
+            ContractError::EmergencyAccessExpired => "Emergency access has expired",
+            ContractError::EmergencyAccessRevoked => "Emergency access has been revoked",
+            ContractError::ProviderNotVerified => "Provider is not verified or has been suspended",
+            ContractError::ProviderLicenseInvalid => {
+                "Provider license is missing, malformed or expired"
+            }
+            ContractError::RecordRetracted => "Record has been retracted",
+            ContractError::InvalidIntraocularPressure => {
+                "Intraocular pressure is outside the measurable range"
+            }
+            ContractError::InvalidVisualAcuity => {
+                "Visual acuity is not a valid Snellen or logMAR value"
+            }
+            ContractError::MissingEyeData => "Measurement is missing for one eye",
+            ContractError::GuardianshipNotFound => "Guardianship not found",
+            ContractError::ConsentPurposeMismatch => {
+                "Consent does not cover the declared purpose or record type"
+            }
+            ContractError::AttachmentNotFound => "Attachment not found",
+            ContractError::ImportJobNotFound => "Import job not found",
+            ContractError::ImportJobClosed => "Import job is already completed",
+            ContractError::ImportChunkMismatch => {
+                "Import chunk was already applied with different contents"
+            }
+            ContractError::ShareTokenNotFound => "Share token not found",
+            ContractError::ShareTokenExpired => "Share token has expired",
+            ContractError::ShareTokenInactive => "Share token was already redeemed or revoked",
+            ContractError::ReferralNotFound => "Referral not found",
+            ContractError::InvalidReferralStatus => {
+                "Referral is not in a status that allows this action"
+            }
+            ContractError::PolicyAnalysisFailed => "Policy failed static analysis",
+            ContractError::PolicyVersionNotFound => "Policy version not found",
+            ContractError::PolicyRolloutNotFound => "Policy has no staged version",
+            ContractError::PolicyAttached => "Access policy is still attached to a target",
+            ContractError::DecryptionFailed => "Stored data could not be decrypted",
         }
     }
 }
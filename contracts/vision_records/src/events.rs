@@ -1,12 +1,19 @@
 #![allow(deprecated)] // events().publish migration tracked separately
 
+use crate::amendment::RetractionReason;
 use crate::appointment::AppointmentType;
+use crate::attachment::AttachmentKind;
 use crate::audit::{AccessAction, AccessResult, AuditEntry};
-use crate::circuit_breaker::PauseScope;
+use crate::circuit_breaker::{AutoPause, PauseScope, TripTrigger};
 use crate::emergency::EmergencyCondition;
 use crate::errors::{ErrorCategory, ErrorContext, ErrorSeverity};
+use crate::guardianship::{GuardianPermission, GuardianRelationship};
+use crate::patient_profile::EmergencyContact;
+use crate::prescription::LensType;
+use crate::rate_limit::RateLimitConfig;
+use crate::referral::{Referral, ReferralStatus};
 use crate::{AccessLevel, RecordType, Role, VerificationStatus};
-use soroban_sdk::{symbol_short, Address, Env, String};
+use soroban_sdk::{symbol_short, Address, BytesN, Env, String, Vec};
 
 /// Event published when the contract is initialized.
 #[soroban_sdk::contracttype]
@@ -351,6 +358,33 @@ pub fn publish_contract_resumed(env: &Env, caller: Address, scope: PauseScope) {
     env.events().publish(topics, data);
 }
 
+/// Alert published when a trip rule pauses the contract automatically.
+#[soroban_sdk::contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CircuitBreakerTrippedEvent {
+    pub trigger: TripTrigger,
+    pub scope: PauseScope,
+    pub tripped_by: Address,
+    pub observed: u32,
+    pub window_seconds: u64,
+    pub resume_at: u64,
+    pub timestamp: u64,
+}
+
+pub fn publish_circuit_breaker_tripped(env: &Env, auto: &AutoPause, window_seconds: u64) {
+    let topics = (symbol_short!("CB_TRIP"), auto.trigger.clone());
+    let data = CircuitBreakerTrippedEvent {
+        trigger: auto.trigger.clone(),
+        scope: auto.scope.clone(),
+        tripped_by: auto.tripped_by.clone(),
+        observed: auto.observed,
+        window_seconds,
+        resume_at: auto.resume_at,
+        timestamp: env.ledger().timestamp(),
+    };
+    env.events().publish(topics, data);
+}
+
 pub fn publish_access_violation(
     env: &Env,
     caller: Address,
@@ -511,7 +545,8 @@ pub fn publish_meta_access_granted(
 pub struct ConsentGrantedEvent {
     pub patient: Address,
     pub grantee: Address,
-    pub consent_type: crate::ConsentType,
+    pub purposes: Vec<crate::ConsentType>,
+    pub record_types: Vec<crate::RecordType>,
     pub expires_at: u64,
     pub timestamp: u64,
 }
@@ -530,14 +565,16 @@ pub fn publish_consent_granted(
     env: &Env,
     patient: Address,
     grantee: Address,
-    consent_type: crate::ConsentType,
+    purposes: Vec<crate::ConsentType>,
+    record_types: Vec<crate::RecordType>,
     expires_at: u64,
 ) {
     let topics = (symbol_short!("CST_GRT"), patient.clone(), grantee.clone());
     let data = ConsentGrantedEvent {
         patient,
         grantee,
-        consent_type,
+        purposes,
+        record_types,
         expires_at,
         timestamp: env.ledger().timestamp(),
     };
@@ -647,13 +684,16 @@ pub struct EmergencyAccessRevokedEvent {
     pub timestamp: u64,
 }
 
-/// Event published when emergency contacts are notified.
+/// Event published when a patient's emergency contact is notified.
+/// Off-chain notifiers use the contact details to reach the contact.
 #[soroban_sdk::contracttype]
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct EmergencyContactNotifiedEvent {
     pub access_id: u64,
     pub patient: Address,
-    pub contact: Address,
+    pub requester: Address,
+    pub contact: EmergencyContact,
+    pub record_id: Option<u64>,
     pub timestamp: u64,
 }
 
@@ -715,13 +755,21 @@ pub fn publish_emergency_contact_notified(
     env: &Env,
     access_id: u64,
     patient: Address,
-    contact: Address,
+    requester: Address,
+    contact: EmergencyContact,
+    record_id: Option<u64>,
 ) {
-    let topics = (symbol_short!("EMRG_NOT"), patient.clone(), contact.clone());
+    let topics = (
+        symbol_short!("EMRG_NOT"),
+        patient.clone(),
+        requester.clone(),
+    );
     let data = EmergencyContactNotifiedEvent {
         access_id,
         patient,
+        requester,
         contact,
+        record_id,
         timestamp: env.ledger().timestamp(),
     };
     env.events().publish(topics, data);
@@ -973,6 +1021,449 @@ pub fn publish_appointment_verified(
     env.events().publish(topics, data);
 }
 
+/// Event published when a prescription is issued.
+#[soroban_sdk::contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PrescriptionIssuedEvent {
+    pub prescription_id: u64,
+    pub patient: Address,
+    pub provider: Address,
+    pub lens_type: LensType,
+    pub expires_at: u64,
+    pub timestamp: u64,
+}
+
+/// Event published when a prescription is verified by an optical retailer.
+#[soroban_sdk::contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PrescriptionVerifiedEvent {
+    pub prescription_id: u64,
+    pub patient: Address,
+    pub provider: Address,
+    pub verifier: Address,
+    pub timestamp: u64,
+}
+
+/// Event published when a prescription is dispensed.
+#[soroban_sdk::contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PrescriptionDispensedEvent {
+    pub prescription_id: u64,
+    pub patient: Address,
+    pub provider: Address,
+    pub dispensed_by: Address,
+    pub timestamp: u64,
+}
+
+/// Event published when a prescription is revoked.
+#[soroban_sdk::contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PrescriptionRevokedEvent {
+    pub prescription_id: u64,
+    pub patient: Address,
+    pub provider: Address,
+    pub revoked_by: Address,
+    pub reason: String,
+    pub timestamp: u64,
+}
+
+/// Publishes an event when a prescription is issued.
+pub fn publish_prescription_issued(
+    env: &Env,
+    prescription_id: u64,
+    patient: Address,
+    provider: Address,
+    lens_type: LensType,
+    expires_at: u64,
+) {
+    let topics = (symbol_short!("RX_ISS"), patient.clone(), provider.clone());
+    let data = PrescriptionIssuedEvent {
+        prescription_id,
+        patient,
+        provider,
+        lens_type,
+        expires_at,
+        timestamp: env.ledger().timestamp(),
+    };
+    env.events().publish(topics, data);
+}
+
+/// Publishes an event when a prescription is verified.
+pub fn publish_prescription_verified(
+    env: &Env,
+    prescription_id: u64,
+    patient: Address,
+    provider: Address,
+    verifier: Address,
+) {
+    let topics = (symbol_short!("RX_VER"), patient.clone(), provider.clone());
+    let data = PrescriptionVerifiedEvent {
+        prescription_id,
+        patient,
+        provider,
+        verifier,
+        timestamp: env.ledger().timestamp(),
+    };
+    env.events().publish(topics, data);
+}
+
+/// Publishes an event when a prescription is dispensed.
+pub fn publish_prescription_dispensed(
+    env: &Env,
+    prescription_id: u64,
+    patient: Address,
+    provider: Address,
+    dispensed_by: Address,
+) {
+    let topics = (symbol_short!("RX_DSP"), patient.clone(), provider.clone());
+    let data = PrescriptionDispensedEvent {
+        prescription_id,
+        patient,
+        provider,
+        dispensed_by,
+        timestamp: env.ledger().timestamp(),
+    };
+    env.events().publish(topics, data);
+}
+
+/// Publishes an event when a prescription is revoked.
+pub fn publish_prescription_revoked(
+    env: &Env,
+    prescription_id: u64,
+    patient: Address,
+    provider: Address,
+    revoked_by: Address,
+    reason: String,
+) {
+    let topics = (symbol_short!("RX_REV"), patient.clone(), provider.clone());
+    let data = PrescriptionRevokedEvent {
+        prescription_id,
+        patient,
+        provider,
+        revoked_by,
+        reason,
+        timestamp: env.ledger().timestamp(),
+    };
+    env.events().publish(topics, data);
+}
+
+/// Event published when a user registers an encryption public key.
+#[soroban_sdk::contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct EncryptionPublicKeySetEvent {
+    pub user: Address,
+    pub timestamp: u64,
+}
+
+/// Publishes an event when a user registers an encryption public key.
+pub fn publish_encryption_public_key_set(env: &Env, user: Address) {
+    let topics = (symbol_short!("ENC_PUB"), user.clone());
+    let data = EncryptionPublicKeySetEvent {
+        user,
+        timestamp: env.ledger().timestamp(),
+    };
+    env.events().publish(topics, data);
+}
+
+/// Event published when an admin changes a per-operation rate limit.
+#[soroban_sdk::contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RateLimitConfigSetEvent {
+    pub admin: Address,
+    pub operation: String,
+    pub max_requests: u32,
+    pub window_seconds: u64,
+    pub burst: u32,
+    pub timestamp: u64,
+}
+
+fn rate_limit_config_event(
+    env: &Env,
+    admin: Address,
+    config: &RateLimitConfig,
+) -> RateLimitConfigSetEvent {
+    RateLimitConfigSetEvent {
+        admin,
+        operation: config.operation.clone(),
+        max_requests: config.max_requests,
+        window_seconds: config.window_seconds,
+        burst: config.burst,
+        timestamp: env.ledger().timestamp(),
+    }
+}
+
+/// Publishes an event when an operation's default rate limit is set.
+pub fn publish_rate_limit_config_set(env: &Env, admin: Address, config: &RateLimitConfig) {
+    let topics = (symbol_short!("RL_CFG"), config.operation.clone());
+    env.events()
+        .publish(topics, rate_limit_config_event(env, admin, config));
+}
+
+/// Publishes an event when a role-specific rate limit is set.
+pub fn publish_role_rate_limit_set(
+    env: &Env,
+    admin: Address,
+    role: Role,
+    config: &RateLimitConfig,
+) {
+    let topics = (symbol_short!("RL_RCFG"), config.operation.clone(), role);
+    env.events()
+        .publish(topics, rate_limit_config_event(env, admin, config));
+}
+
+/// Event published when a guardian or proxy is added for a patient.
+#[soroban_sdk::contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct GuardianAddedEvent {
+    pub patient: Address,
+    pub guardian: Address,
+    pub relationship: GuardianRelationship,
+    pub permissions: Vec<GuardianPermission>,
+    pub added_by: Address,
+    pub expires_at: u64,
+    pub timestamp: u64,
+}
+
+/// Publishes an event when a guardian or proxy is added for a patient.
+pub fn publish_guardian_added(
+    env: &Env,
+    patient: Address,
+    guardian: Address,
+    relationship: GuardianRelationship,
+    permissions: Vec<GuardianPermission>,
+    added_by: Address,
+    expires_at: u64,
+) {
+    let topics = (symbol_short!("GRD_ADD"), patient.clone(), guardian.clone());
+    let data = GuardianAddedEvent {
+        patient,
+        guardian,
+        relationship,
+        permissions,
+        added_by,
+        expires_at,
+        timestamp: env.ledger().timestamp(),
+    };
+    env.events().publish(topics, data);
+}
+
+/// Event published when a guardianship ends before or at majority.
+#[soroban_sdk::contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct GuardianRemovedEvent {
+    pub patient: Address,
+    pub guardian: Address,
+    pub removed_by: Address,
+    pub timestamp: u64,
+}
+
+/// Publishes an event when a guardianship ends.
+pub fn publish_guardian_removed(
+    env: &Env,
+    patient: Address,
+    guardian: Address,
+    removed_by: Address,
+) {
+    let topics = (symbol_short!("GRD_REM"), patient.clone(), guardian.clone());
+    let data = GuardianRemovedEvent {
+        patient,
+        guardian,
+        removed_by,
+        timestamp: env.ledger().timestamp(),
+    };
+    env.events().publish(topics, data);
+}
+
+/// Event published when a patient who has come of age takes over their
+/// records from their guardians.
+#[soroban_sdk::contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct MajorityClaimedEvent {
+    pub patient: Address,
+    pub guardians_released: u32,
+    pub timestamp: u64,
+}
+
+/// Publishes an event when a patient claims majority.
+pub fn publish_majority_claimed(env: &Env, patient: Address, guardians_released: u32) {
+    let topics = (symbol_short!("GRD_MAJ"), patient.clone());
+    let data = MajorityClaimedEvent {
+        patient,
+        guardians_released,
+        timestamp: env.ledger().timestamp(),
+    };
+    env.events().publish(topics, data);
+}
+
+/// Event published when a wrapped record data key is submitted.
+#[soroban_sdk::contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RecordKeyWrappedEvent {
+    pub record_id: u64,
+    pub recipient: Address,
+    pub key_version: u32,
+    pub timestamp: u64,
+}
+
+/// Publishes an event when a wrapped record data key is submitted.
+pub fn publish_record_key_wrapped(env: &Env, record_id: u64, recipient: Address, key_version: u32) {
+    let topics = (symbol_short!("KEY_WRAP"), record_id, recipient.clone());
+    let data = RecordKeyWrappedEvent {
+        record_id,
+        recipient,
+        key_version,
+        timestamp: env.ledger().timestamp(),
+    };
+    env.events().publish(topics, data);
+}
+
+/// Event published when a record's key envelope moves to a new epoch after
+/// a revocation.
+#[soroban_sdk::contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RecordRekeyedEvent {
+    pub record_id: u64,
+    pub key_version: u32,
+    pub key_epoch: u32,
+    pub timestamp: u64,
+}
+
+/// Publishes an event when a record's key envelope is re-keyed.
+pub fn publish_record_rekeyed(env: &Env, record_id: u64, key_version: u32, key_epoch: u32) {
+    let topics = (symbol_short!("REC_REKEY"), record_id);
+    let data = RecordRekeyedEvent {
+        record_id,
+        key_version,
+        key_epoch,
+        timestamp: env.ledger().timestamp(),
+    };
+    env.events().publish(topics, data);
+}
+
+/// Event published when a record is amended.
+#[soroban_sdk::contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RecordAmendedEvent {
+    pub record_id: u64,
+    pub patient: Address,
+    pub amended_by: Address,
+    pub version: u64,
+    pub reason: String,
+    pub timestamp: u64,
+}
+
+/// Event published when a file is attached to a record.
+#[soroban_sdk::contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AttachmentAddedEvent {
+    pub record_id: u64,
+    pub attachment_id: u32,
+    pub kind: AttachmentKind,
+    pub content_hash: BytesN<32>,
+    pub added_by: Address,
+    pub timestamp: u64,
+}
+
+/// Event published when an attachment is removed from a record.
+#[soroban_sdk::contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AttachmentRemovedEvent {
+    pub record_id: u64,
+    pub attachment_id: u32,
+    pub content_hash: BytesN<32>,
+    pub removed_by: Address,
+    pub timestamp: u64,
+}
+
+pub fn publish_attachment_added(
+    env: &Env,
+    record_id: u64,
+    attachment_id: u32,
+    kind: AttachmentKind,
+    content_hash: BytesN<32>,
+    added_by: Address,
+) {
+    let topics = (symbol_short!("ATT_ADD"), record_id);
+    let data = AttachmentAddedEvent {
+        record_id,
+        attachment_id,
+        kind,
+        content_hash,
+        added_by,
+        timestamp: env.ledger().timestamp(),
+    };
+    env.events().publish(topics, data);
+}
+
+pub fn publish_attachment_removed(
+    env: &Env,
+    record_id: u64,
+    attachment_id: u32,
+    content_hash: BytesN<32>,
+    removed_by: Address,
+) {
+    let topics = (symbol_short!("ATT_REM"), record_id);
+    let data = AttachmentRemovedEvent {
+        record_id,
+        attachment_id,
+        content_hash,
+        removed_by,
+        timestamp: env.ledger().timestamp(),
+    };
+    env.events().publish(topics, data);
+}
+
+/// Event published when a record is retracted.
+#[soroban_sdk::contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RecordRetractedEvent {
+    pub record_id: u64,
+    pub patient: Address,
+    pub retracted_by: Address,
+    pub reason: RetractionReason,
+    pub timestamp: u64,
+}
+
+/// Publishes an event when a record is amended.
+pub fn publish_record_amended(
+    env: &Env,
+    record_id: u64,
+    patient: Address,
+    amended_by: Address,
+    version: u64,
+    reason: String,
+) {
+    let topics = (symbol_short!("REC_AMD"), patient.clone(), record_id);
+    let data = RecordAmendedEvent {
+        record_id,
+        patient,
+        amended_by,
+        version,
+        reason,
+        timestamp: env.ledger().timestamp(),
+    };
+    env.events().publish(topics, data);
+}
+
+/// Publishes an event when a record is retracted.
+pub fn publish_record_retracted(
+    env: &Env,
+    record_id: u64,
+    patient: Address,
+    retracted_by: Address,
+    reason: RetractionReason,
+) {
+    let topics = (symbol_short!("REC_RET"), patient.clone(), record_id);
+    let data = RecordRetractedEvent {
+        record_id,
+        patient,
+        retracted_by,
+        reason,
+        timestamp: env.ledger().timestamp(),
+    };
+    env.events().publish(topics, data);
+}
+
 /// Event published when an audit log entry is created.
 #[soroban_sdk::contracttype]
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -1263,3 +1754,131 @@ pub fn emit_emergency_event(
     };
     env.events().publish(topics, data);
 }
+
+/// Event published when a bulk import chunk has been applied.
+#[soroban_sdk::contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ImportChunkAppliedEvent {
+    pub job_id: u64,
+    pub chunk: u32,
+    pub imported: u32,
+    pub skipped: u32,
+    pub failed: u32,
+    pub timestamp: u64,
+}
+
+pub fn publish_import_chunk_applied(
+    env: &Env,
+    job_id: u64,
+    chunk: u32,
+    imported: u32,
+    skipped: u32,
+    failed: u32,
+) {
+    let topics = (symbol_short!("IMP_CHK"), job_id);
+    let data = ImportChunkAppliedEvent {
+        job_id,
+        chunk,
+        imported,
+        skipped,
+        failed,
+        timestamp: env.ledger().timestamp(),
+    };
+    env.events().publish(topics, data);
+}
+
+/// Event published when a patient issues a share token.
+#[soroban_sdk::contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ShareTokenCreatedEvent {
+    pub token_id: u64,
+    pub patient: Address,
+    pub record_ids: Vec<u64>,
+    pub level: AccessLevel,
+    pub expires_at: u64,
+    pub timestamp: u64,
+}
+
+/// Event published when a share token is redeemed or revoked.
+#[soroban_sdk::contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ShareTokenClosedEvent {
+    pub token_id: u64,
+    pub patient: Address,
+    /// The redeemer, or the patient for a revocation.
+    pub actor: Address,
+    pub timestamp: u64,
+}
+
+pub fn publish_share_token_created(
+    env: &Env,
+    token_id: u64,
+    patient: Address,
+    record_ids: Vec<u64>,
+    level: AccessLevel,
+    expires_at: u64,
+) {
+    let topics = (symbol_short!("SHR_NEW"), patient.clone());
+    let data = ShareTokenCreatedEvent {
+        token_id,
+        patient,
+        record_ids,
+        level,
+        expires_at,
+        timestamp: env.ledger().timestamp(),
+    };
+    env.events().publish(topics, data);
+}
+
+pub fn publish_share_token_redeemed(env: &Env, token_id: u64, patient: Address, redeemer: Address) {
+    let topics = (symbol_short!("SHR_RED"), patient.clone());
+    let data = ShareTokenClosedEvent {
+        token_id,
+        patient,
+        actor: redeemer,
+        timestamp: env.ledger().timestamp(),
+    };
+    env.events().publish(topics, data);
+}
+
+pub fn publish_share_token_revoked(env: &Env, token_id: u64, patient: Address) {
+    let topics = (symbol_short!("SHR_REV"), patient.clone());
+    let data = ShareTokenClosedEvent {
+        token_id,
+        patient: patient.clone(),
+        actor: patient,
+        timestamp: env.ledger().timestamp(),
+    };
+    env.events().publish(topics, data);
+}
+
+/// Event published whenever a referral is created or changes status.
+#[soroban_sdk::contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ReferralUpdatedEvent {
+    pub referral_id: u64,
+    pub from_provider: Address,
+    pub to_provider: Address,
+    pub patient: Address,
+    pub status: ReferralStatus,
+    pub actor: Address,
+    pub timestamp: u64,
+}
+
+pub fn publish_referral_updated(env: &Env, referral: &Referral, actor: Address) {
+    let topics = (
+        symbol_short!("REF_UPD"),
+        referral.id,
+        referral.patient.clone(),
+    );
+    let data = ReferralUpdatedEvent {
+        referral_id: referral.id,
+        from_provider: referral.from_provider.clone(),
+        to_provider: referral.to_provider.clone(),
+        patient: referral.patient.clone(),
+        status: referral.status.clone(),
+        actor,
+        timestamp: env.ledger().timestamp(),
+    };
+    env.events().publish(topics, data);
+}
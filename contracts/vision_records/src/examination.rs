@@ -2,9 +2,23 @@
 use soroban_sdk::{contracttype, symbol_short, Env, String, Symbol, Vec};
 use teye_common::concurrency::{self, FieldChange, UpdateOutcome, VersionStamp};
 
+use crate::validation::parse_hundredths;
+use crate::ContractError;
+
 const TTL_THRESHOLD: u32 = 5184000;
 const TTL_EXTEND_TO: u32 = 10368000;
 
+/// Measurable intraocular pressure in mmHg. Tonometers read up to about
+/// 80 mmHg; anything beyond is a transcription error.
+const IOP_RANGE: (u32, u32) = (1, 80);
+/// logMAR in hundredths: -0.30 (better than 20/10) to 1.70 (worse than 20/800).
+const LOGMAR_RANGE: (i64, i64) = (-30, 170);
+const MAX_ACUITY_LEN: u32 = 16;
+/// Largest Snellen numerator or denominator (e.g. 20/2000).
+const MAX_SNELLEN_TERM: u32 = 2000;
+/// Low-vision notations recorded when no chart line can be read.
+const LOW_VISION_CODES: [&[u8]; 4] = [b"CF", b"HM", b"LP", b"NLP"];
+
 fn extend_ttl_exam_key(env: &Env, key: &(Symbol, u64)) {
     env.storage()
         .persistent()
@@ -114,6 +128,122 @@ pub struct EyeExamination {
     pub clinical_notes: String,
 }
 
+// ── Validation ────────────────────────────────────────────────
+
+fn parse_snellen_term(digits: &[u8]) -> Option<u32> {
+    if digits.is_empty() || digits.len() > 4 {
+        return None;
+    }
+    let mut value = 0u32;
+    for &b in digits {
+        if !b.is_ascii_digit() {
+            return None;
+        }
+        value = value * 10 + u32::from(b - b'0');
+    }
+    (1..=MAX_SNELLEN_TERM).contains(&value).then_some(value)
+}
+
+/// Snellen fraction such as "20/40" or "6/12", optionally followed by a
+/// letter adjustment like "20/20-2" or "6/9+1".
+fn is_snellen(bytes: &[u8]) -> bool {
+    let slash = match bytes.iter().position(|&b| b == b'/') {
+        Some(i) => i,
+        None => return false,
+    };
+    let (numerator, rest) = (&bytes[..slash], &bytes[slash + 1..]);
+    let denominator = match rest.iter().position(|&b| b == b'+' || b == b'-') {
+        Some(i) => {
+            let letters = &rest[i + 1..];
+            if letters.len() != 1 || !(b'1'..=b'9').contains(&letters[0]) {
+                return false;
+            }
+            &rest[..i]
+        }
+        None => rest,
+    };
+    parse_snellen_term(numerator).is_some() && parse_snellen_term(denominator).is_some()
+}
+
+/// Validate one eye's acuity: a Snellen fraction, a logMAR value in
+/// -0.30..+1.70, or one of the low-vision codes CF, HM, LP and NLP.
+pub fn validate_acuity_value(value: &String) -> Result<(), ContractError> {
+    let len = value.len();
+    if len == 0 {
+        return Err(ContractError::MissingEyeData);
+    }
+    if len > MAX_ACUITY_LEN {
+        return Err(ContractError::InvalidVisualAcuity);
+    }
+    let mut buf = [0u8; MAX_ACUITY_LEN as usize];
+    value.copy_into_slice(&mut buf[..len as usize]);
+    let bytes = &buf[..len as usize];
+
+    if LOW_VISION_CODES.contains(&bytes) || is_snellen(bytes) {
+        return Ok(());
+    }
+    match parse_hundredths(value) {
+        Some(v) if v >= LOGMAR_RANGE.0 && v <= LOGMAR_RANGE.1 => Ok(()),
+        _ => Err(ContractError::InvalidVisualAcuity),
+    }
+}
+
+fn validate_measurement(m: &PhysicalMeasurement) -> Result<(), ContractError> {
+    validate_acuity_value(&m.left_eye)?;
+    validate_acuity_value(&m.right_eye)
+}
+
+fn require_both_eyes(left: &String, right: &String) -> Result<(), ContractError> {
+    if left.is_empty() || right.is_empty() {
+        return Err(ContractError::MissingEyeData);
+    }
+    Ok(())
+}
+
+/// Validate intraocular pressure for both eyes. A zero reading means the
+/// eye was not measured.
+pub fn validate_iop(iop: &IntraocularPressure) -> Result<(), ContractError> {
+    if iop.left_eye == 0 || iop.right_eye == 0 {
+        return Err(ContractError::MissingEyeData);
+    }
+    let (min, max) = IOP_RANGE;
+    if !(min..=max).contains(&iop.left_eye) || !(min..=max).contains(&iop.right_eye) {
+        return Err(ContractError::InvalidIntraocularPressure);
+    }
+    if iop.method.is_empty() {
+        return Err(ContractError::InvalidInput);
+    }
+    Ok(())
+}
+
+/// Validate the clinical fields of an examination before it is stored.
+///
+/// Acuity must be present and well-formed for both eyes, corrected acuity
+/// included when given. IOP must be within the measurable range. Visual
+/// field and fundus results, when present, must cover both eyes.
+pub fn validate_examination(
+    visual_acuity: &VisualAcuity,
+    iop: &IntraocularPressure,
+    visual_field: &OptVisualField,
+    fundus_photo: &OptFundusPhotography,
+) -> Result<(), ContractError> {
+    validate_measurement(&visual_acuity.uncorrected)?;
+    if let OptPhysicalMeasurement::Some(corrected) = &visual_acuity.corrected {
+        validate_measurement(corrected)?;
+    }
+    validate_iop(iop)?;
+    if let OptVisualField::Some(field) = visual_field {
+        require_both_eyes(&field.left_eye_reliability, &field.right_eye_reliability)?;
+    }
+    if let OptFundusPhotography::Some(fundus) = fundus_photo {
+        require_both_eyes(
+            &fundus.cup_to_disc_ratio_left,
+            &fundus.cup_to_disc_ratio_right,
+        )?;
+    }
+    Ok(())
+}
+
 pub fn exam_key(record_id: u64) -> (Symbol, u64) {
     (symbol_short!("EXAM"), record_id)
 }
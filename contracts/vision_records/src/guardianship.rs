@@ -0,0 +1,100 @@
+//! Guardian and proxy relationships.
+//!
+//! A guardian acts for a patient within the permissions granted to them.
+//! Guardianships of minors end automatically at the majority date recorded
+//! on the patient's profile; the patient then claims sole control with
+//! `claim_majority`. Adult patients may appoint their own proxies.
+
+use soroban_sdk::{contracttype, symbol_short, Address, Env, Symbol, Vec};
+
+// ── Storage keys ──────────────────────────────────────────────
+const GUARD: Symbol = symbol_short!("GUARD");
+
+const TTL_THRESHOLD: u32 = 5184000;
+const TTL_EXTEND_TO: u32 = 10368000;
+
+fn extend_ttl_guardian_key(env: &Env, key: &(Symbol, Address, Address)) {
+    env.storage()
+        .persistent()
+        .extend_ttl(key, TTL_THRESHOLD, TTL_EXTEND_TO);
+}
+
+// ── Types ─────────────────────────────────────────────────────
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[repr(u32)]
+pub enum GuardianRelationship {
+    Parent = 1,
+    LegalGuardian = 2,
+    /// Proxy appointed by an adult patient, e.g. a carer.
+    Proxy = 3,
+}
+
+/// What a guardian may do on the patient's behalf.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[repr(u32)]
+pub enum GuardianPermission {
+    /// Read the patient's records (`check_access` reports `Read`).
+    ViewRecords = 1,
+    /// Grant and revoke other users' access to the patient's records.
+    ManageAccess = 2,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Guardianship {
+    pub patient: Address,
+    pub guardian: Address,
+    pub relationship: GuardianRelationship,
+    pub permissions: Vec<GuardianPermission>,
+    pub established_by: Address,
+    pub established_at: u64,
+    /// The patient's majority date; 0 for proxies of adults.
+    pub expires_at: u64,
+}
+
+impl Guardianship {
+    pub fn is_active(&self, now: u64) -> bool {
+        self.expires_at == 0 || now < self.expires_at
+    }
+}
+
+// ── Storage ───────────────────────────────────────────────────
+
+fn guardian_key(patient: &Address, guardian: &Address) -> (Symbol, Address, Address) {
+    (GUARD, patient.clone(), guardian.clone())
+}
+
+pub fn get_guardianship(env: &Env, patient: &Address, guardian: &Address) -> Option<Guardianship> {
+    env.storage()
+        .persistent()
+        .get(&guardian_key(patient, guardian))
+}
+
+pub fn set_guardianship(env: &Env, guardianship: &Guardianship) {
+    let key = guardian_key(&guardianship.patient, &guardianship.guardian);
+    env.storage().persistent().set(&key, guardianship);
+    extend_ttl_guardian_key(env, &key);
+}
+
+pub fn remove_guardianship(env: &Env, patient: &Address, guardian: &Address) {
+    env.storage()
+        .persistent()
+        .remove(&guardian_key(patient, guardian));
+}
+
+/// True if `guardian` holds an unexpired guardianship of `patient` that
+/// includes `permission`.
+pub fn has_guardian_permission(
+    env: &Env,
+    patient: &Address,
+    guardian: &Address,
+    permission: &GuardianPermission,
+) -> bool {
+    match get_guardianship(env, patient, guardian) {
+        Some(g) => g.is_active(env.ledger().timestamp()) && g.permissions.contains(permission),
+        None => false,
+    }
+}
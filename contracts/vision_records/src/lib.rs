@@ -1,55 +1,88 @@
 #![no_std]
 #![allow(clippy::too_many_arguments, clippy::large_enum_variant)]
 extern crate alloc;
+pub mod amendment;
 pub mod appointment;
+pub mod attachment;
 pub mod audit;
 pub mod circuit_breaker;
 pub mod emergency;
+pub mod envelope;
 pub mod errors;
 pub mod events;
 pub mod examination;
+pub mod guardianship;
+pub mod migration;
+pub mod pagination;
 pub mod patient_profile;
 pub mod prescription;
 pub mod provider;
 pub mod rate_limit;
 pub mod rbac;
+pub mod record_index;
+pub mod referral;
+pub mod share;
 pub mod validation;
 
+use alloc::string::ToString;
+use key_manager::{DerivedKey, KeyManagerContractClient};
 use soroban_sdk::{
     contract, contractimpl, contracttype, symbol_short, Address, Bytes, BytesN, Env, String,
     Symbol, Vec,
 };
-use alloc::string::ToString;
-
-use alloc::string::ToString;
-use key_manager::{DerivedKey, KeyManagerContractClient};
-use teye_common::{
-    admin_tiers, multisig, progressive_auth, risk_engine, session, whitelist, AdminTier,
-    KeyManager, StdString, StdVec,
+use teye_common::concurrency::{
+    ConflictEntry, FieldChange, ResolutionStrategy, UpdateOutcome, VersionStamp,
 };
-use teye_common::concurrency::{ConflictEntry, FieldChange, ResolutionStrategy, UpdateOutcome, VersionStamp};
 use teye_common::metering::{MeteringHook, MeteringOpType};
+use teye_common::{
+    admin_tiers, multisig, progressive_auth, risk_engine, session, whitelist, AdminTier, KeyError,
+    KeyManager, StdString, StdVec, KEY_LEN, NONCE_LEN,
+};
 
 /// Re-export the contract-specific error type at the crate root.
 pub use errors::ContractError;
 
 /// Re-export provider types needed by other modules (e.g. events).
-pub use provider::VerificationStatus;
+pub use provider::{Certification, License, Location, Provider, VerificationStatus};
 
 /// Re-export error helpers used throughout the contract.
 pub use errors::{create_error_context, log_error};
 
 /// Re-export types from submodules used directly in the contract impl.
-pub use audit::{AccessAction, AccessResult};
+pub use amendment::{RecordVersion, RetractionReason, Tombstone};
+pub use appointment::{Appointment, AppointmentHistoryEntry, AppointmentStatus, AppointmentType};
+pub use attachment::{Attachment, AttachmentInput, AttachmentKind, AttachmentManifest};
+pub use audit::{
+    AccessAction, AccessReport, AccessReportEntry, AccessResult, AccessorKind, AuditEntry,
+    AuditFilter, AuditPage,
+};
+pub use emergency::{EmergencyAccess, EmergencyAuditEntry, EmergencyCondition, EmergencyStatus};
+pub use envelope::{KeyEnvelope, WrappedKey};
 pub use examination::{
-    EyeExamination, IntraocularPressure, OptFundusPhotography, OptRetinalImaging, OptVisualField,
+    EyeExamination, FundusPhotography, IntraocularPressure, OptFundusPhotography,
+    OptPhysicalMeasurement, OptRetinalImaging, OptVisualField, PhysicalMeasurement,
     SlitLampFindings, VisualAcuity,
 };
+pub use guardianship::{GuardianPermission, GuardianRelationship, Guardianship};
+pub use migration::{
+    ImportChunkReport, ImportItemKind, ImportItemResult, ImportJob, ImportOutcome,
+    ImportProfileInput, ImportProvenance, ImportRecordInput, ImportStatus, ImportUserInput,
+};
+pub use pagination::{
+    AppointmentPage, EmergencyAccessPage, GrantPage, PolicyPage, PrescriptionPage, ProviderPage,
+    RecordFilter, RecordPage, ReferralPage, ShareTokenPage,
+};
 pub use patient_profile::{
     EmergencyContact, InsuranceInfo, OptionalEmergencyContact, OptionalInsuranceInfo,
     PatientProfile,
 };
-pub use prescription::{LensType, OptionalContactLensData, Prescription, PrescriptionData};
+pub use prescription::{
+    ContactLensData, LensType, OptionalContactLensData, Prescription, PrescriptionData,
+    PrescriptionStatus,
+};
+pub use rate_limit::{RateLimitConfig, RateLimitStats, RateLimitStatus};
+pub use referral::{Referral, ReferralStatus};
+pub use share::{ShareToken, ShareTokenStatus};
 
 /// Storage keys for the contract
 const ADMIN: Symbol = symbol_short!("ADMIN");
@@ -61,6 +94,9 @@ const RATE_TRACK: Symbol = symbol_short!("RL_IN_TRK");
 const TTL_THRESHOLD: u32 = 5184000;
 const TTL_EXTEND_TO: u32 = 10368000;
 
+/// Largest number of records `get_records` returns in one call.
+const MAX_BATCH_READ: u32 = 20;
+
 const ENC_CUR: Symbol = symbol_short!("ENC_CUR");
 const ENC_KEY: Symbol = symbol_short!("ENC_KEY");
 const KEY_MGR: Symbol = symbol_short!("KEY_MGR");
@@ -125,30 +161,114 @@ fn encryption_key_action_hash(env: &Env, version: &String, key: &String) -> Byte
 }
 
 fn consent_key(patient: &Address, grantee: &Address) -> (Symbol, Address, Address) {
+    (symbol_short!("CONSENT2"), patient.clone(), grantee.clone())
+}
+
+/// Key of consent stored as [`LegacyConsentGrant`], before consent was
+/// scoped to purposes and record types.
+fn legacy_consent_key(patient: &Address, grantee: &Address) -> (Symbol, Address, Address) {
     (symbol_short!("CONSENT"), patient.clone(), grantee.clone())
 }
 
+/// The consent `patient` has given `grantee`, in any state. Consent stored
+/// in the legacy layout is read as covering its single purpose and every
+/// record type; it moves to the current layout the next time it is written.
+pub(crate) fn load_consent(
+    env: &Env,
+    patient: &Address,
+    grantee: &Address,
+) -> Option<ConsentGrant> {
+    let storage = env.storage().persistent();
+    if let Some(consent) = storage.get::<_, ConsentGrant>(&consent_key(patient, grantee)) {
+        return Some(consent);
+    }
+    storage
+        .get::<_, LegacyConsentGrant>(&legacy_consent_key(patient, grantee))
+        .map(|legacy| ConsentGrant {
+            patient: legacy.patient,
+            grantee: legacy.grantee,
+            purposes: Vec::from_array(env, [legacy.consent_type]),
+            record_types: Vec::new(env),
+            granted_at: legacy.granted_at,
+            expires_at: legacy.expires_at,
+            revoked: legacy.revoked,
+        })
+}
+
+/// Stores `consent` in the current layout, dropping any legacy copy.
+fn store_consent(env: &Env, consent: &ConsentGrant) {
+    let key = consent_key(&consent.patient, &consent.grantee);
+    env.storage().persistent().set(&key, consent);
+    extend_ttl_access_key(env, &key);
+    env.storage()
+        .persistent()
+        .remove(&legacy_consent_key(&consent.patient, &consent.grantee));
+}
+
+fn get_active_consent(env: &Env, patient: &Address, grantee: &Address) -> Option<ConsentGrant> {
+    load_consent(env, patient, grantee)
+        .filter(|consent| !consent.revoked && consent.expires_at > env.ledger().timestamp())
+}
+
+/// Active consent for any purpose.
 fn has_active_consent(env: &Env, patient: &Address, grantee: &Address) -> bool {
-    let key = consent_key(patient, grantee);
-    if let Some(consent) = env.storage().persistent().get::<_, ConsentGrant>(&key) {
-        !consent.revoked && consent.expires_at > env.ledger().timestamp()
-    } else {
-        false
+    get_active_consent(env, patient, grantee).is_some()
+}
+
+/// How a patient's consent to `grantee` applies to a read of a
+/// `record_type` record for `purpose`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum ConsentScope {
+    Absent,
+    Covered,
+    OtherPurpose,
+    OtherRecordType,
+}
+
+fn consent_scope(
+    env: &Env,
+    patient: &Address,
+    grantee: &Address,
+    purpose: &ConsentType,
+    record_type: &RecordType,
+) -> ConsentScope {
+    match get_active_consent(env, patient, grantee) {
+        None => ConsentScope::Absent,
+        Some(consent) if !consent.purposes.contains(purpose) => ConsentScope::OtherPurpose,
+        Some(consent)
+            if !consent.record_types.is_empty() && !consent.record_types.contains(record_type) =>
+        {
+            ConsentScope::OtherRecordType
+        }
+        Some(_) => ConsentScope::Covered,
+    }
+}
+
+fn purpose_label(purpose: &ConsentType) -> &'static str {
+    match purpose {
+        ConsentType::Treatment => "treatment",
+        ConsentType::Research => "research",
+        ConsentType::Sharing => "sharing",
+        ConsentType::Billing => "billing",
     }
 }
 
 pub use rbac::{
     build_eval_context, check_policy_engine, create_access_policy, evaluate_access_policies,
     set_record_sensitivity, set_user_credential, simulate_policy_check, AccessPolicy,
-    CredentialType, Permission, PolicyContext, Role, SensitivityLevel, TimeRestriction,
+    CredentialType, Permission, PolicyConditions, PolicyContext, PolicyTarget, Role,
+    SensitivityLevel, TimeRestriction,
 };
 
+/// Purpose a patient consents to, and that a reader declares when reading.
 #[contracttype]
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub enum ConsentType {
     Treatment,
     Research,
+    /// Disclosure outside the contract, e.g. cross-chain export.
     Sharing,
+    Billing,
 }
 
 /// Access levels for record sharing
@@ -165,6 +285,22 @@ pub enum AccessLevel {
     Full,
 }
 
+impl AccessLevel {
+    fn rank(&self) -> u32 {
+        match self {
+            AccessLevel::None => 0,
+            AccessLevel::Read => 1,
+            AccessLevel::Write => 2,
+            AccessLevel::Full => 3,
+        }
+    }
+
+    /// Whether this level includes everything `other` allows.
+    pub fn covers(&self, other: &AccessLevel) -> bool {
+        self.rank() >= other.rank()
+    }
+}
+
 /// Vision record types
 #[contracttype]
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -183,15 +319,6 @@ pub enum RecordType {
     LabResult,
 }
 
-/// Status for emergency access grants
-#[contracttype]
-#[derive(Clone, Debug, Eq, PartialEq)]
-pub enum EmergencyStatus {
-    Active,
-    Revoked,
-    Expired,
-}
-
 /// User information structure
 #[contracttype]
 #[derive(Clone, Debug)]
@@ -217,6 +344,17 @@ pub struct VisionRecord {
     pub updated_at: u64,
 }
 
+/// Outcome of `get_record_for_purpose`. A refused read is returned rather
+/// than raised so that its audit entry is kept.
+#[contracttype]
+#[derive(Clone, Debug)]
+pub enum RecordRead {
+    Granted(VisionRecord),
+    /// `ContractError` code of the refusal: `Unauthorized`, `AccessDenied`
+    /// or `ConsentPurposeMismatch`.
+    Denied(u32),
+}
+
 /// Access grant structure
 #[contracttype]
 #[derive(Clone, Debug)]
@@ -230,8 +368,24 @@ pub struct AccessGrant {
 
 /// Consent grant structure for patient-to-provider consent tracking
 #[contracttype]
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Eq, PartialEq)]
 pub struct ConsentGrant {
+    pub patient: Address,
+    pub grantee: Address,
+    /// Purposes the grantee may read for.
+    pub purposes: Vec<ConsentType>,
+    /// Record types covered; empty covers every type.
+    pub record_types: Vec<RecordType>,
+    pub granted_at: u64,
+    pub expires_at: u64,
+    pub revoked: bool,
+}
+
+/// Consent as stored before it was scoped: one purpose, every record type.
+/// Only read, through [`load_consent`].
+#[contracttype(export = false)]
+#[derive(Clone, Debug)]
+pub(crate) struct LegacyConsentGrant {
     pub patient: Address,
     pub grantee: Address,
     pub consent_type: ConsentType,
@@ -308,22 +462,210 @@ impl VisionRecordsContract {
         record_id: u64,
         version: Option<u32>,
     ) -> Result<Option<(StdVec<u8>, String)>, ContractError> {
-        let (manager, key_id) = match Self::get_key_manager_config(env) {
-            Some(cfg) => cfg,
-            None => return Ok(None),
-        };
+        Ok(
+            Self::record_data_key(env, record_id, version).map(|derived| {
+                (
+                    derived.key.to_array().to_vec(),
+                    String::from_str(env, &derived.version.to_string()),
+                )
+            }),
+        )
+    }
 
+    /// The key manager's data key for `record_id`, if a key manager is
+    /// configured. `version` selects a historical key version.
+    fn record_data_key(env: &Env, record_id: u64, version: Option<u32>) -> Option<DerivedKey> {
+        let (manager, key_id) = Self::get_key_manager_config(env)?;
         let client = KeyManagerContractClient::new(env, &manager);
-        let derived: DerivedKey = match version {
-            Some(ver) => client.derive_record_key_with_version(&key_id, &record_id, &ver),
-            None => client.derive_record_key(&key_id, &record_id),
+        Some(match version {
+            Some(ver) => client.derive_record_key_with_version(
+                &env.current_contract_address(),
+                &key_id,
+                &record_id,
+                &ver,
+            ),
+            None => client.derive_record_key(&env.current_contract_address(), &key_id, &record_id),
+        })
+    }
+
+    /// Re-keys the record's envelope if its current `key_version` is newer
+    /// than the envelope's. Records encrypted without the key manager have
+    /// no envelope.
+    fn seal_record_envelope(env: &Env, record: &VisionRecord) {
+        let version = match record
+            .key_version
+            .as_ref()
+            .and_then(Self::parse_key_version_u32)
+        {
+            Some(version) => version,
+            None => return,
+        };
+        if let Some(data_key) = Self::record_data_key(env, record.id, Some(version)) {
+            envelope::seal(env, record.id, &data_key);
+        }
+    }
+
+    /// Re-keys the record's envelope after a recipient lost access, so the
+    /// data key they may have unwrapped is no longer the current one.
+    fn rekey_record_envelope(env: &Env, record_id: u64) {
+        let Some(header) = envelope::get_envelope(env, record_id) else {
+            return;
+        };
+        let Some(data_key) = Self::record_data_key(env, record_id, Some(header.key_version)) else {
+            return;
         };
-        let bytes = derived.key.to_array().to_vec();
-        let version_str = derived.version.to_string();
-        Ok(Some((
-            bytes,
-            String::from_str(env, &version_str),
-        )))
+        if let Some(epoch) = envelope::rekey(env, record_id, &data_key) {
+            events::publish_record_rekeyed(env, record_id, header.key_version, epoch);
+        }
+    }
+
+    /// Encrypts `data_hash` for storage on `record_id`, returning the
+    /// ciphertext and the key version used (KeyManager preferred, fallback
+    /// to ENC_KEY).
+    fn encrypt_data_hash(
+        env: &Env,
+        record_id: u64,
+        data_hash: &String,
+    ) -> Result<(String, Option<String>), ContractError> {
+        let (master_bytes, key_version) =
+            if let Some((bytes, ver)) = Self::derive_key_manager_bytes(env, record_id, None)? {
+                (bytes, Some(ver))
+            } else {
+                let current_version: Option<String> = env.storage().instance().get(&ENC_CUR);
+                let mut master_bytes: StdVec<u8> = StdVec::new();
+                if let Some(ver) = current_version.clone() {
+                    if let Some(sv) = env
+                        .storage()
+                        .persistent()
+                        .get::<(Symbol, String), String>(&(ENC_KEY, ver.clone()))
+                    {
+                        let hex = sv.to_string();
+                        if let Some(bytes) = teye_common::hex_to_bytes(&hex) {
+                            master_bytes = bytes;
+                        }
+                    }
+                }
+                (master_bytes, current_version)
+            };
+
+        Ok((
+            Self::seal_data_hash(env, master_bytes, data_hash)?,
+            key_version,
+        ))
+    }
+
+    /// Seals `data_hash` under `key` with a fresh nonce from the ledger PRNG.
+    /// Without key material the hash is stored hex-encoded, as it always was.
+    fn seal_data_hash(
+        env: &Env,
+        key: StdVec<u8>,
+        data_hash: &String,
+    ) -> Result<String, ContractError> {
+        let plaintext: StdString = data_hash.to_string();
+        if key.is_empty() {
+            return Ok(String::from_str(
+                env,
+                &teye_common::bytes_to_hex(plaintext.as_bytes()),
+            ));
+        }
+        let mut nonce = [0u8; NONCE_LEN];
+        env.prng().fill(&mut nonce);
+        let ciphertext = KeyManager::new(Self::aes_key(env, key))
+            .encrypt_with_nonce(None, &plaintext, nonce)
+            .map_err(Self::key_error)?;
+        Ok(String::from_str(env, &ciphertext))
+    }
+
+    /// The AES-256 key for `key` material. Keys set before AES-GCM may have
+    /// any length; those are stretched through SHA-256.
+    fn aes_key(env: &Env, key: StdVec<u8>) -> StdVec<u8> {
+        if key.len() == KEY_LEN {
+            return key;
+        }
+        env.crypto()
+            .sha256(&Bytes::from_slice(env, &key))
+            .to_array()
+            .to_vec()
+    }
+
+    fn key_error(err: KeyError) -> ContractError {
+        match err {
+            KeyError::InvalidKeyLength | KeyError::InvalidKeyId => ContractError::InvalidInput,
+            KeyError::RandomnessUnavailable => ContractError::TransientFailure,
+            KeyError::UnknownKey
+            | KeyError::UnknownKeyVersion
+            | KeyError::KeyMismatch
+            | KeyError::MalformedCiphertext
+            | KeyError::UnsupportedFormat
+            | KeyError::AuthenticationFailed
+            | KeyError::InvalidUtf8 => ContractError::DecryptionFailed,
+        }
+    }
+
+    /// Reads a `data_hash` stored by the XOR scheme used before AES-GCM.
+    /// Only accepted if it decodes to something `add_record` would take.
+    fn legacy_data_hash(env: &Env, key: &[u8], stored: &str) -> Option<String> {
+        let plain = teye_common::decrypt_legacy_xor(key, stored)?;
+        let plain = String::from_str(env, &plain);
+        validation::validate_data_hash(&plain).ok()?;
+        Some(plain)
+    }
+
+    /// Decrypts a stored `data_hash` written under `key_version`, including
+    /// ones written by the XOR scheme used before AES-GCM. Fails with
+    /// `DecryptionFailed` when the key material does not open it.
+    fn decrypt_data_hash(
+        env: &Env,
+        record_id: u64,
+        key_version: &Option<String>,
+        data_hash: &String,
+    ) -> Result<String, ContractError> {
+        // Prefer KeyManager-derived key when configured; fallback to ENC_KEY
+        let mut master_bytes: StdVec<u8> = StdVec::new();
+        let mut used_key_manager = false;
+        if let Some(_cfg) = Self::get_key_manager_config(env) {
+            let version_u32 = key_version.as_ref().and_then(Self::parse_key_version_u32);
+            let allow_key_manager = key_version.is_none() || version_u32.is_some();
+            if allow_key_manager {
+                if let Some((bytes, _)) =
+                    Self::derive_key_manager_bytes(env, record_id, version_u32)?
+                {
+                    master_bytes = bytes;
+                    used_key_manager = true;
+                }
+            }
+        }
+
+        if !used_key_manager {
+            let key_ver = key_version
+                .clone()
+                .or_else(|| env.storage().instance().get(&ENC_CUR));
+            if let Some(ver) = key_ver {
+                if let Some(sv) = env
+                    .storage()
+                    .persistent()
+                    .get::<(Symbol, String), String>(&(ENC_KEY, ver.clone()))
+                {
+                    let hex = sv.to_string();
+                    if let Some(bytes) = teye_common::hex_to_bytes(&hex) {
+                        master_bytes = bytes;
+                    }
+                }
+            }
+        }
+
+        let stored: StdString = data_hash.to_string();
+        if master_bytes.is_empty() {
+            // Hex-encoded without a key, by either scheme.
+            return Ok(Self::legacy_data_hash(env, &master_bytes, &stored)
+                .unwrap_or_else(|| data_hash.clone()));
+        }
+        match KeyManager::new(Self::aes_key(env, master_bytes.clone())).decrypt(None, &stored) {
+            Ok(plain) => Ok(String::from_str(env, &plain)),
+            // Written before AES-GCM under the raw key.
+            Err(err) => Self::legacy_data_hash(env, &master_bytes, &stored)
+                .ok_or_else(|| Self::key_error(err)),
+        }
     }
 
     fn parse_key_version_u32(version: &String) -> Option<u32> {
@@ -364,6 +706,22 @@ impl VisionRecordsContract {
         Ok(())
     }
 
+    /// Spends `cost` tokens from `caller`'s budget for `operation`, using the
+    /// limit configured for the caller's active role if there is one.
+    fn enforce_operation_limit(
+        env: &Env,
+        caller: &Address,
+        operation: &str,
+        cost: u32,
+    ) -> Result<(), ContractError> {
+        let operation = String::from_str(env, operation);
+        let role = rbac::get_active_assignment(env, caller).map(|a| a.role);
+        if !rate_limit::check_rate_limit(env, caller, role.as_ref(), &operation, cost) {
+            return Err(ContractError::RateLimitExceeded);
+        }
+        Ok(())
+    }
+
     /// Initialize the contract with an admin address
     pub fn initialize(env: Env, admin: Address) -> Result<(), ContractError> {
         if env.storage().instance().has(&INITIALIZED) {
@@ -641,6 +999,7 @@ impl VisionRecordsContract {
 
     /// Set or rotate an encryption master key under a given `version`.
     /// Stores the key bytes persistently under (ENC_KEY, version) and updates current.
+    /// `key` is a hex-encoded 32-byte AES-256 key.
     pub fn set_encryption_key(
         env: Env,
         caller: Address,
@@ -660,6 +1019,11 @@ impl VisionRecordsContract {
                 "admin_or_system_admin",
             );
         }
+        // Keys are hex-encoded AES-256 keys.
+        let key_len = teye_common::hex_to_bytes(&key.to_string()).map(|bytes| bytes.len());
+        if key_len != Some(KEY_LEN) {
+            return Err(ContractError::InvalidInput);
+        }
 
         let auth_session = session::start_or_refresh_session(
             &env,
@@ -748,6 +1112,8 @@ impl VisionRecordsContract {
 
     /// Configure the external Key Manager used for per-record key derivation.
     /// Requires at least `ContractAdmin` tier, or legacy admin/SystemAdmin.
+    /// The key manager must also let this contract derive from
+    /// `root_key_id` (its `set_record_key_deriver`).
     pub fn set_key_manager(
         env: Env,
         caller: Address,
@@ -765,94 +1131,346 @@ impl VisionRecordsContract {
         Ok(())
     }
 
+    /// Register the caller's encryption public key (an uncompressed
+    /// BLS12-381 G1 point), which record data keys are wrapped to.
+    pub fn set_encryption_public_key(
+        env: Env,
+        caller: Address,
+        public_key: BytesN<96>,
+    ) -> Result<(), ContractError> {
+        circuit_breaker::require_not_paused(&env, &circuit_breaker::PauseScope::Global)?;
+        caller.require_auth();
+        if !envelope::is_valid_public_key(&env, &public_key) {
+            return Err(ContractError::InvalidInput);
+        }
+        envelope::set_public_key(&env, &caller, &public_key);
+        events::publish_encryption_public_key_set(&env, caller);
+        Ok(())
+    }
+
+    pub fn get_encryption_public_key(env: Env, user: Address) -> Option<BytesN<96>> {
+        envelope::get_public_key(&env, &user)
+    }
+
+    /// Return the key envelope header for a record, if it has one.
+    pub fn get_record_key_envelope(env: Env, record_id: u64) -> Option<KeyEnvelope> {
+        envelope::get_envelope(&env, record_id)
+    }
+
+    /// Store the record's data key wrapped off-chain for `recipient` (see
+    /// [`envelope::wrap`]). The patient or the record's provider submits it,
+    /// for the patient, the provider or a current record-level grantee, under
+    /// the envelope's current key version and epoch (see
+    /// [`envelope::data_key`]).
+    #[allow(clippy::too_many_arguments)]
+    pub fn submit_wrapped_record_key(
+        env: Env,
+        caller: Address,
+        record_id: u64,
+        recipient: Address,
+        key_version: u32,
+        ephemeral: BytesN<96>,
+        wrapped_key: BytesN<32>,
+    ) -> Result<(), ContractError> {
+        circuit_breaker::require_not_paused(&env, &circuit_breaker::PauseScope::Global)?;
+        caller.require_auth();
+
+        let record: VisionRecord = env
+            .storage()
+            .persistent()
+            .get(&(symbol_short!("RECORD"), record_id))
+            .ok_or(ContractError::RecordNotFound)?;
+        if caller != record.patient && caller != record.provider {
+            return Self::unauthorized(
+                &env,
+                &caller,
+                "submit_wrapped_record_key",
+                "record_owner_or_provider",
+            );
+        }
+        if recipient != record.patient
+            && recipient != record.provider
+            && Self::check_record_access(env.clone(), record_id, recipient.clone())
+                == AccessLevel::None
+        {
+            return Err(ContractError::AccessDenied);
+        }
+
+        let mut header =
+            envelope::get_envelope(&env, record_id).ok_or(ContractError::InvalidInput)?;
+        if key_version != header.key_version || !envelope::is_valid_public_key(&env, &ephemeral) {
+            return Err(ContractError::InvalidInput);
+        }
+
+        envelope::add_wrapped_key(
+            &env,
+            &mut header,
+            &WrappedKey {
+                recipient: recipient.clone(),
+                key_version,
+                ephemeral,
+                wrapped_key,
+                wrapped_at: env.ledger().timestamp(),
+            },
+        );
+        events::publish_record_key_wrapped(&env, record_id, recipient, key_version);
+        Ok(())
+    }
+
+    /// Return the record data key wrapped for `recipient`, if any. Wrapped
+    /// keys are only usable with the recipient's secret key.
+    pub fn get_wrapped_record_key(
+        env: Env,
+        record_id: u64,
+        recipient: Address,
+    ) -> Option<WrappedKey> {
+        envelope::get_wrapped_key(&env, record_id, &recipient)
+    }
+
     /// Return the current rate limiting configuration, if any.
     pub fn get_rate_limit_config(env: Env) -> Option<(u64, u64)> {
         env.storage().instance().get(&RATE_CFG)
     }
 
-    /// Enables or disables whitelist enforcement globally.
+    fn validate_operation_limit(config: &RateLimitConfig) -> Result<(), ContractError> {
+        if config.operation.is_empty() || config.max_requests == 0 || config.window_seconds == 0 {
+            return Err(ContractError::InvalidInput);
+        }
+        Ok(())
+    }
+
+    /// Set the default token-bucket limit for an operation (`add_record`,
+    /// `get_record` or `grant_access`; batch calls draw on the same budget,
+    /// one token per item).
     ///
     /// Requires at least `ContractAdmin` tier, or legacy admin/SystemAdmin.
-    pub fn set_whitelist_enabled(
+    pub fn set_operation_rate_limit(
         env: Env,
         caller: Address,
-        enabled: bool,
+        config: RateLimitConfig,
     ) -> Result<(), ContractError> {
         caller.require_auth();
         if !Self::has_admin_access(&env, &caller, &AdminTier::ContractAdmin) {
             return Self::unauthorized(
                 &env,
                 &caller,
-                "set_whitelist_enabled",
+                "set_operation_rate_limit",
                 "admin_tier:ContractAdmin",
             );
         }
-        whitelist::set_whitelist_enabled(&env, enabled);
+        Self::validate_operation_limit(&config)?;
+        rate_limit::set_rate_limit_config(&env, &config);
+        events::publish_rate_limit_config_set(&env, caller, &config);
         Ok(())
     }
 
-    /// Adds an address to the whitelist.
+    /// Set the limit for an operation as it applies to callers whose active
+    /// role is `role`. Overrides the operation default.
     ///
     /// Requires at least `ContractAdmin` tier, or legacy admin/SystemAdmin.
-    pub fn add_to_whitelist(env: Env, caller: Address, user: Address) -> Result<(), ContractError> {
+    pub fn set_role_rate_limit(
+        env: Env,
+        caller: Address,
+        role: Role,
+        config: RateLimitConfig,
+    ) -> Result<(), ContractError> {
         caller.require_auth();
         if !Self::has_admin_access(&env, &caller, &AdminTier::ContractAdmin) {
             return Self::unauthorized(
                 &env,
                 &caller,
-                "add_to_whitelist",
+                "set_role_rate_limit",
                 "admin_tier:ContractAdmin",
             );
         }
-        whitelist::add_to_whitelist(&env, &user);
+        Self::validate_operation_limit(&config)?;
+        rate_limit::set_role_rate_limit_config(&env, &role, &config);
+        events::publish_role_rate_limit_set(&env, caller, role, &config);
         Ok(())
     }
 
-    /// Removes an address from the whitelist.
+    /// Remove the default limit for an operation. Role overrides are kept.
     ///
     /// Requires at least `ContractAdmin` tier, or legacy admin/SystemAdmin.
-    pub fn remove_from_whitelist(
+    pub fn remove_operation_rate_limit(
         env: Env,
         caller: Address,
-        user: Address,
+        operation: String,
     ) -> Result<(), ContractError> {
         caller.require_auth();
         if !Self::has_admin_access(&env, &caller, &AdminTier::ContractAdmin) {
             return Self::unauthorized(
                 &env,
                 &caller,
-                "remove_from_whitelist",
+                "remove_operation_rate_limit",
                 "admin_tier:ContractAdmin",
             );
         }
-        whitelist::remove_from_whitelist(&env, &user);
+        rate_limit::remove_rate_limit_config(&env, &operation);
         Ok(())
     }
 
-    pub fn is_whitelist_enabled(env: Env) -> bool {
-        whitelist::is_whitelist_enabled(&env)
+    /// Remove the role override for an operation.
+    ///
+    /// Requires at least `ContractAdmin` tier, or legacy admin/SystemAdmin.
+    pub fn remove_role_rate_limit(
+        env: Env,
+        caller: Address,
+        role: Role,
+        operation: String,
+    ) -> Result<(), ContractError> {
+        caller.require_auth();
+        if !Self::has_admin_access(&env, &caller, &AdminTier::ContractAdmin) {
+            return Self::unauthorized(
+                &env,
+                &caller,
+                "remove_role_rate_limit",
+                "admin_tier:ContractAdmin",
+            );
+        }
+        rate_limit::remove_role_rate_limit_config(&env, &operation, &role);
+        Ok(())
     }
 
-    pub fn is_whitelisted(env: Env, user: Address) -> bool {
-        whitelist::is_whitelisted(&env, &user)
+    /// Default limit for an operation, if configured.
+    pub fn get_operation_rate_limit(env: Env, operation: String) -> Option<RateLimitConfig> {
+        rate_limit::get_rate_limit_config(&env, &operation)
     }
 
-    /// Register a new user
-    pub fn register_user(
+    /// Role override for an operation, if configured.
+    pub fn get_role_rate_limit(env: Env, operation: String, role: Role) -> Option<RateLimitConfig> {
+        rate_limit::get_role_rate_limit_config(&env, &operation, &role)
+    }
+
+    /// All configured operation defaults.
+    pub fn get_operation_rate_limits(env: Env) -> Vec<RateLimitConfig> {
+        rate_limit::get_all_rate_limit_configs(&env)
+    }
+
+    /// Exempt `address` from per-operation limits, or lift the exemption.
+    ///
+    /// Requires at least `ContractAdmin` tier, or legacy admin/SystemAdmin.
+    pub fn set_rate_limit_bypass(
         env: Env,
         caller: Address,
-        user: Address,
-        role: Role,
-        name: String,
+        address: Address,
+        bypass: bool,
     ) -> Result<(), ContractError> {
-        circuit_breaker::require_not_paused(
-            &env,
-            &circuit_breaker::PauseScope::Function(symbol_short!("REG_USR")),
-        )?;
         caller.require_auth();
-
-        if !whitelist::check_whitelist_access(&env, &caller) {
-            return Self::unauthorized(&env, &caller, "register_user", "whitelisted_caller");
+        if !Self::has_admin_access(&env, &caller, &AdminTier::ContractAdmin) {
+            return Self::unauthorized(
+                &env,
+                &caller,
+                "set_rate_limit_bypass",
+                "admin_tier:ContractAdmin",
+            );
+        }
+        rate_limit::set_rate_limit_bypass(&env, &address, bypass);
+        Ok(())
+    }
+
+    pub fn has_rate_limit_bypass(env: Env, address: Address) -> bool {
+        rate_limit::has_rate_limit_bypass(&env, &address)
+    }
+
+    /// Remaining budget for `address` on `operation` under the limit that
+    /// applies to its current role.
+    pub fn get_rate_limit_status(
+        env: Env,
+        address: Address,
+        operation: String,
+    ) -> Option<RateLimitStatus> {
+        let role = rbac::get_active_assignment(&env, &address).map(|a| a.role);
+        rate_limit::get_rate_limit_status(&env, &address, role.as_ref(), &operation)
+    }
+
+    pub fn get_rate_limit_stats(env: Env) -> RateLimitStats {
+        rate_limit::get_rate_limit_stats(&env)
+    }
+
+    /// Enables or disables whitelist enforcement globally.
+    ///
+    /// Requires at least `ContractAdmin` tier, or legacy admin/SystemAdmin.
+    pub fn set_whitelist_enabled(
+        env: Env,
+        caller: Address,
+        enabled: bool,
+    ) -> Result<(), ContractError> {
+        caller.require_auth();
+        if !Self::has_admin_access(&env, &caller, &AdminTier::ContractAdmin) {
+            return Self::unauthorized(
+                &env,
+                &caller,
+                "set_whitelist_enabled",
+                "admin_tier:ContractAdmin",
+            );
+        }
+        whitelist::set_whitelist_enabled(&env, enabled);
+        Ok(())
+    }
+
+    /// Adds an address to the whitelist.
+    ///
+    /// Requires at least `ContractAdmin` tier, or legacy admin/SystemAdmin.
+    pub fn add_to_whitelist(env: Env, caller: Address, user: Address) -> Result<(), ContractError> {
+        caller.require_auth();
+        if !Self::has_admin_access(&env, &caller, &AdminTier::ContractAdmin) {
+            return Self::unauthorized(
+                &env,
+                &caller,
+                "add_to_whitelist",
+                "admin_tier:ContractAdmin",
+            );
+        }
+        whitelist::add_to_whitelist(&env, &user);
+        Ok(())
+    }
+
+    /// Removes an address from the whitelist.
+    ///
+    /// Requires at least `ContractAdmin` tier, or legacy admin/SystemAdmin.
+    pub fn remove_from_whitelist(
+        env: Env,
+        caller: Address,
+        user: Address,
+    ) -> Result<(), ContractError> {
+        caller.require_auth();
+        if !Self::has_admin_access(&env, &caller, &AdminTier::ContractAdmin) {
+            return Self::unauthorized(
+                &env,
+                &caller,
+                "remove_from_whitelist",
+                "admin_tier:ContractAdmin",
+            );
+        }
+        whitelist::remove_from_whitelist(&env, &user);
+        Ok(())
+    }
+
+    pub fn is_whitelist_enabled(env: Env) -> bool {
+        whitelist::is_whitelist_enabled(&env)
+    }
+
+    pub fn is_whitelisted(env: Env, user: Address) -> bool {
+        whitelist::is_whitelisted(&env, &user)
+    }
+
+    /// Register a new user
+    pub fn register_user(
+        env: Env,
+        caller: Address,
+        user: Address,
+        role: Role,
+        name: String,
+    ) -> Result<(), ContractError> {
+        circuit_breaker::require_not_paused(
+            &env,
+            &circuit_breaker::PauseScope::Function(symbol_short!("REG_USR")),
+        )?;
+        caller.require_auth();
+
+        if !whitelist::check_whitelist_access(&env, &caller) {
+            return Self::unauthorized(&env, &caller, "register_user", "whitelisted_caller");
         }
 
         // Unified check: covers direct role, custom grants, and delegated roles
@@ -951,6 +1569,7 @@ impl VisionRecordsContract {
         }
 
         Self::enforce_rate_limit(&env, &caller)?;
+        Self::enforce_operation_limit(&env, &caller, rate_limit::OP_ADD_RECORD, 1)?;
 
         validation::validate_data_hash(&data_hash)?;
 
@@ -974,7 +1593,7 @@ impl VisionRecordsContract {
                 AccessResult::Denied,
                 Some(String::from_str(&env, "Insufficient permissions")),
             );
-            audit::add_audit_entry(&env, &audit_entry);
+            Self::record_audit(&env, &audit_entry);
             events::publish_audit_log_entry(&env, &audit_entry);
 
             let context = create_error_context(
@@ -999,38 +1618,14 @@ impl VisionRecordsContract {
             );
         }
 
+        Self::require_verified_provider(&env, &provider)?;
+
         // Generate record ID
         let counter_key = symbol_short!("REC_CTR");
         let record_id: u64 = env.storage().instance().get(&counter_key).unwrap_or(0) + 1;
         env.storage().instance().set(&counter_key, &record_id);
 
-        // Determine encryption key material (KeyManager preferred, fallback to ENC_KEY).
-        let (master_bytes, key_version) =
-            if let Some((bytes, ver)) = Self::derive_key_manager_bytes(&env, record_id, None)? {
-                (bytes, Some(ver))
-            } else {
-                let current_version: Option<String> = env.storage().instance().get(&ENC_CUR);
-                let mut master_bytes: StdVec<u8> = StdVec::new();
-                if let Some(ver) = current_version.clone() {
-                    if let Some(sv) = env
-                        .storage()
-                        .persistent()
-                        .get::<(Symbol, String), String>(&(ENC_KEY, ver.clone()))
-                    {
-                        let hex = sv.to_string();
-                        if let Some(bytes) = teye_common::hex_to_bytes(&hex) {
-                            master_bytes = bytes;
-                        }
-                    }
-                }
-                (master_bytes, current_version)
-            };
-
-        // Build KeyManager and encrypt the provided data_hash
-        let km = KeyManager::new(master_bytes);
-        let plaintext: StdString = data_hash.to_string();
-        let ciphertext = km.encrypt(None, &plaintext);
-        let stored_hash = String::from_str(&env, &ciphertext);
+        let (stored_hash, key_version) = Self::encrypt_data_hash(&env, record_id, &data_hash)?;
 
         let record = VisionRecord {
             id: record_id,
@@ -1043,29 +1638,25 @@ impl VisionRecordsContract {
             updated_at: env.ledger().timestamp(),
         };
 
-        let key = (symbol_short!("RECORD"), record_id);
-        env.storage().persistent().set(&key, &record);
-        extend_ttl_u64_key(&env, &key);
-        teye_common::concurrency::init_record_version(&env, record_id, 0);
+        Self::store_new_record(&env, &record);
 
         // Meter: write operation for the provider.
         Self::meter_op(&env, &provider, MeteringOpType::Write);
 
-        // Add to patient's record list
-        let patient_key = (symbol_short!("PAT_REC"), patient.clone());
-        let mut patient_records: Vec<u64> = env
-            .storage()
-            .persistent()
-            .get(&patient_key)
-            .unwrap_or(Vec::new(&env));
-        patient_records.push_back(record_id);
-        env.storage()
-            .persistent()
-            .set(&patient_key, &patient_records);
-
         Ok(record_id)
     }
 
+    /// Persists a freshly created record and adds it to the patient's record
+    /// list and the query indexes.
+    fn store_new_record(env: &Env, record: &VisionRecord) {
+        let key = (symbol_short!("RECORD"), record.id);
+        env.storage().persistent().set(&key, record);
+        extend_ttl_u64_key(env, &key);
+        teye_common::concurrency::init_record_version(env, record.id, 0);
+        Self::seal_record_envelope(env, record);
+        record_index::index_record(env, record);
+    }
+
     /// Add multiple vision records in a single transaction.
     /// Validates provider permission once, then creates all records atomically.
     #[allow(clippy::arithmetic_side_effects)]
@@ -1085,6 +1676,8 @@ impl VisionRecordsContract {
             return Self::unauthorized(&env, &provider, "add_records", "whitelisted_provider");
         }
 
+        Self::enforce_operation_limit(&env, &provider, rate_limit::OP_ADD_RECORD, records.len())?;
+
         // Check provider has WriteRecord permission once for the whole batch
         if !rbac::has_permission(&env, &provider, &Permission::WriteRecord)
             && !rbac::has_permission(&env, &provider, &Permission::SystemAdmin)
@@ -1097,6 +1690,8 @@ impl VisionRecordsContract {
             );
         }
 
+        Self::require_verified_provider(&env, &provider)?;
+
         let counter_key = symbol_short!("REC_CTR");
         let mut current_id: u64 = env.storage().instance().get(&counter_key).unwrap_or(0);
         let mut record_ids = Vec::new(&env);
@@ -1130,17 +1725,18 @@ impl VisionRecordsContract {
             let mut key_version = current_version.clone();
             if let Some((_, key_id)) = key_manager_cfg.as_ref() {
                 if let Some(client) = key_manager_client.as_ref() {
-                    let derived = client.derive_record_key(key_id, &current_id);
+                    let derived = client.derive_record_key(
+                        &env.current_contract_address(),
+                        key_id,
+                        &current_id,
+                    );
                     master_bytes = derived.key.to_array().to_vec();
                     key_version = Some(String::from_str(&env, &derived.version.to_string()));
                 }
             }
 
             // Encrypt input.data_hash with master bytes
-            let km = KeyManager::new(master_bytes);
-            let plaintext: StdString = input.data_hash.to_string();
-            let ciphertext = km.encrypt(None, &plaintext);
-            let stored_hash = String::from_str(&env, &ciphertext);
+            let stored_hash = Self::seal_data_hash(&env, master_bytes, &input.data_hash)?;
 
             let record = VisionRecord {
                 id: current_id,
@@ -1156,17 +1752,8 @@ impl VisionRecordsContract {
             let key = (symbol_short!("RECORD"), current_id);
             env.storage().persistent().set(&key, &record);
             teye_common::concurrency::init_record_version(&env, current_id, 0);
-
-            let patient_key = (symbol_short!("PAT_REC"), input.patient.clone());
-            let mut patient_records: Vec<u64> = env
-                .storage()
-                .persistent()
-                .get(&patient_key)
-                .unwrap_or(Vec::new(&env));
-            patient_records.push_back(current_id);
-            env.storage()
-                .persistent()
-                .set(&patient_key, &patient_records);
+            Self::seal_record_envelope(&env, &record);
+            record_index::index_record(&env, &record);
 
             events::publish_record_added(
                 &env,
@@ -1186,40 +1773,159 @@ impl VisionRecordsContract {
         Ok(record_ids)
     }
 
-    /// Get a vision record by ID.
+    /// Get a vision record by ID, read for treatment. A refused read fails
+    /// the call, which also discards its audit entry; use
+    /// `get_record_for_purpose` to have refusals recorded.
     pub fn get_record(
         env: Env,
         caller: Address,
         record_id: u64,
     ) -> Result<VisionRecord, ContractError> {
         caller.require_auth();
+        Self::enforce_operation_limit(&env, &caller, rate_limit::OP_GET_RECORD, 1)?;
+        Self::read_record(env, caller, record_id, &ConsentType::Treatment)
+    }
+
+    /// Get a vision record for a declared purpose of use. Callers reading on
+    /// the strength of patient consent need consent given for `purpose` that
+    /// covers the record's type. A refused read is audited and returned as
+    /// `RecordRead::Denied` with the error code (`ConsentPurposeMismatch` for
+    /// a purpose or record type the consent does not cover), so the audit
+    /// entry persists.
+    pub fn get_record_for_purpose(
+        env: Env,
+        caller: Address,
+        record_id: u64,
+        purpose: ConsentType,
+    ) -> Result<RecordRead, ContractError> {
+        caller.require_auth();
+        Self::enforce_operation_limit(&env, &caller, rate_limit::OP_GET_RECORD, 1)?;
+        match Self::read_record(env, caller, record_id, &purpose) {
+            Ok(record) => Ok(RecordRead::Granted(record)),
+            Err(
+                err @ (ContractError::Unauthorized
+                | ContractError::AccessDenied
+                | ContractError::ConsentPurposeMismatch),
+            ) => Ok(RecordRead::Denied(err as u32)),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Whether `caller` may read `record` for `purpose`, ignoring break-glass
+    /// access.
+    fn can_read_record(
+        env: &Env,
+        caller: &Address,
+        record: &VisionRecord,
+        purpose: &ConsentType,
+    ) -> ConsentScope {
+        if *caller == record.patient || *caller == record.provider {
+            // Patient can always read their own records
+            // Provider can read records they created
+            return ConsentScope::Covered;
+        }
+        // Broad read permissions, guardianship and record grants don't rest
+        // on consent; anyone else needs consent for this purpose.
+        if rbac::has_permission(env, caller, &Permission::ReadAnyRecord)
+            || rbac::has_permission(env, caller, &Permission::SystemAdmin)
+            || guardianship::has_guardian_permission(
+                env,
+                &record.patient,
+                caller,
+                &GuardianPermission::ViewRecords,
+            )
+            || Self::check_record_access(env.clone(), record.id, caller.clone())
+                != AccessLevel::None
+        {
+            return ConsentScope::Covered;
+        }
+        consent_scope(env, &record.patient, caller, purpose, &record.record_type)
+    }
+
+    /// Rejects a read of `record` by `caller` that an access policy on the
+    /// record or its sensitivity level forbids, auditing the denial.
+    /// Patients reading their own records are not subject to policies. The
+    /// audit entry only persists when the entrypoint returns the refusal
+    /// rather than failing, as `get_record_for_purpose` does.
+    fn enforce_read_policies(
+        env: &Env,
+        caller: &Address,
+        record: &VisionRecord,
+    ) -> Result<(), ContractError> {
+        if *caller == record.patient {
+            return Ok(());
+        }
+        let Some(policy_id) = rbac::violated_record_policy(env, caller, record.id, &record.patient)
+        else {
+            return Ok(());
+        };
+        let audit_entry = audit::create_audit_entry(
+            env,
+            caller.clone(),
+            record.patient.clone(),
+            Some(record.id),
+            AccessAction::Read,
+            AccessResult::Denied,
+            Some(String::from_str(
+                env,
+                &alloc::format!("Access policy not satisfied: {}", policy_id),
+            )),
+        );
+        Self::record_audit(env, &audit_entry);
+        events::publish_audit_log_entry(env, &audit_entry);
+        Err(ContractError::AccessDenied)
+    }
+
+    /// Access-checked, audited record read shared by `get_record` and the
+    /// entrypoints that operate on an existing record. Callers must have
+    /// already required `caller`'s authorization.
+    fn read_record(
+        env: Env,
+        caller: Address,
+        record_id: u64,
+        purpose: &ConsentType,
+    ) -> Result<VisionRecord, ContractError> {
         let key = (symbol_short!("RECORD"), record_id);
         match env.storage().persistent().get::<_, VisionRecord>(&key) {
             Some(record) => {
-                // Check access permissions
-                let has_access = if caller == record.patient || caller == record.provider {
-                    // Patient can always read their own records
-                    // Provider can read records they created
-                    true
+                let scope = Self::can_read_record(&env, &caller, &record, purpose);
+                let has_access = scope == ConsentScope::Covered;
+
+                // Break-glass: an active emergency grant allows the read, and
+                // every such read is audited and reported to the patient's
+                // emergency contact.
+                let emergency_access = if has_access {
+                    None
                 } else {
-                    // Check if caller has broad read permissions, active consent, or explicit grant
-                    rbac::has_permission(&env, &caller, &Permission::ReadAnyRecord)
-                        || rbac::has_permission(&env, &caller, &Permission::SystemAdmin)
-                        || has_active_consent(&env, &record.patient, &caller)
-                        || {
-                            let access_level = Self::check_access(
-                                env.clone(),
-                                record.patient.clone(),
-                                caller.clone(),
-                            );
-                            access_level != AccessLevel::None
-                        }
-                        || Self::check_record_access(env.clone(), record_id, caller.clone())
-                            != AccessLevel::None
+                    emergency::has_active_emergency_access(&env, &record.patient, &caller)
                 };
+                if let Some(access) = &emergency_access {
+                    Self::push_emergency_audit(&env, access.id, &caller, "ACCESSED");
+                    events::publish_emergency_access_used(
+                        &env,
+                        access.id,
+                        record.patient.clone(),
+                        caller.clone(),
+                        Some(record_id),
+                    );
+                    Self::notify_emergency_contact(&env, access, Some(record_id));
+                }
 
-                if !has_access {
+                if !has_access && emergency_access.is_none() {
                     // Log failed access attempt
+                    let reason = match scope {
+                        ConsentScope::OtherPurpose => String::from_str(
+                            &env,
+                            &alloc::format!(
+                                "Consent does not cover purpose: {}",
+                                purpose_label(purpose)
+                            ),
+                        ),
+                        ConsentScope::OtherRecordType => {
+                            String::from_str(&env, "Consent does not cover record type")
+                        }
+                        _ => String::from_str(&env, "Insufficient permissions"),
+                    };
                     let audit_entry = audit::create_audit_entry(
                         &env,
                         caller.clone(),
@@ -1227,25 +1933,56 @@ impl VisionRecordsContract {
                         Some(record_id),
                         AccessAction::Read,
                         AccessResult::Denied,
-                        Some(String::from_str(&env, "Insufficient permissions")),
+                        Some(reason),
                     );
-                    audit::add_audit_entry(&env, &audit_entry);
+                    Self::record_audit(&env, &audit_entry);
                     events::publish_audit_log_entry(&env, &audit_entry);
 
+                    if scope != ConsentScope::Absent {
+                        return Err(ContractError::ConsentPurposeMismatch);
+                    }
                     return Self::unauthorized(&env, &caller, "get_record", "record_read_access");
                 }
 
+                // Break-glass reads are not subject to access policies.
+                if emergency_access.is_none() {
+                    Self::enforce_read_policies(&env, &caller, &record)?;
+                }
+
+                if amendment::is_retracted(&env, record_id) {
+                    let audit_entry = audit::create_audit_entry(
+                        &env,
+                        caller.clone(),
+                        record.patient.clone(),
+                        Some(record_id),
+                        AccessAction::Read,
+                        AccessResult::Failure,
+                        Some(String::from_str(&env, "Record retracted")),
+                    );
+                    Self::record_audit(&env, &audit_entry);
+                    events::publish_audit_log_entry(&env, &audit_entry);
+                    return Err(ContractError::RecordRetracted);
+                }
+
                 // Log successful access
+                let action = if emergency_access.is_some() {
+                    AccessAction::EmergencyAccess
+                } else {
+                    AccessAction::Read
+                };
                 let audit_entry = audit::create_audit_entry(
                     &env,
                     caller.clone(),
                     record.patient.clone(),
                     Some(record_id),
-                    AccessAction::Read,
+                    action,
                     AccessResult::Success,
-                    None,
+                    Some(String::from_str(
+                        &env,
+                        &alloc::format!("Purpose: {}", purpose_label(purpose)),
+                    )),
                 );
-                audit::add_audit_entry(&env, &audit_entry);
+                Self::record_audit(&env, &audit_entry);
                 events::publish_audit_log_entry(&env, &audit_entry);
 
                 // Meter: read operation for the caller.
@@ -1253,52 +1990,12 @@ impl VisionRecordsContract {
 
                 // Decrypt data_hash for authorized caller before returning
                 let mut out_record = record.clone();
-                // Prefer KeyManager-derived key when configured; fallback to ENC_KEY
-                let mut master_bytes: StdVec<u8> = StdVec::new();
-                let mut used_key_manager = false;
-                if let Some(_cfg) = Self::get_key_manager_config(&env) {
-                    let version_u32 = out_record
-                        .key_version
-                        .as_ref()
-                        .and_then(Self::parse_key_version_u32);
-                    let allow_key_manager =
-                        out_record.key_version.is_none() || version_u32.is_some();
-                    if allow_key_manager {
-                        if let Some((bytes, _)) =
-                            Self::derive_key_manager_bytes(&env, record_id, version_u32)?
-                        {
-                            master_bytes = bytes;
-                            used_key_manager = true;
-                        }
-                    }
-                }
-
-                if !used_key_manager {
-                    let key_ver = out_record
-                        .key_version
-                        .clone()
-                        .or_else(|| env.storage().instance().get(&ENC_CUR));
-                    if let Some(ver) = key_ver {
-                        if let Some(sv) = env
-                            .storage()
-                            .persistent()
-                            .get::<(Symbol, String), String>(&(ENC_KEY, ver.clone()))
-                        {
-                            let hex = sv.to_string();
-                            if let Some(bytes) = teye_common::hex_to_bytes(&hex) {
-                                master_bytes = bytes;
-                            }
-                        }
-                    }
-                }
-
-                if !master_bytes.is_empty() || out_record.key_version.is_none() {
-                    let km = KeyManager::new(master_bytes);
-                    let ciphertext_std: StdString = out_record.data_hash.to_string();
-                    if let Some(plain) = km.decrypt(None, &ciphertext_std) {
-                        out_record.data_hash = String::from_str(&env, &plain);
-                    }
-                }
+                out_record.data_hash = Self::decrypt_data_hash(
+                    &env,
+                    record_id,
+                    &record.key_version,
+                    &record.data_hash,
+                )?;
 
                 Ok(out_record)
             }
@@ -1314,7 +2011,7 @@ impl VisionRecordsContract {
                     AccessResult::NotFound,
                     Some(String::from_str(&env, "Record not found")),
                 );
-                audit::add_audit_entry(&env, &audit_entry);
+                Self::record_audit(&env, &audit_entry);
                 events::publish_audit_log_entry(&env, &audit_entry);
 
                 let resource_id = String::from_str(&env, "get_record");
@@ -1337,51 +2034,477 @@ impl VisionRecordsContract {
         }
     }
 
-    /// Add eye examination details for an existing record
-    #[allow(clippy::too_many_arguments)]
-    pub fn add_eye_examination(
+    /// Get multiple vision records by ID in a single call.
+    ///
+    /// Each record goes through the same access, policy, retraction and
+    /// audit checks as `get_record`. Fails with `RecordNotFound` or
+    /// `Unauthorized` if any of the requested records cannot be read, and
+    /// with `InvalidInput` for an empty batch or one larger than
+    /// `MAX_BATCH_READ`.
+    pub fn get_records(
         env: Env,
         caller: Address,
-        record_id: u64,
-        visual_acuity: VisualAcuity,
-        iop: IntraocularPressure,
-        slit_lamp: SlitLampFindings,
-        visual_field: OptVisualField,
-        retina_imaging: OptRetinalImaging,
-        fundus_photo: OptFundusPhotography,
-        clinical_notes: String,
-    ) -> Result<(), ContractError> {
-        circuit_breaker::require_not_paused(&env, &circuit_breaker::PauseScope::Global)?;
+        record_ids: Vec<u64>,
+    ) -> Result<Vec<VisionRecord>, ContractError> {
         caller.require_auth();
+        if record_ids.is_empty() || record_ids.len() > MAX_BATCH_READ {
+            return Err(ContractError::InvalidInput);
+        }
+        Self::enforce_operation_limit(&env, &caller, rate_limit::OP_GET_RECORD, record_ids.len())?;
 
-        let record = Self::get_record(env.clone(), caller.clone(), record_id)?;
+        let mut records = Vec::new(&env);
+        for record_id in record_ids.iter() {
+            let record = Self::read_record(
+                env.clone(),
+                caller.clone(),
+                record_id,
+                &ConsentType::Treatment,
+            )?;
+            records.push_back(record);
+        }
+        Ok(records)
+    }
 
-        let has_perm = if caller == record.provider {
-            rbac::has_permission(&env, &caller, &Permission::WriteRecord)
+    /// Whether `caller` may modify `record`: its provider with `WriteRecord`,
+    /// a delegate of that provider, or a `SystemAdmin`.
+    fn can_write_record(env: &Env, caller: &Address, record: &VisionRecord) -> bool {
+        let has_perm = if *caller == record.provider {
+            rbac::has_permission(env, caller, &Permission::WriteRecord)
         } else {
-            rbac::has_delegated_permission(
-                &env,
-                &record.provider,
-                &caller,
-                &Permission::WriteRecord,
-            )
+            rbac::has_delegated_permission(env, &record.provider, caller, &Permission::WriteRecord)
         };
+        has_perm || rbac::has_permission(env, caller, &Permission::SystemAdmin)
+    }
 
-        if !has_perm && !rbac::has_permission(&env, &caller, &Permission::SystemAdmin) {
-            return Self::unauthorized(
-                &env,
-                &caller,
-                "add_eye_examination",
-                "permission:WriteRecord_or_SystemAdmin",
-            );
-        }
-
-        if record.record_type != RecordType::Examination {
-            return Err(ContractError::InvalidRecordType);
-        }
-
-        let exam = EyeExamination {
-            record_id,
+    /// Amend a record's `data_hash` under optimistic concurrency control.
+    ///
+    /// The superseded hash is kept in the record's version chain together
+    /// with the author and reason of every amendment. On a `Conflicted`
+    /// outcome the record is left unchanged.
+    pub fn amend_record(
+        env: Env,
+        caller: Address,
+        record_id: u64,
+        expected_version: u64,
+        node_id: u32,
+        data_hash: String,
+        reason: String,
+    ) -> Result<UpdateOutcome, ContractError> {
+        circuit_breaker::require_not_paused(&env, &circuit_breaker::PauseScope::Global)?;
+        caller.require_auth();
+
+        validation::validate_data_hash(&data_hash)?;
+        if reason.is_empty() {
+            return Err(ContractError::InvalidInput);
+        }
+
+        let key = (symbol_short!("RECORD"), record_id);
+        let mut record: VisionRecord = env
+            .storage()
+            .persistent()
+            .get(&key)
+            .ok_or(ContractError::RecordNotFound)?;
+
+        if !Self::can_write_record(&env, &caller, &record) {
+            return Self::unauthorized(
+                &env,
+                &caller,
+                "amend_record",
+                "permission:WriteRecord_or_SystemAdmin",
+            );
+        }
+        if amendment::is_retracted(&env, record_id) {
+            return Err(ContractError::RecordRetracted);
+        }
+
+        let previous_version = teye_common::concurrency::get_record_version(&env, record_id);
+        let (stored_hash, key_version) = Self::encrypt_data_hash(&env, record_id, &data_hash)?;
+
+        let mut changed_fields = Vec::new(&env);
+        changed_fields.push_back(FieldChange {
+            field_name: String::from_str(&env, "data_hash"),
+            old_hash: record.data_hash.clone(),
+            new_hash: stored_hash.clone(),
+        });
+        let outcome = teye_common::concurrency::compare_and_swap(
+            &env,
+            record_id,
+            expected_version,
+            node_id,
+            &caller,
+            &changed_fields,
+        );
+        let stamp = match &outcome {
+            UpdateOutcome::Applied(stamp) | UpdateOutcome::Merged(stamp) => stamp.clone(),
+            UpdateOutcome::Conflicted(_) => return Ok(outcome),
+        };
+        teye_common::concurrency::save_field_snapshot(&env, record_id, &changed_fields);
+
+        let now = env.ledger().timestamp();
+        let original = RecordVersion {
+            version: previous_version,
+            data_hash: record.data_hash.clone(),
+            key_version: record.key_version.clone(),
+            author: record.provider.clone(),
+            reason: String::from_str(&env, ""),
+            created_at: record.created_at,
+        };
+        let amended = RecordVersion {
+            version: stamp.version,
+            data_hash: stored_hash.clone(),
+            key_version: key_version.clone(),
+            author: caller.clone(),
+            reason: reason.clone(),
+            created_at: now,
+        };
+        amendment::push_version(&env, record_id, &original, &amended);
+
+        record.data_hash = stored_hash;
+        record.key_version = key_version;
+        record.updated_at = now;
+        env.storage().persistent().set(&key, &record);
+        extend_ttl_u64_key(&env, &key);
+        Self::seal_record_envelope(&env, &record);
+
+        let audit_entry = audit::create_audit_entry(
+            &env,
+            caller.clone(),
+            record.patient.clone(),
+            Some(record_id),
+            AccessAction::Write,
+            AccessResult::Success,
+            Some(reason.clone()),
+        );
+        Self::record_audit(&env, &audit_entry);
+        events::publish_audit_log_entry(&env, &audit_entry);
+        events::publish_record_amended(
+            &env,
+            record_id,
+            record.patient,
+            caller,
+            stamp.version,
+            reason,
+        );
+
+        Ok(outcome)
+    }
+
+    /// Retract a record, leaving a tombstone with a reason code. The record
+    /// and its version chain are kept for provenance, but `get_record` and
+    /// further amendments fail with `RecordRetracted`.
+    pub fn retract_record(
+        env: Env,
+        caller: Address,
+        record_id: u64,
+        reason: RetractionReason,
+        note: String,
+    ) -> Result<(), ContractError> {
+        circuit_breaker::require_not_paused(&env, &circuit_breaker::PauseScope::Global)?;
+        caller.require_auth();
+
+        let record: VisionRecord = env
+            .storage()
+            .persistent()
+            .get(&(symbol_short!("RECORD"), record_id))
+            .ok_or(ContractError::RecordNotFound)?;
+
+        if !Self::can_write_record(&env, &caller, &record) {
+            return Self::unauthorized(
+                &env,
+                &caller,
+                "retract_record",
+                "permission:WriteRecord_or_SystemAdmin",
+            );
+        }
+        if amendment::is_retracted(&env, record_id) {
+            return Err(ContractError::RecordRetracted);
+        }
+
+        // Retraction is a new version of the record, applied unconditionally.
+        let current = teye_common::concurrency::get_record_version(&env, record_id);
+        let version = match teye_common::concurrency::compare_and_swap(
+            &env,
+            record_id,
+            current,
+            0,
+            &caller,
+            &Vec::new(&env),
+        ) {
+            UpdateOutcome::Applied(stamp) | UpdateOutcome::Merged(stamp) => stamp.version,
+            UpdateOutcome::Conflicted(_) => return Err(ContractError::VersionConflict),
+        };
+
+        amendment::set_tombstone(
+            &env,
+            &Tombstone {
+                record_id,
+                retracted_by: caller.clone(),
+                retracted_at: env.ledger().timestamp(),
+                reason: reason.clone(),
+                note: note.clone(),
+                version,
+            },
+        );
+
+        let audit_entry = audit::create_audit_entry(
+            &env,
+            caller.clone(),
+            record.patient.clone(),
+            Some(record_id),
+            AccessAction::Delete,
+            AccessResult::Success,
+            Some(note),
+        );
+        Self::record_audit(&env, &audit_entry);
+        events::publish_audit_log_entry(&env, &audit_entry);
+        events::publish_record_retracted(&env, record_id, record.patient, caller, reason);
+
+        Ok(())
+    }
+
+    /// Return a record's version chain, oldest first, with each `data_hash`
+    /// decrypted. The last entry is the current version. Retracted records
+    /// stay readable here so their provenance can be reviewed.
+    pub fn get_record_history(
+        env: Env,
+        caller: Address,
+        record_id: u64,
+    ) -> Result<Vec<RecordVersion>, ContractError> {
+        caller.require_auth();
+
+        let record: VisionRecord = env
+            .storage()
+            .persistent()
+            .get(&(symbol_short!("RECORD"), record_id))
+            .ok_or(ContractError::RecordNotFound)?;
+
+        match Self::can_read_record(&env, &caller, &record, &ConsentType::Treatment) {
+            ConsentScope::Covered => {}
+            ConsentScope::Absent => {
+                return Self::unauthorized(
+                    &env,
+                    &caller,
+                    "get_record_history",
+                    "record_read_access",
+                );
+            }
+            _ => return Err(ContractError::ConsentPurposeMismatch),
+        }
+        Self::enforce_read_policies(&env, &caller, &record)?;
+
+        let mut stored = amendment::get_history(&env, record_id);
+        if stored.is_empty() {
+            stored.push_back(RecordVersion {
+                version: teye_common::concurrency::get_record_version(&env, record_id),
+                data_hash: record.data_hash.clone(),
+                key_version: record.key_version.clone(),
+                author: record.provider.clone(),
+                reason: String::from_str(&env, ""),
+                created_at: record.created_at,
+            });
+        }
+
+        let mut history = Vec::new(&env);
+        for mut entry in stored.iter() {
+            entry.data_hash =
+                Self::decrypt_data_hash(&env, record_id, &entry.key_version, &entry.data_hash)?;
+            history.push_back(entry);
+        }
+
+        let audit_entry = audit::create_audit_entry(
+            &env,
+            caller,
+            record.patient,
+            Some(record_id),
+            AccessAction::Read,
+            AccessResult::Success,
+            None,
+        );
+        Self::record_audit(&env, &audit_entry);
+        events::publish_audit_log_entry(&env, &audit_entry);
+
+        Ok(history)
+    }
+
+    /// Return the tombstone of a retracted record, if any.
+    pub fn get_record_tombstone(env: Env, record_id: u64) -> Option<Tombstone> {
+        amendment::get_tombstone(&env, record_id)
+    }
+
+    /// Attach files (imaging, reports) to a record. Same authorization as
+    /// amending the record. Returns the new attachment ids.
+    pub fn add_attachments(
+        env: Env,
+        caller: Address,
+        record_id: u64,
+        attachments: Vec<AttachmentInput>,
+    ) -> Result<Vec<u32>, ContractError> {
+        circuit_breaker::require_not_paused(&env, &circuit_breaker::PauseScope::Global)?;
+        caller.require_auth();
+
+        let record = Self::read_record(
+            env.clone(),
+            caller.clone(),
+            record_id,
+            &ConsentType::Treatment,
+        )?;
+        if !Self::can_write_record(&env, &caller, &record) {
+            return Self::unauthorized(
+                &env,
+                &caller,
+                "add_attachments",
+                "permission:WriteRecord_or_SystemAdmin",
+            );
+        }
+
+        let ids = attachment::add_attachments(&env, record_id, &caller, &attachments)?;
+        for (id, input) in ids.iter().zip(attachments.iter()) {
+            events::publish_attachment_added(
+                &env,
+                record_id,
+                id,
+                input.kind,
+                input.content_hash,
+                caller.clone(),
+            );
+        }
+        Ok(ids)
+    }
+
+    /// Remove an attachment from a record's manifest.
+    pub fn remove_attachment(
+        env: Env,
+        caller: Address,
+        record_id: u64,
+        attachment_id: u32,
+    ) -> Result<(), ContractError> {
+        circuit_breaker::require_not_paused(&env, &circuit_breaker::PauseScope::Global)?;
+        caller.require_auth();
+
+        let record: VisionRecord = env
+            .storage()
+            .persistent()
+            .get(&(symbol_short!("RECORD"), record_id))
+            .ok_or(ContractError::RecordNotFound)?;
+        if !Self::can_write_record(&env, &caller, &record) {
+            return Self::unauthorized(
+                &env,
+                &caller,
+                "remove_attachment",
+                "permission:WriteRecord_or_SystemAdmin",
+            );
+        }
+        if amendment::is_retracted(&env, record_id) {
+            return Err(ContractError::RecordRetracted);
+        }
+
+        let removed = attachment::remove_attachment(&env, record_id, attachment_id)?;
+        events::publish_attachment_removed(
+            &env,
+            record_id,
+            attachment_id,
+            removed.content_hash,
+            caller,
+        );
+        Ok(())
+    }
+
+    /// A record's attachment manifest, for callers who may read the record.
+    pub fn get_attachment_manifest(
+        env: Env,
+        caller: Address,
+        record_id: u64,
+    ) -> Result<Option<AttachmentManifest>, ContractError> {
+        caller.require_auth();
+        Self::read_record(env.clone(), caller, record_id, &ConsentType::Treatment)?;
+        Ok(attachment::get_manifest(&env, record_id))
+    }
+
+    /// Whether a downloaded file matches the hash and size recorded for
+    /// the attachment. Same access as reading the record.
+    pub fn verify_attachment(
+        env: Env,
+        caller: Address,
+        record_id: u64,
+        attachment_id: u32,
+        content_hash: BytesN<32>,
+        size_bytes: u64,
+    ) -> Result<bool, ContractError> {
+        caller.require_auth();
+        Self::read_record(env.clone(), caller, record_id, &ConsentType::Treatment)?;
+        Ok(attachment::verify_attachment(
+            &env,
+            record_id,
+            attachment_id,
+            &content_hash,
+            size_bytes,
+        ))
+    }
+
+    /// Digest of a record's attachment manifest, see
+    /// [`attachment::manifest_digest`]. `None` if nothing is attached.
+    /// Same access as reading the record.
+    pub fn get_attachment_digest(
+        env: Env,
+        caller: Address,
+        record_id: u64,
+    ) -> Result<Option<BytesN<32>>, ContractError> {
+        caller.require_auth();
+        Self::read_record(env.clone(), caller, record_id, &ConsentType::Treatment)?;
+        Ok(attachment::get_manifest(&env, record_id).map(|m| m.digest))
+    }
+
+    /// Add eye examination details for an existing record
+    #[allow(clippy::too_many_arguments)]
+    pub fn add_eye_examination(
+        env: Env,
+        caller: Address,
+        record_id: u64,
+        visual_acuity: VisualAcuity,
+        iop: IntraocularPressure,
+        slit_lamp: SlitLampFindings,
+        visual_field: OptVisualField,
+        retina_imaging: OptRetinalImaging,
+        fundus_photo: OptFundusPhotography,
+        clinical_notes: String,
+    ) -> Result<(), ContractError> {
+        circuit_breaker::require_not_paused(&env, &circuit_breaker::PauseScope::Global)?;
+        caller.require_auth();
+
+        let record = Self::read_record(
+            env.clone(),
+            caller.clone(),
+            record_id,
+            &ConsentType::Treatment,
+        )?;
+
+        let has_perm = if caller == record.provider {
+            rbac::has_permission(&env, &caller, &Permission::WriteRecord)
+        } else {
+            rbac::has_delegated_permission(
+                &env,
+                &record.provider,
+                &caller,
+                &Permission::WriteRecord,
+            )
+        };
+
+        if !has_perm && !rbac::has_permission(&env, &caller, &Permission::SystemAdmin) {
+            return Self::unauthorized(
+                &env,
+                &caller,
+                "add_eye_examination",
+                "permission:WriteRecord_or_SystemAdmin",
+            );
+        }
+
+        if record.record_type != RecordType::Examination {
+            return Err(ContractError::InvalidRecordType);
+        }
+        examination::validate_examination(&visual_acuity, &iop, &visual_field, &fundus_photo)?;
+
+        let exam = EyeExamination {
+            record_id,
             visual_acuity,
             iop,
             slit_lamp,
@@ -1426,7 +2549,12 @@ impl VisionRecordsContract {
         circuit_breaker::require_not_paused(&env, &circuit_breaker::PauseScope::Global)?;
         caller.require_auth();
 
-        let record = Self::get_record(env.clone(), caller.clone(), record_id)?;
+        let record = Self::read_record(
+            env.clone(),
+            caller.clone(),
+            record_id,
+            &ConsentType::Treatment,
+        )?;
 
         let has_perm = if caller == record.provider {
             rbac::has_permission(&env, &caller, &Permission::WriteRecord)
@@ -1451,6 +2579,7 @@ impl VisionRecordsContract {
         if record.record_type != RecordType::Examination {
             return Err(ContractError::InvalidRecordType);
         }
+        examination::validate_examination(&visual_acuity, &iop, &visual_field, &fundus_photo)?;
 
         let exam = EyeExamination {
             record_id,
@@ -1482,7 +2611,12 @@ impl VisionRecordsContract {
         record_id: u64,
     ) -> Result<EyeExamination, ContractError> {
         caller.require_auth();
-        let record = Self::get_record(env.clone(), caller.clone(), record_id)?;
+        let record = Self::read_record(
+            env.clone(),
+            caller.clone(),
+            record_id,
+            &ConsentType::Treatment,
+        )?;
 
         let has_perm = if caller == record.patient || caller == record.provider {
             true
@@ -1517,7 +2651,12 @@ impl VisionRecordsContract {
     ) -> Result<(), ContractError> {
         caller.require_auth();
 
-        let record = Self::get_record(env.clone(), caller.clone(), record_id)?;
+        let record = Self::read_record(
+            env.clone(),
+            caller.clone(),
+            record_id,
+            &ConsentType::Treatment,
+        )?;
         let has_perm = if caller == record.provider {
             rbac::has_permission(&env, &caller, &Permission::WriteRecord)
         } else {
@@ -1562,8 +2701,8 @@ impl VisionRecordsContract {
         caller.require_auth();
 
         let admin = Self::get_admin(env.clone())?;
-        let has_admin = caller == admin
-            || rbac::has_permission(&env, &caller, &Permission::SystemAdmin);
+        let has_admin =
+            caller == admin || rbac::has_permission(&env, &caller, &Permission::SystemAdmin);
 
         if !has_admin {
             let key = (symbol_short!("RECORD"), record_id);
@@ -1601,13 +2740,280 @@ impl VisionRecordsContract {
         Ok(())
     }
 
-    /// Get all records for a patient
-    pub fn get_patient_records(env: Env, patient: Address) -> Vec<u64> {
-        let key = (symbol_short!("PAT_REC"), patient);
+    /// Get the total number of records created
+    pub fn get_record_count(env: Env) -> u64 {
         env.storage()
-            .persistent()
-            .get(&key)
-            .unwrap_or(Vec::new(&env))
+            .instance()
+            .get(&symbol_short!("REC_CTR"))
+            .unwrap_or(0)
+    }
+
+    /// Get the first `MAX_PAGE_LIMIT` record ids of a patient.
+    ///
+    /// Deprecated: use `get_patient_records_page` to see every record.
+    pub fn get_patient_records(env: Env, patient: Address) -> Vec<u64> {
+        record_index::page_patient_records(
+            &env,
+            &patient,
+            None,
+            pagination::MAX_PAGE_LIMIT,
+            false,
+            |_| true,
+        )
+        .record_ids
+    }
+
+    /// Page through a patient's record ids in ascending id order, optionally
+    /// filtered by record type and creation date. `cursor` is the position
+    /// returned as `next_cursor` by the previous page (`None` to start).
+    pub fn get_patient_records_page(
+        env: Env,
+        patient: Address,
+        filter: RecordFilter,
+        cursor: Option<u64>,
+        limit: u32,
+    ) -> Result<RecordPage, ContractError> {
+        pagination::validate_limit(limit)?;
+        pagination::validate_range(filter.from, filter.to)?;
+
+        Ok(record_index::page_patient_records(
+            &env,
+            &patient,
+            cursor,
+            limit,
+            false,
+            Self::record_filter(&env, &filter),
+        ))
+    }
+
+    /// Keeps the record ids whose records match `filter`, loading a record
+    /// only when the filter needs it.
+    fn record_filter<'a>(env: &'a Env, filter: &'a RecordFilter) -> impl FnMut(u64) -> bool + 'a {
+        let unfiltered =
+            filter.record_types.is_empty() && filter.from.is_none() && filter.to.is_none();
+        move |record_id| {
+            if unfiltered {
+                return true;
+            }
+            let key = (symbol_short!("RECORD"), record_id);
+            let Some(record) = env.storage().persistent().get::<_, VisionRecord>(&key) else {
+                return false;
+            };
+            (filter.record_types.is_empty() || filter.record_types.contains(&record.record_type))
+                && pagination::in_range(record.created_at, filter.from, filter.to)
+        }
+    }
+
+    /// Page through the records a provider created, optionally filtered by
+    /// record type and creation date. `newest_first` lists the most recent
+    /// records first; `cursor` is the `next_cursor` of the previous page.
+    pub fn get_provider_records_page(
+        env: Env,
+        provider: Address,
+        filter: RecordFilter,
+        newest_first: bool,
+        cursor: Option<u64>,
+        limit: u32,
+    ) -> Result<RecordPage, ContractError> {
+        pagination::validate_limit(limit)?;
+        pagination::validate_range(filter.from, filter.to)?;
+
+        Ok(record_index::page_provider_records(
+            &env,
+            &provider,
+            cursor,
+            limit,
+            newest_first,
+            Self::record_filter(&env, &filter),
+        ))
+    }
+
+    /// Page through a patient's records of one type.
+    pub fn get_patient_records_by_type(
+        env: Env,
+        patient: Address,
+        record_type: RecordType,
+        newest_first: bool,
+        cursor: Option<u64>,
+        limit: u32,
+    ) -> Result<RecordPage, ContractError> {
+        pagination::validate_limit(limit)?;
+
+        Ok(record_index::page_patient_type_records(
+            &env,
+            &patient,
+            &record_type,
+            cursor,
+            limit,
+            newest_first,
+        ))
+    }
+
+    /// Page through the records `provider` created on `day`, counted in
+    /// whole days since the Unix epoch (UTC), i.e. `created_at / 86400`.
+    pub fn get_records_by_day(
+        env: Env,
+        provider: Address,
+        day: u64,
+        newest_first: bool,
+        cursor: Option<u64>,
+        limit: u32,
+    ) -> Result<RecordPage, ContractError> {
+        pagination::validate_limit(limit)?;
+
+        Ok(record_index::page_day_records(
+            &env,
+            &provider,
+            day,
+            cursor,
+            limit,
+            newest_first,
+        ))
+    }
+
+    /// Add up to `max_records` records created before the provider, type
+    /// and day indexes existed to those indexes, resuming where the
+    /// previous call stopped. Returns the number of records still to add;
+    /// call again until it is zero.
+    pub fn backfill_record_indexes(
+        env: Env,
+        caller: Address,
+        max_records: u32,
+    ) -> Result<u64, ContractError> {
+        caller.require_auth();
+        if !Self::has_admin_access(&env, &caller, &AdminTier::ContractAdmin) {
+            return Err(ContractError::Unauthorized);
+        }
+        if max_records == 0 || max_records > record_index::MAX_BACKFILL_BATCH {
+            return Err(ContractError::InvalidInput);
+        }
+
+        let last_record_id = Self::get_record_count(env.clone());
+        Ok(record_index::backfill(&env, last_record_id, max_records))
+    }
+
+    /// Page through the patient-level access grants a patient has issued,
+    /// in the order grantees were first granted access. Revoked grants are
+    /// skipped; expired grants are returned so callers can see their history.
+    pub fn get_patient_grants(
+        env: Env,
+        patient: Address,
+        cursor: Option<u64>,
+        limit: u32,
+    ) -> Result<GrantPage, ContractError> {
+        pagination::validate_limit(limit)?;
+
+        let (grants, next_cursor) = Self::grantee_list(&patient).page_map(
+            &env,
+            cursor,
+            limit,
+            false,
+            |grantee: Address| {
+                env.storage()
+                    .persistent()
+                    .get(&(symbol_short!("ACCESS"), patient.clone(), grantee))
+            },
+            |_: &AccessGrant| true,
+        );
+        Ok(GrantPage {
+            grants,
+            next_cursor,
+        })
+    }
+
+    /// Page through the access audit log in ascending id order.
+    ///
+    /// Patients may query entries about themselves (`filter.patient` set to
+    /// the caller); any other query requires `SystemAdmin`.
+    pub fn get_audit_log(
+        env: Env,
+        caller: Address,
+        filter: AuditFilter,
+        cursor: Option<u64>,
+        limit: u32,
+    ) -> Result<AuditPage, ContractError> {
+        caller.require_auth();
+        pagination::validate_limit(limit)?;
+        pagination::validate_range(filter.from, filter.to)?;
+
+        let own_log = filter.patient.as_ref() == Some(&caller);
+        if !own_log && !rbac::has_permission(&env, &caller, &Permission::SystemAdmin) {
+            return Self::unauthorized(&env, &caller, "get_audit_log", "patient_or_SystemAdmin");
+        }
+
+        Ok(audit::query_audit_log(&env, &filter, cursor, limit))
+    }
+
+    /// Accounting of disclosures: everyone other than the patient who
+    /// accessed, or tried to access, the patient's data in `from..=to`,
+    /// with per-accessor counts and last access time. Walks the patient's
+    /// audit index a page at a time; pass `next_cursor` back as `cursor`
+    /// until it is `None`.
+    ///
+    /// Only the patient or one of their active delegates may request it.
+    pub fn get_access_report(
+        env: Env,
+        caller: Address,
+        patient: Address,
+        from: u64,
+        to: u64,
+        cursor: Option<u64>,
+    ) -> Result<AccessReport, ContractError> {
+        caller.require_auth();
+        pagination::validate_range(Some(from), Some(to))?;
+
+        if caller != patient && !Self::is_delegate_of(&env, &patient, &caller) {
+            return Self::unauthorized(&env, &caller, "get_access_report", "patient_or_delegate");
+        }
+
+        Ok(audit::build_access_report(
+            &env,
+            &patient,
+            from,
+            to,
+            cursor,
+            |accessor| Self::classify_accessor(&env, &patient, accessor),
+        ))
+    }
+
+    /// Stores an audit entry with how its actor relates to the patient now,
+    /// so access reports reflect the relationship at access time.
+    fn record_audit(env: &Env, entry: &AuditEntry) {
+        let kind = if entry.actor == entry.patient {
+            None
+        } else if entry.action == AccessAction::EmergencyAccess {
+            Some(AccessorKind::Emergency)
+        } else {
+            Some(Self::classify_accessor(env, &entry.patient, &entry.actor))
+        };
+        audit::add_audit_entry(env, entry, kind);
+    }
+
+    fn is_delegate_of(env: &Env, delegator: &Address, delegatee: &Address) -> bool {
+        rbac::get_active_delegation(env, delegator, delegatee).is_some()
+            || rbac::get_active_scoped_delegation(env, delegator, delegatee).is_some()
+    }
+
+    /// How `accessor` currently relates to `patient`.
+    fn classify_accessor(env: &Env, patient: &Address, accessor: &Address) -> AccessorKind {
+        if Self::is_delegate_of(env, patient, accessor) {
+            AccessorKind::Delegate
+        } else if env.storage().persistent().has(&(
+            symbol_short!("ACCESS"),
+            patient.clone(),
+            accessor.clone(),
+        )) || has_active_consent(env, patient, accessor)
+        {
+            AccessorKind::Grantee
+        } else if rbac::has_permission(env, accessor, &Permission::ReadAnyRecord)
+            || rbac::has_permission(env, accessor, &Permission::SystemAdmin)
+        {
+            AccessorKind::Privileged
+        } else if emergency::has_active_emergency_access(env, patient, accessor).is_some() {
+            AccessorKind::Emergency
+        } else {
+            AccessorKind::Other
+        }
     }
 
     /// Grant access to a user
@@ -1628,6 +3034,7 @@ impl VisionRecordsContract {
         caller.require_auth();
 
         Self::enforce_rate_limit(&env, &caller)?;
+        Self::enforce_operation_limit(&env, &caller, rate_limit::OP_GRANT_ACCESS, 1)?;
 
         validation::validate_duration(duration_seconds)?;
 
@@ -1636,6 +3043,13 @@ impl VisionRecordsContract {
         } else {
             // Specific patient→caller delegation for ManageAccess
             rbac::has_delegated_permission(&env, &patient, &caller, &Permission::ManageAccess)
+                // Or caller is the patient's guardian with ManageAccess
+                || guardianship::has_guardian_permission(
+                    &env,
+                    &patient,
+                    &caller,
+                    &GuardianPermission::ManageAccess,
+                )
                 // Or caller has SystemAdmin (unified: direct + any delegation)
                 || rbac::has_permission(&env, &caller, &Permission::SystemAdmin)
         };
@@ -1651,7 +3065,7 @@ impl VisionRecordsContract {
                 AccessResult::Denied,
                 Some(String::from_str(&env, "Insufficient permissions")),
             );
-            audit::add_audit_entry(&env, &audit_entry);
+            Self::record_audit(&env, &audit_entry);
             events::publish_audit_log_entry(&env, &audit_entry);
             return Self::unauthorized(
                 &env,
@@ -1674,25 +3088,7 @@ impl VisionRecordsContract {
         env.storage().persistent().set(&key, &grant);
         extend_ttl_access_key(&env, &key);
 
-        // Track the grantee address in the patient's grantee list for purge iteration.
-        let list_key = (symbol_short!("ACC_LST"), patient.clone());
-        let mut grantees: Vec<Address> = env
-            .storage()
-            .persistent()
-            .get(&list_key)
-            .unwrap_or(Vec::new(&env));
-        // Avoid duplicates: only append if not already present.
-        let mut found = false;
-        for i in 0..grantees.len() {
-            if grantees.get(i) == Some(grantee.clone()) {
-                found = true;
-                break;
-            }
-        }
-        if !found {
-            grantees.push_back(grantee.clone());
-            env.storage().persistent().set(&list_key, &grantees);
-        }
+        Self::track_grantee(&env, &patient, &grantee);
 
         events::publish_access_granted(&env, patient, grantee, level, duration_seconds, expires_at);
 
@@ -1714,6 +3110,8 @@ impl VisionRecordsContract {
             return Err(ContractError::InvalidInput);
         }
 
+        Self::enforce_operation_limit(&env, &patient, rate_limit::OP_GRANT_ACCESS, grants.len())?;
+
         let now = env.ledger().timestamp();
         for grant in grants.iter() {
             let expires_at = now + grant.duration_seconds;
@@ -1730,6 +3128,7 @@ impl VisionRecordsContract {
                 grant.grantee.clone(),
             );
             env.storage().persistent().set(&key, &access_grant);
+            Self::track_grantee(&env, &patient, &grant.grantee);
 
             events::publish_access_granted(
                 &env,
@@ -1741,15 +3140,54 @@ impl VisionRecordsContract {
             );
         }
 
+        circuit_breaker::record_signal(
+            &env,
+            circuit_breaker::TripTrigger::BatchGrantBurst,
+            &patient,
+        );
         events::publish_batch_access_granted(&env, patient, grants.len());
 
         Ok(())
     }
 
-    /// Check access level with ABAC policy evaluation
+    /// The grantees a patient has ever granted patient-level access, in the
+    /// order they were first granted.
+    fn grantee_list(patient: &Address) -> pagination::BucketList<Address> {
+        pagination::BucketList::new(symbol_short!("ACC_LST"), patient.clone())
+    }
+
+    /// Track the grantee address in the patient's grantee list, used for grant
+    /// listings. Duplicates are not appended.
+    fn track_grantee(env: &Env, patient: &Address, grantee: &Address) {
+        let listed_key = (symbol_short!("ACC_LSTD"), patient.clone(), grantee.clone());
+        if !env.storage().persistent().has(&listed_key) {
+            env.storage().persistent().set(&listed_key, &true);
+            extend_ttl_access_key(env, &listed_key);
+            Self::grantee_list(patient).push(env, grantee);
+        }
+    }
+
+    /// Check access level with ABAC policy evaluation. Guardians holding
+    /// `ViewRecords` get at least `Read`.
     pub fn check_access(env: Env, patient: Address, grantee: Address) -> AccessLevel {
+        let level = Self::granted_access_level(&env, &patient, &grantee);
+        if level == AccessLevel::None
+            && guardianship::has_guardian_permission(
+                &env,
+                &patient,
+                &grantee,
+                &GuardianPermission::ViewRecords,
+            )
+        {
+            return AccessLevel::Read;
+        }
+        level
+    }
+
+    /// Access level from consent plus an explicit grant.
+    fn granted_access_level(env: &Env, patient: &Address, grantee: &Address) -> AccessLevel {
         // First check traditional consent-based access
-        if !has_active_consent(&env, &patient, &grantee) {
+        if !has_active_consent(env, patient, grantee) {
             return AccessLevel::None;
         }
 
@@ -1760,9 +3198,9 @@ impl VisionRecordsContract {
                 // ABAC is optional here: if no policies are configured, valid
                 // consent+grant should still provide access.
                 let default_policy_ids = [
-                    String::from_str(&env, "default_medical_access"),
-                    String::from_str(&env, "emergency_access"),
-                    String::from_str(&env, "research_access"),
+                    String::from_str(env, "default_medical_access"),
+                    String::from_str(env, "emergency_access"),
+                    String::from_str(env, "research_access"),
                 ];
                 let mut has_any_policy = false;
                 for policy_id in default_policy_ids {
@@ -1774,7 +3212,7 @@ impl VisionRecordsContract {
                 }
 
                 if !has_any_policy
-                    || evaluate_access_policies(&env, &grantee, None, Some(patient.clone()))
+                    || evaluate_access_policies(env, grantee, None, Some(patient.clone()))
                 {
                     return grant.level;
                 }
@@ -1784,7 +3222,9 @@ impl VisionRecordsContract {
         AccessLevel::None
     }
 
-    /// Grant record-level access to a specific record.
+    /// Grant record-level access to a specific record. This does not give the
+    /// grantee the record's data key: the patient or provider wraps it for
+    /// them off-chain and hands it over with `submit_wrapped_record_key`.
     #[allow(clippy::arithmetic_side_effects)]
     pub fn grant_record_access(
         env: Env,
@@ -1808,6 +3248,27 @@ impl VisionRecordsContract {
             return Self::unauthorized(&env, &patient, "grant_record_access", "record_owner");
         }
 
+        Self::store_record_grant(
+            &env,
+            &patient,
+            &grantee,
+            record_id,
+            &level,
+            duration_seconds,
+        );
+        Ok(())
+    }
+
+    /// Writes a record-level grant.
+    #[allow(clippy::arithmetic_side_effects)]
+    fn store_record_grant(
+        env: &Env,
+        patient: &Address,
+        grantee: &Address,
+        record_id: u64,
+        level: &AccessLevel,
+        duration_seconds: u64,
+    ) {
         let now = env.ledger().timestamp();
         let expires_at = now + duration_seconds;
         let grant = AccessGrant {
@@ -1820,18 +3281,61 @@ impl VisionRecordsContract {
 
         let key = (symbol_short!("REC_ACC"), record_id, grantee.clone());
         env.storage().persistent().set(&key, &grant);
-        extend_ttl_record_access_key(&env, &key);
+        extend_ttl_record_access_key(env, &key);
 
         events::publish_record_access_granted(
-            &env,
-            patient,
-            grantee,
+            env,
+            patient.clone(),
+            grantee.clone(),
             record_id,
-            level,
+            level.clone(),
             duration_seconds,
             expires_at,
         );
-        Ok(())
+    }
+
+    /// Grants `level` on `record_id` unless `grantee` already holds at least
+    /// that level on it, so a stronger or longer-standing grant is never
+    /// replaced. Returns whether a grant was written.
+    fn grant_record_unless_held(
+        env: &Env,
+        patient: &Address,
+        grantee: &Address,
+        record_id: u64,
+        level: &AccessLevel,
+        duration_seconds: u64,
+    ) -> bool {
+        let held = Self::check_record_access(env.clone(), record_id, grantee.clone());
+        if held.covers(level) {
+            return false;
+        }
+        Self::store_record_grant(env, patient, grantee, record_id, level, duration_seconds);
+        true
+    }
+
+    /// Removes `grantee`'s grant on `record_id` if it is still the one
+    /// issued at `granted_at` with `level` for `duration_seconds`. A grant
+    /// that has been replaced since is left in place.
+    fn remove_issued_grant(
+        env: &Env,
+        grantee: &Address,
+        record_id: u64,
+        level: &AccessLevel,
+        granted_at: u64,
+        duration_seconds: u64,
+    ) {
+        let key = (symbol_short!("REC_ACC"), record_id, grantee.clone());
+        let Some(grant) = env.storage().persistent().get::<_, AccessGrant>(&key) else {
+            return;
+        };
+        if grant.level != *level
+            || grant.granted_at != granted_at
+            || grant.expires_at != granted_at.saturating_add(duration_seconds)
+        {
+            return;
+        }
+        env.storage().persistent().remove(&key);
+        Self::rekey_record_envelope(env, record_id);
     }
 
     /// Check record-level access for a specific grantee.
@@ -1845,7 +3349,9 @@ impl VisionRecordsContract {
         AccessLevel::None
     }
 
-    /// Revoke record-level access for a specific record.
+    /// Revoke record-level access for a specific record. The record's key
+    /// envelope is re-keyed and every wrapped key dropped; the patient or
+    /// provider wraps the new data key again for those who keep access.
     pub fn revoke_record_access(
         env: Env,
         patient: Address,
@@ -1864,40 +3370,274 @@ impl VisionRecordsContract {
             return Self::unauthorized(&env, &patient, "revoke_record_access", "record_owner");
         }
 
-        let key = (symbol_short!("REC_ACC"), record_id, grantee);
-        env.storage().persistent().remove(&key);
+        let key = (symbol_short!("REC_ACC"), record_id, grantee.clone());
+        if env.storage().persistent().has(&key) {
+            env.storage().persistent().remove(&key);
+            Self::rekey_record_envelope(&env, record_id);
+        }
+        Ok(())
+    }
+
+    // ── Share tokens ─────────────────────────────────────────────
+
+    /// Issue a one-time share token for some of the patient's records.
+    /// `share_key` is an ed25519 public key whose signing key is handed out
+    /// off-chain; the token can be redeemed within `valid_for_seconds`, and
+    /// the grants it turns into last `access_duration_seconds` from
+    /// redemption.
+    pub fn create_share_token(
+        env: Env,
+        patient: Address,
+        share_key: BytesN<32>,
+        record_ids: Vec<u64>,
+        level: AccessLevel,
+        valid_for_seconds: u64,
+        access_duration_seconds: u64,
+    ) -> Result<u64, ContractError> {
+        circuit_breaker::require_not_paused(&env, &circuit_breaker::PauseScope::Global)?;
+        patient.require_auth();
+        validation::validate_duration(valid_for_seconds)?;
+        validation::validate_duration(access_duration_seconds)?;
+
+        if level == AccessLevel::None
+            || record_ids.is_empty()
+            || record_ids.len() > share::MAX_SHARED_RECORDS
+        {
+            return Err(ContractError::InvalidInput);
+        }
+        if share::key_in_use(&env, &share_key) {
+            return Err(ContractError::DuplicateRecord);
+        }
+        for (index, record_id) in record_ids.iter().enumerate() {
+            if record_ids.first_index_of(record_id) != Some(index as u32) {
+                return Err(ContractError::InvalidInput);
+            }
+            let record: VisionRecord = env
+                .storage()
+                .persistent()
+                .get(&(symbol_short!("RECORD"), record_id))
+                .ok_or(ContractError::RecordNotFound)?;
+            if record.patient != patient {
+                return Self::unauthorized(&env, &patient, "create_share_token", "record_owner");
+            }
+        }
+
+        let now = env.ledger().timestamp();
+        let token = ShareToken {
+            id: share::next_id(&env),
+            patient: patient.clone(),
+            share_key,
+            record_ids: record_ids.clone(),
+            level: level.clone(),
+            access_duration: access_duration_seconds,
+            created_at: now,
+            expires_at: now.saturating_add(valid_for_seconds),
+            status: ShareTokenStatus::Active,
+            redeemed_by: None,
+            redeemed_at: 0,
+            granted_record_ids: Vec::new(&env),
+        };
+        share::insert_token(&env, &token);
+        events::publish_share_token_created(
+            &env,
+            token.id,
+            patient,
+            record_ids,
+            level,
+            token.expires_at,
+        );
+
+        Ok(token.id)
+    }
+
+    /// Redeem a share token with a signature, made with the token's
+    /// signing key, over [`share::redemption_message`] for `redeemer`. The
+    /// caller receives a record-level grant for every record on the token
+    /// they cannot already access at the token's level; the token cannot be
+    /// used again.
+    pub fn redeem_share_token(
+        env: Env,
+        redeemer: Address,
+        share_key: BytesN<32>,
+        signature: BytesN<64>,
+    ) -> Result<ShareToken, ContractError> {
+        circuit_breaker::require_not_paused(&env, &circuit_breaker::PauseScope::Global)?;
+        redeemer.require_auth();
+
+        let mut token =
+            share::find_by_key(&env, &share_key).ok_or(ContractError::ShareTokenNotFound)?;
+        if token.status != ShareTokenStatus::Active {
+            return Err(ContractError::ShareTokenInactive);
+        }
+        let now = env.ledger().timestamp();
+        if now >= token.expires_at {
+            return Err(ContractError::ShareTokenExpired);
+        }
+        if redeemer == token.patient {
+            return Err(ContractError::InvalidInput);
+        }
+        let message = share::redemption_message(&env, &share_key, &redeemer);
+        env.crypto()
+            .ed25519_verify(&share_key, &message, &signature);
+
+        let mut granted = Vec::new(&env);
+        for record_id in token.record_ids.iter() {
+            if !Self::grant_record_unless_held(
+                &env,
+                &token.patient,
+                &redeemer,
+                record_id,
+                &token.level,
+                token.access_duration,
+            ) {
+                continue;
+            }
+            granted.push_back(record_id);
+            let audit_entry = audit::create_audit_entry(
+                &env,
+                redeemer.clone(),
+                token.patient.clone(),
+                Some(record_id),
+                AccessAction::GrantAccess,
+                AccessResult::Success,
+                Some(String::from_str(
+                    &env,
+                    &alloc::format!("Share token redeemed: {}", token.id),
+                )),
+            );
+            Self::record_audit(&env, &audit_entry);
+            events::publish_audit_log_entry(&env, &audit_entry);
+        }
+
+        token.status = ShareTokenStatus::Redeemed;
+        token.redeemed_by = Some(redeemer.clone());
+        token.redeemed_at = now;
+        token.granted_record_ids = granted;
+        share::set_token(&env, &token);
+        events::publish_share_token_redeemed(&env, token.id, token.patient.clone(), redeemer);
+
+        Ok(token)
+    }
+
+    /// Revoke a share token. Revoking a redeemed token also removes the
+    /// record grants it produced, unless they have since been replaced by
+    /// another grant.
+    pub fn revoke_share_token(
+        env: Env,
+        patient: Address,
+        token_id: u64,
+    ) -> Result<(), ContractError> {
+        circuit_breaker::require_not_paused(&env, &circuit_breaker::PauseScope::Global)?;
+        patient.require_auth();
+
+        let mut token =
+            share::get_token(&env, token_id).ok_or(ContractError::ShareTokenNotFound)?;
+        if token.patient != patient {
+            return Self::unauthorized(&env, &patient, "revoke_share_token", "token_owner");
+        }
+        if token.status == ShareTokenStatus::Revoked {
+            return Err(ContractError::ShareTokenInactive);
+        }
+        if let Some(redeemer) = &token.redeemed_by {
+            for record_id in token.granted_record_ids.iter() {
+                Self::remove_issued_grant(
+                    &env,
+                    redeemer,
+                    record_id,
+                    &token.level,
+                    token.redeemed_at,
+                    token.access_duration,
+                );
+            }
+        }
+
+        token.status = ShareTokenStatus::Revoked;
+        share::set_token(&env, &token);
+        events::publish_share_token_revoked(&env, token_id, patient);
         Ok(())
     }
 
-    /// Grant consent for a grantee.
+    pub fn get_share_token(env: Env, token_id: u64) -> Option<ShareToken> {
+        share::get_token(&env, token_id)
+    }
+
+    /// Page through the tokens the patient has issued, oldest first.
+    pub fn get_patient_share_tokens_page(
+        env: Env,
+        patient: Address,
+        cursor: Option<u64>,
+        limit: u32,
+    ) -> Result<ShareTokenPage, ContractError> {
+        pagination::validate_limit(limit)?;
+
+        let (tokens, next_cursor) = share::page_patient_tokens(&env, &patient, cursor, limit);
+        Ok(ShareTokenPage {
+            tokens,
+            next_cursor,
+        })
+    }
+
+    /// Grant consent for a grantee, for one purpose and every record type.
     pub fn grant_consent(
         env: Env,
         patient: Address,
         grantee: Address,
         consent_type: ConsentType,
         duration_seconds: u64,
+    ) -> Result<(), ContractError> {
+        Self::grant_scoped_consent(
+            env.clone(),
+            patient,
+            grantee,
+            Vec::from_array(&env, [consent_type]),
+            Vec::new(&env),
+            duration_seconds,
+        )
+    }
+
+    /// Grant consent limited to `purposes` and, unless empty, to
+    /// `record_types`. Replaces any consent previously given to `grantee`.
+    pub fn grant_scoped_consent(
+        env: Env,
+        patient: Address,
+        grantee: Address,
+        purposes: Vec<ConsentType>,
+        record_types: Vec<RecordType>,
+        duration_seconds: u64,
     ) -> Result<(), ContractError> {
         circuit_breaker::require_not_paused(&env, &circuit_breaker::PauseScope::Global)?;
         patient.require_auth();
-        if duration_seconds == 0 {
+        if duration_seconds == 0 || purposes.is_empty() {
             return Err(ContractError::InvalidInput);
         }
         let now = env.ledger().timestamp();
         let consent = ConsentGrant {
             patient: patient.clone(),
             grantee: grantee.clone(),
-            consent_type: consent_type.clone(),
+            purposes,
+            record_types,
             granted_at: now,
             expires_at: now.saturating_add(duration_seconds),
             revoked: false,
         };
-        let key = consent_key(&patient, &grantee);
-        env.storage().persistent().set(&key, &consent);
-        extend_ttl_access_key(&env, &key);
-        events::publish_consent_granted(&env, patient, grantee, consent_type, consent.expires_at);
+        store_consent(&env, &consent);
+        events::publish_consent_granted(
+            &env,
+            patient,
+            grantee,
+            consent.purposes.clone(),
+            consent.record_types.clone(),
+            consent.expires_at,
+        );
         Ok(())
     }
 
+    /// The consent `patient` has given `grantee`, including revoked or
+    /// expired consent.
+    pub fn get_consent(env: Env, patient: Address, grantee: Address) -> Option<ConsentGrant> {
+        load_consent(&env, &patient, &grantee)
+    }
+
     /// Revoke previously granted consent.
     pub fn revoke_consent(
         env: Env,
@@ -1906,10 +3646,9 @@ impl VisionRecordsContract {
     ) -> Result<(), ContractError> {
         circuit_breaker::require_not_paused(&env, &circuit_breaker::PauseScope::Global)?;
         patient.require_auth();
-        let key = consent_key(&patient, &grantee);
-        if let Some(mut consent) = env.storage().persistent().get::<_, ConsentGrant>(&key) {
+        if let Some(mut consent) = load_consent(&env, &patient, &grantee) {
             consent.revoked = true;
-            env.storage().persistent().set(&key, &consent);
+            store_consent(&env, &consent);
         }
         events::publish_consent_revoked(&env, patient, grantee);
         Ok(())
@@ -1921,11 +3660,40 @@ impl VisionRecordsContract {
         patient: Address,
         grantee: Address,
     ) -> Result<(), ContractError> {
-        circuit_breaker::require_not_paused(
-            &env,
-            &circuit_breaker::PauseScope::Function(symbol_short!("RVK_ACC")),
-        )?;
-        patient.require_auth();
+        Self::revoke_access_on_behalf(env, patient.clone(), patient, grantee)
+    }
+
+    /// Revoke access on the patient's behalf. The patient, a guardian or
+    /// delegate holding `ManageAccess`, or a SystemAdmin may revoke.
+    pub fn revoke_access_on_behalf(
+        env: Env,
+        caller: Address,
+        patient: Address,
+        grantee: Address,
+    ) -> Result<(), ContractError> {
+        circuit_breaker::require_not_paused(
+            &env,
+            &circuit_breaker::PauseScope::Function(symbol_short!("RVK_ACC")),
+        )?;
+        caller.require_auth();
+
+        let has_perm = caller == patient
+            || rbac::has_delegated_permission(&env, &patient, &caller, &Permission::ManageAccess)
+            || guardianship::has_guardian_permission(
+                &env,
+                &patient,
+                &caller,
+                &GuardianPermission::ManageAccess,
+            )
+            || rbac::has_permission(&env, &caller, &Permission::SystemAdmin);
+        if !has_perm {
+            return Self::unauthorized(
+                &env,
+                &caller,
+                "revoke_access_on_behalf",
+                "patient_or_permission:ManageAccess_or_SystemAdmin",
+            );
+        }
 
         let key = (symbol_short!("ACCESS"), patient.clone(), grantee.clone());
         env.storage().persistent().remove(&key);
@@ -1944,18 +3712,64 @@ impl VisionRecordsContract {
         // Log successful access revoke
         let audit_entry = audit::create_audit_entry(
             &env,
-            patient.clone(),
+            caller,
             patient.clone(),
             None,
             AccessAction::RevokeAccess,
             AccessResult::Success,
             None,
         );
-        audit::add_audit_entry(&env, &audit_entry);
+        Self::record_audit(&env, &audit_entry);
         events::publish_audit_log_entry(&env, &audit_entry);
 
+        Ok(())
+    }
+
+    /// Create a patient profile.
+    /// The patient or a user with ManageUsers permission may create it.
+    pub fn create_profile(
+        env: Env,
+        caller: Address,
+        patient: Address,
+        date_of_birth_hash: String,
+        gender_hash: String,
+        blood_type_hash: String,
+    ) -> Result<(), ContractError> {
+        circuit_breaker::require_not_paused(&env, &circuit_breaker::PauseScope::Global)?;
+        caller.require_auth();
+
+        if caller != patient && !rbac::has_permission(&env, &caller, &Permission::ManageUsers) {
+            return Self::unauthorized(
+                &env,
+                &caller,
+                "create_profile",
+                "profile_owner_or_ManageUsers",
+            );
+        }
+
+        let profile_key = (symbol_short!("PAT_PROF"), patient.clone());
+        if env.storage().persistent().has(&profile_key) {
+            return Err(ContractError::DuplicateRecord);
+        }
+
+        let now = env.ledger().timestamp();
+        let profile = PatientProfile {
+            patient: patient.clone(),
+            created_at: now,
+            updated_at: now,
+            is_active: true,
+            date_of_birth_hash,
+            gender_hash,
+            blood_type_hash,
+            emergency_contact: OptionalEmergencyContact::None,
+            insurance_info: OptionalInsuranceInfo::None,
+            medical_history_refs: Vec::new(&env),
+            majority_at: 0,
+            guardians: Vec::new(&env),
+        };
         env.storage().persistent().set(&profile_key, &profile);
-        events::publish_profile_updated(&env, patient);
+        extend_ttl_address_key(&env, &profile_key);
+        events::publish_profile_created(&env, patient);
 
         Ok(())
     }
@@ -2079,6 +3893,204 @@ impl VisionRecordsContract {
         env.storage().persistent().has(&profile_key)
     }
 
+    /// Add a guardian or proxy for `patient`, linked to their profile.
+    ///
+    /// For a minor, `majority_at` is the date the patient comes of age; it is
+    /// recorded on the profile and the guardianship ends then. It is rejected
+    /// once the profile's recorded majority date has passed. Minors'
+    /// guardians must be added by a user with ManageUsers. An adult patient's
+    /// proxies need the patient's own authorization, whoever adds them.
+    pub fn add_guardian(
+        env: Env,
+        caller: Address,
+        patient: Address,
+        guardian: Address,
+        relationship: GuardianRelationship,
+        permissions: Vec<GuardianPermission>,
+        majority_at: u64,
+    ) -> Result<(), ContractError> {
+        circuit_breaker::require_not_paused(&env, &circuit_breaker::PauseScope::Global)?;
+        caller.require_auth();
+
+        if guardian == patient || permissions.is_empty() {
+            return Err(ContractError::InvalidInput);
+        }
+
+        let profile_key = (symbol_short!("PAT_PROF"), patient.clone());
+        let mut profile: PatientProfile = env
+            .storage()
+            .persistent()
+            .get(&profile_key)
+            .ok_or(ContractError::UserNotFound)?;
+
+        let now = env.ledger().timestamp();
+        if majority_at != 0 {
+            // A majority date can be recorded or moved while the patient is a
+            // minor, but never used to make an adult a minor again.
+            let reached_majority = profile.majority_at != 0 && profile.majority_at <= now;
+            if majority_at <= now || reached_majority {
+                return Err(ContractError::InvalidTimestamp);
+            }
+            profile.majority_at = majority_at;
+        }
+        let is_minor = profile.majority_at > now;
+
+        if !is_minor && caller != patient {
+            patient.require_auth();
+        }
+        let may_appoint = rbac::has_permission(&env, &caller, &Permission::ManageUsers)
+            || (caller == patient && !is_minor);
+        if !may_appoint {
+            return Self::unauthorized(
+                &env,
+                &caller,
+                "add_guardian",
+                "adult_patient_or_ManageUsers",
+            );
+        }
+
+        let expires_at = if is_minor { profile.majority_at } else { 0 };
+        let guardianship = Guardianship {
+            patient: patient.clone(),
+            guardian: guardian.clone(),
+            relationship: relationship.clone(),
+            permissions: permissions.clone(),
+            established_by: caller.clone(),
+            established_at: now,
+            expires_at,
+        };
+        guardianship::set_guardianship(&env, &guardianship);
+
+        if !profile.guardians.contains(&guardian) {
+            profile.guardians.push_back(guardian.clone());
+        }
+        profile.updated_at = now;
+        env.storage().persistent().set(&profile_key, &profile);
+        extend_ttl_address_key(&env, &profile_key);
+
+        events::publish_guardian_added(
+            &env,
+            patient,
+            guardian,
+            relationship,
+            permissions,
+            caller,
+            expires_at,
+        );
+        Ok(())
+    }
+
+    /// End a guardianship. The guardian may step down; an adult patient or a
+    /// user with ManageUsers may remove them.
+    pub fn remove_guardian(
+        env: Env,
+        caller: Address,
+        patient: Address,
+        guardian: Address,
+    ) -> Result<(), ContractError> {
+        circuit_breaker::require_not_paused(&env, &circuit_breaker::PauseScope::Global)?;
+        caller.require_auth();
+
+        let profile_key = (symbol_short!("PAT_PROF"), patient.clone());
+        let mut profile: PatientProfile = env
+            .storage()
+            .persistent()
+            .get(&profile_key)
+            .ok_or(ContractError::UserNotFound)?;
+        if guardianship::get_guardianship(&env, &patient, &guardian).is_none() {
+            return Err(ContractError::GuardianshipNotFound);
+        }
+
+        let now = env.ledger().timestamp();
+        let may_remove = caller == guardian
+            || (caller == patient && profile.majority_at <= now)
+            || rbac::has_permission(&env, &caller, &Permission::ManageUsers);
+        if !may_remove {
+            return Self::unauthorized(
+                &env,
+                &caller,
+                "remove_guardian",
+                "guardian_or_adult_patient_or_ManageUsers",
+            );
+        }
+
+        guardianship::remove_guardianship(&env, &patient, &guardian);
+        if let Some(index) = profile.guardians.first_index_of(&guardian) {
+            profile.guardians.remove(index);
+        }
+        profile.updated_at = now;
+        env.storage().persistent().set(&profile_key, &profile);
+
+        events::publish_guardian_removed(&env, patient, guardian, caller);
+        Ok(())
+    }
+
+    /// Hand-over at majority: once the majority date has passed, the patient
+    /// clears every guardianship of their minority and takes sole control.
+    /// Proxies the patient appointed as an adult are kept.
+    pub fn claim_majority(env: Env, patient: Address) -> Result<u32, ContractError> {
+        circuit_breaker::require_not_paused(&env, &circuit_breaker::PauseScope::Global)?;
+        patient.require_auth();
+
+        let profile_key = (symbol_short!("PAT_PROF"), patient.clone());
+        let mut profile: PatientProfile = env
+            .storage()
+            .persistent()
+            .get(&profile_key)
+            .ok_or(ContractError::UserNotFound)?;
+        let now = env.ledger().timestamp();
+        if profile.majority_at == 0 || profile.majority_at > now {
+            return Err(ContractError::InvalidTimestamp);
+        }
+
+        let mut kept = Vec::new(&env);
+        let mut released = 0u32;
+        for guardian in profile.guardians.iter() {
+            match guardianship::get_guardianship(&env, &patient, &guardian) {
+                Some(g) if g.expires_at == 0 => kept.push_back(guardian),
+                _ => {
+                    guardianship::remove_guardianship(&env, &patient, &guardian);
+                    events::publish_guardian_removed(
+                        &env,
+                        patient.clone(),
+                        guardian,
+                        patient.clone(),
+                    );
+                    released = released.saturating_add(1);
+                }
+            }
+        }
+        profile.guardians = kept;
+        profile.updated_at = now;
+        env.storage().persistent().set(&profile_key, &profile);
+
+        events::publish_majority_claimed(&env, patient, released);
+        Ok(released)
+    }
+
+    /// All guardianships recorded for `patient`, including any that expired
+    /// and have not yet been cleared by `claim_majority`.
+    pub fn get_guardians(env: Env, patient: Address) -> Vec<Guardianship> {
+        let profile_key = (symbol_short!("PAT_PROF"), patient.clone());
+        let mut out = Vec::new(&env);
+        if let Some(profile) = env
+            .storage()
+            .persistent()
+            .get::<_, PatientProfile>(&profile_key)
+        {
+            for guardian in profile.guardians.iter() {
+                if let Some(g) = guardianship::get_guardianship(&env, &patient, &guardian) {
+                    out.push_back(g);
+                }
+            }
+        }
+        out
+    }
+
+    pub fn get_guardianship(env: Env, patient: Address, guardian: Address) -> Option<Guardianship> {
+        guardianship::get_guardianship(&env, &patient, &guardian)
+    }
+
     /// Grants a custom permission to a user.
     /// Requires the caller to have ManageUsers permission.
     pub fn grant_custom_permission(
@@ -2098,194 +4110,2453 @@ impl VisionRecordsContract {
                 "permission:ManageUsers",
             );
         }
-        rbac::grant_custom_permission(&env, user, permission)
-            .map_err(|_| ContractError::UserNotFound)?;
+        rbac::grant_custom_permission(&env, user, permission)
+            .map_err(|_| ContractError::UserNotFound)?;
+        Ok(())
+    }
+
+    /// Revokes a custom permission from a user.
+    /// Requires the caller to have ManageUsers permission.
+    pub fn revoke_custom_permission(
+        env: Env,
+        caller: Address,
+        user: Address,
+        permission: Permission,
+    ) -> Result<(), ContractError> {
+        circuit_breaker::require_not_paused(&env, &circuit_breaker::PauseScope::Global)?;
+        caller.require_auth();
+        // Unified check: covers direct role, custom grants, and delegated roles
+        if !rbac::has_permission(&env, &caller, &Permission::ManageUsers) {
+            return Self::unauthorized(
+                &env,
+                &caller,
+                "revoke_custom_permission",
+                "permission:ManageUsers",
+            );
+        }
+        rbac::revoke_custom_permission(&env, user, permission)
+            .map_err(|_| ContractError::UserNotFound)?;
+        Ok(())
+    }
+
+    /// Delegates a role to another user with an expiration timestamp.
+    /// The delegator must authenticate the transaction.
+    pub fn delegate_role(
+        env: Env,
+        delegator: Address,
+        delegatee: Address,
+        role: Role,
+        expires_at: u64,
+    ) -> Result<(), ContractError> {
+        circuit_breaker::require_not_paused(&env, &circuit_breaker::PauseScope::Global)?;
+        delegator.require_auth();
+        rbac::delegate_role(&env, delegator, delegatee, role, expires_at);
+        Ok(())
+    }
+
+    /// Pauses contract operations for a given scope.
+    pub fn pause_contract(
+        env: Env,
+        caller: Address,
+        scope: circuit_breaker::PauseScope,
+    ) -> Result<(), ContractError> {
+        caller.require_auth();
+        circuit_breaker::pause_contract(&env, &caller, scope)
+    }
+
+    /// Resumes contract operations for a given scope.
+    pub fn resume_contract(
+        env: Env,
+        caller: Address,
+        scope: circuit_breaker::PauseScope,
+    ) -> Result<(), ContractError> {
+        caller.require_auth();
+        circuit_breaker::resume_contract(&env, &caller, scope)
+    }
+
+    /// Installs or replaces an automatic trip rule. Same authorization as
+    /// `pause_contract`.
+    pub fn set_trip_rule(
+        env: Env,
+        caller: Address,
+        rule: circuit_breaker::TripRule,
+    ) -> Result<(), ContractError> {
+        caller.require_auth();
+        circuit_breaker::set_trip_rule(&env, &caller, rule)
+    }
+
+    /// Removes the trip rule for `trigger`.
+    pub fn remove_trip_rule(
+        env: Env,
+        caller: Address,
+        trigger: circuit_breaker::TripTrigger,
+    ) -> Result<(), ContractError> {
+        caller.require_auth();
+        circuit_breaker::remove_trip_rule(&env, &caller, &trigger)
+    }
+
+    pub fn get_trip_rule(
+        env: Env,
+        trigger: circuit_breaker::TripTrigger,
+    ) -> Option<circuit_breaker::TripRule> {
+        circuit_breaker::get_trip_rule(&env, &trigger)
+    }
+
+    /// The automatic pause in effect for `scope`, if any.
+    pub fn get_auto_pause(
+        env: Env,
+        scope: circuit_breaker::PauseScope,
+    ) -> Option<circuit_breaker::AutoPause> {
+        circuit_breaker::get_auto_pause(&env, &scope)
+    }
+
+    /// Confirms an automatic pause so it no longer lifts after its cooldown.
+    pub fn confirm_auto_pause(
+        env: Env,
+        caller: Address,
+        scope: circuit_breaker::PauseScope,
+    ) -> Result<(), ContractError> {
+        caller.require_auth();
+        circuit_breaker::confirm_auto_pause(&env, &caller, &scope)
+    }
+
+    /// Creates an ACL group.
+    pub fn create_acl_group(
+        env: Env,
+        caller: Address,
+        group_name: String,
+        permissions: Vec<Permission>,
+    ) -> Result<(), ContractError> {
+        circuit_breaker::require_not_paused(&env, &circuit_breaker::PauseScope::Global)?;
+        caller.require_auth();
+        if !rbac::has_permission(&env, &caller, &Permission::ManageUsers) {
+            return Self::unauthorized(&env, &caller, "create_acl_group", "permission:ManageUsers");
+        }
+        rbac::create_group(&env, group_name, permissions);
+        Ok(())
+    }
+
+    /// Adds a user to an ACL group.
+    pub fn add_user_to_group(
+        env: Env,
+        caller: Address,
+        user: Address,
+        group_name: String,
+    ) -> Result<(), ContractError> {
+        circuit_breaker::require_not_paused(&env, &circuit_breaker::PauseScope::Global)?;
+        caller.require_auth();
+        if !rbac::has_permission(&env, &caller, &Permission::ManageUsers) {
+            return Self::unauthorized(
+                &env,
+                &caller,
+                "add_user_to_group",
+                "permission:ManageUsers",
+            );
+        }
+        // Fails for unknown groups and users already in MAX_USER_GROUPS groups.
+        rbac::add_to_group(&env, user, group_name).map_err(|_| ContractError::InvalidInput)
+    }
+
+    /// Removes a user from an ACL group.
+    pub fn remove_user_from_group(
+        env: Env,
+        caller: Address,
+        user: Address,
+        group_name: String,
+    ) -> Result<(), ContractError> {
+        circuit_breaker::require_not_paused(&env, &circuit_breaker::PauseScope::Global)?;
+        caller.require_auth();
+        if !rbac::has_permission(&env, &caller, &Permission::ManageUsers) {
+            return Self::unauthorized(
+                &env,
+                &caller,
+                "remove_user_from_group",
+                "permission:ManageUsers",
+            );
+        }
+        rbac::remove_from_group(&env, user, group_name);
+        Ok(())
+    }
+
+    /// Returns all ACL groups assigned to a user, at most `MAX_USER_GROUPS`.
+    pub fn get_user_groups(env: Env, user: Address) -> Vec<String> {
+        env.storage()
+            .persistent()
+            .get(&rbac::user_groups_key(&user))
+            .unwrap_or(Vec::new(&env))
+    }
+
+    /// Checks if a user has a specific permission.
+    /// Returns true if the user has the permission, false otherwise.
+    pub fn check_permission(env: Env, user: Address, permission: Permission) -> bool {
+        rbac::has_permission(&env, &user, &permission)
+    }
+
+    // ======================== Appointment Scheduling ========================
+
+    /// Returns true if `caller` may act on a booking between `patient` and
+    /// `provider`: the patient, the provider, a delegate holding the
+    /// provider's WriteRecord permission, or a SystemAdmin.
+    fn can_manage_appointment(
+        env: &Env,
+        caller: &Address,
+        patient: &Address,
+        provider: &Address,
+    ) -> bool {
+        *caller == *patient
+            || *caller == *provider
+            || rbac::has_delegated_permission(env, provider, caller, &Permission::WriteRecord)
+            || rbac::has_permission(env, caller, &Permission::SystemAdmin)
+    }
+
+    fn load_appointment(env: &Env, appointment_id: u64) -> Result<Appointment, ContractError> {
+        appointment::get_appointment(env, appointment_id).ok_or(ContractError::AppointmentNotFound)
+    }
+
+    fn push_appointment_history(
+        env: &Env,
+        appointment_id: u64,
+        action: &str,
+        actor: &Address,
+        previous_status: AppointmentStatus,
+        new_status: AppointmentStatus,
+        notes: Option<String>,
+    ) {
+        appointment::add_history_entry(
+            env,
+            &AppointmentHistoryEntry {
+                appointment_id,
+                action: String::from_str(env, action),
+                actor: actor.clone(),
+                timestamp: env.ledger().timestamp(),
+                previous_status,
+                new_status,
+                notes,
+            },
+        );
+    }
+
+    /// Schedule an appointment between a patient and a provider.
+    ///
+    /// The caller must be the patient, the provider, a provider delegate or a
+    /// SystemAdmin. The slot must be in the future, last 1–480 minutes and
+    /// must not overlap another active appointment of the same provider.
+    pub fn schedule_appointment(
+        env: Env,
+        caller: Address,
+        patient: Address,
+        provider: Address,
+        appointment_type: AppointmentType,
+        scheduled_at: u64,
+        duration_minutes: u32,
+        notes: Option<String>,
+    ) -> Result<u64, ContractError> {
+        circuit_breaker::require_not_paused(&env, &circuit_breaker::PauseScope::Global)?;
+        caller.require_auth();
+
+        if !Self::can_manage_appointment(&env, &caller, &patient, &provider) {
+            return Self::unauthorized(
+                &env,
+                &caller,
+                "schedule_appointment",
+                "patient_or_provider_or_SystemAdmin",
+            );
+        }
+        Self::require_verified_provider(&env, &provider)?;
+
+        if scheduled_at <= env.ledger().timestamp() {
+            return Err(ContractError::InvalidAppointmentTime);
+        }
+        if duration_minutes == 0 || duration_minutes > appointment::MAX_APPOINTMENT_MINUTES {
+            return Err(ContractError::InvalidInput);
+        }
+        if appointment::find_provider_conflict(
+            &env,
+            &provider,
+            scheduled_at,
+            duration_minutes,
+            None,
+        )
+        .is_some()
+        {
+            return Err(ContractError::AppointmentConflict);
+        }
+
+        let now = env.ledger().timestamp();
+        let appointment_id = appointment::increment_appointment_counter(&env);
+        let appt = Appointment {
+            id: appointment_id,
+            patient: patient.clone(),
+            provider: provider.clone(),
+            appointment_type: appointment_type.clone(),
+            scheduled_at,
+            duration_minutes,
+            status: AppointmentStatus::Scheduled,
+            notes: notes.clone(),
+            created_at: now,
+            updated_at: now,
+            verified_at: None,
+            verified_by: None,
+            reminder_sent: false,
+        };
+        appointment::set_appointment(&env, &appt);
+
+        Self::push_appointment_history(
+            &env,
+            appointment_id,
+            "CREATED",
+            &caller,
+            AppointmentStatus::None,
+            AppointmentStatus::Scheduled,
+            notes,
+        );
+
+        events::publish_appointment_scheduled(
+            &env,
+            appointment_id,
+            patient,
+            provider,
+            appointment_type,
+            scheduled_at,
+        );
+
+        Ok(appointment_id)
+    }
+
+    /// Confirm a scheduled or rescheduled appointment.
+    pub fn confirm_appointment(
+        env: Env,
+        caller: Address,
+        appointment_id: u64,
+    ) -> Result<(), ContractError> {
+        circuit_breaker::require_not_paused(&env, &circuit_breaker::PauseScope::Global)?;
+        caller.require_auth();
+
+        let mut appt = Self::load_appointment(&env, appointment_id)?;
+        if !Self::can_manage_appointment(&env, &caller, &appt.patient, &appt.provider) {
+            return Self::unauthorized(
+                &env,
+                &caller,
+                "confirm_appointment",
+                "patient_or_provider_or_SystemAdmin",
+            );
+        }
+
+        if appt.status != AppointmentStatus::Scheduled
+            && appt.status != AppointmentStatus::Rescheduled
+        {
+            return Err(ContractError::InvalidAppointmentStatus);
+        }
+
+        let previous = appt.status.clone();
+        appt.status = AppointmentStatus::Confirmed;
+        appt.updated_at = env.ledger().timestamp();
+        appointment::set_appointment(&env, &appt);
+
+        Self::push_appointment_history(
+            &env,
+            appointment_id,
+            "CONFIRMED",
+            &caller,
+            previous,
+            AppointmentStatus::Confirmed,
+            None,
+        );
+        events::publish_appointment_confirmed(
+            &env,
+            appointment_id,
+            appt.patient,
+            appt.provider,
+            caller,
+        );
+
+        Ok(())
+    }
+
+    /// Move an active appointment to a new future time slot.
+    /// Resets the reminder flag so a fresh reminder is sent for the new slot.
+    pub fn reschedule_appointment(
+        env: Env,
+        caller: Address,
+        appointment_id: u64,
+        new_scheduled_at: u64,
+    ) -> Result<(), ContractError> {
+        circuit_breaker::require_not_paused(&env, &circuit_breaker::PauseScope::Global)?;
+        caller.require_auth();
+
+        let mut appt = Self::load_appointment(&env, appointment_id)?;
+        if !Self::can_manage_appointment(&env, &caller, &appt.patient, &appt.provider) {
+            return Self::unauthorized(
+                &env,
+                &caller,
+                "reschedule_appointment",
+                "patient_or_provider_or_SystemAdmin",
+            );
+        }
+
+        if !appointment::is_active_status(&appt.status) {
+            return Err(ContractError::InvalidAppointmentStatus);
+        }
+        if new_scheduled_at <= env.ledger().timestamp() {
+            return Err(ContractError::InvalidAppointmentTime);
+        }
+        if appointment::find_provider_conflict(
+            &env,
+            &appt.provider,
+            new_scheduled_at,
+            appt.duration_minutes,
+            Some(appointment_id),
+        )
+        .is_some()
+        {
+            return Err(ContractError::AppointmentConflict);
+        }
+
+        let previous = appt.status.clone();
+        let old_scheduled_at = appt.scheduled_at;
+        appt.scheduled_at = new_scheduled_at;
+        appt.status = AppointmentStatus::Rescheduled;
+        appt.reminder_sent = false;
+        appt.updated_at = env.ledger().timestamp();
+        appointment::set_appointment(&env, &appt);
+
+        Self::push_appointment_history(
+            &env,
+            appointment_id,
+            "RESCHEDULED",
+            &caller,
+            previous,
+            AppointmentStatus::Rescheduled,
+            None,
+        );
+        events::publish_appointment_rescheduled(
+            &env,
+            appointment_id,
+            appt.patient,
+            appt.provider,
+            old_scheduled_at,
+            new_scheduled_at,
+            caller,
+        );
+
+        Ok(())
+    }
+
+    /// Cancel an active appointment, releasing the provider's time slot.
+    pub fn cancel_appointment(
+        env: Env,
+        caller: Address,
+        appointment_id: u64,
+    ) -> Result<(), ContractError> {
+        circuit_breaker::require_not_paused(&env, &circuit_breaker::PauseScope::Global)?;
+        caller.require_auth();
+
+        let mut appt = Self::load_appointment(&env, appointment_id)?;
+        if !Self::can_manage_appointment(&env, &caller, &appt.patient, &appt.provider) {
+            return Self::unauthorized(
+                &env,
+                &caller,
+                "cancel_appointment",
+                "patient_or_provider_or_SystemAdmin",
+            );
+        }
+
+        if !appointment::is_active_status(&appt.status) {
+            return Err(ContractError::InvalidAppointmentStatus);
+        }
+
+        let previous = appt.status.clone();
+        appt.status = AppointmentStatus::Cancelled;
+        appt.updated_at = env.ledger().timestamp();
+        appointment::set_appointment(&env, &appt);
+
+        Self::push_appointment_history(
+            &env,
+            appointment_id,
+            "CANCELLED",
+            &caller,
+            previous,
+            AppointmentStatus::Cancelled,
+            None,
+        );
+        events::publish_appointment_cancelled(
+            &env,
+            appointment_id,
+            appt.patient,
+            appt.provider,
+            caller,
+        );
+
+        Ok(())
+    }
+
+    /// Mark an appointment as completed.
+    /// Only the provider, a provider delegate or a SystemAdmin may complete a visit.
+    pub fn complete_appointment(
+        env: Env,
+        caller: Address,
+        appointment_id: u64,
+    ) -> Result<(), ContractError> {
+        circuit_breaker::require_not_paused(&env, &circuit_breaker::PauseScope::Global)?;
+        caller.require_auth();
+
+        let mut appt = Self::load_appointment(&env, appointment_id)?;
+        let is_provider_side = caller == appt.provider
+            || rbac::has_delegated_permission(
+                &env,
+                &appt.provider,
+                &caller,
+                &Permission::WriteRecord,
+            )
+            || rbac::has_permission(&env, &caller, &Permission::SystemAdmin);
+        if !is_provider_side {
+            return Self::unauthorized(
+                &env,
+                &caller,
+                "complete_appointment",
+                "provider_or_SystemAdmin",
+            );
+        }
+
+        if !appointment::is_active_status(&appt.status) {
+            return Err(ContractError::InvalidAppointmentStatus);
+        }
+
+        let previous = appt.status.clone();
+        appt.status = AppointmentStatus::Completed;
+        appt.updated_at = env.ledger().timestamp();
+        appointment::set_appointment(&env, &appt);
+
+        Self::push_appointment_history(
+            &env,
+            appointment_id,
+            "COMPLETED",
+            &caller,
+            previous,
+            AppointmentStatus::Completed,
+            None,
+        );
+        events::publish_appointment_completed(
+            &env,
+            appointment_id,
+            appt.patient,
+            appt.provider,
+            caller,
+        );
+
+        Ok(())
+    }
+
+    /// Attach verification metadata to an appointment.
+    /// Requires the ManageUsers permission.
+    pub fn verify_appointment(
+        env: Env,
+        caller: Address,
+        appointment_id: u64,
+    ) -> Result<(), ContractError> {
+        circuit_breaker::require_not_paused(&env, &circuit_breaker::PauseScope::Global)?;
+        caller.require_auth();
+
+        if !rbac::has_permission(&env, &caller, &Permission::ManageUsers) {
+            return Self::unauthorized(
+                &env,
+                &caller,
+                "verify_appointment",
+                "permission:ManageUsers",
+            );
+        }
+
+        let mut appt = Self::load_appointment(&env, appointment_id)?;
+        appt.verified_at = Some(env.ledger().timestamp());
+        appt.verified_by = Some(caller.clone());
+        appt.updated_at = env.ledger().timestamp();
+        appointment::set_appointment(&env, &appt);
+
+        Self::push_appointment_history(
+            &env,
+            appointment_id,
+            "VERIFIED",
+            &caller,
+            appt.status.clone(),
+            appt.status.clone(),
+            None,
+        );
+        events::publish_appointment_verified(
+            &env,
+            appointment_id,
+            appt.patient,
+            appt.provider,
+            caller,
+        );
+
+        Ok(())
+    }
+
+    /// Flag reminders for active appointments starting within
+    /// `reminder_window_seconds`. Returns the number of reminders sent.
+    pub fn send_appointment_reminders(env: Env, reminder_window_seconds: u64) -> u32 {
+        let due = appointment::get_appointments_needing_reminders(&env, reminder_window_seconds);
+        let contract = env.current_contract_address();
+        let mut sent = 0u32;
+        for appt in due.iter() {
+            if appointment::mark_reminder_sent(&env, appt.id).is_none() {
+                continue;
+            }
+            Self::push_appointment_history(
+                &env,
+                appt.id,
+                "REMINDER_SENT",
+                &contract,
+                appt.status.clone(),
+                appt.status.clone(),
+                None,
+            );
+            events::publish_appointment_reminder(
+                &env,
+                appt.id,
+                appt.patient.clone(),
+                appt.provider.clone(),
+                appt.scheduled_at,
+            );
+            sent = sent.saturating_add(1);
+        }
+        sent
+    }
+
+    /// Get an appointment by ID.
+    pub fn get_appointment(env: Env, appointment_id: u64) -> Result<Appointment, ContractError> {
+        Self::load_appointment(&env, appointment_id)
+    }
+
+    /// Get the first `MAX_PAGE_LIMIT` appointments of a patient.
+    ///
+    /// Deprecated: use `get_patient_appointments_page`.
+    pub fn get_patient_appointments(env: Env, patient: Address) -> Vec<Appointment> {
+        appointment::page_patient_appointments(
+            &env,
+            &patient,
+            None,
+            pagination::MAX_PAGE_LIMIT,
+            false,
+            |_| true,
+        )
+        .0
+    }
+
+    /// Get upcoming (future, still active) appointments among a patient's
+    /// most recently booked `MAX_PAGE_SCAN`, most recent booking first.
+    ///
+    /// Deprecated: use `get_patient_appointments_page` with `upcoming_only`.
+    pub fn get_patient_upcoming(env: Env, patient: Address) -> Vec<Appointment> {
+        appointment::page_patient_appointments(
+            &env,
+            &patient,
+            None,
+            pagination::MAX_PAGE_LIMIT,
+            true,
+            |appt| appointment::is_upcoming(&env, appt),
+        )
+        .0
+    }
+
+    /// Get the first `MAX_PAGE_LIMIT` appointments of a provider.
+    ///
+    /// Deprecated: use `get_provider_appointments_page`.
+    pub fn get_provider_appointments(env: Env, provider: Address) -> Vec<Appointment> {
+        appointment::page_provider_appointments(
+            &env,
+            &provider,
+            None,
+            pagination::MAX_PAGE_LIMIT,
+            false,
+        )
+        .0
+    }
+
+    /// Page through a patient's appointments in booking order. With
+    /// `upcoming_only` only future appointments that are still active are
+    /// returned.
+    pub fn get_patient_appointments_page(
+        env: Env,
+        patient: Address,
+        upcoming_only: bool,
+        cursor: Option<u64>,
+        limit: u32,
+    ) -> Result<AppointmentPage, ContractError> {
+        pagination::validate_limit(limit)?;
+
+        let (appointments, next_cursor) =
+            appointment::page_patient_appointments(&env, &patient, cursor, limit, false, |appt| {
+                !upcoming_only || appointment::is_upcoming(&env, appt)
+            });
+        Ok(AppointmentPage {
+            appointments,
+            next_cursor,
+        })
+    }
+
+    /// Page through a provider's appointments in booking order, optionally
+    /// most recent first.
+    pub fn get_provider_appointments_page(
+        env: Env,
+        provider: Address,
+        newest_first: bool,
+        cursor: Option<u64>,
+        limit: u32,
+    ) -> Result<AppointmentPage, ContractError> {
+        pagination::validate_limit(limit)?;
+
+        let (appointments, next_cursor) =
+            appointment::page_provider_appointments(&env, &provider, cursor, limit, newest_first);
+        Ok(AppointmentPage {
+            appointments,
+            next_cursor,
+        })
+    }
+
+    /// Get the change history of an appointment.
+    pub fn get_appointment_history(env: Env, appointment_id: u64) -> Vec<AppointmentHistoryEntry> {
+        appointment::get_appointment_history(&env, appointment_id)
+    }
+
+    // ======================== Referrals ========================
+
+    /// Whether `caller` may make access decisions for `patient`: the patient,
+    /// a ManageAccess delegate, or a guardian holding ManageAccess.
+    fn acts_for_patient(env: &Env, caller: &Address, patient: &Address) -> bool {
+        caller == patient
+            || rbac::has_delegated_permission(env, patient, caller, &Permission::ManageAccess)
+            || guardianship::has_guardian_permission(
+                env,
+                patient,
+                caller,
+                &GuardianPermission::ManageAccess,
+            )
+    }
+
+    fn load_referral(env: &Env, referral_id: u64) -> Result<Referral, ContractError> {
+        referral::get_referral(env, referral_id).ok_or(ContractError::ReferralNotFound)
+    }
+
+    /// Refer a patient to a specialist for a second opinion on some of the
+    /// patient's records. The referring provider must be able to read the
+    /// records; nothing is shared until the patient approves. `reason_hash`
+    /// is the content hash or off-chain reference of the referral letter.
+    pub fn create_referral(
+        env: Env,
+        from_provider: Address,
+        to_provider: Address,
+        patient: Address,
+        record_ids: Vec<u64>,
+        reason_hash: String,
+    ) -> Result<u64, ContractError> {
+        circuit_breaker::require_not_paused(&env, &circuit_breaker::PauseScope::Global)?;
+        from_provider.require_auth();
+
+        if !rbac::has_permission(&env, &from_provider, &Permission::WriteRecord) {
+            return Self::unauthorized(
+                &env,
+                &from_provider,
+                "create_referral",
+                "permission:WriteRecord",
+            );
+        }
+        validation::validate_data_hash(&reason_hash)?;
+        if from_provider == to_provider
+            || record_ids.is_empty()
+            || record_ids.len() > referral::MAX_REFERRAL_RECORDS
+        {
+            return Err(ContractError::InvalidInput);
+        }
+        // The specialist must be able to write the response record.
+        if !rbac::has_permission(&env, &to_provider, &Permission::WriteRecord) {
+            return Err(ContractError::InvalidRole);
+        }
+        Self::require_verified_provider(&env, &to_provider)?;
+
+        for (index, record_id) in record_ids.iter().enumerate() {
+            if record_ids.first_index_of(record_id) != Some(index as u32) {
+                return Err(ContractError::InvalidInput);
+            }
+            let record: VisionRecord = env
+                .storage()
+                .persistent()
+                .get(&(symbol_short!("RECORD"), record_id))
+                .ok_or(ContractError::RecordNotFound)?;
+            if record.patient != patient
+                || Self::can_read_record(&env, &from_provider, &record, &ConsentType::Treatment)
+                    != ConsentScope::Covered
+            {
+                return Self::unauthorized(
+                    &env,
+                    &from_provider,
+                    "create_referral",
+                    "record_read_access",
+                );
+            }
+            Self::enforce_read_policies(&env, &from_provider, &record)?;
+        }
+
+        let now = env.ledger().timestamp();
+        let referral = Referral {
+            id: referral::next_id(&env),
+            from_provider: from_provider.clone(),
+            to_provider,
+            patient,
+            record_ids,
+            reason_hash,
+            status: ReferralStatus::Pending,
+            created_at: now,
+            updated_at: now,
+            access_duration: 0,
+            approved_at: 0,
+            granted_record_ids: Vec::new(&env),
+            response_record_id: None,
+        };
+        referral::insert_referral(&env, &referral);
+        events::publish_referral_updated(&env, &referral, from_provider);
+
+        Ok(referral.id)
+    }
+
+    /// Approve a pending referral, giving the specialist read access to the
+    /// referred records for `access_duration_seconds`.
+    pub fn approve_referral(
+        env: Env,
+        caller: Address,
+        referral_id: u64,
+        access_duration_seconds: u64,
+    ) -> Result<(), ContractError> {
+        circuit_breaker::require_not_paused(&env, &circuit_breaker::PauseScope::Global)?;
+        caller.require_auth();
+        validation::validate_duration(access_duration_seconds)?;
+
+        let mut referral = Self::load_referral(&env, referral_id)?;
+        if !Self::acts_for_patient(&env, &caller, &referral.patient) {
+            return Self::unauthorized(
+                &env,
+                &caller,
+                "approve_referral",
+                "patient_or_ManageAccess",
+            );
+        }
+        if referral.status != ReferralStatus::Pending {
+            return Err(ContractError::InvalidReferralStatus);
+        }
+
+        let mut granted = Vec::new(&env);
+        for record_id in referral.record_ids.iter() {
+            if Self::grant_record_unless_held(
+                &env,
+                &referral.patient,
+                &referral.to_provider,
+                record_id,
+                &AccessLevel::Read,
+                access_duration_seconds,
+            ) {
+                granted.push_back(record_id);
+            }
+        }
+        let now = env.ledger().timestamp();
+        referral.status = ReferralStatus::Approved;
+        referral.access_duration = access_duration_seconds;
+        referral.approved_at = now;
+        referral.granted_record_ids = granted;
+        referral.updated_at = now;
+        referral::set_referral(&env, &referral);
+        events::publish_referral_updated(&env, &referral, caller);
+        Ok(())
+    }
+
+    /// Decline a pending referral.
+    pub fn decline_referral(
+        env: Env,
+        caller: Address,
+        referral_id: u64,
+    ) -> Result<(), ContractError> {
+        circuit_breaker::require_not_paused(&env, &circuit_breaker::PauseScope::Global)?;
+        caller.require_auth();
+
+        let mut referral = Self::load_referral(&env, referral_id)?;
+        if !Self::acts_for_patient(&env, &caller, &referral.patient) {
+            return Self::unauthorized(
+                &env,
+                &caller,
+                "decline_referral",
+                "patient_or_ManageAccess",
+            );
+        }
+        if referral.status != ReferralStatus::Pending {
+            return Err(ContractError::InvalidReferralStatus);
+        }
+
+        referral.status = ReferralStatus::Declined;
+        referral.updated_at = env.ledger().timestamp();
+        referral::set_referral(&env, &referral);
+        events::publish_referral_updated(&env, &referral, caller);
+        Ok(())
+    }
+
+    /// Withdraw an open referral. Grants the approval gave the specialist
+    /// are revoked, unless they have since been replaced by another grant.
+    pub fn cancel_referral(
+        env: Env,
+        from_provider: Address,
+        referral_id: u64,
+    ) -> Result<(), ContractError> {
+        circuit_breaker::require_not_paused(&env, &circuit_breaker::PauseScope::Global)?;
+        from_provider.require_auth();
+
+        let mut referral = Self::load_referral(&env, referral_id)?;
+        if referral.from_provider != from_provider {
+            return Self::unauthorized(
+                &env,
+                &from_provider,
+                "cancel_referral",
+                "referring_provider",
+            );
+        }
+        if !referral.is_open() {
+            return Err(ContractError::InvalidReferralStatus);
+        }
+        if referral.status == ReferralStatus::Approved {
+            for record_id in referral.granted_record_ids.iter() {
+                Self::remove_issued_grant(
+                    &env,
+                    &referral.to_provider,
+                    record_id,
+                    &AccessLevel::Read,
+                    referral.approved_at,
+                    referral.access_duration,
+                );
+            }
+        }
+
+        referral.status = ReferralStatus::Cancelled;
+        referral.updated_at = env.ledger().timestamp();
+        referral::set_referral(&env, &referral);
+        events::publish_referral_updated(&env, &referral, from_provider);
+        Ok(())
+    }
+
+    /// Complete an approved referral by linking the specialist's response,
+    /// a record they wrote for the patient with `add_record`. The referring
+    /// provider is granted read access to it for the referral's access
+    /// duration.
+    pub fn respond_to_referral(
+        env: Env,
+        to_provider: Address,
+        referral_id: u64,
+        response_record_id: u64,
+    ) -> Result<(), ContractError> {
+        circuit_breaker::require_not_paused(&env, &circuit_breaker::PauseScope::Global)?;
+        to_provider.require_auth();
+
+        let mut referral = Self::load_referral(&env, referral_id)?;
+        if referral.to_provider != to_provider {
+            return Self::unauthorized(
+                &env,
+                &to_provider,
+                "respond_to_referral",
+                "referred_provider",
+            );
+        }
+        if referral.status != ReferralStatus::Approved {
+            return Err(ContractError::InvalidReferralStatus);
+        }
+        let record: VisionRecord = env
+            .storage()
+            .persistent()
+            .get(&(symbol_short!("RECORD"), response_record_id))
+            .ok_or(ContractError::RecordNotFound)?;
+        if record.patient != referral.patient || record.provider != to_provider {
+            return Err(ContractError::InvalidInput);
+        }
+
+        Self::store_record_grant(
+            &env,
+            &referral.patient,
+            &referral.from_provider,
+            response_record_id,
+            &AccessLevel::Read,
+            referral.access_duration,
+        );
+        referral.status = ReferralStatus::Completed;
+        referral.response_record_id = Some(response_record_id);
+        referral.updated_at = env.ledger().timestamp();
+        referral::set_referral(&env, &referral);
+        events::publish_referral_updated(&env, &referral, to_provider);
+        Ok(())
+    }
+
+    pub fn get_referral(env: Env, referral_id: u64) -> Option<Referral> {
+        referral::get_referral(&env, referral_id)
+    }
+
+    /// Page through the referrals `user` takes part in as referring
+    /// provider, specialist or patient, oldest first.
+    pub fn get_referrals_page(
+        env: Env,
+        user: Address,
+        cursor: Option<u64>,
+        limit: u32,
+    ) -> Result<ReferralPage, ContractError> {
+        pagination::validate_limit(limit)?;
+
+        let (referrals, next_cursor) = referral::page_user_referrals(&env, &user, cursor, limit);
+        Ok(ReferralPage {
+            referrals,
+            next_cursor,
+        })
+    }
+
+    // ======================== Bulk Import ========================
+
+    /// Open a bulk import job for the legacy EHR system `source`.
+    /// Requires ContractAdmin.
+    pub fn start_import(env: Env, caller: Address, source: String) -> Result<u64, ContractError> {
+        circuit_breaker::require_not_paused(&env, &circuit_breaker::PauseScope::Global)?;
+        caller.require_auth();
+
+        if !Self::has_admin_access(&env, &caller, &AdminTier::ContractAdmin) {
+            return Self::unauthorized(&env, &caller, "start_import", "admin_tier:ContractAdmin");
+        }
+        if source.is_empty() {
+            return Err(ContractError::InvalidInput);
+        }
+
+        Ok(migration::start_job(&env, &source, &caller).id)
+    }
+
+    /// Apply one chunk of an import job: users first, then patient profiles,
+    /// then historical records, keeping their original timestamps. Items
+    /// that fail validation or already exist are reported rather than
+    /// aborting the chunk. Re-submitting an applied chunk with the same
+    /// contents returns its stored report without importing anything; with
+    /// different contents it fails with `ImportChunkMismatch`.
+    pub fn import_chunk(
+        env: Env,
+        caller: Address,
+        job_id: u64,
+        chunk: u32,
+        users: Vec<ImportUserInput>,
+        profiles: Vec<ImportProfileInput>,
+        records: Vec<ImportRecordInput>,
+    ) -> Result<ImportChunkReport, ContractError> {
+        circuit_breaker::require_not_paused(&env, &circuit_breaker::PauseScope::Global)?;
+        caller.require_auth();
+
+        if !Self::has_admin_access(&env, &caller, &AdminTier::ContractAdmin) {
+            return Self::unauthorized(&env, &caller, "import_chunk", "admin_tier:ContractAdmin");
+        }
+        let mut job = migration::get_job(&env, job_id).ok_or(ContractError::ImportJobNotFound)?;
+        let payload_hash = migration::payload_hash(&env, &users, &profiles, &records);
+        if let Some(report) = migration::get_report(&env, job_id, chunk) {
+            if migration::get_payload_hash(&env, job_id, chunk) != Some(payload_hash) {
+                return Err(ContractError::ImportChunkMismatch);
+            }
+            return Ok(report);
+        }
+        if job.status == ImportStatus::Completed {
+            return Err(ContractError::ImportJobClosed);
+        }
+        if users.is_empty() && profiles.is_empty() && records.is_empty() {
+            return Err(ContractError::InvalidInput);
+        }
+        if migration::chunk_writes(users.len(), profiles.len(), records.len())
+            > migration::MAX_CHUNK_WRITES
+        {
+            return Err(ContractError::InvalidInput);
+        }
+
+        let mut report = ImportChunkReport::new(&env, job_id, chunk);
+        for (index, input) in users.iter().enumerate() {
+            let (outcome, code) = match Self::import_user(&env, &input) {
+                Ok(outcome) => (outcome, 0),
+                Err(err) => (ImportOutcome::Failed, err as u32),
+            };
+            report.push(ImportItemKind::User, index as u32, outcome, 0, code);
+        }
+        for (index, input) in profiles.iter().enumerate() {
+            let (outcome, code) = match Self::import_profile(&env, &input) {
+                Ok(outcome) => (outcome, 0),
+                Err(err) => (ImportOutcome::Failed, err as u32),
+            };
+            report.push(ImportItemKind::Profile, index as u32, outcome, 0, code);
+        }
+        for (index, input) in records.iter().enumerate() {
+            let (outcome, record_id, code) = match Self::import_record(&env, &job, &input) {
+                Ok((outcome, record_id)) => (outcome, record_id, 0),
+                Err(err) => (ImportOutcome::Failed, 0, err as u32),
+            };
+            report.push(
+                ImportItemKind::Record,
+                index as u32,
+                outcome,
+                record_id,
+                code,
+            );
+        }
+
+        migration::finish_chunk(&env, &mut job, &report, &payload_hash);
+        events::publish_import_chunk_applied(
+            &env,
+            job_id,
+            chunk,
+            report.imported,
+            report.skipped,
+            report.failed,
+        );
+
+        Ok(report)
+    }
+
+    /// Close an import job; further new chunks are rejected with
+    /// `ImportJobClosed`.
+    pub fn complete_import(
+        env: Env,
+        caller: Address,
+        job_id: u64,
+    ) -> Result<ImportJob, ContractError> {
+        circuit_breaker::require_not_paused(&env, &circuit_breaker::PauseScope::Global)?;
+        caller.require_auth();
+
+        if !Self::has_admin_access(&env, &caller, &AdminTier::ContractAdmin) {
+            return Self::unauthorized(
+                &env,
+                &caller,
+                "complete_import",
+                "admin_tier:ContractAdmin",
+            );
+        }
+        let mut job = migration::get_job(&env, job_id).ok_or(ContractError::ImportJobNotFound)?;
+        if job.status == ImportStatus::Completed {
+            return Err(ContractError::ImportJobClosed);
+        }
+        job.status = ImportStatus::Completed;
+        job.updated_at = env.ledger().timestamp();
+        migration::set_job(&env, &job);
+
+        Ok(job)
+    }
+
+    pub fn get_import_job(env: Env, job_id: u64) -> Option<ImportJob> {
+        migration::get_job(&env, job_id)
+    }
+
+    pub fn get_import_report(env: Env, job_id: u64, chunk: u32) -> Option<ImportChunkReport> {
+        migration::get_report(&env, job_id, chunk)
+    }
+
+    /// Where an imported record came from; `None` for records created
+    /// on-chain.
+    pub fn get_import_provenance(env: Env, record_id: u64) -> Option<ImportProvenance> {
+        migration::get_provenance(&env, record_id)
+    }
+
+    fn import_user(env: &Env, input: &ImportUserInput) -> Result<ImportOutcome, ContractError> {
+        validation::validate_name(&input.name)?;
+        if input.registered_at > env.ledger().timestamp() {
+            return Err(ContractError::InvalidTimestamp);
+        }
+        // Administrators are appointed on-chain, never migrated.
+        if matches!(input.role, Role::None | Role::Admin) {
+            return Err(ContractError::InvalidRole);
+        }
+
+        let key = (symbol_short!("USER"), input.user.clone());
+        if env.storage().persistent().has(&key) {
+            return Ok(ImportOutcome::Skipped);
+        }
+        let user = User {
+            address: input.user.clone(),
+            role: input.role.clone(),
+            name: input.name.clone(),
+            registered_at: input.registered_at,
+            is_active: true,
+        };
+        env.storage().persistent().set(&key, &user);
+        extend_ttl_address_key(env, &key);
+        rbac::assign_role(env, input.user.clone(), input.role.clone(), 0);
+        events::publish_user_registered(
+            env,
+            input.user.clone(),
+            input.role.clone(),
+            input.name.clone(),
+        );
+
+        Ok(ImportOutcome::Imported)
+    }
+
+    fn import_profile(
+        env: &Env,
+        input: &ImportProfileInput,
+    ) -> Result<ImportOutcome, ContractError> {
+        if input.created_at > env.ledger().timestamp() {
+            return Err(ContractError::InvalidTimestamp);
+        }
+
+        let key = (symbol_short!("PAT_PROF"), input.patient.clone());
+        if env.storage().persistent().has(&key) {
+            return Ok(ImportOutcome::Skipped);
+        }
+        let profile = PatientProfile {
+            patient: input.patient.clone(),
+            created_at: input.created_at,
+            updated_at: input.created_at,
+            is_active: true,
+            date_of_birth_hash: input.date_of_birth_hash.clone(),
+            gender_hash: input.gender_hash.clone(),
+            blood_type_hash: input.blood_type_hash.clone(),
+            emergency_contact: OptionalEmergencyContact::None,
+            insurance_info: OptionalInsuranceInfo::None,
+            medical_history_refs: Vec::new(env),
+            majority_at: 0,
+            guardians: Vec::new(env),
+        };
+        env.storage().persistent().set(&key, &profile);
+        extend_ttl_address_key(env, &key);
+        events::publish_profile_created(env, input.patient.clone());
+
+        Ok(ImportOutcome::Imported)
+    }
+
+    fn import_record(
+        env: &Env,
+        job: &ImportJob,
+        input: &ImportRecordInput,
+    ) -> Result<(ImportOutcome, u64), ContractError> {
+        if input.legacy_id.is_empty() {
+            return Err(ContractError::InvalidInput);
+        }
+        if let Some(record_id) = migration::imported_record_id(env, &job.source, &input.legacy_id) {
+            return Ok((ImportOutcome::Skipped, record_id));
+        }
+        validation::validate_data_hash(&input.data_hash)?;
+        if input.created_at == 0 || input.created_at > env.ledger().timestamp() {
+            return Err(ContractError::InvalidTimestamp);
+        }
+        if !rbac::has_permission(env, &input.provider, &Permission::WriteRecord) {
+            return Err(ContractError::InsufficientPermissions);
+        }
+        Self::require_verified_provider(env, &input.provider)?;
+
+        let counter_key = symbol_short!("REC_CTR");
+        let record_id = env
+            .storage()
+            .instance()
+            .get::<Symbol, u64>(&counter_key)
+            .unwrap_or(0)
+            .saturating_add(1);
+        let (stored_hash, key_version) = Self::encrypt_data_hash(env, record_id, &input.data_hash)?;
+        env.storage().instance().set(&counter_key, &record_id);
+
+        let record = VisionRecord {
+            id: record_id,
+            patient: input.patient.clone(),
+            provider: input.provider.clone(),
+            record_type: input.record_type.clone(),
+            data_hash: stored_hash,
+            key_version,
+            created_at: input.created_at,
+            updated_at: input.created_at,
+        };
+        Self::store_new_record(env, &record);
+        migration::mark_imported(
+            env,
+            &ImportProvenance {
+                record_id,
+                job_id: job.id,
+                source: job.source.clone(),
+                legacy_id: input.legacy_id.clone(),
+                imported_at: env.ledger().timestamp(),
+            },
+        );
+        events::publish_record_added(
+            env,
+            record_id,
+            input.patient.clone(),
+            input.provider.clone(),
+            input.record_type.clone(),
+        );
+
+        Ok((ImportOutcome::Imported, record_id))
+    }
+
+    // ======================== Provider Registry ========================
+
+    /// Rejects providers that are not currently verified: `ProviderNotFound`
+    /// for accounts without a provider profile, `ProviderNotVerified` for
+    /// profiles that are pending, rejected or suspended. A role alone is not
+    /// enough to act as a provider.
+    fn require_verified_provider(env: &Env, provider: &Address) -> Result<(), ContractError> {
+        if Self::load_provider(env, provider)?.is_verified() {
+            Ok(())
+        } else {
+            Err(ContractError::ProviderNotVerified)
+        }
+    }
+
+    fn load_provider(env: &Env, provider: &Address) -> Result<Provider, ContractError> {
+        provider::get_provider(env, provider).ok_or(ContractError::ProviderNotFound)
+    }
+
+    /// Register a provider profile with licenses, specialties, certifications
+    /// and practice locations. The provider starts in `Pending` status.
+    ///
+    /// Providers may register themselves; users with ManageUsers permission
+    /// may register on their behalf.
+    pub fn register_provider(
+        env: Env,
+        caller: Address,
+        provider: Address,
+        name: String,
+        licenses: Vec<License>,
+        specialties: Vec<String>,
+        certifications: Vec<Certification>,
+        locations: Vec<Location>,
+    ) -> Result<u64, ContractError> {
+        circuit_breaker::require_not_paused(&env, &circuit_breaker::PauseScope::Global)?;
+        caller.require_auth();
+
+        if caller != provider && !rbac::has_permission(&env, &caller, &Permission::ManageUsers) {
+            return Self::unauthorized(
+                &env,
+                &caller,
+                "register_provider",
+                "self_or_permission:ManageUsers",
+            );
+        }
+
+        validation::validate_name(&name)?;
+        if provider::get_provider(&env, &provider).is_some() {
+            return Err(ContractError::ProviderAlreadyRegistered);
+        }
+        validation::validate_licenses(&licenses, env.ledger().timestamp())?;
+
+        let record = Provider {
+            address: provider.clone(),
+            name: name.clone(),
+            licenses,
+            specialties: specialties.clone(),
+            certifications,
+            locations,
+            verification_status: VerificationStatus::Pending,
+            registered_at: env.ledger().timestamp(),
+            verified_at: None,
+            verified_by: None,
+            is_active: true,
+        };
+        provider::set_provider(&env, &record);
+        for specialty in specialties.iter() {
+            provider::add_provider_to_specialty_index(&env, &specialty, &provider);
+        }
+
+        let provider_id = provider::increment_provider_counter(&env);
+        provider::add_provider_id(&env, provider_id, &provider);
+        events::publish_provider_registered(&env, provider, name, provider_id);
+
+        Ok(provider_id)
+    }
+
+    /// Record the outcome of a provider's license review.
+    ///
+    /// `status` must be `Verified` or `Rejected`; verification also reinstates
+    /// a suspended provider and requires at least one unexpired license.
+    /// Requires OperatorAdmin tier or above.
+    pub fn verify_provider(
+        env: Env,
+        caller: Address,
+        provider: Address,
+        status: VerificationStatus,
+    ) -> Result<(), ContractError> {
+        circuit_breaker::require_not_paused(&env, &circuit_breaker::PauseScope::Global)?;
+        caller.require_auth();
+
+        if !Self::has_admin_access(&env, &caller, &AdminTier::OperatorAdmin) {
+            return Self::unauthorized(
+                &env,
+                &caller,
+                "verify_provider",
+                "admin_tier:OperatorAdmin",
+            );
+        }
+
+        let mut record = Self::load_provider(&env, &provider)?;
+        match status {
+            VerificationStatus::Verified => {
+                if !record.has_current_license(env.ledger().timestamp()) {
+                    return Err(ContractError::ProviderLicenseInvalid);
+                }
+            }
+            VerificationStatus::Rejected => {}
+            VerificationStatus::Pending | VerificationStatus::Suspended => {
+                return Err(ContractError::InvalidVerificationStatus);
+            }
+        }
+
+        record.verification_status = status.clone();
+        record.verified_at = Some(env.ledger().timestamp());
+        record.verified_by = Some(caller.clone());
+        provider::set_provider(&env, &record);
+
+        events::publish_provider_verified(&env, provider, caller, status);
+
+        Ok(())
+    }
+
+    /// Suspend a provider. Suspended providers cannot add records or issue
+    /// prescriptions until re-verified. Requires OperatorAdmin tier or above.
+    pub fn suspend_provider(
+        env: Env,
+        caller: Address,
+        provider: Address,
+    ) -> Result<(), ContractError> {
+        circuit_breaker::require_not_paused(&env, &circuit_breaker::PauseScope::Global)?;
+        caller.require_auth();
+
+        if !Self::has_admin_access(&env, &caller, &AdminTier::OperatorAdmin) {
+            return Self::unauthorized(
+                &env,
+                &caller,
+                "suspend_provider",
+                "admin_tier:OperatorAdmin",
+            );
+        }
+
+        let mut record = Self::load_provider(&env, &provider)?;
+        if record.verification_status == VerificationStatus::Suspended {
+            return Err(ContractError::InvalidVerificationStatus);
+        }
+
+        record.verification_status = VerificationStatus::Suspended;
+        provider::set_provider(&env, &record);
+
+        events::publish_provider_verified(&env, provider, caller, VerificationStatus::Suspended);
+
+        Ok(())
+    }
+
+    /// Get a provider's registry profile.
+    pub fn get_provider(env: Env, provider: Address) -> Result<Provider, ContractError> {
+        Self::load_provider(&env, &provider)
+    }
+
+    /// Get the addresses of the first `MAX_PAGE_LIMIT` providers listing
+    /// `specialty`.
+    ///
+    /// Deprecated: use `search_by_specialty_page`.
+    pub fn search_providers_by_specialty(env: Env, specialty: String) -> Vec<Address> {
+        Self::addresses(
+            &env,
+            provider::page_providers_by_specialty(
+                &env,
+                &specialty,
+                None,
+                pagination::MAX_PAGE_LIMIT,
+                |_| true,
+            )
+            .0,
+        )
+    }
+
+    /// Get the addresses of the active providers in the given status among
+    /// the first `MAX_PAGE_SCAN` that entered it.
+    ///
+    /// Deprecated: use `search_by_status_page`.
+    pub fn search_providers_by_status(env: Env, status: VerificationStatus) -> Vec<Address> {
+        Self::addresses(
+            &env,
+            provider::page_providers_by_status(&env, &status, None, pagination::MAX_PAGE_LIMIT).0,
+        )
+    }
+
+    /// Find verified, active providers with `specialty` who practise at a
+    /// location matching `city` and/or `state` (`None` matches any), among
+    /// the first `MAX_PAGE_SCAN` providers listing the specialty.
+    ///
+    /// Deprecated: use `find_providers_page`.
+    pub fn find_providers(
+        env: Env,
+        specialty: String,
+        city: Option<String>,
+        state: Option<String>,
+    ) -> Vec<Provider> {
+        provider::page_providers_by_specialty(
+            &env,
+            &specialty,
+            None,
+            pagination::MAX_PAGE_LIMIT,
+            |p| p.is_verified() && p.has_specialty(&specialty) && p.practises_in(&city, &state),
+        )
+        .0
+    }
+
+    fn addresses(env: &Env, providers: Vec<Provider>) -> Vec<Address> {
+        let mut addresses = Vec::new(env);
+        for p in providers.iter() {
+            addresses.push_back(p.address);
+        }
+        addresses
+    }
+
+    /// Page through the providers listing `specialty`, in registration order.
+    pub fn search_by_specialty_page(
+        env: Env,
+        specialty: String,
+        cursor: Option<u64>,
+        limit: u32,
+    ) -> Result<ProviderPage, ContractError> {
+        pagination::validate_limit(limit)?;
+
+        let (providers, next_cursor) =
+            provider::page_providers_by_specialty(&env, &specialty, cursor, limit, |_| true);
+        Ok(ProviderPage {
+            providers,
+            next_cursor,
+        })
+    }
+
+    /// Page through the active providers currently in `status`.
+    pub fn search_by_status_page(
+        env: Env,
+        status: VerificationStatus,
+        cursor: Option<u64>,
+        limit: u32,
+    ) -> Result<ProviderPage, ContractError> {
+        pagination::validate_limit(limit)?;
+
+        let (providers, next_cursor) =
+            provider::page_providers_by_status(&env, &status, cursor, limit);
+        Ok(ProviderPage {
+            providers,
+            next_cursor,
+        })
+    }
+
+    /// Page through the verified, active providers with `specialty` who
+    /// practise at a location matching `city` and/or `state` (`None`
+    /// matches any).
+    pub fn find_providers_page(
+        env: Env,
+        specialty: String,
+        city: Option<String>,
+        state: Option<String>,
+        cursor: Option<u64>,
+        limit: u32,
+    ) -> Result<ProviderPage, ContractError> {
+        pagination::validate_limit(limit)?;
+
+        let (providers, next_cursor) =
+            provider::page_providers_by_specialty(&env, &specialty, cursor, limit, |p| {
+                p.is_verified() && p.has_specialty(&specialty) && p.practises_in(&city, &state)
+            });
+        Ok(ProviderPage {
+            providers,
+            next_cursor,
+        })
+    }
+
+    // ======================== Prescriptions ========================
+
+    /// Returns true if `user` holds an active prescriber role.
+    fn is_prescriber(env: &Env, user: &Address) -> bool {
+        matches!(
+            rbac::get_active_assignment(env, user).map(|a| a.role),
+            Some(Role::Optometrist) | Some(Role::Ophthalmologist)
+        )
+    }
+
+    /// Returns true if `user` is a registered non-patient member of the
+    /// network (staff, prescriber or admin), such as an optical retailer or
+    /// an ER clinician.
+    fn is_network_member(env: &Env, user: &Address) -> bool {
+        matches!(
+            rbac::get_active_assignment(env, user).map(|a| a.role),
+            Some(Role::Staff)
+                | Some(Role::Optometrist)
+                | Some(Role::Ophthalmologist)
+                | Some(Role::Admin)
+        )
+    }
+
+    fn load_prescription(env: &Env, prescription_id: u64) -> Result<Prescription, ContractError> {
+        prescription::get_prescription(env, prescription_id)
+            .ok_or(ContractError::PrescriptionNotFound)
+    }
+
+    /// Rejects prescriptions that can no longer be filled.
+    fn require_fillable(env: &Env, rx: &Prescription) -> Result<(), ContractError> {
+        if rx.status != PrescriptionStatus::Active {
+            return Err(ContractError::InvalidPrescriptionStatus);
+        }
+        if rx.is_expired(env.ledger().timestamp()) {
+            return Err(ContractError::PrescriptionExpired);
+        }
+        Ok(())
+    }
+
+    /// Issue a glasses or contact lens prescription for a patient.
+    ///
+    /// The provider must hold the Optometrist or Ophthalmologist role. Both
+    /// eyes are checked against accepted clinical ranges, contact lens
+    /// prescriptions must carry fitting data, and the prescription expires
+    /// `validity_seconds` after issuance (1 hour to 5 years).
+    pub fn issue_prescription(
+        env: Env,
+        provider: Address,
+        patient: Address,
+        lens_type: LensType,
+        left_eye: PrescriptionData,
+        right_eye: PrescriptionData,
+        contact_data: OptionalContactLensData,
+        validity_seconds: u64,
+        metadata_hash: String,
+    ) -> Result<u64, ContractError> {
+        circuit_breaker::require_not_paused(&env, &circuit_breaker::PauseScope::Global)?;
+        provider.require_auth();
+
+        if !Self::is_prescriber(&env, &provider) {
+            return Self::unauthorized(
+                &env,
+                &provider,
+                "issue_prescription",
+                "role:Optometrist_or_Ophthalmologist",
+            );
+        }
+        Self::require_verified_provider(&env, &provider)?;
+
+        prescription::validate_prescription(&lens_type, &left_eye, &right_eye, &contact_data)?;
+        validation::validate_duration(validity_seconds)?;
+        validation::validate_data_hash(&metadata_hash)?;
+
+        let now = env.ledger().timestamp();
+        let prescription_id = prescription::increment_prescription_counter(&env);
+        let expires_at = now.saturating_add(validity_seconds);
+        let rx = Prescription {
+            id: prescription_id,
+            patient: patient.clone(),
+            provider: provider.clone(),
+            lens_type: lens_type.clone(),
+            left_eye,
+            right_eye,
+            contact_data,
+            issued_at: now,
+            expires_at,
+            verified: false,
+            metadata_hash,
+            status: PrescriptionStatus::Active,
+            verified_by: None,
+            verified_at: None,
+            dispensed_by: None,
+            dispensed_at: None,
+        };
+        prescription::save_prescription(&env, &rx);
+
+        events::publish_prescription_issued(
+            &env,
+            prescription_id,
+            patient,
+            provider,
+            lens_type,
+            expires_at,
+        );
+
+        Ok(prescription_id)
+    }
+
+    /// Confirm that a prescription is genuine, unrevoked and unexpired.
+    ///
+    /// Intended for optical retailers before filling an order; the verifier
+    /// must be a registered staff member, prescriber or admin. Returns the
+    /// verified prescription.
+    pub fn verify_prescription(
+        env: Env,
+        verifier: Address,
+        prescription_id: u64,
+    ) -> Result<Prescription, ContractError> {
+        circuit_breaker::require_not_paused(&env, &circuit_breaker::PauseScope::Global)?;
+        verifier.require_auth();
+
+        if !Self::is_network_member(&env, &verifier) {
+            return Self::unauthorized(
+                &env,
+                &verifier,
+                "verify_prescription",
+                "role:Staff_or_provider_or_Admin",
+            );
+        }
+
+        let rx = Self::load_prescription(&env, prescription_id)?;
+        Self::require_fillable(&env, &rx)?;
+
+        prescription::verify_prescription(&env, prescription_id, verifier.clone());
+        events::publish_prescription_verified(
+            &env,
+            prescription_id,
+            rx.patient,
+            rx.provider,
+            verifier,
+        );
+
+        Self::load_prescription(&env, prescription_id)
+    }
+
+    /// Record that a verified prescription has been filled.
+    /// A prescription can only be dispensed once, and not after it expires.
+    pub fn dispense_prescription(
+        env: Env,
+        dispenser: Address,
+        prescription_id: u64,
+    ) -> Result<(), ContractError> {
+        circuit_breaker::require_not_paused(&env, &circuit_breaker::PauseScope::Global)?;
+        dispenser.require_auth();
+
+        if !Self::is_network_member(&env, &dispenser) {
+            return Self::unauthorized(
+                &env,
+                &dispenser,
+                "dispense_prescription",
+                "role:Staff_or_provider_or_Admin",
+            );
+        }
+
+        let mut rx = Self::load_prescription(&env, prescription_id)?;
+        Self::require_fillable(&env, &rx)?;
+        if !rx.verified {
+            return Err(ContractError::InvalidPrescriptionStatus);
+        }
+
+        rx.status = PrescriptionStatus::Dispensed;
+        rx.dispensed_by = Some(dispenser.clone());
+        rx.dispensed_at = Some(env.ledger().timestamp());
+        prescription::save_prescription(&env, &rx);
+
+        events::publish_prescription_dispensed(
+            &env,
+            prescription_id,
+            rx.patient,
+            rx.provider,
+            dispenser,
+        );
+
+        Ok(())
+    }
+
+    /// Revoke an active prescription.
+    /// Only the issuing provider or a SystemAdmin may revoke.
+    pub fn revoke_prescription(
+        env: Env,
+        caller: Address,
+        prescription_id: u64,
+        reason: String,
+    ) -> Result<(), ContractError> {
+        circuit_breaker::require_not_paused(&env, &circuit_breaker::PauseScope::Global)?;
+        caller.require_auth();
+
+        let mut rx = Self::load_prescription(&env, prescription_id)?;
+        if caller != rx.provider && !rbac::has_permission(&env, &caller, &Permission::SystemAdmin) {
+            return Self::unauthorized(
+                &env,
+                &caller,
+                "revoke_prescription",
+                "issuing_provider_or_SystemAdmin",
+            );
+        }
+
+        if rx.status != PrescriptionStatus::Active {
+            return Err(ContractError::InvalidPrescriptionStatus);
+        }
+
+        rx.status = PrescriptionStatus::Revoked;
+        prescription::save_prescription(&env, &rx);
+
+        events::publish_prescription_revoked(
+            &env,
+            prescription_id,
+            rx.patient,
+            rx.provider,
+            caller,
+            reason,
+        );
+
+        Ok(())
+    }
+
+    /// Get a prescription by ID.
+    pub fn get_prescription(env: Env, prescription_id: u64) -> Result<Prescription, ContractError> {
+        Self::load_prescription(&env, prescription_id)
+    }
+
+    /// Get the IDs of the first `MAX_PAGE_LIMIT` prescriptions issued to a
+    /// patient.
+    ///
+    /// Deprecated: use `get_prescription_history_page`.
+    pub fn get_prescription_history(env: Env, patient: Address) -> Vec<u64> {
+        prescription::page_patient_history(&env, &patient, None, pagination::MAX_PAGE_LIMIT, false)
+            .0
+    }
+
+    /// Page through the IDs of the prescriptions issued to a patient, in
+    /// issue order or most recent first.
+    pub fn get_prescription_history_page(
+        env: Env,
+        patient: Address,
+        newest_first: bool,
+        cursor: Option<u64>,
+        limit: u32,
+    ) -> Result<PrescriptionPage, ContractError> {
+        pagination::validate_limit(limit)?;
+
+        let (prescription_ids, next_cursor) =
+            prescription::page_patient_history(&env, &patient, cursor, limit, newest_first);
+        Ok(PrescriptionPage {
+            prescription_ids,
+            next_cursor,
+        })
+    }
+
+    // ======================== Emergency Access ========================
+
+    fn push_emergency_audit(env: &Env, access_id: u64, actor: &Address, action: &str) {
+        emergency::add_audit_entry(
+            env,
+            &EmergencyAuditEntry {
+                access_id,
+                actor: actor.clone(),
+                action: String::from_str(env, action),
+                timestamp: env.ledger().timestamp(),
+            },
+        );
+    }
+
+    /// Notify the patient's emergency contact, if their profile has one, and
+    /// record the contact on the grant the first time it is told.
+    fn notify_emergency_contact(env: &Env, access: &EmergencyAccess, record_id: Option<u64>) {
+        let profile_key = (symbol_short!("PAT_PROF"), access.patient.clone());
+        let profile: Option<PatientProfile> = env.storage().persistent().get(&profile_key);
+        if let Some(PatientProfile {
+            emergency_contact: OptionalEmergencyContact::Some(contact),
+            ..
+        }) = profile
+        {
+            events::publish_emergency_contact_notified(
+                env,
+                access.id,
+                access.patient.clone(),
+                access.requester.clone(),
+                contact.clone(),
+                record_id,
+            );
+            Self::push_emergency_audit(env, access.id, &env.current_contract_address(), "NOTIFIED");
+
+            if !access.notified_contacts.contains(&contact) {
+                let mut access = access.clone();
+                access.notified_contacts.push_back(contact);
+                emergency::set_emergency_access(env, &access);
+            }
+        }
+    }
+
+    /// Request time-boxed break-glass access to a patient's records.
+    ///
+    /// The requester must be registered as Staff, a provider or an Admin and
+    /// must attest to the emergency. Access lasts at most 24 hours, is
+    /// audited, and the patient's emergency contact is notified.
+    pub fn request_emergency_access(
+        env: Env,
+        requester: Address,
+        patient: Address,
+        condition: EmergencyCondition,
+        attestation: String,
+        duration_seconds: u64,
+    ) -> Result<u64, ContractError> {
+        circuit_breaker::require_not_paused(&env, &circuit_breaker::PauseScope::Global)?;
+        requester.require_auth();
+
+        if !Self::is_network_member(&env, &requester) {
+            return Self::unauthorized(
+                &env,
+                &requester,
+                "request_emergency_access",
+                "role:Staff_or_provider_or_Admin",
+            );
+        }
+        if attestation.is_empty() {
+            return Err(ContractError::InvalidAttestation);
+        }
+        if duration_seconds == 0 || duration_seconds > emergency::MAX_EMERGENCY_DURATION {
+            return Err(ContractError::InvalidInput);
+        }
+
+        let now = env.ledger().timestamp();
+        let access = EmergencyAccess {
+            id: emergency::increment_emergency_counter(&env),
+            patient: patient.clone(),
+            requester: requester.clone(),
+            condition: condition.clone(),
+            attestation,
+            granted_at: now,
+            expires_at: now.saturating_add(duration_seconds),
+            status: EmergencyStatus::Active,
+            notified_contacts: Vec::new(&env),
+        };
+        emergency::set_emergency_access(&env, &access);
+
+        Self::push_emergency_audit(&env, access.id, &requester, "GRANTED");
+        events::publish_emergency_access_granted(
+            &env,
+            access.id,
+            patient,
+            requester,
+            condition,
+            access.expires_at,
+        );
+        Self::notify_emergency_contact(&env, &access, None);
+
+        Ok(access.id)
+    }
+
+    /// Revoke an active emergency access grant.
+    /// The patient, the original requester or a SystemAdmin may revoke.
+    pub fn revoke_emergency_access(
+        env: Env,
+        caller: Address,
+        access_id: u64,
+    ) -> Result<(), ContractError> {
+        circuit_breaker::require_not_paused(&env, &circuit_breaker::PauseScope::Global)?;
+        caller.require_auth();
+
+        let access = emergency::get_emergency_access(&env, access_id)
+            .ok_or(ContractError::EmergencyAccessNotFound)?;
+        if caller != access.patient
+            && caller != access.requester
+            && !rbac::has_permission(&env, &caller, &Permission::SystemAdmin)
+        {
+            return Self::unauthorized(
+                &env,
+                &caller,
+                "revoke_emergency_access",
+                "patient_or_requester_or_SystemAdmin",
+            );
+        }
+
+        match access.status {
+            EmergencyStatus::Revoked => return Err(ContractError::EmergencyAccessRevoked),
+            EmergencyStatus::Expired => return Err(ContractError::EmergencyAccessExpired),
+            EmergencyStatus::Active if access.expires_at <= env.ledger().timestamp() => {
+                return Err(ContractError::EmergencyAccessExpired)
+            }
+            EmergencyStatus::Active => {}
+        }
+
+        emergency::revoke_emergency_access(&env, access_id);
+        Self::push_emergency_audit(&env, access_id, &caller, "REVOKED");
+        events::publish_emergency_access_revoked(&env, access_id, access.patient, caller);
+
+        Ok(())
+    }
+
+    /// Mark emergency grants past their expiry as expired.
+    /// Returns the number of grants expired.
+    pub fn expire_emergency_accesses(env: Env) -> u32 {
+        emergency::expire_emergency_accesses(&env)
+    }
+
+    /// Get an emergency access grant by ID.
+    pub fn get_emergency_access(
+        env: Env,
+        access_id: u64,
+    ) -> Result<EmergencyAccess, ContractError> {
+        emergency::get_emergency_access(&env, access_id)
+            .ok_or(ContractError::EmergencyAccessNotFound)
+    }
+
+    /// Get the active emergency grant held by `requester` for `patient`, if any.
+    pub fn check_emergency_access(
+        env: Env,
+        patient: Address,
+        requester: Address,
+    ) -> Option<EmergencyAccess> {
+        emergency::has_active_emergency_access(&env, &patient, &requester)
+    }
+
+    /// Get the active emergency grants among the first `MAX_PAGE_SCAN`
+    /// issued for a patient.
+    ///
+    /// Deprecated: use `get_emergency_accesses_page`.
+    pub fn get_patient_emergency_accesses(env: Env, patient: Address) -> Vec<EmergencyAccess> {
+        emergency::page_patient_emergency_accesses(
+            &env,
+            &patient,
+            None,
+            pagination::MAX_PAGE_LIMIT,
+            |access| access.status == EmergencyStatus::Active,
+        )
+        .0
+    }
+
+    /// Page through the emergency grants issued for a patient, in the order
+    /// they were requested. With `active_only` only grants still in the
+    /// `Active` status are returned.
+    pub fn get_emergency_accesses_page(
+        env: Env,
+        patient: Address,
+        active_only: bool,
+        cursor: Option<u64>,
+        limit: u32,
+    ) -> Result<EmergencyAccessPage, ContractError> {
+        pagination::validate_limit(limit)?;
+
+        let (accesses, next_cursor) =
+            emergency::page_patient_emergency_accesses(&env, &patient, cursor, limit, |access| {
+                !active_only || access.status == EmergencyStatus::Active
+            });
+        Ok(EmergencyAccessPage {
+            accesses,
+            next_cursor,
+        })
+    }
+
+    /// Get the audit trail of an emergency access grant.
+    pub fn get_emergency_audit_trail(env: Env, access_id: u64) -> Vec<EmergencyAuditEntry> {
+        emergency::get_audit_entries(&env, access_id)
+    }
+
+    // ======================== Admin Tier Management ========================
+
+    /// Promotes or assigns a target address to the specified admin tier.
+    ///
+    /// Only a `SuperAdmin` may call this.
+    pub fn promote_admin(
+        env: Env,
+        caller: Address,
+        target: Address,
+        tier: AdminTier,
+    ) -> Result<(), ContractError> {
+        caller.require_auth();
+        if !admin_tiers::promote_admin(&env, &caller, &target, tier) {
+            return Self::unauthorized(&env, &caller, "promote_admin", "admin_tier:SuperAdmin");
+        }
+        admin_tiers::track_admin(&env, &target);
+        Ok(())
+    }
+
+    /// Removes the admin tier from the target address entirely.
+    ///
+    /// Only a `SuperAdmin` may call this.
+    pub fn demote_admin(env: Env, caller: Address, target: Address) -> Result<(), ContractError> {
+        caller.require_auth();
+        if !admin_tiers::demote_admin(&env, &caller, &target) {
+            return Self::unauthorized(&env, &caller, "demote_admin", "admin_tier:SuperAdmin");
+        }
+        admin_tiers::untrack_admin(&env, &target);
+        Ok(())
+    }
+
+    /// Returns the admin tier of the given address, if any.
+    pub fn get_admin_tier(env: Env, admin: Address) -> Option<AdminTier> {
+        admin_tiers::get_admin_tier(&env, &admin)
+    }
+
+    // ======================== Access Policies ========================
+
+    /// Create or replace an attribute-based access policy. It takes effect
+    /// once attached to a record or a sensitivity level. Requires
+    /// ContractAdmin.
+    pub fn create_access_policy(
+        env: Env,
+        caller: Address,
+        policy: AccessPolicy,
+    ) -> Result<(), ContractError> {
+        circuit_breaker::require_not_paused(&env, &circuit_breaker::PauseScope::Global)?;
+        caller.require_auth();
+
+        if !Self::has_admin_access(&env, &caller, &AdminTier::ContractAdmin) {
+            return Self::unauthorized(
+                &env,
+                &caller,
+                "create_access_policy",
+                "admin_tier:ContractAdmin",
+            );
+        }
+        if policy.id.is_empty() {
+            return Err(ContractError::InvalidInput);
+        }
+        match policy.conditions.time_restriction {
+            TimeRestriction::HourRange(start, end) if start > 23 || end > 23 => {
+                return Err(ContractError::InvalidInput);
+            }
+            TimeRestriction::DaysOfWeek(mask) if mask == 0 || mask > 0b111_1111 => {
+                return Err(ContractError::InvalidInput);
+            }
+            _ => {}
+        }
+
+        rbac::create_access_policy(&env, policy);
+        Ok(())
+    }
+
+    /// Delete an access policy. Requires ContractAdmin.
+    pub fn remove_access_policy(
+        env: Env,
+        caller: Address,
+        policy_id: String,
+    ) -> Result<(), ContractError> {
+        circuit_breaker::require_not_paused(&env, &circuit_breaker::PauseScope::Global)?;
+        caller.require_auth();
+
+        if !Self::has_admin_access(&env, &caller, &AdminTier::ContractAdmin) {
+            return Self::unauthorized(
+                &env,
+                &caller,
+                "remove_access_policy",
+                "admin_tier:ContractAdmin",
+            );
+        }
+        if rbac::policy_attachment_count(&env, &policy_id) > 0 {
+            return Err(ContractError::PolicyAttached);
+        }
+        if !rbac::remove_access_policy(&env, &policy_id) {
+            return Err(ContractError::InvalidInput);
+        }
+        Ok(())
+    }
+
+    pub fn get_access_policy(env: Env, policy_id: String) -> Option<AccessPolicy> {
+        rbac::get_access_policy(&env, &policy_id)
+    }
+
+    /// Enforce a policy on reads of a record, or of every record at a
+    /// sensitivity level. Requires ContractAdmin.
+    pub fn attach_access_policy(
+        env: Env,
+        caller: Address,
+        target: PolicyTarget,
+        policy_id: String,
+    ) -> Result<(), ContractError> {
+        circuit_breaker::require_not_paused(&env, &circuit_breaker::PauseScope::Global)?;
+        caller.require_auth();
+
+        if !Self::has_admin_access(&env, &caller, &AdminTier::ContractAdmin) {
+            return Self::unauthorized(
+                &env,
+                &caller,
+                "attach_access_policy",
+                "admin_tier:ContractAdmin",
+            );
+        }
+        if rbac::get_access_policy(&env, &policy_id).is_none() {
+            return Err(ContractError::InvalidInput);
+        }
+        if let PolicyTarget::Record(record_id) = target {
+            if !env
+                .storage()
+                .persistent()
+                .has(&(symbol_short!("RECORD"), record_id))
+            {
+                return Err(ContractError::RecordNotFound);
+            }
+        }
+        if !rbac::attach_policy(&env, &target, &policy_id) {
+            return Err(ContractError::DuplicateRecord);
+        }
+        Ok(())
+    }
+
+    /// Requires ContractAdmin.
+    pub fn detach_access_policy(
+        env: Env,
+        caller: Address,
+        target: PolicyTarget,
+        policy_id: String,
+    ) -> Result<(), ContractError> {
+        circuit_breaker::require_not_paused(&env, &circuit_breaker::PauseScope::Global)?;
+        caller.require_auth();
+
+        if !Self::has_admin_access(&env, &caller, &AdminTier::ContractAdmin) {
+            return Self::unauthorized(
+                &env,
+                &caller,
+                "detach_access_policy",
+                "admin_tier:ContractAdmin",
+            );
+        }
+        if !rbac::detach_policy(&env, &target, &policy_id) {
+            return Err(ContractError::InvalidInput);
+        }
+        Ok(())
+    }
+
+    pub fn get_attached_policies(env: Env, target: PolicyTarget) -> Vec<String> {
+        rbac::attached_policies(&env, &target)
+    }
+
+    /// Record the credential a user has been verified to hold. Requires
+    /// ContractAdmin.
+    pub fn set_user_credential(
+        env: Env,
+        caller: Address,
+        user: Address,
+        credential: CredentialType,
+    ) -> Result<(), ContractError> {
+        circuit_breaker::require_not_paused(&env, &circuit_breaker::PauseScope::Global)?;
+        caller.require_auth();
+
+        if !Self::has_admin_access(&env, &caller, &AdminTier::ContractAdmin) {
+            return Self::unauthorized(
+                &env,
+                &caller,
+                "set_user_credential",
+                "admin_tier:ContractAdmin",
+            );
+        }
+        rbac::set_user_credential(&env, user, credential);
         Ok(())
     }
 
-    /// Revokes a custom permission from a user.
-    /// Requires the caller to have ManageUsers permission.
-    pub fn revoke_custom_permission(
+    pub fn get_user_credential(env: Env, user: Address) -> CredentialType {
+        rbac::get_user_credential(&env, &user)
+    }
+
+    /// Classify a record; records default to `Standard`. Requires
+    /// ContractAdmin.
+    pub fn set_record_sensitivity(
         env: Env,
         caller: Address,
-        user: Address,
-        permission: Permission,
+        record_id: u64,
+        sensitivity: SensitivityLevel,
     ) -> Result<(), ContractError> {
         circuit_breaker::require_not_paused(&env, &circuit_breaker::PauseScope::Global)?;
         caller.require_auth();
-        // Unified check: covers direct role, custom grants, and delegated roles
-        if !rbac::has_permission(&env, &caller, &Permission::ManageUsers) {
+
+        if !Self::has_admin_access(&env, &caller, &AdminTier::ContractAdmin) {
             return Self::unauthorized(
                 &env,
                 &caller,
-                "revoke_custom_permission",
-                "permission:ManageUsers",
+                "set_record_sensitivity",
+                "admin_tier:ContractAdmin",
             );
         }
-        rbac::revoke_custom_permission(&env, user, permission)
-            .map_err(|_| ContractError::UserNotFound)?;
+        if !env
+            .storage()
+            .persistent()
+            .has(&(symbol_short!("RECORD"), record_id))
+        {
+            return Err(ContractError::RecordNotFound);
+        }
+        rbac::set_record_sensitivity(&env, record_id, sensitivity);
         Ok(())
     }
 
-    /// Delegates a role to another user with an expiration timestamp.
-    /// The delegator must authenticate the transaction.
-    pub fn delegate_role(
+    pub fn get_record_sensitivity(env: Env, record_id: u64) -> SensitivityLevel {
+        rbac::get_record_sensitivity(&env, &record_id)
+    }
+
+    // ======================== Policy Engine Management ========================
+
+    /// Stores a composable policy definition on-chain.
+    /// Requires SystemAdmin permission or admin tier.
+    ///
+    /// When policy analysis is enabled, a policy with any static-analysis
+    /// finding is rejected with `PolicyAnalysisFailed`; `analyze_policy`
+    /// lists the findings. A version already in the policy's history is
+    /// rejected with `DuplicateRecord`.
+    pub fn store_policy(
         env: Env,
-        delegator: Address,
-        delegatee: Address,
-        role: Role,
-        expires_at: u64,
+        caller: Address,
+        policy: teye_common::policy_dsl::PolicyDefinition,
     ) -> Result<(), ContractError> {
-        circuit_breaker::require_not_paused(&env, &circuit_breaker::PauseScope::Global)?;
-        delegator.require_auth();
-        rbac::delegate_role(&env, delegator, delegatee, role, expires_at);
-        Ok(())
+        caller.require_auth();
+        if !Self::has_admin_access(&env, &caller, &AdminTier::ContractAdmin) {
+            return Self::unauthorized(&env, &caller, "store_policy", "admin_tier:ContractAdmin");
+        }
+        use teye_common::policy_engine::StorePolicyError;
+        teye_common::policy_engine::store_policy_checked(&env, &policy).map_err(|err| match err {
+            StorePolicyError::Version(err) => Self::policy_version_error(err),
+            StorePolicyError::Findings(_) => ContractError::PolicyAnalysisFailed,
+        })
     }
 
-    /// Pauses contract operations for a given scope.
-    pub fn pause_contract(
+    /// Stages a new version of a stored policy that goes live at
+    /// `activate_at`. Requires SystemAdmin permission or admin tier.
+    pub fn schedule_policy(
         env: Env,
         caller: Address,
-        scope: circuit_breaker::PauseScope,
-    ) -> Result<(), ContractError> {
+        policy: teye_common::policy_dsl::PolicyDefinition,
+        activate_at: u64,
+    ) -> Result<teye_common::policy_engine::PolicyRollout, ContractError> {
         caller.require_auth();
-        circuit_breaker::pause_contract(&env, &caller, scope)
+        if !Self::has_admin_access(&env, &caller, &AdminTier::ContractAdmin) {
+            return Self::unauthorized(
+                &env,
+                &caller,
+                "schedule_policy",
+                "admin_tier:ContractAdmin",
+            );
+        }
+        Self::check_policy_analysis(&env, &policy)?;
+        teye_common::policy_engine::schedule_policy(&env, &policy, activate_at)
+            .map_err(Self::policy_version_error)
     }
 
-    /// Resumes contract operations for a given scope.
-    pub fn resume_contract(
+    /// Stages a new version of a stored policy in shadow mode: it is
+    /// evaluated next to the active version and disagreements are logged.
+    /// Requires SystemAdmin permission or admin tier.
+    pub fn shadow_policy(
         env: Env,
         caller: Address,
-        scope: circuit_breaker::PauseScope,
-    ) -> Result<(), ContractError> {
+        policy: teye_common::policy_dsl::PolicyDefinition,
+    ) -> Result<teye_common::policy_engine::PolicyRollout, ContractError> {
         caller.require_auth();
-        circuit_breaker::resume_contract(&env, &caller, scope)
+        if !Self::has_admin_access(&env, &caller, &AdminTier::ContractAdmin) {
+            return Self::unauthorized(&env, &caller, "shadow_policy", "admin_tier:ContractAdmin");
+        }
+        Self::check_policy_analysis(&env, &policy)?;
+        teye_common::policy_engine::shadow_policy(&env, &policy).map_err(Self::policy_version_error)
     }
 
-    /// Creates an ACL group.
-    pub fn create_acl_group(
+    /// Activates a policy's staged version now.
+    /// Requires SystemAdmin permission or admin tier.
+    pub fn promote_policy(
         env: Env,
         caller: Address,
-        group_name: String,
-        permissions: Vec<Permission>,
-    ) -> Result<(), ContractError> {
-        circuit_breaker::require_not_paused(&env, &circuit_breaker::PauseScope::Global)?;
+        name: String,
+    ) -> Result<teye_common::policy_dsl::PolicyId, ContractError> {
         caller.require_auth();
-        if !rbac::has_permission(&env, &caller, &Permission::ManageUsers) {
-            return Self::unauthorized(&env, &caller, "create_acl_group", "permission:ManageUsers");
+        if !Self::has_admin_access(&env, &caller, &AdminTier::ContractAdmin) {
+            return Self::unauthorized(&env, &caller, "promote_policy", "admin_tier:ContractAdmin");
         }
-        rbac::create_group(&env, group_name, permissions);
-        Ok(())
+        teye_common::policy_engine::promote_policy(&env, &name).map_err(Self::policy_version_error)
     }
 
-    /// Adds a user to an ACL group.
-    pub fn add_user_to_group(
+    /// Drops a policy's staged version without activating it.
+    /// Requires SystemAdmin permission or admin tier.
+    pub fn cancel_policy_rollout(
         env: Env,
         caller: Address,
-        user: Address,
-        group_name: String,
+        name: String,
     ) -> Result<(), ContractError> {
-        circuit_breaker::require_not_paused(&env, &circuit_breaker::PauseScope::Global)?;
         caller.require_auth();
-        if !rbac::has_permission(&env, &caller, &Permission::ManageUsers) {
+        if !Self::has_admin_access(&env, &caller, &AdminTier::ContractAdmin) {
             return Self::unauthorized(
                 &env,
                 &caller,
-                "add_user_to_group",
-                "permission:ManageUsers",
+                "cancel_policy_rollout",
+                "admin_tier:ContractAdmin",
             );
         }
-        rbac::add_to_group(&env, user, group_name).map_err(|_| ContractError::InvalidInput)
+        teye_common::policy_engine::cancel_rollout(&env, &name).map_err(Self::policy_version_error)
     }
 
-    /// Removes a user from an ACL group.
-    pub fn remove_user_from_group(
+    /// Makes a recorded version the active version of its policy and cancels
+    /// any staged rollout. Requires SystemAdmin permission or admin tier.
+    pub fn rollback_policy(
         env: Env,
         caller: Address,
-        user: Address,
-        group_name: String,
+        name: String,
+        version: u32,
     ) -> Result<(), ContractError> {
-        circuit_breaker::require_not_paused(&env, &circuit_breaker::PauseScope::Global)?;
         caller.require_auth();
-        if !rbac::has_permission(&env, &caller, &Permission::ManageUsers) {
+        if !Self::has_admin_access(&env, &caller, &AdminTier::ContractAdmin) {
             return Self::unauthorized(
                 &env,
                 &caller,
-                "remove_user_from_group",
-                "permission:ManageUsers",
+                "rollback_policy",
+                "admin_tier:ContractAdmin",
             );
         }
-        rbac::remove_from_group(&env, user, group_name);
-        Ok(())
+        teye_common::policy_engine::rollback_policy(&env, &name, version)
+            .map_err(Self::policy_version_error)
     }
 
-    /// Returns all ACL groups assigned to a user.
-    pub fn get_user_groups(env: Env, user: Address) -> Vec<String> {
-        env.storage()
-            .persistent()
-            .get(&rbac::user_groups_key(&user))
-            .unwrap_or(Vec::new(&env))
+    /// Activates every scheduled policy version whose time has come and
+    /// returns how many were activated. Anyone may call this; evaluation
+    /// already uses due versions, this only persists them.
+    pub fn apply_policy_rollouts(env: Env) -> u32 {
+        teye_common::policy_engine::apply_due_rollouts(&env)
     }
 
-    /// Checks if a user has a specific permission.
-    /// Returns true if the user has the permission, false otherwise.
-    pub fn check_permission(env: Env, user: Address, permission: Permission) -> bool {
-        rbac::has_permission(&env, &user, &permission)
+    /// Returns every recorded version of a policy, oldest first.
+    pub fn get_policy_history(env: Env, name: String) -> Vec<u32> {
+        teye_common::policy_engine::policy_history(&env, &name)
     }
 
-    // ======================== Admin Tier Management ========================
+    /// Returns the staged rollout for a policy, if any.
+    pub fn get_policy_rollout(
+        env: Env,
+        name: String,
+    ) -> Option<teye_common::policy_engine::PolicyRollout> {
+        teye_common::policy_engine::get_rollout(&env, &name)
+    }
 
-    /// Promotes or assigns a target address to the specified admin tier.
-    ///
-    /// Only a `SuperAdmin` may call this.
-    pub fn promote_admin(
+    /// Returns the requests on which a shadow version disagreed with the
+    /// active version, oldest first.
+    pub fn get_shadow_divergences(
         env: Env,
-        caller: Address,
-        target: Address,
-        tier: AdminTier,
-    ) -> Result<(), ContractError> {
-        caller.require_auth();
-        if !admin_tiers::promote_admin(&env, &caller, &target, tier) {
-            return Self::unauthorized(&env, &caller, "promote_admin", "admin_tier:SuperAdmin");
-        }
-        admin_tiers::track_admin(&env, &target);
-        Ok(())
+        name: String,
+    ) -> Vec<teye_common::policy_engine::ShadowDivergence> {
+        teye_common::policy_engine::shadow_divergences(&env, &name)
     }
 
-    /// Removes the admin tier from the target address entirely.
-    ///
-    /// Only a `SuperAdmin` may call this.
-    pub fn demote_admin(env: Env, caller: Address, target: Address) -> Result<(), ContractError> {
-        caller.require_auth();
-        if !admin_tiers::demote_admin(&env, &caller, &target) {
-            return Self::unauthorized(&env, &caller, "demote_admin", "admin_tier:SuperAdmin");
+    fn check_policy_analysis(
+        env: &Env,
+        policy: &teye_common::policy_dsl::PolicyDefinition,
+    ) -> Result<(), ContractError> {
+        if teye_common::policy_engine::is_analysis_required(env)
+            && !teye_common::policy_engine::analyze_candidate(env, policy).is_empty()
+        {
+            return Err(ContractError::PolicyAnalysisFailed);
         }
-        admin_tiers::untrack_admin(&env, &target);
         Ok(())
     }
 
-    /// Returns the admin tier of the given address, if any.
-    pub fn get_admin_tier(env: Env, admin: Address) -> Option<AdminTier> {
-        admin_tiers::get_admin_tier(&env, &admin)
+    fn policy_version_error(err: teye_common::policy_engine::PolicyVersionError) -> ContractError {
+        use teye_common::policy_engine::PolicyVersionError;
+        match err {
+            PolicyVersionError::NoActiveVersion | PolicyVersionError::VersionNotFound => {
+                ContractError::PolicyVersionNotFound
+            }
+            PolicyVersionError::VersionExists => ContractError::DuplicateRecord,
+            PolicyVersionError::NoPendingRollout => ContractError::PolicyRolloutNotFound,
+            PolicyVersionError::InvalidActivationTime => ContractError::InvalidTimestamp,
+        }
     }
 
-    // ======================== Policy Engine Management ========================
-
-    /// Stores a composable policy definition on-chain.
+    /// Turns the static analysis check in `store_policy` on or off.
     /// Requires SystemAdmin permission or admin tier.
-    pub fn store_policy(
+    pub fn set_policy_analysis(
         env: Env,
         caller: Address,
-        policy: teye_common::policy_dsl::PolicyDefinition,
+        required: bool,
     ) -> Result<(), ContractError> {
         caller.require_auth();
         if !Self::has_admin_access(&env, &caller, &AdminTier::ContractAdmin) {
-            return Self::unauthorized(&env, &caller, "store_policy", "admin_tier:ContractAdmin");
+            return Self::unauthorized(
+                &env,
+                &caller,
+                "set_policy_analysis",
+                "admin_tier:ContractAdmin",
+            );
         }
-        teye_common::policy_engine::store_policy(&env, &policy);
+        teye_common::policy_engine::set_analysis_required(&env, required);
         Ok(())
     }
 
+    /// Returns whether `store_policy` runs the static analysis check.
+    pub fn is_policy_analysis_required(env: Env) -> bool {
+        teye_common::policy_engine::is_analysis_required(&env)
+    }
+
+    /// Runs static analysis on a policy against the stored policies, as if it
+    /// replaced any stored policy with the same name. Nothing is stored.
+    pub fn analyze_policy(
+        env: Env,
+        policy: teye_common::policy_dsl::PolicyDefinition,
+    ) -> Vec<teye_common::policy_analyzer::PolicyFinding> {
+        teye_common::policy_engine::analyze_candidate(&env, &policy)
+    }
+
     /// Removes a composable policy definition from on-chain storage.
     /// Requires SystemAdmin permission or admin tier.
     pub fn remove_policy(
@@ -2301,9 +6572,38 @@ impl VisionRecordsContract {
         Ok(())
     }
 
-    /// Lists all registered policy identifiers.
+    /// Lists the first `MAX_PAGE_LIMIT` registered policy identifiers.
+    ///
+    /// Deprecated: use `list_policies_page`.
     pub fn list_policies(env: Env) -> Vec<teye_common::policy_dsl::PolicyId> {
-        teye_common::policy_engine::list_policies(&env)
+        Self::page_policies(&env, None, pagination::MAX_PAGE_LIMIT).policies
+    }
+
+    /// Page through the registered policy identifiers in registration order.
+    pub fn list_policies_page(
+        env: Env,
+        cursor: Option<u64>,
+        limit: u32,
+    ) -> Result<PolicyPage, ContractError> {
+        pagination::validate_limit(limit)?;
+        Ok(Self::page_policies(&env, cursor, limit))
+    }
+
+    fn page_policies(env: &Env, cursor: Option<u64>, limit: u32) -> PolicyPage {
+        let ids = teye_common::policy_engine::list_policies(env);
+        let (policies, next_cursor) = pagination::page_positions(
+            env,
+            u64::from(ids.len()),
+            cursor,
+            limit,
+            false,
+            |pos| ids.get(pos as u32),
+            |_| true,
+        );
+        PolicyPage {
+            policies,
+            next_cursor,
+        }
     }
 
     /// Sets the conflict resolution strategy used by the policy engine.
@@ -2326,7 +6626,8 @@ impl VisionRecordsContract {
         Ok(())
     }
 
-    /// Evaluates the composable policy engine for the calling user.
+    /// Evaluates the composable policy engine for the calling user, who must
+    /// authorize since shadow versions log the request when they disagree.
     /// Returns true if the policy engine permits the action.
     pub fn evaluate_policy_engine(
         env: Env,
@@ -2334,19 +6635,42 @@ impl VisionRecordsContract {
         action: String,
         resource_id: Option<u64>,
     ) -> bool {
+        caller.require_auth();
         let action_str: alloc::string::String = action.to_string();
         rbac::check_policy_engine(&env, &caller, &action_str, resource_id)
     }
 
-    /// Runs a what-if policy simulation without applying changes.
+    /// Runs a what-if policy simulation for `subject` without applying
+    /// changes. `caller` must authorize and may simulate for themselves.
+    /// Simulating for another user, or with `explain` set to get the
+    /// evaluation trace, requires ContractAdmin.
     pub fn simulate_policy(
         env: Env,
         caller: Address,
+        subject: Address,
         action: String,
         resource_id: Option<u64>,
-    ) -> teye_common::policy_dsl::SimulationResult {
+        explain: bool,
+    ) -> Result<teye_common::policy_dsl::SimulationResult, ContractError> {
+        caller.require_auth();
+        if (explain || subject != caller)
+            && !Self::has_admin_access(&env, &caller, &AdminTier::ContractAdmin)
+        {
+            return Self::unauthorized(
+                &env,
+                &caller,
+                "simulate_policy",
+                "admin_tier:ContractAdmin",
+            );
+        }
         let action_str: alloc::string::String = action.to_string();
-        rbac::simulate_policy_check(&env, &caller, &action_str, resource_id)
+        Ok(rbac::simulate_policy_check(
+            &env,
+            &subject,
+            &action_str,
+            resource_id,
+            explain,
+        ))
     }
 
     // ======================== Internal Helpers ========================
@@ -2443,11 +6767,24 @@ fn export_internal_record_id(record_id: &BytesN<32>) -> u64 {
 ///
 /// Returns `Ok(VisionRecord)` when the caller is authorised, or a
 /// `ContractError` otherwise.  Mirrors the rule-set from
-/// [`VisionRecordsContract::get_record`] without duplicating audit logging.
+/// [`VisionRecordsContract::get_record`], including access policies, but
+/// only a policy denial is audited.
 fn export_check_access(
     env: &Env,
     caller: &Address,
     rid: u64,
+) -> Result<VisionRecord, ContractError> {
+    let record = export_check_grants(env, caller, rid)?;
+    // Access policies bind everyone but the patient, as on `get_record`.
+    VisionRecordsContract::enforce_read_policies(env, caller, &record)?;
+    Ok(record)
+}
+
+/// The grant, consent and permission half of [`export_check_access`].
+fn export_check_grants(
+    env: &Env,
+    caller: &Address,
+    rid: u64,
 ) -> Result<VisionRecord, ContractError> {
     let key = (symbol_short!("RECORD"), rid);
     let record: VisionRecord = env
@@ -2468,26 +6805,20 @@ fn export_check_access(
         return Ok(record);
     }
 
-    // Active patient-to-caller consent grant.
-    if has_active_consent(env, &record.patient, caller) {
+    // Active patient-to-caller consent to share records of this type.
+    let scope = consent_scope(
+        env,
+        &record.patient,
+        caller,
+        &ConsentType::Sharing,
+        &record.record_type,
+    );
+    if scope == ConsentScope::Covered {
         return Ok(record);
     }
 
-    // Patient-level AccessGrant (symbol_short!("ACCESS"), patient, grantee).
-    let patient_grant_key = (
-        symbol_short!("ACCESS"),
-        record.patient.clone(),
-        caller.clone(),
-    );
-    if let Some(grant) = env
-        .storage()
-        .persistent()
-        .get::<_, AccessGrant>(&patient_grant_key)
-    {
-        if grant.level != AccessLevel::None && grant.expires_at > env.ledger().timestamp() {
-            return Ok(record);
-        }
-    }
+    // Patient-level grants only count alongside consent (see
+    // `check_access`), so they add nothing to the check above.
 
     // Record-level AccessGrant (symbol_short!("REC_ACC"), record_id, grantee).
     let rec_grant_key = (symbol_short!("REC_ACC"), rid, caller.clone());
@@ -2501,7 +6832,11 @@ fn export_check_access(
         }
     }
 
-    Err(ContractError::Unauthorized)
+    if scope == ConsentScope::Absent {
+        Err(ContractError::Unauthorized)
+    } else {
+        Err(ContractError::ConsentPurposeMismatch)
+    }
 }
 
 /// Prepares a vision record for cross-chain export.
@@ -2520,12 +6855,15 @@ fn export_check_access(
 /// # Access control
 /// `caller.require_auth()` is enforced unconditionally.  The caller must then
 /// be the record's `patient`, the record's `provider`, hold the
-/// `ReadAnyRecord` or `SystemAdmin` RBAC permission, or possess a valid
-/// consent / access grant – exactly as [`VisionRecordsContract::get_record`].
+/// `ReadAnyRecord` or `SystemAdmin` RBAC permission, hold a record-level
+/// access grant, or have consent for `ConsentType::Sharing` covering the
+/// record's type.
 ///
 /// # Errors
 /// * [`ContractError::RecordNotFound`] – no record with that ID exists.
 /// * [`ContractError::Unauthorized`]  – caller lacks read permission.
+/// * [`ContractError::ConsentPurposeMismatch`] – caller's consent does not
+///   cover sharing this record.
 pub fn prepare_record_for_export(
     env: &Env,
     caller: &Address,
@@ -2557,12 +6895,12 @@ pub fn prepare_record_for_export(
     ));
     // RecordType discriminant serialised as a 4-byte big-endian value
     let rec_type_disc: u32 = match record.record_type {
-        RecordType::Examination  => 0,
+        RecordType::Examination => 0,
         RecordType::Prescription => 1,
-        RecordType::Diagnosis    => 2,
-        RecordType::Treatment    => 3,
-        RecordType::Surgery      => 4,
-        RecordType::LabResult    => 5,
+        RecordType::Diagnosis => 2,
+        RecordType::Treatment => 3,
+        RecordType::Surgery => 4,
+        RecordType::LabResult => 5,
     };
     fields.push_back((
         symbol_short!("rec_type"),
@@ -2581,11 +6919,25 @@ pub fn prepare_record_for_export(
     if let Some(exam) = examination::get_examination(env, rid) {
         fields.push_back((
             symbol_short!("va_l"),
-            Bytes::from_slice(env, exam.visual_acuity.uncorrected.left_eye.to_string().as_bytes()),
+            Bytes::from_slice(
+                env,
+                exam.visual_acuity
+                    .uncorrected
+                    .left_eye
+                    .to_string()
+                    .as_bytes(),
+            ),
         ));
         fields.push_back((
             symbol_short!("va_r"),
-            Bytes::from_slice(env, exam.visual_acuity.uncorrected.right_eye.to_string().as_bytes()),
+            Bytes::from_slice(
+                env,
+                exam.visual_acuity
+                    .uncorrected
+                    .right_eye
+                    .to_string()
+                    .as_bytes(),
+            ),
         ));
         fields.push_back((
             symbol_short!("iop_l"),
@@ -2686,3 +7038,69 @@ mod test_admin_tiers;
 
 #[cfg(test)]
 mod test_occ;
+
+#[cfg(test)]
+mod test_appointment;
+
+#[cfg(test)]
+mod test_prescription;
+
+#[cfg(test)]
+mod test_emergency;
+
+#[cfg(test)]
+mod test_provider;
+
+#[cfg(test)]
+mod test_support;
+
+#[cfg(test)]
+mod test_pagination;
+
+#[cfg(test)]
+mod test_amendment;
+
+#[cfg(test)]
+mod test_access_report;
+
+#[cfg(test)]
+mod test_envelope;
+
+#[cfg(test)]
+mod test_encryption;
+
+#[cfg(test)]
+mod test_examination;
+
+#[cfg(test)]
+mod test_rate_limit;
+
+#[cfg(test)]
+mod test_guardianship;
+
+#[cfg(test)]
+mod test_consent;
+
+#[cfg(test)]
+mod test_attachment;
+
+#[cfg(test)]
+mod test_import;
+
+#[cfg(test)]
+mod test_access_policy;
+
+#[cfg(test)]
+mod test_share;
+
+#[cfg(test)]
+mod test_referral;
+
+#[cfg(test)]
+mod test_policy_analysis;
+
+#[cfg(test)]
+mod test_policy_explain;
+
+#[cfg(test)]
+mod test_policy_versioning;
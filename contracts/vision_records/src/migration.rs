@@ -0,0 +1,311 @@
+//! Bulk onboarding of clinics migrating from legacy EHR systems.
+//!
+//! An admin opens an import job for a legacy source system and submits its
+//! users, patient profiles and historical records in numbered chunks. Each
+//! chunk is applied once: its report is stored and re-submitting the chunk
+//! returns that report instead of importing it again, so a migration script
+//! can resume after any failure by replaying from the first chunk without a
+//! report. Items inside a chunk fail individually, and records are
+//! deduplicated by their legacy id across jobs for the same source.
+//!
+//! A chunk's size is bounded by the ledger entries its items write, which
+//! must stay within the per-transaction write limit, and the hash of its
+//! payload is stored with the report so a replay with different contents
+//! is rejected rather than answered with the old report.
+
+use crate::rbac::Role;
+use crate::RecordType;
+use soroban_sdk::{
+    contracttype, symbol_short, xdr::ToXdr, Address, BytesN, Env, String, Symbol, Vec,
+};
+
+// ── Storage keys ──────────────────────────────────────────────
+const IMP_CTR: Symbol = symbol_short!("IMP_CTR");
+const IMP_JOB: Symbol = symbol_short!("IMP_JOB");
+const IMP_CHK: Symbol = symbol_short!("IMP_CHK");
+const IMP_SRC: Symbol = symbol_short!("IMP_SRC");
+const IMP_REC: Symbol = symbol_short!("IMP_REC");
+const IMP_HSH: Symbol = symbol_short!("IMP_HSH");
+
+const TTL_THRESHOLD: u32 = 5184000;
+const TTL_EXTEND_TO: u32 = 10368000;
+
+/// Ledger entries written per imported user: the user and its role.
+pub const USER_WRITES: u32 = 2;
+/// Ledger entries written per imported profile.
+pub const PROFILE_WRITES: u32 = 1;
+/// Ledger entries written per imported record: the record, its version
+/// stamp and clock, its key envelope, two entries in each of the four
+/// record indexes, and its provenance and legacy-id entries.
+pub const RECORD_WRITES: u32 = 14;
+/// Ledger entries written once per chunk: the report, its payload hash and
+/// the job.
+pub const CHUNK_WRITES: u32 = 3;
+/// Upper bound on the ledger entries one chunk writes.
+pub const MAX_CHUNK_WRITES: u32 = 50;
+
+// ── Types ─────────────────────────────────────────────────────
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[repr(u32)]
+pub enum ImportStatus {
+    Open = 1,
+    Completed = 2,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ImportJob {
+    pub id: u64,
+    /// Name of the legacy system; scopes legacy record ids.
+    pub source: String,
+    pub started_by: Address,
+    pub status: ImportStatus,
+    pub started_at: u64,
+    pub updated_at: u64,
+    pub chunks_applied: u32,
+    pub imported: u32,
+    pub skipped: u32,
+    pub failed: u32,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ImportUserInput {
+    pub user: Address,
+    pub role: Role,
+    pub name: String,
+    pub registered_at: u64,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ImportProfileInput {
+    pub patient: Address,
+    pub date_of_birth_hash: String,
+    pub gender_hash: String,
+    pub blood_type_hash: String,
+    pub created_at: u64,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ImportRecordInput {
+    /// The record's id in the legacy system.
+    pub legacy_id: String,
+    pub patient: Address,
+    pub provider: Address,
+    pub record_type: RecordType,
+    pub data_hash: String,
+    pub created_at: u64,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[repr(u32)]
+pub enum ImportItemKind {
+    User = 1,
+    Profile = 2,
+    Record = 3,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[repr(u32)]
+pub enum ImportOutcome {
+    Imported = 1,
+    /// Already present, e.g. from an earlier job; left unchanged.
+    Skipped = 2,
+    Failed = 3,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ImportItemResult {
+    pub kind: ImportItemKind,
+    /// Position of the item in its input list.
+    pub index: u32,
+    pub outcome: ImportOutcome,
+    /// The new or existing record id; 0 for users and profiles.
+    pub record_id: u64,
+    /// `ContractError` code when the item failed, otherwise 0.
+    pub error_code: u32,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ImportChunkReport {
+    pub job_id: u64,
+    pub chunk: u32,
+    pub applied_at: u64,
+    pub imported: u32,
+    pub skipped: u32,
+    pub failed: u32,
+    pub items: Vec<ImportItemResult>,
+}
+
+impl ImportChunkReport {
+    pub fn new(env: &Env, job_id: u64, chunk: u32) -> Self {
+        ImportChunkReport {
+            job_id,
+            chunk,
+            applied_at: env.ledger().timestamp(),
+            imported: 0,
+            skipped: 0,
+            failed: 0,
+            items: Vec::new(env),
+        }
+    }
+
+    pub fn push(
+        &mut self,
+        kind: ImportItemKind,
+        index: u32,
+        outcome: ImportOutcome,
+        record_id: u64,
+        error_code: u32,
+    ) {
+        match outcome {
+            ImportOutcome::Imported => self.imported = self.imported.saturating_add(1),
+            ImportOutcome::Skipped => self.skipped = self.skipped.saturating_add(1),
+            ImportOutcome::Failed => self.failed = self.failed.saturating_add(1),
+        }
+        self.items.push_back(ImportItemResult {
+            kind,
+            index,
+            outcome,
+            record_id,
+            error_code,
+        });
+    }
+}
+
+/// Marks a record as migrated rather than created on-chain.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ImportProvenance {
+    pub record_id: u64,
+    pub job_id: u64,
+    pub source: String,
+    pub legacy_id: String,
+    pub imported_at: u64,
+}
+
+// ── Storage ───────────────────────────────────────────────────
+
+fn set_persistent<K, V>(env: &Env, key: &K, value: &V)
+where
+    K: soroban_sdk::IntoVal<Env, soroban_sdk::Val>,
+    V: soroban_sdk::IntoVal<Env, soroban_sdk::Val>,
+{
+    env.storage().persistent().set(key, value);
+    env.storage()
+        .persistent()
+        .extend_ttl(key, TTL_THRESHOLD, TTL_EXTEND_TO);
+}
+
+/// Opens a new job for `source` and returns it.
+pub fn start_job(env: &Env, source: &String, started_by: &Address) -> ImportJob {
+    let id: u64 = env
+        .storage()
+        .instance()
+        .get(&IMP_CTR)
+        .unwrap_or(0u64)
+        .saturating_add(1);
+    env.storage().instance().set(&IMP_CTR, &id);
+
+    let now = env.ledger().timestamp();
+    let job = ImportJob {
+        id,
+        source: source.clone(),
+        started_by: started_by.clone(),
+        status: ImportStatus::Open,
+        started_at: now,
+        updated_at: now,
+        chunks_applied: 0,
+        imported: 0,
+        skipped: 0,
+        failed: 0,
+    };
+    set_job(env, &job);
+    job
+}
+
+pub fn get_job(env: &Env, job_id: u64) -> Option<ImportJob> {
+    env.storage().persistent().get(&(IMP_JOB, job_id))
+}
+
+pub fn set_job(env: &Env, job: &ImportJob) {
+    set_persistent(env, &(IMP_JOB, job.id), job);
+}
+
+pub fn get_report(env: &Env, job_id: u64, chunk: u32) -> Option<ImportChunkReport> {
+    env.storage().persistent().get(&(IMP_CHK, job_id, chunk))
+}
+
+/// Most ledger entries a chunk of this many items can write.
+pub fn chunk_writes(users: u32, profiles: u32, records: u32) -> u32 {
+    CHUNK_WRITES
+        .saturating_add(users.saturating_mul(USER_WRITES))
+        .saturating_add(profiles.saturating_mul(PROFILE_WRITES))
+        .saturating_add(records.saturating_mul(RECORD_WRITES))
+}
+
+/// SHA-256 of a chunk's payload.
+pub fn payload_hash(
+    env: &Env,
+    users: &Vec<ImportUserInput>,
+    profiles: &Vec<ImportProfileInput>,
+    records: &Vec<ImportRecordInput>,
+) -> BytesN<32> {
+    let payload = (users.clone(), profiles.clone(), records.clone()).to_xdr(env);
+    env.crypto().sha256(&payload).into()
+}
+
+/// Hash of the payload the chunk was applied with.
+pub fn get_payload_hash(env: &Env, job_id: u64, chunk: u32) -> Option<BytesN<32>> {
+    env.storage().persistent().get(&(IMP_HSH, job_id, chunk))
+}
+
+/// Stores the chunk's report and payload hash and folds its totals into
+/// the job.
+pub fn finish_chunk(
+    env: &Env,
+    job: &mut ImportJob,
+    report: &ImportChunkReport,
+    payload_hash: &BytesN<32>,
+) {
+    set_persistent(env, &(IMP_CHK, job.id, report.chunk), report);
+    set_persistent(env, &(IMP_HSH, job.id, report.chunk), payload_hash);
+    job.chunks_applied = job.chunks_applied.saturating_add(1);
+    job.imported = job.imported.saturating_add(report.imported);
+    job.skipped = job.skipped.saturating_add(report.skipped);
+    job.failed = job.failed.saturating_add(report.failed);
+    job.updated_at = env.ledger().timestamp();
+    set_job(env, job);
+}
+
+/// Record id already imported for `legacy_id` from `source`, if any.
+pub fn imported_record_id(env: &Env, source: &String, legacy_id: &String) -> Option<u64> {
+    env.storage()
+        .persistent()
+        .get(&(IMP_SRC, source.clone(), legacy_id.clone()))
+}
+
+pub fn mark_imported(env: &Env, provenance: &ImportProvenance) {
+    set_persistent(
+        env,
+        &(
+            IMP_SRC,
+            provenance.source.clone(),
+            provenance.legacy_id.clone(),
+        ),
+        &provenance.record_id,
+    );
+    set_persistent(env, &(IMP_REC, provenance.record_id), provenance);
+}
+
+pub fn get_provenance(env: &Env, record_id: u64) -> Option<ImportProvenance> {
+    env.storage().persistent().get(&(IMP_REC, record_id))
+}
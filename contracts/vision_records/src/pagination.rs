@@ -0,0 +1,319 @@
+use soroban_sdk::{contracttype, Env, IntoVal, Symbol, TryFromVal, Val, Vec};
+use teye_common::policy_dsl::PolicyId;
+
+use crate::{
+    AccessGrant, Appointment, ContractError, EmergencyAccess, Provider, RecordType, Referral,
+    ShareToken,
+};
+
+/// Largest page a list entrypoint will return.
+pub const MAX_PAGE_LIMIT: u32 = 25;
+
+/// Upper bound on the stored items examined by one page request, chosen so
+/// a page stays within the per-transaction ledger read limit. Filtered
+/// queries may return a short (or empty) page together with a cursor when
+/// this bound is reached before the page fills up.
+pub const MAX_PAGE_SCAN: u32 = 25;
+
+/// Items stored per entry of a [`BucketList`].
+pub const BUCKET_SIZE: u32 = 25;
+
+const TTL_THRESHOLD: u32 = 5184000;
+const TTL_EXTEND_TO: u32 = 10368000;
+
+/// Optional filters for record listings. An empty `record_types` matches
+/// every type. Dates apply to `created_at` and are inclusive.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RecordFilter {
+    pub record_types: Vec<RecordType>,
+    pub from: Option<u64>,
+    pub to: Option<u64>,
+}
+
+/// A page of record ids, in ascending id order unless requested newest first.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RecordPage {
+    pub record_ids: Vec<u64>,
+    /// Pass back as `cursor` to continue; `None` once the listing is exhausted.
+    pub next_cursor: Option<u64>,
+}
+
+/// A page of prescription ids.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PrescriptionPage {
+    pub prescription_ids: Vec<u64>,
+    pub next_cursor: Option<u64>,
+}
+
+/// A page of patient-level access grants in the order they were first granted.
+#[contracttype]
+#[derive(Clone, Debug)]
+pub struct GrantPage {
+    pub grants: Vec<AccessGrant>,
+    pub next_cursor: Option<u64>,
+}
+
+/// A page of appointments.
+#[contracttype]
+#[derive(Clone, Debug)]
+pub struct AppointmentPage {
+    pub appointments: Vec<Appointment>,
+    pub next_cursor: Option<u64>,
+}
+
+/// A page of provider profiles.
+#[contracttype]
+#[derive(Clone, Debug)]
+pub struct ProviderPage {
+    pub providers: Vec<Provider>,
+    pub next_cursor: Option<u64>,
+}
+
+/// A page of emergency access grants.
+#[contracttype]
+#[derive(Clone, Debug)]
+pub struct EmergencyAccessPage {
+    pub accesses: Vec<EmergencyAccess>,
+    pub next_cursor: Option<u64>,
+}
+
+/// A page of referrals.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ReferralPage {
+    pub referrals: Vec<Referral>,
+    pub next_cursor: Option<u64>,
+}
+
+/// A page of share tokens.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ShareTokenPage {
+    pub tokens: Vec<ShareToken>,
+    pub next_cursor: Option<u64>,
+}
+
+/// A page of registered policy identifiers.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PolicyPage {
+    pub policies: Vec<PolicyId>,
+    pub next_cursor: Option<u64>,
+}
+
+/// Reject page sizes outside `1..=MAX_PAGE_LIMIT`.
+pub fn validate_limit(limit: u32) -> Result<(), ContractError> {
+    if limit == 0 || limit > MAX_PAGE_LIMIT {
+        return Err(ContractError::InvalidInput);
+    }
+    Ok(())
+}
+
+/// Reject inverted date ranges.
+pub fn validate_range(from: Option<u64>, to: Option<u64>) -> Result<(), ContractError> {
+    match (from, to) {
+        (Some(from), Some(to)) if from > to => Err(ContractError::InvalidInput),
+        _ => Ok(()),
+    }
+}
+
+/// True if `timestamp` falls within the optional inclusive range.
+pub fn in_range(timestamp: u64, from: Option<u64>, to: Option<u64>) -> bool {
+    from.map_or(true, |from| timestamp >= from) && to.map_or(true, |to| timestamp <= to)
+}
+
+/// Page through `ids`, oldest first or newest first, keeping the ids `keep`
+/// accepts. `cursor` counts the ids already examined from the starting end.
+/// At most `MAX_PAGE_SCAN` ids are examined per page.
+pub fn page_ids(
+    env: &Env,
+    ids: &Vec<u64>,
+    cursor: Option<u64>,
+    limit: u32,
+    newest_first: bool,
+    mut keep: impl FnMut(u64) -> bool,
+) -> RecordPage {
+    let (record_ids, next_cursor) = page_positions(
+        env,
+        u64::from(ids.len()),
+        cursor,
+        limit,
+        newest_first,
+        |pos| ids.get(pos as u32),
+        |id| keep(*id),
+    );
+    RecordPage {
+        record_ids,
+        next_cursor,
+    }
+}
+
+/// Page through the positions `0..total` of a list, fetching each item with
+/// `fetch` and keeping the items `keep` accepts. `cursor` counts the
+/// positions already examined from the starting end. At most
+/// `MAX_PAGE_SCAN` positions are examined per page. Returns the page and the
+/// cursor for the next one, `None` once the list is exhausted.
+pub fn page_positions<T>(
+    env: &Env,
+    total: u64,
+    cursor: Option<u64>,
+    limit: u32,
+    newest_first: bool,
+    mut fetch: impl FnMut(u64) -> Option<T>,
+    mut keep: impl FnMut(&T) -> bool,
+) -> (Vec<T>, Option<u64>)
+where
+    T: IntoVal<Env, Val> + TryFromVal<Env, Val>,
+{
+    let mut items = Vec::new(env);
+    let mut pos = cursor.unwrap_or(0);
+    let scan_end = pos.saturating_add(u64::from(MAX_PAGE_SCAN));
+
+    while pos < total && pos < scan_end && items.len() < limit {
+        let index = if newest_first { total - 1 - pos } else { pos };
+        pos += 1;
+        if let Some(item) = fetch(index) {
+            if keep(&item) {
+                items.push_back(item);
+            }
+        }
+    }
+
+    (items, if pos < total { Some(pos) } else { None })
+}
+
+/// An append-only list stored in entries of `BUCKET_SIZE` items under
+/// `(prefix, owner, bucket)`, with its length under `(prefix, owner)`.
+/// Appending touches two entries and a page of at most `MAX_PAGE_SCAN`
+/// items touches at most two buckets, however long the list grows.
+pub struct BucketList<K> {
+    prefix: Symbol,
+    owner: K,
+}
+
+impl<K> BucketList<K>
+where
+    K: Clone,
+    Val: TryFromVal<Env, K>,
+{
+    pub fn new(prefix: Symbol, owner: K) -> Self {
+        Self { prefix, owner }
+    }
+
+    fn len_key(&self) -> (Symbol, K) {
+        (self.prefix.clone(), self.owner.clone())
+    }
+
+    fn bucket_key(&self, bucket: u64) -> (Symbol, K, u64) {
+        (self.prefix.clone(), self.owner.clone(), bucket)
+    }
+
+    /// Number of items appended so far.
+    pub fn len(&self, env: &Env) -> u64 {
+        env.storage().persistent().get(&self.len_key()).unwrap_or(0)
+    }
+
+    pub fn is_empty(&self, env: &Env) -> bool {
+        self.len(env) == 0
+    }
+
+    /// Appends `item` at the end of the list.
+    pub fn push<T>(&self, env: &Env, item: &T)
+    where
+        T: IntoVal<Env, Val> + TryFromVal<Env, Val> + Clone,
+    {
+        let len = self.len(env);
+        let key = self.bucket_key(len / u64::from(BUCKET_SIZE));
+        let mut bucket: Vec<T> = env
+            .storage()
+            .persistent()
+            .get(&key)
+            .unwrap_or(Vec::new(env));
+        bucket.push_back(item.clone());
+        env.storage().persistent().set(&key, &bucket);
+        env.storage()
+            .persistent()
+            .extend_ttl(&key, TTL_THRESHOLD, TTL_EXTEND_TO);
+
+        let len_key = self.len_key();
+        env.storage().persistent().set(&len_key, &(len + 1));
+        env.storage()
+            .persistent()
+            .extend_ttl(&len_key, TTL_THRESHOLD, TTL_EXTEND_TO);
+    }
+
+    /// Returns a fetch function for [`page_positions`] that loads each
+    /// bucket once while the page stays inside it.
+    pub fn reader<'a, T>(&'a self, env: &'a Env) -> impl FnMut(u64) -> Option<T> + 'a
+    where
+        T: IntoVal<Env, Val> + TryFromVal<Env, Val> + 'a,
+    {
+        let mut cached: Option<(u64, Vec<T>)> = None;
+        move |pos| {
+            let bucket = pos / u64::from(BUCKET_SIZE);
+            if cached.as_ref().map(|(index, _)| *index) != Some(bucket) {
+                let items: Vec<T> = env
+                    .storage()
+                    .persistent()
+                    .get(&self.bucket_key(bucket))
+                    .unwrap_or(Vec::new(env));
+                cached = Some((bucket, items));
+            }
+            let offset = (pos % u64::from(BUCKET_SIZE)) as u32;
+            cached.as_ref().and_then(|(_, items)| items.get(offset))
+        }
+    }
+
+    /// Page through a list of keys, loading the item each key refers to
+    /// with `load`; see [`page_positions`].
+    pub fn page_map<I, T>(
+        &self,
+        env: &Env,
+        cursor: Option<u64>,
+        limit: u32,
+        newest_first: bool,
+        mut load: impl FnMut(I) -> Option<T>,
+        keep: impl FnMut(&T) -> bool,
+    ) -> (Vec<T>, Option<u64>)
+    where
+        I: IntoVal<Env, Val> + TryFromVal<Env, Val>,
+        T: IntoVal<Env, Val> + TryFromVal<Env, Val>,
+    {
+        let mut read = self.reader::<I>(env);
+        page_positions(
+            env,
+            self.len(env),
+            cursor,
+            limit,
+            newest_first,
+            |pos| read(pos).and_then(&mut load),
+            keep,
+        )
+    }
+
+    /// Page through the list; see [`page_positions`].
+    pub fn page<T>(
+        &self,
+        env: &Env,
+        cursor: Option<u64>,
+        limit: u32,
+        newest_first: bool,
+        keep: impl FnMut(&T) -> bool,
+    ) -> (Vec<T>, Option<u64>)
+    where
+        T: IntoVal<Env, Val> + TryFromVal<Env, Val>,
+    {
+        page_positions(
+            env,
+            self.len(env),
+            cursor,
+            limit,
+            newest_first,
+            self.reader(env),
+            keep,
+        )
+    }
+}
@@ -58,4 +58,10 @@ pub struct PatientProfile {
 
     // Medical history references (IPFS hashes or record IDs)
     pub medical_history_refs: Vec<String>,
+
+    // Guardianship: when the patient comes of age (0 if never recorded; a
+    // past date marks an adult) and who currently holds a guardian or proxy
+    // relationship.
+    pub majority_at: u64,
+    pub guardians: Vec<Address>,
 }
@@ -1,6 +1,24 @@
-use soroban_sdk::{contracttype, Address, Env, String, Vec};
+use soroban_sdk::{contracttype, symbol_short, Address, Env, String, Symbol, Vec};
 use teye_common::concurrency::{self, FieldChange, UpdateOutcome, VersionStamp};
 
+use crate::pagination::BucketList;
+use crate::validation::parse_hundredths;
+use crate::ContractError;
+
+const RX_CTR: Symbol = symbol_short!("RX_CTR");
+
+// Accepted clinical ranges, in hundredths of the unit (dioptres, degrees, mm).
+const SPHERE_RANGE: (i64, i64) = (-2500, 2500);
+const CYLINDER_RANGE: (i64, i64) = (-1000, 1000);
+const AXIS_RANGE: (i64, i64) = (0, 18000);
+const ADD_RANGE: (i64, i64) = (0, 400);
+const PD_RANGE: (i64, i64) = (4000, 8000);
+const BASE_CURVE_RANGE: (i64, i64) = (700, 1000);
+const DIAMETER_RANGE: (i64, i64) = (1200, 1600);
+/// Lens powers are ground in quarter-dioptre steps.
+const DIOPTRE_STEP: i64 = 25;
+const MAX_BRAND_LEN: u32 = 64;
+
 #[contracttype]
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub enum LensType {
@@ -34,6 +52,18 @@ pub enum OptionalContactLensData {
     Some(ContactLensData),
 }
 
+/// Lifecycle of a prescription once issued.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum PrescriptionStatus {
+    /// Issued and fillable (subject to expiry).
+    Active,
+    /// Filled by an optical retailer; cannot be filled again.
+    Dispensed,
+    /// Withdrawn by the issuing provider or an admin.
+    Revoked,
+}
+
 #[contracttype]
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct Prescription {
@@ -48,24 +78,84 @@ pub struct Prescription {
     pub expires_at: u64,
     pub verified: bool,
     pub metadata_hash: String,
+    pub status: PrescriptionStatus,
+    pub verified_by: Option<Address>,
+    pub verified_at: Option<u64>,
+    pub dispensed_by: Option<Address>,
+    pub dispensed_at: Option<u64>,
+}
+
+impl Prescription {
+    pub fn is_expired(&self, now: u64) -> bool {
+        now >= self.expires_at
+    }
+}
+
+fn check_range(value: &String, (min, max): (i64, i64), step: i64) -> Result<(), ContractError> {
+    match parse_hundredths(value) {
+        Some(v) if v >= min && v <= max && v % step == 0 => Ok(()),
+        _ => Err(ContractError::InvalidPrescription),
+    }
+}
+
+/// Validate one eye's refraction against accepted clinical ranges.
+///
+/// SPH −25.00..+25.00 D, CYL −10.00..+10.00 D and ADD 0.00..+4.00 D in
+/// 0.25 D steps; AXIS 0..180 whole degrees; PD 40..80 mm.
+pub fn validate_eye_data(data: &PrescriptionData) -> Result<(), ContractError> {
+    check_range(&data.sphere, SPHERE_RANGE, DIOPTRE_STEP)?;
+    check_range(&data.cylinder, CYLINDER_RANGE, DIOPTRE_STEP)?;
+    check_range(&data.axis, AXIS_RANGE, 100)?;
+    check_range(&data.add, ADD_RANGE, DIOPTRE_STEP)?;
+    check_range(&data.pd, PD_RANGE, 1)
+}
+
+/// Validate contact lens fitting parameters.
+///
+/// Base curve 7.00..10.00 mm, diameter 12.00..16.00 mm and a non-empty brand.
+pub fn validate_contact_data(data: &ContactLensData) -> Result<(), ContractError> {
+    check_range(&data.base_curve, BASE_CURVE_RANGE, 1)?;
+    check_range(&data.diameter, DIAMETER_RANGE, 1)?;
+    if data.brand.is_empty() || data.brand.len() > MAX_BRAND_LEN {
+        return Err(ContractError::InvalidPrescription);
+    }
+    Ok(())
+}
+
+/// Validate both eyes and the lens-type specific data of a prescription.
+/// Contact lens prescriptions require fitting data; glasses must not carry it.
+pub fn validate_prescription(
+    lens_type: &LensType,
+    left_eye: &PrescriptionData,
+    right_eye: &PrescriptionData,
+    contact_data: &OptionalContactLensData,
+) -> Result<(), ContractError> {
+    validate_eye_data(left_eye)?;
+    validate_eye_data(right_eye)?;
+    match (lens_type, contact_data) {
+        (LensType::ContactLens, OptionalContactLensData::Some(data)) => validate_contact_data(data),
+        (LensType::Glasses, OptionalContactLensData::None) => Ok(()),
+        _ => Err(ContractError::InvalidPrescription),
+    }
+}
+
+pub fn increment_prescription_counter(env: &Env) -> u64 {
+    let current: u64 = env.storage().instance().get(&RX_CTR).unwrap_or(0);
+    let next = current + 1;
+    env.storage().instance().set(&RX_CTR, &next);
+    next
 }
 
 pub fn save_prescription(env: &Env, prescription: &Prescription) {
     let key = (soroban_sdk::symbol_short!("RX"), prescription.id);
+    let is_new = !env.storage().persistent().has(&key);
     env.storage().persistent().set(&key, prescription);
+    if !is_new {
+        return;
+    }
 
     // Track patient history
-    let history_key = (
-        soroban_sdk::symbol_short!("RX_HIST"),
-        prescription.patient.clone(),
-    );
-    let mut history: Vec<u64> = env
-        .storage()
-        .persistent()
-        .get(&history_key)
-        .unwrap_or(Vec::new(env));
-    history.push_back(prescription.id);
-    env.storage().persistent().set(&history_key, &history);
+    history_list(&prescription.patient).push(env, &prescription.id);
 }
 
 pub fn get_prescription(env: &Env, id: u64) -> Option<Prescription> {
@@ -73,20 +163,29 @@ pub fn get_prescription(env: &Env, id: u64) -> Option<Prescription> {
     env.storage().persistent().get(&key)
 }
 
-pub fn get_patient_history(env: &Env, patient: Address) -> Vec<u64> {
-    let history_key = (soroban_sdk::symbol_short!("RX_HIST"), patient);
-    env.storage()
-        .persistent()
-        .get(&history_key)
-        .unwrap_or(Vec::new(env))
+fn history_list(patient: &Address) -> BucketList<Address> {
+    BucketList::new(symbol_short!("RX_HIST"), patient.clone())
+}
+
+/// Page through the ids of a patient's prescriptions in issue order.
+pub fn page_patient_history(
+    env: &Env,
+    patient: &Address,
+    cursor: Option<u64>,
+    limit: u32,
+    newest_first: bool,
+) -> (Vec<u64>, Option<u64>) {
+    history_list(patient).page(env, cursor, limit, newest_first, |_| true)
 }
 
+/// Marks a prescription as verified by `verifier`.
+/// Authorization and status checks are the caller's responsibility.
 pub fn verify_prescription(env: &Env, id: u64, verifier: Address) -> bool {
     if let Some(mut rx) = get_prescription(env, id) {
-        verifier.require_auth();
         rx.verified = true;
-        let key = (soroban_sdk::symbol_short!("RX"), id);
-        env.storage().persistent().set(&key, &rx);
+        rx.verified_by = Some(verifier);
+        rx.verified_at = Some(env.ledger().timestamp());
+        save_prescription(env, &rx);
         return true;
     }
     false
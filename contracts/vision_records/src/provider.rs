@@ -1,6 +1,8 @@
 #![allow(clippy::arithmetic_side_effects)]
 use soroban_sdk::{contracttype, symbol_short, Address, Env, String, Vec};
 
+use crate::pagination::BucketList;
+
 const TTL_THRESHOLD: u32 = 5184000;
 const TTL_EXTEND_TO: u32 = 10368000;
 
@@ -10,19 +12,13 @@ fn extend_ttl(env: &Env, key: &(soroban_sdk::Symbol, Address)) {
         .extend_ttl(key, TTL_THRESHOLD, TTL_EXTEND_TO);
 }
 
-fn extend_ttl_string_key(env: &Env, key: &(soroban_sdk::Symbol, String)) {
-    env.storage()
-        .persistent()
-        .extend_ttl(key, TTL_THRESHOLD, TTL_EXTEND_TO);
-}
-
 fn extend_ttl_u64_key(env: &Env, key: &(soroban_sdk::Symbol, u64)) {
     env.storage()
         .persistent()
         .extend_ttl(key, TTL_THRESHOLD, TTL_EXTEND_TO);
 }
 
-fn extend_ttl_status_key(env: &Env, key: &(soroban_sdk::Symbol, VerificationStatus)) {
+fn extend_ttl_status_key(env: &Env, key: &(soroban_sdk::Symbol, VerificationStatus, Address)) {
     env.storage()
         .persistent()
         .extend_ttl(key, TTL_THRESHOLD, TTL_EXTEND_TO);
@@ -85,16 +81,50 @@ pub struct Provider {
     pub is_active: bool,
 }
 
+impl Provider {
+    /// True if the provider may currently practise on the platform.
+    pub fn is_verified(&self) -> bool {
+        self.is_active && self.verification_status == VerificationStatus::Verified
+    }
+
+    /// True if at least one license on file has not yet expired.
+    pub fn has_current_license(&self, now: u64) -> bool {
+        self.licenses
+            .iter()
+            .any(|license| license.expiry_date > now)
+    }
+
+    pub fn has_specialty(&self, specialty: &String) -> bool {
+        self.specialties.contains(specialty)
+    }
+
+    /// True if any practice location matches the given city and/or state.
+    /// A `None` filter matches every location.
+    pub fn practises_in(&self, city: &Option<String>, state: &Option<String>) -> bool {
+        self.locations.iter().any(|location| {
+            city.as_ref().map_or(true, |c| location.city == *c)
+                && state.as_ref().map_or(true, |s| location.state == *s)
+        })
+    }
+}
+
 pub fn provider_key(provider: &Address) -> (soroban_sdk::Symbol, Address) {
     (symbol_short!("PROV"), provider.clone())
 }
 
-pub fn specialty_index_key(specialty: &String) -> (soroban_sdk::Symbol, String) {
-    (symbol_short!("SPEC_IDX"), specialty.clone())
+fn specialty_list(specialty: &String) -> BucketList<String> {
+    BucketList::new(symbol_short!("SPEC_LST"), specialty.clone())
+}
+
+fn status_list(status: &VerificationStatus) -> BucketList<VerificationStatus> {
+    BucketList::new(symbol_short!("STAT_LST"), status.clone())
 }
 
-pub fn status_index_key(status: &VerificationStatus) -> (soroban_sdk::Symbol, VerificationStatus) {
-    (symbol_short!("STAT_IDX"), status.clone())
+fn status_member_key(
+    status: &VerificationStatus,
+    provider: &Address,
+) -> (soroban_sdk::Symbol, VerificationStatus, Address) {
+    (symbol_short!("STAT_IN"), status.clone(), provider.clone())
 }
 
 pub fn get_provider(env: &Env, provider: &Address) -> Option<Provider> {
@@ -104,118 +134,67 @@ pub fn get_provider(env: &Env, provider: &Address) -> Option<Provider> {
 
 pub fn set_provider(env: &Env, provider: &Provider) {
     let key = provider_key(&provider.address);
-
-    // Get old provider to update status index if status changed
-    let old_provider = get_provider(env, &provider.address);
-
     env.storage().persistent().set(&key, provider);
     extend_ttl(env, &key);
 
-    // Update status index
-    if let Some(old) = old_provider {
-        // Remove from old status index if status or active state changed
-        if old.verification_status != provider.verification_status
-            || old.is_active != provider.is_active
-        {
-            remove_provider_from_status_index(env, &old.verification_status, &provider.address);
-        }
-    }
-
-    // Add to new status index if active
     if provider.is_active {
-        add_provider_to_status_index(env, &provider.verification_status, &provider.address);
-    } else {
-        // Remove from status index if provider is inactive
-        remove_provider_from_status_index(env, &provider.verification_status, &provider.address);
+        add_provider_to_status_list(env, &provider.verification_status, &provider.address);
     }
 }
 
+/// Appends `provider` to the list of `specialty`. Specialties are fixed at
+/// registration, so each provider is appended at most once.
 pub fn add_provider_to_specialty_index(env: &Env, specialty: &String, provider: &Address) {
-    let key = specialty_index_key(specialty);
-    let mut providers: Vec<Address> = env
-        .storage()
-        .persistent()
-        .get(&key)
-        .unwrap_or(Vec::new(env));
-    if !providers.contains(provider) {
-        providers.push_back(provider.clone());
-    }
-    env.storage().persistent().set(&key, &providers);
-    extend_ttl_string_key(env, &key);
-}
-
-pub fn remove_provider_from_specialty_index(env: &Env, specialty: &String, provider: &Address) {
-    let key = specialty_index_key(specialty);
-    if let Some(providers) = env.storage().persistent().get::<_, Vec<Address>>(&key) {
-        let mut new_providers = Vec::new(env);
-        for i in 0..providers.len() {
-            if let Some(p) = providers.get(i) {
-                if p != *provider {
-                    new_providers.push_back(p);
-                }
-            }
-        }
-        if !new_providers.is_empty() {
-            env.storage().persistent().set(&key, &new_providers);
-            extend_ttl_string_key(env, &key);
-        } else {
-            env.storage().persistent().remove(&key);
-        }
-    }
+    specialty_list(specialty).push(env, provider);
 }
 
-pub fn get_providers_by_specialty(env: &Env, specialty: &String) -> Vec<Address> {
-    let key = specialty_index_key(specialty);
-    env.storage()
-        .persistent()
-        .get(&key)
-        .unwrap_or(Vec::new(env))
-}
-
-pub fn add_provider_to_status_index(env: &Env, status: &VerificationStatus, provider: &Address) {
-    let key = status_index_key(status);
-    let mut providers: Vec<Address> = env
-        .storage()
-        .persistent()
-        .get(&key)
-        .unwrap_or(Vec::new(env));
-    if !providers.contains(provider) {
-        providers.push_back(provider.clone());
+/// Appends `provider` to the list of `status` the first time it enters that
+/// status. Entries are never removed; listings skip providers whose status
+/// has changed since.
+fn add_provider_to_status_list(env: &Env, status: &VerificationStatus, provider: &Address) {
+    let member_key = status_member_key(status, provider);
+    if env.storage().persistent().has(&member_key) {
+        return;
     }
-    env.storage().persistent().set(&key, &providers);
-    extend_ttl_status_key(env, &key);
+    env.storage().persistent().set(&member_key, &true);
+    extend_ttl_status_key(env, &member_key);
+    status_list(status).push(env, provider);
 }
 
-pub fn remove_provider_from_status_index(
+/// Page through the providers registered with `specialty`, loading each
+/// profile and keeping the ones `keep` accepts.
+pub fn page_providers_by_specialty(
+    env: &Env,
+    specialty: &String,
+    cursor: Option<u64>,
+    limit: u32,
+    keep: impl FnMut(&Provider) -> bool,
+) -> (Vec<Provider>, Option<u64>) {
+    specialty_list(specialty).page_map(
+        env,
+        cursor,
+        limit,
+        false,
+        |address: Address| get_provider(env, &address),
+        keep,
+    )
+}
+
+/// Page through the active providers currently in `status`.
+pub fn page_providers_by_status(
     env: &Env,
     status: &VerificationStatus,
-    provider: &Address,
-) {
-    let key = status_index_key(status);
-    if let Some(providers) = env.storage().persistent().get::<_, Vec<Address>>(&key) {
-        let mut new_providers = Vec::new(env);
-        for i in 0..providers.len() {
-            if let Some(p) = providers.get(i) {
-                if p != *provider {
-                    new_providers.push_back(p);
-                }
-            }
-        }
-        if !new_providers.is_empty() {
-            env.storage().persistent().set(&key, &new_providers);
-            extend_ttl_status_key(env, &key);
-        } else {
-            env.storage().persistent().remove(&key);
-        }
-    }
-}
-
-pub fn get_providers_by_status(env: &Env, status: &VerificationStatus) -> Vec<Address> {
-    let key = status_index_key(status);
-    env.storage()
-        .persistent()
-        .get(&key)
-        .unwrap_or(Vec::new(env))
+    cursor: Option<u64>,
+    limit: u32,
+) -> (Vec<Provider>, Option<u64>) {
+    status_list(status).page_map(
+        env,
+        cursor,
+        limit,
+        false,
+        |address: Address| get_provider(env, &address),
+        |p: &Provider| p.is_active && p.verification_status == *status,
+    )
 }
 
 pub fn get_provider_counter(env: &Env) -> u64 {
@@ -230,21 +209,7 @@ pub fn increment_provider_counter(env: &Env) -> u64 {
     count
 }
 
-pub fn get_all_provider_ids(env: &Env) -> Vec<u64> {
-    let ids_key = symbol_short!("PROV_IDS");
-    env.storage()
-        .instance()
-        .get(&ids_key)
-        .unwrap_or(Vec::new(env))
-}
-
 pub fn add_provider_id(env: &Env, provider_id: u64, provider: &Address) {
-    let ids_key = symbol_short!("PROV_IDS");
-    let mut ids: Vec<u64> = get_all_provider_ids(env);
-    if !ids.contains(provider_id) {
-        ids.push_back(provider_id);
-        env.storage().instance().set(&ids_key, &ids);
-    }
     let id_key = (symbol_short!("PROV_ID"), provider_id);
     env.storage().persistent().set(&id_key, provider);
     extend_ttl_u64_key(env, &id_key);
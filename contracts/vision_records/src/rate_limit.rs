@@ -1,14 +1,41 @@
+//! Per-operation, per-role rate limiting.
+//!
+//! Each metered operation has a default [`RateLimitConfig`] and may carry
+//! role-specific overrides. Limits are token buckets: a caller can spend up
+//! to `max_requests + burst` tokens at once, and tokens refill at
+//! `max_requests` per `window_seconds`. Batch calls spend one token per item
+//! from the budget of the single-item operation they wrap.
+
+#![allow(clippy::arithmetic_side_effects)]
 use soroban_sdk::{contracttype, symbol_short, Address, Env, String, Symbol, Vec};
 
+use crate::rbac::Role;
+
 // ── Storage keys ──────────────────────────────────────────────
 pub(crate) const RATE_LIMIT_CONFIG: Symbol = symbol_short!("RL_CFG");
-pub(crate) const RATE_LIMIT_WINDOW: Symbol = symbol_short!("RL_WIN");
-pub(crate) const RATE_LIMIT_COUNT: Symbol = symbol_short!("RL_CNT");
+pub(crate) const RATE_LIMIT_ROLE_CONFIG: Symbol = symbol_short!("RL_RCFG");
+pub(crate) const RATE_LIMIT_BUCKET: Symbol = symbol_short!("RL_BKT");
 pub(crate) const RATE_LIMIT_BYPASS: Symbol = symbol_short!("RL_BYP");
+pub(crate) const RATE_LIMIT_OPS: Symbol = symbol_short!("RL_OPS");
+/// Instance tuple of totals recorded before stats moved to per-operation
+/// keys. Read-only; still included in `get_rate_limit_stats`.
+pub(crate) const RATE_LIMIT_STATS: Symbol = symbol_short!("RL_STATS");
+pub(crate) const RATE_LIMIT_SEEN: Symbol = symbol_short!("RL_SEEN");
+pub(crate) const RATE_LIMIT_UNIQUE: Symbol = symbol_short!("RL_UNIQ");
+pub(crate) const RATE_LIMIT_OP_REQUESTS: Symbol = symbol_short!("RL_REQS");
+pub(crate) const RATE_LIMIT_OP_HITS: Symbol = symbol_short!("RL_HITS");
 
 const TTL_THRESHOLD: u32 = 5184000;
 const TTL_EXTEND_TO: u32 = 10368000;
 
+/// Number of operations listed in `RateLimitStats::top_rate_limited_operations`.
+const TOP_OPERATIONS: u32 = 5;
+
+// ── Metered operations ────────────────────────────────────────
+pub const OP_ADD_RECORD: &str = "add_record";
+pub const OP_GET_RECORD: &str = "get_record";
+pub const OP_GRANT_ACCESS: &str = "grant_access";
+
 /// Extends the time-to-live (TTL) for rate limit storage keys.
 fn extend_ttl_config_key(env: &Env, key: &(Symbol, String)) {
     env.storage()
@@ -16,19 +43,19 @@ fn extend_ttl_config_key(env: &Env, key: &(Symbol, String)) {
         .extend_ttl(key, TTL_THRESHOLD, TTL_EXTEND_TO);
 }
 
-fn extend_ttl_window_key(env: &Env, key: &(Symbol, Address, String)) {
+fn extend_ttl_role_config_key(env: &Env, key: &(Symbol, String, Role)) {
     env.storage()
         .persistent()
         .extend_ttl(key, TTL_THRESHOLD, TTL_EXTEND_TO);
 }
 
-fn extend_ttl_count_key(env: &Env, key: &(Symbol, Address, String)) {
+fn extend_ttl_bucket_key(env: &Env, key: &(Symbol, Address, String)) {
     env.storage()
         .persistent()
         .extend_ttl(key, TTL_THRESHOLD, TTL_EXTEND_TO);
 }
 
-fn extend_ttl_bypass_key(env: &Env, key: &(Symbol, Address)) {
+fn extend_ttl_address_key(env: &Env, key: &(Symbol, Address)) {
     env.storage()
         .persistent()
         .extend_ttl(key, TTL_THRESHOLD, TTL_EXTEND_TO);
@@ -38,24 +65,44 @@ fn extend_ttl_bypass_key(env: &Env, key: &(Symbol, Address)) {
 
 /// Rate limit configuration for an operation type
 #[contracttype]
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Eq, PartialEq)]
 pub struct RateLimitConfig {
-    pub max_requests: u32,   // Maximum requests allowed in the window
+    pub max_requests: u32,   // Sustained requests allowed per window
     pub window_seconds: u64, // Time window in seconds
+    pub burst: u32,          // Extra requests allowed on top of a full window
     pub operation: String,   // Operation name (e.g., "add_record", "get_record")
 }
 
+impl RateLimitConfig {
+    /// Bucket size in tokens.
+    pub fn capacity(&self) -> u64 {
+        u64::from(self.max_requests) + u64::from(self.burst)
+    }
+}
+
+/// Token bucket state. `level` is kept in units of `1 / window_seconds`
+/// tokens so refills stay exact in integer arithmetic.
+#[contracttype]
+#[derive(Clone, Debug)]
+pub struct RateLimitBucket {
+    pub level: u64,
+    pub updated_at: u64,
+}
+
 /// Rate limit status for an address
 #[contracttype]
 #[derive(Clone, Debug)]
 pub struct RateLimitStatus {
     pub address: Address,
     pub operation: String,
+    /// Tokens spent and not yet refilled.
     pub current_count: u32,
+    /// Tokens available right now.
+    pub remaining: u32,
     pub max_requests: u32,
+    pub burst: u32,
     pub window_seconds: u64,
-    pub window_start: u64,
-    pub window_end: u64,
+    /// When the bucket will be full again.
     pub reset_at: u64,
 }
 
@@ -64,63 +111,92 @@ pub struct RateLimitStatus {
 #[derive(Clone, Debug)]
 pub struct RateLimitStats {
     pub total_requests: u64,
-    pub rate_limited_requests: u64,
+    /// Admitted requests that left the caller with less than one token.
+    /// Refused requests roll back with their transaction and are not seen.
+    pub budget_exhausted: u64,
     pub unique_addresses: u64,
-    pub top_rate_limited_operations: Vec<String>,
+    /// Operations ranked by `budget_exhausted`.
+    pub top_exhausted_operations: Vec<String>,
 }
 
 // ── Storage Functions ────────────────────────────────────────
 
-/// Gets rate limit configuration for an operation
+/// Gets the default rate limit configuration for an operation
 pub fn get_rate_limit_config(env: &Env, operation: &String) -> Option<RateLimitConfig> {
     let key = (RATE_LIMIT_CONFIG, operation.clone());
     env.storage().persistent().get(&key)
 }
 
-/// Sets rate limit configuration for an operation
+/// Sets the default rate limit configuration for an operation
 pub fn set_rate_limit_config(env: &Env, config: &RateLimitConfig) {
     let key = (RATE_LIMIT_CONFIG, config.operation.clone());
     env.storage().persistent().set(&key, config);
     extend_ttl_config_key(env, &key);
+    index_operation(env, &config.operation);
 }
 
-/// Gets the current rate limit window start time for an address and operation
-pub fn get_rate_limit_window(env: &Env, address: &Address, operation: &String) -> u64 {
-    let key = (RATE_LIMIT_WINDOW, address.clone(), operation.clone());
+/// Removes the default rate limit configuration for an operation. Role
+/// overrides are kept.
+pub fn remove_rate_limit_config(env: &Env, operation: &String) {
     env.storage()
         .persistent()
-        .get(&key)
-        .unwrap_or(env.ledger().timestamp())
+        .remove(&(RATE_LIMIT_CONFIG, operation.clone()));
+}
+
+/// Gets the configuration override for `role`, if one is set
+pub fn get_role_rate_limit_config(
+    env: &Env,
+    operation: &String,
+    role: &Role,
+) -> Option<RateLimitConfig> {
+    let key = (RATE_LIMIT_ROLE_CONFIG, operation.clone(), role.clone());
+    env.storage().persistent().get(&key)
+}
+
+/// Sets the configuration override for `role`
+pub fn set_role_rate_limit_config(env: &Env, role: &Role, config: &RateLimitConfig) {
+    let key = (
+        RATE_LIMIT_ROLE_CONFIG,
+        config.operation.clone(),
+        role.clone(),
+    );
+    env.storage().persistent().set(&key, config);
+    extend_ttl_role_config_key(env, &key);
+    index_operation(env, &config.operation);
 }
 
-/// Sets the rate limit window start time for an address and operation
-pub fn set_rate_limit_window(env: &Env, address: &Address, operation: &String, window_start: u64) {
-    let key = (RATE_LIMIT_WINDOW, address.clone(), operation.clone());
-    env.storage().persistent().set(&key, &window_start);
-    extend_ttl_window_key(env, &key);
+/// Removes the configuration override for `role`
+pub fn remove_role_rate_limit_config(env: &Env, operation: &String, role: &Role) {
+    env.storage()
+        .persistent()
+        .remove(&(RATE_LIMIT_ROLE_CONFIG, operation.clone(), role.clone()));
 }
 
-/// Gets the current request count for an address and operation in the current window
-pub fn get_rate_limit_count(env: &Env, address: &Address, operation: &String) -> u32 {
-    let key = (RATE_LIMIT_COUNT, address.clone(), operation.clone());
-    env.storage().persistent().get(&key).unwrap_or(0)
+/// Returns the configuration that applies to a caller holding `role`: the
+/// role override if one exists, otherwise the operation default.
+pub fn effective_config(
+    env: &Env,
+    operation: &String,
+    role: Option<&Role>,
+) -> Option<RateLimitConfig> {
+    role.and_then(|r| get_role_rate_limit_config(env, operation, r))
+        .or_else(|| get_rate_limit_config(env, operation))
 }
 
-/// Increments the rate limit count for an address and operation
-pub fn increment_rate_limit_count(env: &Env, address: &Address, operation: &String) -> u32 {
-    let key = (RATE_LIMIT_COUNT, address.clone(), operation.clone());
-    let current: u32 = env.storage().persistent().get(&key).unwrap_or(0);
-    let new_count = current + 1;
-    env.storage().persistent().set(&key, &new_count);
-    extend_ttl_count_key(env, &key);
-    new_count
+/// Operations that have ever had a configuration set.
+pub fn get_configured_operations(env: &Env) -> Vec<String> {
+    env.storage()
+        .instance()
+        .get(&RATE_LIMIT_OPS)
+        .unwrap_or(Vec::new(env))
 }
 
-/// Resets the rate limit count for an address and operation
-pub fn reset_rate_limit_count(env: &Env, address: &Address, operation: &String) {
-    let key = (RATE_LIMIT_COUNT, address.clone(), operation.clone());
-    env.storage().persistent().set(&key, &0u32);
-    extend_ttl_count_key(env, &key);
+fn index_operation(env: &Env, operation: &String) {
+    let mut ops = get_configured_operations(env);
+    if !ops.contains(operation) {
+        ops.push_back(operation.clone());
+        env.storage().instance().set(&RATE_LIMIT_OPS, &ops);
+    }
 }
 
 /// Checks if an address has rate limit bypass (e.g., verified providers)
@@ -134,115 +210,239 @@ pub fn set_rate_limit_bypass(env: &Env, address: &Address, bypass: bool) {
     let key = (RATE_LIMIT_BYPASS, address.clone());
     if bypass {
         env.storage().persistent().set(&key, &true);
-        extend_ttl_bypass_key(env, &key);
+        extend_ttl_address_key(env, &key);
     } else {
         env.storage().persistent().remove(&key);
     }
 }
 
-// ── Rate Limit Logic ────────────────────────────────────────
+// ── Token bucket ─────────────────────────────────────────────
 
-/// Checks if a request should be rate limited
-/// Returns (is_allowed, current_count, max_requests, reset_at)
-pub fn check_rate_limit(env: &Env, address: &Address, operation: &String) -> (bool, u32, u32, u64) {
-    // Check if address has bypass
-    if has_rate_limit_bypass(env, address) {
-        return (true, 0, 0, 0);
-    }
+fn get_bucket(env: &Env, address: &Address, operation: &String) -> Option<RateLimitBucket> {
+    let key = (RATE_LIMIT_BUCKET, address.clone(), operation.clone());
+    env.storage().persistent().get(&key)
+}
 
-    // Get rate limit configuration
-    let config = match get_rate_limit_config(env, operation) {
-        Some(cfg) => cfg,
-        None => {
-            // No rate limit configured for this operation - allow
-            return (true, 0, 0, 0);
-        }
-    };
+fn set_bucket(env: &Env, address: &Address, operation: &String, bucket: &RateLimitBucket) {
+    let key = (RATE_LIMIT_BUCKET, address.clone(), operation.clone());
+    env.storage().persistent().set(&key, bucket);
+    extend_ttl_bucket_key(env, &key);
+}
 
-    let current_time = env.ledger().timestamp();
-    let window_start = get_rate_limit_window(env, address, operation);
-    let window_end = window_start + config.window_seconds;
-
-    // Check if current window has expired
-    if current_time >= window_end {
-        // Reset window and count
-        set_rate_limit_window(env, address, operation, current_time);
-        reset_rate_limit_count(env, address, operation);
-        let new_count = increment_rate_limit_count(env, address, operation);
-        let new_window_end = current_time + config.window_seconds;
-        return (true, new_count, config.max_requests, new_window_end);
+/// Current bucket level after refilling up to `now`. New buckets start full.
+fn refilled_level(config: &RateLimitConfig, bucket: Option<&RateLimitBucket>, now: u64) -> u64 {
+    let full = config.capacity().saturating_mul(config.window_seconds);
+    match bucket {
+        None => full,
+        Some(b) => {
+            let elapsed = now.saturating_sub(b.updated_at);
+            b.level
+                .saturating_add(elapsed.saturating_mul(u64::from(config.max_requests)))
+                .min(full)
+        }
     }
+}
 
-    // Window is still active, check count
-    let current_count = get_rate_limit_count(env, address, operation);
-
-    // If this is the first request in a window (count is 0), store the window_start
-    if current_count == 0 {
-        set_rate_limit_window(env, address, operation, window_start);
+/// Spends `cost` tokens from `address`'s bucket for `operation`.
+///
+/// Returns false if the bucket does not hold enough tokens; nothing is
+/// spent in that case. Operations without a configuration, and addresses
+/// with a bypass, are always allowed.
+pub fn check_rate_limit(
+    env: &Env,
+    address: &Address,
+    role: Option<&Role>,
+    operation: &String,
+    cost: u32,
+) -> bool {
+    if has_rate_limit_bypass(env, address) {
+        return true;
+    }
+    let config = match effective_config(env, operation, role) {
+        Some(cfg) => cfg,
+        None => return true,
+    };
+    if config.max_requests == 0 || config.window_seconds == 0 {
+        return true;
     }
 
-    if current_count >= config.max_requests {
-        // Rate limit exceeded
-        return (false, current_count, config.max_requests, window_end);
+    let now = env.ledger().timestamp();
+    let bucket = get_bucket(env, address, operation);
+    let first_use = bucket.is_none();
+    let level = refilled_level(&config, bucket.as_ref(), now);
+    let token = config.window_seconds;
+    let needed = u64::from(cost).saturating_mul(token);
+    if needed > level {
+        return false;
     }
 
-    // Increment count and allow
-    let new_count = increment_rate_limit_count(env, address, operation);
-    (true, new_count, config.max_requests, window_end)
+    let remaining = level - needed;
+    set_bucket(
+        env,
+        address,
+        operation,
+        &RateLimitBucket {
+            level: remaining,
+            updated_at: now,
+        },
+    );
+    record_request(
+        env,
+        address,
+        operation,
+        u64::from(cost),
+        first_use,
+        remaining < token,
+    );
+    true
 }
 
 /// Gets rate limit status for an address and operation
 pub fn get_rate_limit_status(
     env: &Env,
     address: &Address,
+    role: Option<&Role>,
     operation: &String,
 ) -> Option<RateLimitStatus> {
-    let config = get_rate_limit_config(env, operation)?;
-    let window_start = get_rate_limit_window(env, address, operation);
-    let current_count = get_rate_limit_count(env, address, operation);
-    let window_end = window_start + config.window_seconds;
+    let config = effective_config(env, operation, role)?;
+    let now = env.ledger().timestamp();
+    let level = refilled_level(&config, get_bucket(env, address, operation).as_ref(), now);
+    let token = config.window_seconds.max(1);
+    let full = config.capacity().saturating_mul(config.window_seconds);
+    let remaining = level / token;
+    let reset_at = if config.max_requests == 0 {
+        now
+    } else {
+        now + (full - level).div_ceil(u64::from(config.max_requests))
+    };
 
     Some(RateLimitStatus {
         address: address.clone(),
         operation: operation.clone(),
-        current_count,
+        current_count: (config.capacity() - remaining) as u32,
+        remaining: remaining as u32,
         max_requests: config.max_requests,
+        burst: config.burst,
         window_seconds: config.window_seconds,
-        window_start,
-        window_end,
-        reset_at: window_end,
+        reset_at,
     })
 }
 
-/// Gets all rate limit configurations
+/// Gets all default rate limit configurations
 pub fn get_all_rate_limit_configs(env: &Env) -> Vec<RateLimitConfig> {
-    // Note: This is a simplified implementation
-    // In a production system, you might want to maintain an index of all operations
     let mut configs = Vec::new(env);
+    for op in get_configured_operations(env).iter() {
+        if let Some(config) = get_rate_limit_config(env, &op) {
+            configs.push_back(config);
+        }
+    }
+    configs
+}
 
-    // Common operations that might have rate limits
-    let mut operations = Vec::new(env);
-    operations.push_back(String::from_str(env, "add_record"));
-    operations.push_back(String::from_str(env, "get_record"));
-    operations.push_back(String::from_str(env, "grant_access"));
-    operations.push_back(String::from_str(env, "register_user"));
-
-    for i in 0..operations.len() {
-        if let Some(op) = operations.get(i) {
-            if let Some(config) = get_rate_limit_config(env, &op) {
-                configs.push_back(config);
-            }
+// ── Statistics ──────────────────────────────────────────────
+
+fn extend_ttl_operation_key(env: &Env, key: &(Symbol, String)) {
+    env.storage()
+        .persistent()
+        .extend_ttl(key, TTL_THRESHOLD, TTL_EXTEND_TO);
+}
+
+fn bump_persistent_counter<K>(env: &Env, key: &K, by: u64) -> u64
+where
+    K: soroban_sdk::IntoVal<Env, soroban_sdk::Val>,
+{
+    let count: u64 = env.storage().persistent().get(key).unwrap_or(0) + by;
+    env.storage().persistent().set(key, &count);
+    count
+}
+
+/// Records an admitted request under `operation`'s own counters, so
+/// requests for different operations never write the same entry.
+/// `exhausted` marks a request that left the bucket with less than one
+/// token; refused requests are rolled back and never reach here.
+fn record_request(
+    env: &Env,
+    address: &Address,
+    operation: &String,
+    cost: u64,
+    first_use: bool,
+    exhausted: bool,
+) {
+    let requests_key = (RATE_LIMIT_OP_REQUESTS, operation.clone());
+    bump_persistent_counter(env, &requests_key, cost);
+    extend_ttl_operation_key(env, &requests_key);
+
+    if first_use {
+        let seen_key = (RATE_LIMIT_SEEN, address.clone());
+        if !env.storage().persistent().has(&seen_key) {
+            env.storage().persistent().set(&seen_key, &true);
+            extend_ttl_address_key(env, &seen_key);
+            bump_persistent_counter(env, &RATE_LIMIT_UNIQUE, 1);
+            env.storage()
+                .persistent()
+                .extend_ttl(&RATE_LIMIT_UNIQUE, TTL_THRESHOLD, TTL_EXTEND_TO);
         }
     }
 
-    configs
+    if exhausted {
+        let hits_key = (RATE_LIMIT_OP_HITS, operation.clone());
+        bump_persistent_counter(env, &hits_key, 1);
+        extend_ttl_operation_key(env, &hits_key);
+    }
 }
 
-/// Gets addresses that have rate limit bypass
-pub fn get_rate_limit_bypass_addresses(env: &Env) -> Vec<Address> {
-    // Note: This is a simplified implementation
-    // In production, you might want to maintain an index
-    // This would need to be implemented with an index in a real system
-    // For now, return empty vector
-    Vec::new(env)
+/// Returns aggregate limiter statistics, summed over the configured
+/// operations. Operations are ranked by how often callers exhausted their
+/// budget.
+pub fn get_rate_limit_stats(env: &Env) -> RateLimitStats {
+    let (mut total, mut exhausted, legacy_unique): (u64, u64, u64) = env
+        .storage()
+        .instance()
+        .get(&RATE_LIMIT_STATS)
+        .unwrap_or((0, 0, 0));
+    let unique: u64 = env
+        .storage()
+        .persistent()
+        .get(&RATE_LIMIT_UNIQUE)
+        .unwrap_or(0);
+
+    // Insertion sort by hit count, descending; the operation list is small.
+    let mut ranked: Vec<(u64, String)> = Vec::new(env);
+    for op in get_configured_operations(env).iter() {
+        let requests: u64 = env
+            .storage()
+            .persistent()
+            .get(&(RATE_LIMIT_OP_REQUESTS, op.clone()))
+            .unwrap_or(0);
+        let hits: u64 = env
+            .storage()
+            .persistent()
+            .get(&(RATE_LIMIT_OP_HITS, op.clone()))
+            .unwrap_or(0);
+        total += requests;
+        exhausted += hits;
+        if hits == 0 {
+            continue;
+        }
+        let mut at = ranked.len();
+        for (i, (other, _)) in ranked.iter().enumerate() {
+            if hits > other {
+                at = i as u32;
+                break;
+            }
+        }
+        ranked.insert(at, (hits, op));
+    }
+
+    let mut top = Vec::new(env);
+    for (_, op) in ranked.iter().take(TOP_OPERATIONS as usize) {
+        top.push_back(op);
+    }
+
+    RateLimitStats {
+        total_requests: total,
+        budget_exhausted: exhausted,
+        unique_addresses: legacy_unique + unique,
+        top_exhausted_operations: top,
+    }
 }
@@ -3,6 +3,10 @@ use soroban_sdk::{contracttype, symbol_short, Address, Env, String, Symbol, Vec}
 const TTL_THRESHOLD: u32 = 5184000;
 const TTL_EXTEND_TO: u32 = 10368000;
 
+/// Most ACL groups a user may belong to. Permission checks load every group
+/// of the user, so the list must stay small.
+pub const MAX_USER_GROUPS: u32 = 10;
+
 /// Time-based access restrictions
 #[contracttype]
 #[derive(Clone, Debug, Eq, PartialEq, Copy)]
@@ -59,6 +63,15 @@ pub struct AccessPolicy {
     pub enabled: bool,
 }
 
+/// What an access policy is attached to. Policies attached to a sensitivity
+/// level apply to every record currently classified at that level.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum PolicyTarget {
+    Record(u64),
+    Sensitivity(SensitivityLevel),
+}
+
 fn extend_ttl_address_key(env: &Env, key: &(soroban_sdk::Symbol, Address)) {
     env.storage()
         .persistent()
@@ -209,6 +222,14 @@ pub struct RevokedDelegation {
     pub is_scoped: bool,
 }
 
+pub fn policy_target_key(target: &PolicyTarget) -> (Symbol, PolicyTarget) {
+    (symbol_short!("POL_TGT"), target.clone())
+}
+
+pub fn policy_attachments_key(id: &String) -> (Symbol, String) {
+    (symbol_short!("POL_ATTN"), id.clone())
+}
+
 pub fn user_credential_key(user: &Address) -> (Symbol, Address) {
     (symbol_short!("USER_CRED"), user.clone())
 }
@@ -469,6 +490,9 @@ pub fn add_to_group(env: &Env, user: Address, group_name: String) -> Result<(),
         .unwrap_or(Vec::new(env));
 
     if !groups.contains(&group_name) {
+        if groups.len() >= MAX_USER_GROUPS {
+            return Err(());
+        }
         groups.push_back(group_name);
         env.storage()
             .persistent()
@@ -668,7 +692,7 @@ fn satisfies_time_restriction(env: &Env, restriction: &TimeRestriction) -> bool
 }
 
 /// Get user's credential type from storage
-fn get_user_credential(env: &Env, user: &Address) -> CredentialType {
+pub fn get_user_credential(env: &Env, user: &Address) -> CredentialType {
     let key = user_credential_key(user);
     env.storage()
         .persistent()
@@ -677,7 +701,7 @@ fn get_user_credential(env: &Env, user: &Address) -> CredentialType {
 }
 
 /// Get record sensitivity level from storage
-fn get_record_sensitivity(env: &Env, record_id: &u64) -> SensitivityLevel {
+pub fn get_record_sensitivity(env: &Env, record_id: &u64) -> SensitivityLevel {
     let key = record_sensitivity_key(record_id);
     env.storage()
         .persistent()
@@ -740,16 +764,7 @@ pub fn evaluate_policy(env: &Env, policy: &AccessPolicy, context: &PolicyContext
     if conditions.consent_required {
         if let (Some(patient), Some(_record_id)) = (&context.patient, &context.resource_id) {
             // Check if there's active consent for this user to access this patient's records
-            let consent_key = (
-                symbol_short!("CONSENT"),
-                patient.clone(),
-                context.user.clone(),
-            );
-            if let Some(consent) = env
-                .storage()
-                .persistent()
-                .get::<_, ConsentGrant>(&consent_key)
-            {
+            if let Some(consent) = crate::load_consent(env, patient, &context.user) {
                 if consent.revoked || consent.expires_at <= context.current_time {
                     return false;
                 }
@@ -825,24 +840,127 @@ pub fn set_record_sensitivity(env: &Env, record_id: u64, sensitivity: Sensitivit
 pub fn create_access_policy(env: &Env, policy: AccessPolicy) {
     let key = access_policy_key(&policy.id);
     env.storage().persistent().set(&key, &policy);
+    env.storage()
+        .persistent()
+        .extend_ttl(&key, TTL_THRESHOLD, TTL_EXTEND_TO);
 }
 
-fn extend_ttl_u64_key(env: &Env, key: &(soroban_sdk::Symbol, u64)) {
+pub fn get_access_policy(env: &Env, id: &String) -> Option<AccessPolicy> {
+    env.storage().persistent().get(&access_policy_key(id))
+}
+
+/// Delete an access policy. Callers must first check it is no longer
+/// attached anywhere, see [`policy_attachment_count`].
+pub fn remove_access_policy(env: &Env, id: &String) -> bool {
+    let key = access_policy_key(id);
+    let existed = env.storage().persistent().has(&key);
+    env.storage().persistent().remove(&key);
+    existed
+}
+
+/// Ids of the policies attached to `target`.
+pub fn attached_policies(env: &Env, target: &PolicyTarget) -> Vec<String> {
     env.storage()
         .persistent()
-        .extend_ttl(key, TTL_THRESHOLD, TTL_EXTEND_TO);
+        .get(&policy_target_key(target))
+        .unwrap_or(Vec::new(env))
 }
 
-/// Consent grant structure for ABAC evaluation
-#[contracttype]
-#[derive(Clone, Debug, Eq, PartialEq)]
-pub struct ConsentGrant {
-    pub patient: Address,
-    pub grantee: Address,
-    pub consent_type: crate::ConsentType,
-    pub granted_at: u64,
-    pub expires_at: u64,
-    pub revoked: bool,
+/// Number of targets a policy is attached to.
+pub fn policy_attachment_count(env: &Env, id: &String) -> u32 {
+    env.storage()
+        .persistent()
+        .get(&policy_attachments_key(id))
+        .unwrap_or(0)
+}
+
+fn set_policy_attachment_count(env: &Env, id: &String, count: u32) {
+    let key = policy_attachments_key(id);
+    if count == 0 {
+        env.storage().persistent().remove(&key);
+    } else {
+        env.storage().persistent().set(&key, &count);
+        env.storage()
+            .persistent()
+            .extend_ttl(&key, TTL_THRESHOLD, TTL_EXTEND_TO);
+    }
+}
+
+/// Attach a policy to `target`; returns false if it already was.
+pub fn attach_policy(env: &Env, target: &PolicyTarget, policy_id: &String) -> bool {
+    let key = policy_target_key(target);
+    let mut ids = attached_policies(env, target);
+    if ids.contains(policy_id) {
+        return false;
+    }
+    ids.push_back(policy_id.clone());
+    env.storage().persistent().set(&key, &ids);
+    env.storage()
+        .persistent()
+        .extend_ttl(&key, TTL_THRESHOLD, TTL_EXTEND_TO);
+    let count = policy_attachment_count(env, policy_id).saturating_add(1);
+    set_policy_attachment_count(env, policy_id, count);
+    true
+}
+
+/// Detach a policy from `target`; returns false if it was not attached.
+pub fn detach_policy(env: &Env, target: &PolicyTarget, policy_id: &String) -> bool {
+    let key = policy_target_key(target);
+    let mut ids = attached_policies(env, target);
+    match ids.first_index_of(policy_id) {
+        Some(index) => {
+            ids.remove(index);
+            if ids.is_empty() {
+                env.storage().persistent().remove(&key);
+            } else {
+                env.storage().persistent().set(&key, &ids);
+            }
+            let count = policy_attachment_count(env, policy_id).saturating_sub(1);
+            set_policy_attachment_count(env, policy_id, count);
+            true
+        }
+        None => false,
+    }
+}
+
+/// Returns the id of the first enabled policy attached to the record, or to
+/// its sensitivity level, that `user` does not satisfy. Every applicable
+/// policy must pass; disabled and deleted policies are skipped.
+pub fn violated_record_policy(
+    env: &Env,
+    user: &Address,
+    record_id: u64,
+    patient: &Address,
+) -> Option<String> {
+    let mut ids = attached_policies(env, &PolicyTarget::Record(record_id));
+    ids.append(&attached_policies(
+        env,
+        &PolicyTarget::Sensitivity(get_record_sensitivity(env, &record_id)),
+    ));
+    if ids.is_empty() {
+        return None;
+    }
+
+    let context = PolicyContext {
+        user: user.clone(),
+        resource_id: Some(record_id),
+        patient: Some(patient.clone()),
+        current_time: env.ledger().timestamp(),
+    };
+    for id in ids.iter() {
+        if let Some(policy) = get_access_policy(env, &id) {
+            if policy.enabled && !evaluate_policy(env, &policy, &context) {
+                return Some(id);
+            }
+        }
+    }
+    None
+}
+
+fn extend_ttl_u64_key(env: &Env, key: &(soroban_sdk::Symbol, u64)) {
+    env.storage()
+        .persistent()
+        .extend_ttl(key, TTL_THRESHOLD, TTL_EXTEND_TO);
 }
 
 // ======================== Policy Engine Integration ========================
@@ -937,14 +1055,20 @@ pub fn check_policy_engine(
 }
 
 /// Runs a policy simulation without side-effects, useful for what-if analysis.
+/// With `explain`, the result also carries the evaluation trace.
 pub fn simulate_policy_check(
     env: &Env,
     user: &Address,
     action: &str,
     resource_id: Option<u64>,
+    explain: bool,
 ) -> teye_common::policy_dsl::SimulationResult {
     let ctx = build_eval_context(env, user, action, resource_id);
-    teye_common::policy_engine::simulate(env, &ctx)
+    if explain {
+        teye_common::policy_engine::explain(env, &ctx)
+    } else {
+        teye_common::policy_engine::simulate(env, &ctx)
+    }
 }
 
 // ── Numeric helpers for on-chain string building ────────────────────────────
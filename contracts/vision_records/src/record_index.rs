@@ -0,0 +1,264 @@
+//! Record indexes: by patient, by provider, by patient and record type,
+//! and by provider and creation day. Each index is a list of record ids in
+//! creation order, stored in buckets (see [`BucketList`]) and appended to
+//! whenever a record is created.
+//!
+//! Records created before the provider, type and day indexes existed are
+//! added by [`backfill`], into separate lists that are read as the head of
+//! each index so the combined list stays in creation order.
+
+use soroban_sdk::{symbol_short, Address, Env, Symbol, TryFromVal, Val, Vec};
+
+use crate::pagination::{self, BucketList, RecordPage};
+use crate::{RecordType, VisionRecord};
+
+// ── Storage keys ──────────────────────────────────────────────
+/// Monolithic per-patient list written before the list was bucketed. It is
+/// no longer appended to and is read as the head of the patient's list.
+const PAT_REC: Symbol = symbol_short!("PAT_REC");
+const PAT_RECB: Symbol = symbol_short!("PAT_RECB");
+const IX_PROV: Symbol = symbol_short!("IX_PROV");
+const IX_PTYP: Symbol = symbol_short!("IX_PTYP");
+const IX_DAY: Symbol = symbol_short!("IX_DAY");
+const BF_PROV: Symbol = symbol_short!("BF_PROV");
+const BF_PTYP: Symbol = symbol_short!("BF_PTYP");
+const BF_DAY: Symbol = symbol_short!("BF_DAY");
+/// Id of the first record indexed when it was created. Older records are
+/// left to [`backfill`].
+const IX_FROM: Symbol = symbol_short!("IX_FROM");
+/// Id of the next record [`backfill`] will index.
+const IX_BFILL: Symbol = symbol_short!("IX_BFILL");
+
+/// Most records [`backfill`] indexes in one call.
+pub const MAX_BACKFILL_BATCH: u32 = 5;
+
+pub const DAY_SECONDS: u64 = 86_400;
+
+/// Day bucket (days since the Unix epoch, UTC) of a ledger timestamp.
+pub fn day_bucket(timestamp: u64) -> u64 {
+    timestamp / DAY_SECONDS
+}
+
+fn patient_list(patient: &Address) -> BucketList<Address> {
+    BucketList::new(PAT_RECB, patient.clone())
+}
+
+fn legacy_patient_records(env: &Env, patient: &Address) -> Vec<u64> {
+    env.storage()
+        .persistent()
+        .get(&(PAT_REC, patient.clone()))
+        .unwrap_or(Vec::new(env))
+}
+
+/// An index made of the records backfilled into it followed by the records
+/// appended to it as they were created.
+struct RecordIndex<K> {
+    backfilled: BucketList<K>,
+    live: BucketList<K>,
+}
+
+fn provider_index(provider: &Address) -> RecordIndex<Address> {
+    RecordIndex {
+        backfilled: BucketList::new(BF_PROV, provider.clone()),
+        live: BucketList::new(IX_PROV, provider.clone()),
+    }
+}
+
+fn patient_type_index(
+    patient: &Address,
+    record_type: &RecordType,
+) -> RecordIndex<(Address, RecordType)> {
+    let owner = (patient.clone(), record_type.clone());
+    RecordIndex {
+        backfilled: BucketList::new(BF_PTYP, owner.clone()),
+        live: BucketList::new(IX_PTYP, owner),
+    }
+}
+
+fn day_index(provider: &Address, day: u64) -> RecordIndex<(Address, u64)> {
+    let owner = (provider.clone(), day);
+    RecordIndex {
+        backfilled: BucketList::new(BF_DAY, owner.clone()),
+        live: BucketList::new(IX_DAY, owner),
+    }
+}
+
+/// Page through a list made of `head_len` ids read with `head` followed by
+/// the ids in `tail`; see [`pagination::page_positions`].
+#[allow(clippy::too_many_arguments)]
+fn page_joined<K>(
+    env: &Env,
+    head_len: u64,
+    mut head: impl FnMut(u64) -> Option<u64>,
+    tail: &BucketList<K>,
+    cursor: Option<u64>,
+    limit: u32,
+    newest_first: bool,
+    mut keep: impl FnMut(u64) -> bool,
+) -> RecordPage
+where
+    K: Clone,
+    Val: TryFromVal<Env, K>,
+{
+    let mut read_tail = tail.reader::<u64>(env);
+    let (record_ids, next_cursor) = pagination::page_positions(
+        env,
+        head_len + tail.len(env),
+        cursor,
+        limit,
+        newest_first,
+        |pos| {
+            if pos < head_len {
+                head(pos)
+            } else {
+                read_tail(pos - head_len)
+            }
+        },
+        |id| keep(*id),
+    );
+    RecordPage {
+        record_ids,
+        next_cursor,
+    }
+}
+
+impl<K> RecordIndex<K>
+where
+    K: Clone,
+    Val: TryFromVal<Env, K>,
+{
+    fn page(
+        &self,
+        env: &Env,
+        cursor: Option<u64>,
+        limit: u32,
+        newest_first: bool,
+        keep: impl FnMut(u64) -> bool,
+    ) -> RecordPage {
+        page_joined(
+            env,
+            self.backfilled.len(env),
+            self.backfilled.reader::<u64>(env),
+            &self.live,
+            cursor,
+            limit,
+            newest_first,
+            keep,
+        )
+    }
+}
+
+/// Number of records created for `patient`.
+pub fn patient_record_count(env: &Env, patient: &Address) -> u64 {
+    u64::from(legacy_patient_records(env, patient).len()) + patient_list(patient).len(env)
+}
+
+/// Page through a patient's record ids in creation order, keeping the ids
+/// `keep` accepts; see [`pagination::page_positions`].
+pub fn page_patient_records(
+    env: &Env,
+    patient: &Address,
+    cursor: Option<u64>,
+    limit: u32,
+    newest_first: bool,
+    keep: impl FnMut(u64) -> bool,
+) -> RecordPage {
+    let legacy = legacy_patient_records(env, patient);
+    page_joined(
+        env,
+        u64::from(legacy.len()),
+        |pos| legacy.get(pos as u32),
+        &patient_list(patient),
+        cursor,
+        limit,
+        newest_first,
+        keep,
+    )
+}
+
+/// Adds a newly created record to every index.
+pub fn index_record(env: &Env, record: &VisionRecord) {
+    if !env.storage().instance().has(&IX_FROM) {
+        env.storage().instance().set(&IX_FROM, &record.id);
+    }
+    patient_list(&record.patient).push(env, &record.id);
+    provider_index(&record.provider).live.push(env, &record.id);
+    patient_type_index(&record.patient, &record.record_type)
+        .live
+        .push(env, &record.id);
+    day_index(&record.provider, day_bucket(record.created_at))
+        .live
+        .push(env, &record.id);
+}
+
+/// Adds up to `max_records` of the records created before the provider,
+/// type and day indexes existed to those indexes, oldest first, resuming
+/// where the previous call stopped. `last_record_id` is the id of the
+/// newest record. Returns the number of records still to backfill.
+pub fn backfill(env: &Env, last_record_id: u64, max_records: u32) -> u64 {
+    let end: u64 = env
+        .storage()
+        .instance()
+        .get(&IX_FROM)
+        .unwrap_or_else(|| last_record_id.saturating_add(1));
+    env.storage().instance().set(&IX_FROM, &end);
+
+    let mut next: u64 = env.storage().instance().get(&IX_BFILL).unwrap_or(1);
+    let mut done = 0;
+    while next < end && done < max_records {
+        let key = (symbol_short!("RECORD"), next);
+        if let Some(record) = env.storage().persistent().get::<_, VisionRecord>(&key) {
+            provider_index(&record.provider)
+                .backfilled
+                .push(env, &record.id);
+            patient_type_index(&record.patient, &record.record_type)
+                .backfilled
+                .push(env, &record.id);
+            day_index(&record.provider, day_bucket(record.created_at))
+                .backfilled
+                .push(env, &record.id);
+        }
+        next += 1;
+        done += 1;
+    }
+    env.storage().instance().set(&IX_BFILL, &next);
+
+    end.saturating_sub(next)
+}
+
+/// Page through the records `provider` created; see
+/// [`pagination::page_positions`].
+pub fn page_provider_records(
+    env: &Env,
+    provider: &Address,
+    cursor: Option<u64>,
+    limit: u32,
+    newest_first: bool,
+    keep: impl FnMut(u64) -> bool,
+) -> RecordPage {
+    provider_index(provider).page(env, cursor, limit, newest_first, keep)
+}
+
+/// Page through a patient's records of one type.
+pub fn page_patient_type_records(
+    env: &Env,
+    patient: &Address,
+    record_type: &RecordType,
+    cursor: Option<u64>,
+    limit: u32,
+    newest_first: bool,
+) -> RecordPage {
+    patient_type_index(patient, record_type).page(env, cursor, limit, newest_first, |_| true)
+}
+
+/// Page through the records `provider` created on `day`.
+pub fn page_day_records(
+    env: &Env,
+    provider: &Address,
+    day: u64,
+    cursor: Option<u64>,
+    limit: u32,
+    newest_first: bool,
+) -> RecordPage {
+    day_index(provider, day).page(env, cursor, limit, newest_first, |_| true)
+}
@@ -0,0 +1,130 @@
+//! Provider-to-provider referrals for second opinions.
+//!
+//! A referring provider asks a specialist to review some of a patient's
+//! records. Nothing is shared until the patient approves, at which point the
+//! specialist receives record-level read grants. The specialist closes the
+//! loop by linking a response record, which the referring provider may
+//! then read.
+
+use crate::pagination::BucketList;
+use soroban_sdk::{contracttype, symbol_short, Address, Env, String, Symbol, Vec};
+
+// ── Storage keys ──────────────────────────────────────────────
+const REF_CTR: Symbol = symbol_short!("REF_CTR");
+const REF: Symbol = symbol_short!("REF");
+const REF_USR: Symbol = symbol_short!("REF_USR");
+
+const TTL_THRESHOLD: u32 = 5184000;
+const TTL_EXTEND_TO: u32 = 10368000;
+
+/// Most records one referral may cover.
+pub const MAX_REFERRAL_RECORDS: u32 = 20;
+
+// ── Types ─────────────────────────────────────────────────────
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[repr(u32)]
+pub enum ReferralStatus {
+    /// Awaiting the patient's approval.
+    Pending = 1,
+    /// Approved; the specialist can read the referred records.
+    Approved = 2,
+    Declined = 3,
+    /// The specialist has linked a response record.
+    Completed = 4,
+    /// Withdrawn by the referring provider.
+    Cancelled = 5,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Referral {
+    pub id: u64,
+    pub from_provider: Address,
+    pub to_provider: Address,
+    pub patient: Address,
+    pub record_ids: Vec<u64>,
+    /// Content hash or off-chain reference of the referral letter; the
+    /// clinical reason itself is never stored on-chain.
+    pub reason_hash: String,
+    pub status: ReferralStatus,
+    pub created_at: u64,
+    pub updated_at: u64,
+    /// How long grants made for this referral last; set on approval.
+    pub access_duration: u64,
+    pub approved_at: u64,
+    /// Records the approval granted the specialist access to. Records they
+    /// could already read are left out.
+    pub granted_record_ids: Vec<u64>,
+    pub response_record_id: Option<u64>,
+}
+
+impl Referral {
+    /// Whether the referral can still move on; declined, completed and
+    /// cancelled referrals are final.
+    pub fn is_open(&self) -> bool {
+        matches!(
+            self.status,
+            ReferralStatus::Pending | ReferralStatus::Approved
+        )
+    }
+}
+
+// ── Storage ───────────────────────────────────────────────────
+
+pub fn next_id(env: &Env) -> u64 {
+    let id: u64 = env
+        .storage()
+        .instance()
+        .get(&REF_CTR)
+        .unwrap_or(0u64)
+        .saturating_add(1);
+    env.storage().instance().set(&REF_CTR, &id);
+    id
+}
+
+pub fn get_referral(env: &Env, referral_id: u64) -> Option<Referral> {
+    env.storage().persistent().get(&(REF, referral_id))
+}
+
+pub fn set_referral(env: &Env, referral: &Referral) {
+    let key = (REF, referral.id);
+    env.storage().persistent().set(&key, referral);
+    env.storage()
+        .persistent()
+        .extend_ttl(&key, TTL_THRESHOLD, TTL_EXTEND_TO);
+}
+
+/// Stores a new referral and lists it for all three participants.
+pub fn insert_referral(env: &Env, referral: &Referral) {
+    set_referral(env, referral);
+    for user in [
+        &referral.from_provider,
+        &referral.to_provider,
+        &referral.patient,
+    ] {
+        user_list(user).push(env, &referral.id);
+    }
+}
+
+fn user_list(user: &Address) -> BucketList<Address> {
+    BucketList::new(REF_USR, user.clone())
+}
+
+/// Page through the referrals `user` takes part in, oldest first.
+pub fn page_user_referrals(
+    env: &Env,
+    user: &Address,
+    cursor: Option<u64>,
+    limit: u32,
+) -> (Vec<Referral>, Option<u64>) {
+    user_list(user).page_map(
+        env,
+        cursor,
+        limit,
+        false,
+        |id| get_referral(env, id),
+        |_| true,
+    )
+}
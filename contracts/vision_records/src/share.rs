@@ -0,0 +1,136 @@
+//! Patient-issued share tokens.
+//!
+//! A share token commits to an ed25519 public key whose signing key the
+//! patient hands out off-chain (e.g. as a QR code). The holder redeems the
+//! token once, before it expires, by signing [`redemption_message`] for
+//! their own address, and receives ordinary record-level access grants for
+//! the records it covers. The signing key never appears on-chain, and a
+//! signature is only good for the address it names, so a pending
+//! redemption cannot be replayed by someone else.
+
+use crate::pagination::BucketList;
+use crate::AccessLevel;
+use soroban_sdk::{
+    contracttype, symbol_short, xdr::ToXdr, Address, Bytes, BytesN, Env, Symbol, Vec,
+};
+
+// ── Storage keys ──────────────────────────────────────────────
+const SHR_CTR: Symbol = symbol_short!("SHR_CTR");
+const SHR_TOK: Symbol = symbol_short!("SHR_TOK");
+const SHR_KEY: Symbol = symbol_short!("SHR_KEY");
+const SHR_PAT: Symbol = symbol_short!("SHR_PAT");
+
+const TTL_THRESHOLD: u32 = 5184000;
+const TTL_EXTEND_TO: u32 = 10368000;
+
+/// Most records one token may cover.
+pub const MAX_SHARED_RECORDS: u32 = 20;
+
+// ── Types ─────────────────────────────────────────────────────
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[repr(u32)]
+pub enum ShareTokenStatus {
+    Active = 1,
+    Redeemed = 2,
+    Revoked = 3,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ShareToken {
+    pub id: u64,
+    pub patient: Address,
+    /// ed25519 public key whose signing key redeems the token.
+    pub share_key: BytesN<32>,
+    pub record_ids: Vec<u64>,
+    pub level: AccessLevel,
+    /// How long the resulting grants last, counted from redemption.
+    pub access_duration: u64,
+    pub created_at: u64,
+    /// The token must be redeemed before this time.
+    pub expires_at: u64,
+    pub status: ShareTokenStatus,
+    pub redeemed_by: Option<Address>,
+    pub redeemed_at: u64,
+    /// Records the redemption granted access to. Records the redeemer
+    /// could already access at the token's level or above are left out.
+    pub granted_record_ids: Vec<u64>,
+}
+
+/// The message a redeemer signs with the token's signing key:
+/// `"redeem_share_token" || share_key || redeemer` (the address as XDR).
+pub fn redemption_message(env: &Env, share_key: &BytesN<32>, redeemer: &Address) -> Bytes {
+    let mut msg = Bytes::from_slice(env, b"redeem_share_token");
+    msg.append(&Bytes::from_slice(env, &share_key.to_array()));
+    msg.append(&redeemer.clone().to_xdr(env));
+    msg
+}
+
+// ── Storage ───────────────────────────────────────────────────
+
+pub fn next_id(env: &Env) -> u64 {
+    let id: u64 = env
+        .storage()
+        .instance()
+        .get(&SHR_CTR)
+        .unwrap_or(0u64)
+        .saturating_add(1);
+    env.storage().instance().set(&SHR_CTR, &id);
+    id
+}
+
+pub fn get_token(env: &Env, token_id: u64) -> Option<ShareToken> {
+    env.storage().persistent().get(&(SHR_TOK, token_id))
+}
+
+pub fn set_token(env: &Env, token: &ShareToken) {
+    let key = (SHR_TOK, token.id);
+    env.storage().persistent().set(&key, token);
+    env.storage()
+        .persistent()
+        .extend_ttl(&key, TTL_THRESHOLD, TTL_EXTEND_TO);
+}
+
+/// Stores a new token, indexing it by share key and by patient.
+pub fn insert_token(env: &Env, token: &ShareToken) {
+    set_token(env, token);
+
+    let share_key = (SHR_KEY, token.share_key.clone());
+    env.storage().persistent().set(&share_key, &token.id);
+    env.storage()
+        .persistent()
+        .extend_ttl(&share_key, TTL_THRESHOLD, TTL_EXTEND_TO);
+
+    patient_list(&token.patient).push(env, &token.id);
+}
+
+/// Whether any token, in any state, was issued for `share_key`.
+pub fn key_in_use(env: &Env, share_key: &BytesN<32>) -> bool {
+    env.storage()
+        .persistent()
+        .has(&(SHR_KEY, share_key.clone()))
+}
+
+pub fn find_by_key(env: &Env, share_key: &BytesN<32>) -> Option<ShareToken> {
+    let id: u64 = env
+        .storage()
+        .persistent()
+        .get(&(SHR_KEY, share_key.clone()))?;
+    get_token(env, id)
+}
+
+fn patient_list(patient: &Address) -> BucketList<Address> {
+    BucketList::new(SHR_PAT, patient.clone())
+}
+
+/// Page through the tokens the patient has issued, oldest first.
+pub fn page_patient_tokens(
+    env: &Env,
+    patient: &Address,
+    cursor: Option<u64>,
+    limit: u32,
+) -> (Vec<ShareToken>, Option<u64>) {
+    patient_list(patient).page_map(env, cursor, limit, false, |id| get_token(env, id), |_| true)
+}
@@ -8,6 +8,7 @@
 )]
 
 use super::*;
+use crate::test_support::verify_provider;
 use soroban_sdk::testutils::{Address as _, Ledger};
 use soroban_sdk::Env;
 
@@ -38,6 +39,7 @@ fn test_whitelist_register_user_and_add_record_enforcement() {
     let user = Address::generate(&env);
     let patient = Address::generate(&env);
     let provider = Address::generate(&env);
+    verify_provider(&env, &client, &admin, &provider);
     let data_hash = String::from_str(&env, "QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG");
 
     client.set_whitelist_enabled(&admin, &true);
@@ -119,6 +121,7 @@ fn test_whitelist_enforced_for_add_records_batch() {
         &Role::Optometrist,
         &String::from_str(&env, "Provider"),
     );
+    verify_provider(&env, &client, &admin, &provider);
 
     client.set_whitelist_enabled(&admin, &true);
     assert!(!client.is_whitelisted(&provider));
@@ -188,11 +191,12 @@ fn test_rate_limit_add_record_and_grant_access() {
     let admin = Address::generate(&env);
     client.initialize(&admin);
 
-    // Configure a small window for testing
-    client.set_rate_limit_config(&admin, &2, &60, &0);
-
     let patient = Address::generate(&env);
     let provider = Address::generate(&env);
+    verify_provider(&env, &client, &admin, &provider);
+
+    // Configure a small window for testing
+    client.set_rate_limit_config(&admin, &2, &60, &0);
     let data_hash = String::from_str(&env, "QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG");
 
     // First two record additions should succeed
@@ -352,6 +356,7 @@ fn test_get_record_consent_required() {
 
     let patient = Address::generate(&env);
     let provider = Address::generate(&env);
+    verify_provider(&env, &client, &admin, &provider);
     let doctor = Address::generate(&env);
     let data_hash = String::from_str(&env, "QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG");
 
@@ -0,0 +1,209 @@
+#![allow(
+    clippy::unwrap_used,
+    clippy::expect_used,
+    clippy::panic,
+    clippy::arithmetic_side_effects
+)]
+
+use super::{
+    AccessAction, AccessPolicy, AccessResult, AuditFilter, ConsentType, ContractError,
+    CredentialType, PolicyConditions, PolicyTarget, RecordRead, RecordType, Role, SensitivityLevel,
+    TimeRestriction,
+};
+use crate::test_support::{self, TestContext};
+use soroban_sdk::{testutils::Ledger as _, Address, Env, String, Vec};
+
+const HOUR: u64 = 3_600;
+/// 10:00 UTC on a weekday.
+const CLINIC_OPEN: u64 = 20_000 * 86_400 + 10 * HOUR;
+
+// ── Helpers ──────────────────────────────────────────────────────
+
+/// The provider holds a medical license and wrote a surgery record for the
+/// patient; `unlicensed` is a second verified provider without one.
+fn setup() -> (TestContext, Address, u64) {
+    let ctx = test_support::setup();
+    ctx.env.ledger().set_timestamp(CLINIC_OPEN);
+    ctx.client
+        .set_user_credential(&ctx.admin, &ctx.provider, &CredentialType::MedicalLicense);
+    let unlicensed = ctx.register(Role::Optometrist, "Dr. Pending");
+    let record_id = ctx.add_record(RecordType::Surgery);
+    (ctx, unlicensed, record_id)
+}
+
+/// Restricted records are only readable by licensed staff in clinic hours.
+fn clinic_hours_policy(env: &Env) -> AccessPolicy {
+    AccessPolicy {
+        id: String::from_str(env, "restricted_clinic_hours"),
+        name: String::from_str(env, "Licensed staff, clinic hours"),
+        conditions: PolicyConditions {
+            required_role: Role::None,
+            time_restriction: TimeRestriction::BusinessHours,
+            required_credential: CredentialType::MedicalLicense,
+            min_sensitivity_level: SensitivityLevel::Restricted,
+            consent_required: false,
+        },
+        enabled: true,
+    }
+}
+
+fn restrict(ctx: &TestContext, record_id: u64) {
+    let policy = clinic_hours_policy(&ctx.env);
+    ctx.client.create_access_policy(&ctx.admin, &policy);
+    ctx.client.attach_access_policy(
+        &ctx.admin,
+        &PolicyTarget::Sensitivity(SensitivityLevel::Restricted),
+        &policy.id,
+    );
+    ctx.client
+        .set_record_sensitivity(&ctx.admin, &record_id, &SensitivityLevel::Restricted);
+}
+
+// ======================== Enforcement ========================
+
+#[test]
+fn test_restricted_records_need_license_and_clinic_hours() {
+    let (ctx, unlicensed, record_id) = setup();
+    // Unclassified records are not affected.
+    ctx.client.get_record(&unlicensed, &record_id);
+
+    restrict(&ctx, record_id);
+    ctx.client.get_record(&ctx.provider, &record_id);
+    let denied = ctx.client.try_get_record(&unlicensed, &record_id);
+    assert_eq!(denied.unwrap_err().unwrap(), ContractError::AccessDenied);
+
+    // After hours even licensed staff are turned away; the patient is not.
+    ctx.env.ledger().set_timestamp(CLINIC_OPEN + 10 * HOUR);
+    let denied = ctx.client.try_get_record(&ctx.provider, &record_id);
+    assert_eq!(denied.unwrap_err().unwrap(), ContractError::AccessDenied);
+    ctx.client.get_record(&ctx.patient, &record_id);
+
+    // Batch reads, attachments and history are held to the same policies.
+    ctx.env.ledger().set_timestamp(CLINIC_OPEN);
+    let ids = Vec::from_array(&ctx.env, [record_id]);
+    let denied = ctx.client.try_get_records(&unlicensed, &ids);
+    assert_eq!(denied.unwrap_err().unwrap(), ContractError::AccessDenied);
+    let denied = ctx
+        .client
+        .try_get_attachment_manifest(&unlicensed, &record_id);
+    assert_eq!(denied.unwrap_err().unwrap(), ContractError::AccessDenied);
+    let denied = ctx.client.try_get_record_history(&unlicensed, &record_id);
+    assert_eq!(denied.unwrap_err().unwrap(), ContractError::AccessDenied);
+    assert_eq!(ctx.client.get_records(&ctx.provider, &ids).len(), 1);
+
+    // Downgrading the record lifts the sensitivity-level policy.
+    ctx.client
+        .set_record_sensitivity(&ctx.admin, &record_id, &SensitivityLevel::Confidential);
+    ctx.client.get_record(&ctx.provider, &record_id);
+}
+
+#[test]
+fn test_record_policy_denial_is_audited() {
+    let (ctx, unlicensed, record_id) = setup();
+    let mut policy = clinic_hours_policy(&ctx.env);
+    policy.conditions.min_sensitivity_level = SensitivityLevel::Public;
+    ctx.client.create_access_policy(&ctx.admin, &policy);
+    let target = PolicyTarget::Record(record_id);
+    ctx.client
+        .attach_access_policy(&ctx.admin, &target, &policy.id);
+    assert_eq!(
+        ctx.client.get_attached_policies(&target),
+        Vec::from_array(&ctx.env, [policy.id.clone()])
+    );
+
+    // A purpose-declared read returns the refusal, so its audit entry is kept.
+    let result =
+        ctx.client
+            .try_get_record_for_purpose(&unlicensed, &record_id, &ConsentType::Treatment);
+    assert!(matches!(
+        result.unwrap().unwrap(),
+        RecordRead::Denied(code) if code == ContractError::AccessDenied as u32
+    ));
+    let filter = AuditFilter {
+        patient: Some(ctx.patient.clone()),
+        actor: Some(unlicensed.clone()),
+        record_id: Some(record_id),
+        actions: Vec::from_array(&ctx.env, [AccessAction::Read]),
+        results: Vec::from_array(&ctx.env, [AccessResult::Denied]),
+        from: None,
+        to: None,
+    };
+    let entries = ctx
+        .client
+        .get_audit_log(&ctx.patient, &filter, &None, &10)
+        .entries;
+    assert_eq!(
+        entries.get(0).unwrap().reason,
+        Some(String::from_str(
+            &ctx.env,
+            "Access policy not satisfied: restricted_clinic_hours"
+        ))
+    );
+
+    // Disabled policies are not enforced.
+    policy.enabled = false;
+    ctx.client.create_access_policy(&ctx.admin, &policy);
+    ctx.client.get_record(&unlicensed, &record_id);
+}
+
+// ======================== Administration ========================
+
+#[test]
+fn test_policy_administration() {
+    let (ctx, unlicensed, record_id) = setup();
+    let policy = clinic_hours_policy(&ctx.env);
+
+    let denied = ctx.client.try_create_access_policy(&ctx.provider, &policy);
+    assert_eq!(denied.unwrap_err().unwrap(), ContractError::Unauthorized);
+    let denied = ctx.client.try_set_user_credential(
+        &ctx.provider,
+        &unlicensed,
+        &CredentialType::MedicalLicense,
+    );
+    assert_eq!(denied.unwrap_err().unwrap(), ContractError::Unauthorized);
+
+    let mut bad_hours = policy.clone();
+    bad_hours.conditions.time_restriction = TimeRestriction::HourRange(8, 24);
+    let invalid = ctx.client.try_create_access_policy(&ctx.admin, &bad_hours);
+    assert_eq!(invalid.unwrap_err().unwrap(), ContractError::InvalidInput);
+
+    let target = PolicyTarget::Record(record_id);
+    let unknown = ctx
+        .client
+        .try_attach_access_policy(&ctx.admin, &target, &policy.id);
+    assert_eq!(unknown.unwrap_err().unwrap(), ContractError::InvalidInput);
+    ctx.client.create_access_policy(&ctx.admin, &policy);
+    ctx.client
+        .attach_access_policy(&ctx.admin, &target, &policy.id);
+    let twice = ctx
+        .client
+        .try_attach_access_policy(&ctx.admin, &target, &policy.id);
+    assert_eq!(twice.unwrap_err().unwrap(), ContractError::DuplicateRecord);
+    let missing = ctx.client.try_attach_access_policy(
+        &ctx.admin,
+        &PolicyTarget::Record(record_id + 1),
+        &policy.id,
+    );
+    assert_eq!(missing.unwrap_err().unwrap(), ContractError::RecordNotFound);
+
+    // An attached policy cannot be deleted, so re-creating its id later
+    // cannot revive a forgotten attachment.
+    let attached = ctx.client.try_remove_access_policy(&ctx.admin, &policy.id);
+    assert_eq!(
+        attached.unwrap_err().unwrap(),
+        ContractError::PolicyAttached
+    );
+    ctx.client
+        .detach_access_policy(&ctx.admin, &target, &policy.id);
+    assert!(ctx.client.get_attached_policies(&target).is_empty());
+    ctx.client.remove_access_policy(&ctx.admin, &policy.id);
+    assert!(ctx.client.get_access_policy(&policy.id).is_none());
+    assert_eq!(
+        ctx.client.get_user_credential(&ctx.provider),
+        CredentialType::MedicalLicense
+    );
+    assert_eq!(
+        ctx.client.get_record_sensitivity(&record_id),
+        SensitivityLevel::Standard
+    );
+}
@@ -0,0 +1,214 @@
+#![allow(
+    clippy::unwrap_used,
+    clippy::expect_used,
+    clippy::panic,
+    clippy::arithmetic_side_effects
+)]
+
+use super::{
+    AccessLevel, AccessReport, AccessReportEntry, AccessorKind, ConsentType, ContractError,
+    EmergencyCondition, RecordType, Role,
+};
+use crate::audit::MAX_REPORT_SCAN;
+use crate::test_support::{self, TestContext};
+use soroban_sdk::{testutils::Address as _, Address, String};
+
+const HOUR: u64 = 3_600;
+
+// ── Helpers ──────────────────────────────────────────────────────
+
+fn setup() -> (TestContext, u64) {
+    let ctx = test_support::setup();
+    let record_id = ctx.add_record(RecordType::Examination);
+    (ctx, record_id)
+}
+
+fn entry_for(report: &AccessReport, accessor: &Address) -> AccessReportEntry {
+    report
+        .entries
+        .iter()
+        .find(|e| e.accessor == *accessor)
+        .expect("accessor missing from report")
+}
+
+// ======================== Report contents ========================
+
+#[test]
+fn test_access_report_aggregates_per_accessor() {
+    let (ctx, record_id) = setup();
+    let grantee = Address::generate(&ctx.env);
+    let nurse = ctx.register(Role::Staff, "ER Nurse");
+
+    ctx.client.grant_consent(
+        &ctx.patient,
+        &grantee,
+        &ConsentType::Treatment,
+        &(30 * 24 * HOUR),
+    );
+    ctx.client.grant_access(
+        &ctx.patient,
+        &ctx.patient,
+        &grantee,
+        &AccessLevel::Read,
+        &(30 * 24 * HOUR),
+    );
+    ctx.advance(HOUR);
+    ctx.client.get_record(&grantee, &record_id);
+    ctx.advance(HOUR);
+    ctx.client.get_record(&grantee, &record_id);
+
+    ctx.client.request_emergency_access(
+        &nurse,
+        &ctx.patient,
+        &EmergencyCondition::Unconscious,
+        &String::from_str(&ctx.env, "Unconscious on arrival"),
+        &HOUR,
+    );
+    ctx.advance(60);
+    ctx.client.get_record(&nurse, &record_id);
+    ctx.client.get_record(&ctx.admin, &record_id);
+    ctx.client.get_record(&ctx.provider, &record_id);
+    // The patient's own reads are not disclosures.
+    ctx.client.get_record(&ctx.patient, &record_id);
+
+    let now = ctx.env.ledger().timestamp();
+    let report = ctx
+        .client
+        .get_access_report(&ctx.patient, &ctx.patient, &0, &now, &None);
+    assert_eq!(report.next_cursor, None);
+
+    let g = entry_for(&report, &grantee);
+    assert_eq!(g.kind, AccessorKind::Grantee);
+    assert_eq!(g.access_count, 2);
+    assert_eq!(g.last_access, 10_000 + 2 * HOUR);
+
+    let n = entry_for(&report, &nurse);
+    assert_eq!(n.kind, AccessorKind::Emergency);
+    assert_eq!(n.access_count, 1);
+    assert_eq!(n.last_access, now);
+
+    assert_eq!(
+        entry_for(&report, &ctx.admin).kind,
+        AccessorKind::Privileged
+    );
+    // Optometrists read through their role's ReadAnyRecord permission.
+    assert_eq!(
+        entry_for(&report, &ctx.provider).kind,
+        AccessorKind::Privileged
+    );
+    assert!(!report.entries.iter().any(|e| e.accessor == ctx.patient));
+}
+
+#[test]
+fn test_access_report_respects_window() {
+    let (ctx, record_id) = setup();
+    ctx.advance(HOUR);
+    ctx.client.get_record(&ctx.admin, &record_id);
+    ctx.advance(HOUR);
+    ctx.client.get_record(&ctx.admin, &record_id);
+    ctx.advance(HOUR);
+    ctx.client.get_record(&ctx.admin, &record_id);
+
+    let second = 10_000 + 2 * HOUR;
+    let report = ctx
+        .client
+        .get_access_report(&ctx.patient, &ctx.patient, &second, &second, &None);
+    assert_eq!(report.entries.len(), 1);
+    let entry = report.entries.get(0).unwrap();
+    assert_eq!(entry.access_count, 1);
+    assert_eq!(entry.last_access, second);
+
+    let before =
+        ctx.client
+            .get_access_report(&ctx.patient, &ctx.patient, &0, &(10_000 + HOUR - 1), &None);
+    assert!(before.entries.iter().all(|e| e.accessor != ctx.admin));
+
+    let inverted = ctx
+        .client
+        .try_get_access_report(&ctx.patient, &ctx.patient, &second, &0, &None);
+    assert_eq!(inverted.unwrap_err().unwrap(), ContractError::InvalidInput);
+}
+
+#[test]
+fn test_access_report_keeps_kind_at_access_time() {
+    let (ctx, record_id) = setup();
+    let grantee = Address::generate(&ctx.env);
+    ctx.client.grant_consent(
+        &ctx.patient,
+        &grantee,
+        &ConsentType::Treatment,
+        &(30 * 24 * HOUR),
+    );
+    ctx.client.grant_access(
+        &ctx.patient,
+        &ctx.patient,
+        &grantee,
+        &AccessLevel::Read,
+        &(30 * 24 * HOUR),
+    );
+    ctx.advance(HOUR);
+    ctx.client.get_record(&grantee, &record_id);
+
+    // Ending the relationship does not rewrite how the read happened.
+    ctx.client.revoke_consent(&ctx.patient, &grantee);
+    ctx.client.revoke_access(&ctx.patient, &grantee);
+
+    let now = ctx.env.ledger().timestamp();
+    let report = ctx
+        .client
+        .get_access_report(&ctx.patient, &ctx.patient, &0, &now, &None);
+    let g = entry_for(&report, &grantee);
+    assert_eq!(g.kind, AccessorKind::Grantee);
+    assert_eq!(g.access_count, 1);
+}
+
+#[test]
+fn test_access_report_pages_through_window() {
+    let (ctx, record_id) = setup();
+    for _ in 0..MAX_REPORT_SCAN + 5 {
+        ctx.advance(60);
+        ctx.client.get_record(&ctx.admin, &record_id);
+    }
+    let now = ctx.env.ledger().timestamp();
+
+    let first = ctx
+        .client
+        .get_access_report(&ctx.patient, &ctx.patient, &0, &now, &None);
+    assert!(first.next_cursor.is_some());
+    let second =
+        ctx.client
+            .get_access_report(&ctx.patient, &ctx.patient, &0, &now, &first.next_cursor);
+    assert_eq!(second.next_cursor, None);
+
+    let total =
+        entry_for(&first, &ctx.admin).access_count + entry_for(&second, &ctx.admin).access_count;
+    assert_eq!(total, (MAX_REPORT_SCAN + 5) as u32);
+    assert_eq!(entry_for(&second, &ctx.admin).last_access, now);
+}
+
+// ======================== Authorization ========================
+
+#[test]
+fn test_access_report_patient_or_delegate_only() {
+    let (ctx, _) = setup();
+    let delegate = Address::generate(&ctx.env);
+    let now = ctx.env.ledger().timestamp();
+
+    let denied = ctx
+        .client
+        .try_get_access_report(&delegate, &ctx.patient, &0, &now, &None);
+    assert_eq!(denied.unwrap_err().unwrap(), ContractError::Unauthorized);
+
+    // Even the record's provider cannot see the patient's disclosures.
+    let denied = ctx
+        .client
+        .try_get_access_report(&ctx.provider, &ctx.patient, &0, &now, &None);
+    assert_eq!(denied.unwrap_err().unwrap(), ContractError::Unauthorized);
+
+    ctx.client
+        .delegate_role(&ctx.patient, &delegate, &Role::Patient, &0);
+    let report = ctx
+        .client
+        .get_access_report(&delegate, &ctx.patient, &0, &now, &None);
+    assert_eq!(report.patient, ctx.patient);
+}
@@ -0,0 +1,197 @@
+#![allow(
+    clippy::unwrap_used,
+    clippy::expect_used,
+    clippy::panic,
+    clippy::arithmetic_side_effects
+)]
+
+use super::{ContractError, RecordType, RetractionReason};
+use crate::test_support::{self, TestContext};
+use soroban_sdk::{testutils::Address as _, testutils::Ledger as _, Address, String};
+use teye_common::concurrency::UpdateOutcome;
+
+const ORIGINAL_HASH: &str = test_support::DATA_HASH;
+const AMENDED_HASH: &str = "QmT78zSuBmuS4z925WZfrqQ1qHaJ56DQaTfyMUF7F8ff5o";
+
+// ── Helpers ──────────────────────────────────────────────────────
+
+fn setup() -> (TestContext, u64) {
+    let ctx = test_support::setup();
+    let record_id = ctx.add_record(RecordType::Diagnosis);
+    (ctx, record_id)
+}
+
+fn amend(ctx: &TestContext, record_id: u64, reason: &str) -> UpdateOutcome {
+    let version = ctx.client.get_record_version_stamp(&record_id).version;
+    ctx.client.amend_record(
+        &ctx.provider,
+        &record_id,
+        &version,
+        &1,
+        &String::from_str(&ctx.env, AMENDED_HASH),
+        &String::from_str(&ctx.env, reason),
+    )
+}
+
+// ======================== Amendment ========================
+
+#[test]
+fn test_amend_record_keeps_version_chain() {
+    let (ctx, record_id) = setup();
+    ctx.env.ledger().set_timestamp(20_000);
+
+    let stamp = match amend(&ctx, record_id, "Revised diagnosis") {
+        UpdateOutcome::Applied(stamp) => stamp,
+        other => panic!("expected Applied, got {:?}", other),
+    };
+    assert_eq!(stamp.version, 2);
+
+    let record = ctx.client.get_record(&ctx.patient, &record_id);
+    assert_eq!(record.data_hash, String::from_str(&ctx.env, AMENDED_HASH));
+    assert_eq!(record.created_at, 10_000);
+    assert_eq!(record.updated_at, 20_000);
+
+    let history = ctx.client.get_record_history(&ctx.patient, &record_id);
+    assert_eq!(history.len(), 2);
+    let original = history.get(0).unwrap();
+    assert_eq!(original.version, 1);
+    assert_eq!(
+        original.data_hash,
+        String::from_str(&ctx.env, ORIGINAL_HASH)
+    );
+    assert_eq!(original.author, ctx.provider);
+    assert_eq!(original.created_at, 10_000);
+    let amended = history.get(1).unwrap();
+    assert_eq!(amended.version, 2);
+    assert_eq!(amended.data_hash, String::from_str(&ctx.env, AMENDED_HASH));
+    assert_eq!(
+        amended.reason,
+        String::from_str(&ctx.env, "Revised diagnosis")
+    );
+    assert_eq!(amended.created_at, 20_000);
+}
+
+#[test]
+fn test_unamended_record_history_is_current_version() {
+    let (ctx, record_id) = setup();
+    let history = ctx.client.get_record_history(&ctx.patient, &record_id);
+    assert_eq!(history.len(), 1);
+    assert_eq!(
+        history.get(0).unwrap().data_hash,
+        String::from_str(&ctx.env, ORIGINAL_HASH)
+    );
+
+    let stranger = Address::generate(&ctx.env);
+    let denied = ctx.client.try_get_record_history(&stranger, &record_id);
+    assert_eq!(denied.unwrap_err().unwrap(), ContractError::Unauthorized);
+}
+
+#[test]
+fn test_amend_record_requires_writer_and_reason() {
+    let (ctx, record_id) = setup();
+    let hash = String::from_str(&ctx.env, AMENDED_HASH);
+
+    let denied = ctx.client.try_amend_record(
+        &ctx.patient,
+        &record_id,
+        &1,
+        &1,
+        &hash,
+        &String::from_str(&ctx.env, "Patient request"),
+    );
+    assert_eq!(denied.unwrap_err().unwrap(), ContractError::Unauthorized);
+
+    let no_reason = ctx.client.try_amend_record(
+        &ctx.provider,
+        &record_id,
+        &1,
+        &1,
+        &hash,
+        &String::from_str(&ctx.env, ""),
+    );
+    assert_eq!(no_reason.unwrap_err().unwrap(), ContractError::InvalidInput);
+
+    let missing = ctx.client.try_amend_record(
+        &ctx.provider,
+        &99,
+        &1,
+        &1,
+        &hash,
+        &String::from_str(&ctx.env, "Typo"),
+    );
+    assert_eq!(missing.unwrap_err().unwrap(), ContractError::RecordNotFound);
+}
+
+// ======================== Retraction ========================
+
+#[test]
+fn test_retract_record_leaves_tombstone() {
+    let (ctx, record_id) = setup();
+    amend(&ctx, record_id, "Revised diagnosis");
+
+    ctx.client.retract_record(
+        &ctx.provider,
+        &record_id,
+        &RetractionReason::WrongPatient,
+        &String::from_str(&ctx.env, "Filed against the wrong chart"),
+    );
+
+    let tombstone = ctx.client.get_record_tombstone(&record_id).unwrap();
+    assert_eq!(tombstone.reason, RetractionReason::WrongPatient);
+    assert_eq!(tombstone.retracted_by, ctx.provider);
+    assert_eq!(tombstone.version, 3);
+
+    let read = ctx.client.try_get_record(&ctx.patient, &record_id);
+    assert_eq!(read.unwrap_err().unwrap(), ContractError::RecordRetracted);
+
+    // Provenance survives the retraction.
+    let history = ctx.client.get_record_history(&ctx.patient, &record_id);
+    assert_eq!(history.len(), 2);
+}
+
+#[test]
+fn test_retracted_record_cannot_change() {
+    let (ctx, record_id) = setup();
+    let note = String::from_str(&ctx.env, "Duplicate entry");
+    ctx.client.retract_record(
+        &ctx.provider,
+        &record_id,
+        &RetractionReason::Duplicate,
+        &note,
+    );
+
+    let again = ctx.client.try_retract_record(
+        &ctx.provider,
+        &record_id,
+        &RetractionReason::Duplicate,
+        &note,
+    );
+    assert_eq!(again.unwrap_err().unwrap(), ContractError::RecordRetracted);
+
+    let version = ctx.client.get_record_version_stamp(&record_id).version;
+    let amended = ctx.client.try_amend_record(
+        &ctx.provider,
+        &record_id,
+        &version,
+        &1,
+        &String::from_str(&ctx.env, AMENDED_HASH),
+        &String::from_str(&ctx.env, "Too late"),
+    );
+    assert_eq!(
+        amended.unwrap_err().unwrap(),
+        ContractError::RecordRetracted
+    );
+}
+
+#[test]
+fn test_retract_record_requires_writer() {
+    let (ctx, record_id) = setup();
+    let denied = ctx.client.try_retract_record(
+        &ctx.patient,
+        &record_id,
+        &RetractionReason::PatientRequest,
+        &String::from_str(&ctx.env, ""),
+    );
+    assert_eq!(denied.unwrap_err().unwrap(), ContractError::Unauthorized);
+    assert!(ctx.client.get_record_tombstone(&record_id).is_none());
+}
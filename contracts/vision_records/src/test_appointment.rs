@@ -0,0 +1,471 @@
+#![allow(
+    clippy::unwrap_used,
+    clippy::expect_used,
+    clippy::panic,
+    clippy::arithmetic_side_effects
+)]
+
+use super::{
+    AppointmentStatus, AppointmentType, ContractError, Role, VisionRecordsContract,
+    VisionRecordsContractClient,
+};
+use crate::test_support::verify_provider;
+use soroban_sdk::{
+    symbol_short, testutils::Address as _, testutils::Ledger as _, Address, Env, String, Vec,
+};
+
+// ── Helpers ──────────────────────────────────────────────────────
+
+fn setup() -> (Env, VisionRecordsContractClient<'static>, Address) {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.ledger().set_timestamp(10_000);
+
+    let contract_id = env.register(VisionRecordsContract, ());
+    let client = VisionRecordsContractClient::new(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    (env, client, admin)
+}
+
+fn register(
+    env: &Env,
+    client: &VisionRecordsContractClient,
+    admin: &Address,
+    role: Role,
+) -> Address {
+    let user = Address::generate(env);
+    let is_patient = role == Role::Patient;
+    client.register_user(admin, &user, &role, &String::from_str(env, "User"));
+    if !is_patient {
+        verify_provider(env, client, admin, &user);
+    }
+    user
+}
+
+/// Ids in `provider`'s double-booking bucket for the day containing `at`.
+fn day_bucket(
+    env: &Env,
+    client: &VisionRecordsContractClient,
+    provider: &Address,
+    at: u64,
+) -> Vec<u64> {
+    let key = (symbol_short!("APPT_PDAY"), provider.clone(), at / 86_400);
+    env.as_contract(&client.address, || {
+        env.storage()
+            .persistent()
+            .get(&key)
+            .unwrap_or(Vec::new(env))
+    })
+}
+
+fn book(
+    client: &VisionRecordsContractClient,
+    caller: &Address,
+    patient: &Address,
+    provider: &Address,
+    scheduled_at: u64,
+    duration: u32,
+) -> u64 {
+    client.schedule_appointment(
+        caller,
+        patient,
+        provider,
+        &AppointmentType::Examination,
+        &scheduled_at,
+        &duration,
+        &None,
+    )
+}
+
+// ======================== Scheduling ========================
+
+#[test]
+fn test_schedule_by_patient_and_provider() {
+    let (env, client, admin) = setup();
+    let provider = register(&env, &client, &admin, Role::Optometrist);
+    let patient = register(&env, &client, &admin, Role::Patient);
+
+    let first = book(&client, &patient, &patient, &provider, 20_000, 30);
+    let second = book(&client, &provider, &patient, &provider, 30_000, 30);
+    assert_eq!(first, 1);
+    assert_eq!(second, 2);
+
+    let appt = client.get_appointment(&first);
+    assert_eq!(appt.patient, patient);
+    assert_eq!(appt.provider, provider);
+    assert_eq!(appt.status, AppointmentStatus::Scheduled);
+    assert_eq!(appt.duration_minutes, 30);
+    assert_eq!(client.get_provider_appointments(&provider).len(), 2);
+    assert_eq!(client.get_patient_upcoming(&patient).len(), 2);
+}
+
+#[test]
+fn test_appointment_pages_span_buckets() {
+    let (env, client, admin) = setup();
+    let provider = register(&env, &client, &admin, Role::Optometrist);
+    let patient = register(&env, &client, &admin, Role::Patient);
+
+    // 30 non-overlapping hour-long slots, more than one bucket's worth.
+    for i in 0..30u64 {
+        book(
+            &client,
+            &patient,
+            &patient,
+            &provider,
+            20_000 + i * 3_600,
+            30,
+        );
+    }
+    client.cancel_appointment(&patient, &1);
+
+    let first = client.get_patient_appointments_page(&patient, &false, &None, &20);
+    assert_eq!(first.appointments.len(), 20);
+    assert_eq!(first.appointments.get(0).unwrap().id, 1);
+    let second = client.get_patient_appointments_page(&patient, &false, &first.next_cursor, &20);
+    assert_eq!(second.appointments.len(), 10);
+    assert_eq!(second.appointments.get(9).unwrap().id, 30);
+    assert_eq!(second.next_cursor, None);
+
+    let upcoming = client.get_patient_appointments_page(&patient, &true, &None, &25);
+    assert_eq!(upcoming.appointments.get(0).unwrap().id, 2);
+
+    let latest = client.get_provider_appointments_page(&provider, &true, &None, &5);
+    assert_eq!(latest.appointments.get(0).unwrap().id, 30);
+    assert_eq!(latest.next_cursor, Some(5));
+
+    assert_eq!(client.get_provider_appointments(&provider).len(), 25);
+    assert_eq!(
+        client
+            .try_get_provider_appointments_page(&provider, &false, &None, &26)
+            .err(),
+        Some(Ok(ContractError::InvalidInput))
+    );
+}
+
+#[test]
+fn test_schedule_unauthorized_third_party() {
+    let (env, client, admin) = setup();
+    let provider = register(&env, &client, &admin, Role::Optometrist);
+    let patient = register(&env, &client, &admin, Role::Patient);
+    let other_provider = register(&env, &client, &admin, Role::Optometrist);
+
+    let result = client.try_schedule_appointment(
+        &other_provider,
+        &patient,
+        &provider,
+        &AppointmentType::Examination,
+        &20_000,
+        &30,
+        &None,
+    );
+    assert_eq!(result.unwrap_err().unwrap(), ContractError::Unauthorized);
+}
+
+#[test]
+fn test_schedule_rejects_past_time_and_bad_duration() {
+    let (env, client, admin) = setup();
+    let provider = register(&env, &client, &admin, Role::Optometrist);
+    let patient = register(&env, &client, &admin, Role::Patient);
+
+    let past = client.try_schedule_appointment(
+        &patient,
+        &patient,
+        &provider,
+        &AppointmentType::Examination,
+        &5_000,
+        &30,
+        &None,
+    );
+    assert_eq!(
+        past.unwrap_err().unwrap(),
+        ContractError::InvalidAppointmentTime
+    );
+
+    for duration in [0u32, 481u32] {
+        let result = client.try_schedule_appointment(
+            &patient,
+            &patient,
+            &provider,
+            &AppointmentType::Examination,
+            &20_000,
+            &duration,
+            &None,
+        );
+        assert_eq!(result.unwrap_err().unwrap(), ContractError::InvalidInput);
+    }
+}
+
+// ======================== Double Booking ========================
+
+#[test]
+fn test_provider_double_booking_rejected() {
+    let (env, client, admin) = setup();
+    let provider = register(&env, &client, &admin, Role::Optometrist);
+    let patient_a = register(&env, &client, &admin, Role::Patient);
+    let patient_b = register(&env, &client, &admin, Role::Patient);
+
+    // 20_000 .. 21_800
+    book(&client, &patient_a, &patient_a, &provider, 20_000, 30);
+
+    let overlapping = client.try_schedule_appointment(
+        &patient_b,
+        &patient_b,
+        &provider,
+        &AppointmentType::Consultation,
+        &21_000,
+        &30,
+        &None,
+    );
+    assert_eq!(
+        overlapping.unwrap_err().unwrap(),
+        ContractError::AppointmentConflict
+    );
+
+    // Back-to-back slots do not overlap.
+    book(&client, &patient_b, &patient_b, &provider, 21_800, 30);
+}
+
+#[test]
+fn test_cancelled_slot_can_be_rebooked() {
+    let (env, client, admin) = setup();
+    let provider = register(&env, &client, &admin, Role::Optometrist);
+    let patient_a = register(&env, &client, &admin, Role::Patient);
+    let patient_b = register(&env, &client, &admin, Role::Patient);
+
+    let id = book(&client, &patient_a, &patient_a, &provider, 20_000, 60);
+    assert_eq!(
+        day_bucket(&env, &client, &provider, 20_000),
+        Vec::from_array(&env, [id])
+    );
+    client.cancel_appointment(&patient_a, &id);
+    assert!(day_bucket(&env, &client, &provider, 20_000).is_empty());
+
+    let rebooked = book(&client, &patient_b, &patient_b, &provider, 20_000, 60);
+    assert_eq!(
+        client.get_appointment(&rebooked).status,
+        AppointmentStatus::Scheduled
+    );
+
+    // Completed appointments leave the bucket too.
+    client.complete_appointment(&provider, &rebooked);
+    assert!(day_bucket(&env, &client, &provider, 20_000).is_empty());
+}
+
+#[test]
+fn test_schedule_requires_verified_provider() {
+    let (env, client, admin) = setup();
+    let patient = register(&env, &client, &admin, Role::Patient);
+    let unregistered = Address::generate(&env);
+    let res = client.try_schedule_appointment(
+        &patient,
+        &patient,
+        &unregistered,
+        &AppointmentType::Examination,
+        &20_000,
+        &30,
+        &None,
+    );
+    assert_eq!(res.unwrap_err().unwrap(), ContractError::ProviderNotFound);
+}
+
+#[test]
+fn test_reschedule_conflict_and_success() {
+    let (env, client, admin) = setup();
+    let provider = register(&env, &client, &admin, Role::Optometrist);
+    let patient = register(&env, &client, &admin, Role::Patient);
+
+    let first = book(&client, &patient, &patient, &provider, 20_000, 30);
+    let second = book(&client, &patient, &patient, &provider, 40_000, 30);
+
+    let clash = client.try_reschedule_appointment(&patient, &second, &20_600);
+    assert_eq!(
+        clash.unwrap_err().unwrap(),
+        ContractError::AppointmentConflict
+    );
+
+    // Moving within its own slot does not conflict with itself.
+    client.reschedule_appointment(&patient, &first, &20_600);
+    let appt = client.get_appointment(&first);
+    assert_eq!(appt.scheduled_at, 20_600);
+    assert_eq!(appt.status, AppointmentStatus::Rescheduled);
+    assert!(!appt.reminder_sent);
+
+    let past = client.try_reschedule_appointment(&patient, &first, &1_000);
+    assert_eq!(
+        past.unwrap_err().unwrap(),
+        ContractError::InvalidAppointmentTime
+    );
+}
+
+#[test]
+fn test_conflicts_detected_across_days() {
+    let (env, client, admin) = setup();
+    let provider = register(&env, &client, &admin, Role::Optometrist);
+    let patient = register(&env, &client, &admin, Role::Patient);
+    let day = 86_400u64;
+
+    // A late-evening slot on day 1 running past midnight into day 2.
+    let late = book(&client, &patient, &patient, &provider, 2 * day - 1_800, 120);
+    let clash = client.try_schedule_appointment(
+        &patient,
+        &patient,
+        &provider,
+        &AppointmentType::Consultation,
+        &(2 * day + 600),
+        &30,
+        &None,
+    );
+    assert_eq!(
+        clash.unwrap_err().unwrap(),
+        ContractError::AppointmentConflict
+    );
+
+    // Once moved to another day, the old slot is free and the new one is taken.
+    client.reschedule_appointment(&patient, &late, &(5 * day));
+    book(&client, &patient, &patient, &provider, 2 * day + 600, 30);
+    let clash = client.try_reschedule_appointment(&patient, &late, &(2 * day + 900));
+    assert_eq!(
+        clash.unwrap_err().unwrap(),
+        ContractError::AppointmentConflict
+    );
+    let taken = client.try_schedule_appointment(
+        &patient,
+        &patient,
+        &provider,
+        &AppointmentType::Consultation,
+        &(5 * day + 60),
+        &30,
+        &None,
+    );
+    assert_eq!(
+        taken.unwrap_err().unwrap(),
+        ContractError::AppointmentConflict
+    );
+}
+
+// ======================== Lifecycle ========================
+
+#[test]
+fn test_lifecycle_records_history() {
+    let (env, client, admin) = setup();
+    let provider = register(&env, &client, &admin, Role::Optometrist);
+    let patient = register(&env, &client, &admin, Role::Patient);
+
+    let id = book(&client, &patient, &patient, &provider, 20_000, 30);
+    client.confirm_appointment(&patient, &id);
+    assert_eq!(
+        client.get_appointment(&id).status,
+        AppointmentStatus::Confirmed
+    );
+
+    // Confirming twice is an invalid transition.
+    let again = client.try_confirm_appointment(&patient, &id);
+    assert_eq!(
+        again.unwrap_err().unwrap(),
+        ContractError::InvalidAppointmentStatus
+    );
+
+    client.complete_appointment(&provider, &id);
+    assert_eq!(
+        client.get_appointment(&id).status,
+        AppointmentStatus::Completed
+    );
+
+    let history = client.get_appointment_history(&id);
+    assert_eq!(history.len(), 3);
+    assert_eq!(
+        history.get(0).unwrap().action,
+        String::from_str(&env, "CREATED")
+    );
+    assert_eq!(
+        history.get(1).unwrap().action,
+        String::from_str(&env, "CONFIRMED")
+    );
+    let completed = history.get(2).unwrap();
+    assert_eq!(completed.action, String::from_str(&env, "COMPLETED"));
+    assert_eq!(completed.previous_status, AppointmentStatus::Confirmed);
+    assert_eq!(completed.new_status, AppointmentStatus::Completed);
+    assert_eq!(completed.actor, provider);
+
+    // Completed appointments are frozen.
+    let cancel = client.try_cancel_appointment(&patient, &id);
+    assert_eq!(
+        cancel.unwrap_err().unwrap(),
+        ContractError::InvalidAppointmentStatus
+    );
+}
+
+#[test]
+fn test_only_provider_side_can_complete() {
+    let (env, client, admin) = setup();
+    let provider = register(&env, &client, &admin, Role::Optometrist);
+    let patient = register(&env, &client, &admin, Role::Patient);
+
+    let id = book(&client, &patient, &patient, &provider, 20_000, 30);
+    let result = client.try_complete_appointment(&patient, &id);
+    assert_eq!(result.unwrap_err().unwrap(), ContractError::Unauthorized);
+}
+
+#[test]
+fn test_unrelated_user_cannot_cancel() {
+    let (env, client, admin) = setup();
+    let provider = register(&env, &client, &admin, Role::Optometrist);
+    let patient = register(&env, &client, &admin, Role::Patient);
+    let stranger = register(&env, &client, &admin, Role::Patient);
+
+    let id = book(&client, &patient, &patient, &provider, 20_000, 30);
+    let result = client.try_cancel_appointment(&stranger, &id);
+    assert_eq!(result.unwrap_err().unwrap(), ContractError::Unauthorized);
+}
+
+#[test]
+fn test_verify_appointment_requires_manage_users() {
+    let (env, client, admin) = setup();
+    let provider = register(&env, &client, &admin, Role::Optometrist);
+    let patient = register(&env, &client, &admin, Role::Patient);
+
+    let id = book(&client, &patient, &patient, &provider, 20_000, 30);
+    let denied = client.try_verify_appointment(&patient, &id);
+    assert_eq!(denied.unwrap_err().unwrap(), ContractError::Unauthorized);
+
+    client.verify_appointment(&admin, &id);
+    let appt = client.get_appointment(&id);
+    assert_eq!(appt.verified_by, Some(admin));
+    assert_eq!(appt.verified_at, Some(10_000));
+}
+
+#[test]
+fn test_get_missing_appointment() {
+    let (_env, client, _admin) = setup();
+    let result = client.try_get_appointment(&42);
+    assert_eq!(
+        result.unwrap_err().unwrap(),
+        ContractError::AppointmentNotFound
+    );
+}
+
+// ======================== Reminders ========================
+
+#[test]
+fn test_send_reminders_once() {
+    let (env, client, admin) = setup();
+    let provider = register(&env, &client, &admin, Role::Optometrist);
+    let patient = register(&env, &client, &admin, Role::Patient);
+
+    let soon = book(&client, &patient, &patient, &provider, 12_000, 30);
+    let later = book(&client, &patient, &patient, &provider, 100_000, 30);
+
+    assert_eq!(client.send_appointment_reminders(&7_200), 1);
+    assert!(client.get_appointment(&soon).reminder_sent);
+    assert!(!client.get_appointment(&later).reminder_sent);
+
+    // Already reminded appointments are skipped.
+    assert_eq!(client.send_appointment_reminders(&7_200), 0);
+
+    // Rescheduling re-arms the reminder.
+    client.reschedule_appointment(&patient, &soon, &13_000);
+    assert_eq!(client.send_appointment_reminders(&7_200), 1);
+}
@@ -0,0 +1,185 @@
+#![allow(
+    clippy::unwrap_used,
+    clippy::expect_used,
+    clippy::panic,
+    clippy::arithmetic_side_effects
+)]
+
+use super::{
+    attachment, AttachmentInput, AttachmentKind, ContractError, RecordType, RetractionReason,
+};
+use crate::test_support::{self, TestContext};
+use soroban_sdk::{testutils::Address as _, Address, BytesN, Env, String, Vec};
+
+// ── Helpers ──────────────────────────────────────────────────────
+
+fn setup() -> (TestContext, u64) {
+    let ctx = test_support::setup();
+    let record_id = ctx.add_record(RecordType::Examination);
+    (ctx, record_id)
+}
+
+fn image(env: &Env, seed: u8, mime_type: &str) -> AttachmentInput {
+    AttachmentInput {
+        kind: AttachmentKind::FundusPhoto,
+        content_hash: BytesN::from_array(env, &[seed; 32]),
+        mime_type: String::from_str(env, mime_type),
+        size_bytes: 1_000 + u64::from(seed),
+        storage_uri: String::from_str(env, "s3://clinic-imaging/visit-42/img.dcm"),
+        key_ref: String::from_str(env, "record-key:v1"),
+    }
+}
+
+fn images(env: &Env, seeds: &[u8]) -> Vec<AttachmentInput> {
+    let mut out = Vec::new(env);
+    for seed in seeds {
+        out.push_back(image(env, *seed, "application/dicom"));
+    }
+    out
+}
+
+// ======================== Manifest ========================
+
+#[test]
+fn test_attach_files_and_verify_integrity() {
+    let (ctx, record_id) = setup();
+    let ids = ctx
+        .client
+        .add_attachments(&ctx.provider, &record_id, &images(&ctx.env, &[1, 2, 3]));
+    assert_eq!(ids, Vec::from_array(&ctx.env, [1, 2, 3]));
+
+    let manifest = ctx
+        .client
+        .get_attachment_manifest(&ctx.patient, &record_id)
+        .unwrap();
+    assert_eq!(manifest.attachments.len(), 3);
+    assert_eq!(manifest.attachments.get(1).unwrap().size_bytes, 1_002);
+    assert_eq!(
+        manifest.digest,
+        attachment::manifest_digest(&ctx.env, &manifest.attachments)
+    );
+    assert_eq!(
+        ctx.client.get_attachment_digest(&ctx.patient, &record_id),
+        Some(manifest.digest)
+    );
+
+    let hash = BytesN::from_array(&ctx.env, &[2; 32]);
+    assert!(ctx
+        .client
+        .verify_attachment(&ctx.patient, &record_id, &2, &hash, &1_002));
+    assert!(!ctx
+        .client
+        .verify_attachment(&ctx.patient, &record_id, &2, &hash, &1_003));
+    assert!(!ctx
+        .client
+        .verify_attachment(&ctx.patient, &record_id, &1, &hash, &1_002));
+}
+
+#[test]
+fn test_remove_attachment_keeps_ids_unique() {
+    let (ctx, record_id) = setup();
+    ctx.client
+        .add_attachments(&ctx.provider, &record_id, &images(&ctx.env, &[1, 2]));
+    let before = ctx
+        .client
+        .get_attachment_digest(&ctx.patient, &record_id)
+        .unwrap();
+
+    ctx.client.remove_attachment(&ctx.provider, &record_id, &1);
+    assert_ne!(
+        ctx.client
+            .get_attachment_digest(&ctx.patient, &record_id)
+            .unwrap(),
+        before
+    );
+    let missing = ctx
+        .client
+        .try_remove_attachment(&ctx.provider, &record_id, &1);
+    assert_eq!(
+        missing.unwrap_err().unwrap(),
+        ContractError::AttachmentNotFound
+    );
+
+    // The same file can be attached again, under a fresh id.
+    let ids = ctx
+        .client
+        .add_attachments(&ctx.provider, &record_id, &images(&ctx.env, &[1]));
+    assert_eq!(ids, Vec::from_array(&ctx.env, [3]));
+}
+
+// ======================== Validation ========================
+
+#[test]
+fn test_attachments_are_validated_and_access_checked() {
+    let (ctx, record_id) = setup();
+    ctx.client
+        .add_attachments(&ctx.provider, &record_id, &images(&ctx.env, &[1]));
+
+    let duplicate =
+        ctx.client
+            .try_add_attachments(&ctx.provider, &record_id, &images(&ctx.env, &[1]));
+    assert_eq!(duplicate.unwrap_err().unwrap(), ContractError::InvalidInput);
+    let bad_mime = ctx.client.try_add_attachments(
+        &ctx.provider,
+        &record_id,
+        &Vec::from_array(&ctx.env, [image(&ctx.env, 9, "dicom")]),
+    );
+    assert_eq!(bad_mime.unwrap_err().unwrap(), ContractError::InvalidInput);
+    let mut batch = Vec::new(&ctx.env);
+    for seed in 10..(10 + attachment::MAX_ATTACHMENTS) {
+        batch.push_back(image(&ctx.env, seed as u8, "image/png"));
+    }
+    let too_many = ctx
+        .client
+        .try_add_attachments(&ctx.provider, &record_id, &batch);
+    assert_eq!(too_many.unwrap_err().unwrap(), ContractError::InvalidInput);
+
+    // The patient may read the manifest but not change it.
+    let denied = ctx
+        .client
+        .try_remove_attachment(&ctx.patient, &record_id, &1);
+    assert_eq!(denied.unwrap_err().unwrap(), ContractError::Unauthorized);
+    let stranger = Address::generate(&ctx.env);
+    let denied = ctx
+        .client
+        .try_get_attachment_manifest(&stranger, &record_id);
+    assert_eq!(denied.unwrap_err().unwrap(), ContractError::Unauthorized);
+    let denied = ctx.client.try_verify_attachment(
+        &stranger,
+        &record_id,
+        &1,
+        &BytesN::from_array(&ctx.env, &[1; 32]),
+        &1_001,
+    );
+    assert_eq!(denied.unwrap_err().unwrap(), ContractError::Unauthorized);
+    let denied = ctx.client.try_get_attachment_digest(&stranger, &record_id);
+    assert_eq!(denied.unwrap_err().unwrap(), ContractError::Unauthorized);
+}
+
+#[test]
+fn test_retracted_record_attachments_are_frozen() {
+    let (ctx, record_id) = setup();
+    ctx.client
+        .add_attachments(&ctx.provider, &record_id, &images(&ctx.env, &[1]));
+    ctx.client.retract_record(
+        &ctx.provider,
+        &record_id,
+        &RetractionReason::WrongPatient,
+        &String::from_str(&ctx.env, "Filed against the wrong chart"),
+    );
+
+    let removed = ctx
+        .client
+        .try_remove_attachment(&ctx.provider, &record_id, &1);
+    assert_eq!(
+        removed.unwrap_err().unwrap(),
+        ContractError::RecordRetracted
+    );
+    let manifest = ctx
+        .client
+        .try_get_attachment_manifest(&ctx.patient, &record_id);
+    assert_eq!(
+        manifest.unwrap_err().unwrap(),
+        ContractError::RecordRetracted
+    );
+}
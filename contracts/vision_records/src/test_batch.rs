@@ -9,6 +9,7 @@ use super::{
     AccessLevel, BatchGrantInput, BatchRecordInput, ContractError, RecordType, Role,
     VisionRecordsContract, VisionRecordsContractClient,
 };
+use crate::test_support::verify_provider;
 use soroban_sdk::{testutils::Address as _, testutils::Ledger as _, Address, Env, String, Vec};
 
 // ── Helpers ──────────────────────────────────────────────────────
@@ -34,6 +35,7 @@ fn register_provider(env: &Env, client: &VisionRecordsContractClient, admin: &Ad
         &Role::Optometrist,
         &String::from_str(env, "Dr. Provider"),
     );
+    verify_provider(env, client, admin, &provider);
     provider
 }
 
@@ -167,7 +169,9 @@ fn test_batch_add_records_admin_can_create() {
     let (env, client, admin) = setup();
     let patient = register_patient(&env, &client, &admin, "Alice");
 
-    // Admin has SystemAdmin permission, so can batch-create records
+    // Admin has SystemAdmin permission and, once verified as a provider,
+    // can batch-create records
+    verify_provider(&env, &client, &admin, &admin);
     let mut inputs = Vec::new(&env);
     inputs.push_back(BatchRecordInput {
         patient: patient.clone(),
@@ -248,7 +252,7 @@ fn test_batch_get_records() {
     subset.push_back(1u64);
     subset.push_back(3u64);
 
-    let records = client.get_records(&subset);
+    let records = client.get_records(&patient, &subset);
     assert_eq!(records.len(), 2);
     assert_eq!(records.get(0).unwrap().id, 1);
     assert_eq!(records.get(1).unwrap().id, 3);
@@ -256,12 +260,13 @@ fn test_batch_get_records() {
 
 #[test]
 fn test_batch_get_records_not_found() {
-    let (env, client, _admin) = setup();
+    let (env, client, admin) = setup();
+    let patient = register_patient(&env, &client, &admin, "Alice");
 
     let mut ids = Vec::new(&env);
     ids.push_back(999u64);
 
-    let result = client.try_get_records(&ids);
+    let result = client.try_get_records(&patient, &ids);
     assert_eq!(
         result.err().unwrap().unwrap(),
         ContractError::RecordNotFound
@@ -287,13 +292,56 @@ fn test_batch_get_records_partial_not_found() {
     ids.push_back(1u64);
     ids.push_back(999u64);
 
-    let result = client.try_get_records(&ids);
+    let result = client.try_get_records(&patient, &ids);
     assert_eq!(
         result.err().unwrap().unwrap(),
         ContractError::RecordNotFound
     );
 }
 
+#[test]
+fn test_batch_get_records_requires_access_to_every_record() {
+    let (env, client, admin) = setup();
+    let provider = register_provider(&env, &client, &admin);
+    let alice = register_patient(&env, &client, &admin, "Alice");
+    let bob = register_patient(&env, &client, &admin, "Bob");
+
+    let mut inputs = Vec::new(&env);
+    for patient in [&alice, &bob] {
+        inputs.push_back(BatchRecordInput {
+            patient: patient.clone(),
+            record_type: RecordType::Examination,
+            data_hash: String::from_str(&env, "hash"),
+        });
+    }
+    let ids = client.add_records(&provider, &inputs);
+
+    // Alice may read her own record but not Bob's.
+    let result = client.try_get_records(&alice, &ids);
+    assert_eq!(result.err().unwrap().unwrap(), ContractError::Unauthorized);
+
+    let mut own = Vec::new(&env);
+    own.push_back(ids.get(0).unwrap());
+    assert_eq!(client.get_records(&alice, &own).len(), 1);
+}
+
+#[test]
+fn test_batch_get_records_rejects_oversized_batch() {
+    let (env, client, admin) = setup();
+    let patient = register_patient(&env, &client, &admin, "Alice");
+
+    let mut ids = Vec::new(&env);
+    for id in 1..=21u64 {
+        ids.push_back(id);
+    }
+    let result = client.try_get_records(&patient, &ids);
+    assert_eq!(result.err().unwrap().unwrap(), ContractError::InvalidInput);
+
+    let empty = Vec::new(&env);
+    let result = client.try_get_records(&patient, &empty);
+    assert_eq!(result.err().unwrap().unwrap(), ContractError::InvalidInput);
+}
+
 // ======================== Batch Access Grants ========================
 
 #[test]
@@ -442,7 +490,7 @@ fn test_batch_add_and_retrieve_round_trip() {
     let ids = client.add_records(&provider, &inputs);
 
     // Retrieve all via batch
-    let records = client.get_records(&ids);
+    let records = client.get_records(&patient, &ids);
     assert_eq!(records.len(), 2);
 
     assert_eq!(records.get(0).unwrap().record_type, RecordType::Examination);
@@ -0,0 +1,206 @@
+#![allow(
+    clippy::unwrap_used,
+    clippy::expect_used,
+    clippy::panic,
+    clippy::arithmetic_side_effects
+)]
+
+use super::{
+    AccessAction, AccessResult, AuditFilter, ConsentType, ContractError, RecordRead, RecordType,
+};
+use crate::test_support::{self, TestContext};
+use crate::LegacyConsentGrant;
+use soroban_sdk::{symbol_short, testutils::Address as _, Address, String, Vec};
+
+const DAY: u64 = 86_400;
+
+// ── Helpers ──────────────────────────────────────────────────────
+
+fn setup() -> (TestContext, Address) {
+    let ctx = test_support::setup();
+    let researcher = Address::generate(&ctx.env);
+    (ctx, researcher)
+}
+
+/// Asserts that a purpose-declared read was refused with `error`.
+fn assert_denied(read: RecordRead, error: ContractError) {
+    assert!(
+        matches!(read, RecordRead::Denied(code) if code == error as u32),
+        "expected {:?}, got {:?}",
+        error,
+        read
+    );
+}
+
+// ======================== Purposes ========================
+
+#[test]
+fn test_reads_must_match_consented_purpose() {
+    let (ctx, researcher) = setup();
+    let record_id = ctx.add_record(RecordType::Examination);
+    ctx.client
+        .grant_consent(&ctx.patient, &researcher, &ConsentType::Treatment, &DAY);
+
+    // Treatment consent is not reusable for research.
+    ctx.client.get_record(&researcher, &record_id);
+    let reused = ctx
+        .client
+        .get_record_for_purpose(&researcher, &record_id, &ConsentType::Research);
+    assert_denied(reused, ContractError::ConsentPurposeMismatch);
+
+    // Research consent covers research reads and nothing else.
+    ctx.client
+        .grant_consent(&ctx.patient, &researcher, &ConsentType::Research, &DAY);
+    ctx.client
+        .get_record_for_purpose(&researcher, &record_id, &ConsentType::Research);
+    let treatment = ctx.client.try_get_record(&researcher, &record_id);
+    assert_eq!(
+        treatment.unwrap_err().unwrap(),
+        ContractError::ConsentPurposeMismatch
+    );
+
+    // No consent at all stays a plain authorization failure.
+    let stranger = Address::generate(&ctx.env);
+    let denied = ctx
+        .client
+        .get_record_for_purpose(&stranger, &record_id, &ConsentType::Billing);
+    assert_denied(denied, ContractError::Unauthorized);
+}
+
+#[test]
+fn test_consent_scoped_to_record_types() {
+    let (ctx, researcher) = setup();
+    let exam_id = ctx.add_record(RecordType::Examination);
+    let prescription_id = ctx.add_record(RecordType::Prescription);
+    ctx.client.grant_scoped_consent(
+        &ctx.patient,
+        &researcher,
+        &Vec::from_array(&ctx.env, [ConsentType::Billing, ConsentType::Treatment]),
+        &Vec::from_array(&ctx.env, [RecordType::Prescription]),
+        &DAY,
+    );
+
+    ctx.client
+        .get_record_for_purpose(&researcher, &prescription_id, &ConsentType::Billing);
+    ctx.client.get_record(&researcher, &prescription_id);
+    let other_type =
+        ctx.client
+            .get_record_for_purpose(&researcher, &exam_id, &ConsentType::Billing);
+    assert_denied(other_type, ContractError::ConsentPurposeMismatch);
+
+    let consent = ctx.client.get_consent(&ctx.patient, &researcher).unwrap();
+    assert_eq!(consent.purposes.len(), 2);
+    assert_eq!(consent.expires_at, 10_000 + DAY);
+
+    let empty = ctx.client.try_grant_scoped_consent(
+        &ctx.patient,
+        &researcher,
+        &Vec::new(&ctx.env),
+        &Vec::new(&ctx.env),
+        &DAY,
+    );
+    assert_eq!(empty.unwrap_err().unwrap(), ContractError::InvalidInput);
+}
+
+// ======================== Legacy layout ========================
+
+#[test]
+fn test_legacy_consent_is_read_and_migrated() {
+    let (ctx, researcher) = setup();
+    let record_id = ctx.add_record(RecordType::Examination);
+    let legacy_key = (
+        symbol_short!("CONSENT"),
+        ctx.patient.clone(),
+        researcher.clone(),
+    );
+    ctx.env.as_contract(&ctx.contract_id, || {
+        ctx.env.storage().persistent().set(
+            &legacy_key,
+            &LegacyConsentGrant {
+                patient: ctx.patient.clone(),
+                grantee: researcher.clone(),
+                consent_type: ConsentType::Treatment,
+                granted_at: 10_000,
+                expires_at: 10_000 + DAY,
+                revoked: false,
+            },
+        );
+    });
+
+    let consent = ctx.client.get_consent(&ctx.patient, &researcher).unwrap();
+    assert_eq!(
+        consent.purposes,
+        Vec::from_array(&ctx.env, [ConsentType::Treatment])
+    );
+    assert!(consent.record_types.is_empty());
+    ctx.client.get_record(&researcher, &record_id);
+    let other = ctx
+        .client
+        .get_record_for_purpose(&researcher, &record_id, &ConsentType::Research);
+    assert_denied(other, ContractError::ConsentPurposeMismatch);
+
+    // Revoking rewrites the grant in the current layout.
+    ctx.client.revoke_consent(&ctx.patient, &researcher);
+    assert!(
+        ctx.client
+            .get_consent(&ctx.patient, &researcher)
+            .unwrap()
+            .revoked
+    );
+    let legacy_left = ctx.env.as_contract(&ctx.contract_id, || {
+        ctx.env.storage().persistent().has(&legacy_key)
+    });
+    assert!(!legacy_left);
+    assert!(ctx.client.try_get_record(&researcher, &record_id).is_err());
+}
+
+// ======================== Auditing ========================
+
+#[test]
+fn test_purpose_recorded_in_audit_log() {
+    let (ctx, researcher) = setup();
+    let record_id = ctx.add_record(RecordType::Examination);
+    ctx.client
+        .grant_consent(&ctx.patient, &researcher, &ConsentType::Treatment, &DAY);
+    ctx.client.get_record(&researcher, &record_id);
+
+    // The refusal is returned, not raised, so its audit entry is kept.
+    let result =
+        ctx.client
+            .try_get_record_for_purpose(&researcher, &record_id, &ConsentType::Research);
+    assert_denied(
+        result.unwrap().unwrap(),
+        ContractError::ConsentPurposeMismatch,
+    );
+
+    let filter = AuditFilter {
+        patient: Some(ctx.patient.clone()),
+        actor: Some(researcher.clone()),
+        record_id: Some(record_id),
+        actions: Vec::from_array(&ctx.env, [AccessAction::Read]),
+        results: Vec::new(&ctx.env),
+        from: None,
+        to: None,
+    };
+    let entries = ctx
+        .client
+        .get_audit_log(&ctx.patient, &filter, &None, &10)
+        .entries;
+    assert_eq!(entries.len(), 2);
+
+    let allowed = entries.get(0).unwrap();
+    assert_eq!(allowed.result, AccessResult::Success);
+    assert_eq!(
+        allowed.reason,
+        Some(String::from_str(&ctx.env, "Purpose: treatment"))
+    );
+    let rejected = entries.get(1).unwrap();
+    assert_eq!(rejected.result, AccessResult::Denied);
+    assert_eq!(
+        rejected.reason,
+        Some(String::from_str(
+            &ctx.env,
+            "Consent does not cover purpose: research"
+        ))
+    );
+}
@@ -0,0 +1,317 @@
+#![allow(
+    clippy::unwrap_used,
+    clippy::expect_used,
+    clippy::panic,
+    clippy::arithmetic_side_effects
+)]
+
+use super::{
+    ContractError, EmergencyCondition, EmergencyContact, EmergencyStatus, RecordType, Role,
+};
+use crate::test_support::{self, TestContext};
+use soroban_sdk::testutils::{Address as _, Events as _, Ledger as _};
+use soroban_sdk::xdr::{ContractEventBody, ScSymbol, ScVal};
+use soroban_sdk::{Address, Env, String};
+
+const HOUR: u64 = 3_600;
+
+// ── Helpers ──────────────────────────────────────────────────────
+
+fn setup() -> (TestContext, Address, u64) {
+    let ctx = test_support::setup();
+    // Staff hold no ReadAnyRecord permission, so reads depend on the emergency grant.
+    let clinician = ctx.register(Role::Staff, "ER Nurse");
+    let record_id = ctx.add_record(RecordType::Examination);
+    (ctx, clinician, record_id)
+}
+
+fn request(ctx: &TestContext, clinician: &Address, duration: u64) -> u64 {
+    ctx.client.request_emergency_access(
+        clinician,
+        &ctx.patient,
+        &EmergencyCondition::Unconscious,
+        &String::from_str(&ctx.env, "Patient unconscious after collision"),
+        &duration,
+    )
+}
+
+fn add_emergency_contact(ctx: &TestContext) {
+    let env = &ctx.env;
+    ctx.client.create_profile(
+        &ctx.patient,
+        &ctx.patient,
+        &String::from_str(env, "dob_hash"),
+        &String::from_str(env, "gender_hash"),
+        &String::from_str(env, "blood_hash"),
+    );
+    ctx.client.update_emergency_contact(
+        &ctx.patient,
+        &ctx.patient,
+        &Some(EmergencyContact {
+            name: String::from_str(env, "Jane Doe"),
+            relationship: String::from_str(env, "Spouse"),
+            phone: String::from_str(env, "+1234567890"),
+            email: String::from_str(env, "jane@example.com"),
+        }),
+    );
+}
+
+/// True if the last invocation published an event whose first topic is `name`.
+fn last_call_emitted(env: &Env, name: &str) -> bool {
+    let expected = ScVal::Symbol(ScSymbol(name.try_into().unwrap()));
+    env.events()
+        .all()
+        .events()
+        .iter()
+        .any(|event| match &event.body {
+            ContractEventBody::V0(body) => body.topics.first() == Some(&expected),
+        })
+}
+
+fn assert_audit_actions(ctx: &TestContext, access_id: u64, expected: &[&str]) {
+    let trail = ctx.client.get_emergency_audit_trail(&access_id);
+    assert_eq!(trail.len() as usize, expected.len());
+    for (entry, action) in trail.iter().zip(expected.iter()) {
+        assert_eq!(entry.action, String::from_str(&ctx.env, action));
+    }
+}
+
+// ======================== Requesting ========================
+
+#[test]
+fn test_emergency_grant_allows_record_read() {
+    let (ctx, clinician, record_id) = setup();
+
+    let denied = ctx.client.try_get_record(&clinician, &record_id);
+    assert_eq!(denied.unwrap_err().unwrap(), ContractError::Unauthorized);
+
+    let access_id = request(&ctx, &clinician, HOUR);
+    let access = ctx.client.get_emergency_access(&access_id);
+    assert_eq!(access.status, EmergencyStatus::Active);
+    assert_eq!(access.expires_at, 10_000 + HOUR);
+    // No emergency contact on file, so nobody was notified.
+    assert!(access.notified_contacts.is_empty());
+    assert!(ctx
+        .client
+        .check_emergency_access(&ctx.patient, &clinician)
+        .is_some());
+
+    let record = ctx.client.get_record(&clinician, &record_id);
+    assert_eq!(record.patient, ctx.patient);
+    assert!(last_call_emitted(&ctx.env, "EMRG_USE"));
+
+    assert_audit_actions(&ctx, access_id, &["GRANTED", "ACCESSED"]);
+}
+
+#[test]
+fn test_emergency_read_notifies_contact() {
+    let (ctx, clinician, record_id) = setup();
+    add_emergency_contact(&ctx);
+
+    let access_id = request(&ctx, &clinician, HOUR);
+    assert!(last_call_emitted(&ctx.env, "EMRG_NOT"));
+    let notified = ctx
+        .client
+        .get_emergency_access(&access_id)
+        .notified_contacts;
+    assert_eq!(notified.len(), 1);
+    assert_eq!(
+        notified.get(0).unwrap().name,
+        String::from_str(&ctx.env, "Jane Doe")
+    );
+
+    ctx.client.get_record(&clinician, &record_id);
+    assert!(last_call_emitted(&ctx.env, "EMRG_NOT"));
+    // The same contact is listed once however often it is told.
+    let access = ctx.client.get_emergency_access(&access_id);
+    assert_eq!(access.notified_contacts, notified);
+
+    assert_audit_actions(
+        &ctx,
+        access_id,
+        &["GRANTED", "NOTIFIED", "ACCESSED", "NOTIFIED"],
+    );
+}
+
+#[test]
+fn test_request_validation() {
+    let (ctx, clinician, _) = setup();
+
+    let empty = ctx.client.try_request_emergency_access(
+        &clinician,
+        &ctx.patient,
+        &EmergencyCondition::LifeThreatening,
+        &String::from_str(&ctx.env, ""),
+        &HOUR,
+    );
+    assert_eq!(
+        empty.unwrap_err().unwrap(),
+        ContractError::InvalidAttestation
+    );
+
+    for duration in [0u64, 86_401u64] {
+        let result = ctx.client.try_request_emergency_access(
+            &clinician,
+            &ctx.patient,
+            &EmergencyCondition::LifeThreatening,
+            &String::from_str(&ctx.env, "Emergency"),
+            &duration,
+        );
+        assert_eq!(result.unwrap_err().unwrap(), ContractError::InvalidInput);
+    }
+}
+
+#[test]
+fn test_patient_cannot_request_emergency_access() {
+    let (ctx, _, _) = setup();
+    let other_patient = ctx.register(Role::Patient, "Patient");
+
+    let result = ctx.client.try_request_emergency_access(
+        &other_patient,
+        &ctx.patient,
+        &EmergencyCondition::LifeThreatening,
+        &String::from_str(&ctx.env, "Emergency"),
+        &HOUR,
+    );
+    assert_eq!(result.unwrap_err().unwrap(), ContractError::Unauthorized);
+}
+
+// ======================== Expiry & Revocation ========================
+
+#[test]
+fn test_emergency_access_expires() {
+    let (ctx, clinician, record_id) = setup();
+    let access_id = request(&ctx, &clinician, HOUR);
+
+    ctx.env.ledger().set_timestamp(10_000 + HOUR);
+    let denied = ctx.client.try_get_record(&clinician, &record_id);
+    assert_eq!(denied.unwrap_err().unwrap(), ContractError::Unauthorized);
+
+    assert_eq!(ctx.client.expire_emergency_accesses(), 1);
+    assert_eq!(
+        ctx.client.get_emergency_access(&access_id).status,
+        EmergencyStatus::Expired
+    );
+    assert_eq!(ctx.client.expire_emergency_accesses(), 0);
+
+    let revoke = ctx
+        .client
+        .try_revoke_emergency_access(&ctx.patient, &access_id);
+    assert_eq!(
+        revoke.unwrap_err().unwrap(),
+        ContractError::EmergencyAccessExpired
+    );
+}
+
+#[test]
+fn test_patient_revokes_emergency_access() {
+    let (ctx, clinician, record_id) = setup();
+    let access_id = request(&ctx, &clinician, HOUR);
+
+    let stranger = Address::generate(&ctx.env);
+    let denied = ctx
+        .client
+        .try_revoke_emergency_access(&stranger, &access_id);
+    assert_eq!(denied.unwrap_err().unwrap(), ContractError::Unauthorized);
+
+    ctx.client.revoke_emergency_access(&ctx.patient, &access_id);
+    assert_eq!(
+        ctx.client.get_emergency_access(&access_id).status,
+        EmergencyStatus::Revoked
+    );
+
+    let read = ctx.client.try_get_record(&clinician, &record_id);
+    assert_eq!(read.unwrap_err().unwrap(), ContractError::Unauthorized);
+
+    let again = ctx
+        .client
+        .try_revoke_emergency_access(&ctx.admin, &access_id);
+    assert_eq!(
+        again.unwrap_err().unwrap(),
+        ContractError::EmergencyAccessRevoked
+    );
+    assert_audit_actions(&ctx, access_id, &["GRANTED", "REVOKED"]);
+}
+
+#[test]
+fn test_emergency_access_found_after_many_later_grants() {
+    let (ctx, clinician, record_id) = setup();
+    let access_id = request(&ctx, &clinician, 4 * HOUR);
+
+    // Grants for other patients no longer push this one out of view.
+    for _ in 0..120 {
+        ctx.client.request_emergency_access(
+            &clinician,
+            &Address::generate(&ctx.env),
+            &EmergencyCondition::LifeThreatening,
+            &String::from_str(&ctx.env, "Mass casualty triage"),
+            &HOUR,
+        );
+    }
+
+    let active = ctx
+        .client
+        .check_emergency_access(&ctx.patient, &clinician)
+        .unwrap();
+    assert_eq!(active.id, access_id);
+    ctx.client.get_record(&clinician, &record_id);
+}
+
+#[test]
+fn test_revoking_one_grant_keeps_other_live_grant() {
+    let (ctx, clinician, _) = setup();
+    let long = request(&ctx, &clinician, 4 * HOUR);
+    let short = request(&ctx, &clinician, HOUR);
+
+    ctx.client.revoke_emergency_access(&ctx.patient, &long);
+    assert_eq!(
+        ctx.client
+            .check_emergency_access(&ctx.patient, &clinician)
+            .unwrap()
+            .id,
+        short
+    );
+
+    ctx.env.ledger().set_timestamp(10_000 + HOUR);
+    assert!(ctx
+        .client
+        .check_emergency_access(&ctx.patient, &clinician)
+        .is_none());
+}
+
+#[test]
+fn test_patient_emergency_access_pages() {
+    let (ctx, clinician, _) = setup();
+    let revoked = request(&ctx, &clinician, HOUR);
+    let live = request(&ctx, &clinician, HOUR);
+    ctx.client.revoke_emergency_access(&ctx.patient, &revoked);
+
+    let all = ctx
+        .client
+        .get_emergency_accesses_page(&ctx.patient, &false, &None, &1);
+    assert_eq!(all.accesses.get(0).unwrap().id, revoked);
+    assert_eq!(all.next_cursor, Some(1));
+
+    let active = ctx
+        .client
+        .get_emergency_accesses_page(&ctx.patient, &true, &None, &10);
+    assert_eq!(active.accesses.len(), 1);
+    assert_eq!(active.accesses.get(0).unwrap().id, live);
+    assert_eq!(active.next_cursor, None);
+    assert_eq!(
+        ctx.client
+            .get_patient_emergency_accesses(&ctx.patient)
+            .len(),
+        1
+    );
+}
+
+#[test]
+fn test_get_missing_emergency_access() {
+    let (ctx, _, _) = setup();
+    let result = ctx.client.try_get_emergency_access(&7);
+    assert_eq!(
+        result.unwrap_err().unwrap(),
+        ContractError::EmergencyAccessNotFound
+    );
+}
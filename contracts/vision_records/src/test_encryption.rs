@@ -0,0 +1,112 @@
+#![allow(
+    clippy::unwrap_used,
+    clippy::expect_used,
+    clippy::panic,
+    clippy::arithmetic_side_effects
+)]
+
+use super::{ContractError, RecordType, VisionRecord, ENC_CUR, ENC_KEY};
+use crate::test_support::{setup, TestContext, DATA_HASH};
+use soroban_sdk::{symbol_short, String};
+use teye_common::{bytes_to_hex, StdString, StdVec};
+
+/// A 16-byte key, as `set_encryption_key` accepted before AES-256.
+const LEGACY_KEY: [u8; 16] = [0x5a; 16];
+
+// ── Helpers ──────────────────────────────────────────────────────
+
+/// Installs `key` as the current ENC_KEY the way an older contract left it.
+fn set_legacy_key(ctx: &TestContext, key: &[u8]) {
+    let version = String::from_str(&ctx.env, "1");
+    let hex = String::from_str(&ctx.env, &bytes_to_hex(key));
+    ctx.env.as_contract(&ctx.client.address, || {
+        ctx.env
+            .storage()
+            .persistent()
+            .set(&(ENC_KEY, version.clone()), &hex);
+        ctx.env.storage().instance().set(&ENC_CUR, &version);
+    });
+}
+
+/// Replaces the stored data hash of `record_id` as-is.
+fn overwrite_stored_hash(ctx: &TestContext, record_id: u64, stored: &str) {
+    let key = (symbol_short!("RECORD"), record_id);
+    ctx.env.as_contract(&ctx.client.address, || {
+        let mut record: VisionRecord = ctx.env.storage().persistent().get(&key).unwrap();
+        record.data_hash = String::from_str(&ctx.env, stored);
+        ctx.env.storage().persistent().set(&key, &record);
+    });
+}
+
+fn stored_hash(ctx: &TestContext, record_id: u64) -> String {
+    let key = (symbol_short!("RECORD"), record_id);
+    ctx.env.as_contract(&ctx.client.address, || {
+        let record: VisionRecord = ctx.env.storage().persistent().get(&key).unwrap();
+        record.data_hash
+    })
+}
+
+fn xor_hex(key: &[u8], plaintext: &str) -> StdString {
+    let bytes: StdVec<u8> = plaintext
+        .bytes()
+        .enumerate()
+        .map(|(i, b)| b ^ key[i % key.len()])
+        .collect();
+    bytes_to_hex(&bytes)
+}
+
+// ── Tests ────────────────────────────────────────────────────────
+
+#[test]
+fn test_short_legacy_key_seals_and_opens() {
+    let ctx = setup();
+    set_legacy_key(&ctx, &LEGACY_KEY);
+
+    let record_id = ctx.add_record(RecordType::Examination);
+    assert_ne!(
+        stored_hash(&ctx, record_id),
+        String::from_str(&ctx.env, DATA_HASH)
+    );
+
+    let record = ctx.client.get_record(&ctx.patient, &record_id);
+    assert_eq!(record.data_hash, String::from_str(&ctx.env, DATA_HASH));
+}
+
+#[test]
+fn test_legacy_xor_records_still_read() {
+    let ctx = setup();
+    set_legacy_key(&ctx, &LEGACY_KEY);
+    let record_id = ctx.add_record(RecordType::Examination);
+    overwrite_stored_hash(&ctx, record_id, &xor_hex(&LEGACY_KEY, DATA_HASH));
+
+    let record = ctx.client.get_record(&ctx.patient, &record_id);
+    assert_eq!(record.data_hash, String::from_str(&ctx.env, DATA_HASH));
+}
+
+#[test]
+fn test_keyless_records_are_hex_encoded() {
+    let ctx = setup();
+    let record_id = ctx.add_record(RecordType::Examination);
+    assert_eq!(
+        stored_hash(&ctx, record_id),
+        String::from_str(&ctx.env, &bytes_to_hex(DATA_HASH.as_bytes()))
+    );
+
+    let record = ctx.client.get_record(&ctx.patient, &record_id);
+    assert_eq!(record.data_hash, String::from_str(&ctx.env, DATA_HASH));
+}
+
+#[test]
+fn test_undecryptable_hash_is_an_error() {
+    let ctx = setup();
+    set_legacy_key(&ctx, &LEGACY_KEY);
+    let record_id = ctx.add_record(RecordType::Examination);
+
+    // Sealed under another key: neither AES-GCM nor the XOR scheme opens it.
+    set_legacy_key(&ctx, &[0x33; 32]);
+    let result = ctx.client.try_get_record(&ctx.patient, &record_id);
+    assert_eq!(
+        result.unwrap_err().unwrap(),
+        ContractError::DecryptionFailed
+    );
+}
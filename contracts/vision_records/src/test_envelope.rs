@@ -0,0 +1,291 @@
+#![allow(
+    clippy::unwrap_used,
+    clippy::expect_used,
+    clippy::panic,
+    clippy::arithmetic_side_effects
+)]
+
+use super::envelope;
+use super::{AccessLevel, ContractError, RecordType};
+use crate::test_support::{self, TestContext, DATA_HASH};
+use key_manager::{DerivedKey, KeyManagerContract, KeyManagerContractClient, KeyPolicy, KeyType};
+use soroban_sdk::crypto::bls12_381::Fr;
+use soroban_sdk::{testutils::Address as _, Address, Bytes, BytesN, Env, String, Vec};
+
+const DAY: u64 = 86_400;
+
+// ── Helpers ──────────────────────────────────────────────────────
+
+/// The key manager the contract derives record keys from.
+struct Keys {
+    manager: KeyManagerContractClient<'static>,
+    root: BytesN<32>,
+}
+
+fn setup() -> (TestContext, Keys) {
+    let ctx = test_support::setup();
+
+    let km_id = ctx.env.register(KeyManagerContract, ());
+    let manager = KeyManagerContractClient::new(&ctx.env, &km_id);
+    manager.initialize(&ctx.admin, &Address::generate(&ctx.env));
+    let root = manager.create_master_key(
+        &ctx.admin,
+        &KeyType::Encryption,
+        &KeyPolicy {
+            max_uses: 0,
+            not_before: 0,
+            not_after: 0,
+            allowed_ops: Vec::new(&ctx.env),
+        },
+        &0,
+        &BytesN::from_array(&ctx.env, &[9u8; 32]),
+    );
+    manager.set_record_key_deriver(&ctx.admin, &root, &ctx.contract_id, &true);
+    ctx.client.set_key_manager(&ctx.admin, &km_id, &root);
+
+    (ctx, Keys { manager, root })
+}
+
+fn secret(env: &Env, seed: u8) -> Fr {
+    Fr::from_bytes(BytesN::from_array(env, &[seed; 32]))
+}
+
+/// Registers a public key for `user` and returns the matching secret.
+fn register_key(ctx: &TestContext, user: &Address, seed: u8) -> Fr {
+    let sk = secret(&ctx.env, seed);
+    ctx.client
+        .set_encryption_public_key(user, &envelope::public_key_for(&ctx.env, &sk));
+    sk
+}
+
+/// The record's current data key, as its provider would obtain it.
+fn data_key(ctx: &TestContext, keys: &Keys, record_id: u64) -> DerivedKey {
+    keys.manager
+        .derive_record_key(&ctx.admin, &keys.root, &record_id)
+}
+
+/// Wraps `key` for `recipient` off-chain and submits it as `caller`.
+fn submit(
+    ctx: &TestContext,
+    caller: &Address,
+    record_id: u64,
+    recipient: &Address,
+    key: &DerivedKey,
+) -> Result<(), ContractError> {
+    let public_key = ctx.client.get_encryption_public_key(recipient).unwrap();
+    let (ephemeral, wrapped_key) =
+        envelope::wrap(&ctx.env, &public_key, record_id, key, &secret(&ctx.env, 42));
+    match ctx.client.try_submit_wrapped_record_key(
+        caller,
+        &record_id,
+        recipient,
+        &key.version,
+        &ephemeral,
+        &wrapped_key,
+    ) {
+        Ok(_) => Ok(()),
+        Err(err) => Err(err.unwrap()),
+    }
+}
+
+/// Unwraps `recipient`'s copy of the record key inside the contract's env.
+fn unwrap_for(ctx: &TestContext, record_id: u64, recipient: &Address, sk: &Fr) -> BytesN<32> {
+    let wrapped = ctx
+        .client
+        .get_wrapped_record_key(&record_id, recipient)
+        .expect("no wrapped key");
+    envelope::unwrap(&ctx.env, &wrapped, record_id, sk).key
+}
+
+// ======================== Sealing ========================
+
+#[test]
+fn test_patient_key_is_wrapped_off_chain() {
+    let (ctx, keys) = setup();
+    let patient_sk = register_key(&ctx, &ctx.patient, 7);
+    let record_id = ctx.add_record(RecordType::Examination);
+
+    let header = ctx.client.get_record_key_envelope(&record_id).unwrap();
+    assert_eq!(header.key_version, 1);
+    assert!(header.recipients.is_empty());
+
+    let key = data_key(&ctx, &keys, record_id);
+    submit(&ctx, &ctx.provider, record_id, &ctx.patient, &key).unwrap();
+
+    let header = ctx.client.get_record_key_envelope(&record_id).unwrap();
+    assert_eq!(
+        header.recipients,
+        Vec::from_array(&ctx.env, [ctx.patient.clone()])
+    );
+    let unwrapped = unwrap_for(&ctx, record_id, &ctx.patient, &patient_sk);
+    assert_eq!(unwrapped, key.key);
+    assert_eq!(
+        header.key_commitment,
+        ctx.env
+            .crypto()
+            .sha256(&Bytes::from_array(&ctx.env, &unwrapped.to_array()))
+            .to_bytes()
+    );
+
+    // A different secret recovers nothing useful.
+    assert_ne!(
+        unwrap_for(&ctx, record_id, &ctx.patient, &secret(&ctx.env, 8)),
+        key.key
+    );
+}
+
+#[test]
+fn test_no_envelope_without_key_manager() {
+    let ctx = test_support::setup();
+    let record_id = ctx.add_record(RecordType::Examination);
+    assert!(ctx.client.get_record_key_envelope(&record_id).is_none());
+}
+
+#[test]
+fn test_record_keys_need_an_authorized_deriver() {
+    let (ctx, keys) = setup();
+    let stranger = Address::generate(&ctx.env);
+    let denied = keys
+        .manager
+        .try_derive_record_key(&stranger, &keys.root, &1);
+    assert_eq!(
+        denied.unwrap_err().unwrap(),
+        key_manager::ContractError::Unauthorized
+    );
+}
+
+// ======================== Submissions ========================
+
+#[test]
+fn test_wrapped_key_submission_rules() {
+    let (ctx, keys) = setup();
+    register_key(&ctx, &ctx.patient, 7);
+    let record_id = ctx.add_record(RecordType::Examination);
+    let key = data_key(&ctx, &keys, record_id);
+
+    let stranger = Address::generate(&ctx.env);
+    register_key(&ctx, &stranger, 13);
+    assert_eq!(
+        submit(&ctx, &stranger, record_id, &ctx.patient, &key),
+        Err(ContractError::Unauthorized)
+    );
+    // Only the patient, the provider and record grantees can receive it.
+    assert_eq!(
+        submit(&ctx, &ctx.patient, record_id, &stranger, &key),
+        Err(ContractError::AccessDenied)
+    );
+    let stale = DerivedKey {
+        key: key.key.clone(),
+        version: key.version + 1,
+    };
+    assert_eq!(
+        submit(&ctx, &ctx.patient, record_id, &ctx.patient, &stale),
+        Err(ContractError::InvalidInput)
+    );
+    assert_eq!(
+        submit(&ctx, &ctx.patient, record_id, &ctx.patient, &key),
+        Ok(())
+    );
+}
+
+#[test]
+fn test_record_grantee_wrap_and_revoke() {
+    let (ctx, keys) = setup();
+    let patient_sk = register_key(&ctx, &ctx.patient, 7);
+    let record_id = ctx.add_record(RecordType::Examination);
+    let key = data_key(&ctx, &keys, record_id);
+    submit(&ctx, &ctx.provider, record_id, &ctx.patient, &key).unwrap();
+
+    let grantee = Address::generate(&ctx.env);
+    let grantee_sk = register_key(&ctx, &grantee, 11);
+    ctx.client
+        .grant_record_access(&ctx.patient, &grantee, &record_id, &AccessLevel::Read, &DAY);
+    // Granting alone wraps nothing; the patient wraps for the grantee.
+    assert!(ctx
+        .client
+        .get_wrapped_record_key(&record_id, &grantee)
+        .is_none());
+    submit(&ctx, &ctx.patient, record_id, &grantee, &key).unwrap();
+
+    let header = ctx.client.get_record_key_envelope(&record_id).unwrap();
+    assert_eq!(
+        header.recipients,
+        Vec::from_array(&ctx.env, [ctx.patient.clone(), grantee.clone()])
+    );
+    assert_eq!(unwrap_for(&ctx, record_id, &grantee, &grantee_sk), key.key);
+
+    // Revoking re-keys the envelope and drops every wrapped key.
+    ctx.client
+        .revoke_record_access(&ctx.patient, &grantee, &record_id);
+    let header = ctx.client.get_record_key_envelope(&record_id).unwrap();
+    assert_eq!(header.key_version, key.version);
+    assert_eq!(header.key_epoch, 1);
+    assert!(header.recipients.is_empty());
+    for recipient in [&ctx.patient, &grantee] {
+        assert!(ctx
+            .client
+            .get_wrapped_record_key(&record_id, recipient)
+            .is_none());
+    }
+
+    // The old key no longer matches; the patient's copy is wrapped again.
+    let new_key = envelope::data_key(&ctx.env, &key, header.key_epoch);
+    assert_ne!(new_key.key, key.key);
+    assert_eq!(
+        header.key_commitment,
+        ctx.env
+            .crypto()
+            .sha256(&Bytes::from_array(&ctx.env, &new_key.key.to_array()))
+            .to_bytes()
+    );
+    submit(&ctx, &ctx.provider, record_id, &ctx.patient, &new_key).unwrap();
+    assert_eq!(
+        unwrap_for(&ctx, record_id, &ctx.patient, &patient_sk),
+        new_key.key
+    );
+    assert_eq!(
+        submit(&ctx, &ctx.patient, record_id, &grantee, &new_key),
+        Err(ContractError::AccessDenied)
+    );
+}
+
+#[test]
+fn test_amend_after_rotation_rekeys_envelope() {
+    let (ctx, keys) = setup();
+    let patient_sk = register_key(&ctx, &ctx.patient, 7);
+    let record_id = ctx.add_record(RecordType::Examination);
+    let old_key = data_key(&ctx, &keys, record_id);
+    submit(&ctx, &ctx.provider, record_id, &ctx.patient, &old_key).unwrap();
+
+    keys.manager.rotate_key(&ctx.admin, &keys.root);
+    let version = ctx.client.get_record_version_stamp(&record_id).version;
+    ctx.client.amend_record(
+        &ctx.provider,
+        &record_id,
+        &version,
+        &1,
+        &String::from_str(&ctx.env, DATA_HASH),
+        &String::from_str(&ctx.env, "Re-encrypt after revocation"),
+    );
+
+    let header = ctx.client.get_record_key_envelope(&record_id).unwrap();
+    assert_eq!(header.key_version, 2);
+    assert!(header.recipients.is_empty());
+    assert!(ctx
+        .client
+        .get_wrapped_record_key(&record_id, &ctx.patient)
+        .is_none());
+
+    // Keys wrapped under the old version are refused.
+    assert_eq!(
+        submit(&ctx, &ctx.provider, record_id, &ctx.patient, &old_key),
+        Err(ContractError::InvalidInput)
+    );
+    let new_key = data_key(&ctx, &keys, record_id);
+    assert_ne!(new_key.key, old_key.key);
+    submit(&ctx, &ctx.provider, record_id, &ctx.patient, &new_key).unwrap();
+    assert_eq!(
+        unwrap_for(&ctx, record_id, &ctx.patient, &patient_sk),
+        new_key.key
+    );
+}
@@ -0,0 +1,191 @@
+#![allow(
+    clippy::unwrap_used,
+    clippy::expect_used,
+    clippy::panic,
+    clippy::arithmetic_side_effects
+)]
+
+use super::examination::{validate_acuity_value, validate_iop};
+use super::{
+    ContractError, FundusPhotography, IntraocularPressure, OptFundusPhotography,
+    OptPhysicalMeasurement, OptRetinalImaging, OptVisualField, PhysicalMeasurement, RecordType,
+    SlitLampFindings, VisualAcuity,
+};
+use crate::test_support::{setup, TestContext};
+use soroban_sdk::{Env, String};
+
+// ── Helpers ──────────────────────────────────────────────────────
+
+fn acuity(env: &Env, left: &str, right: &str) -> VisualAcuity {
+    VisualAcuity {
+        uncorrected: PhysicalMeasurement {
+            left_eye: String::from_str(env, left),
+            right_eye: String::from_str(env, right),
+        },
+        corrected: OptPhysicalMeasurement::None,
+    }
+}
+
+fn iop(env: &Env, left: u32, right: u32) -> IntraocularPressure {
+    IntraocularPressure {
+        left_eye: left,
+        right_eye: right,
+        method: String::from_str(env, "Goldmann"),
+        timestamp: 10_000,
+    }
+}
+
+fn slit_lamp(env: &Env) -> SlitLampFindings {
+    SlitLampFindings {
+        cornea: String::from_str(env, "Clear"),
+        anterior_chamber: String::from_str(env, "Deep and quiet"),
+        iris: String::from_str(env, "Normal"),
+        lens: String::from_str(env, "Clear"),
+    }
+}
+
+fn try_add(
+    ctx: &TestContext,
+    record_id: u64,
+    visual_acuity: &VisualAcuity,
+    pressure: &IntraocularPressure,
+    fundus: &OptFundusPhotography,
+) -> Result<(), ContractError> {
+    match ctx.client.try_add_eye_examination(
+        &ctx.provider,
+        &record_id,
+        visual_acuity,
+        pressure,
+        &slit_lamp(&ctx.env),
+        &OptVisualField::None,
+        &OptRetinalImaging::None,
+        fundus,
+        &String::from_str(&ctx.env, "Routine"),
+    ) {
+        Ok(_) => Ok(()),
+        Err(err) => Err(err.unwrap()),
+    }
+}
+
+// ======================== Field validators ========================
+
+#[test]
+fn test_acuity_formats() {
+    let env = Env::default();
+    let check = |s: &str| validate_acuity_value(&String::from_str(&env, s));
+
+    for ok in [
+        "20/20", "6/6", "20/200", "20/20-2", "6/9+1", "0.00", "-0.10", "1.30", "CF", "NLP",
+    ] {
+        assert_eq!(check(ok), Ok(()), "{}", ok);
+    }
+    for bad in [
+        "20/", "/20", "20/0", "20/20-", "20/20-12", "20:20", "2.00", "-0.40", "0.125", "HMX",
+    ] {
+        assert_eq!(
+            check(bad),
+            Err(ContractError::InvalidVisualAcuity),
+            "{}",
+            bad
+        );
+    }
+    assert_eq!(check(""), Err(ContractError::MissingEyeData));
+}
+
+#[test]
+fn test_iop_ranges() {
+    let env = Env::default();
+    assert_eq!(validate_iop(&iop(&env, 15, 16)), Ok(()));
+    assert_eq!(validate_iop(&iop(&env, 1, 80)), Ok(()));
+    assert_eq!(
+        validate_iop(&iop(&env, 15, 81)),
+        Err(ContractError::InvalidIntraocularPressure)
+    );
+    assert_eq!(
+        validate_iop(&iop(&env, 0, 16)),
+        Err(ContractError::MissingEyeData)
+    );
+}
+
+// ======================== add_eye_examination ========================
+
+#[test]
+fn test_add_examination_rejects_invalid_fields() {
+    let ctx = setup();
+    let record_id = ctx.add_record(RecordType::Examination);
+    let env = &ctx.env;
+    let good_va = acuity(env, "20/20", "20/25");
+    let good_iop = iop(env, 14, 15);
+
+    assert_eq!(
+        try_add(
+            &ctx,
+            record_id,
+            &good_va,
+            &iop(env, 14, 120),
+            &OptFundusPhotography::None
+        ),
+        Err(ContractError::InvalidIntraocularPressure)
+    );
+    assert_eq!(
+        try_add(
+            &ctx,
+            record_id,
+            &acuity(env, "20/20", "twenty"),
+            &good_iop,
+            &OptFundusPhotography::None
+        ),
+        Err(ContractError::InvalidVisualAcuity)
+    );
+    assert_eq!(
+        try_add(
+            &ctx,
+            record_id,
+            &acuity(env, "20/20", ""),
+            &good_iop,
+            &OptFundusPhotography::None
+        ),
+        Err(ContractError::MissingEyeData)
+    );
+
+    let one_eyed_fundus = OptFundusPhotography::Some(FundusPhotography {
+        image_url: String::from_str(env, "ipfs://fundus"),
+        image_hash: String::from_str(env, "e3b0c44298fc1c149afbf4c8996fb924"),
+        cup_to_disc_ratio_left: String::from_str(env, "0.3"),
+        cup_to_disc_ratio_right: String::from_str(env, ""),
+        macula_status: String::from_str(env, "Flat"),
+    });
+    assert_eq!(
+        try_add(&ctx, record_id, &good_va, &good_iop, &one_eyed_fundus),
+        Err(ContractError::MissingEyeData)
+    );
+
+    assert_eq!(
+        try_add(
+            &ctx,
+            record_id,
+            &good_va,
+            &good_iop,
+            &OptFundusPhotography::None
+        ),
+        Ok(())
+    );
+    let exam = ctx.client.get_eye_examination(&ctx.provider, &record_id);
+    assert_eq!(exam.iop.right_eye, 15);
+}
+
+#[test]
+fn test_add_examination_requires_examination_record() {
+    let ctx = setup();
+    let record_id = ctx.add_record(RecordType::Diagnosis);
+    assert_eq!(
+        try_add(
+            &ctx,
+            record_id,
+            &acuity(&ctx.env, "20/20", "20/20"),
+            &iop(&ctx.env, 14, 15),
+            &OptFundusPhotography::None
+        ),
+        Err(ContractError::InvalidRecordType)
+    );
+}
@@ -0,0 +1,288 @@
+#![allow(
+    clippy::unwrap_used,
+    clippy::expect_used,
+    clippy::panic,
+    clippy::arithmetic_side_effects
+)]
+
+use super::{AccessLevel, ContractError, GuardianPermission, GuardianRelationship, RecordType};
+use crate::test_support::{self, TestContext};
+use soroban_sdk::{testutils::Address as _, testutils::Ledger as _, Address, Env, String, Vec};
+
+const DAY: u64 = 86_400;
+const YEAR: u64 = 365 * DAY;
+
+// ── Helpers ──────────────────────────────────────────────────────
+
+/// The patient has a profile and one record; `parent` is not their guardian yet.
+fn setup() -> (TestContext, Address, u64) {
+    let ctx = test_support::setup();
+    let parent = Address::generate(&ctx.env);
+    let hash = String::from_str(&ctx.env, "e3b0c44298fc1c149afbf4c8996fb924");
+    ctx.client
+        .create_profile(&ctx.patient, &ctx.patient, &hash, &hash, &hash);
+    let record_id = ctx.add_record(RecordType::Examination);
+    (ctx, parent, record_id)
+}
+
+fn permissions(env: &Env, perms: &[GuardianPermission]) -> Vec<GuardianPermission> {
+    let mut out = Vec::new(env);
+    for p in perms {
+        out.push_back(p.clone());
+    }
+    out
+}
+
+/// Registers `parent` as the patient's guardian until majority in a year.
+fn add_parent(ctx: &TestContext, parent: &Address, perms: &[GuardianPermission]) -> u64 {
+    let majority_at = ctx.env.ledger().timestamp() + YEAR;
+    ctx.client.add_guardian(
+        &ctx.admin,
+        &ctx.patient,
+        parent,
+        &GuardianRelationship::Parent,
+        &permissions(&ctx.env, perms),
+        &majority_at,
+    );
+    majority_at
+}
+
+// ======================== Access ========================
+
+#[test]
+fn test_guardian_reads_and_manages_access() {
+    let (ctx, parent, record_id) = setup();
+    add_parent(
+        &ctx,
+        &parent,
+        &[
+            GuardianPermission::ViewRecords,
+            GuardianPermission::ManageAccess,
+        ],
+    );
+
+    assert_eq!(
+        ctx.client.check_access(&ctx.patient, &parent),
+        AccessLevel::Read
+    );
+    let record = ctx.client.get_record(&parent, &record_id);
+    assert_eq!(record.patient, ctx.patient);
+
+    let specialist = Address::generate(&ctx.env);
+    ctx.client
+        .grant_access(&parent, &ctx.patient, &specialist, &AccessLevel::Read, &DAY);
+    assert_eq!(
+        ctx.client
+            .get_patient_grants(&ctx.patient, &None, &10)
+            .grants
+            .len(),
+        1
+    );
+    ctx.client
+        .revoke_access_on_behalf(&parent, &ctx.patient, &specialist);
+    assert_eq!(
+        ctx.client
+            .get_patient_grants(&ctx.patient, &None, &10)
+            .grants
+            .len(),
+        0
+    );
+
+    let profile = ctx.client.get_profile(&ctx.patient);
+    assert_eq!(
+        profile.guardians,
+        Vec::from_array(&ctx.env, [parent.clone()])
+    );
+}
+
+#[test]
+fn test_guardian_permissions_are_scoped() {
+    let (ctx, parent, _) = setup();
+    add_parent(&ctx, &parent, &[GuardianPermission::ViewRecords]);
+
+    let specialist = Address::generate(&ctx.env);
+    let denied =
+        ctx.client
+            .try_grant_access(&parent, &ctx.patient, &specialist, &AccessLevel::Read, &DAY);
+    assert_eq!(denied.unwrap_err().unwrap(), ContractError::Unauthorized);
+
+    ctx.client.grant_access(
+        &ctx.patient,
+        &ctx.patient,
+        &specialist,
+        &AccessLevel::Read,
+        &DAY,
+    );
+    let denied = ctx
+        .client
+        .try_revoke_access_on_behalf(&parent, &ctx.patient, &specialist);
+    assert_eq!(denied.unwrap_err().unwrap(), ContractError::Unauthorized);
+
+    // A stranger is not a guardian at all.
+    let stranger = Address::generate(&ctx.env);
+    assert_eq!(
+        ctx.client.check_access(&ctx.patient, &stranger),
+        AccessLevel::None
+    );
+}
+
+// ======================== Majority ========================
+
+#[test]
+fn test_guardianship_ends_at_majority() {
+    let (ctx, parent, _) = setup();
+    let majority_at = add_parent(
+        &ctx,
+        &parent,
+        &[
+            GuardianPermission::ViewRecords,
+            GuardianPermission::ManageAccess,
+        ],
+    );
+    let early = ctx.client.try_claim_majority(&ctx.patient);
+    assert_eq!(early.unwrap_err().unwrap(), ContractError::InvalidTimestamp);
+
+    ctx.env.ledger().set_timestamp(majority_at);
+    assert_eq!(
+        ctx.client.check_access(&ctx.patient, &parent),
+        AccessLevel::None
+    );
+    let denied = ctx.client.try_grant_access(
+        &parent,
+        &ctx.patient,
+        &Address::generate(&ctx.env),
+        &AccessLevel::Read,
+        &DAY,
+    );
+    assert_eq!(denied.unwrap_err().unwrap(), ContractError::Unauthorized);
+
+    assert_eq!(ctx.client.claim_majority(&ctx.patient), 1);
+    let profile = ctx.client.get_profile(&ctx.patient);
+    assert_eq!(profile.majority_at, majority_at);
+    assert!(profile.guardians.is_empty());
+    assert!(ctx.client.get_guardianship(&ctx.patient, &parent).is_none());
+
+    // Once of age, new proxies need the patient's own authorization.
+    let proxy = Address::generate(&ctx.env);
+    ctx.client.add_guardian(
+        &ctx.admin,
+        &ctx.patient,
+        &proxy,
+        &GuardianRelationship::Proxy,
+        &permissions(&ctx.env, &[GuardianPermission::ViewRecords]),
+        &0,
+    );
+    assert!(ctx
+        .env
+        .auths()
+        .iter()
+        .any(|(address, _)| *address == ctx.patient));
+}
+
+#[test]
+fn test_adult_cannot_be_made_minor_again() {
+    let (ctx, parent, _) = setup();
+    let majority_at = add_parent(&ctx, &parent, &[GuardianPermission::ViewRecords]);
+
+    // While a minor, the majority date may still be corrected.
+    let later = majority_at + YEAR;
+    ctx.client.add_guardian(
+        &ctx.admin,
+        &ctx.patient,
+        &parent,
+        &GuardianRelationship::Parent,
+        &permissions(&ctx.env, &[GuardianPermission::ViewRecords]),
+        &later,
+    );
+    assert_eq!(ctx.client.get_profile(&ctx.patient).majority_at, later);
+
+    ctx.env.ledger().set_timestamp(later);
+    ctx.client.claim_majority(&ctx.patient);
+
+    // A future date would turn the adult back into a minor, skipping their
+    // authorization and locking them out of removing the guardian.
+    let proxy = Address::generate(&ctx.env);
+    let relapse = ctx.client.try_add_guardian(
+        &ctx.admin,
+        &ctx.patient,
+        &proxy,
+        &GuardianRelationship::Parent,
+        &permissions(&ctx.env, &[GuardianPermission::ViewRecords]),
+        &(later + YEAR),
+    );
+    assert_eq!(
+        relapse.unwrap_err().unwrap(),
+        ContractError::InvalidTimestamp
+    );
+    assert_eq!(ctx.client.get_profile(&ctx.patient).majority_at, later);
+    assert!(ctx.client.get_guardianship(&ctx.patient, &proxy).is_none());
+}
+
+// ======================== Appointment ========================
+
+#[test]
+fn test_guardian_appointment_rules() {
+    let (ctx, parent, _) = setup();
+    let view = permissions(&ctx.env, &[GuardianPermission::ViewRecords]);
+    add_parent(&ctx, &parent, &[GuardianPermission::ViewRecords]);
+
+    // A minor cannot appoint or remove their own guardians.
+    let friend = Address::generate(&ctx.env);
+    let denied = ctx.client.try_add_guardian(
+        &ctx.patient,
+        &ctx.patient,
+        &friend,
+        &GuardianRelationship::Proxy,
+        &view,
+        &0,
+    );
+    assert_eq!(denied.unwrap_err().unwrap(), ContractError::Unauthorized);
+    let denied = ctx
+        .client
+        .try_remove_guardian(&ctx.patient, &ctx.patient, &parent);
+    assert_eq!(denied.unwrap_err().unwrap(), ContractError::Unauthorized);
+
+    // An adult appoints a proxy, who can step down.
+    let adult = Address::generate(&ctx.env);
+    let hash = String::from_str(&ctx.env, "e3b0c44298fc1c149afbf4c8996fb924");
+    ctx.client
+        .create_profile(&adult, &adult, &hash, &hash, &hash);
+    let carer = Address::generate(&ctx.env);
+    ctx.client.add_guardian(
+        &adult,
+        &adult,
+        &carer,
+        &GuardianRelationship::Proxy,
+        &view,
+        &0,
+    );
+    assert_eq!(
+        ctx.client
+            .get_guardianship(&adult, &carer)
+            .unwrap()
+            .expires_at,
+        0
+    );
+    let denied = ctx.client.try_remove_guardian(&friend, &adult, &carer);
+    assert_eq!(denied.unwrap_err().unwrap(), ContractError::Unauthorized);
+    ctx.client.remove_guardian(&carer, &adult, &carer);
+    assert!(ctx.client.get_guardians(&adult).is_empty());
+
+    let missing = ctx.client.try_remove_guardian(&adult, &adult, &carer);
+    assert_eq!(
+        missing.unwrap_err().unwrap(),
+        ContractError::GuardianshipNotFound
+    );
+    let no_profile = ctx.client.try_add_guardian(
+        &ctx.admin,
+        &Address::generate(&ctx.env),
+        &carer,
+        &GuardianRelationship::Proxy,
+        &view,
+        &0,
+    );
+    assert_eq!(
+        no_profile.unwrap_err().unwrap(),
+        ContractError::UserNotFound
+    );
+}
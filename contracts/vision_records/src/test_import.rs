@@ -0,0 +1,284 @@
+#![allow(
+    clippy::unwrap_used,
+    clippy::expect_used,
+    clippy::panic,
+    clippy::arithmetic_side_effects
+)]
+
+use super::{
+    ContractError, ImportItemKind, ImportOutcome, ImportProfileInput, ImportRecordInput,
+    ImportStatus, ImportUserInput, RecordType, Role,
+};
+use crate::test_support::{self, TestContext};
+use soroban_sdk::{testutils::Address as _, testutils::Ledger as _, Address, String, Vec};
+
+const NOW: u64 = 1_700_000_000;
+const DAY: u64 = 86_400;
+const HASH: &str = "QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG";
+
+// ── Helpers ──────────────────────────────────────────────────────
+
+fn setup() -> (TestContext, String) {
+    let ctx = test_support::setup_without_provider_account();
+    ctx.env.ledger().set_timestamp(NOW);
+    let source = String::from_str(&ctx.env, "legacy-ehr");
+    (ctx, source)
+}
+
+fn users(ctx: &TestContext) -> Vec<ImportUserInput> {
+    Vec::from_array(
+        &ctx.env,
+        [
+            ImportUserInput {
+                user: ctx.provider.clone(),
+                role: Role::Optometrist,
+                name: String::from_str(&ctx.env, "Dr. Legacy"),
+                registered_at: NOW - 900 * DAY,
+            },
+            ImportUserInput {
+                user: ctx.patient.clone(),
+                role: Role::Patient,
+                name: String::from_str(&ctx.env, "Pat Ient"),
+                registered_at: NOW - 800 * DAY,
+            },
+        ],
+    )
+}
+
+fn profiles(ctx: &TestContext) -> Vec<ImportProfileInput> {
+    Vec::from_array(
+        &ctx.env,
+        [ImportProfileInput {
+            patient: ctx.patient.clone(),
+            date_of_birth_hash: String::from_str(&ctx.env, "dob_hash"),
+            gender_hash: String::from_str(&ctx.env, "gender_hash"),
+            blood_type_hash: String::from_str(&ctx.env, "blood_hash"),
+            created_at: NOW - 800 * DAY,
+        }],
+    )
+}
+
+fn record(
+    ctx: &TestContext,
+    legacy_id: &str,
+    data_hash: &str,
+    created_at: u64,
+) -> ImportRecordInput {
+    ImportRecordInput {
+        legacy_id: String::from_str(&ctx.env, legacy_id),
+        patient: ctx.patient.clone(),
+        provider: ctx.provider.clone(),
+        record_type: RecordType::Examination,
+        data_hash: String::from_str(&ctx.env, data_hash),
+        created_at,
+    }
+}
+
+// ======================== Import ========================
+
+#[test]
+fn test_import_chunk_preserves_history() {
+    let (ctx, source) = setup();
+    let job_id = ctx.client.start_import(&ctx.admin, &source);
+    let created_at = NOW - 700 * DAY;
+    let records = Vec::from_array(
+        &ctx.env,
+        [
+            record(&ctx, "EXAM-1", HASH, created_at),
+            record(&ctx, "EXAM-2", HASH, created_at + DAY),
+        ],
+    );
+
+    let report = ctx.client.import_chunk(
+        &ctx.admin,
+        &job_id,
+        &0,
+        &users(&ctx),
+        &profiles(&ctx),
+        &records,
+    );
+    assert_eq!((report.imported, report.skipped, report.failed), (5, 0, 0));
+    let first = report.items.get(3).unwrap();
+    assert_eq!(first.kind, ImportItemKind::Record);
+
+    let user = ctx.client.get_user(&ctx.provider);
+    assert_eq!(user.registered_at, NOW - 900 * DAY);
+    assert_eq!(
+        ctx.client.get_profile(&ctx.patient).created_at,
+        NOW - 800 * DAY
+    );
+
+    let stored = ctx.client.get_record(&ctx.provider, &first.record_id);
+    assert_eq!(stored.created_at, created_at);
+    assert_eq!(stored.data_hash, String::from_str(&ctx.env, HASH));
+    let provenance = ctx.client.get_import_provenance(&first.record_id).unwrap();
+    assert_eq!(provenance.job_id, job_id);
+    assert_eq!(provenance.legacy_id, String::from_str(&ctx.env, "EXAM-1"));
+
+    assert_eq!(ctx.client.get_patient_records(&ctx.patient).len(), 2);
+    let day = ctx
+        .client
+        .get_records_by_day(&ctx.provider, &(created_at / DAY), &false, &None, &10);
+    assert_eq!(day.record_ids, Vec::from_array(&ctx.env, [first.record_id]));
+}
+
+#[test]
+fn test_import_is_idempotent_and_reports_failures() {
+    let (ctx, source) = setup();
+    let job_id = ctx.client.start_import(&ctx.admin, &source);
+    let records = Vec::from_array(
+        &ctx.env,
+        [
+            record(&ctx, "EXAM-1", HASH, NOW - DAY),
+            record(&ctx, "EXAM-2", "", NOW - DAY),
+            record(&ctx, "EXAM-3", HASH, NOW + DAY),
+        ],
+    );
+    let report = ctx.client.import_chunk(
+        &ctx.admin,
+        &job_id,
+        &0,
+        &users(&ctx),
+        &Vec::new(&ctx.env),
+        &records,
+    );
+    assert_eq!((report.imported, report.failed), (3, 2));
+    assert_eq!(report.items.get(2).unwrap().error_code, 0);
+    assert_eq!(
+        report.items.get(3).unwrap().error_code,
+        ContractError::InvalidInput as u32
+    );
+    assert_eq!(
+        report.items.get(4).unwrap().error_code,
+        ContractError::InvalidTimestamp as u32
+    );
+
+    // Replaying an applied chunk returns its report unchanged.
+    let replay = ctx.client.import_chunk(
+        &ctx.admin,
+        &job_id,
+        &0,
+        &users(&ctx),
+        &Vec::new(&ctx.env),
+        &records,
+    );
+    assert_eq!(replay, report);
+    assert_eq!(ctx.client.get_record_count(), 1);
+    // Replaying it with different contents is refused.
+    let altered = ctx.client.try_import_chunk(
+        &ctx.admin,
+        &job_id,
+        &0,
+        &Vec::new(&ctx.env),
+        &Vec::new(&ctx.env),
+        &records,
+    );
+    assert_eq!(
+        altered.unwrap_err().unwrap(),
+        ContractError::ImportChunkMismatch
+    );
+
+    // A retry job for the same source skips what is already there.
+    let retry_id = ctx.client.start_import(&ctx.admin, &source);
+    let fixed = Vec::from_array(
+        &ctx.env,
+        [
+            record(&ctx, "EXAM-1", HASH, NOW - DAY),
+            record(&ctx, "EXAM-2", HASH, NOW - DAY),
+        ],
+    );
+    let retry = ctx.client.import_chunk(
+        &ctx.admin,
+        &retry_id,
+        &0,
+        &users(&ctx),
+        &Vec::new(&ctx.env),
+        &fixed,
+    );
+    assert_eq!((retry.imported, retry.skipped, retry.failed), (1, 3, 0));
+    let skipped = retry.items.get(2).unwrap();
+    assert_eq!(skipped.outcome, ImportOutcome::Skipped);
+    assert_eq!(skipped.record_id, report.items.get(2).unwrap().record_id);
+    assert_eq!(ctx.client.get_record_count(), 2);
+
+    let job = ctx.client.get_import_job(&retry_id).unwrap();
+    assert_eq!((job.chunks_applied, job.imported, job.skipped), (1, 1, 3));
+}
+
+// ======================== Job lifecycle ========================
+
+#[test]
+fn test_import_requires_admin_and_open_job() {
+    let (ctx, source) = setup();
+    let outsider = Address::generate(&ctx.env);
+    let denied = ctx.client.try_start_import(&outsider, &source);
+    assert_eq!(denied.unwrap_err().unwrap(), ContractError::Unauthorized);
+
+    let job_id = ctx.client.start_import(&ctx.admin, &source);
+    let denied = ctx.client.try_import_chunk(
+        &outsider,
+        &job_id,
+        &0,
+        &users(&ctx),
+        &Vec::new(&ctx.env),
+        &Vec::new(&ctx.env),
+    );
+    assert_eq!(denied.unwrap_err().unwrap(), ContractError::Unauthorized);
+    let missing = ctx.client.try_import_chunk(
+        &ctx.admin,
+        &(job_id + 1),
+        &0,
+        &users(&ctx),
+        &Vec::new(&ctx.env),
+        &Vec::new(&ctx.env),
+    );
+    assert_eq!(
+        missing.unwrap_err().unwrap(),
+        ContractError::ImportJobNotFound
+    );
+    let empty = ctx.client.try_import_chunk(
+        &ctx.admin,
+        &job_id,
+        &0,
+        &Vec::new(&ctx.env),
+        &Vec::new(&ctx.env),
+        &Vec::new(&ctx.env),
+    );
+    assert_eq!(empty.unwrap_err().unwrap(), ContractError::InvalidInput);
+    // Four records write more ledger entries than one chunk may.
+    let mut records = Vec::new(&ctx.env);
+    for legacy_id in ["EXAM-1", "EXAM-2", "EXAM-3", "EXAM-4"] {
+        records.push_back(record(&ctx, legacy_id, HASH, NOW - DAY));
+    }
+    let oversized = ctx.client.try_import_chunk(
+        &ctx.admin,
+        &job_id,
+        &0,
+        &Vec::new(&ctx.env),
+        &Vec::new(&ctx.env),
+        &records,
+    );
+    assert_eq!(oversized.unwrap_err().unwrap(), ContractError::InvalidInput);
+    records.pop_back();
+    let report = ctx.client.import_chunk(
+        &ctx.admin,
+        &job_id,
+        &0,
+        &users(&ctx),
+        &Vec::new(&ctx.env),
+        &records,
+    );
+    assert_eq!(report.imported, 5);
+
+    let job = ctx.client.complete_import(&ctx.admin, &job_id);
+    assert_eq!(job.status, ImportStatus::Completed);
+    let closed = ctx.client.try_import_chunk(
+        &ctx.admin,
+        &job_id,
+        &1,
+        &users(&ctx),
+        &Vec::new(&ctx.env),
+        &Vec::new(&ctx.env),
+    );
+    assert_eq!(closed.unwrap_err().unwrap(), ContractError::ImportJobClosed);
+}
@@ -6,6 +6,7 @@
 )]
 
 use super::*;
+use crate::test_support::verify_provider;
 use alloc::boxed::Box;
 use soroban_sdk::testutils::Address as _;
 use soroban_sdk::{Env, String, Vec};
@@ -32,6 +33,7 @@ fn register_provider(client: &VisionRecordsContractClient, env: &Env, admin: &Ad
         &Role::Optometrist,
         &String::from_str(env, "Dr. Provider"),
     );
+    verify_provider(env, client, admin, &provider);
     provider
 }
 
@@ -260,6 +262,83 @@ fn test_stale_version_triggers_conflict_manual_review() {
     }
 }
 
+#[test]
+fn test_stale_version_from_same_node_applies_under_manual_review() {
+    let (env, admin, client) = setup_env();
+    let provider = register_provider(&client, &env, &admin);
+    let patient = register_patient(&client, &env, &admin);
+
+    let record_id = add_exam_record(&client, &env, &admin, &patient, &provider);
+
+    client.grant_consent(
+        &patient,
+        &provider,
+        &ConsentType::Treatment,
+        &157_680_000u64,
+    );
+    client.grant_access(
+        &patient,
+        &patient,
+        &provider,
+        &AccessLevel::Full,
+        &157_680_000u64,
+    );
+    client.set_record_resolution_strategy(&provider, &record_id, &ResolutionStrategy::ManualReview);
+
+    let stamp_v1 = client.get_record_version_stamp(&record_id);
+
+    let va = VisualAcuity {
+        uncorrected: examination::PhysicalMeasurement {
+            left_eye: String::from_str(&env, "20/20"),
+            right_eye: String::from_str(&env, "20/25"),
+        },
+        corrected: examination::OptPhysicalMeasurement::None,
+    };
+    let iop = IntraocularPressure {
+        left_eye: 14,
+        right_eye: 15,
+        method: String::from_str(&env, "Goldmann"),
+        timestamp: 1000,
+    };
+    let slit = SlitLampFindings {
+        cornea: String::from_str(&env, "clear"),
+        anterior_chamber: String::from_str(&env, "deep"),
+        iris: String::from_str(&env, "normal"),
+        lens: String::from_str(&env, "clear"),
+    };
+
+    let mut changed = Vec::new(&env);
+    changed.push_back(FieldChange {
+        field_name: String::from_str(&env, "visual_acuity"),
+        old_hash: String::from_str(&env, "none"),
+        new_hash: String::from_str(&env, "va_hash_1"),
+    });
+
+    // Both updates come from node 1; the second is written against version 1
+    // but the only update it missed is its own, so it is causally after.
+    for (notes, expected_version) in [("First update", 2u64), ("Second update", 3u64)] {
+        let outcome = client.update_examination_versioned(
+            &provider,
+            &record_id,
+            &stamp_v1.version,
+            &1u32,
+            &va,
+            &iop,
+            &slit,
+            &OptVisualField::None,
+            &OptRetinalImaging::None,
+            &OptFundusPhotography::None,
+            &String::from_str(&env, notes),
+            &changed,
+        );
+        match outcome {
+            UpdateOutcome::Applied(s) => assert_eq!(s.version, expected_version),
+            other => panic!("Expected Applied, got {:?}", other),
+        }
+    }
+    assert!(client.get_pending_conflicts().is_empty());
+}
+
 #[test]
 fn test_last_writer_wins_strategy() {
     let (env, admin, client) = setup_env();
@@ -0,0 +1,594 @@
+#![allow(
+    clippy::unwrap_used,
+    clippy::expect_used,
+    clippy::panic,
+    clippy::arithmetic_side_effects
+)]
+
+use super::{
+    audit, AccessAction, AccessLevel, AccessResult, AuditEntry, AuditFilter, BatchGrantInput,
+    BatchRecordInput, ContractError, RecordFilter, RecordType, Role, VisionRecord,
+};
+use crate::test_support::{setup, TestContext, DATA_HASH};
+use soroban_sdk::{
+    symbol_short, testutils::Address as _, testutils::Ledger as _, Address, Env, String, Vec,
+};
+
+const DAY: u64 = 86_400;
+
+// ── Helpers ──────────────────────────────────────────────────────
+
+/// Adds one record per entry in `types`, one day apart.
+fn add_records(ctx: &TestContext, types: &[RecordType]) -> Vec<u64> {
+    let mut ids = Vec::new(&ctx.env);
+    for record_type in types {
+        ids.push_back(ctx.client.add_record(
+            &ctx.provider,
+            &ctx.patient,
+            &ctx.provider,
+            record_type,
+            &String::from_str(&ctx.env, DATA_HASH),
+        ));
+        let now = ctx.env.ledger().timestamp();
+        ctx.env.ledger().set_timestamp(now + DAY);
+    }
+    ids
+}
+
+fn record_filter(
+    env: &Env,
+    types: &[RecordType],
+    from: Option<u64>,
+    to: Option<u64>,
+) -> RecordFilter {
+    let mut record_types = Vec::new(env);
+    for t in types {
+        record_types.push_back(t.clone());
+    }
+    RecordFilter {
+        record_types,
+        from,
+        to,
+    }
+}
+
+fn audit_filter(env: &Env, patient: Option<Address>) -> AuditFilter {
+    AuditFilter {
+        patient,
+        actor: None,
+        record_id: None,
+        actions: Vec::new(env),
+        results: Vec::new(env),
+        from: None,
+        to: None,
+    }
+}
+
+// ======================== Records ========================
+
+#[test]
+fn test_records_page_walks_all_ids_in_order() {
+    let ctx = setup();
+    let ids = add_records(
+        &ctx,
+        &[
+            RecordType::Examination,
+            RecordType::Diagnosis,
+            RecordType::Examination,
+            RecordType::Prescription,
+            RecordType::Examination,
+        ],
+    );
+
+    let mut seen = Vec::new(&ctx.env);
+    let mut cursor = None;
+    let mut pages = 0;
+    loop {
+        let page = ctx.client.get_patient_records_page(
+            &ctx.patient,
+            &record_filter(&ctx.env, &[], None, None),
+            &cursor,
+            &2,
+        );
+        assert!(page.record_ids.len() <= 2);
+        seen.append(&page.record_ids);
+        pages += 1;
+        match page.next_cursor {
+            Some(next) => cursor = Some(next),
+            None => break,
+        }
+    }
+    assert_eq!(seen, ids);
+    assert_eq!(pages, 3);
+}
+
+#[test]
+fn test_records_page_filters_by_type_and_date() {
+    let ctx = setup();
+    let ids = add_records(
+        &ctx,
+        &[
+            RecordType::Examination,
+            RecordType::Diagnosis,
+            RecordType::Examination,
+            RecordType::Examination,
+        ],
+    );
+
+    let exams = ctx.client.get_patient_records_page(
+        &ctx.patient,
+        &record_filter(&ctx.env, &[RecordType::Examination], None, None),
+        &None,
+        &10,
+    );
+    assert_eq!(
+        exams.record_ids,
+        Vec::from_array(
+            &ctx.env,
+            [
+                ids.get(0).unwrap(),
+                ids.get(2).unwrap(),
+                ids.get(3).unwrap()
+            ]
+        )
+    );
+    assert_eq!(exams.next_cursor, None);
+
+    // Second and third day only.
+    let window = ctx.client.get_patient_records_page(
+        &ctx.patient,
+        &record_filter(&ctx.env, &[], Some(10_000 + DAY), Some(10_000 + 2 * DAY)),
+        &None,
+        &10,
+    );
+    assert_eq!(
+        window.record_ids,
+        Vec::from_array(&ctx.env, [ids.get(1).unwrap(), ids.get(2).unwrap()])
+    );
+}
+
+#[test]
+fn test_page_arguments_are_validated() {
+    let ctx = setup();
+
+    for limit in [0u32, 101u32] {
+        let result = ctx.client.try_get_patient_records_page(
+            &ctx.patient,
+            &record_filter(&ctx.env, &[], None, None),
+            &None,
+            &limit,
+        );
+        assert_eq!(result.unwrap_err().unwrap(), ContractError::InvalidInput);
+    }
+
+    let inverted = ctx.client.try_get_patient_records_page(
+        &ctx.patient,
+        &record_filter(&ctx.env, &[], Some(20_000), Some(10_000)),
+        &None,
+        &10,
+    );
+    assert_eq!(inverted.unwrap_err().unwrap(), ContractError::InvalidInput);
+
+    let empty = ctx.client.get_patient_records_page(
+        &ctx.patient,
+        &record_filter(&ctx.env, &[], None, None),
+        &None,
+        &10,
+    );
+    assert!(empty.record_ids.is_empty());
+    assert_eq!(empty.next_cursor, None);
+}
+
+// ======================== Indexes ========================
+
+#[test]
+fn test_provider_index_lists_latest_exams_first() {
+    let ctx = setup();
+    let ids = add_records(
+        &ctx,
+        &[
+            RecordType::Examination,
+            RecordType::Diagnosis,
+            RecordType::Examination,
+            RecordType::Examination,
+        ],
+    );
+
+    // Records from another provider, created in a batch.
+    let other = ctx.register(Role::Ophthalmologist, "Dr. Other");
+    let mut batch = Vec::new(&ctx.env);
+    for _ in 0..2 {
+        batch.push_back(BatchRecordInput {
+            patient: ctx.patient.clone(),
+            record_type: RecordType::Examination,
+            data_hash: String::from_str(&ctx.env, DATA_HASH),
+        });
+    }
+    let batch_ids = ctx.client.add_records(&other, &batch);
+
+    let exams = record_filter(&ctx.env, &[RecordType::Examination], None, None);
+    let first = ctx
+        .client
+        .get_provider_records_page(&ctx.provider, &exams, &true, &None, &2);
+    assert_eq!(
+        first.record_ids,
+        Vec::from_array(&ctx.env, [ids.get(3).unwrap(), ids.get(2).unwrap()])
+    );
+    let rest =
+        ctx.client
+            .get_provider_records_page(&ctx.provider, &exams, &true, &first.next_cursor, &2);
+    assert_eq!(
+        rest.record_ids,
+        Vec::from_array(&ctx.env, [ids.get(0).unwrap()])
+    );
+    assert_eq!(rest.next_cursor, None);
+
+    let everything = record_filter(&ctx.env, &[], None, None);
+    let page = ctx
+        .client
+        .get_provider_records_page(&other, &everything, &false, &None, &10);
+    assert_eq!(page.record_ids, batch_ids);
+}
+
+#[test]
+fn test_type_and_day_indexes() {
+    let ctx = setup();
+    let ids = add_records(
+        &ctx,
+        &[
+            RecordType::Examination,
+            RecordType::Prescription,
+            RecordType::Examination,
+        ],
+    );
+
+    let exams = ctx.client.get_patient_records_by_type(
+        &ctx.patient,
+        &RecordType::Examination,
+        &false,
+        &None,
+        &10,
+    );
+    assert_eq!(
+        exams.record_ids,
+        Vec::from_array(&ctx.env, [ids.get(0).unwrap(), ids.get(2).unwrap()])
+    );
+    let surgeries = ctx.client.get_patient_records_by_type(
+        &ctx.patient,
+        &RecordType::Surgery,
+        &false,
+        &None,
+        &10,
+    );
+    assert!(surgeries.record_ids.is_empty());
+
+    // Records were added one day apart starting at t = 10_000 (day 0).
+    let day_one = ctx
+        .client
+        .get_records_by_day(&ctx.provider, &1, &false, &None, &10);
+    assert_eq!(
+        day_one.record_ids,
+        Vec::from_array(&ctx.env, [ids.get(1).unwrap()])
+    );
+    assert!(ctx
+        .client
+        .get_records_by_day(&ctx.provider, &3, &false, &None, &10)
+        .record_ids
+        .is_empty());
+    // Each provider has its own day list.
+    let other = Address::generate(&ctx.env);
+    assert!(ctx
+        .client
+        .get_records_by_day(&other, &1, &false, &None, &10)
+        .record_ids
+        .is_empty());
+    let invalid = ctx
+        .client
+        .try_get_records_by_day(&ctx.provider, &0, &false, &None, &0);
+    assert_eq!(invalid.unwrap_err().unwrap(), ContractError::InvalidInput);
+}
+
+/// Writes records the way they were stored before the provider, type and
+/// day indexes existed: the record plus the patient's monolithic list.
+fn write_unindexed_records(ctx: &TestContext, types: &[RecordType]) {
+    ctx.env.as_contract(&ctx.client.address, || {
+        let storage = ctx.env.storage();
+        let mut ids = Vec::<u64>::new(&ctx.env);
+        for (index, record_type) in types.iter().enumerate() {
+            let id = index as u64 + 1;
+            let record = VisionRecord {
+                id,
+                patient: ctx.patient.clone(),
+                provider: ctx.provider.clone(),
+                record_type: record_type.clone(),
+                data_hash: String::from_str(&ctx.env, DATA_HASH),
+                key_version: None,
+                created_at: 5_000 + id,
+                updated_at: 5_000 + id,
+            };
+            storage
+                .persistent()
+                .set(&(symbol_short!("RECORD"), id), &record);
+            ids.push_back(id);
+        }
+        storage
+            .persistent()
+            .set(&(symbol_short!("PAT_REC"), ctx.patient.clone()), &ids);
+        storage
+            .instance()
+            .set(&symbol_short!("REC_CTR"), &(types.len() as u64));
+    });
+}
+
+#[test]
+fn test_backfill_adds_older_records_ahead_of_new_ones() {
+    let ctx = setup();
+    write_unindexed_records(
+        &ctx,
+        &[
+            RecordType::Examination,
+            RecordType::Prescription,
+            RecordType::Examination,
+        ],
+    );
+    let new_ids = add_records(&ctx, &[RecordType::Examination]);
+    let new_id = new_ids.get(0).unwrap();
+    assert_eq!(new_id, 4);
+
+    let everything = record_filter(&ctx.env, &[], None, None);
+    let before =
+        ctx.client
+            .get_provider_records_page(&ctx.provider, &everything, &false, &None, &10);
+    assert_eq!(before.record_ids, new_ids);
+
+    let stranger = Address::generate(&ctx.env);
+    assert_eq!(
+        ctx.client
+            .try_backfill_record_indexes(&stranger, &2)
+            .unwrap_err()
+            .unwrap(),
+        ContractError::Unauthorized
+    );
+    assert_eq!(
+        ctx.client
+            .try_backfill_record_indexes(&ctx.admin, &0)
+            .unwrap_err()
+            .unwrap(),
+        ContractError::InvalidInput
+    );
+
+    assert_eq!(ctx.client.backfill_record_indexes(&ctx.admin, &2), 1);
+    assert_eq!(ctx.client.backfill_record_indexes(&ctx.admin, &5), 0);
+    // Nothing is added twice once the backfill is complete.
+    assert_eq!(ctx.client.backfill_record_indexes(&ctx.admin, &5), 0);
+
+    let after =
+        ctx.client
+            .get_provider_records_page(&ctx.provider, &everything, &false, &None, &10);
+    assert_eq!(
+        after.record_ids,
+        Vec::from_array(&ctx.env, [1, 2, 3, new_id])
+    );
+    let latest = ctx
+        .client
+        .get_provider_records_page(&ctx.provider, &everything, &true, &None, &2);
+    assert_eq!(latest.record_ids, Vec::from_array(&ctx.env, [new_id, 3]));
+
+    let exams = ctx.client.get_patient_records_by_type(
+        &ctx.patient,
+        &RecordType::Examination,
+        &false,
+        &None,
+        &10,
+    );
+    assert_eq!(exams.record_ids, Vec::from_array(&ctx.env, [1, 3, new_id]));
+    let day_zero = ctx
+        .client
+        .get_records_by_day(&ctx.provider, &0, &false, &None, &10);
+    assert_eq!(
+        day_zero.record_ids,
+        Vec::from_array(&ctx.env, [1, 2, 3, new_id])
+    );
+}
+
+// ======================== Grants ========================
+
+#[test]
+fn test_grants_page_skips_revoked_grants() {
+    let ctx = setup();
+    let a = Address::generate(&ctx.env);
+    let b = Address::generate(&ctx.env);
+    let c = Address::generate(&ctx.env);
+
+    ctx.client
+        .grant_access(&ctx.patient, &ctx.patient, &a, &AccessLevel::Read, &DAY);
+    ctx.client.grant_access_batch(
+        &ctx.patient,
+        &Vec::from_array(
+            &ctx.env,
+            [
+                BatchGrantInput {
+                    grantee: b.clone(),
+                    level: AccessLevel::Write,
+                    duration_seconds: DAY,
+                },
+                BatchGrantInput {
+                    grantee: c.clone(),
+                    level: AccessLevel::Full,
+                    duration_seconds: DAY,
+                },
+            ],
+        ),
+    );
+    ctx.client.revoke_access(&ctx.patient, &b);
+
+    let first = ctx.client.get_patient_grants(&ctx.patient, &None, &1);
+    assert_eq!(first.grants.len(), 1);
+    assert_eq!(first.grants.get(0).unwrap().grantee, a);
+
+    let rest = ctx
+        .client
+        .get_patient_grants(&ctx.patient, &first.next_cursor, &10);
+    assert_eq!(rest.grants.len(), 1);
+    assert_eq!(rest.grants.get(0).unwrap().grantee, c);
+    assert_eq!(rest.grants.get(0).unwrap().level, AccessLevel::Full);
+    assert_eq!(rest.next_cursor, None);
+
+    // Granting `a` again updates its grant without listing it twice.
+    ctx.client
+        .grant_access(&ctx.patient, &ctx.patient, &a, &AccessLevel::Full, &DAY);
+    let all = ctx.client.get_patient_grants(&ctx.patient, &None, &10);
+    assert_eq!(all.grants.len(), 2);
+    assert_eq!(all.grants.get(0).unwrap().level, AccessLevel::Full);
+}
+
+// ======================== Audit Log ========================
+
+#[test]
+fn test_patient_pages_own_audit_log() {
+    let ctx = setup();
+    let ids = add_records(&ctx, &[RecordType::Examination]);
+    let record_id = ids.get(0).unwrap();
+
+    // Three reads on consecutive days.
+    for _ in 0..3 {
+        ctx.client.get_record(&ctx.patient, &record_id);
+        let now = ctx.env.ledger().timestamp();
+        ctx.env.ledger().set_timestamp(now + DAY);
+    }
+
+    let mut own = audit_filter(&ctx.env, Some(ctx.patient.clone()));
+    own.actions.push_back(AccessAction::Read);
+    let first = ctx.client.get_audit_log(&ctx.patient, &own, &None, &2);
+    assert_eq!(first.entries.len(), 2);
+    assert!(first.next_cursor.is_some());
+    let rest = ctx
+        .client
+        .get_audit_log(&ctx.patient, &own, &first.next_cursor, &2);
+    assert_eq!(rest.entries.len(), 1);
+    assert_eq!(rest.next_cursor, None);
+    assert!(first.entries.get(1).unwrap().id < rest.entries.get(0).unwrap().id);
+
+    // The second day only.
+    let mut day_two = audit_filter(&ctx.env, Some(ctx.patient.clone()));
+    day_two.from = Some(10_000 + 2 * DAY);
+    day_two.to = Some(10_000 + 2 * DAY);
+    let page = ctx.client.get_audit_log(&ctx.patient, &day_two, &None, &10);
+    assert_eq!(page.entries.len(), 1);
+    assert_eq!(page.entries.get(0).unwrap().timestamp, 10_000 + 2 * DAY);
+    assert_eq!(page.next_cursor, None);
+}
+
+/// Writes audit entries the way they were stored before the per-key lists:
+/// the entry plus per-id flags.
+fn write_legacy_audit_entries(ctx: &TestContext, count: u64) {
+    ctx.env.as_contract(&ctx.client.address, || {
+        let storage = ctx.env.storage();
+        for id in 1..=count {
+            let entry = AuditEntry {
+                id,
+                timestamp: 5_000 + id,
+                actor: ctx.provider.clone(),
+                patient: ctx.patient.clone(),
+                record_id: None,
+                action: AccessAction::Read,
+                result: AccessResult::Success,
+                reason: None,
+                ip_address: None,
+                user_agent: None,
+            };
+            storage
+                .persistent()
+                .set(&(symbol_short!("AUD_ENT"), id), &entry);
+            storage
+                .persistent()
+                .set(&(symbol_short!("AUD_PAT"), ctx.patient.clone(), id), &true);
+            storage
+                .persistent()
+                .set(&(symbol_short!("AUD_USR"), ctx.provider.clone(), id), &true);
+        }
+        storage.instance().set(&symbol_short!("AUD_CTR"), &count);
+    });
+}
+
+#[test]
+fn test_audit_log_spans_legacy_and_listed_entries() {
+    let ctx = setup();
+    write_legacy_audit_entries(&ctx, 3);
+    let ids = add_records(&ctx, &[RecordType::Examination]);
+    for _ in 0..30 {
+        ctx.client.get_record(&ctx.patient, &ids.get(0).unwrap());
+    }
+
+    // 3 legacy entries and 30 reads.
+    let own = audit_filter(&ctx.env, Some(ctx.patient.clone()));
+    let mut seen = Vec::new(&ctx.env);
+    let mut cursor = None;
+    loop {
+        let page = ctx.client.get_audit_log(&ctx.patient, &own, &cursor, &25);
+        for entry in page.entries.iter() {
+            seen.push_back(entry.id);
+        }
+        cursor = page.next_cursor;
+        if cursor.is_none() {
+            break;
+        }
+    }
+    assert_eq!(seen.len(), 33);
+    for (i, id) in seen.iter().enumerate() {
+        assert_eq!(id, i as u64 + 1);
+    }
+
+    // A time window starting inside the legacy entries.
+    let mut window = audit_filter(&ctx.env, Some(ctx.patient.clone()));
+    window.from = Some(5_002);
+    let page = ctx.client.get_audit_log(&ctx.patient, &window, &None, &3);
+    assert_eq!(page.entries.get(0).unwrap().id, 2);
+    assert_eq!(page.entries.get(2).unwrap().id, 4);
+
+    // The provider only appears in the legacy entries.
+    let mut by_provider = audit_filter(&ctx.env, None);
+    by_provider.actor = Some(ctx.provider.clone());
+    let page = ctx
+        .client
+        .get_audit_log(&ctx.admin, &by_provider, &None, &25);
+    assert_eq!(page.entries.len(), 3);
+    assert_eq!(page.next_cursor, None);
+
+    #[allow(deprecated)]
+    ctx.env.as_contract(&ctx.client.address, || {
+        assert_eq!(
+            audit::get_patient_audit_log(&ctx.env, &ctx.patient).len(),
+            25
+        );
+        assert_eq!(
+            audit::get_recent_audit_log(&ctx.env, 5).get(4).unwrap().id,
+            33
+        );
+    });
+}
+
+#[test]
+fn test_audit_log_requires_patient_or_admin() {
+    let ctx = setup();
+    let ids = add_records(&ctx, &[RecordType::Examination]);
+    ctx.client.get_record(&ctx.patient, &ids.get(0).unwrap());
+
+    let stranger = Address::generate(&ctx.env);
+    let other_patient = audit_filter(&ctx.env, Some(ctx.patient.clone()));
+    let denied = ctx
+        .client
+        .try_get_audit_log(&stranger, &other_patient, &None, &10);
+    assert_eq!(denied.unwrap_err().unwrap(), ContractError::Unauthorized);
+
+    let everything = audit_filter(&ctx.env, None);
+    let denied = ctx
+        .client
+        .try_get_audit_log(&ctx.patient, &everything, &None, &10);
+    assert_eq!(denied.unwrap_err().unwrap(), ContractError::Unauthorized);
+
+    let page = ctx
+        .client
+        .get_audit_log(&ctx.admin, &everything, &None, &10);
+    assert_eq!(page.entries.len(), 1);
+}
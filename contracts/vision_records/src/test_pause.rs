@@ -1,8 +1,13 @@
+use crate::test_support::verify_provider;
 use crate::{
-    circuit_breaker::PauseScope, rbac::Role, ContractError, RecordType, VisionRecordsContract,
+    circuit_breaker::{PauseScope, TripRule, TripTrigger},
+    rbac::Role,
+    AccessLevel, BatchGrantInput, ConsentType, ContractError, RecordType, VisionRecordsContract,
     VisionRecordsContractClient,
 };
-use soroban_sdk::{symbol_short, testutils::Address as _, Address, Env, String};
+use soroban_sdk::{
+    symbol_short, testutils::Address as _, testutils::Ledger as _, Address, Env, String, Vec,
+};
 
 fn setup_test() -> (Env, VisionRecordsContractClient<'static>, Address) {
     let env = Env::default();
@@ -66,6 +71,7 @@ fn test_granular_pause() {
         &Role::Optometrist,
         &String::from_str(&env, "Doc"),
     );
+    verify_provider(&env, &client, &admin, &doctor);
 
     // Admin pauses ONLY `ADD_REC` function
     let add_rec_scope = PauseScope::Function(symbol_short!("ADD_REC"));
@@ -108,3 +114,163 @@ fn test_unauthorized_pause() {
     let res = client.try_pause_contract(&staff, &PauseScope::Global);
     assert_eq!(res.unwrap_err().unwrap(), ContractError::Unauthorized);
 }
+
+fn batch_rule(scope: PauseScope) -> TripRule {
+    TripRule {
+        trigger: TripTrigger::BatchGrantBurst,
+        threshold: 2,
+        window_seconds: 3600,
+        scope,
+        cooldown_seconds: 600,
+    }
+}
+
+fn one_grant(env: &Env) -> Vec<BatchGrantInput> {
+    Vec::from_array(
+        env,
+        [BatchGrantInput {
+            grantee: Address::generate(env),
+            level: AccessLevel::Read,
+            duration_seconds: 3600,
+        }],
+    )
+}
+
+#[test]
+fn test_batch_grant_burst_trips_and_auto_resumes() {
+    let (env, client, admin) = setup_test();
+    env.ledger().set_timestamp(10_000);
+    client.set_trip_rule(&admin, &batch_rule(PauseScope::Global));
+
+    let patient = Address::generate(&env);
+    let other = Address::generate(&env);
+    client.grant_access_batch(&patient, &one_grant(&env));
+    client.grant_access_batch(&patient, &one_grant(&env));
+    // Each caller is counted on their own.
+    client.grant_access_batch(&other, &one_grant(&env));
+    assert!(client.get_auto_pause(&PauseScope::Global).is_none());
+
+    // The third call in the window trips the breaker for later calls.
+    client.grant_access_batch(&patient, &one_grant(&env));
+    let auto = client.get_auto_pause(&PauseScope::Global).unwrap();
+    assert_eq!(auto.tripped_by, patient);
+    assert_eq!(auto.observed, 3);
+    assert_eq!(auto.resume_at, 10_600);
+    let res = client.try_grant_access_batch(&patient, &one_grant(&env));
+    assert_eq!(res.unwrap_err().unwrap(), ContractError::Paused);
+
+    env.ledger().set_timestamp(10_600);
+    client.grant_access(&patient, &patient, &admin, &AccessLevel::Read, &3600);
+    assert!(client.get_auto_pause(&PauseScope::Global).is_none());
+}
+
+#[test]
+fn test_confirmed_auto_pause_stays_until_resumed() {
+    let (env, client, admin) = setup_test();
+    let scope = PauseScope::Function(symbol_short!("GRT_ACC"));
+    client.set_trip_rule(&admin, &batch_rule(scope.clone()));
+
+    let patient = Address::generate(&env);
+    for _ in 0..3 {
+        client.grant_access_batch(&patient, &one_grant(&env));
+    }
+
+    let staff = Address::generate(&env);
+    let res = client.try_confirm_auto_pause(&staff, &scope);
+    assert_eq!(res.unwrap_err().unwrap(), ContractError::Unauthorized);
+    client.confirm_auto_pause(&admin, &scope);
+
+    env.ledger()
+        .set_timestamp(env.ledger().timestamp() + 10_000);
+    let grantee = Address::generate(&env);
+    let res = client.try_grant_access(&patient, &patient, &grantee, &AccessLevel::Read, &3600);
+    assert_eq!(res.unwrap_err().unwrap(), ContractError::Paused);
+
+    client.resume_contract(&admin, &scope);
+    client.grant_access(&patient, &patient, &grantee, &AccessLevel::Read, &3600);
+}
+
+#[test]
+fn test_trip_rule_validation_and_removal() {
+    let (_env, client, admin) = setup_test();
+    let invalid = TripRule {
+        threshold: 0,
+        ..batch_rule(PauseScope::Global)
+    };
+    let res = client.try_set_trip_rule(&admin, &invalid);
+    assert_eq!(res.unwrap_err().unwrap(), ContractError::InvalidInput);
+
+    client.set_trip_rule(&admin, &batch_rule(PauseScope::Global));
+    assert_eq!(
+        client.get_trip_rule(&TripTrigger::BatchGrantBurst),
+        Some(batch_rule(PauseScope::Global))
+    );
+    client.remove_trip_rule(&admin, &TripTrigger::BatchGrantBurst);
+    assert!(client
+        .get_trip_rule(&TripTrigger::BatchGrantBurst)
+        .is_none());
+}
+
+#[test]
+fn test_lapsed_auto_pause_can_trip_again() {
+    let (env, client, admin) = setup_test();
+    env.ledger().set_timestamp(10_000);
+    client.set_trip_rule(&admin, &batch_rule(PauseScope::Global));
+
+    let patient = Address::generate(&env);
+    for _ in 0..3 {
+        client.grant_access_batch(&patient, &one_grant(&env));
+    }
+    assert!(client.get_auto_pause(&PauseScope::Global).is_some());
+
+    // Past the cooldown and the counting window, a new burst pauses again.
+    env.ledger().set_timestamp(20_000);
+    assert!(client.get_auto_pause(&PauseScope::Global).is_none());
+    let res = client.try_confirm_auto_pause(&admin, &PauseScope::Global);
+    assert_eq!(res.unwrap_err().unwrap(), ContractError::InvalidInput);
+    for _ in 0..3 {
+        client.grant_access_batch(&patient, &one_grant(&env));
+    }
+    let auto = client.get_auto_pause(&PauseScope::Global).unwrap();
+    assert_eq!(auto.tripped_at, 20_000);
+    assert_eq!(auto.resume_at, 20_600);
+    let res = client.try_grant_access_batch(&patient, &one_grant(&env));
+    assert_eq!(res.unwrap_err().unwrap(), ContractError::Paused);
+}
+
+#[test]
+fn test_kept_denials_trip_per_caller() {
+    let (env, client, admin) = setup_test();
+    verify_provider(&env, &client, &admin, &admin);
+    let patient = Address::generate(&env);
+    let record_id = client.add_record(
+        &admin,
+        &patient,
+        &admin,
+        &RecordType::Examination,
+        &String::from_str(&env, "QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG"),
+    );
+    let scope = PauseScope::Function(symbol_short!("GRT_ACC"));
+    client.set_trip_rule(
+        &admin,
+        &TripRule {
+            trigger: TripTrigger::AccessDenied,
+            ..batch_rule(scope.clone())
+        },
+    );
+
+    let prober = Address::generate(&env);
+    let other = Address::generate(&env);
+    for caller in [&prober, &prober, &other, &other] {
+        client.get_record_for_purpose(caller, &record_id, &ConsentType::Treatment);
+    }
+    // A failed read rolls back its denial, so it is not counted.
+    assert!(client.try_get_record(&prober, &record_id).is_err());
+    assert!(client.get_auto_pause(&scope).is_none());
+
+    client.get_record_for_purpose(&prober, &record_id, &ConsentType::Treatment);
+    let auto = client.get_auto_pause(&scope).unwrap();
+    assert_eq!(auto.trigger, TripTrigger::AccessDenied);
+    assert_eq!(auto.tripped_by, prober);
+    assert_eq!(auto.observed, 3);
+}
@@ -0,0 +1,113 @@
+#![allow(
+    clippy::unwrap_used,
+    clippy::expect_used,
+    clippy::panic,
+    clippy::arithmetic_side_effects
+)]
+
+use super::ContractError;
+use crate::test_support::{self, TestContext};
+use soroban_sdk::{testutils::Address as _, testutils::Ledger as _, Address, Env};
+use teye_common::policy_analyzer::FindingKind;
+use teye_common::policy_dsl::PolicyDefinition;
+use teye_common::policy_syntax::compile;
+
+// ── Helpers ──────────────────────────────────────────────────────
+
+fn setup() -> TestContext {
+    let ctx = test_support::setup();
+    ctx.env.ledger().set_timestamp(2_000_000_000);
+    ctx
+}
+
+fn policy(env: &Env, source: &str) -> PolicyDefinition {
+    compile(env, source).unwrap()
+}
+
+// ── Tests ────────────────────────────────────────────────────────
+
+#[test]
+fn test_analysis_is_opt_in() {
+    let ctx = setup();
+    let permit = policy(
+        &ctx.env,
+        r#"policy "retina_permit" permit if role == "optometrist" and dept in ["retina", "glaucoma"]"#,
+    );
+    let deny = policy(
+        &ctx.env,
+        r#"policy "retina_deny" deny if dept in ["glaucoma", "retina"] and role == "optometrist""#,
+    );
+
+    assert!(!ctx.client.is_policy_analysis_required());
+    ctx.client.store_policy(&ctx.admin, &permit);
+    ctx.client.store_policy(&ctx.admin, &deny);
+    assert_eq!(ctx.client.list_policies().len(), 2);
+    let page = ctx.client.list_policies_page(&None, &1);
+    assert_eq!(page.policies.len(), 1);
+    let rest = ctx.client.list_policies_page(&page.next_cursor, &1);
+    assert_eq!(rest.policies.len(), 1);
+    assert_eq!(rest.next_cursor, None);
+
+    // The view still reports the overlap without storing anything.
+    let findings = ctx.client.analyze_policy(&deny);
+    assert_eq!(findings.len(), 1);
+    let finding = findings.get(0).unwrap();
+    assert_eq!(finding.kind, FindingKind::PermitDenyOverlap);
+    assert_eq!(finding.related.get(0).unwrap(), deny.id);
+}
+
+#[test]
+fn test_store_policy_rejects_findings_when_required() {
+    let ctx = setup();
+    ctx.client.set_policy_analysis(&ctx.admin, &true);
+    assert!(ctx.client.is_policy_analysis_required());
+
+    let broad = policy(
+        &ctx.env,
+        r#"policy "optometrists" priority 10 permit if role == "optometrist""#,
+    );
+    ctx.client.store_policy(&ctx.admin, &broad);
+
+    let rejected = [
+        // Shadowed by the broader, higher-priority policy.
+        r#"policy "retina" priority 20 permit if role == "optometrist" and dept == "retina""#,
+        // Contradictory branches.
+        r#"policy "nobody" deny if role == "nurse" and role == "optometrist""#,
+        // Wrong arity.
+        r#"policy "broken" permit if not(role == "nurse", role == "admin")"#,
+        // Expired before the current ledger time.
+        r#"policy "legacy" permit if time from 1000 until 1999999999"#,
+    ];
+    for source in rejected {
+        let candidate = policy(&ctx.env, source);
+        assert!(!ctx.client.analyze_policy(&candidate).is_empty());
+        assert_eq!(
+            ctx.client.try_store_policy(&ctx.admin, &candidate),
+            Err(Ok(ContractError::PolicyAnalysisFailed))
+        );
+    }
+    assert_eq!(ctx.client.list_policies().len(), 1);
+
+    // A new version replaces the stored policy instead of overlapping it.
+    let mut narrowed = policy(
+        &ctx.env,
+        r#"policy "optometrists" version 2 priority 10 permit if role == "optometrist" and time on mon..fri"#,
+    );
+    ctx.client.store_policy(&ctx.admin, &narrowed);
+
+    narrowed.id.version = 3;
+    narrowed.rule = policy(&ctx.env, r#"policy "x" permit if role == "nurse""#).rule;
+    ctx.client.store_policy(&ctx.admin, &narrowed);
+    assert_eq!(ctx.client.list_policies().get(0).unwrap(), narrowed.id);
+}
+
+#[test]
+fn test_set_policy_analysis_requires_admin() {
+    let ctx = setup();
+    let stranger = Address::generate(&ctx.env);
+    assert_eq!(
+        ctx.client.try_set_policy_analysis(&stranger, &true),
+        Err(Ok(ContractError::Unauthorized))
+    );
+    assert!(!ctx.client.is_policy_analysis_required());
+}
@@ -0,0 +1,94 @@
+#![allow(
+    clippy::unwrap_used,
+    clippy::expect_used,
+    clippy::panic,
+    clippy::arithmetic_side_effects
+)]
+
+use super::ContractError;
+use crate::test_support::{self, TestContext};
+use soroban_sdk::{Env, String};
+use teye_common::policy_dsl::{RuleKind, SimulationVerdict};
+use teye_common::policy_syntax::compile;
+
+// ── Helpers ──────────────────────────────────────────────────────
+
+/// Stores a "surgeons" policy that the optometrist does not satisfy.
+fn setup() -> TestContext {
+    let ctx = test_support::setup();
+    let policy = compile(
+        &ctx.env,
+        r#"policy "surgeons" permit if role == "ophthalmologist" or credential == "medical_license""#,
+    )
+    .unwrap();
+    ctx.client.store_policy(&ctx.admin, &policy);
+    ctx
+}
+
+fn read(env: &Env) -> String {
+    String::from_str(env, "read")
+}
+
+// ── Tests ────────────────────────────────────────────────────────
+
+#[test]
+fn test_admin_explains_denial() {
+    let ctx = setup();
+    let result =
+        ctx.client
+            .simulate_policy(&ctx.admin, &ctx.provider, &read(&ctx.env), &None, &true);
+
+    assert_eq!(result.verdict, SimulationVerdict::Denied);
+    assert!(!result.trace_truncated);
+    assert_eq!(result.trace.len(), 3);
+
+    let root = result.trace.get(0).unwrap();
+    assert_eq!(root.kind, RuleKind::Or);
+    assert!(!root.result);
+
+    let role = result.trace.get(1).unwrap();
+    assert_eq!(role.kind, RuleKind::Attribute);
+    assert!(!role.result);
+    assert_eq!(role.attr_key, String::from_str(&ctx.env, "role"));
+    assert_eq!(
+        role.expected.get(0).unwrap(),
+        String::from_str(&ctx.env, "ophthalmologist")
+    );
+    assert_eq!(
+        role.actual.get(0).unwrap(),
+        String::from_str(&ctx.env, "optometrist")
+    );
+
+    // The clinician has no credential, so nothing was compared.
+    let credential = result.trace.get(2).unwrap();
+    assert_eq!(credential.path.get(0), Some(1));
+    assert!(credential.actual.is_empty());
+}
+
+#[test]
+fn test_simulate_without_explain_has_no_trace() {
+    let ctx = setup();
+    let result =
+        ctx.client
+            .simulate_policy(&ctx.provider, &ctx.provider, &read(&ctx.env), &None, &false);
+    // Simulating for oneself still needs the caller's signature.
+    assert_eq!(ctx.env.auths().len(), 1);
+    assert_eq!(ctx.env.auths()[0].0, ctx.provider);
+    assert_eq!(result.verdict, SimulationVerdict::Denied);
+    assert_eq!(result.evaluated_count, 1);
+    assert!(result.trace.is_empty());
+}
+
+#[test]
+fn test_explain_and_other_subjects_require_admin() {
+    let ctx = setup();
+    let explain_self =
+        ctx.client
+            .try_simulate_policy(&ctx.provider, &ctx.provider, &read(&ctx.env), &None, &true);
+    assert_eq!(explain_self.err(), Some(Ok(ContractError::Unauthorized)));
+
+    let other_subject =
+        ctx.client
+            .try_simulate_policy(&ctx.provider, &ctx.admin, &read(&ctx.env), &None, &false);
+    assert_eq!(other_subject.err(), Some(Ok(ContractError::Unauthorized)));
+}
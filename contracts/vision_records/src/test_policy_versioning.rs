@@ -0,0 +1,136 @@
+#![allow(
+    clippy::unwrap_used,
+    clippy::expect_used,
+    clippy::panic,
+    clippy::arithmetic_side_effects
+)]
+
+use super::ContractError;
+use crate::test_support::{self, TestContext};
+use soroban_sdk::{testutils::Address as _, testutils::Ledger as _, Address, Env, String};
+use teye_common::policy_dsl::{PolicyDefinition, PolicyEffect};
+use teye_common::policy_engine::RolloutMode;
+use teye_common::policy_syntax::compile;
+
+// ── Helpers ──────────────────────────────────────────────────────
+
+fn setup() -> TestContext {
+    let ctx = test_support::setup();
+    ctx.client
+        .store_policy(&ctx.admin, &version(&ctx.env, 1, "optometrist"));
+    ctx
+}
+
+/// Version `n` of the "clinic" policy, permitting `role`.
+fn version(env: &Env, n: u32, role: &str) -> PolicyDefinition {
+    let source = alloc::format!(r#"policy "clinic" version {n} permit if role == "{role}""#);
+    compile(env, &source).unwrap()
+}
+
+fn name(env: &Env) -> String {
+    String::from_str(env, "clinic")
+}
+
+fn permitted(ctx: &TestContext) -> bool {
+    ctx.client
+        .evaluate_policy_engine(&ctx.provider, &String::from_str(&ctx.env, "read"), &None)
+}
+
+// ── Tests ────────────────────────────────────────────────────────
+
+#[test]
+fn test_scheduled_version_goes_live_at_activation_time() {
+    let ctx = setup();
+    let v2 = version(&ctx.env, 2, "ophthalmologist");
+
+    assert_eq!(
+        ctx.client.try_schedule_policy(&ctx.admin, &v2, &10_000),
+        Err(Ok(ContractError::InvalidTimestamp))
+    );
+    let rollout = ctx.client.schedule_policy(&ctx.admin, &v2, &20_000);
+    assert_eq!(rollout.mode, RolloutMode::Scheduled);
+    assert!(rollout.backward_compatible);
+    assert!(permitted(&ctx));
+
+    ctx.env.ledger().set_timestamp(20_000);
+    assert!(!permitted(&ctx));
+    assert_eq!(ctx.client.list_policies().get(0).unwrap().version, 1);
+    assert_eq!(ctx.client.apply_policy_rollouts(), 1);
+    assert_eq!(ctx.client.list_policies().get(0).unwrap(), v2.id);
+    assert!(ctx.client.get_policy_rollout(&name(&ctx.env)).is_none());
+
+    let history = ctx.client.get_policy_history(&name(&ctx.env));
+    assert_eq!(history.len(), 2);
+    assert_eq!(
+        ctx.client.try_schedule_policy(&ctx.admin, &v2, &30_000),
+        Err(Ok(ContractError::DuplicateRecord))
+    );
+}
+
+#[test]
+fn test_shadow_version_logs_divergences() {
+    let ctx = setup();
+    let v2 = version(&ctx.env, 2, "ophthalmologist");
+    ctx.client.shadow_policy(&ctx.admin, &v2);
+
+    // Decisions still follow version 1.
+    assert!(permitted(&ctx));
+    assert!(ctx
+        .env
+        .auths()
+        .iter()
+        .any(|(address, _)| *address == ctx.provider));
+    let log = ctx.client.get_shadow_divergences(&name(&ctx.env));
+    assert_eq!(log.len(), 1);
+    let divergence = log.get(0).unwrap();
+    assert_eq!(divergence.subject, ctx.provider);
+    assert_eq!(divergence.active_effect, PolicyEffect::Permit);
+    assert_eq!(divergence.candidate_effect, PolicyEffect::Deny);
+
+    assert_eq!(
+        ctx.client.promote_policy(&ctx.admin, &name(&ctx.env)),
+        v2.id
+    );
+    assert!(!permitted(&ctx));
+    assert_eq!(
+        ctx.client
+            .try_cancel_policy_rollout(&ctx.admin, &name(&ctx.env)),
+        Err(Ok(ContractError::PolicyRolloutNotFound))
+    );
+}
+
+#[test]
+fn test_rollback_restores_prior_version() {
+    let ctx = setup();
+    ctx.client
+        .store_policy(&ctx.admin, &version(&ctx.env, 2, "ophthalmologist"));
+    ctx.client
+        .shadow_policy(&ctx.admin, &version(&ctx.env, 3, "staff"));
+    assert!(!permitted(&ctx));
+
+    ctx.client.rollback_policy(&ctx.admin, &name(&ctx.env), &1);
+    assert!(permitted(&ctx));
+    assert_eq!(ctx.client.list_policies().get(0).unwrap().version, 1);
+    // The staged version is dropped along with the rollback.
+    assert!(ctx.client.get_policy_rollout(&name(&ctx.env)).is_none());
+    assert_eq!(ctx.client.get_policy_history(&name(&ctx.env)).len(), 3);
+
+    // Recorded versions cannot be rewritten in place.
+    assert_eq!(
+        ctx.client
+            .try_store_policy(&ctx.admin, &version(&ctx.env, 2, "staff")),
+        Err(Ok(ContractError::DuplicateRecord))
+    );
+
+    assert_eq!(
+        ctx.client
+            .try_rollback_policy(&ctx.admin, &name(&ctx.env), &7),
+        Err(Ok(ContractError::PolicyVersionNotFound))
+    );
+    let stranger = Address::generate(&ctx.env);
+    assert_eq!(
+        ctx.client
+            .try_rollback_policy(&stranger, &name(&ctx.env), &2),
+        Err(Ok(ContractError::Unauthorized))
+    );
+}
@@ -0,0 +1,369 @@
+#![allow(
+    clippy::unwrap_used,
+    clippy::expect_used,
+    clippy::panic,
+    clippy::arithmetic_side_effects
+)]
+
+use super::{
+    ContactLensData, ContractError, LensType, OptionalContactLensData, PrescriptionData,
+    PrescriptionStatus, Role, VisionRecordsContract, VisionRecordsContractClient,
+};
+use crate::test_support::verify_provider;
+use soroban_sdk::testutils::{Address as _, Events as _, Ledger as _};
+use soroban_sdk::xdr::{ContractEventBody, ScSymbol, ScVal};
+use soroban_sdk::{Address, Env, String};
+
+const ONE_YEAR: u64 = 31_536_000;
+const METADATA_HASH: &str = "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855";
+
+// ── Helpers ──────────────────────────────────────────────────────
+
+fn setup() -> (Env, VisionRecordsContractClient<'static>, Address) {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.ledger().set_timestamp(10_000);
+
+    let contract_id = env.register(VisionRecordsContract, ());
+    let client = VisionRecordsContractClient::new(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    (env, client, admin)
+}
+
+fn register(
+    env: &Env,
+    client: &VisionRecordsContractClient,
+    admin: &Address,
+    role: Role,
+) -> Address {
+    let user = Address::generate(env);
+    client.register_user(admin, &user, &role, &String::from_str(env, "User"));
+    if matches!(role, Role::Optometrist | Role::Ophthalmologist) {
+        verify_provider(env, client, admin, &user);
+    }
+    user
+}
+
+fn eye(env: &Env, sphere: &str, cylinder: &str, axis: &str) -> PrescriptionData {
+    PrescriptionData {
+        sphere: String::from_str(env, sphere),
+        cylinder: String::from_str(env, cylinder),
+        axis: String::from_str(env, axis),
+        add: String::from_str(env, "0.00"),
+        pd: String::from_str(env, "62"),
+    }
+}
+
+fn issue_glasses(
+    env: &Env,
+    client: &VisionRecordsContractClient,
+    doctor: &Address,
+    patient: &Address,
+) -> u64 {
+    client.issue_prescription(
+        doctor,
+        patient,
+        &LensType::Glasses,
+        &eye(env, "-2.50", "-1.25", "180"),
+        &eye(env, "-2.75", "-1.00", "175"),
+        &OptionalContactLensData::None,
+        &ONE_YEAR,
+        &String::from_str(env, METADATA_HASH),
+    )
+}
+
+/// Number of events from the last invocation whose first topic is `name`.
+fn events_named(env: &Env, name: &str) -> usize {
+    let expected = ScVal::Symbol(ScSymbol(name.try_into().unwrap()));
+    env.events()
+        .all()
+        .events()
+        .iter()
+        .filter(|event| match &event.body {
+            ContractEventBody::V0(body) => body.topics.first() == Some(&expected),
+        })
+        .count()
+}
+
+// ======================== Issuance ========================
+
+#[test]
+fn test_prescription_workflow() {
+    let (env, client, admin) = setup();
+    let doctor = register(&env, &client, &admin, Role::Optometrist);
+    let retailer = register(&env, &client, &admin, Role::Staff);
+    let patient = register(&env, &client, &admin, Role::Patient);
+
+    let rx_id = issue_glasses(&env, &client, &doctor, &patient);
+    assert_eq!(rx_id, 1);
+    assert_eq!(events_named(&env, "RX_ISS"), 1);
+    assert_eq!(events_named(&env, "STREAM"), 0);
+
+    let rx = client.get_prescription(&rx_id);
+    assert_eq!(rx.patient, patient);
+    assert_eq!(rx.provider, doctor);
+    assert_eq!(rx.expires_at, 10_000 + ONE_YEAR);
+    assert_eq!(rx.status, PrescriptionStatus::Active);
+    assert!(!rx.verified);
+
+    let verified = client.verify_prescription(&retailer, &rx_id);
+    assert!(verified.verified);
+    assert_eq!(verified.verified_by, Some(retailer.clone()));
+
+    client.dispense_prescription(&retailer, &rx_id);
+    let dispensed = client.get_prescription(&rx_id);
+    assert_eq!(dispensed.status, PrescriptionStatus::Dispensed);
+    assert_eq!(dispensed.dispensed_by, Some(retailer.clone()));
+    assert_eq!(dispensed.dispensed_at, Some(10_000));
+
+    // A prescription cannot be filled twice.
+    let again = client.try_dispense_prescription(&retailer, &rx_id);
+    assert_eq!(
+        again.unwrap_err().unwrap(),
+        ContractError::InvalidPrescriptionStatus
+    );
+
+    let history = client.get_prescription_history(&patient);
+    assert_eq!(history.len(), 1);
+    assert_eq!(history.get(0).unwrap(), rx_id);
+    let page = client.get_prescription_history_page(&patient, &true, &None, &10);
+    assert_eq!(page.prescription_ids, history);
+    assert_eq!(page.next_cursor, None);
+}
+
+#[test]
+fn test_contact_lens_workflow() {
+    let (env, client, admin) = setup();
+    let doctor = register(&env, &client, &admin, Role::Ophthalmologist);
+    let patient = register(&env, &client, &admin, Role::Patient);
+
+    let eye_data = eye(&env, "-3.00", "0.00", "0");
+    let contact_data = ContactLensData {
+        base_curve: String::from_str(&env, "8.6"),
+        diameter: String::from_str(&env, "14.2"),
+        brand: String::from_str(&env, "Acuvue"),
+    };
+
+    let rx_id = client.issue_prescription(
+        &doctor,
+        &patient,
+        &LensType::ContactLens,
+        &eye_data,
+        &eye_data,
+        &OptionalContactLensData::Some(contact_data),
+        &15_768_000, // 6 months
+        &String::from_str(&env, METADATA_HASH),
+    );
+
+    let rx = client.get_prescription(&rx_id);
+    assert_eq!(rx.lens_type, LensType::ContactLens);
+    assert!(matches!(rx.contact_data, OptionalContactLensData::Some(_)));
+
+    // Contact lens prescriptions must carry fitting data.
+    let missing = client.try_issue_prescription(
+        &doctor,
+        &patient,
+        &LensType::ContactLens,
+        &eye_data,
+        &eye_data,
+        &OptionalContactLensData::None,
+        &15_768_000,
+        &String::from_str(&env, METADATA_HASH),
+    );
+    assert_eq!(
+        missing.unwrap_err().unwrap(),
+        ContractError::InvalidPrescription
+    );
+}
+
+#[test]
+fn test_issue_requires_prescriber_role() {
+    let (env, client, admin) = setup();
+    let staff = register(&env, &client, &admin, Role::Staff);
+    let patient = register(&env, &client, &admin, Role::Patient);
+
+    let result = client.try_issue_prescription(
+        &staff,
+        &patient,
+        &LensType::Glasses,
+        &eye(&env, "-1.00", "0.00", "0"),
+        &eye(&env, "-1.00", "0.00", "0"),
+        &OptionalContactLensData::None,
+        &ONE_YEAR,
+        &String::from_str(&env, METADATA_HASH),
+    );
+    assert_eq!(result.unwrap_err().unwrap(), ContractError::Unauthorized);
+}
+
+#[test]
+fn test_issue_rejects_out_of_range_values() {
+    let (env, client, admin) = setup();
+    let doctor = register(&env, &client, &admin, Role::Optometrist);
+    let patient = register(&env, &client, &admin, Role::Patient);
+    let valid = eye(&env, "-1.00", "-0.50", "90");
+
+    let cases = [
+        eye(&env, "-30.00", "0.00", "0"), // sphere beyond -25 D
+        eye(&env, "-1.10", "0.00", "0"),  // not a quarter-dioptre step
+        eye(&env, "-1.00", "-12.00", "90"),
+        eye(&env, "-1.00", "-0.50", "181"),
+        eye(&env, "-1.00", "-0.50", "90.5"),
+        eye(&env, "abc", "0.00", "0"),
+    ];
+    for bad in cases.iter() {
+        let result = client.try_issue_prescription(
+            &doctor,
+            &patient,
+            &LensType::Glasses,
+            bad,
+            &valid,
+            &OptionalContactLensData::None,
+            &ONE_YEAR,
+            &String::from_str(&env, METADATA_HASH),
+        );
+        assert_eq!(
+            result.unwrap_err().unwrap(),
+            ContractError::InvalidPrescription
+        );
+    }
+
+    let mut bad_pd = valid.clone();
+    bad_pd.pd = String::from_str(&env, "95");
+    let result = client.try_issue_prescription(
+        &doctor,
+        &patient,
+        &LensType::Glasses,
+        &valid,
+        &bad_pd,
+        &OptionalContactLensData::None,
+        &ONE_YEAR,
+        &String::from_str(&env, METADATA_HASH),
+    );
+    assert_eq!(
+        result.unwrap_err().unwrap(),
+        ContractError::InvalidPrescription
+    );
+
+    // Validity outside 1 hour .. 5 years.
+    let result = client.try_issue_prescription(
+        &doctor,
+        &patient,
+        &LensType::Glasses,
+        &valid,
+        &valid,
+        &OptionalContactLensData::None,
+        &0,
+        &String::from_str(&env, METADATA_HASH),
+    );
+    assert_eq!(result.unwrap_err().unwrap(), ContractError::InvalidInput);
+}
+
+// ======================== Verification & Dispensing ========================
+
+#[test]
+fn test_expired_prescription_cannot_be_verified_or_dispensed() {
+    let (env, client, admin) = setup();
+    let doctor = register(&env, &client, &admin, Role::Optometrist);
+    let retailer = register(&env, &client, &admin, Role::Staff);
+    let patient = register(&env, &client, &admin, Role::Patient);
+
+    let rx_id = issue_glasses(&env, &client, &doctor, &patient);
+    client.verify_prescription(&retailer, &rx_id);
+
+    env.ledger().set_timestamp(10_000 + ONE_YEAR);
+
+    let verify = client.try_verify_prescription(&retailer, &rx_id);
+    assert_eq!(
+        verify.unwrap_err().unwrap(),
+        ContractError::PrescriptionExpired
+    );
+    let dispense = client.try_dispense_prescription(&retailer, &rx_id);
+    assert_eq!(
+        dispense.unwrap_err().unwrap(),
+        ContractError::PrescriptionExpired
+    );
+}
+
+#[test]
+fn test_dispense_requires_verification() {
+    let (env, client, admin) = setup();
+    let doctor = register(&env, &client, &admin, Role::Optometrist);
+    let retailer = register(&env, &client, &admin, Role::Staff);
+    let patient = register(&env, &client, &admin, Role::Patient);
+
+    let rx_id = issue_glasses(&env, &client, &doctor, &patient);
+    let result = client.try_dispense_prescription(&retailer, &rx_id);
+    assert_eq!(
+        result.unwrap_err().unwrap(),
+        ContractError::InvalidPrescriptionStatus
+    );
+}
+
+#[test]
+fn test_patient_cannot_verify_or_dispense() {
+    let (env, client, admin) = setup();
+    let doctor = register(&env, &client, &admin, Role::Optometrist);
+    let patient = register(&env, &client, &admin, Role::Patient);
+    let outsider = Address::generate(&env);
+
+    let rx_id = issue_glasses(&env, &client, &doctor, &patient);
+    for caller in [&patient, &outsider] {
+        let verify = client.try_verify_prescription(caller, &rx_id);
+        assert_eq!(verify.unwrap_err().unwrap(), ContractError::Unauthorized);
+        let dispense = client.try_dispense_prescription(caller, &rx_id);
+        assert_eq!(dispense.unwrap_err().unwrap(), ContractError::Unauthorized);
+    }
+}
+
+#[test]
+fn test_verify_missing_prescription() {
+    let (env, client, admin) = setup();
+    let retailer = register(&env, &client, &admin, Role::Staff);
+
+    let result = client.try_verify_prescription(&retailer, &99);
+    assert_eq!(
+        result.unwrap_err().unwrap(),
+        ContractError::PrescriptionNotFound
+    );
+}
+
+// ======================== Revocation ========================
+
+#[test]
+fn test_revoke_prescription() {
+    let (env, client, admin) = setup();
+    let doctor = register(&env, &client, &admin, Role::Optometrist);
+    let other_doctor = register(&env, &client, &admin, Role::Optometrist);
+    let retailer = register(&env, &client, &admin, Role::Staff);
+    let patient = register(&env, &client, &admin, Role::Patient);
+
+    let rx_id = issue_glasses(&env, &client, &doctor, &patient);
+
+    let denied =
+        client.try_revoke_prescription(&other_doctor, &rx_id, &String::from_str(&env, "not mine"));
+    assert_eq!(denied.unwrap_err().unwrap(), ContractError::Unauthorized);
+
+    client.revoke_prescription(
+        &doctor,
+        &rx_id,
+        &String::from_str(&env, "transcription error"),
+    );
+    assert_eq!(
+        client.get_prescription(&rx_id).status,
+        PrescriptionStatus::Revoked
+    );
+
+    let verify = client.try_verify_prescription(&retailer, &rx_id);
+    assert_eq!(
+        verify.unwrap_err().unwrap(),
+        ContractError::InvalidPrescriptionStatus
+    );
+    let again = client.try_revoke_prescription(&admin, &rx_id, &String::from_str(&env, "dup"));
+    assert_eq!(
+        again.unwrap_err().unwrap(),
+        ContractError::InvalidPrescriptionStatus
+    );
+}
@@ -0,0 +1,371 @@
+#![allow(
+    clippy::unwrap_used,
+    clippy::expect_used,
+    clippy::panic,
+    clippy::arithmetic_side_effects
+)]
+
+use super::{
+    Certification, ContractError, License, Location, RecordType, Role, VerificationStatus,
+    VisionRecordsContract, VisionRecordsContractClient,
+};
+use soroban_sdk::{testutils::Address as _, testutils::Ledger as _, Address, Env, String, Vec};
+
+const DATA_HASH: &str = "QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG";
+
+// ── Helpers ──────────────────────────────────────────────────────
+
+fn setup() -> (Env, VisionRecordsContractClient<'static>, Address) {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.ledger().set_timestamp(10_000);
+
+    let contract_id = env.register(VisionRecordsContract, ());
+    let client = VisionRecordsContractClient::new(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    (env, client, admin)
+}
+
+fn license(env: &Env, expiry_date: u64) -> License {
+    License {
+        number: String::from_str(env, "LIC123456"),
+        issuing_authority: String::from_str(env, "State Board"),
+        issued_date: 1_000,
+        expiry_date,
+        license_type: String::from_str(env, "Ophthalmology"),
+    }
+}
+
+fn location(env: &Env, city: &str, state: &str) -> Location {
+    Location {
+        name: String::from_str(env, "Main Office"),
+        address: String::from_str(env, "123 Main St"),
+        city: String::from_str(env, city),
+        state: String::from_str(env, state),
+        zip: String::from_str(env, "12345"),
+        country: String::from_str(env, "USA"),
+    }
+}
+
+/// Registers `provider` with the RBAC role and a provider profile, without
+/// verifying it.
+fn register_provider(
+    env: &Env,
+    client: &VisionRecordsContractClient,
+    admin: &Address,
+    specialty: &str,
+    city: &str,
+    license_expiry: u64,
+) -> Address {
+    let provider = Address::generate(env);
+    client.register_user(
+        admin,
+        &provider,
+        &Role::Ophthalmologist,
+        &String::from_str(env, "Dr. Iris"),
+    );
+    client.register_provider(
+        &provider,
+        &provider,
+        &String::from_str(env, "Dr. Iris"),
+        &Vec::from_array(env, [license(env, license_expiry)]),
+        &Vec::from_array(env, [String::from_str(env, specialty)]),
+        &Vec::<Certification>::new(env),
+        &Vec::from_array(env, [location(env, city, "CA")]),
+    );
+    provider
+}
+
+fn add_record(
+    env: &Env,
+    client: &VisionRecordsContractClient,
+    provider: &Address,
+) -> Result<u64, ContractError> {
+    let patient = Address::generate(env);
+    client
+        .try_add_record(
+            provider,
+            &patient,
+            provider,
+            &RecordType::Examination,
+            &String::from_str(env, DATA_HASH),
+        )
+        .map(|r| r.unwrap())
+        .map_err(|e| e.unwrap())
+}
+
+// ======================== Registration ========================
+
+#[test]
+fn test_register_provider_starts_pending() {
+    let (env, client, admin) = setup();
+    let provider = register_provider(&env, &client, &admin, "Retina", "Fresno", 1_000_000);
+
+    let profile = client.get_provider(&provider);
+    assert_eq!(profile.verification_status, VerificationStatus::Pending);
+    assert!(profile.is_active);
+    assert!(profile.verified_by.is_none());
+    assert_eq!(
+        client.search_providers_by_status(&VerificationStatus::Pending),
+        Vec::from_array(&env, [provider.clone()])
+    );
+    assert_eq!(
+        client.search_providers_by_specialty(&String::from_str(&env, "Retina")),
+        Vec::from_array(&env, [provider])
+    );
+}
+
+#[test]
+fn test_register_provider_rejects_duplicates_and_strangers() {
+    let (env, client, admin) = setup();
+    let provider = register_provider(&env, &client, &admin, "Retina", "Fresno", 1_000_000);
+    let empty_licenses = Vec::<License>::new(&env);
+    let empty_strings = Vec::<String>::new(&env);
+    let empty_certs = Vec::<Certification>::new(&env);
+    let empty_locations = Vec::<Location>::new(&env);
+
+    let duplicate = client.try_register_provider(
+        &admin,
+        &provider,
+        &String::from_str(&env, "Dr. Iris"),
+        &empty_licenses,
+        &empty_strings,
+        &empty_certs,
+        &empty_locations,
+    );
+    assert_eq!(
+        duplicate.unwrap_err().unwrap(),
+        ContractError::ProviderAlreadyRegistered
+    );
+
+    let stranger = Address::generate(&env);
+    let other = Address::generate(&env);
+    let denied = client.try_register_provider(
+        &stranger,
+        &other,
+        &String::from_str(&env, "Dr. Who"),
+        &empty_licenses,
+        &empty_strings,
+        &empty_certs,
+        &empty_locations,
+    );
+    assert_eq!(denied.unwrap_err().unwrap(), ContractError::Unauthorized);
+}
+
+#[test]
+fn test_register_provider_validates_licenses() {
+    let (env, client, _admin) = setup();
+    let provider = Address::generate(&env);
+    let register = |licenses: Vec<License>| {
+        client.try_register_provider(
+            &provider,
+            &provider,
+            &String::from_str(&env, "Dr. Iris"),
+            &licenses,
+            &Vec::<String>::new(&env),
+            &Vec::<Certification>::new(&env),
+            &Vec::<Location>::new(&env),
+        )
+    };
+
+    let mut blank_number = license(&env, 1_000_000);
+    blank_number.number = String::from_str(&env, "");
+    let mut future_issue = license(&env, 1_000_000);
+    future_issue.issued_date = 20_000;
+    let mut inverted = license(&env, 1_000_000);
+    inverted.issued_date = 5_000;
+    inverted.expiry_date = 5_000;
+
+    for licenses in [
+        Vec::new(&env),
+        Vec::from_array(&env, [blank_number]),
+        Vec::from_array(&env, [future_issue]),
+        Vec::from_array(&env, [inverted]),
+    ] {
+        assert_eq!(
+            register(licenses).unwrap_err().unwrap(),
+            ContractError::ProviderLicenseInvalid
+        );
+    }
+    assert!(register(Vec::from_array(&env, [license(&env, 1_000_000)])).is_ok());
+}
+
+// ======================== Verification Lifecycle ========================
+
+#[test]
+fn test_verify_provider_requires_admin_and_current_license() {
+    let (env, client, admin) = setup();
+    let provider = register_provider(&env, &client, &admin, "Retina", "Fresno", 1_000_000);
+    let expired = register_provider(&env, &client, &admin, "Retina", "Fresno", 5_000);
+
+    let denied = client.try_verify_provider(&provider, &provider, &VerificationStatus::Verified);
+    assert_eq!(denied.unwrap_err().unwrap(), ContractError::Unauthorized);
+
+    let no_license = client.try_verify_provider(&admin, &expired, &VerificationStatus::Verified);
+    assert_eq!(
+        no_license.unwrap_err().unwrap(),
+        ContractError::ProviderLicenseInvalid
+    );
+
+    let pending = client.try_verify_provider(&admin, &provider, &VerificationStatus::Pending);
+    assert_eq!(
+        pending.unwrap_err().unwrap(),
+        ContractError::InvalidVerificationStatus
+    );
+
+    client.verify_provider(&admin, &provider, &VerificationStatus::Verified);
+    let profile = client.get_provider(&provider);
+    assert_eq!(profile.verification_status, VerificationStatus::Verified);
+    assert_eq!(profile.verified_by, Some(admin));
+    assert_eq!(profile.verified_at, Some(10_000));
+}
+
+#[test]
+fn test_unverified_and_suspended_providers_cannot_add_records() {
+    let (env, client, admin) = setup();
+    let unregistered = Address::generate(&env);
+    client.register_user(
+        &admin,
+        &unregistered,
+        &Role::Optometrist,
+        &String::from_str(&env, "Dr. Nobody"),
+    );
+    assert_eq!(
+        add_record(&env, &client, &unregistered),
+        Err(ContractError::ProviderNotFound)
+    );
+
+    let provider = register_provider(&env, &client, &admin, "Retina", "Fresno", 1_000_000);
+
+    assert_eq!(
+        add_record(&env, &client, &provider),
+        Err(ContractError::ProviderNotVerified)
+    );
+
+    client.verify_provider(&admin, &provider, &VerificationStatus::Verified);
+    assert!(add_record(&env, &client, &provider).is_ok());
+
+    client.suspend_provider(&admin, &provider);
+    assert_eq!(
+        client.get_provider(&provider).verification_status,
+        VerificationStatus::Suspended
+    );
+    assert_eq!(
+        add_record(&env, &client, &provider),
+        Err(ContractError::ProviderNotVerified)
+    );
+    let again = client.try_suspend_provider(&admin, &provider);
+    assert_eq!(
+        again.unwrap_err().unwrap(),
+        ContractError::InvalidVerificationStatus
+    );
+
+    // Re-verification reinstates the provider.
+    client.verify_provider(&admin, &provider, &VerificationStatus::Verified);
+    assert!(add_record(&env, &client, &provider).is_ok());
+}
+
+#[test]
+fn test_rejected_provider_cannot_add_records() {
+    let (env, client, admin) = setup();
+    let provider = register_provider(&env, &client, &admin, "Retina", "Fresno", 1_000_000);
+
+    client.verify_provider(&admin, &provider, &VerificationStatus::Rejected);
+    assert_eq!(
+        add_record(&env, &client, &provider),
+        Err(ContractError::ProviderNotVerified)
+    );
+    assert_eq!(
+        client.search_providers_by_status(&VerificationStatus::Rejected),
+        Vec::from_array(&env, [provider])
+    );
+}
+
+#[test]
+fn test_suspend_missing_provider() {
+    let (env, client, admin) = setup();
+    let result = client.try_suspend_provider(&admin, &Address::generate(&env));
+    assert_eq!(
+        result.unwrap_err().unwrap(),
+        ContractError::ProviderNotFound
+    );
+}
+
+// ======================== Search ========================
+
+#[test]
+fn test_find_providers_by_specialty_and_location() {
+    let (env, client, admin) = setup();
+    let fresno = register_provider(&env, &client, &admin, "Retina", "Fresno", 1_000_000);
+    let oakland = register_provider(&env, &client, &admin, "Retina", "Oakland", 1_000_000);
+    let glaucoma = register_provider(&env, &client, &admin, "Glaucoma", "Fresno", 1_000_000);
+    let unverified = register_provider(&env, &client, &admin, "Retina", "Fresno", 1_000_000);
+    for p in [&fresno, &oakland, &glaucoma] {
+        client.verify_provider(&admin, p, &VerificationStatus::Verified);
+    }
+    assert_eq!(
+        client.get_provider(&unverified).verification_status,
+        VerificationStatus::Pending
+    );
+
+    let retina = String::from_str(&env, "Retina");
+    let in_fresno = client.find_providers(&retina, &Some(String::from_str(&env, "Fresno")), &None);
+    assert_eq!(in_fresno.len(), 1);
+    assert_eq!(in_fresno.get(0).unwrap().address, fresno);
+
+    let in_state = client.find_providers(&retina, &None, &Some(String::from_str(&env, "CA")));
+    assert_eq!(in_state.len(), 2);
+
+    client.suspend_provider(&admin, &oakland);
+    assert_eq!(client.find_providers(&retina, &None, &None).len(), 1);
+}
+
+#[test]
+fn test_provider_search_pages() {
+    let (env, client, admin) = setup();
+    let retina = String::from_str(&env, "Retina");
+    let mut providers = Vec::new(&env);
+    for i in 0..30 {
+        let p = register_provider(&env, &client, &admin, "Retina", "Fresno", 1_000_000);
+        if i % 2 == 0 {
+            client.verify_provider(&admin, &p, &VerificationStatus::Verified);
+        }
+        providers.push_back(p);
+    }
+
+    let first = client.search_by_specialty_page(&retina, &None, &25);
+    assert_eq!(first.providers.len(), 25);
+    assert_eq!(first.next_cursor, Some(25));
+    let rest = client.search_by_specialty_page(&retina, &first.next_cursor, &25);
+    assert_eq!(rest.providers.len(), 5);
+    assert_eq!(rest.next_cursor, None);
+
+    // Every second provider is verified, so 25 scanned entries yield 13.
+    let found = client.find_providers_page(&retina, &None, &None, &None, &25);
+    assert_eq!(found.providers.len(), 13);
+    assert_eq!(found.next_cursor, Some(25));
+    let found = client.find_providers_page(&retina, &None, &None, &found.next_cursor, &25);
+    assert_eq!(found.providers.len(), 2);
+
+    // Verified providers no longer show up as pending.
+    let pending = client.search_by_status_page(&VerificationStatus::Pending, &None, &25);
+    assert_eq!(pending.providers.len(), 12);
+    assert_eq!(
+        pending.providers.get(0).unwrap().address,
+        providers.get(1).unwrap()
+    );
+    let verified = client.search_by_status_page(&VerificationStatus::Verified, &None, &25);
+    assert_eq!(verified.providers.len(), 15);
+
+    client.suspend_provider(&admin, &providers.get(0).unwrap());
+    client.verify_provider(
+        &admin,
+        &providers.get(0).unwrap(),
+        &VerificationStatus::Verified,
+    );
+    let verified = client.search_by_status_page(&VerificationStatus::Verified, &None, &25);
+    assert_eq!(verified.providers.len(), 15);
+}
@@ -0,0 +1,221 @@
+#![allow(
+    clippy::unwrap_used,
+    clippy::expect_used,
+    clippy::panic,
+    clippy::arithmetic_side_effects
+)]
+
+use super::{
+    AccessLevel, BatchGrantInput, BatchRecordInput, ContractError, RateLimitConfig, RecordType,
+    Role,
+};
+use crate::test_support::{setup, DATA_HASH};
+use soroban_sdk::{testutils::Address as _, Address, Env, String, Vec};
+
+const HOUR: u64 = 3_600;
+
+// ── Helpers ──────────────────────────────────────────────────────
+
+fn config(
+    env: &Env,
+    operation: &str,
+    max_requests: u32,
+    window_seconds: u64,
+    burst: u32,
+) -> RateLimitConfig {
+    RateLimitConfig {
+        max_requests,
+        window_seconds,
+        burst,
+        operation: String::from_str(env, operation),
+    }
+}
+
+// ======================== Role limits ========================
+
+#[test]
+fn test_role_override_limits_providers_but_not_patients() {
+    let ctx = setup();
+    let record_id = ctx.add_record(RecordType::Examination);
+    ctx.client
+        .set_operation_rate_limit(&ctx.admin, &config(&ctx.env, "get_record", 100, HOUR, 0));
+    ctx.client.set_role_rate_limit(
+        &ctx.admin,
+        &Role::Optometrist,
+        &config(&ctx.env, "get_record", 3, HOUR, 2),
+    );
+
+    // Sustained budget plus burst, then refused.
+    for _ in 0..5 {
+        ctx.client.get_record(&ctx.provider, &record_id);
+    }
+    let refused = ctx.client.try_get_record(&ctx.provider, &record_id);
+    assert_eq!(
+        refused.unwrap_err().unwrap(),
+        ContractError::RateLimitExceeded
+    );
+
+    // The patient falls back to the operation default.
+    for _ in 0..10 {
+        ctx.client.get_record(&ctx.patient, &record_id);
+    }
+    let status = ctx
+        .client
+        .get_rate_limit_status(&ctx.patient, &String::from_str(&ctx.env, "get_record"))
+        .unwrap();
+    assert_eq!(status.max_requests, 100);
+    assert_eq!(status.remaining, 90);
+}
+
+#[test]
+fn test_bucket_refills_at_sustained_rate() {
+    let ctx = setup();
+    let record_id = ctx.add_record(RecordType::Examination);
+    let operation = String::from_str(&ctx.env, "get_record");
+    ctx.client
+        .set_operation_rate_limit(&ctx.admin, &config(&ctx.env, "get_record", 4, HOUR, 0));
+
+    for _ in 0..4 {
+        ctx.client.get_record(&ctx.provider, &record_id);
+    }
+    assert!(ctx
+        .client
+        .try_get_record(&ctx.provider, &record_id)
+        .is_err());
+
+    let status = ctx
+        .client
+        .get_rate_limit_status(&ctx.provider, &operation)
+        .unwrap();
+    assert_eq!(status.remaining, 0);
+    assert_eq!(status.current_count, 4);
+    assert_eq!(status.reset_at, ctx.env.ledger().timestamp() + HOUR);
+
+    // One token every quarter window.
+    ctx.advance(HOUR / 4);
+    ctx.client.get_record(&ctx.provider, &record_id);
+    assert!(ctx
+        .client
+        .try_get_record(&ctx.provider, &record_id)
+        .is_err());
+
+    ctx.client
+        .set_rate_limit_bypass(&ctx.admin, &ctx.provider, &true);
+    ctx.client.get_record(&ctx.provider, &record_id);
+}
+
+// ======================== Batch calls ========================
+
+#[test]
+fn test_batch_calls_spend_one_token_per_item() {
+    let ctx = setup();
+    ctx.client
+        .set_operation_rate_limit(&ctx.admin, &config(&ctx.env, "add_record", 2, HOUR, 1));
+    ctx.client
+        .set_operation_rate_limit(&ctx.admin, &config(&ctx.env, "grant_access", 2, HOUR, 0));
+
+    let mut records = Vec::new(&ctx.env);
+    for _ in 0..4 {
+        records.push_back(BatchRecordInput {
+            patient: ctx.patient.clone(),
+            record_type: RecordType::Examination,
+            data_hash: String::from_str(&ctx.env, DATA_HASH),
+        });
+    }
+    let too_many = ctx.client.try_add_records(&ctx.provider, &records);
+    assert_eq!(
+        too_many.unwrap_err().unwrap(),
+        ContractError::RateLimitExceeded
+    );
+    records.pop_back();
+    assert_eq!(ctx.client.add_records(&ctx.provider, &records).len(), 3);
+    assert!(ctx
+        .client
+        .try_add_record(
+            &ctx.provider,
+            &ctx.patient,
+            &ctx.provider,
+            &RecordType::Examination,
+            &String::from_str(&ctx.env, DATA_HASH),
+        )
+        .is_err());
+
+    let mut grants = Vec::new(&ctx.env);
+    for _ in 0..2 {
+        grants.push_back(BatchGrantInput {
+            grantee: Address::generate(&ctx.env),
+            level: AccessLevel::Read,
+            duration_seconds: HOUR,
+        });
+    }
+    ctx.client.grant_access_batch(&ctx.patient, &grants);
+    let single = ctx.client.try_grant_access(
+        &ctx.patient,
+        &ctx.patient,
+        &Address::generate(&ctx.env),
+        &AccessLevel::Read,
+        &HOUR,
+    );
+    assert_eq!(
+        single.unwrap_err().unwrap(),
+        ContractError::RateLimitExceeded
+    );
+}
+
+// ======================== Administration ========================
+
+#[test]
+fn test_rate_limit_admin_and_stats() {
+    let ctx = setup();
+    let record_id = ctx.add_record(RecordType::Examination);
+    let get_record = config(&ctx.env, "get_record", 2, HOUR, 0);
+
+    let denied = ctx
+        .client
+        .try_set_operation_rate_limit(&ctx.provider, &get_record);
+    assert_eq!(denied.unwrap_err().unwrap(), ContractError::Unauthorized);
+    let invalid = ctx
+        .client
+        .try_set_operation_rate_limit(&ctx.admin, &config(&ctx.env, "get_record", 0, HOUR, 0));
+    assert_eq!(invalid.unwrap_err().unwrap(), ContractError::InvalidInput);
+
+    ctx.client.set_operation_rate_limit(&ctx.admin, &get_record);
+    assert_eq!(
+        ctx.client
+            .get_operation_rate_limit(&get_record.operation)
+            .unwrap(),
+        get_record
+    );
+    assert_eq!(ctx.client.get_operation_rate_limits().len(), 1);
+
+    ctx.client.get_record(&ctx.provider, &record_id);
+    ctx.client.get_record(&ctx.provider, &record_id);
+    ctx.client.get_record(&ctx.patient, &record_id);
+
+    let stats = ctx.client.get_rate_limit_stats();
+    assert_eq!(stats.total_requests, 3);
+    assert_eq!(stats.unique_addresses, 2);
+    // The provider's second read drained their bucket.
+    assert_eq!(stats.budget_exhausted, 1);
+    assert_eq!(
+        stats.top_exhausted_operations,
+        Vec::from_array(&ctx.env, [get_record.operation.clone()])
+    );
+
+    // A refused read rolls back and is not counted.
+    assert!(ctx
+        .client
+        .try_get_record(&ctx.provider, &record_id)
+        .is_err());
+    let stats = ctx.client.get_rate_limit_stats();
+    assert_eq!(stats.total_requests, 3);
+    assert_eq!(stats.budget_exhausted, 1);
+
+    ctx.client
+        .remove_operation_rate_limit(&ctx.admin, &get_record.operation);
+    assert!(ctx
+        .client
+        .get_operation_rate_limit(&get_record.operation)
+        .is_none());
+    ctx.client.get_record(&ctx.provider, &record_id);
+}
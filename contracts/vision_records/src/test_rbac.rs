@@ -5,7 +5,10 @@
     clippy::arithmetic_side_effects
 )]
 
-use super::{ConsentType, Permission, Role, VisionRecordsContract, VisionRecordsContractClient};
+use super::{
+    ConsentType, ContractError, Permission, Role, VisionRecordsContract,
+    VisionRecordsContractClient,
+};
 use soroban_sdk::{testutils::Address as _, testutils::Ledger as _, Address, Env, String, Vec};
 
 fn setup_test() -> (Env, VisionRecordsContractClient<'static>, Address) {
@@ -277,6 +280,29 @@ fn test_acl_group_multiple_groups() {
     assert!(groups.contains(group2_name));
 }
 
+#[test]
+fn test_user_group_membership_is_capped() {
+    let (env, client, admin) = setup_test();
+    let user = Address::generate(&env);
+    let perms = Vec::from_array(&env, [Permission::ReadAnyRecord]);
+
+    let names = [
+        "g0", "g1", "g2", "g3", "g4", "g5", "g6", "g7", "g8", "g9", "g10",
+    ];
+    for name in names {
+        client.create_acl_group(&admin, &String::from_str(&env, name), &perms);
+    }
+    for name in &names[..10] {
+        client.add_user_to_group(&admin, &user, &String::from_str(&env, name));
+    }
+
+    let over = client.try_add_user_to_group(&admin, &user, &String::from_str(&env, "g10"));
+    assert_eq!(over, Err(Ok(ContractError::InvalidInput)));
+    // Re-adding an existing group is still a no-op.
+    client.add_user_to_group(&admin, &user, &String::from_str(&env, "g0"));
+    assert_eq!(client.get_user_groups(&user).len(), 10);
+}
+
 #[test]
 fn test_acl_group_unauthorized_management() {
     let (env, client, admin) = setup_test();
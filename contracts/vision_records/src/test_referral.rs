@@ -0,0 +1,240 @@
+#![allow(
+    clippy::unwrap_used,
+    clippy::expect_used,
+    clippy::panic,
+    clippy::arithmetic_side_effects
+)]
+
+use super::{AccessLevel, ContractError, RecordType, ReferralStatus, Role};
+use crate::test_support::{self, TestContext};
+use soroban_sdk::{testutils::Address as _, Address, String, Vec};
+
+const DAY: u64 = 86_400;
+const HASH: &str = "QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG";
+/// Content hash of the referral letter, which is kept off-chain.
+const LETTER_HASH: &str = "QmT78zSuBmuS4z925WZfrqQ1qHaJ56DQaTfyMUF7F8ff5o";
+
+// ── Helpers ──────────────────────────────────────────────────────
+
+/// The patient has two records; `specialist` is a second verified provider.
+fn setup() -> (TestContext, Address, Vec<u64>) {
+    let ctx = test_support::setup();
+    let specialist = ctx.register(Role::Ophthalmologist, "Dr. Retina");
+    let records = Vec::from_array(
+        &ctx.env,
+        [
+            ctx.add_record(RecordType::Examination),
+            ctx.add_record(RecordType::Diagnosis),
+        ],
+    );
+    (ctx, specialist, records)
+}
+
+fn refer(ctx: &TestContext, specialist: &Address, records: &Vec<u64>) -> u64 {
+    ctx.client.create_referral(
+        &ctx.provider,
+        specialist,
+        &ctx.patient,
+        records,
+        &String::from_str(&ctx.env, LETTER_HASH),
+    )
+}
+
+// ======================== Lifecycle ========================
+
+#[test]
+fn test_referral_round_trip() {
+    let (ctx, specialist, records) = setup();
+    let referral_id = refer(&ctx, &specialist, &records);
+    let referral = ctx.client.get_referral(&referral_id).unwrap();
+    assert_eq!(referral.status, ReferralStatus::Pending);
+    for user in [&ctx.provider, &specialist, &ctx.patient] {
+        let page = ctx.client.get_referrals_page(user, &None, &25);
+        assert_eq!(page.referrals.len(), 1);
+        assert_eq!(page.next_cursor, None);
+    }
+    let first = records.get(0).unwrap();
+    assert_eq!(
+        ctx.client.check_record_access(&first, &specialist),
+        AccessLevel::None
+    );
+
+    ctx.client
+        .approve_referral(&ctx.patient, &referral_id, &(30 * DAY));
+    for record_id in records.iter() {
+        assert_eq!(
+            ctx.client.check_record_access(&record_id, &specialist),
+            AccessLevel::Read
+        );
+    }
+
+    let response_id = ctx.client.add_record(
+        &specialist,
+        &ctx.patient,
+        &specialist,
+        &RecordType::Diagnosis,
+        &String::from_str(&ctx.env, HASH),
+    );
+    ctx.client
+        .respond_to_referral(&specialist, &referral_id, &response_id);
+    let referral = ctx.client.get_referral(&referral_id).unwrap();
+    assert_eq!(referral.status, ReferralStatus::Completed);
+    assert_eq!(referral.response_record_id, Some(response_id));
+    assert_eq!(
+        ctx.client.check_record_access(&response_id, &ctx.provider),
+        AccessLevel::Read
+    );
+
+    let again = ctx
+        .client
+        .try_respond_to_referral(&specialist, &referral_id, &response_id);
+    assert_eq!(
+        again.unwrap_err().unwrap(),
+        ContractError::InvalidReferralStatus
+    );
+}
+
+#[test]
+fn test_decline_and_cancel_referral() {
+    let (ctx, specialist, records) = setup();
+    let declined = refer(&ctx, &specialist, &records);
+    let stranger = Address::generate(&ctx.env);
+    let denied = ctx.client.try_decline_referral(&stranger, &declined);
+    assert_eq!(denied.unwrap_err().unwrap(), ContractError::Unauthorized);
+    ctx.client.decline_referral(&ctx.patient, &declined);
+    let late = ctx
+        .client
+        .try_approve_referral(&ctx.patient, &declined, &DAY);
+    assert_eq!(
+        late.unwrap_err().unwrap(),
+        ContractError::InvalidReferralStatus
+    );
+
+    // Cancelling an approved referral withdraws the specialist's access.
+    let cancelled = refer(&ctx, &specialist, &records);
+    ctx.client.approve_referral(&ctx.patient, &cancelled, &DAY);
+    let denied = ctx.client.try_cancel_referral(&specialist, &cancelled);
+    assert_eq!(denied.unwrap_err().unwrap(), ContractError::Unauthorized);
+    ctx.client.cancel_referral(&ctx.provider, &cancelled);
+    assert_eq!(
+        ctx.client
+            .check_record_access(&records.get(0).unwrap(), &specialist),
+        AccessLevel::None
+    );
+    assert_eq!(
+        ctx.client.get_referral(&cancelled).unwrap().status,
+        ReferralStatus::Cancelled
+    );
+
+    let first = ctx.client.get_referrals_page(&ctx.patient, &None, &1);
+    assert_eq!(first.referrals.get(0).unwrap().id, declined);
+    assert_eq!(first.next_cursor, Some(1));
+    let rest = ctx
+        .client
+        .get_referrals_page(&ctx.patient, &first.next_cursor, &1);
+    assert_eq!(rest.referrals.get(0).unwrap().id, cancelled);
+    assert_eq!(rest.next_cursor, None);
+}
+
+#[test]
+fn test_cancel_referral_keeps_existing_grants() {
+    let (ctx, specialist, records) = setup();
+    let first = records.get(0).unwrap();
+    let second = records.get(1).unwrap();
+    ctx.client
+        .grant_record_access(&ctx.patient, &specialist, &first, &AccessLevel::Write, &DAY);
+
+    let referral_id = refer(&ctx, &specialist, &records);
+    ctx.client
+        .approve_referral(&ctx.patient, &referral_id, &(30 * DAY));
+    let referral = ctx.client.get_referral(&referral_id).unwrap();
+    assert_eq!(
+        referral.granted_record_ids,
+        Vec::from_array(&ctx.env, [second])
+    );
+    assert_eq!(
+        ctx.client.check_record_access(&first, &specialist),
+        AccessLevel::Write
+    );
+
+    // Only the grant the referral made is withdrawn.
+    ctx.client.cancel_referral(&ctx.provider, &referral_id);
+    assert_eq!(
+        ctx.client.check_record_access(&first, &specialist),
+        AccessLevel::Write
+    );
+    assert_eq!(
+        ctx.client.check_record_access(&second, &specialist),
+        AccessLevel::None
+    );
+}
+
+// ======================== Validation ========================
+
+#[test]
+fn test_referral_validation() {
+    let (ctx, specialist, records) = setup();
+    let reason = String::from_str(&ctx.env, LETTER_HASH);
+
+    // The clinical reason must not be written on-chain in the clear.
+    let plain = ctx.client.try_create_referral(
+        &ctx.provider,
+        &specialist,
+        &ctx.patient,
+        &records,
+        &String::from_str(&ctx.env, "Suspected retinal detachment"),
+    );
+    assert_eq!(plain.unwrap_err().unwrap(), ContractError::InvalidInput);
+
+    let other_patient = Address::generate(&ctx.env);
+    let wrong_patient = ctx.client.try_create_referral(
+        &ctx.provider,
+        &specialist,
+        &other_patient,
+        &records,
+        &reason,
+    );
+    assert_eq!(
+        wrong_patient.unwrap_err().unwrap(),
+        ContractError::Unauthorized
+    );
+    let not_a_provider = ctx.client.try_create_referral(
+        &ctx.provider,
+        &other_patient,
+        &ctx.patient,
+        &records,
+        &reason,
+    );
+    assert_eq!(
+        not_a_provider.unwrap_err().unwrap(),
+        ContractError::InvalidRole
+    );
+    let to_self = ctx.client.try_create_referral(
+        &ctx.provider,
+        &ctx.provider,
+        &ctx.patient,
+        &records,
+        &reason,
+    );
+    assert_eq!(to_self.unwrap_err().unwrap(), ContractError::InvalidInput);
+
+    // The response must be the specialist's own record for the patient,
+    // and only once the patient has approved.
+    let referral_id = refer(&ctx, &specialist, &records);
+    let early =
+        ctx.client
+            .try_respond_to_referral(&specialist, &referral_id, &records.get(0).unwrap());
+    assert_eq!(
+        early.unwrap_err().unwrap(),
+        ContractError::InvalidReferralStatus
+    );
+    ctx.client
+        .approve_referral(&ctx.patient, &referral_id, &DAY);
+    let not_theirs =
+        ctx.client
+            .try_respond_to_referral(&specialist, &referral_id, &records.get(0).unwrap());
+    assert_eq!(
+        not_theirs.unwrap_err().unwrap(),
+        ContractError::InvalidInput
+    );
+}
@@ -0,0 +1,276 @@
+#![allow(
+    clippy::unwrap_used,
+    clippy::expect_used,
+    clippy::panic,
+    clippy::arithmetic_side_effects
+)]
+
+use super::{share, AccessLevel, ContractError, RecordType, ShareToken, ShareTokenStatus};
+use crate::test_support::{self, TestContext};
+use ed25519_dalek::{Signer, SigningKey};
+use soroban_sdk::{testutils::Address as _, testutils::Ledger as _, Address, BytesN, Env, Vec};
+
+const HOUR: u64 = 3_600;
+const DAY: u64 = 86_400;
+
+// ── Helpers ──────────────────────────────────────────────────────
+
+/// The patient has three records; `clinic` has no access to them.
+fn setup() -> (TestContext, Address, Vec<u64>) {
+    let ctx = test_support::setup();
+    let clinic = Address::generate(&ctx.env);
+    let records = Vec::from_array(
+        &ctx.env,
+        [
+            ctx.add_record(RecordType::Examination),
+            ctx.add_record(RecordType::Prescription),
+            ctx.add_record(RecordType::Diagnosis),
+        ],
+    );
+    (ctx, clinic, records)
+}
+
+/// The signing key handed out off-chain, e.g. in a QR code.
+fn signing_key(seed: u8) -> SigningKey {
+    SigningKey::from_bytes(&[seed; 32])
+}
+
+fn share_key(env: &Env, seed: u8) -> BytesN<32> {
+    BytesN::from_array(env, &signing_key(seed).verifying_key().to_bytes())
+}
+
+/// Signs the redemption message for `redeemer` with the key from `seed`.
+fn sign_redemption(env: &Env, seed: u8, redeemer: &Address) -> BytesN<64> {
+    let msg = share::redemption_message(env, &share_key(env, seed), redeemer);
+    let mut buf = [0u8; 256];
+    let len = msg.len() as usize;
+    msg.copy_into_slice(&mut buf[..len]);
+    BytesN::from_array(env, &signing_key(seed).sign(&buf[..len]).to_bytes())
+}
+
+fn redeem(ctx: &TestContext, seed: u8, redeemer: &Address) -> ShareToken {
+    ctx.client.redeem_share_token(
+        redeemer,
+        &share_key(&ctx.env, seed),
+        &sign_redemption(&ctx.env, seed, redeemer),
+    )
+}
+
+fn try_redeem(ctx: &TestContext, seed: u8, redeemer: &Address) -> ContractError {
+    ctx.client
+        .try_redeem_share_token(
+            redeemer,
+            &share_key(&ctx.env, seed),
+            &sign_redemption(&ctx.env, seed, redeemer),
+        )
+        .unwrap_err()
+        .unwrap()
+}
+
+/// Shares the first two records for a week of read access, redeemable
+/// within a day.
+fn share_two(ctx: &TestContext, records: &Vec<u64>, seed: u8) -> u64 {
+    let shared = Vec::from_array(&ctx.env, [records.get(0).unwrap(), records.get(1).unwrap()]);
+    ctx.client.create_share_token(
+        &ctx.patient,
+        &share_key(&ctx.env, seed),
+        &shared,
+        &AccessLevel::Read,
+        &DAY,
+        &(7 * DAY),
+    )
+}
+
+// ======================== Redemption ========================
+
+#[test]
+fn test_redeem_share_token_once() {
+    let (ctx, clinic, records) = setup();
+    let token_id = share_two(&ctx, &records, 1);
+
+    assert_eq!(
+        try_redeem(&ctx, 9, &clinic),
+        ContractError::ShareTokenNotFound
+    );
+
+    let token = redeem(&ctx, 1, &clinic);
+    assert_eq!(token.id, token_id);
+    assert_eq!(token.status, ShareTokenStatus::Redeemed);
+    assert_eq!(token.redeemed_by, Some(clinic.clone()));
+
+    ctx.client.get_record(&clinic, &records.get(0).unwrap());
+    ctx.client.get_record(&clinic, &records.get(1).unwrap());
+    assert_eq!(
+        ctx.client
+            .check_record_access(&records.get(1).unwrap(), &clinic),
+        AccessLevel::Read
+    );
+    let unshared = ctx.client.try_get_record(&clinic, &records.get(2).unwrap());
+    assert_eq!(unshared.unwrap_err().unwrap(), ContractError::Unauthorized);
+
+    // The token is spent, whoever holds the key next.
+    let other = Address::generate(&ctx.env);
+    assert_eq!(
+        try_redeem(&ctx, 1, &other),
+        ContractError::ShareTokenInactive
+    );
+}
+
+#[test]
+fn test_redemption_signature_is_bound_to_redeemer() {
+    let (ctx, clinic, records) = setup();
+    share_two(&ctx, &records, 1);
+
+    // Someone who copies the clinic's pending redemption cannot use its
+    // signature for their own address.
+    let copycat = Address::generate(&ctx.env);
+    let stolen = ctx.client.try_redeem_share_token(
+        &copycat,
+        &share_key(&ctx.env, 1),
+        &sign_redemption(&ctx.env, 1, &clinic),
+    );
+    assert!(stolen.is_err());
+
+    let token = redeem(&ctx, 1, &clinic);
+    assert_eq!(token.redeemed_by, Some(clinic.clone()));
+    assert_eq!(
+        ctx.client
+            .check_record_access(&records.get(0).unwrap(), &copycat),
+        AccessLevel::None
+    );
+}
+
+#[test]
+fn test_share_token_and_grant_expire() {
+    let (ctx, clinic, records) = setup();
+    share_two(&ctx, &records, 1);
+    share_two(&ctx, &records, 2);
+
+    ctx.env.ledger().set_timestamp(10_000 + DAY - HOUR);
+    redeem(&ctx, 2, &clinic);
+    ctx.env.ledger().set_timestamp(10_000 + DAY);
+    assert_eq!(
+        try_redeem(&ctx, 1, &clinic),
+        ContractError::ShareTokenExpired
+    );
+
+    // The grant runs for its own duration from redemption.
+    ctx.env.ledger().set_timestamp(10_000 + 8 * DAY - 2 * HOUR);
+    ctx.client.get_record(&clinic, &records.get(0).unwrap());
+    ctx.env.ledger().set_timestamp(10_000 + 8 * DAY);
+    let expired = ctx.client.try_get_record(&clinic, &records.get(0).unwrap());
+    assert_eq!(expired.unwrap_err().unwrap(), ContractError::Unauthorized);
+}
+
+// ======================== Revocation and validation ========================
+
+#[test]
+fn test_revoke_share_token() {
+    let (ctx, clinic, records) = setup();
+    let unused = share_two(&ctx, &records, 1);
+    ctx.client.revoke_share_token(&ctx.patient, &unused);
+    assert_eq!(
+        try_redeem(&ctx, 1, &clinic),
+        ContractError::ShareTokenInactive
+    );
+
+    // Revoking a redeemed token withdraws the access it granted.
+    let used = share_two(&ctx, &records, 2);
+    redeem(&ctx, 2, &clinic);
+    let denied = ctx.client.try_revoke_share_token(&clinic, &used);
+    assert_eq!(denied.unwrap_err().unwrap(), ContractError::Unauthorized);
+    ctx.client.revoke_share_token(&ctx.patient, &used);
+    let withdrawn = ctx.client.try_get_record(&clinic, &records.get(0).unwrap());
+    assert_eq!(withdrawn.unwrap_err().unwrap(), ContractError::Unauthorized);
+
+    let first = ctx
+        .client
+        .get_patient_share_tokens_page(&ctx.patient, &None, &1);
+    assert_eq!(first.tokens.len(), 1);
+    assert_eq!(first.next_cursor, Some(1));
+    let rest = ctx
+        .client
+        .get_patient_share_tokens_page(&ctx.patient, &first.next_cursor, &25);
+    assert_eq!(rest.tokens.len(), 1);
+    assert_eq!(rest.next_cursor, None);
+    assert_eq!(rest.tokens.get(0).unwrap().id, used);
+    assert_eq!(
+        rest.tokens.get(0).unwrap().status,
+        ShareTokenStatus::Revoked
+    );
+}
+
+#[test]
+fn test_share_token_leaves_other_grants_alone() {
+    let (ctx, clinic, records) = setup();
+    let first = records.get(0).unwrap();
+    let second = records.get(1).unwrap();
+    ctx.client
+        .grant_record_access(&ctx.patient, &clinic, &first, &AccessLevel::Full, &DAY);
+
+    // A read token does not downgrade the clinic's full access.
+    let token_id = share_two(&ctx, &records, 1);
+    let token = redeem(&ctx, 1, &clinic);
+    assert_eq!(
+        token.granted_record_ids,
+        Vec::from_array(&ctx.env, [second])
+    );
+    assert_eq!(
+        ctx.client.check_record_access(&first, &clinic),
+        AccessLevel::Full
+    );
+
+    // The patient later grants the second record directly; revoking the
+    // token removes neither grant.
+    ctx.env.ledger().set_timestamp(10_000 + HOUR);
+    ctx.client
+        .grant_record_access(&ctx.patient, &clinic, &second, &AccessLevel::Write, &DAY);
+    ctx.client.revoke_share_token(&ctx.patient, &token_id);
+    assert_eq!(
+        ctx.client.check_record_access(&first, &clinic),
+        AccessLevel::Full
+    );
+    assert_eq!(
+        ctx.client.check_record_access(&second, &clinic),
+        AccessLevel::Write
+    );
+}
+
+#[test]
+fn test_share_token_validation() {
+    let (ctx, _, records) = setup();
+    share_two(&ctx, &records, 1);
+    let reused = ctx.client.try_create_share_token(
+        &ctx.patient,
+        &share_key(&ctx.env, 1),
+        &Vec::from_array(&ctx.env, [records.get(2).unwrap()]),
+        &AccessLevel::Read,
+        &DAY,
+        &DAY,
+    );
+    assert_eq!(reused.unwrap_err().unwrap(), ContractError::DuplicateRecord);
+
+    let repeated = ctx.client.try_create_share_token(
+        &ctx.patient,
+        &share_key(&ctx.env, 2),
+        &Vec::from_array(&ctx.env, [records.get(2).unwrap(), records.get(2).unwrap()]),
+        &AccessLevel::Read,
+        &DAY,
+        &DAY,
+    );
+    assert_eq!(repeated.unwrap_err().unwrap(), ContractError::InvalidInput);
+
+    let stranger = Address::generate(&ctx.env);
+    let not_theirs = ctx.client.try_create_share_token(
+        &stranger,
+        &share_key(&ctx.env, 2),
+        &Vec::from_array(&ctx.env, [records.get(0).unwrap()]),
+        &AccessLevel::Read,
+        &DAY,
+        &DAY,
+    );
+    assert_eq!(
+        not_theirs.unwrap_err().unwrap(),
+        ContractError::Unauthorized
+    );
+}
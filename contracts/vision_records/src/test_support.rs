@@ -0,0 +1,122 @@
+//! Helpers shared by the contract test modules.
+
+use super::{
+    Certification, License, Location, RecordType, Role, VerificationStatus, VisionRecordsContract,
+    VisionRecordsContractClient,
+};
+use soroban_sdk::testutils::{Address as _, Ledger as _};
+use soroban_sdk::{Address, Env, String, Vec};
+
+/// Ledger time the shared fixture starts at.
+pub const START: u64 = 10_000;
+
+/// Content hash of the records the tests add.
+pub const DATA_HASH: &str = "QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG";
+
+/// An initialised contract with a verified optometrist and a patient who
+/// has not registered. All auths are mocked.
+pub struct TestContext {
+    pub env: Env,
+    pub contract_id: Address,
+    pub client: VisionRecordsContractClient<'static>,
+    pub admin: Address,
+    pub provider: Address,
+    pub patient: Address,
+}
+
+pub fn setup() -> TestContext {
+    let ctx = context();
+    ctx.register_as(&ctx.provider, Role::Optometrist, "Dr. Test");
+    ctx
+}
+
+/// Like [`setup`], but the provider has only a verified provider profile
+/// and no user account, as before that account is imported.
+pub fn setup_without_provider_account() -> TestContext {
+    let ctx = context();
+    verify_provider(&ctx.env, &ctx.client, &ctx.admin, &ctx.provider);
+    ctx
+}
+
+fn context() -> TestContext {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.ledger().set_timestamp(START);
+
+    let contract_id = env.register(VisionRecordsContract, ());
+    let client = VisionRecordsContractClient::new(&env, &contract_id);
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    TestContext {
+        provider: Address::generate(&env),
+        patient: Address::generate(&env),
+        env,
+        contract_id,
+        client,
+        admin,
+    }
+}
+
+impl TestContext {
+    /// Registers a new user. Providers also get a verified provider profile.
+    pub fn register(&self, role: Role, name: &str) -> Address {
+        let user = Address::generate(&self.env);
+        self.register_as(&user, role, name);
+        user
+    }
+
+    fn register_as(&self, user: &Address, role: Role, name: &str) {
+        let is_provider = matches!(role, Role::Optometrist | Role::Ophthalmologist);
+        self.client
+            .register_user(&self.admin, user, &role, &String::from_str(&self.env, name));
+        if is_provider {
+            verify_provider(&self.env, &self.client, &self.admin, user);
+        }
+    }
+
+    /// Adds a record the provider wrote for the patient.
+    pub fn add_record(&self, record_type: RecordType) -> u64 {
+        self.client.add_record(
+            &self.provider,
+            &self.patient,
+            &self.provider,
+            &record_type,
+            &String::from_str(&self.env, DATA_HASH),
+        )
+    }
+
+    /// Moves the ledger clock forward.
+    pub fn advance(&self, seconds: u64) {
+        let now = self.env.ledger().timestamp();
+        self.env.ledger().set_timestamp(now + seconds);
+    }
+}
+
+/// Gives `provider` a verified provider profile so it may author records.
+///
+/// `admin` must hold the OperatorAdmin tier or above.
+pub fn verify_provider(
+    env: &Env,
+    client: &VisionRecordsContractClient,
+    admin: &Address,
+    provider: &Address,
+) {
+    let license = License {
+        number: String::from_str(env, "LIC-TEST-0001"),
+        issuing_authority: String::from_str(env, "State Board"),
+        issued_date: 0,
+        expiry_date: u64::MAX,
+        license_type: String::from_str(env, "Optometry"),
+    };
+    client.register_provider(
+        provider,
+        provider,
+        &String::from_str(env, "Dr. Test"),
+        &Vec::from_array(env, [license]),
+        &Vec::<String>::new(env),
+        &Vec::<Certification>::new(env),
+        &Vec::<Location>::new(env),
+    );
+    client.verify_provider(admin, provider, &VerificationStatus::Verified);
+}
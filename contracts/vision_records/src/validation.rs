@@ -1,6 +1,6 @@
 use soroban_sdk::String;
 
-use crate::ContractError;
+use crate::{ContractError, License};
 
 const MIN_NAME_LEN: u32 = 2;
 const MAX_NAME_LEN: u32 = 64;
@@ -8,6 +8,9 @@ const MAX_NAME_LEN: u32 = 64;
 const MIN_HASH_LEN: u32 = 32;
 const MAX_HASH_LEN: u32 = 64;
 
+const MAX_LICENSE_FIELD_LEN: u32 = 64;
+const MAX_LICENSES: u32 = 10;
+
 const MIN_DURATION_SECONDS: u64 = 3600; // 1 hour
 const MAX_DURATION_SECONDS: u64 = 157_680_000; // 5 years
 
@@ -80,6 +83,88 @@ pub fn validate_duration(duration_seconds: u64) -> Result<(), ContractError> {
     Ok(())
 }
 
+/// Validate the licenses submitted with a provider registration.
+/// At least one and at most MAX_LICENSES are required; each needs a number,
+/// issuing authority and type of at most MAX_LICENSE_FIELD_LEN bytes and an
+/// issue date no later than `now` and before its expiry date. Expired
+/// licenses are accepted here and rejected at verification.
+pub fn validate_licenses(
+    licenses: &soroban_sdk::Vec<License>,
+    now: u64,
+) -> Result<(), ContractError> {
+    if licenses.is_empty() || licenses.len() > MAX_LICENSES {
+        return Err(ContractError::ProviderLicenseInvalid);
+    }
+    for license in licenses.iter() {
+        let fields_ok = [
+            &license.number,
+            &license.issuing_authority,
+            &license.license_type,
+        ]
+        .iter()
+        .all(|field| (1..=MAX_LICENSE_FIELD_LEN).contains(&field.len()));
+        if !fields_ok || license.issued_date > now || license.issued_date >= license.expiry_date {
+            return Err(ContractError::ProviderLicenseInvalid);
+        }
+    }
+    Ok(())
+}
+
+const MAX_DECIMAL_LEN: u32 = 16;
+
+/// Parse a signed decimal string such as "-2.25", "+0.50" or "62" into
+/// hundredths (e.g. -225, 50, 6200).
+/// At most two fractional digits are accepted; anything else returns `None`.
+pub fn parse_hundredths(value: &String) -> Option<i64> {
+    let len = value.len();
+    if len == 0 || len > MAX_DECIMAL_LEN {
+        return None;
+    }
+    let mut buf = [0u8; MAX_DECIMAL_LEN as usize];
+    value.copy_into_slice(&mut buf[..len as usize]);
+    let bytes = &buf[..len as usize];
+
+    let (negative, digits) = match bytes[0] {
+        b'-' => (true, &bytes[1..]),
+        b'+' => (false, &bytes[1..]),
+        _ => (false, bytes),
+    };
+
+    let mut whole: i64 = 0;
+    let mut frac: i64 = 0;
+    let mut frac_digits = 0u32;
+    let mut seen_digit = false;
+    let mut seen_dot = false;
+    for &b in digits {
+        match b {
+            b'0'..=b'9' => {
+                let d = i64::from(b - b'0');
+                if seen_dot {
+                    if frac_digits == 2 {
+                        return None;
+                    }
+                    frac = frac * 10 + d;
+                    frac_digits += 1;
+                } else {
+                    whole = whole * 10 + d;
+                }
+                seen_digit = true;
+            }
+            b'.' if !seen_dot => seen_dot = true,
+            _ => return None,
+        }
+    }
+    if !seen_digit || (seen_dot && frac_digits == 0) {
+        return None;
+    }
+    if frac_digits == 1 {
+        frac *= 10;
+    }
+
+    let value = whole * 100 + frac;
+    Some(if negative { -value } else { value })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -179,4 +264,24 @@ mod tests {
             Err(ContractError::InvalidInput)
         );
     }
+
+    #[test]
+    fn test_parse_hundredths() {
+        let env = Env::default();
+        let parse = |s: &str| parse_hundredths(&String::from_str(&env, s));
+
+        assert_eq!(parse("-2.25"), Some(-225));
+        assert_eq!(parse("+0.50"), Some(50));
+        assert_eq!(parse("62"), Some(6200));
+        assert_eq!(parse("8.6"), Some(860));
+        assert_eq!(parse("0"), Some(0));
+
+        assert_eq!(parse(""), None);
+        assert_eq!(parse("-"), None);
+        assert_eq!(parse("1."), None);
+        assert_eq!(parse("1.255"), None);
+        assert_eq!(parse("1.2.3"), None);
+        assert_eq!(parse("abc"), None);
+        assert_eq!(parse("12345678901234567"), None);
+    }
 }
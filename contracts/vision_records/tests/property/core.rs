@@ -14,7 +14,9 @@
 use proptest::prelude::*;
 use soroban_sdk::testutils::Address as _;
 use soroban_sdk::{Address, Env, String};
-use vision_records::{RecordType, VisionRecordsContract, VisionRecordsContractClient};
+use vision_records::{
+    License, RecordType, VerificationStatus, VisionRecordsContract, VisionRecordsContractClient,
+};
 
 // ── Helpers ───────────────────────────────────────────────────────────────────
 
@@ -31,6 +33,29 @@ fn setup() -> (Env, VisionRecordsContractClient<'static>, Address) {
     (env, client, admin)
 }
 
+/// A provider with a verified registry profile, as `add_record` requires.
+fn verified_provider(env: &Env, client: &VisionRecordsContractClient, admin: &Address) -> Address {
+    let provider = Address::generate(env);
+    let license = License {
+        number: String::from_str(env, "LIC-PROP-0001"),
+        issuing_authority: String::from_str(env, "State Board"),
+        issued_date: 0,
+        expiry_date: u64::MAX,
+        license_type: String::from_str(env, "Optometry"),
+    };
+    client.register_provider(
+        &provider,
+        &provider,
+        &String::from_str(env, "Dr. Property"),
+        &soroban_sdk::vec![env, license],
+        &soroban_sdk::vec![env],
+        &soroban_sdk::vec![env],
+        &soroban_sdk::vec![env],
+    );
+    client.verify_provider(admin, &provider, &VerificationStatus::Verified);
+    provider
+}
+
 /// Map a u8 to one of the six `RecordType` variants so proptest can generate them.
 fn record_type_from_u8(n: u8) -> RecordType {
     match n % 6 {
@@ -51,7 +76,7 @@ proptest! {
     fn prop_record_id_monotonic(n_records in 1usize..=10usize) {
         let (env, client, _admin) = setup();
         let patient = Address::generate(&env);
-        let provider = Address::generate(&env);
+        let provider = verified_provider(&env, &client, &_admin);
 
         for expected_id in 1..=(n_records as u64) {
             let hash = String::from_str(
@@ -75,7 +100,7 @@ proptest! {
         let (env, client, _admin) = setup();
 
         let patient = Address::generate(&env);
-        let provider = Address::generate(&env);
+        let provider = verified_provider(&env, &client, &_admin);
         let rtype = record_type_from_u8(record_type_seed);
         let hash = String::from_str(
             &env,
@@ -96,7 +121,7 @@ proptest! {
     fn prop_patient_records_always_includes_new(n_records in 1usize..=8usize) {
         let (env, client, _admin) = setup();
         let patient = Address::generate(&env);
-        let provider = Address::generate(&env);
+        let provider = verified_provider(&env, &client, &_admin);
         let hash = String::from_str(
             &env,
             "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855",        );
@@ -130,7 +155,7 @@ proptest! {
     fn prop_record_count_increments(n_records in 0usize..=12usize) {
         let (env, client, _admin) = setup();
         let patient = Address::generate(&env);
-        let provider = Address::generate(&env);
+        let provider = verified_provider(&env, &client, &_admin);
 
         prop_assert_eq!(client.get_record_count(), 0u64);
 
@@ -149,7 +174,7 @@ proptest! {
         let (env, client, _admin) = setup();
         let patient_a = Address::generate(&env);
         let patient_b = Address::generate(&env);
-        let provider = Address::generate(&env);
+        let provider = verified_provider(&env, &client, &_admin);
         let hash = String::from_str(
             &env,
             "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855",        );
@@ -0,0 +1,495 @@
+//! C-CDA R2.1 rendering.
+//!
+//! The export is a Continuity of Care Document (CCD). The sections a CCD
+//! requires but the chain holds no data for are emitted with
+//! `nullFlavor="NI"`. Ophthalmology data is carried in:
+//!
+//! - Results: coded intraocular pressure observations per eye.
+//! - Physical Exam: the record index plus visual acuity, slit lamp, visual
+//!   field and fundus findings per examination.
+//! - Medical Equipment: spectacle and contact lens prescriptions.
+//!
+//! Patient demographics live off-chain (the profile stores only hashes), so
+//! they are emitted as `nullFlavor="UNK"` for the receiving system to fill in.
+
+use vision_records::examination::OptPhysicalMeasurement;
+use vision_records::{
+    EyeExamination, LensType, OptFundusPhotography, OptRetinalImaging, OptVisualField,
+    OptionalContactLensData, Prescription, PrescriptionStatus, VisionRecord,
+};
+
+use crate::{address, hl7_datetime, iso_datetime, record_type_code, text, PatientExport};
+
+/// Root for instance identifiers minted by this exporter.
+pub const ID_ROOT: &str = "2E9C7B8A-41D3-4F0E-9C55-7A1B3D6E0F42";
+
+pub const US_REALM_HEADER: &str = "2.16.840.1.113883.10.20.22.1.1";
+pub const CCD_DOCUMENT: &str = "2.16.840.1.113883.10.20.22.1.2";
+
+const LOINC: &str = "2.16.840.1.113883.6.1";
+const SNOMED: &str = "2.16.840.1.113883.6.96";
+const TEMPLATE_VERSION: &str = "2015-08-01";
+
+/// Escape text for use in XML content or attribute values.
+fn esc(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&apos;"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+fn table(headers: &[&str], rows: &[Vec<String>]) -> String {
+    let mut out = String::from("<table><thead><tr>");
+    for h in headers {
+        out.push_str(&format!("<th>{}</th>", esc(h)));
+    }
+    out.push_str("</tr></thead><tbody>");
+    for row in rows {
+        out.push_str("<tr>");
+        for cell in row {
+            out.push_str(&format!("<td>{}</td>", esc(cell)));
+        }
+        out.push_str("</tr>");
+    }
+    out.push_str("</tbody></table>");
+    out
+}
+
+struct Section<'a> {
+    template: &'a str,
+    template_version: &'a str,
+    code: &'a str,
+    display: &'a str,
+    title: &'a str,
+    narrative: String,
+    entries: String,
+}
+
+impl Section<'_> {
+    fn render(&self, out: &mut String) {
+        // A section without narrative has no information to report.
+        let null_flavor = if self.narrative.is_empty() {
+            " nullFlavor=\"NI\""
+        } else {
+            ""
+        };
+        out.push_str(&format!(
+            "<component><section{null_flavor}>\
+             <templateId root=\"{t}\"/><templateId root=\"{t}\" extension=\"{v}\"/>\
+             <code code=\"{c}\" codeSystem=\"{LOINC}\" codeSystemName=\"LOINC\" displayName=\"{d}\"/>\
+             <title>{title}</title>",
+            t = self.template,
+            v = self.template_version,
+            c = self.code,
+            d = esc(self.display),
+            title = esc(self.title),
+        ));
+        if self.narrative.is_empty() {
+            out.push_str("<text>No information</text>");
+        } else {
+            out.push_str(&format!("<text>{}</text>", self.narrative));
+            out.push_str(&self.entries);
+        }
+        out.push_str("</section></component>");
+    }
+}
+
+/// Render the export as a C-CDA R2.1 Continuity of Care Document.
+pub fn to_document(export: &PatientExport) -> String {
+    let patient = address(&export.patient);
+    let now = hl7_datetime(export.generated_at);
+
+    let mut out = String::new();
+    out.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    out.push_str(
+        "<ClinicalDocument xmlns=\"urn:hl7-org:v3\" \
+         xmlns:xsi=\"http://www.w3.org/2001/XMLSchema-instance\">",
+    );
+    out.push_str("<realmCode code=\"US\"/>");
+    out.push_str("<typeId root=\"2.16.840.1.113883.1.3\" extension=\"POCD_HD000040\"/>");
+    for template in [US_REALM_HEADER, CCD_DOCUMENT] {
+        out.push_str(&format!(
+            "<templateId root=\"{template}\"/><templateId root=\"{template}\" extension=\"{TEMPLATE_VERSION}\"/>"
+        ));
+    }
+    out.push_str(&format!(
+        "<id root=\"{ID_ROOT}\" extension=\"{}\"/>",
+        esc(&format!("{patient}-{}", export.generated_at))
+    ));
+    out.push_str(&format!(
+        "<code code=\"34133-9\" codeSystem=\"{LOINC}\" codeSystemName=\"LOINC\" \
+         displayName=\"Summarization of Episode Note\"/>"
+    ));
+    out.push_str("<title>Vision Care Summary</title>");
+    out.push_str(&format!("<effectiveTime value=\"{now}\"/>"));
+    out.push_str(
+        "<confidentialityCode code=\"R\" codeSystem=\"2.16.840.1.113883.5.25\"/>\
+         <languageCode code=\"en-US\"/>",
+    );
+
+    // Header participants.
+    out.push_str(&format!(
+        "<recordTarget><patientRole><id root=\"{ID_ROOT}\" extension=\"{}\"/>\
+         <addr nullFlavor=\"UNK\"/><telecom nullFlavor=\"UNK\"/>\
+         <patient><name nullFlavor=\"UNK\"/>\
+         <administrativeGenderCode nullFlavor=\"UNK\"/><birthTime nullFlavor=\"UNK\"/>\
+         </patient></patientRole></recordTarget>",
+        esc(&patient)
+    ));
+    out.push_str(&format!(
+        "<author><time value=\"{now}\"/><assignedAuthor><id root=\"{ID_ROOT}\" extension=\"records_export\"/>\
+         <addr nullFlavor=\"NA\"/><telecom nullFlavor=\"NA\"/>\
+         <assignedAuthoringDevice><manufacturerModelName>Teye vision_records</manufacturerModelName>\
+         <softwareName>records_export</softwareName></assignedAuthoringDevice>\
+         </assignedAuthor></author>"
+    ));
+    out.push_str(&format!(
+        "<custodian><assignedCustodian><representedCustodianOrganization>\
+         <id root=\"{ID_ROOT}\" extension=\"vision_records\"/><name>Teye vision_records</name>\
+         <telecom nullFlavor=\"NA\"/><addr nullFlavor=\"NA\"/>\
+         </representedCustodianOrganization></assignedCustodian></custodian>"
+    ));
+
+    out.push_str("<component><structuredBody>");
+    for section in sections(export) {
+        section.render(&mut out);
+    }
+    out.push_str("</structuredBody></component></ClinicalDocument>\n");
+    out
+}
+
+fn empty_section<'a>(
+    template: &'a str,
+    template_version: &'a str,
+    code: &'a str,
+    title: &'a str,
+) -> Section<'a> {
+    Section {
+        template,
+        template_version,
+        code,
+        display: title,
+        title,
+        narrative: String::new(),
+        entries: String::new(),
+    }
+}
+
+fn sections(export: &PatientExport) -> Vec<Section<'static>> {
+    vec![
+        empty_section(
+            "2.16.840.1.113883.10.20.22.2.6.1",
+            TEMPLATE_VERSION,
+            "48765-2",
+            "Allergies and adverse reactions",
+        ),
+        empty_section(
+            "2.16.840.1.113883.10.20.22.2.1.1",
+            "2014-06-09",
+            "10160-0",
+            "History of medication use",
+        ),
+        empty_section(
+            "2.16.840.1.113883.10.20.22.2.5.1",
+            TEMPLATE_VERSION,
+            "11450-4",
+            "Problem list",
+        ),
+        results_section(export),
+        empty_section(
+            "2.16.840.1.113883.10.20.22.2.17",
+            TEMPLATE_VERSION,
+            "29762-2",
+            "Social history",
+        ),
+        empty_section(
+            "2.16.840.1.113883.10.20.22.2.4.1",
+            TEMPLATE_VERSION,
+            "8716-3",
+            "Vital signs",
+        ),
+        physical_exam_section(export),
+        equipment_section(export),
+    ]
+}
+
+fn results_section(export: &PatientExport) -> Section<'static> {
+    let mut rows = Vec::new();
+    let mut entries = String::new();
+    for record in &export.records {
+        let Some(exam) = export.examination(record.id) else {
+            continue;
+        };
+        let iop = &exam.iop;
+        let time = hl7_datetime(iop.timestamp);
+        let mut components = String::new();
+        for (eye, value, code, display, site, site_name) in [
+            (
+                "right",
+                iop.right_eye,
+                "79893-8",
+                "Right eye Intraocular pressure",
+                "18944008",
+                "Right eye structure",
+            ),
+            (
+                "left",
+                iop.left_eye,
+                "79892-0",
+                "Left eye Intraocular pressure",
+                "8966001",
+                "Left eye structure",
+            ),
+        ] {
+            let id = format!("exam-{}-iop-{eye}", record.id);
+            rows.push(vec![
+                iso_datetime(iop.timestamp),
+                display.to_string(),
+                format!("{value} mmHg"),
+                text(&iop.method),
+            ]);
+            components.push_str(&format!(
+                "<component><observation classCode=\"OBS\" moodCode=\"EVN\">\
+                 <templateId root=\"2.16.840.1.113883.10.20.22.4.2\"/>\
+                 <templateId root=\"2.16.840.1.113883.10.20.22.4.2\" extension=\"{TEMPLATE_VERSION}\"/>\
+                 <id root=\"{ID_ROOT}\" extension=\"{id}\"/>\
+                 <code code=\"{code}\" codeSystem=\"{LOINC}\" codeSystemName=\"LOINC\" displayName=\"{display}\"/>\
+                 <statusCode code=\"completed\"/><effectiveTime value=\"{time}\"/>\
+                 <value xsi:type=\"PQ\" value=\"{value}\" unit=\"mm[Hg]\"/>\
+                 <methodCode nullFlavor=\"OTH\"><originalText>{method}</originalText></methodCode>\
+                 <targetSiteCode code=\"{site}\" codeSystem=\"{SNOMED}\" codeSystemName=\"SNOMED CT\" displayName=\"{site_name}\"/>\
+                 </observation></component>",
+                method = esc(&text(&iop.method)),
+            ));
+        }
+        entries.push_str(&format!(
+            "<entry typeCode=\"DRIV\"><organizer classCode=\"BATTERY\" moodCode=\"EVN\">\
+             <templateId root=\"2.16.840.1.113883.10.20.22.4.1\"/>\
+             <templateId root=\"2.16.840.1.113883.10.20.22.4.1\" extension=\"{TEMPLATE_VERSION}\"/>\
+             <id root=\"{ID_ROOT}\" extension=\"exam-{}-iop\"/>\
+             <code nullFlavor=\"OTH\"><originalText>Tonometry</originalText></code>\
+             <statusCode code=\"completed\"/><effectiveTime value=\"{time}\"/>\
+             {components}</organizer></entry>",
+            record.id
+        ));
+    }
+
+    let narrative = if rows.is_empty() {
+        String::new()
+    } else {
+        table(&["Date", "Test", "Result", "Method"], &rows)
+    };
+    Section {
+        template: "2.16.840.1.113883.10.20.22.2.3.1",
+        template_version: TEMPLATE_VERSION,
+        code: "30954-2",
+        display: "Relevant diagnostic tests and/or laboratory data",
+        title: "Results",
+        narrative,
+        entries,
+    }
+}
+
+fn record_row(record: &VisionRecord) -> Vec<String> {
+    vec![
+        record.id.to_string(),
+        record_type_code(&record.record_type).to_string(),
+        iso_datetime(record.created_at),
+        address(&record.provider),
+        text(&record.data_hash),
+    ]
+}
+
+fn exam_narrative(record: &VisionRecord, exam: &EyeExamination) -> String {
+    let acuity = &exam.visual_acuity;
+    let (corrected_right, corrected_left) = match &acuity.corrected {
+        OptPhysicalMeasurement::Some(c) => (text(&c.right_eye), text(&c.left_eye)),
+        OptPhysicalMeasurement::None => (String::new(), String::new()),
+    };
+    let mut rows = vec![
+        vec![
+            "Visual acuity, uncorrected".to_string(),
+            text(&acuity.uncorrected.right_eye),
+            text(&acuity.uncorrected.left_eye),
+        ],
+        vec![
+            "Visual acuity, best corrected".to_string(),
+            corrected_right,
+            corrected_left,
+        ],
+        vec![
+            "Intraocular pressure (mmHg)".to_string(),
+            exam.iop.right_eye.to_string(),
+            exam.iop.left_eye.to_string(),
+        ],
+    ];
+    if let OptVisualField::Some(field) = &exam.visual_field {
+        rows.push(vec![
+            "Visual field reliability".to_string(),
+            text(&field.right_eye_reliability),
+            text(&field.left_eye_reliability),
+        ]);
+        rows.push(vec![
+            "Visual field defects".to_string(),
+            text(&field.right_eye_defects),
+            text(&field.left_eye_defects),
+        ]);
+    }
+    if let OptFundusPhotography::Some(fundus) = &exam.fundus_photo {
+        rows.push(vec![
+            "Cup-to-disc ratio".to_string(),
+            text(&fundus.cup_to_disc_ratio_right),
+            text(&fundus.cup_to_disc_ratio_left),
+        ]);
+    }
+
+    let slit = &exam.slit_lamp;
+    let mut items = vec![
+        format!("Cornea: {}", text(&slit.cornea)),
+        format!("Anterior chamber: {}", text(&slit.anterior_chamber)),
+        format!("Iris: {}", text(&slit.iris)),
+        format!("Lens: {}", text(&slit.lens)),
+    ];
+    if let OptFundusPhotography::Some(fundus) = &exam.fundus_photo {
+        items.push(format!("Macula: {}", text(&fundus.macula_status)));
+    }
+    if let OptRetinalImaging::Some(imaging) = &exam.retina_imaging {
+        items.push(format!("Retinal imaging: {}", text(&imaging.findings)));
+    }
+    if !exam.clinical_notes.is_empty() {
+        items.push(format!("Notes: {}", text(&exam.clinical_notes)));
+    }
+
+    let mut out = format!(
+        "<paragraph>Examination, record {} ({})</paragraph>",
+        record.id,
+        iso_datetime(record.created_at)
+    );
+    out.push_str(&table(
+        &["Finding", "Right eye (OD)", "Left eye (OS)"],
+        &rows,
+    ));
+    out.push_str("<list>");
+    for item in items {
+        out.push_str(&format!("<item>{}</item>", esc(&item)));
+    }
+    out.push_str("</list>");
+    out
+}
+
+fn physical_exam_section(export: &PatientExport) -> Section<'static> {
+    let mut narrative = String::new();
+    if !export.records.is_empty() {
+        let rows: Vec<_> = export.records.iter().map(record_row).collect();
+        narrative.push_str("<paragraph>Vision records</paragraph>");
+        narrative.push_str(&table(
+            &["Record", "Type", "Date", "Provider", "Payload hash"],
+            &rows,
+        ));
+    }
+    for record in &export.records {
+        if let Some(exam) = export.examination(record.id) {
+            narrative.push_str(&exam_narrative(record, exam));
+        }
+    }
+    Section {
+        template: "2.16.840.1.113883.10.20.2.10",
+        template_version: TEMPLATE_VERSION,
+        code: "29545-1",
+        display: "Physical findings",
+        title: "Eye examination",
+        narrative,
+        entries: String::new(),
+    }
+}
+
+fn prescription_row(rx: &Prescription) -> Vec<String> {
+    let lens = match rx.lens_type {
+        LensType::Glasses => "Spectacle lenses",
+        LensType::ContactLens => "Contact lenses",
+    };
+    let status = match rx.status {
+        PrescriptionStatus::Active => "Active",
+        PrescriptionStatus::Dispensed => "Dispensed",
+        PrescriptionStatus::Revoked => "Revoked",
+    };
+    let eye = |d: &vision_records::PrescriptionData| {
+        format!(
+            "SPH {} CYL {} AXIS {} ADD {} PD {}",
+            text(&d.sphere),
+            text(&d.cylinder),
+            text(&d.axis),
+            text(&d.add),
+            text(&d.pd)
+        )
+    };
+    let fitting = match &rx.contact_data {
+        OptionalContactLensData::Some(c) => format!(
+            "BC {} DIA {} {}",
+            text(&c.base_curve),
+            text(&c.diameter),
+            text(&c.brand)
+        ),
+        OptionalContactLensData::None => String::new(),
+    };
+    vec![
+        rx.id.to_string(),
+        lens.to_string(),
+        status.to_string(),
+        iso_datetime(rx.issued_at),
+        iso_datetime(rx.expires_at),
+        eye(&rx.right_eye),
+        eye(&rx.left_eye),
+        fitting,
+        address(&rx.provider),
+    ]
+}
+
+fn equipment_section(export: &PatientExport) -> Section<'static> {
+    let narrative = if export.prescriptions.is_empty() {
+        String::new()
+    } else {
+        let rows: Vec<_> = export.prescriptions.iter().map(prescription_row).collect();
+        table(
+            &[
+                "Prescription",
+                "Product",
+                "Status",
+                "Issued",
+                "Expires",
+                "Right eye (OD)",
+                "Left eye (OS)",
+                "Contact lens fitting",
+                "Prescriber",
+            ],
+            &rows,
+        )
+    };
+    Section {
+        template: "2.16.840.1.113883.10.20.22.2.23",
+        template_version: "2014-06-09",
+        code: "46264-8",
+        display: "History of medical device use",
+        title: "Vision prescriptions",
+        narrative,
+        entries: String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_escape() {
+        assert_eq!(esc("a < b & \"c\""), "a &lt; b &amp; &quot;c&quot;");
+    }
+}
@@ -0,0 +1,287 @@
+//! CSV rendering (RFC 4180).
+//!
+//! Records and prescriptions have different shapes, so they are exported as
+//! two tables. Examination findings are flattened onto their record's row.
+
+use vision_records::examination::OptPhysicalMeasurement;
+use vision_records::{LensType, OptionalContactLensData, PrescriptionData, PrescriptionStatus};
+
+use crate::import::{is_decimal, ImportError};
+use crate::{address, iso_datetime, record_type_code, text, PatientExport};
+
+pub const RECORD_COLUMNS: &[&str] = &[
+    "record_id",
+    "record_type",
+    "provider",
+    "data_hash",
+    "created_at",
+    "updated_at",
+    "va_uncorrected_right",
+    "va_uncorrected_left",
+    "va_corrected_right",
+    "va_corrected_left",
+    "iop_right_mmhg",
+    "iop_left_mmhg",
+    "iop_method",
+    "clinical_notes",
+];
+
+pub const PRESCRIPTION_COLUMNS: &[&str] = &[
+    "prescription_id",
+    "provider",
+    "lens_type",
+    "status",
+    "issued_at",
+    "expires_at",
+    "right_sphere",
+    "right_cylinder",
+    "right_axis",
+    "right_add",
+    "right_pd",
+    "left_sphere",
+    "left_cylinder",
+    "left_axis",
+    "left_add",
+    "left_pd",
+    "base_curve",
+    "diameter",
+    "brand",
+];
+
+/// Prefix a field a spreadsheet would evaluate as a formula with `'` so it
+/// is shown as text. Signed numbers such as `-1.25` are left alone.
+fn neutralise_formula(field: &str) -> String {
+    if field.starts_with(['=', '+', '-', '@', '\t', '\r']) && !is_decimal(field) {
+        format!("'{field}")
+    } else {
+        field.to_string()
+    }
+}
+
+/// Neutralise formulas, then quote the field if it contains a delimiter,
+/// quote or line break.
+fn escape(field: &str) -> String {
+    let field = neutralise_formula(field);
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field
+    }
+}
+
+fn write_row(out: &mut String, fields: &[String]) {
+    let row: Vec<String> = fields.iter().map(|f| escape(f)).collect();
+    out.push_str(&row.join(","));
+    out.push_str("\r\n");
+}
+
+fn header(columns: &[&str]) -> Vec<String> {
+    columns.iter().map(|c| c.to_string()).collect()
+}
+
+/// One row per record, with examination findings where present.
+pub fn records(export: &PatientExport) -> String {
+    let mut out = String::new();
+    write_row(&mut out, &header(RECORD_COLUMNS));
+    for record in &export.records {
+        let mut row = vec![
+            record.id.to_string(),
+            record_type_code(&record.record_type).to_string(),
+            address(&record.provider),
+            text(&record.data_hash),
+            iso_datetime(record.created_at),
+            iso_datetime(record.updated_at),
+        ];
+        match export.examination(record.id) {
+            Some(exam) => {
+                let acuity = &exam.visual_acuity;
+                row.push(text(&acuity.uncorrected.right_eye));
+                row.push(text(&acuity.uncorrected.left_eye));
+                match &acuity.corrected {
+                    OptPhysicalMeasurement::Some(corrected) => {
+                        row.push(text(&corrected.right_eye));
+                        row.push(text(&corrected.left_eye));
+                    }
+                    OptPhysicalMeasurement::None => row.extend([String::new(), String::new()]),
+                }
+                row.push(exam.iop.right_eye.to_string());
+                row.push(exam.iop.left_eye.to_string());
+                row.push(text(&exam.iop.method));
+                row.push(text(&exam.clinical_notes));
+            }
+            None => row.resize(RECORD_COLUMNS.len(), String::new()),
+        }
+        write_row(&mut out, &row);
+    }
+    out
+}
+
+fn eye_fields(data: &PrescriptionData) -> [String; 5] {
+    [
+        text(&data.sphere),
+        text(&data.cylinder),
+        text(&data.axis),
+        text(&data.add),
+        text(&data.pd),
+    ]
+}
+
+/// One row per prescription.
+pub fn prescriptions(export: &PatientExport) -> String {
+    let mut out = String::new();
+    write_row(&mut out, &header(PRESCRIPTION_COLUMNS));
+    for rx in &export.prescriptions {
+        let mut row = vec![
+            rx.id.to_string(),
+            address(&rx.provider),
+            match rx.lens_type {
+                LensType::Glasses => "glasses",
+                LensType::ContactLens => "contact-lens",
+            }
+            .to_string(),
+            match rx.status {
+                PrescriptionStatus::Active => "active",
+                PrescriptionStatus::Dispensed => "dispensed",
+                PrescriptionStatus::Revoked => "revoked",
+            }
+            .to_string(),
+            iso_datetime(rx.issued_at),
+            iso_datetime(rx.expires_at),
+        ];
+        row.extend(eye_fields(&rx.right_eye));
+        row.extend(eye_fields(&rx.left_eye));
+        match &rx.contact_data {
+            OptionalContactLensData::Some(contact) => row.extend([
+                text(&contact.base_curve),
+                text(&contact.diameter),
+                text(&contact.brand),
+            ]),
+            OptionalContactLensData::None => row.resize(PRESCRIPTION_COLUMNS.len(), String::new()),
+        }
+        write_row(&mut out, &row);
+    }
+    out
+}
+
+/// Parse CSV content into rows, each tagged with the line it starts on.
+pub(crate) fn parse(content: &str) -> Result<Vec<(usize, Vec<String>)>, ImportError> {
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut field = String::new();
+    let mut line = 1;
+    let mut row_line = 1;
+    let mut in_quotes = false;
+    let mut quoted = false;
+    let mut chars = content.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if in_quotes {
+            match c {
+                '"' if chars.peek() == Some(&'"') => {
+                    chars.next();
+                    field.push('"');
+                }
+                '"' => in_quotes = false,
+                '\n' => {
+                    line += 1;
+                    field.push(c);
+                }
+                _ => field.push(c),
+            }
+            continue;
+        }
+        match c {
+            '"' if field.is_empty() && !quoted => {
+                in_quotes = true;
+                quoted = true;
+            }
+            '"' => {
+                return Err(ImportError::Syntax {
+                    line,
+                    message: "unexpected quote in unquoted field".to_string(),
+                })
+            }
+            ',' => {
+                row.push(std::mem::take(&mut field));
+                quoted = false;
+            }
+            '\r' if chars.peek() == Some(&'\n') => {}
+            '\n' => {
+                row.push(std::mem::take(&mut field));
+                rows.push((row_line, std::mem::take(&mut row)));
+                quoted = false;
+                line += 1;
+                row_line = line;
+            }
+            _ if quoted => {
+                return Err(ImportError::Syntax {
+                    line,
+                    message: "text after closing quote".to_string(),
+                })
+            }
+            _ => field.push(c),
+        }
+    }
+    if in_quotes {
+        return Err(ImportError::Syntax {
+            line,
+            message: "unterminated quoted field".to_string(),
+        });
+    }
+    if !field.is_empty() || !row.is_empty() || quoted {
+        row.push(field);
+        rows.push((row_line, row));
+    }
+    Ok(rows)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_escape_and_parse_round_trip() {
+        let fields = vec![
+            "plain".to_string(),
+            "with, comma".to_string(),
+            "say \"hi\"".to_string(),
+            "two\nlines".to_string(),
+            String::new(),
+        ];
+        let mut out = String::new();
+        write_row(&mut out, &fields);
+        write_row(&mut out, &fields);
+
+        let rows = parse(&out).unwrap();
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0], (1, fields.clone()));
+        // The embedded newline pushes the second row down a line.
+        assert_eq!(rows[1], (3, fields));
+    }
+
+    #[test]
+    fn test_escape_neutralises_formulas() {
+        assert_eq!(escape("=HYPERLINK(\"x\")"), "\"'=HYPERLINK(\"\"x\"\")\"");
+        assert_eq!(escape("+1+1"), "'+1+1");
+        assert_eq!(escape("-2+3"), "'-2+3");
+        assert_eq!(escape("@SUM(A1)"), "'@SUM(A1)");
+        assert_eq!(escape("\tcmd"), "'\tcmd");
+        // Signed decimals are data, not formulas.
+        assert_eq!(escape("-1.25"), "-1.25");
+        assert_eq!(escape("+0.50"), "+0.50");
+        assert_eq!(escape("20/20"), "20/20");
+    }
+
+    #[test]
+    fn test_parse_rejects_malformed_quotes() {
+        assert_eq!(
+            parse("a,\"b\nc"),
+            Err(ImportError::Syntax {
+                line: 2,
+                message: "unterminated quoted field".to_string(),
+            })
+        );
+        assert!(parse("a,b\"c\n").is_err());
+        assert!(parse("a,\"b\"c\n").is_err());
+    }
+}
@@ -0,0 +1,347 @@
+//! FHIR R4 rendering.
+//!
+//! The export is a `collection` Bundle holding one `Patient`, a
+//! `DocumentReference` per on-chain record pointing at its encrypted payload,
+//! `Observation`s for structured examination findings and a
+//! `VisionPrescription` per prescription.
+
+use serde_json::{json, Value};
+use vision_records::examination::OptPhysicalMeasurement;
+use vision_records::{
+    EyeExamination, LensType, OptFundusPhotography, OptRetinalImaging, OptVisualField,
+    OptionalContactLensData, Prescription, PrescriptionData, PrescriptionStatus, VisionRecord,
+};
+
+use crate::{address, iso_datetime, record_type_code, text, PatientExport};
+
+/// Identifier system for Stellar account and contract addresses.
+pub const ADDRESS_SYSTEM: &str = "urn:teye:stellar-address";
+/// Code system for `RecordType`.
+pub const RECORD_TYPE_SYSTEM: &str = "urn:teye:record-type";
+/// Identifier system for on-chain record and prescription ids.
+pub const RECORD_ID_SYSTEM: &str = "urn:teye:record-id";
+pub const PRESCRIPTION_ID_SYSTEM: &str = "urn:teye:prescription-id";
+
+const LOINC: &str = "http://loinc.org";
+const SNOMED: &str = "http://snomed.info/sct";
+const UCUM: &str = "http://unitsofmeasure.org";
+const OBSERVATION_CATEGORY: &str = "http://terminology.hl7.org/CodeSystem/observation-category";
+const VISION_PRODUCT: &str = "http://terminology.hl7.org/CodeSystem/ex-visionprescriptionproduct";
+
+const PATIENT_REF: &str = "Patient/patient";
+
+#[derive(Clone, Copy)]
+enum Eye {
+    Right,
+    Left,
+}
+
+impl Eye {
+    fn code(self) -> &'static str {
+        match self {
+            Eye::Right => "right",
+            Eye::Left => "left",
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            Eye::Right => "right eye",
+            Eye::Left => "left eye",
+        }
+    }
+
+    fn body_site(self) -> Value {
+        let (code, display) = match self {
+            Eye::Right => ("18944008", "Right eye structure"),
+            Eye::Left => ("8966001", "Left eye structure"),
+        };
+        json!({ "coding": [{ "system": SNOMED, "code": code, "display": display }] })
+    }
+}
+
+/// Render the export as a FHIR R4 `Bundle` of type `collection`.
+pub fn to_bundle(export: &PatientExport) -> Value {
+    let mut entries = vec![patient(export)];
+    for record in &export.records {
+        entries.push(document_reference(record));
+        if let Some(exam) = export.examination(record.id) {
+            entries.extend(observations(record, exam));
+        }
+    }
+    for rx in &export.prescriptions {
+        entries.push(vision_prescription(rx));
+    }
+
+    json!({
+        "resourceType": "Bundle",
+        "type": "collection",
+        "timestamp": iso_datetime(export.generated_at),
+        "entry": entries
+            .into_iter()
+            .map(|resource| json!({ "resource": resource }))
+            .collect::<Vec<_>>(),
+    })
+}
+
+/// Pretty-printed JSON form of [`to_bundle`].
+pub fn to_json(export: &PatientExport) -> String {
+    // Serialising a `Value` cannot fail.
+    serde_json::to_string_pretty(&to_bundle(export)).unwrap_or_default()
+}
+
+fn address_identifier(addr: &soroban_sdk::Address) -> Value {
+    json!({ "system": ADDRESS_SYSTEM, "value": address(addr) })
+}
+
+fn patient(export: &PatientExport) -> Value {
+    json!({
+        "resourceType": "Patient",
+        "id": "patient",
+        "identifier": [address_identifier(&export.patient)],
+    })
+}
+
+fn record_ref(record_id: u64) -> Value {
+    json!({ "reference": format!("DocumentReference/record-{record_id}") })
+}
+
+fn document_reference(record: &VisionRecord) -> Value {
+    let mut resource = json!({
+        "resourceType": "DocumentReference",
+        "id": format!("record-{}", record.id),
+        "identifier": [{ "system": RECORD_ID_SYSTEM, "value": record.id.to_string() }],
+        "status": "current",
+        "type": {
+            "coding": [{ "system": RECORD_TYPE_SYSTEM, "code": record_type_code(&record.record_type) }],
+        },
+        "subject": { "reference": PATIENT_REF },
+        "date": iso_datetime(record.updated_at),
+        "author": [{ "identifier": address_identifier(&record.provider) }],
+        "content": [{
+            "attachment": {
+                "title": "Encrypted record payload",
+                "url": text(&record.data_hash),
+                "creation": iso_datetime(record.created_at),
+            },
+        }],
+    });
+    if let Some(version) = &record.key_version {
+        resource["securityLabel"] = json!([{ "text": format!("key version {}", text(version)) }]);
+    }
+    resource
+}
+
+fn observation(record: &VisionRecord, id: String, code: Value, eye: Option<Eye>) -> Value {
+    let mut resource = json!({
+        "resourceType": "Observation",
+        "id": id,
+        "status": "final",
+        "category": [{ "coding": [{ "system": OBSERVATION_CATEGORY, "code": "exam" }] }],
+        "code": code,
+        "subject": { "reference": PATIENT_REF },
+        "effectiveDateTime": iso_datetime(record.created_at),
+        "performer": [{ "identifier": address_identifier(&record.provider) }],
+        "derivedFrom": [record_ref(record.id)],
+    });
+    if let Some(eye) = eye {
+        resource["bodySite"] = eye.body_site();
+    }
+    resource
+}
+
+fn text_code(label: &str) -> Value {
+    json!({ "text": label })
+}
+
+fn component(label: &str, value: String) -> Value {
+    json!({ "code": text_code(label), "valueString": value })
+}
+
+fn observations(record: &VisionRecord, exam: &EyeExamination) -> Vec<Value> {
+    let id = record.id;
+    let mut out = Vec::new();
+
+    let acuity = &exam.visual_acuity;
+    for (eye, uncorrected) in [
+        (Eye::Right, &acuity.uncorrected.right_eye),
+        (Eye::Left, &acuity.uncorrected.left_eye),
+    ] {
+        let mut obs = observation(
+            record,
+            format!("exam-{id}-va-{}", eye.code()),
+            text_code(&format!("Visual acuity, {}", eye.label())),
+            Some(eye),
+        );
+        let mut components = vec![component("Uncorrected", text(uncorrected))];
+        if let OptPhysicalMeasurement::Some(corrected) = &acuity.corrected {
+            let value = match eye {
+                Eye::Right => &corrected.right_eye,
+                Eye::Left => &corrected.left_eye,
+            };
+            components.push(component("Best corrected", text(value)));
+        }
+        obs["component"] = json!(components);
+        out.push(obs);
+    }
+
+    let iop = &exam.iop;
+    for (eye, value, loinc) in [
+        (Eye::Right, iop.right_eye, "79893-8"),
+        (Eye::Left, iop.left_eye, "79892-0"),
+    ] {
+        let display = format!("Intraocular pressure, {}", eye.label());
+        let mut obs = observation(
+            record,
+            format!("exam-{id}-iop-{}", eye.code()),
+            json!({ "coding": [{ "system": LOINC, "code": loinc }], "text": display }),
+            Some(eye),
+        );
+        obs["effectiveDateTime"] = json!(iso_datetime(iop.timestamp));
+        obs["method"] = text_code(&text(&iop.method));
+        obs["valueQuantity"] =
+            json!({ "value": value, "unit": "mmHg", "system": UCUM, "code": "mm[Hg]" });
+        out.push(obs);
+    }
+
+    let slit = &exam.slit_lamp;
+    let mut obs = observation(
+        record,
+        format!("exam-{id}-slit-lamp"),
+        text_code("Slit lamp examination"),
+        None,
+    );
+    obs["component"] = json!([
+        component("Cornea", text(&slit.cornea)),
+        component("Anterior chamber", text(&slit.anterior_chamber)),
+        component("Iris", text(&slit.iris)),
+        component("Lens", text(&slit.lens)),
+    ]);
+    out.push(obs);
+
+    if let OptVisualField::Some(field) = &exam.visual_field {
+        let mut obs = observation(
+            record,
+            format!("exam-{id}-visual-field"),
+            text_code("Visual field examination"),
+            None,
+        );
+        obs["component"] = json!([
+            component("Right eye reliability", text(&field.right_eye_reliability)),
+            component("Right eye defects", text(&field.right_eye_defects)),
+            component("Left eye reliability", text(&field.left_eye_reliability)),
+            component("Left eye defects", text(&field.left_eye_defects)),
+        ]);
+        out.push(obs);
+    }
+
+    if let OptRetinalImaging::Some(imaging) = &exam.retina_imaging {
+        let mut obs = observation(
+            record,
+            format!("exam-{id}-retinal-imaging"),
+            text_code("Retinal imaging"),
+            None,
+        );
+        obs["valueString"] = json!(text(&imaging.findings));
+        obs["note"] = json!([{ "text": format!(
+            "Image {} (hash {})",
+            text(&imaging.image_url),
+            text(&imaging.image_hash)
+        ) }]);
+        out.push(obs);
+    }
+
+    if let OptFundusPhotography::Some(fundus) = &exam.fundus_photo {
+        let mut obs = observation(
+            record,
+            format!("exam-{id}-fundus"),
+            text_code("Fundus photography"),
+            None,
+        );
+        obs["component"] = json!([
+            component(
+                "Cup-to-disc ratio, right eye",
+                text(&fundus.cup_to_disc_ratio_right)
+            ),
+            component(
+                "Cup-to-disc ratio, left eye",
+                text(&fundus.cup_to_disc_ratio_left)
+            ),
+            component("Macula", text(&fundus.macula_status)),
+        ]);
+        obs["note"] = json!([{ "text": format!(
+            "Image {} (hash {})",
+            text(&fundus.image_url),
+            text(&fundus.image_hash)
+        ) }]);
+        out.push(obs);
+    }
+
+    if !exam.clinical_notes.is_empty() {
+        let mut obs = observation(
+            record,
+            format!("exam-{id}-notes"),
+            text_code("Clinical notes"),
+            None,
+        );
+        obs["valueString"] = json!(text(&exam.clinical_notes));
+        out.push(obs);
+    }
+
+    out
+}
+
+/// Parse a contract decimal string into a JSON number, falling back to the
+/// original text if it is not numeric.
+fn decimal(value: &soroban_sdk::String) -> Value {
+    let raw = text(value);
+    raw.trim_start_matches('+')
+        .parse::<f64>()
+        .ok()
+        .and_then(serde_json::Number::from_f64)
+        .map(Value::Number)
+        .unwrap_or(Value::String(raw))
+}
+
+fn lens_specification(rx: &Prescription, eye: Eye, data: &PrescriptionData) -> Value {
+    let product = match rx.lens_type {
+        LensType::Glasses => "lens",
+        LensType::ContactLens => "contact",
+    };
+    let mut spec = json!({
+        "product": { "coding": [{ "system": VISION_PRODUCT, "code": product }] },
+        "eye": eye.code(),
+        "sphere": decimal(&data.sphere),
+        "cylinder": decimal(&data.cylinder),
+        "axis": decimal(&data.axis),
+        "add": decimal(&data.add),
+        "note": [{ "text": format!("Pupillary distance {} mm", text(&data.pd)) }],
+    });
+    if let OptionalContactLensData::Some(contact) = &rx.contact_data {
+        spec["backCurve"] = decimal(&contact.base_curve);
+        spec["diameter"] = decimal(&contact.diameter);
+        spec["brand"] = json!(text(&contact.brand));
+    }
+    spec
+}
+
+fn vision_prescription(rx: &Prescription) -> Value {
+    let status = match rx.status {
+        PrescriptionStatus::Active | PrescriptionStatus::Dispensed => "active",
+        PrescriptionStatus::Revoked => "cancelled",
+    };
+    json!({
+        "resourceType": "VisionPrescription",
+        "id": format!("rx-{}", rx.id),
+        "identifier": [{ "system": PRESCRIPTION_ID_SYSTEM, "value": rx.id.to_string() }],
+        "status": status,
+        "created": iso_datetime(rx.issued_at),
+        "patient": { "reference": PATIENT_REF },
+        "dateWritten": iso_datetime(rx.issued_at),
+        "prescriber": { "identifier": address_identifier(&rx.provider) },
+        "lensSpecification": [
+            lens_specification(rx, Eye::Right, &rx.right_eye),
+            lens_specification(rx, Eye::Left, &rx.left_eye),
+        ],
+    })
+}
@@ -0,0 +1,623 @@
+//! Schema validation for files offered for import.
+//!
+//! The content is parsed and checked against the structure this crate
+//! exports, so a file is accepted for what it contains rather than for its
+//! extension.
+
+use core::fmt;
+
+use serde_json::Value;
+
+use crate::ccda::{CCD_DOCUMENT, US_REALM_HEADER};
+use crate::csv::{PRESCRIPTION_COLUMNS, RECORD_COLUMNS};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ImportFormat {
+    FhirBundle,
+    Ccda,
+    Csv,
+}
+
+/// Result of a successful validation.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ImportSummary {
+    pub format: ImportFormat,
+    /// Bundle entries, CSV data rows or C-CDA sections.
+    pub entries: usize,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ImportError {
+    /// The content could not be parsed at all.
+    Syntax { line: usize, message: String },
+    /// The content parsed but does not match the expected schema.
+    Schema { path: String, message: String },
+}
+
+impl fmt::Display for ImportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ImportError::Syntax { line, message } => write!(f, "line {line}: {message}"),
+            ImportError::Schema { path, message } => write!(f, "{path}: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for ImportError {}
+
+fn schema<T>(path: impl Into<String>, message: impl Into<String>) -> Result<T, ImportError> {
+    Err(ImportError::Schema {
+        path: path.into(),
+        message: message.into(),
+    })
+}
+
+/// Validate `content` against the schema for `format`.
+pub fn validate_import(format: ImportFormat, content: &str) -> Result<ImportSummary, ImportError> {
+    let entries = match format {
+        ImportFormat::FhirBundle => validate_fhir(content)?,
+        ImportFormat::Ccda => validate_ccda(content)?,
+        ImportFormat::Csv => validate_csv(content)?,
+    };
+    Ok(ImportSummary { format, entries })
+}
+
+// ======================== FHIR ========================
+
+fn require<'a>(value: &'a Value, path: &str, field: &str) -> Result<&'a Value, ImportError> {
+    match value.get(field) {
+        Some(v) if !v.is_null() => Ok(v),
+        _ => schema(format!("{path}.{field}"), "required field is missing"),
+    }
+}
+
+fn require_str<'a>(value: &'a Value, path: &str, field: &str) -> Result<&'a str, ImportError> {
+    require(value, path, field)?.as_str().map_or_else(
+        || schema(format!("{path}.{field}"), "expected a string"),
+        Ok,
+    )
+}
+
+fn require_code(
+    value: &Value,
+    path: &str,
+    field: &str,
+    allowed: &[&str],
+) -> Result<(), ImportError> {
+    let code = require_str(value, path, field)?;
+    if allowed.contains(&code) {
+        Ok(())
+    } else {
+        schema(
+            format!("{path}.{field}"),
+            format!("'{code}' is not one of {allowed:?}"),
+        )
+    }
+}
+
+fn require_array<'a>(
+    value: &'a Value,
+    path: &str,
+    field: &str,
+) -> Result<&'a Vec<Value>, ImportError> {
+    match require(value, path, field)?.as_array() {
+        Some(items) if !items.is_empty() => Ok(items),
+        _ => schema(format!("{path}.{field}"), "expected a non-empty array"),
+    }
+}
+
+fn require_object(value: &Value, path: &str, field: &str) -> Result<(), ImportError> {
+    if require(value, path, field)?.is_object() {
+        Ok(())
+    } else {
+        schema(format!("{path}.{field}"), "expected an object")
+    }
+}
+
+fn validate_resource(resource: &Value, path: &str) -> Result<(), ImportError> {
+    match require_str(resource, path, "resourceType")? {
+        "Patient" => {
+            require_array(resource, path, "identifier")?;
+        }
+        "DocumentReference" => {
+            require_code(
+                resource,
+                path,
+                "status",
+                &["current", "superseded", "entered-in-error"],
+            )?;
+            require_object(resource, path, "subject")?;
+            for (i, content) in require_array(resource, path, "content")?.iter().enumerate() {
+                require_object(content, &format!("{path}.content[{i}]"), "attachment")?;
+            }
+        }
+        "Observation" => {
+            require_code(
+                resource,
+                path,
+                "status",
+                &[
+                    "registered",
+                    "preliminary",
+                    "final",
+                    "amended",
+                    "corrected",
+                    "cancelled",
+                    "entered-in-error",
+                    "unknown",
+                ],
+            )?;
+            require_object(resource, path, "code")?;
+            require_object(resource, path, "subject")?;
+        }
+        "VisionPrescription" => {
+            require_code(
+                resource,
+                path,
+                "status",
+                &["active", "cancelled", "draft", "entered-in-error"],
+            )?;
+            require_str(resource, path, "created")?;
+            require_object(resource, path, "patient")?;
+            require_str(resource, path, "dateWritten")?;
+            require_object(resource, path, "prescriber")?;
+            let specs = require_array(resource, path, "lensSpecification")?;
+            for (i, spec) in specs.iter().enumerate() {
+                let spec_path = format!("{path}.lensSpecification[{i}]");
+                require_object(spec, &spec_path, "product")?;
+                require_code(spec, &spec_path, "eye", &["right", "left"])?;
+            }
+        }
+        other => {
+            return schema(
+                format!("{path}.resourceType"),
+                format!("unsupported resource type '{other}'"),
+            )
+        }
+    }
+    Ok(())
+}
+
+fn validate_fhir(content: &str) -> Result<usize, ImportError> {
+    let bundle: Value = serde_json::from_str(content).map_err(|e| ImportError::Syntax {
+        line: e.line(),
+        message: e.to_string(),
+    })?;
+
+    if require_str(&bundle, "Bundle", "resourceType")? != "Bundle" {
+        return schema("Bundle.resourceType", "expected 'Bundle'");
+    }
+    require_code(
+        &bundle,
+        "Bundle",
+        "type",
+        &["collection", "document", "searchset"],
+    )?;
+
+    let entries = match bundle.get("entry") {
+        None => return Ok(0),
+        Some(Value::Array(entries)) => entries,
+        Some(_) => return schema("Bundle.entry", "expected an array"),
+    };
+    for (i, entry) in entries.iter().enumerate() {
+        let path = format!("Bundle.entry[{i}]");
+        let resource = require(entry, &path, "resource")?;
+        validate_resource(resource, &format!("{path}.resource"))?;
+    }
+    Ok(entries.len())
+}
+
+// ======================== CSV ========================
+
+const RECORD_TYPES: &[&str] = &[
+    "examination",
+    "prescription",
+    "diagnosis",
+    "treatment",
+    "surgery",
+    "lab-result",
+];
+
+fn is_timestamp(value: &str) -> bool {
+    // YYYY-MM-DDTHH:MM:SSZ
+    let bytes = value.as_bytes();
+    bytes.len() == 20
+        && bytes.iter().enumerate().all(|(i, b)| match i {
+            4 | 7 => *b == b'-',
+            10 => *b == b'T',
+            13 | 16 => *b == b':',
+            19 => *b == b'Z',
+            _ => b.is_ascii_digit(),
+        })
+}
+
+pub(crate) fn is_decimal(value: &str) -> bool {
+    let digits = value.trim_start_matches(['+', '-']);
+    !digits.is_empty()
+        && digits.chars().filter(|c| *c == '.').count() <= 1
+        && digits.chars().all(|c| c.is_ascii_digit() || c == '.')
+        && !digits.starts_with('.')
+        && !digits.ends_with('.')
+}
+
+enum Column {
+    Integer,
+    Timestamp,
+    Decimal,
+    Code(&'static [&'static str]),
+    Text,
+}
+
+/// Expected kind of a column and whether it must be non-empty.
+type ColumnRule = fn(&str) -> (Column, bool);
+
+fn record_column(name: &str) -> (Column, bool) {
+    match name {
+        "record_id" => (Column::Integer, true),
+        "record_type" => (Column::Code(RECORD_TYPES), true),
+        "provider" | "data_hash" => (Column::Text, true),
+        "created_at" | "updated_at" => (Column::Timestamp, true),
+        "iop_right_mmhg" | "iop_left_mmhg" => (Column::Integer, false),
+        _ => (Column::Text, false),
+    }
+}
+
+fn prescription_column(name: &str) -> (Column, bool) {
+    match name {
+        "prescription_id" => (Column::Integer, true),
+        "provider" => (Column::Text, true),
+        "lens_type" => (Column::Code(&["glasses", "contact-lens"]), true),
+        "status" => (Column::Code(&["active", "dispensed", "revoked"]), true),
+        "issued_at" | "expires_at" => (Column::Timestamp, true),
+        "brand" => (Column::Text, false),
+        "base_curve" | "diameter" => (Column::Decimal, false),
+        _ => (Column::Decimal, true),
+    }
+}
+
+fn validate_csv(content: &str) -> Result<usize, ImportError> {
+    let rows = crate::csv::parse(content)?;
+    let Some(((_, header), data)) = rows.split_first() else {
+        return schema("header", "file is empty");
+    };
+
+    let (columns, column_rule): (&[&str], ColumnRule) = if header
+        .iter()
+        .map(String::as_str)
+        .eq(RECORD_COLUMNS.iter().copied())
+    {
+        (RECORD_COLUMNS, record_column)
+    } else if header
+        .iter()
+        .map(String::as_str)
+        .eq(PRESCRIPTION_COLUMNS.iter().copied())
+    {
+        (PRESCRIPTION_COLUMNS, prescription_column)
+    } else {
+        return schema(
+            "header",
+            "columns match neither the records nor the prescriptions export",
+        );
+    };
+
+    for (line, row) in data {
+        if row.len() != columns.len() {
+            return schema(
+                format!("line {line}"),
+                format!("expected {} fields, found {}", columns.len(), row.len()),
+            );
+        }
+        for (name, value) in columns.iter().zip(row) {
+            let (kind, required) = column_rule(name);
+            if value.is_empty() {
+                if required {
+                    return schema(format!("line {line}, {name}"), "required value is empty");
+                }
+                continue;
+            }
+            let valid = match kind {
+                Column::Integer => value.parse::<u64>().is_ok(),
+                Column::Timestamp => is_timestamp(value),
+                Column::Decimal => is_decimal(value),
+                Column::Code(allowed) => allowed.contains(&value.as_str()),
+                Column::Text => true,
+            };
+            if !valid {
+                return schema(
+                    format!("line {line}, {name}"),
+                    format!("invalid value '{value}'"),
+                );
+            }
+        }
+    }
+    Ok(data.len())
+}
+
+// ======================== C-CDA ========================
+
+struct Element {
+    /// Names of the enclosing elements, outermost first, then this element.
+    path: Vec<String>,
+    attrs: Vec<(String, String)>,
+}
+
+impl Element {
+    fn name(&self) -> &str {
+        self.path.last().map(String::as_str).unwrap_or_default()
+    }
+
+    fn attr(&self, name: &str) -> Option<&str> {
+        self.attrs
+            .iter()
+            .find(|(k, _)| k == name)
+            .map(|(_, v)| v.as_str())
+    }
+
+    fn parent(&self) -> Option<&str> {
+        self.path
+            .len()
+            .checked_sub(2)
+            .map(|i| self.path[i].as_str())
+    }
+}
+
+fn xml_error<T>(line: usize, message: impl Into<String>) -> Result<T, ImportError> {
+    Err(ImportError::Syntax {
+        line,
+        message: message.into(),
+    })
+}
+
+fn parse_attrs(raw: &str, line: usize) -> Result<Vec<(String, String)>, ImportError> {
+    let mut attrs = Vec::new();
+    let mut rest = raw.trim();
+    while !rest.is_empty() {
+        let Some(eq) = rest.find('=') else {
+            return xml_error(line, format!("malformed attribute '{rest}'"));
+        };
+        let name = rest[..eq].trim().to_string();
+        let value_part = rest[eq + 1..].trim_start();
+        let Some(quote) = value_part
+            .chars()
+            .next()
+            .filter(|c| *c == '"' || *c == '\'')
+        else {
+            return xml_error(line, format!("attribute '{name}' value must be quoted"));
+        };
+        let Some(end) = value_part[1..].find(quote) else {
+            return xml_error(line, format!("unterminated value for attribute '{name}'"));
+        };
+        attrs.push((name, value_part[1..=end].to_string()));
+        rest = value_part[end + 2..].trim_start();
+    }
+    Ok(attrs)
+}
+
+/// Check well-formedness and collect every element with its ancestry.
+fn parse_xml(content: &str) -> Result<Vec<Element>, ImportError> {
+    let mut elements = Vec::new();
+    let mut stack: Vec<String> = Vec::new();
+    let mut rest = content;
+    let mut line = 1;
+    let mut seen_root = false;
+
+    while let Some(start) = rest.find('<') {
+        let text = &rest[..start];
+        if !stack.is_empty() || text.trim().is_empty() {
+            line += text.matches('\n').count();
+        } else {
+            return xml_error(line, "text outside the root element");
+        }
+        rest = &rest[start..];
+
+        let (terminator, skip) = if rest.starts_with("<!--") {
+            ("-->", true)
+        } else if rest.starts_with("<?") {
+            ("?>", true)
+        } else {
+            (">", false)
+        };
+        let Some(end) = rest.find(terminator) else {
+            return xml_error(line, "unterminated markup");
+        };
+        let tag = &rest[1..end];
+        let tag_line = line;
+        line += tag.matches('\n').count();
+        rest = &rest[end + terminator.len()..];
+        if skip {
+            continue;
+        }
+
+        if let Some(name) = tag.strip_prefix('/') {
+            let name = name.trim();
+            match stack.pop() {
+                Some(open) if open == name => {}
+                Some(open) => {
+                    return xml_error(tag_line, format!("expected </{open}>, found </{name}>"))
+                }
+                None => return xml_error(tag_line, format!("unexpected </{name}>")),
+            }
+            continue;
+        }
+
+        let self_closing = tag.ends_with('/');
+        let body = tag.trim_end_matches('/');
+        let name_end = body.find(|c: char| c.is_whitespace()).unwrap_or(body.len());
+        let name = &body[..name_end];
+        if name.is_empty() {
+            return xml_error(tag_line, "missing element name");
+        }
+        if stack.is_empty() {
+            if seen_root {
+                return xml_error(tag_line, "more than one root element");
+            }
+            seen_root = true;
+        }
+
+        let mut path = stack.clone();
+        path.push(name.to_string());
+        elements.push(Element {
+            path,
+            attrs: parse_attrs(&body[name_end..], tag_line)?,
+        });
+        if !self_closing {
+            stack.push(name.to_string());
+        }
+    }
+    if let Some(open) = stack.pop() {
+        return xml_error(line, format!("<{open}> is never closed"));
+    }
+    if !seen_root || !rest.trim().is_empty() {
+        return xml_error(line, "no root element or trailing text");
+    }
+    Ok(elements)
+}
+
+fn validate_ccda(content: &str) -> Result<usize, ImportError> {
+    let elements = parse_xml(content)?;
+    let root = &elements[0];
+    if root.name() != "ClinicalDocument" {
+        return schema(root.name(), "expected ClinicalDocument root element");
+    }
+    if root.attr("xmlns") != Some("urn:hl7-org:v3") {
+        return schema("ClinicalDocument", "expected the urn:hl7-org:v3 namespace");
+    }
+
+    let header = |name: &'static str| {
+        elements
+            .iter()
+            .filter(move |e| e.path.len() == 2 && e.name() == name)
+    };
+    if !header("typeId").any(|e| {
+        e.attr("root") == Some("2.16.840.1.113883.1.3")
+            && e.attr("extension") == Some("POCD_HD000040")
+    }) {
+        return schema("ClinicalDocument.typeId", "missing CDA R2 typeId");
+    }
+    for template in [US_REALM_HEADER, CCD_DOCUMENT] {
+        if !header("templateId").any(|e| e.attr("root") == Some(template)) {
+            return schema(
+                "ClinicalDocument.templateId",
+                format!("missing templateId {template}"),
+            );
+        }
+    }
+    for required in [
+        "id",
+        "code",
+        "effectiveTime",
+        "recordTarget",
+        "author",
+        "custodian",
+    ] {
+        if header(required).next().is_none() {
+            return schema(
+                format!("ClinicalDocument.{required}"),
+                "required element is missing",
+            );
+        }
+    }
+    if !elements.iter().any(|e| {
+        e.path
+            .ends_with(&["recordTarget".into(), "patientRole".into(), "id".into()])
+    }) {
+        return schema(
+            "ClinicalDocument.recordTarget.patientRole.id",
+            "required element is missing",
+        );
+    }
+
+    let sections: Vec<usize> = elements
+        .iter()
+        .enumerate()
+        .filter(|(_, e)| e.name() == "section")
+        .map(|(i, _)| i)
+        .collect();
+    if !elements.iter().any(|e| e.name() == "structuredBody") || sections.is_empty() {
+        return schema("ClinicalDocument.component.structuredBody", "no sections");
+    }
+    for (n, &index) in sections.iter().enumerate() {
+        let depth = elements[index].path.len();
+        let children = elements[index + 1..]
+            .iter()
+            .take_while(|e| e.path.len() > depth)
+            .filter(|e| e.path.len() == depth + 1 && e.parent() == Some("section"));
+        let mut has_template = false;
+        let mut has_code = false;
+        for child in children {
+            match child.name() {
+                "templateId" => has_template |= child.attr("root").is_some(),
+                "code" => {
+                    has_code |= child.attr("code").is_some() && child.attr("codeSystem").is_some()
+                }
+                _ => {}
+            }
+        }
+        if !has_template || !has_code {
+            return schema(
+                format!("ClinicalDocument.component.structuredBody.section[{n}]"),
+                "sections need a templateId and a coded code",
+            );
+        }
+    }
+    Ok(sections.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fhir_reports_path_of_missing_field() {
+        let bundle = r#"{
+            "resourceType": "Bundle",
+            "type": "collection",
+            "entry": [
+                { "resource": { "resourceType": "Patient", "identifier": [{ "value": "G" }] } },
+                { "resource": { "resourceType": "VisionPrescription", "status": "active" } }
+            ]
+        }"#;
+        assert_eq!(
+            validate_import(ImportFormat::FhirBundle, bundle),
+            Err(ImportError::Schema {
+                path: "Bundle.entry[1].resource.created".to_string(),
+                message: "required field is missing".to_string(),
+            })
+        );
+
+        let syntax = validate_import(ImportFormat::FhirBundle, "{\n\"resourceType\": }");
+        assert!(matches!(syntax, Err(ImportError::Syntax { line: 2, .. })));
+    }
+
+    #[test]
+    fn test_csv_rejects_unknown_header_and_bad_values() {
+        assert!(matches!(
+            validate_import(ImportFormat::Csv, "id,name\r\n1,Alice\r\n"),
+            Err(ImportError::Schema { path, .. }) if path == "header"
+        ));
+
+        let header = RECORD_COLUMNS.join(",");
+        let bad_type = format!(
+            "{header}\r\n1,x-ray,G,hash,2024-03-01T09:30:00Z,2024-03-01T09:30:00Z,,,,,,,,\r\n"
+        );
+        assert!(matches!(
+            validate_import(ImportFormat::Csv, &bad_type),
+            Err(ImportError::Schema { path, .. }) if path == "line 2, record_type"
+        ));
+    }
+
+    #[test]
+    fn test_ccda_requires_well_formed_clinical_document() {
+        assert!(matches!(
+            validate_import(
+                ImportFormat::Ccda,
+                "<ClinicalDocument>\n<title></ClinicalDocument>"
+            ),
+            Err(ImportError::Syntax { line: 2, .. })
+        ));
+        assert!(matches!(
+            validate_import(ImportFormat::Ccda, "<CCDA><Patient id=\"1\"/></CCDA>"),
+            Err(ImportError::Schema { .. })
+        ));
+    }
+}
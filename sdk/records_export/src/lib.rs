@@ -0,0 +1,228 @@
+//! Host-side export of a patient's vision records for data portability requests.
+//!
+//! Records, examinations and prescriptions are fetched through the
+//! `vision_records` contract client and rendered as a FHIR R4 Bundle, a
+//! C-CDA R2.1 Continuity of Care Document or CSV. Files received for import
+//! are checked against the same schemas with [`validate_import`].
+
+pub mod ccda;
+pub mod csv;
+pub mod fhir;
+pub mod import;
+
+use core::fmt;
+
+use soroban_sdk::{Address, Env};
+use vision_records::{
+    pagination::MAX_PAGE_LIMIT, ContractError, EyeExamination, Prescription, RecordFilter,
+    RecordType, VisionRecord, VisionRecordsContractClient,
+};
+
+pub use import::{validate_import, ImportError, ImportFormat, ImportSummary};
+
+/// Everything exported for a single patient.
+#[derive(Clone, Debug)]
+pub struct PatientExport {
+    pub patient: Address,
+    /// Ledger timestamp (seconds) at which the export was taken.
+    pub generated_at: u64,
+    pub records: Vec<VisionRecord>,
+    pub examinations: Vec<EyeExamination>,
+    pub prescriptions: Vec<Prescription>,
+}
+
+/// Failure while fetching a patient's data from the contract.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ExportError {
+    /// The contract rejected the call.
+    Contract(ContractError),
+    /// The call could not be invoked or its result could not be decoded.
+    Invocation(&'static str),
+}
+
+impl fmt::Display for ExportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExportError::Contract(err) => write!(f, "contract error: {}", err.message()),
+            ExportError::Invocation(op) => write!(f, "failed to invoke {op}"),
+        }
+    }
+}
+
+impl std::error::Error for ExportError {}
+
+/// Unwraps the nested result of a generated `try_*` client call.
+fn call<T, C, I>(
+    op: &'static str,
+    result: Result<Result<T, C>, Result<ContractError, I>>,
+) -> Result<T, ExportError> {
+    match result {
+        Ok(Ok(value)) => Ok(value),
+        Err(Ok(err)) => Err(ExportError::Contract(err)),
+        Ok(Err(_)) | Err(Err(_)) => Err(ExportError::Invocation(op)),
+    }
+}
+
+impl PatientExport {
+    /// Fetch all of `patient`'s records, examinations and prescriptions.
+    /// Retracted records are skipped.
+    ///
+    /// `caller` must be allowed to read the patient's records (the patient
+    /// themselves, a granted provider or an admin) and must authorize the
+    /// `get_record` and `get_eye_examination` calls.
+    pub fn fetch(
+        env: &Env,
+        client: &VisionRecordsContractClient,
+        caller: &Address,
+        patient: &Address,
+    ) -> Result<Self, ExportError> {
+        let mut record_ids = Vec::new();
+        let filter = RecordFilter {
+            record_types: soroban_sdk::Vec::new(env),
+            from: None,
+            to: None,
+        };
+        let mut cursor = None;
+        loop {
+            let page = call(
+                "get_patient_records_page",
+                client.try_get_patient_records_page(patient, &filter, &cursor, &MAX_PAGE_LIMIT),
+            )?;
+            record_ids.extend(page.record_ids.iter());
+            cursor = page.next_cursor;
+            if cursor.is_none() {
+                break;
+            }
+        }
+
+        let mut records = Vec::new();
+        let mut examinations = Vec::new();
+        for record_id in record_ids {
+            // Retracted records are not part of the exported chart.
+            let record = match call("get_record", client.try_get_record(caller, &record_id)) {
+                Ok(record) => record,
+                Err(ExportError::Contract(ContractError::RecordRetracted)) => continue,
+                Err(err) => return Err(err),
+            };
+            if record.record_type == RecordType::Examination {
+                // Examination records without structured findings are exported
+                // as plain records.
+                match call(
+                    "get_eye_examination",
+                    client.try_get_eye_examination(caller, &record_id),
+                ) {
+                    Ok(exam) => examinations.push(exam),
+                    Err(ExportError::Contract(ContractError::RecordNotFound)) => {}
+                    Err(err) => return Err(err),
+                }
+            }
+            records.push(record);
+        }
+
+        let mut prescriptions = Vec::new();
+        let mut cursor = None;
+        loop {
+            let page = call(
+                "get_prescription_history_page",
+                client.try_get_prescription_history_page(patient, &false, &cursor, &MAX_PAGE_LIMIT),
+            )?;
+            for rx_id in page.prescription_ids.iter() {
+                prescriptions.push(call(
+                    "get_prescription",
+                    client.try_get_prescription(&rx_id),
+                )?);
+            }
+            cursor = page.next_cursor;
+            if cursor.is_none() {
+                break;
+            }
+        }
+
+        Ok(Self {
+            patient: patient.clone(),
+            generated_at: env.ledger().timestamp(),
+            records,
+            examinations,
+            prescriptions,
+        })
+    }
+
+    /// The structured examination attached to `record_id`, if any.
+    pub fn examination(&self, record_id: u64) -> Option<&EyeExamination> {
+        self.examinations.iter().find(|e| e.record_id == record_id)
+    }
+}
+
+/// Convert a contract string into an owned host string.
+pub(crate) fn text(value: &soroban_sdk::String) -> String {
+    let mut buf = vec![0u8; value.len() as usize];
+    value.copy_into_slice(&mut buf);
+    String::from_utf8_lossy(&buf).into_owned()
+}
+
+/// Strkey (`G...` / `C...`) form of an address.
+pub(crate) fn address(value: &Address) -> String {
+    text(&value.to_string())
+}
+
+pub(crate) fn record_type_code(record_type: &RecordType) -> &'static str {
+    match record_type {
+        RecordType::Examination => "examination",
+        RecordType::Prescription => "prescription",
+        RecordType::Diagnosis => "diagnosis",
+        RecordType::Treatment => "treatment",
+        RecordType::Surgery => "surgery",
+        RecordType::LabResult => "lab-result",
+    }
+}
+
+/// Split a unix timestamp into UTC (year, month, day, hour, minute, second).
+fn civil(timestamp: u64) -> (i64, u32, u32, u32, u32, u32) {
+    let days = (timestamp / 86_400) as i64;
+    let secs = timestamp % 86_400;
+
+    // Howard Hinnant's days-to-civil algorithm.
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    (
+        year,
+        month,
+        day,
+        (secs / 3_600) as u32,
+        (secs % 3_600 / 60) as u32,
+        (secs % 60) as u32,
+    )
+}
+
+/// ISO 8601 / FHIR `instant` form, e.g. `2024-03-01T09:30:00Z`.
+pub(crate) fn iso_datetime(timestamp: u64) -> String {
+    let (y, mo, d, h, mi, s) = civil(timestamp);
+    format!("{y:04}-{mo:02}-{d:02}T{h:02}:{mi:02}:{s:02}Z")
+}
+
+/// HL7 v3 `TS` form, e.g. `20240301093000+0000`.
+pub(crate) fn hl7_datetime(timestamp: u64) -> String {
+    let (y, mo, d, h, mi, s) = civil(timestamp);
+    format!("{y:04}{mo:02}{d:02}{h:02}{mi:02}{s:02}+0000")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_datetime_formats() {
+        assert_eq!(iso_datetime(0), "1970-01-01T00:00:00Z");
+        assert_eq!(iso_datetime(951_782_400), "2000-02-29T00:00:00Z");
+        assert_eq!(iso_datetime(1_709_285_400), "2024-03-01T09:30:00Z");
+        assert_eq!(hl7_datetime(1_709_285_400), "20240301093000+0000");
+    }
+}
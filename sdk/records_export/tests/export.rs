@@ -0,0 +1,239 @@
+#![cfg(test)]
+
+use records_export::{ccda, csv, fhir, validate_import, ImportFormat, PatientExport};
+use soroban_sdk::{testutils::Address as _, testutils::Ledger as _, Address, Env, String};
+use vision_records::examination::{OptPhysicalMeasurement, PhysicalMeasurement};
+use vision_records::{
+    Certification, IntraocularPressure, LensType, License, Location, OptFundusPhotography,
+    OptRetinalImaging, OptVisualField, OptionalContactLensData, PrescriptionData, RecordType, Role,
+    SlitLampFindings, VerificationStatus, VisionRecordsContract, VisionRecordsContractClient,
+    VisualAcuity,
+};
+
+const DATA_HASH: &str = "QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG";
+const METADATA_HASH: &str = "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855";
+
+fn eye(env: &Env, sphere: &str) -> PrescriptionData {
+    PrescriptionData {
+        sphere: String::from_str(env, sphere),
+        cylinder: String::from_str(env, "-0.75"),
+        axis: String::from_str(env, "90"),
+        add: String::from_str(env, "0.00"),
+        pd: String::from_str(env, "62"),
+    }
+}
+
+/// Creates a patient with an examination, a diagnosis and a prescription and
+/// fetches them through the contract client.
+fn fetch_export(env: &Env) -> PatientExport {
+    env.mock_all_auths();
+    env.ledger().set_timestamp(1_709_285_400); // 2024-03-01T09:30:00Z
+
+    let contract_id = env.register(VisionRecordsContract, ());
+    let client = VisionRecordsContractClient::new(env, &contract_id);
+    let admin = Address::generate(env);
+    client.initialize(&admin);
+
+    let provider = Address::generate(env);
+    client.register_user(
+        &admin,
+        &provider,
+        &Role::Optometrist,
+        &String::from_str(env, "Dr. Lens"),
+    );
+    // Only providers with a verified registry profile may add records.
+    let license = License {
+        number: String::from_str(env, "LIC-0001"),
+        issuing_authority: String::from_str(env, "State Board"),
+        issued_date: 0,
+        expiry_date: u64::MAX,
+        license_type: String::from_str(env, "Optometry"),
+    };
+    client.register_provider(
+        &provider,
+        &provider,
+        &String::from_str(env, "Dr. Lens"),
+        &soroban_sdk::Vec::from_array(env, [license]),
+        &soroban_sdk::Vec::<String>::new(env),
+        &soroban_sdk::Vec::<Certification>::new(env),
+        &soroban_sdk::Vec::<Location>::new(env),
+    );
+    client.verify_provider(&admin, &provider, &VerificationStatus::Verified);
+    let patient = Address::generate(env);
+    client.register_user(
+        &admin,
+        &patient,
+        &Role::Patient,
+        &String::from_str(env, "Alice"),
+    );
+
+    let exam_id = client.add_record(
+        &provider,
+        &patient,
+        &provider,
+        &RecordType::Examination,
+        &String::from_str(env, DATA_HASH),
+    );
+    client.add_eye_examination(
+        &provider,
+        &exam_id,
+        &VisualAcuity {
+            uncorrected: PhysicalMeasurement {
+                left_eye: String::from_str(env, "20/40"),
+                right_eye: String::from_str(env, "20/30"),
+            },
+            corrected: OptPhysicalMeasurement::Some(PhysicalMeasurement {
+                left_eye: String::from_str(env, "20/20"),
+                right_eye: String::from_str(env, "20/20"),
+            }),
+        },
+        &IntraocularPressure {
+            left_eye: 16,
+            right_eye: 15,
+            method: String::from_str(env, "Goldmann"),
+            timestamp: 1_709_285_400,
+        },
+        &SlitLampFindings {
+            cornea: String::from_str(env, "Clear"),
+            anterior_chamber: String::from_str(env, "Deep & quiet"),
+            iris: String::from_str(env, "Normal"),
+            lens: String::from_str(env, "Clear"),
+        },
+        &OptVisualField::None,
+        &OptRetinalImaging::None,
+        &OptFundusPhotography::None,
+        &String::from_str(env, "Follow up in 12 months, \"stable\""),
+    );
+    client.add_record(
+        &provider,
+        &patient,
+        &provider,
+        &RecordType::Diagnosis,
+        &String::from_str(env, DATA_HASH),
+    );
+    client.issue_prescription(
+        &provider,
+        &patient,
+        &LensType::Glasses,
+        &eye(env, "-1.25"),
+        &eye(env, "-1.50"),
+        &OptionalContactLensData::None,
+        &31_536_000,
+        &String::from_str(env, METADATA_HASH),
+    );
+
+    PatientExport::fetch(env, &client, &patient, &patient).unwrap()
+}
+
+#[test]
+fn test_fetch_collects_records_examinations_and_prescriptions() {
+    let env = Env::default();
+    let export = fetch_export(&env);
+
+    assert_eq!(export.records.len(), 2);
+    assert_eq!(export.examinations.len(), 1);
+    assert_eq!(export.prescriptions.len(), 1);
+    assert!(export.examination(export.records[0].id).is_some());
+    assert!(export.examination(export.records[1].id).is_none());
+}
+
+#[test]
+fn test_fhir_bundle_export() {
+    let env = Env::default();
+    let export = fetch_export(&env);
+    let bundle = fhir::to_bundle(&export);
+
+    assert_eq!(bundle["resourceType"], "Bundle");
+    assert_eq!(bundle["timestamp"], "2024-03-01T09:30:00Z");
+    let types: Vec<&str> = bundle["entry"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|e| e["resource"]["resourceType"].as_str().unwrap())
+        .collect();
+    assert_eq!(types.first(), Some(&"Patient"));
+    assert_eq!(
+        types.iter().filter(|t| **t == "DocumentReference").count(),
+        2
+    );
+    assert_eq!(types.last(), Some(&"VisionPrescription"));
+
+    let iop = bundle["entry"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|e| &e["resource"])
+        .find(|r| r["id"] == "exam-1-iop-right")
+        .unwrap();
+    assert_eq!(iop["valueQuantity"]["value"], 15);
+    assert_eq!(iop["valueQuantity"]["code"], "mm[Hg]");
+
+    let rx = &bundle["entry"].as_array().unwrap().last().unwrap()["resource"];
+    assert_eq!(rx["status"], "active");
+    assert_eq!(rx["lensSpecification"][0]["eye"], "right");
+    assert_eq!(rx["lensSpecification"][0]["sphere"], -1.5);
+
+    let summary = validate_import(ImportFormat::FhirBundle, &fhir::to_json(&export)).unwrap();
+    assert_eq!(summary.entries, types.len());
+}
+
+#[test]
+fn test_ccda_export() {
+    let env = Env::default();
+    let export = fetch_export(&env);
+    let document = ccda::to_document(&export);
+
+    assert!(document.contains("<templateId root=\"2.16.840.1.113883.10.20.22.1.2\""));
+    assert!(document.contains("<effectiveTime value=\"20240301093000+0000\"/>"));
+    assert!(document.contains("code=\"79893-8\""));
+    assert!(document.contains("<value xsi:type=\"PQ\" value=\"15\" unit=\"mm[Hg]\"/>"));
+    assert!(document.contains("Deep &amp; quiet"));
+    assert!(document.contains("SPH -1.50 CYL -0.75 AXIS 90"));
+    // Sections with no on-chain data are flagged rather than omitted.
+    assert!(document.contains("<section nullFlavor=\"NI\">"));
+
+    let summary = validate_import(ImportFormat::Ccda, &document).unwrap();
+    assert_eq!(summary.entries, 8);
+}
+
+#[test]
+fn test_csv_export() {
+    let env = Env::default();
+    let export = fetch_export(&env);
+
+    let records = csv::records(&export);
+    let lines: Vec<&str> = records.split("\r\n").collect();
+    assert!(lines[0].starts_with("record_id,record_type,provider"));
+    assert!(lines[1].starts_with("1,examination,"));
+    assert!(lines[1].ends_with(
+        ",20/30,20/40,20/20,20/20,15,16,Goldmann,\"Follow up in 12 months, \"\"stable\"\"\""
+    ));
+    assert!(lines[2].starts_with("2,diagnosis,"));
+    assert_eq!(
+        validate_import(ImportFormat::Csv, &records)
+            .unwrap()
+            .entries,
+        2
+    );
+
+    let prescriptions = csv::prescriptions(&export);
+    assert!(
+        prescriptions.contains(",glasses,active,2024-03-01T09:30:00Z,2025-03-01T09:30:00Z,-1.50,")
+    );
+    assert_eq!(
+        validate_import(ImportFormat::Csv, &prescriptions)
+            .unwrap()
+            .entries,
+        1
+    );
+}
+
+#[test]
+fn test_formats_are_not_interchangeable() {
+    let env = Env::default();
+    let export = fetch_export(&env);
+
+    assert!(validate_import(ImportFormat::Ccda, &fhir::to_json(&export)).is_err());
+    assert!(validate_import(ImportFormat::FhirBundle, &csv::records(&export)).is_err());
+    assert!(validate_import(ImportFormat::Csv, &ccda::to_document(&export)).is_err());
+}